@@ -0,0 +1,1028 @@
+use clap::Parser;
+use std::collections::BTreeMap;
+use std::io::IsTerminal;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use thinkeloquent_tools_chunking_directory_mapping_core::{
+    load_importance_boosts, parse_size, parse_time_spec, ComplexityCaps, GroupBy, ImportanceBoost, IoThrottle, LanguageMap, NestedRepoMode, OutputFormat, Palette, PathMode,
+    PathStyle, RankMetric, Sampling, ScanHistory, ScanOptions, SecretsMode, SortKey,
+};
+
+#[derive(Parser)]
+#[command(name = "projscan")]
+#[command(about = "A directory scanner for project analysis with enhanced LLM RAG support")]
+#[command(override_usage = "projscan [OPTIONS] [PATH]\n       projscan [OPTIONS] <COMMAND>")]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    // `projscan PATH [OPTIONS]` is shorthand for `projscan scan PATH [OPTIONS]`
+    #[command(flatten)]
+    pub scan: ScanArgs,
+
+    #[command(flatten)]
+    pub global: GlobalArgs,
+}
+
+// Options shared by every subcommand; accepted before or after the subcommand name
+#[derive(clap::Args, Clone)]
+pub struct GlobalArgs {
+    /// Mapper profile to use
+    #[arg(long, global = true, env = "PROJSCAN_PROFILE", default_value = "generic")]
+    pub profile: String,
+
+    /// Enable enhanced content analysis
+    #[arg(long, global = true)]
+    pub enhanced: bool,
+
+    /// Output format as JSON
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Output format as YAML
+    #[arg(long, global = true)]
+    pub yaml: bool,
+
+    /// Maximum complexity score per file (default 10)
+    #[arg(long, global = true, value_name = "SCORE")]
+    pub complexity_cap: Option<f64>,
+
+    /// Maximum branching complexity contribution per file (default 8)
+    #[arg(long, global = true, value_name = "SCORE")]
+    pub branching_cap: Option<f64>,
+
+    /// Disable complexity caps so very large files keep their full score
+    #[arg(long, global = true, conflicts_with_all = ["complexity_cap", "branching_cap"])]
+    pub no_complexity_caps: bool,
+
+    /// Add a percentile rank of each file's complexity across the scanned repo
+    #[arg(long, global = true)]
+    pub normalize_complexity: bool,
+
+    /// Maximum directory depth to descend (the root is depth 0)
+    #[arg(long, global = true, value_name = "N")]
+    pub max_depth: Option<usize>,
+
+    /// Entries scanned per directory before the rest are skipped with a warning (default 100000; 0 for no cap)
+    #[arg(long, global = true, value_name = "N")]
+    pub max_entries_per_dir: Option<usize>,
+
+    /// Nesting at which directories are no longer descended into, with a warning (default 128; 0 for no guard)
+    #[arg(long, global = true, value_name = "N")]
+    pub depth_guard: Option<usize>,
+
+    /// Include hidden files and directories
+    #[arg(long, global = true)]
+    pub hidden: bool,
+
+    /// Follow symbolic links while walking
+    #[arg(long, global = true)]
+    pub follow_symlinks: bool,
+
+    /// Report symbolic links whose target does not exist instead of leaving them out
+    #[arg(long, global = true)]
+    pub broken_symlinks: bool,
+
+    /// Skip paths containing PATTERN, in addition to the defaults (repeatable, or comma-separated in the variable)
+    #[arg(long, global = true, value_name = "PATTERN", env = "PROJSCAN_IGNORE", value_delimiter = ',')]
+    pub ignore: Vec<String>,
+
+    /// Only report files matching GLOB; globs with '/' match the path relative to the root (repeatable)
+    #[arg(long, global = true, value_name = "GLOB")]
+    pub include: Vec<String>,
+
+    /// Analyze files with extension EXT as LANGUAGE, e.g. mjs=javascript (repeatable, or comma-separated)
+    #[arg(long, global = true, value_name = "EXT=LANGUAGE", value_delimiter = ',', value_parser = parse_mapping_arg)]
+    pub language_ext: Vec<(String, String)>,
+
+    /// Analyze files matching GLOB as LANGUAGE whatever their extension, e.g. BUILD=starlark; globs with '/' match the end of the path (repeatable)
+    #[arg(long, global = true, value_name = "GLOB=LANGUAGE", value_parser = parse_mapping_arg)]
+    pub language_for: Vec<(String, String)>,
+
+    /// Read importance boosts per mapper profile from a YAML or JSON FILE, replacing the built-in route, store and wiring boosts
+    #[arg(long, global = true, value_name = "FILE", value_parser = parse_boosts_arg)]
+    pub boosts: Option<BTreeMap<String, Vec<ImportanceBoost>>>,
+
+    /// Only report files of at least SIZE bytes (suffixes kb, mb, gb)
+    #[arg(long, global = true, value_name = "SIZE", value_parser = parse_size_arg)]
+    pub min_size: Option<u64>,
+
+    /// Only report files of at most SIZE bytes, e.g. 10mb to skip giant data files
+    #[arg(long, global = true, value_name = "SIZE", value_parser = parse_size_arg)]
+    pub max_size: Option<u64>,
+
+    /// Only report files modified after WHEN: an RFC 3339 time, a date (2024-05-01) or an age (30d, 12h, 2w)
+    #[arg(long, global = true, value_name = "WHEN", value_parser = parse_time_arg)]
+    pub modified_after: Option<SystemTime>,
+
+    /// Only report files modified before WHEN, in the same forms as --modified-after
+    #[arg(long, global = true, value_name = "WHEN", value_parser = parse_time_arg)]
+    pub modified_before: Option<SystemTime>,
+
+    /// Do not read .projscanignore files found during the walk
+    #[arg(long, global = true)]
+    pub no_ignore_files: bool,
+
+    /// Record per-file metadata (depth, extension, modification time)
+    #[arg(long, global = true)]
+    pub metadata: bool,
+
+    /// Keep entries in the order the filesystem returns them instead of sorting by path
+    #[arg(long, global = true)]
+    pub walk_order: bool,
+
+    /// Record a SHA-256 of each file's content in the result
+    #[arg(long, global = true)]
+    pub hash: bool,
+
+    /// What to do with the files of git submodules and nested repositories; their roots are tagged either way
+    #[arg(long, global = true, value_enum, value_name = "MODE", default_value_t = NestedReposArg::Scan)]
+    pub nested_repos: NestedReposArg,
+
+    /// Look for secrets in file content; found ones are reported and never printed, and strict also drops their chunks
+    #[arg(long, global = true, value_enum, value_name = "MODE", default_value_t = SecretsArg::Off)]
+    pub secrets: SecretsArg,
+
+    /// Do not read file headers to detect MIME types, image sizes and media durations
+    #[arg(long, global = true)]
+    pub no_media: bool,
+
+    /// Read one file at a time with short pauses, so background scans leave the disk to other work
+    #[arg(long, global = true)]
+    pub nice: bool,
+
+    /// Threads analyzing file content in enhanced scans (default one per CPU, or 1 with --nice)
+    #[arg(long, short = 'j', global = true, value_name = "N")]
+    pub jobs: Option<usize>,
+
+    /// Analyze only a sample of files once a scan passes N files, with totals extrapolated in the stats (implies --enhanced)
+    #[arg(long, global = true, value_name = "N")]
+    pub sample_above: Option<usize>,
+
+    /// Share of each directory and extension's files analyzed past its first few, from 0 to 1 (default 0.1)
+    #[arg(long, global = true, value_name = "RATE", requires = "sample_above", value_parser = parse_rate_arg)]
+    pub sample_rate: Option<f64>,
+
+    /// Seed picking the sampled files; the same seed samples the same files
+    #[arg(long, global = true, value_name = "SEED", requires = "sample_above")]
+    pub sample_seed: Option<u64>,
+
+    /// Report the process's peak memory, bytes read and CPU time over the scan (Linux)
+    #[arg(long, global = true)]
+    pub resource_usage: bool,
+
+    /// Write output to FILE; the format follows the extension (.json, .yaml, .csv, .ndjson, .md, .html, .sarif, .db)
+    #[arg(long, short, global = true, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite the --output file, or the chunk --manifest and --export files, if they already exist; let gen-fixture write into a non-empty directory
+    #[arg(long, global = true)]
+    pub force: bool,
+
+    /// How paths are written in results and reports
+    #[arg(long, global = true, value_enum, value_name = "MODE", default_value_t = PathModeArg::Relative)]
+    pub paths: PathModeArg,
+
+    /// Prepend PREFIX to relative paths, e.g. a repository name
+    #[arg(long, global = true, value_name = "PREFIX")]
+    pub path_prefix: Option<String>,
+
+    /// Directory for stored scan history, instead of <path>/.projscan
+    #[arg(long, global = true, value_name = "DIR", env = "PROJSCAN_CACHE_DIR")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Only print results and errors: no progress spinner or warnings
+    #[arg(long, short, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Log scan decisions to stderr (-v info, -vv debug including skipped paths, -vvv trace)
+    #[arg(long, short, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Color text output: tags, directories, and complex files in red (--color alone means auto)
+    #[arg(long, global = true, value_name = "WHEN", env = "PROJSCAN_COLOR", value_enum,
+        default_value_t = ColorChoice::Never, num_args = 0..=1, default_missing_value = "auto")]
+    pub color: ColorChoice,
+}
+
+impl GlobalArgs {
+    pub fn complexity_caps(&self) -> ComplexityCaps {
+        if self.no_complexity_caps {
+            return ComplexityCaps::uncapped();
+        }
+        let defaults = ComplexityCaps::default();
+        ComplexityCaps {
+            complexity: self.complexity_cap.or(defaults.complexity),
+            branching: self.branching_cap.or(defaults.branching),
+        }
+    }
+
+    pub fn languages(&self) -> LanguageMap {
+        let mut languages = LanguageMap::default();
+        for (extension, language) in &self.language_ext {
+            languages.map_extension(extension, language);
+        }
+        for (pattern, language) in &self.language_for {
+            languages.add_override(pattern, language);
+        }
+        languages
+    }
+
+    // `auto` colors only a terminal stdout, and never when NO_COLOR is set
+    pub fn palette(&self) -> Palette {
+        let enabled = match self.color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                self.output.is_none()
+                    && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                    && std::io::stdout().is_terminal()
+            }
+        };
+        Palette::new(enabled)
+    }
+
+    pub fn path_style(&self) -> PathStyle {
+        PathStyle { mode: self.paths.into(), prefix: self.path_prefix.clone() }
+    }
+
+    pub fn history(&self, root: &Path) -> ScanHistory {
+        match &self.cache_dir {
+            Some(dir) => ScanHistory::in_dir(dir),
+            None => ScanHistory::for_root(root),
+        }
+    }
+
+    pub fn scan_options(&self) -> ScanOptions {
+        let defaults = ScanOptions::default();
+        ScanOptions {
+            mapper_profile: self.profile.clone(),
+            enhanced_analysis: self.enhanced,
+            complexity_caps: self.complexity_caps(),
+            normalize_complexity: self.normalize_complexity,
+            max_depth: self.max_depth,
+            include_hidden: self.hidden,
+            follow_symlinks: self.follow_symlinks,
+            ignore_patterns: defaults.ignore_patterns.into_iter().chain(self.ignore.iter().cloned()).collect(),
+            include_patterns: self.include.clone(),
+            collect_metadata: self.metadata,
+            use_ignore_files: !self.no_ignore_files,
+            sort_by_path: !self.walk_order,
+            include_broken_symlinks: self.broken_symlinks,
+            sniff_media: !self.no_media,
+            io_throttle: self.nice.then(IoThrottle::default),
+            min_size: self.min_size,
+            max_size: self.max_size,
+            modified_after: self.modified_after,
+            modified_before: self.modified_before,
+            hash_contents: self.hash,
+            nested_repos: self.nested_repos.into(),
+            secrets: self.secrets.into(),
+            max_entries_per_dir: self.max_entries_per_dir.map_or(defaults.max_entries_per_dir, |cap| (cap > 0).then_some(cap)),
+            depth_guard: self.depth_guard.map_or(defaults.depth_guard, |guard| (guard > 0).then_some(guard)),
+            analysis_threads: self.jobs.unwrap_or(if self.nice { 1 } else { 0 }),
+            sampling: self.sample_above.map(|above_files| {
+                let defaults = Sampling::default();
+                Sampling {
+                    above_files,
+                    rate: self.sample_rate.unwrap_or(defaults.rate),
+                    seed: self.sample_seed.unwrap_or(defaults.seed),
+                    ..defaults
+                }
+            }),
+            collect_resource_usage: self.resource_usage,
+            languages: self.languages(),
+            importance_boosts: self.boosts.clone().unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(clap::Subcommand)]
+pub enum Command {
+    /// Scan a directory or archive and report its structure (the default command)
+    Scan(ScanArgs),
+
+    /// Run enhanced analysis and branching details on a single file or stdin
+    Analyze(AnalyzeArgs),
+
+    /// Split the scanned files into token-bounded chunks for RAG indexing
+    Chunk(ChunkArgs),
+
+    /// Compare two scans, each a saved JSON result or a directory to scan now
+    Diff(DiffArgs),
+
+    /// List groups of files with identical content and the bytes they waste
+    Dedupe(DedupeArgs),
+
+    /// Print scan statistics only
+    Stats(PathArgs),
+
+    /// List the files ranking highest by size, complexity, importance, imports or churn
+    Top(TopArgs),
+
+    /// Pick the files or chunks most relevant to some keywords within a token budget
+    Select(SelectArgs),
+
+    /// Search file names, summaries, exports and comments through a saved index
+    Search(SearchArgs),
+
+    /// Score the codebase's health and optionally write a README badge
+    Health(HealthArgs),
+
+    /// List the feature flags the code checks, where, and which look stale
+    Flags(FlagsArgs),
+
+    /// Check declared dependency versions against known advisories
+    Audit(AuditArgs),
+
+    /// Compare declared dependency versions with the latest releases, scored per project
+    Outdated(OutdatedArgs),
+
+    /// Contributors, bus factor and last change per directory from git history
+    Ownership(OwnershipArgs),
+
+    /// Run a filter expression against a saved JSON scan result
+    Query(QueryArgs),
+
+    /// Rescan a directory periodically and report what changed
+    Watch(WatchArgs),
+
+    /// Write the scan result in a machine-readable format
+    Export(ExportArgs),
+
+    /// Report how the codebase changes across recorded scans
+    Trend(TrendArgs),
+
+    /// Serve scans, queries and chunks over an HTTP API
+    Serve(ServeArgs),
+
+    /// Generate a reproducible synthetic source tree for scanner tests and benchmarks
+    GenFixture(GenFixtureArgs),
+
+    /// Time the walk, analysis and formatting phases of a scan separately
+    Bench(BenchArgs),
+}
+
+#[derive(clap::Args, Clone)]
+pub struct ScanArgs {
+    /// The directories to scan; several are merged into one result with per-root stats
+    #[arg(id = "path", value_name = "PATH")]
+    pub paths: Vec<String>,
+
+    /// Output the repo-level symbol index (symbol -> file -> line) as JSON
+    #[arg(long)]
+    pub symbols: bool,
+
+    /// Output format for enhanced display
+    #[arg(long, value_enum, env = "PROJSCAN_FORMAT", default_value_t = OutputFormatArg::Basic)]
+    pub format: OutputFormatArg,
+
+    /// Append a snapshot of this scan to the project history (.projscan/history.jsonl, or --cache-dir)
+    #[arg(long)]
+    pub record: bool,
+
+    /// Only output entries matching a filter expression, e.g. "tag:source AND complexity>5 AND path:src/**"
+    #[arg(long, value_name = "EXPR")]
+    pub filter: Option<String>,
+
+    /// Analyze only the newline-separated paths in FILE (- for stdin) instead of walking; relative paths are relative to PATH
+    #[arg(long, value_name = "FILE")]
+    pub paths_from: Option<PathBuf>,
+
+    /// Analyze only files changed since a commit, tag or branch (including uncommitted and untracked files)
+    #[arg(long, value_name = "REF", conflicts_with = "paths_from")]
+    pub since: Option<String>,
+
+    /// Exit with status 3 if any file's complexity score exceeds N (implies --enhanced)
+    #[arg(long, value_name = "N")]
+    pub fail_on_complexity: Option<f64>,
+
+    /// Exit with status 3 if any file has more than N lines (implies --enhanced)
+    #[arg(long, value_name = "N")]
+    pub fail_on_file_lines: Option<usize>,
+
+    /// Exit with status 3 if the scan reported any errors
+    #[arg(long)]
+    pub fail_on_errors: bool,
+
+    /// Classify files as stable, active or new from git history (implies --enhanced)
+    #[arg(long)]
+    pub stability: bool,
+
+    /// Raise the importance of stable files and lower that of new ones (implies --stability)
+    #[arg(long)]
+    pub prefer_stable: bool,
+
+    /// Tag files fix-heavy, feature-heavy or refactor-heavy from conventional-commit prefixes of commits since SINCE (a date or age like 180d; default 365d)
+    #[arg(long, value_name = "SINCE", num_args = 0..=1, default_missing_value = "365d")]
+    pub change_kinds: Option<String>,
+
+    /// Read limits and layer rules (e.g. "api -> core", "core !-> api") from a YAML or JSON policy file; exits with status 3 on violations
+    #[arg(long, value_name = "FILE")]
+    pub policy: Option<PathBuf>,
+
+    /// Order the text output; numeric keys and mtime list the largest or newest first
+    #[arg(long, value_enum, value_name = "KEY")]
+    pub sort: Option<SortKeyArg>,
+
+    /// Split the text output into one section per tag, language or directory
+    #[arg(long, value_enum, value_name = "GROUP")]
+    pub group_by: Option<GroupByArg>,
+
+    /// Show modification times as RFC 3339 timestamps instead of "3d ago"
+    #[arg(long)]
+    pub absolute_times: bool,
+
+    /// Render the result through a Tera template instead of a built-in format
+    #[arg(long, value_name = "FILE", conflicts_with = "symbols")]
+    pub template: Option<PathBuf>,
+
+    /// Explain whether PATH would be scanned, and which pattern or rule skips it, without scanning
+    #[arg(long, value_name = "PATH", conflicts_with = "dry_run")]
+    pub explain: Option<PathBuf>,
+
+    /// List every entry the scan would visit or skip, and why, without analyzing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Write entries as they are scanned, as NDJSON (stdout or --output) or SQLite (.db --output), keeping memory flat; skips related files, percentiles and sorting
+    #[arg(long, conflicts_with_all = [
+        "symbols", "template", "sort", "group_by", "record", "paths_from", "since", "explain", "dry_run",
+        "fail_on_complexity", "fail_on_file_lines", "policy", "stability", "prefer_stable", "change_kinds",
+    ])]
+    pub stream: bool,
+}
+
+#[derive(clap::Args)]
+pub struct PathArgs {
+    /// The directory to scan
+    pub path: Option<String>,
+}
+
+#[derive(clap::Args)]
+pub struct AnalyzeArgs {
+    /// The file to analyze, or - to read from stdin
+    pub file: PathBuf,
+
+    /// Analyze as this language (name or extension, e.g. rust or rs); required for stdin
+    #[arg(long, short = 'l')]
+    pub language: Option<String>,
+}
+
+#[derive(clap::Args)]
+pub struct ChunkArgs {
+    /// The directory to scan
+    pub path: Option<String>,
+
+    /// Token budget per chunk (estimated at ~4 characters per token)
+    #[arg(long, default_value_t = 512)]
+    pub max_tokens: usize,
+
+    /// Lines repeated from the end of one chunk at the start of the next
+    #[arg(long, default_value_t = 2)]
+    pub overlap: usize,
+
+    /// Also write a JSON manifest mapping each chunk ID to its file, lines, symbol, tags and hashes
+    #[arg(long, value_name = "FILE")]
+    pub manifest: Option<PathBuf>,
+
+    /// Chunk stable files first, then active and new ones, by git history
+    #[arg(long)]
+    pub prefer_stable: bool,
+
+    /// Drop chunks that are mostly blank, only a license header, or from generated files
+    #[arg(long)]
+    pub drop_boilerplate: bool,
+
+    /// Drop chunks with fewer distinct words per token than this (0-1; 0.1 removes runs of braces)
+    #[arg(long, value_name = "RATIO")]
+    pub min_density: Option<f64>,
+
+    /// Drop chunks more similar than this (0-1) to a chunk already kept
+    #[arg(long, value_name = "SCORE")]
+    pub max_similarity: Option<f64>,
+
+    /// Write the chunks for a vector store: .jsonl as Qdrant points, .csv for pgvector COPY, or .parquet
+    #[arg(long, value_name = "FILE")]
+    pub export: Option<PathBuf>,
+
+    /// Format of the --export file, instead of inferring it from the extension
+    #[arg(long, value_enum, requires = "export")]
+    pub export_format: Option<VectorFormatArg>,
+
+    /// Embed each chunk through this endpoint and store the vectors in the --manifest and --export files
+    #[arg(long, value_name = "URL")]
+    pub embed_url: Option<String>,
+
+    /// Request format of the --embed-url endpoint
+    #[arg(long, value_enum, default_value_t = EmbedApiArg::Openai)]
+    pub embed_api: EmbedApiArg,
+
+    /// Embedding model name
+    #[arg(long, value_name = "NAME", default_value = "text-embedding-3-small")]
+    pub embed_model: String,
+
+    /// Bearer token for the embedding endpoint
+    #[arg(long, value_name = "KEY", env = "PROJSCAN_EMBED_API_KEY", hide_env_values = true)]
+    pub embed_api_key: Option<String>,
+
+    /// Chunks sent per embedding request
+    #[arg(long, value_name = "N", default_value_t = 64)]
+    pub embed_batch: usize,
+
+    /// Retries for embedding requests that time out or get a 429 or 5xx response
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    pub embed_retries: u32,
+
+    /// Maximum embedding requests per minute
+    #[arg(long, value_name = "N")]
+    pub embed_rpm: Option<u32>,
+}
+
+#[derive(clap::Args)]
+pub struct DiffArgs {
+    /// The earlier scan: a JSON result saved with `projscan --json` or a directory
+    pub old: PathBuf,
+
+    /// The later scan: a JSON result or a directory
+    pub new: PathBuf,
+}
+
+#[derive(clap::Args)]
+pub struct QueryArgs {
+    /// Scan result saved with `projscan --json`
+    pub result: PathBuf,
+
+    /// Filter expression, e.g. "lang:rust AND complexity>5"
+    pub expression: String,
+}
+
+#[derive(clap::Args)]
+pub struct WatchArgs {
+    /// The directory to watch
+    pub path: Option<String>,
+
+    /// Seconds between scans
+    #[arg(long, default_value_t = 2)]
+    pub interval: u64,
+}
+
+#[derive(clap::Args)]
+pub struct ExportArgs {
+    /// The directory to scan
+    pub path: Option<String>,
+
+    /// What to export (defaults to the --output extension, otherwise JSON)
+    #[arg(long = "as", value_enum)]
+    pub format: Option<ExportFormat>,
+}
+
+#[derive(clap::Args)]
+pub struct TrendArgs {
+    /// The project directory whose history to report
+    pub path: Option<String>,
+
+    /// History file to read (defaults to history.jsonl in --cache-dir or <path>/.projscan)
+    #[arg(long)]
+    pub history: Option<PathBuf>,
+
+    /// Scan the project and record a snapshot before reporting
+    #[arg(long)]
+    pub record: bool,
+}
+
+#[derive(clap::Args)]
+pub struct DedupeArgs {
+    /// The directory to scan
+    pub path: Option<String>,
+
+    /// Only consider files with TAG (repeatable)
+    #[arg(long, value_name = "TAG")]
+    pub tag: Vec<String>,
+
+    /// Only consider files with extension EXT, e.g. png (repeatable)
+    #[arg(long, value_name = "EXT")]
+    pub ext: Vec<String>,
+}
+
+#[derive(clap::Args)]
+pub struct TopArgs {
+    /// The directory to scan
+    pub path: Option<String>,
+
+    /// What to rank files by
+    #[arg(long, short, value_enum, default_value_t = RankMetricArg::Size)]
+    pub metric: RankMetricArg,
+
+    /// Number of files to list
+    #[arg(long, short = 'n', default_value_t = 10)]
+    pub limit: usize,
+}
+
+#[derive(clap::Args)]
+pub struct SelectArgs {
+    /// The directory to scan
+    pub path: Option<String>,
+
+    /// Keywords to rank by, e.g. "config parser"; repeat for more
+    #[arg(long, required = true)]
+    pub query: Vec<String>,
+
+    /// Most tokens the selected files or chunks may add up to
+    #[arg(long, default_value_t = 8000)]
+    pub budget: usize,
+
+    /// Rank chunks of the files instead of whole files
+    #[arg(long)]
+    pub chunks: bool,
+}
+
+#[derive(clap::Args)]
+pub struct SearchArgs {
+    /// Words to search for, e.g. "jwt validation"; also AND/OR, quoted phrases and name:auth
+    pub query: String,
+
+    /// The directory to search; its index lives in .projscan/search, or --cache-dir
+    pub path: Option<String>,
+
+    /// Number of files to list
+    #[arg(long, short = 'n', default_value_t = 10)]
+    pub limit: usize,
+
+    /// Rescan and rebuild the index even if one exists
+    #[arg(long)]
+    pub reindex: bool,
+}
+
+#[derive(clap::Args)]
+pub struct HealthArgs {
+    /// The directory to score
+    pub path: Option<String>,
+
+    /// Write a badge instead of the report, to --output or stdout
+    #[arg(long, value_enum)]
+    pub badge: Option<BadgeFormat>,
+}
+
+#[derive(clap::Args)]
+pub struct FlagsArgs {
+    /// The directory to search for flag checks
+    pub path: Option<String>,
+
+    /// List only flags that look stale
+    #[arg(long)]
+    pub stale: bool,
+}
+
+#[derive(clap::Args)]
+pub struct AuditArgs {
+    /// The directory whose manifests are checked
+    pub path: Option<String>,
+
+    /// OSV advisories to check against: a JSON file or a directory of them (repeatable)
+    #[arg(long, value_name = "PATH")]
+    pub advisories: Vec<String>,
+
+    /// Query osv.dev for advisories on each declared dependency
+    #[arg(long)]
+    pub online: bool,
+}
+
+#[derive(clap::Args)]
+pub struct OutdatedArgs {
+    /// The directory whose manifests are checked
+    pub path: Option<String>,
+
+    /// Snapshot of the latest versions as JSON: {"crates.io": {"serde": "1.0.210"}, ...}
+    #[arg(long, value_name = "PATH")]
+    pub index: Option<String>,
+
+    /// Look up the latest versions on deps.dev; --index entries take precedence
+    #[arg(long)]
+    pub online: bool,
+}
+
+#[derive(clap::Args)]
+pub struct OwnershipArgs {
+    /// The directory to report on, inside a git repository
+    pub path: Option<String>,
+
+    /// List only directories where one author made most of the commits
+    #[arg(long)]
+    pub silos: bool,
+}
+
+#[derive(clap::Args)]
+pub struct ServeArgs {
+    /// The directory clients may scan; requested paths outside it are refused
+    pub root: Option<String>,
+
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:7878")]
+    pub bind: SocketAddr,
+
+    /// Number of scan results kept for later requests
+    #[arg(long, default_value_t = 16)]
+    pub max_cached: usize,
+}
+
+#[derive(clap::Args)]
+pub struct BenchArgs {
+    /// Directory to benchmark
+    pub path: Option<String>,
+
+    /// Output format the formatting phase renders
+    #[arg(long, value_enum, default_value_t = OutputFormatArg::Detailed)]
+    pub format: OutputFormatArg,
+}
+
+#[derive(clap::Args)]
+pub struct GenFixtureArgs {
+    /// Directory to write the tree into; it must be empty or missing unless --force is given
+    pub dir: PathBuf,
+
+    /// The same seed and options always generate the same tree
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// Number of files to generate
+    #[arg(long, default_value_t = 100)]
+    pub files: usize,
+
+    /// Directory levels below DIR a file may sit at
+    #[arg(long, default_value_t = 3)]
+    pub depth: usize,
+
+    /// Subdirectories per directory
+    #[arg(long, default_value_t = 4)]
+    pub fan_out: usize,
+
+    /// Relative weight of each language, e.g. rust=3,python=1
+    #[arg(long, value_name = "MIX", value_parser = parse_language_mix)]
+    pub languages: Option<BTreeMap<String, u32>>,
+
+    /// Fewest lines per file
+    #[arg(long, default_value_t = 10)]
+    pub min_lines: usize,
+
+    /// Most lines per file
+    #[arg(long, default_value_t = 200)]
+    pub max_lines: usize,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum ExportFormat {
+    /// The full scan result as JSON
+    Json,
+    /// The full scan result as YAML
+    Yaml,
+    /// One entry per line, then the result without files, as JSON lines
+    Ndjson,
+    /// One row per entry as CSV
+    Csv,
+    /// Summary and file table as Markdown
+    Markdown,
+    /// Summary and file table as a standalone HTML page
+    Html,
+    /// Files, tags and issues as SQLite tables (requires --output)
+    Sqlite,
+    /// The symbol index (symbol -> file -> line) as JSON
+    Symbols,
+    /// Chunks with default options as JSON lines
+    Chunks,
+    /// Security findings as SARIF 2.1.0 for code-scanning tools
+    Sarif,
+    /// An llms.txt index of entry points, directories and key files
+    LlmsTxt,
+    /// A few paragraphs of prose describing the project, for prompts or a README
+    Overview,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum VectorFormatArg {
+    /// One Qdrant point (id, vector, payload) per line
+    Qdrant,
+    /// CSV for `COPY ... WITH (FORMAT csv, HEADER true)` into a pgvector table
+    Pgvector,
+    /// One row per chunk with list columns for tags and the embedding
+    Parquet,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum EmbedApiArg {
+    /// POST {model, input} and read {data: [{embedding}]}; also llama.cpp, vLLM and LM Studio
+    Openai,
+    /// Ollama's /api/embed
+    Ollama,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum NestedReposArg {
+    /// Report their files like any other
+    Scan,
+    /// Leave their files out and record counts per language for each repository
+    Summarize,
+    /// Leave their files out
+    Skip,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum SecretsArg {
+    /// Do not look for secrets
+    Off,
+    /// Replace secrets with [REDACTED] in summaries, signatures and chunks
+    Redact,
+    /// Redact secrets and leave out every chunk that contained one
+    Strict,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum ColorChoice {
+    /// Color when stdout is a terminal and NO_COLOR is not set
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum OutputFormatArg {
+    Basic,
+    Compact,
+    Detailed,
+    Hierarchical,
+    RepoMap,
+    Tree,
+    Summary,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum PathModeArg {
+    /// Relative to the scanned directory with / separators (the root is .)
+    Relative,
+    /// Absolute with / separators
+    Absolute,
+    /// The scan path joined with each entry, as walked
+    AsScanned,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum SortKeyArg {
+    Size,
+    /// Complexity score (enables enhanced analysis)
+    Complexity,
+    /// Importance score (enables enhanced analysis)
+    Importance,
+    /// Modification time
+    Mtime,
+    Path,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum GroupByArg {
+    /// A file with several tags is listed under each
+    Tag,
+    Language,
+    Directory,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum BadgeFormat {
+    /// A self-contained SVG image
+    Svg,
+    /// JSON for a shields.io endpoint badge
+    Shields,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum RankMetricArg {
+    /// File size in bytes
+    Size,
+    /// Complexity score (enables enhanced analysis)
+    Complexity,
+    /// Importance score (enables enhanced analysis)
+    Importance,
+    /// Number of other files importing the file (enables enhanced analysis)
+    Imported,
+    /// Number of commits touching the file (requires a git repository)
+    Churn,
+    /// Number of "fix:" commits touching the file, to find bugfix magnets (requires a git repository)
+    Fixes,
+}
+
+impl From<NestedReposArg> for NestedRepoMode {
+    fn from(arg: NestedReposArg) -> Self {
+        match arg {
+            NestedReposArg::Scan => NestedRepoMode::Scan,
+            NestedReposArg::Summarize => NestedRepoMode::Summarize,
+            NestedReposArg::Skip => NestedRepoMode::Skip,
+        }
+    }
+}
+
+impl From<SecretsArg> for SecretsMode {
+    fn from(arg: SecretsArg) -> Self {
+        match arg {
+            SecretsArg::Off => SecretsMode::Off,
+            SecretsArg::Redact => SecretsMode::Redact,
+            SecretsArg::Strict => SecretsMode::Strict,
+        }
+    }
+}
+
+impl From<RankMetricArg> for RankMetric {
+    fn from(arg: RankMetricArg) -> Self {
+        match arg {
+            RankMetricArg::Size => RankMetric::Size,
+            RankMetricArg::Complexity => RankMetric::Complexity,
+            RankMetricArg::Importance => RankMetric::Importance,
+            RankMetricArg::Imported => RankMetric::Imported,
+            RankMetricArg::Churn => RankMetric::Churn,
+            RankMetricArg::Fixes => RankMetric::Fixes,
+        }
+    }
+}
+
+impl From<PathModeArg> for PathMode {
+    fn from(arg: PathModeArg) -> Self {
+        match arg {
+            PathModeArg::Relative => PathMode::Relative,
+            PathModeArg::Absolute => PathMode::Absolute,
+            PathModeArg::AsScanned => PathMode::AsScanned,
+        }
+    }
+}
+
+impl From<SortKeyArg> for SortKey {
+    fn from(arg: SortKeyArg) -> Self {
+        match arg {
+            SortKeyArg::Size => SortKey::Size,
+            SortKeyArg::Complexity => SortKey::Complexity,
+            SortKeyArg::Importance => SortKey::Importance,
+            SortKeyArg::Mtime => SortKey::Mtime,
+            SortKeyArg::Path => SortKey::Path,
+        }
+    }
+}
+
+impl From<GroupByArg> for GroupBy {
+    fn from(arg: GroupByArg) -> Self {
+        match arg {
+            GroupByArg::Tag => GroupBy::Tag,
+            GroupByArg::Language => GroupBy::Language,
+            GroupByArg::Directory => GroupBy::Directory,
+        }
+    }
+}
+
+impl From<OutputFormatArg> for OutputFormat {
+    fn from(arg: OutputFormatArg) -> Self {
+        match arg {
+            OutputFormatArg::Basic => OutputFormat::Basic,
+            OutputFormatArg::Compact => OutputFormat::Compact,
+            OutputFormatArg::Detailed => OutputFormat::Detailed,
+            OutputFormatArg::Hierarchical => OutputFormat::Hierarchical,
+            OutputFormatArg::RepoMap => OutputFormat::RepoMap,
+            OutputFormatArg::Tree => OutputFormat::Tree,
+            OutputFormatArg::Summary => OutputFormat::Summary,
+        }
+    }
+}
+
+fn parse_size_arg(value: &str) -> Result<u64, String> {
+    parse_size(value).ok_or_else(|| format!("'{}' is not a size such as 4096, 512kb or 10mb", value))
+}
+
+fn parse_rate_arg(value: &str) -> Result<f64, String> {
+    value.parse::<f64>().ok()
+        .filter(|rate| (0.0..=1.0).contains(rate))
+        .ok_or_else(|| format!("'{}' is not a rate from 0 to 1 such as 0.1", value))
+}
+
+// "key=value" with both sides non-empty
+fn parse_mapping_arg(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() && !value.trim().is_empty() => Ok((key.trim().to_string(), value.trim().to_string())),
+        _ => Err(format!("'{}' is not a mapping such as mjs=javascript", value)),
+    }
+}
+
+fn parse_boosts_arg(value: &str) -> Result<BTreeMap<String, Vec<ImportanceBoost>>, String> {
+    load_importance_boosts(Path::new(value)).map_err(|e| e.to_string())
+}
+
+fn parse_language_mix(value: &str) -> Result<BTreeMap<String, u32>, String> {
+    value.split(',')
+        .map(|pair| {
+            let (language, weight) = pair.split_once('=').unwrap_or((pair, "1"));
+            let weight = weight.trim().parse::<u32>().map_err(|_| format!("'{}' is not a weight such as rust=3", pair))?;
+            Ok((language.trim().to_ascii_lowercase(), weight))
+        })
+        .collect()
+}
+
+fn parse_time_arg(value: &str) -> Result<SystemTime, String> {
+    parse_time_spec(value, SystemTime::now())
+        .ok_or_else(|| format!("'{}' is not an RFC 3339 time, a YYYY-MM-DD date or an age such as 30d", value))
+}
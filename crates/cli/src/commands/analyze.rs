@@ -0,0 +1,132 @@
+use serde::Serialize;
+use std::io::Read;
+use std::path::Path;
+use std::time::SystemTime;
+use thinkeloquent_tools_chunking_directory_mapping_core::{
+    BranchingDetails, ContentAnalyzer, EnhancedFileInfo, FileEntry,
+};
+
+use super::{emit_structured, fail, usage_error};
+use crate::cli::{AnalyzeArgs, GlobalArgs};
+
+#[derive(Serialize)]
+struct Analysis<'a> {
+    path: &'a Path,
+    #[serde(flatten)]
+    info: &'a EnhancedFileInfo,
+    branching: &'a BranchingDetails,
+}
+
+pub fn run_analyze(args: AnalyzeArgs, global: &GlobalArgs) {
+    let mut analyzer = ContentAnalyzer::with_caps(global.complexity_caps()).with_languages(global.languages());
+    if let Some(boosts) = global.boosts.as_ref().and_then(|profiles| profiles.get(&global.profile)) {
+        analyzer = analyzer.with_boosts(boosts.clone());
+    }
+    let from_stdin = args.file.as_os_str() == "-";
+    if from_stdin && args.language.is_none() {
+        usage_error("Reading from stdin needs --language to pick the analysis rules");
+    }
+
+    let (entry, content) = if from_stdin {
+        read_stdin()
+    } else {
+        read_file(&args.file)
+    };
+    let language = match &args.language {
+        Some(language) => Some(analyzer.resolve_language(language)),
+        None => analyzer.detect_language_for(&entry),
+    };
+
+    let info = analyzer.analyze_content(&entry, &content, language);
+    let branching = analyzer.analyze_branching_details(&content, &info.language);
+
+    let analysis = Analysis { path: &entry.path, info: &info, branching: &branching };
+    if !emit_structured(global, &analysis, "analysis") {
+        print_info(&entry.path, &info);
+        print_branching(&branching);
+    }
+}
+
+fn read_file(path: &Path) -> (FileEntry, String) {
+    let metadata = std::fs::metadata(path)
+        .unwrap_or_else(|e| fail(format!("Failed to read {}: {}", path.display(), e)));
+    if metadata.is_dir() {
+        fail(format!("{} is a directory; use `projscan scan` instead", path.display()));
+    }
+    let content = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| fail(format!("Failed to read {}: {}", path.display(), e)));
+
+    let entry = FileEntry {
+        name: path.file_name().map_or_else(String::new, |name| name.to_string_lossy().to_string()),
+        path: path.to_path_buf(),
+        size: metadata.len(),
+        modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+        is_dir: false,
+        tags: Vec::new(),
+        metadata: None,
+        enhanced_info: None,
+        symlink: None,
+        media: None,
+        content_hash: None,
+    };
+    (entry, content)
+}
+
+fn read_stdin() -> (FileEntry, String) {
+    let mut content = String::new();
+    std::io::stdin().read_to_string(&mut content)
+        .unwrap_or_else(|e| fail(format!("Failed to read stdin: {}", e)));
+
+    let entry = FileEntry {
+        name: "<stdin>".to_string(),
+        path: "-".into(),
+        size: content.len() as u64,
+        modified: SystemTime::now(),
+        is_dir: false,
+        tags: Vec::new(),
+        metadata: None,
+        enhanced_info: None,
+        symlink: None,
+        media: None,
+        content_hash: None,
+    };
+    (entry, content)
+}
+
+fn print_info(path: &Path, info: &EnhancedFileInfo) {
+    let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+
+    println!("File: {}", path.display());
+    println!("Language: {}", or_dash(info.language.clone()));
+    println!("Lines: {}", or_dash(info.line_count.map(|lines| lines.to_string())));
+    println!("Complexity: {}", or_dash(info.complexity_score.map(|score| format!("{:.2}", score))));
+    println!("Importance: {}", or_dash(info.importance_score.map(|score| format!("{:.2}", score))));
+    println!("Purpose: {}", or_dash(info.purpose.clone()));
+
+    for (label, items) in [("Exports", &info.exports), ("Imports", &info.imports), ("API surface", &info.api_surface)] {
+        if !items.is_empty() {
+            println!("{}: {}", label, items.join(", "));
+        }
+    }
+}
+
+fn print_branching(details: &BranchingDetails) {
+    println!("\nBranching:");
+    println!("  Conditionals: {}", details.conditional_count);
+    println!("  Loops: {}", details.loop_count);
+    println!("  Switches: {}", details.switch_count);
+    println!("  Logical operators: {}", details.logical_operators);
+    println!("  Max nesting: {}", details.max_nesting);
+    println!("  Cyclomatic complexity: {:.2}", details.cyclomatic_complexity);
+    println!("  Cognitive complexity: {:.2}", details.cognitive_complexity);
+    println!("  Pure / non-pure branches: {} / {}", details.pure_branches, details.non_pure_branches);
+    println!("  Hardcoded dates / values: {} / {}", details.hardcoded_dates_count, details.hardcoded_values_count);
+    println!("  Future / past logic: {} / {}", details.future_logic_count, details.past_logic_count);
+
+    if !details.nesting_distribution.is_empty() {
+        let mut levels: Vec<_> = details.nesting_distribution.iter().collect();
+        levels.sort();
+        let levels: Vec<String> = levels.iter().map(|(depth, count)| format!("{}: {}", depth, count)).collect();
+        println!("  Nesting distribution (depth: branches): {}", levels.join(", "));
+    }
+}
@@ -0,0 +1,59 @@
+use std::path::Path;
+use thinkeloquent_tools_chunking_directory_mapping_core::{
+    declared_dependencies, to_sarif, AdvisoryDatabase, DeclaredDependency, FindingKind,
+};
+
+use super::chunk::with_content_source;
+use super::{emit_structured, fail, scan_or_exit, usage_error, write_output};
+use crate::cli::{AuditArgs, ExportFormat, GlobalArgs};
+use crate::output::infer_format;
+
+pub fn run_audit(args: AuditArgs, global: &GlobalArgs) {
+    if args.advisories.is_empty() && !args.online {
+        usage_error("audit needs advisory data: pass --advisories PATH or --online");
+    }
+    let scan_path = args.path.unwrap_or_else(|| ".".to_string());
+    let mut result = scan_or_exit(global, global.scan_options(), &scan_path);
+
+    let mut database = AdvisoryDatabase::new();
+    for path in &args.advisories {
+        let loaded = AdvisoryDatabase::load(Path::new(path))
+            .unwrap_or_else(|e| fail(format!("Failed to read advisories from {}: {}", path, e)));
+        database.extend(loaded);
+    }
+    let dependencies = with_content_source(&scan_path, |fs| declared_dependencies(&result, fs));
+    if args.online {
+        database.extend(fetch_online(&dependencies));
+    }
+    with_content_source(&scan_path, |fs| result.flag_vulnerable_dependencies(&database, fs));
+    // Only dependency findings; permission findings come with `projscan scan`
+    result.findings.retain(|finding| finding.kind == FindingKind::VulnerableDependency);
+
+    if let Some(path) = global.output.as_ref().filter(|path| matches!(infer_format(path), Some(ExportFormat::Sarif))) {
+        write_output(path, global.force, &to_sarif(&result));
+        return;
+    }
+    if emit_structured(global, &result.findings, "dependency audit") {
+        return;
+    }
+
+    println!("{} dependencies checked against {} advisories, {} vulnerable", dependencies.len(), database.len(), result.findings.len());
+    for finding in &result.findings {
+        println!("  {:<7} {}: {}", format!("{:?}", finding.severity), finding.path.display(), finding.message);
+    }
+}
+
+#[cfg(feature = "osv")]
+fn fetch_online(dependencies: &[DeclaredDependency]) -> AdvisoryDatabase {
+    use thinkeloquent_tools_chunking_directory_mapping_core::{fetch_advisories, OSV_QUERY_ENDPOINT};
+
+    let fetched = tokio::runtime::Runtime::new()
+        .map_err(|e| e.to_string())
+        .and_then(|runtime| runtime.block_on(fetch_advisories(OSV_QUERY_ENDPOINT, dependencies)).map_err(|e| e.to_string()));
+    fetched.unwrap_or_else(|e| fail(format!("Failed to query {}: {}", OSV_QUERY_ENDPOINT, e)))
+}
+
+#[cfg(not(feature = "osv"))]
+fn fetch_online(_dependencies: &[DeclaredDependency]) -> AdvisoryDatabase {
+    usage_error("This build of projscan cannot query osv.dev; rebuild with the `osv` feature or pass --advisories");
+}
@@ -0,0 +1,40 @@
+use std::path::Path;
+use thinkeloquent_tools_chunking_directory_mapping_core::{
+    bench_scan, is_archive, load_archive, peak_rss_bytes, OsFileSystem, OutputFormat, OutputFormatter,
+};
+
+use super::{emit_structured, fail};
+use crate::cli::{BenchArgs, GlobalArgs};
+
+pub fn run_bench(args: BenchArgs, global: &GlobalArgs) {
+    let root = args.path.unwrap_or_else(|| ".".to_string());
+    let path = Path::new(&root);
+    let options = global.scan_options();
+    let format: OutputFormat = args.format.into();
+
+    let report = if is_archive(path) && path.is_file() {
+        let fs = load_archive(path).unwrap_or_else(|e| fail(format!("Failed to read archive {}: {}", path.display(), e)));
+        bench_scan(&fs, path, &options, &format)
+    } else {
+        bench_scan(&OsFileSystem, path, &options, &format)
+    }
+    .unwrap_or_else(|e| fail(format!("Benchmark failed: {}", e)));
+
+    if emit_structured(global, &report, "benchmark report") {
+        return;
+    }
+
+    println!("Benchmark for: {} ({} files, {} directories)", root, report.files, report.dirs);
+    println!("{:<10} {:>10} {:>8} {:>12} {:>10} {:>10}", "Phase", "Time", "Items", "Items/s", "Bytes", "Memory");
+    for phase in &report.phases {
+        println!("{:<10} {:>8.1}ms {:>8} {:>12.0} {:>10} {:>10}",
+            phase.name, phase.duration_ms, phase.items, phase.items_per_second(),
+            OutputFormatter::format_size(phase.bytes), OutputFormatter::format_size(phase.retained_bytes));
+    }
+    println!("{:<10} {:>8.1}ms", "Total", report.total_ms);
+    print!("Estimated peak memory: {}", OutputFormatter::format_size(report.peak_memory_bytes));
+    match peak_rss_bytes() {
+        Some(rss) => println!(" (peak RSS {})", OutputFormatter::format_size(rss)),
+        None => println!(),
+    }
+}
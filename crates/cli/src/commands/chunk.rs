@@ -0,0 +1,162 @@
+use std::path::Path;
+use thinkeloquent_tools_chunking_directory_mapping_core::{
+    is_archive, load_archive, to_pgvector_csv, to_qdrant_points, Chunk, ChunkFilter, ChunkManifestEntry, ChunkOptions,
+    Chunker, FileSystem, OsFileSystem, PathStyle, ScanOptions, ScanResult,
+};
+
+use super::scan::assign_stability;
+use super::{emit_structured, fail, scan_or_exit, to_json, usage_error, write_output};
+use crate::cli::{ChunkArgs, GlobalArgs, VectorFormatArg};
+use crate::output::{write_atomic, write_text};
+
+pub fn run_chunk(args: ChunkArgs, global: &GlobalArgs) {
+    let scan_path = args.path.clone().unwrap_or_else(|| ".".to_string());
+    let options = ChunkOptions {
+        max_tokens: args.max_tokens,
+        overlap_lines: args.overlap,
+        filter: ChunkFilter {
+            drop_boilerplate: args.drop_boilerplate,
+            min_density: args.min_density,
+            max_similarity: args.max_similarity,
+        },
+        prefer_stable: args.prefer_stable,
+        secrets: global.secrets.into(),
+        ..Default::default()
+    };
+
+    // Stability is recorded on enhanced analysis
+    let scan_options = ScanOptions { enhanced_analysis: global.enhanced || args.prefer_stable, ..global.scan_options() };
+    let mut result = scan_or_exit(global, scan_options, &scan_path);
+    if args.prefer_stable {
+        assign_stability(&mut result, &scan_path);
+    }
+    let export_format = args.export.as_deref().map(|path| {
+        args.export_format.or_else(|| infer_vector_format(path)).unwrap_or_else(|| {
+            usage_error(format!("Cannot tell the vector store format of {}; pass --export-format", path.display()))
+        })
+    });
+    if args.embed_url.is_some() && args.manifest.is_none() && args.export.is_none() {
+        usage_error("--embed-url needs --manifest or --export to store the vectors in");
+    }
+
+    let style = global.path_style();
+    let chunks = if args.manifest.is_some() || args.export.is_some() {
+        let chunker = Chunker::new(options);
+        let (chunks, manifest) = with_content_source(&scan_path, |fs| chunker.chunk_result_with_manifest(&result, fs));
+        let chunks: Vec<Chunk> = chunks.iter().map(|chunk| chunk.with_path_style(&style, &result.root_path)).collect();
+        let mut manifest: Vec<ChunkManifestEntry> = manifest.iter()
+            .map(|entry| entry.with_path_style(&style, &result.root_path))
+            .collect();
+        if let Some(endpoint) = &args.embed_url {
+            embed_manifest(&args, endpoint, &chunks, &mut manifest);
+        }
+        if let Some(path) = &args.manifest {
+            write_output(path, global.force, &(to_json(&manifest, "chunk manifest") + "\n"));
+        }
+        if let (Some(path), Some(format)) = (&args.export, export_format) {
+            export_chunks(path, global.force, format, &chunks, &manifest);
+        }
+        chunks
+    } else {
+        chunk_result(&result, &scan_path, options, &style)
+    };
+
+    if emit_structured(global, &chunks, "chunks") {
+        return;
+    }
+
+    for chunk in &chunks {
+        println!("{:<60} {:>6} tokens", chunk.id, chunk.token_count);
+    }
+    let total_tokens: usize = chunks.iter().map(|chunk| chunk.token_count).sum();
+    eprintln!("{} chunks, ~{} tokens", chunks.len(), total_tokens);
+}
+
+// Reads file content from the same source the scan used; chunk paths and ids
+// are then written in `style`, like the paths of scan output
+pub(crate) fn chunk_result(result: &ScanResult, scan_path: &str, options: ChunkOptions, style: &PathStyle) -> Vec<Chunk> {
+    let chunker = Chunker::new(options);
+    with_content_source(scan_path, |fs| chunker.chunk_result(result, fs))
+        .iter()
+        .map(|chunk| chunk.with_path_style(style, &result.root_path))
+        .collect()
+}
+
+fn infer_vector_format(path: &Path) -> Option<VectorFormatArg> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "jsonl" | "ndjson" => Some(VectorFormatArg::Qdrant),
+        "csv" => Some(VectorFormatArg::Pgvector),
+        "parquet" => Some(VectorFormatArg::Parquet),
+        _ => None,
+    }
+}
+
+fn export_chunks(path: &Path, force: bool, format: VectorFormatArg, chunks: &[Chunk], manifest: &[ChunkManifestEntry]) {
+    let written = match format {
+        VectorFormatArg::Qdrant => write_text(path, force, &to_qdrant_points(chunks, manifest)),
+        VectorFormatArg::Pgvector => write_text(path, force, &to_pgvector_csv(chunks, manifest)),
+        VectorFormatArg::Parquet => write_atomic(path, force, |temp| write_parquet(temp, chunks, manifest)),
+    };
+    if let Err(e) = written {
+        fail(format!("Failed to write {}: {}", path.display(), e));
+    }
+    eprintln!("Wrote {}", path.display());
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(path: &Path, chunks: &[Chunk], manifest: &[ChunkManifestEntry]) -> std::io::Result<()> {
+    let file = std::fs::File::create(path)?;
+    thinkeloquent_tools_chunking_directory_mapping_core::write_chunks_parquet(chunks, manifest, &file)?;
+    file.sync_all()
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(_path: &Path, _chunks: &[Chunk], _manifest: &[ChunkManifestEntry]) -> std::io::Result<()> {
+    usage_error("This build of projscan has no Parquet support; rebuild with the `parquet` feature");
+}
+
+#[cfg(feature = "embeddings")]
+fn embed_manifest(args: &ChunkArgs, endpoint: &str, chunks: &[Chunk], manifest: &mut [ChunkManifestEntry]) {
+    use crate::cli::EmbedApiArg;
+    use thinkeloquent_tools_chunking_directory_mapping_core::{EmbeddingApi, EmbeddingClient, EmbeddingOptions};
+
+    let options = EmbeddingOptions {
+        endpoint: endpoint.to_string(),
+        api: match args.embed_api {
+            EmbedApiArg::Openai => EmbeddingApi::OpenAi,
+            EmbedApiArg::Ollama => EmbeddingApi::Ollama,
+        },
+        model: args.embed_model.clone(),
+        api_key: args.embed_api_key.clone(),
+        batch_size: args.embed_batch,
+        max_retries: args.embed_retries,
+        requests_per_minute: args.embed_rpm,
+        ..Default::default()
+    };
+    let embedded = tokio::runtime::Runtime::new()
+        .map_err(|e| e.to_string())
+        .and_then(|runtime| {
+            let client = EmbeddingClient::new(options).map_err(|e| e.to_string())?;
+            runtime.block_on(client.embed_manifest(chunks, manifest)).map_err(|e| e.to_string())
+        });
+    match embedded {
+        Ok(()) => eprintln!("Embedded {} chunks through {}", chunks.len(), endpoint),
+        Err(e) => fail(format!("Failed to embed chunks: {}", e)),
+    }
+}
+
+#[cfg(not(feature = "embeddings"))]
+fn embed_manifest(_args: &ChunkArgs, _endpoint: &str, _chunks: &[Chunk], _manifest: &mut [ChunkManifestEntry]) {
+    usage_error("This build of projscan cannot embed chunks; rebuild with the `embeddings` feature");
+}
+
+pub(crate) fn with_content_source<T>(scan_path: &str, read: impl FnOnce(&dyn FileSystem) -> T) -> T {
+    let path = Path::new(scan_path);
+    if is_archive(path) && path.is_file() {
+        let fs = load_archive(path).unwrap_or_else(|e| fail(format!("Failed to read archive {}: {}", path.display(), e)));
+        read(&fs)
+    } else {
+        read(&OsFileSystem)
+    }
+}
@@ -0,0 +1,36 @@
+use thinkeloquent_tools_chunking_directory_mapping_core::{DuplicateReport, FileEntry, OutputFormatter, ScanOptions};
+
+use super::{emit_structured, scan_or_exit};
+use crate::cli::{DedupeArgs, GlobalArgs};
+
+pub fn run_dedupe(args: DedupeArgs, global: &GlobalArgs) {
+    let scan_path = args.path.unwrap_or_else(|| ".".to_string());
+    let options = ScanOptions { hash_contents: true, ..global.scan_options() };
+    let result = scan_or_exit(global, options, &scan_path).with_path_style(&global.path_style());
+
+    let extensions: Vec<String> = args.ext.iter().map(|ext| ext.trim_start_matches('.').to_lowercase()).collect();
+    let wanted = |file: &&FileEntry| {
+        (args.tag.is_empty() || file.tags.iter().any(|tag| args.tag.contains(tag)))
+            && (extensions.is_empty()
+                || file.path.extension().is_some_and(|ext| extensions.contains(&ext.to_string_lossy().to_lowercase())))
+    };
+    let report = DuplicateReport::from_files(result.files.iter().filter(wanted));
+
+    if emit_structured(global, &report, "duplicate report") {
+        return;
+    }
+    if report.groups.is_empty() {
+        println!("No duplicate files in {}", scan_path);
+        return;
+    }
+    let groups = if report.groups.len() == 1 { "group" } else { "groups" };
+    println!("{} duplicate files in {} {}, {} wasted",
+        report.duplicate_files, report.groups.len(), groups, OutputFormatter::format_size(report.wasted_bytes));
+    for group in &report.groups {
+        println!("\n{} wasted: {} copies of {} ({})", OutputFormatter::format_size(group.wasted_bytes),
+            group.paths.len(), OutputFormatter::format_size(group.size), &group.hash[..12]);
+        for path in &group.paths {
+            println!("  {}", path.display());
+        }
+    }
+}
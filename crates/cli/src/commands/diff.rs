@@ -0,0 +1,64 @@
+use std::path::Path;
+use thinkeloquent_tools_chunking_directory_mapping_core::{ScanDiff, ScanOptions, ScanResult};
+
+use super::{emit_structured, load_result, scan_or_exit};
+use crate::cli::{DiffArgs, GlobalArgs};
+
+pub fn run_diff(args: DiffArgs, global: &GlobalArgs) {
+    let old = load_or_scan(&args.old, global);
+    let new = load_or_scan(&args.new, global);
+    let diff = ScanDiff::between(&old, &new);
+
+    if !emit_structured(global, &diff, "diff") {
+        print_diff(&diff);
+    }
+}
+
+// Pass --enhanced when comparing against results saved with it, otherwise every
+// analyzed file reports as modified. Directories are hashed so moves are found;
+// saved results need to have been scanned with --hash for that.
+fn load_or_scan(path: &Path, global: &GlobalArgs) -> ScanResult {
+    if path.is_dir() {
+        let options = ScanOptions { hash_contents: true, ..global.scan_options() };
+        scan_or_exit(global, options, &path.to_string_lossy())
+    } else {
+        load_result(path)
+    }
+}
+
+pub(crate) fn print_diff(diff: &ScanDiff) {
+    for path in &diff.added {
+        println!("+ {}", path.display());
+    }
+    for path in &diff.removed {
+        println!("- {}", path.display());
+    }
+    for file_move in &diff.moved {
+        println!("> moved: {} -> {}", file_move.from.display(), file_move.to.display());
+    }
+    for change in &diff.modified {
+        let complexity = change.complexity_delta()
+            .map_or(String::new(), |delta| format!(", complexity {:+.2}", delta));
+        println!("~ {} ({:+} bytes{})", change.path.display(), change.size_delta(), complexity);
+    }
+    println!("{} added, {} removed, {} moved, {} modified, {} unchanged",
+        diff.added.len(), diff.removed.len(), diff.moved.len(), diff.modified.len(), diff.unchanged);
+
+    if diff.api_changes.is_empty() {
+        return;
+    }
+    println!("\nPublic API changes:");
+    for change in &diff.api_changes {
+        println!("  {}", change.path.display());
+        for name in &change.added {
+            println!("    + {}", name);
+        }
+        for name in &change.removed {
+            println!("    - {}", name);
+        }
+        for item in &change.changed {
+            println!("    ~ {}: {} -> {}", item.name, item.before, item.after);
+        }
+    }
+    println!("Suggested version bump: {}", diff.semver_bump());
+}
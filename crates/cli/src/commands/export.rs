@@ -0,0 +1,115 @@
+use std::io::{self, BufWriter, Write};
+use thinkeloquent_tools_chunking_directory_mapping_core::{
+    to_html, to_llms_txt, to_markdown, to_sarif, write_csv, write_json, write_ndjson, write_yaml, ChunkOptions,
+    ProjectOverview, ScanOptions, ScanResult, SymbolIndex,
+};
+
+use super::chunk::{chunk_result, with_content_source};
+use super::{fail, scan_or_exit, usage_error, write_output};
+use crate::cli::{ExportArgs, ExportFormat, GlobalArgs};
+use crate::output::infer_format;
+
+pub fn run_export(args: ExportArgs, global: &GlobalArgs) {
+    let format = args.format
+        .or_else(|| global.output.as_deref().and_then(infer_format))
+        .unwrap_or(ExportFormat::Json);
+    let scan_path = args.path.unwrap_or_else(|| ".".to_string());
+    let options = ScanOptions {
+        // Symbols, purposes and importance are only produced by enhanced analysis
+        enhanced_analysis: global.enhanced || matches!(format, ExportFormat::Symbols | ExportFormat::LlmsTxt | ExportFormat::Overview),
+        ..global.scan_options()
+    };
+    let result = scan_or_exit(global, options, &scan_path);
+
+    emit_result(format, &result, &scan_path, global);
+}
+
+// Writes the result to --output when given, otherwise to stdout, with paths in
+// the --paths style. Chunks read file content from the paths as scanned and
+// restyle their own paths afterwards; llms.txt and the overview are always
+// relative to the scanned root.
+pub(crate) fn emit_result(format: ExportFormat, result: &ScanResult, scan_path: &str, global: &GlobalArgs) {
+    if let ExportFormat::Chunks | ExportFormat::LlmsTxt | ExportFormat::Overview = format {
+        return write_text(&render(format, result, scan_path, global), global);
+    }
+    let displayed = result.with_path_style(&global.path_style());
+    match format {
+        ExportFormat::Sqlite => {
+            let Some(path) = &global.output else {
+                usage_error("SQLite output needs a file; pass --output results.db");
+            };
+            write_sqlite(&displayed, path, global.force);
+        }
+        ExportFormat::Json => write_streamed(global, |out| write_json(&displayed, out)),
+        ExportFormat::Yaml => write_streamed(global, |out| write_yaml(&displayed, out)),
+        ExportFormat::Csv => write_streamed(global, |out| write_csv(&displayed, out)),
+        ExportFormat::Ndjson => write_streamed(global, |out| write_ndjson(&displayed, out)),
+        _ => write_text(&render(format, &displayed, scan_path, global), global),
+    }
+}
+
+// The full result is serialized to the output as it is produced rather than
+// into a string first, which would hold a huge result twice
+fn write_streamed(global: &GlobalArgs, write: impl FnOnce(&mut dyn Write) -> io::Result<()>) {
+    match &global.output {
+        Some(path) => {
+            if let Err(e) = crate::output::write_streamed(path, global.force, write) {
+                fail(format!("Failed to write {}: {}", path.display(), e));
+            }
+            eprintln!("Wrote {}", path.display());
+        }
+        None => {
+            let mut out = BufWriter::new(io::stdout().lock());
+            if let Err(e) = write(&mut out).and_then(|_| out.flush()) {
+                fail(format!("Failed to write output: {}", e));
+            }
+        }
+    }
+}
+
+fn write_text(text: &str, global: &GlobalArgs) {
+    match &global.output {
+        Some(path) => write_output(path, global.force, text),
+        None => print!("{}", text),
+    }
+}
+
+fn render(format: ExportFormat, result: &ScanResult, scan_path: &str, global: &GlobalArgs) -> String {
+    match format {
+        ExportFormat::Markdown => to_markdown(result),
+        ExportFormat::Html => to_html(result),
+        ExportFormat::Sarif => to_sarif(result),
+        ExportFormat::LlmsTxt => to_llms_txt(result),
+        // Manifests are read for the project type and dependencies
+        ExportFormat::Overview => with_content_source(scan_path, |fs| ProjectOverview::from_result(result, fs)).to_prose(),
+        ExportFormat::Symbols => match SymbolIndex::build(&result.files).to_json() {
+            Ok(json) => json + "\n",
+            Err(e) => fail(format!("Failed to serialize symbol index: {}", e)),
+        },
+        ExportFormat::Chunks => chunk_result(result, scan_path, ChunkOptions { secrets: global.secrets.into(), ..Default::default() }, &global.path_style()).iter()
+            .map(|chunk| match serde_json::to_string(chunk) {
+                Ok(line) => line + "\n",
+                Err(e) => fail(format!("Failed to serialize chunk: {}", e)),
+            })
+            .collect(),
+        ExportFormat::Json | ExportFormat::Yaml | ExportFormat::Csv | ExportFormat::Ndjson | ExportFormat::Sqlite => {
+            unreachable!("{:?} output is written straight to the output", format)
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn write_sqlite(result: &ScanResult, path: &std::path::Path, force: bool) {
+    let written = crate::output::write_atomic(path, force, |temp| {
+        crate::sqlite::write_sqlite(result, temp).map_err(std::io::Error::other)
+    });
+    if let Err(e) = written {
+        fail(format!("Failed to write {}: {}", path.display(), e));
+    }
+    eprintln!("Wrote {}", path.display());
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn write_sqlite(_result: &ScanResult, _path: &std::path::Path, _force: bool) {
+    usage_error("This build of projscan has no SQLite support; rebuild with the `sqlite` feature");
+}
@@ -0,0 +1,35 @@
+use std::path::Path;
+use std::time::SystemTime;
+use thinkeloquent_tools_chunking_directory_mapping_core::{is_archive, load_archive, FeatureFlagReport, OsFileSystem};
+
+use super::{emit_structured, fail, scan_or_exit};
+use crate::cli::{FlagsArgs, GlobalArgs};
+
+pub fn run_flags(args: FlagsArgs, global: &GlobalArgs) {
+    let scan_path = args.path.unwrap_or_else(|| ".".to_string());
+    let path = Path::new(&scan_path);
+    let result = scan_or_exit(global, global.scan_options(), &scan_path);
+
+    let mut report = if is_archive(path) && path.is_file() {
+        let fs = load_archive(path).unwrap_or_else(|e| fail(format!("Failed to read archive {}: {}", path.display(), e)));
+        FeatureFlagReport::compute(&result, &fs, SystemTime::now())
+    } else {
+        FeatureFlagReport::compute(&result, &OsFileSystem, SystemTime::now())
+    };
+    if args.stale {
+        report.flags.retain(|flag| flag.stale.is_some());
+    }
+
+    if emit_structured(global, &report, "feature flag report") {
+        return;
+    }
+
+    println!("{} feature flags in {}, {} looking stale", report.flags.len(), scan_path, report.stale_count);
+    for flag in &report.flags {
+        let stale = flag.stale.map(|reason| format!("  stale: {}", reason)).unwrap_or_default();
+        println!("  {:<32} {:<12} {:>3} uses{}", flag.name, flag.provider.to_string(), flag.locations.len(), stale);
+        for location in &flag.locations {
+            println!("      {}:{}", location.path.display(), location.line);
+        }
+    }
+}
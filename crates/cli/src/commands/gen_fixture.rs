@@ -0,0 +1,31 @@
+use thinkeloquent_tools_chunking_directory_mapping_core::{Fixture, FixtureSpec, OutputFormatter};
+
+use super::{emit_structured, fail, usage_error};
+use crate::cli::{GenFixtureArgs, GlobalArgs};
+
+pub fn run_gen_fixture(args: GenFixtureArgs, global: &GlobalArgs) {
+    let spec = FixtureSpec {
+        seed: args.seed,
+        files: args.files,
+        max_depth: args.depth,
+        fan_out: args.fan_out,
+        languages: args.languages.unwrap_or_else(|| FixtureSpec::default().languages),
+        min_lines: args.min_lines,
+        max_lines: args.max_lines,
+    };
+    let fixture = Fixture::generate(&spec).unwrap_or_else(|e| usage_error(e));
+
+    let occupied = std::fs::read_dir(&args.dir).is_ok_and(|mut entries| entries.next().is_some());
+    if occupied && !global.force {
+        fail(format!("{} is not empty; use --force to write into it anyway", args.dir.display()));
+    }
+    fixture.write_to(&args.dir)
+        .unwrap_or_else(|e| fail(format!("Failed to write fixture to {}: {}", args.dir.display(), e)));
+
+    if emit_structured(global, &spec, "fixture spec") {
+        return;
+    }
+    println!("Generated {} files in {} directories ({}) under {} from seed {}",
+        fixture.files.len(), fixture.directories().len(),
+        OutputFormatter::format_size(fixture.total_size()), args.dir.display(), spec.seed);
+}
@@ -0,0 +1,46 @@
+use std::path::Path;
+use thinkeloquent_tools_chunking_directory_mapping_core::{
+    git_commit_counts, is_archive, load_archive, HealthReport, OsFileSystem, ScanOptions,
+};
+
+use super::{emit_structured, fail, scan_or_exit, to_json, write_output};
+use crate::cli::{BadgeFormat, GlobalArgs, HealthArgs};
+
+pub fn run_health(args: HealthArgs, global: &GlobalArgs) {
+    let scan_path = args.path.unwrap_or_else(|| ".".to_string());
+    let path = Path::new(&scan_path);
+
+    // Complexity scores come from enhanced analysis
+    let options = ScanOptions { enhanced_analysis: true, ..global.scan_options() };
+    let result = scan_or_exit(global, options, &scan_path);
+
+    let report = if is_archive(path) && path.is_file() {
+        let fs = load_archive(path).unwrap_or_else(|e| fail(format!("Failed to read archive {}: {}", path.display(), e)));
+        HealthReport::compute(&result, &fs, None)
+    } else {
+        // Without history the churn category is left out of the score
+        let churn = git_commit_counts(path).ok();
+        HealthReport::compute(&result, &OsFileSystem, churn.as_ref())
+    };
+
+    if let Some(badge) = args.badge {
+        let text = match badge {
+            BadgeFormat::Svg => report.badge_svg(),
+            BadgeFormat::Shields => to_json(&report.shields_endpoint(), "badge") + "\n",
+        };
+        match &global.output {
+            Some(output) => write_output(output, global.force, &text),
+            None => print!("{}", text),
+        }
+        return;
+    }
+
+    if emit_structured(global, &report, "health report") {
+        return;
+    }
+
+    println!("Health of {}: {} ({:.1})", scan_path, report.grade, report.score);
+    for category in &report.categories {
+        println!("  {:<18} {:>6.1}  {}", category.category.to_string(), category.score, category.detail);
+    }
+}
@@ -0,0 +1,204 @@
+use serde::Serialize;
+use std::path::Path;
+use thinkeloquent_tools_chunking_directory_mapping_core::{
+    DirectoryScanner, FileEntry, FileSystem, OutputFormatter, ScanError, ScanOptions, ScanResult, ScanSession, ScanStats,
+};
+
+use crate::cli::{ExportFormat, GlobalArgs};
+use crate::output::{infer_format, write_text};
+use crate::progress;
+
+mod analyze;
+mod audit;
+mod bench;
+mod chunk;
+mod dedupe;
+mod diff;
+mod export;
+mod flags;
+mod gen_fixture;
+mod health;
+mod outdated;
+mod ownership;
+mod query;
+mod scan;
+mod search;
+mod select;
+mod serve;
+mod stats;
+mod stream;
+mod top;
+mod trend;
+mod watch;
+
+pub use analyze::run_analyze;
+pub use audit::run_audit;
+pub use bench::run_bench;
+pub use chunk::run_chunk;
+pub use dedupe::run_dedupe;
+pub use diff::run_diff;
+pub use export::run_export;
+pub use flags::run_flags;
+pub use gen_fixture::run_gen_fixture;
+pub use health::run_health;
+pub use outdated::run_outdated;
+pub use ownership::run_ownership;
+pub use query::run_query;
+pub use scan::run_scan;
+pub use search::run_search;
+pub use select::run_select;
+pub use serve::run_serve;
+pub use stats::run_stats;
+pub use top::run_top;
+pub use trend::run_trend;
+pub use watch::run_watch;
+
+// Exit code for invalid input such as a malformed filter expression
+const USAGE_ERROR: i32 = 2;
+
+pub(crate) fn fail(message: impl std::fmt::Display) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+pub(crate) fn usage_error(message: impl std::fmt::Display) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(USAGE_ERROR);
+}
+
+pub(crate) fn to_json<T: Serialize + ?Sized>(value: &T, what: &str) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|e| fail(format!("Failed to serialize {}: {}", what, e)))
+}
+
+pub(crate) fn to_yaml<T: Serialize + ?Sized>(value: &T, what: &str) -> String {
+    serde_yaml::to_string(value).unwrap_or_else(|e| fail(format!("Failed to serialize {}: {}", what, e)))
+}
+
+pub(crate) fn write_output(path: &Path, force: bool, text: &str) {
+    if let Err(e) = write_text(path, force, text) {
+        fail(format!("Failed to write {}: {}", path.display(), e));
+    }
+    eprintln!("Wrote {}", path.display());
+}
+
+// Structured output for --output, --json and --yaml; returns false when the
+// caller should print its human-readable report instead
+pub(crate) fn emit_structured<T: Serialize + ?Sized>(global: &GlobalArgs, value: &T, what: &str) -> bool {
+    if let Some(path) = &global.output {
+        let text = match infer_format(path) {
+            Some(ExportFormat::Json) => to_json(value, what) + "\n",
+            Some(ExportFormat::Yaml) => to_yaml(value, what),
+            _ => usage_error(format!("The {} can only be written to a .json or .yaml file", what)),
+        };
+        write_output(path, global.force, &text);
+        true
+    } else if global.json {
+        println!("{}", to_json(value, what));
+        true
+    } else if global.yaml {
+        println!("{}", to_yaml(value, what));
+        true
+    } else {
+        false
+    }
+}
+
+pub(crate) fn scan_or_exit(global: &GlobalArgs, options: ScanOptions, path: &str) -> ScanResult {
+    scan_using(global, None, options, path, |scanner| scanner.scan(path))
+}
+
+// For repeated scans: files unchanged since an earlier scan in `session` are not analyzed again
+pub(crate) fn rescan_or_exit(global: &GlobalArgs, session: &ScanSession, options: ScanOptions, path: &str) -> ScanResult {
+    scan_using(global, Some(session), options, path, |scanner| scanner.scan(path))
+}
+
+// Scans several roots into one merged result
+pub(crate) fn scan_many_or_exit(global: &GlobalArgs, options: ScanOptions, paths: &[String]) -> ScanResult {
+    scan_using(global, None, options, &paths.join(", "), |scanner| scanner.scan_many(paths))
+}
+
+// Passes each entry to `sink` as it is scanned instead of collecting them; the result has no files
+pub(crate) fn stream_or_exit(
+    global: &GlobalArgs,
+    options: ScanOptions,
+    path: &str,
+    sink: impl FnMut(FileEntry) -> Result<(), ScanError>,
+) -> ScanResult {
+    scan_using(global, None, options, path, |scanner| scanner.scan_streaming(path, sink))
+}
+
+// Scans entries from `fs` instead of the directory tree, e.g. a list of paths
+pub(crate) fn scan_with_or_exit(global: &GlobalArgs, options: ScanOptions, fs: &dyn FileSystem, path: &str) -> ScanResult {
+    scan_using(global, None, options, path, |scanner| scanner.scan_with(fs, path))
+}
+
+fn scan_using(
+    global: &GlobalArgs,
+    session: Option<&ScanSession>,
+    options: ScanOptions,
+    path: &str,
+    scan: impl FnOnce(&DirectoryScanner) -> Result<ScanResult, ScanError>,
+) -> ScanResult {
+    tracing::info!(path, profile = %options.mapper_profile, enhanced = options.enhanced_analysis, "scanning");
+    let mut scanner = match session {
+        Some(session) => session.scanner(options).unwrap_or_else(|e| usage_error(e)),
+        None => DirectoryScanner::new(options),
+    };
+
+    // Debug logging would tear through the spinner, so it gives way at -vv
+    let spinner = (!global.quiet && global.verbose < 2).then(progress::scan_spinner);
+    if let Some(spinner) = spinner.clone() {
+        scanner = scanner.with_progress(move |scan_progress| progress::update(&spinner, scan_progress));
+    }
+    scanner = scanner.with_metrics(|metrics| tracing::info!(
+        files_per_second = format!("{:.1}", metrics.files_per_second),
+        bytes_per_second = format!("{:.0}", metrics.bytes_per_second),
+        cache_hit_rate = ?metrics.cache_hit_rate(),
+        "scan metrics"
+    ));
+    let scanned = scan(&scanner);
+    if let Some(spinner) = spinner {
+        spinner.finish_and_clear();
+    }
+
+    let result = scanned.unwrap_or_else(|e| fail(format!("Scan failed: {}", e)));
+    tracing::info!(
+        files = result.stats.total_files,
+        dirs = result.stats.total_dirs,
+        issues = result.errors.len(),
+        duration_ms = result.stats.scan_duration_ms,
+        cache = ?result.stats.cache,
+        sampling = ?result.stats.sampling,
+        resources = ?result.stats.resources,
+        "scan complete"
+    );
+    result
+}
+
+// e.g. "Resource usage: peak RSS 48.2MB, read 9.4MB, CPU 1210ms"; figures the
+// platform could not report are left out
+pub(crate) fn print_resources(stats: &ScanStats) {
+    let Some(resources) = &stats.resources else { return };
+    let mut parts = Vec::new();
+    if let Some(bytes) = resources.peak_rss_bytes {
+        parts.push(format!("peak RSS {}", OutputFormatter::format_size(bytes)));
+    }
+    if let Some(bytes) = resources.read_bytes {
+        parts.push(format!("read {}", OutputFormatter::format_size(bytes)));
+    }
+    if let Some(cpu_ms) = resources.cpu_ms {
+        parts.push(format!("CPU {:.0}ms", cpu_ms));
+    }
+    if parts.is_empty() {
+        println!("Resource usage: not available on this platform");
+    } else {
+        println!("Resource usage: {}", parts.join(", "));
+    }
+}
+
+pub(crate) fn load_result(path: &Path) -> ScanResult {
+    std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| ScanResult::from_json(&json).map_err(|e| e.to_string()))
+        .unwrap_or_else(|e| fail(format!("Failed to load scan result {}: {}", path.display(), e)))
+}
@@ -0,0 +1,59 @@
+use std::path::Path;
+use thinkeloquent_tools_chunking_directory_mapping_core::{declared_dependencies, DeclaredDependency, OutdatedReport, VersionIndex};
+
+use super::chunk::with_content_source;
+use super::{emit_structured, fail, scan_or_exit, usage_error};
+use crate::cli::{GlobalArgs, OutdatedArgs};
+
+pub fn run_outdated(args: OutdatedArgs, global: &GlobalArgs) {
+    if args.index.is_none() && !args.online {
+        usage_error("outdated needs the latest versions: pass --index PATH or --online");
+    }
+    let scan_path = args.path.unwrap_or_else(|| ".".to_string());
+    let result = scan_or_exit(global, global.scan_options(), &scan_path);
+
+    let mut index = match &args.index {
+        Some(path) => VersionIndex::load(Path::new(path))
+            .unwrap_or_else(|e| fail(format!("Failed to read version index {}: {}", path, e))),
+        None => VersionIndex::new(),
+    };
+    if args.online {
+        let dependencies = with_content_source(&scan_path, |fs| declared_dependencies(&result, fs));
+        // Snapshot entries win over lookups, so a pinned index stays authoritative
+        let mut fetched = fetch_online(&dependencies);
+        fetched.extend(index);
+        index = fetched;
+    }
+    let report = with_content_source(&scan_path, |fs| OutdatedReport::compute(&result, fs, &index));
+
+    if emit_structured(global, &report, "outdated dependency report") {
+        return;
+    }
+
+    let outdated = report.outdated().count();
+    println!("{} of {} checked dependencies outdated in {} ({} unknown)", outdated, report.dependencies.len(), scan_path, report.unknown);
+    for project in &report.projects {
+        println!("  {:<40} score {:>5.1}  {}/{} outdated", project.path.display().to_string(), project.score, project.outdated, project.checked);
+    }
+    if outdated > 0 {
+        println!();
+    }
+    for dependency in report.outdated() {
+        println!("  {:<32} {:>12} -> {:<12} {:?}  {}", dependency.name, dependency.declared, dependency.latest, dependency.lag, dependency.manifest.display());
+    }
+}
+
+#[cfg(feature = "registry")]
+fn fetch_online(dependencies: &[DeclaredDependency]) -> VersionIndex {
+    use thinkeloquent_tools_chunking_directory_mapping_core::{fetch_latest_versions, DEPS_DEV_ENDPOINT};
+
+    let fetched = tokio::runtime::Runtime::new()
+        .map_err(|e| e.to_string())
+        .and_then(|runtime| runtime.block_on(fetch_latest_versions(DEPS_DEV_ENDPOINT, dependencies)).map_err(|e| e.to_string()));
+    fetched.unwrap_or_else(|e| fail(format!("Failed to look up latest versions: {}", e)))
+}
+
+#[cfg(not(feature = "registry"))]
+fn fetch_online(_dependencies: &[DeclaredDependency]) -> VersionIndex {
+    usage_error("This build of projscan cannot query package registries; rebuild with the `registry` feature or pass --index");
+}
@@ -0,0 +1,35 @@
+use std::path::Path;
+use thinkeloquent_tools_chunking_directory_mapping_core::{format_rfc3339, git_file_history, OwnershipReport};
+
+use super::{emit_structured, fail, scan_or_exit};
+use crate::cli::{GlobalArgs, OwnershipArgs};
+
+pub fn run_ownership(args: OwnershipArgs, global: &GlobalArgs) {
+    let scan_path = args.path.unwrap_or_else(|| ".".to_string());
+
+    // Read history first so a missing repository fails before the scan
+    let history = git_file_history(Path::new(&scan_path))
+        .unwrap_or_else(|e| fail(format!("Ownership needs a git repository: {}", e)));
+    let result = scan_or_exit(global, global.scan_options(), &scan_path);
+
+    let mut report = OwnershipReport::compute(&result, &history);
+    if args.silos {
+        report.directories.retain(|directory| directory.is_silo());
+    }
+
+    if emit_structured(global, &report, "ownership report") {
+        return;
+    }
+
+    println!("{:<24} {:>7} {:>7} {:>7} {:>4}  {:<10}  Top author", "DIRECTORY", "FILES", "COMMITS", "AUTHORS", "BUS", "LAST");
+    for directory in &report.directories {
+        // The date part of the RFC 3339 timestamp
+        let last = directory.last_touched.map(|time| format_rfc3339(time)[..10].to_string()).unwrap_or_default();
+        let top = directory.top_authors.first()
+            .map(|author| format!("{} ({:.0}%)", author.author, author.share * 100.0))
+            .unwrap_or_default();
+        let silo = if directory.is_silo() { "  silo" } else { "" };
+        println!("{:<24} {:>7} {:>7} {:>7} {:>4}  {:<10}  {}{}",
+            directory.directory, directory.files, directory.commits, directory.contributors, directory.bus_factor, last, top, silo);
+    }
+}
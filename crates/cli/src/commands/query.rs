@@ -0,0 +1,22 @@
+use super::{emit_structured, load_result, usage_error};
+use crate::cli::{GlobalArgs, QueryArgs};
+
+pub fn run_query(args: QueryArgs, global: &GlobalArgs) {
+    let result = load_result(&args.result);
+
+    let matches = result.query(&args.expression).unwrap_or_else(|e| usage_error(e));
+
+    if emit_structured(global, &matches, "matches") {
+        return;
+    }
+
+    for entry in &matches {
+        let tags = if entry.tags.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", entry.tags.join(", "))
+        };
+        println!("{}{}", entry.path.display(), tags);
+    }
+    eprintln!("{} of {} entries matched", matches.len(), result.files.len());
+}
@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use thinkeloquent_tools_chunking_directory_mapping_core::{
+    explain_path, git_changed_files, git_file_history, parse_time_spec, plan_walk, FileCommit, FilterExpr, FormatOptions,
+    OutputFormatter, PathListFileSystem, ScanHistory, ScanOptions, ScanResult, ScanSnapshot, SymbolIndex, Thresholds,
+};
+
+use super::chunk::with_content_source;
+use super::export::emit_result;
+use super::stream::stream_scan;
+use super::{emit_structured, fail, print_resources, scan_many_or_exit, scan_or_exit, scan_with_or_exit, usage_error, write_output};
+use crate::cli::{ExportFormat, GlobalArgs, OutputFormatArg, ScanArgs, SortKeyArg};
+use crate::output::infer_format;
+
+pub fn run_scan(args: ScanArgs, global: &GlobalArgs) {
+    // Reject malformed filters before spending time on the scan
+    let filter = args.filter.as_deref()
+        .map(|expression| expression.parse::<FilterExpr>().unwrap_or_else(|e| usage_error(e)));
+    let change_kinds_since = args.change_kinds.as_deref().map(|since| {
+        parse_time_spec(since, SystemTime::now())
+            .unwrap_or_else(|| usage_error(format!("Invalid --change-kinds time {:?}; use a date, RFC 3339 time or age such as 180d", since)))
+    });
+
+    let multiple = args.paths.len() > 1;
+    if multiple && (args.paths_from.is_some() || args.since.is_some() || args.explain.is_some() || args.dry_run) {
+        usage_error("--paths-from, --since, --explain and --dry-run take a single PATH");
+    }
+    let mut scan_path = args.paths.first().cloned().unwrap_or_else(|| ".".to_string());
+    // Flags override the limits of a policy file
+    let policy = args.policy.as_deref()
+        .map(|path| Thresholds::load(path).unwrap_or_else(|e| usage_error(format!("Failed to read policy {}: {}", path.display(), e))))
+        .unwrap_or_default();
+    let thresholds = Thresholds {
+        max_complexity: args.fail_on_complexity.or(policy.max_complexity),
+        max_file_lines: args.fail_on_file_lines.or(policy.max_file_lines),
+        fail_on_errors: args.fail_on_errors || policy.fail_on_errors,
+        layers: policy.layers,
+    };
+
+    let options = ScanOptions {
+        // Symbols, complexity, line counts and purposes are only produced by enhanced analysis
+        enhanced_analysis: global.enhanced
+            || args.symbols
+            || matches!(args.format, OutputFormatArg::RepoMap)
+            || matches!(global.output.as_deref().and_then(infer_format), Some(ExportFormat::LlmsTxt))
+            || thresholds.max_complexity.is_some()
+            || thresholds.max_file_lines.is_some()
+            || !thresholds.layers.is_empty()
+            || args.stability
+            || args.prefer_stable
+            || global.sample_above.is_some()
+            || matches!(args.sort, Some(SortKeyArg::Complexity | SortKeyArg::Importance)),
+        output_format: args.format.clone().into(),
+        ..global.scan_options()
+    };
+
+    if let Some(path) = &args.explain {
+        return explain(global, &options, &scan_path, path);
+    }
+    if args.dry_run {
+        return dry_run(global, &options, &scan_path);
+    }
+
+    if args.stream {
+        if multiple {
+            usage_error("--stream takes a single PATH");
+        }
+        let result = stream_scan(global, options, &scan_path, filter.as_ref());
+        return check_thresholds(&thresholds, &result, &scan_path);
+    }
+
+    let mut result = match &args.paths_from {
+        Some(list) => scan_with_or_exit(global, options, &read_path_list(list), &scan_path),
+        None => match &args.since {
+            Some(since) => scan_with_or_exit(global, options, &changed_since(&scan_path, since), &scan_path),
+            None if multiple => scan_many_or_exit(global, options, &args.paths),
+            None => scan_or_exit(global, options, &scan_path),
+        },
+    };
+    // History, chunks and exports of a merged scan belong to the roots' common ancestor
+    if multiple {
+        scan_path = result.root_path.to_string_lossy().to_string();
+    }
+    if args.stability || args.prefer_stable || change_kinds_since.is_some() {
+        let history = file_history(&scan_path);
+        if args.stability || args.prefer_stable {
+            let classified = result.assign_stability(&history, SystemTime::now());
+            tracing::info!(files = classified, "classified files by stability");
+        }
+        if args.prefer_stable {
+            result.weigh_importance_by_stability();
+        }
+        if let Some(since) = change_kinds_since {
+            let tagged = result.tag_change_kinds(&history, Some(since));
+            tracing::info!(files = tagged, "tagged files by change kind");
+        }
+    }
+
+    if args.record {
+        record_snapshot(&global.history(Path::new(&scan_path)), &result);
+    }
+
+    if let Some(filter) = &filter {
+        result.retain_matching(filter);
+    }
+
+    if let Some(template) = &args.template {
+        let report = render_report(template, &result.with_path_style(&global.path_style()));
+        match &global.output {
+            Some(path) => write_output(path, global.force, &report),
+            None => print!("{}", report),
+        }
+    } else if let Some(output) = &global.output {
+        let format = if args.symbols {
+            ExportFormat::Symbols
+        } else {
+            infer_format(output).unwrap_or_else(|| usage_error(format!(
+                "Cannot infer an output format from {}; use .json, .yaml, .csv, .ndjson, .md, .html, .sarif, .db or llms.txt",
+                output.display()
+            )))
+        };
+        emit_result(format, &result, &scan_path, global);
+    } else if args.symbols {
+        match SymbolIndex::build(&result.with_path_style(&global.path_style()).files).to_json() {
+            Ok(json) => println!("{}", json),
+            Err(e) => fail(format!("Failed to serialize symbol index: {}", e)),
+        }
+    } else if global.json {
+        emit_result(ExportFormat::Json, &result, &scan_path, global);
+    } else if global.yaml {
+        emit_result(ExportFormat::Yaml, &result, &scan_path, global);
+    } else {
+        // Print basic stats
+        println!("Scan completed for: {}", if multiple { args.paths.join(", ") } else { scan_path.clone() });
+        println!("Files found: {}", result.stats.total_files);
+        println!("Directories: {}", result.stats.total_dirs);
+        println!("Total size: {} bytes", result.stats.total_size);
+        println!("Scan duration: {}ms", result.stats.scan_duration_ms);
+        println!("Files per second: {:.2}", result.stats.files_per_second);
+        print_resources(&result.stats);
+        for root in &result.roots {
+            println!("  {}: {} files, {} directories, {} bytes",
+                root.path.display(), root.stats.total_files, root.stats.total_dirs, root.stats.total_size);
+        }
+        if !result.nested_repositories.is_empty() {
+            println!("Nested repositories:");
+        }
+        for repo in &result.nested_repositories {
+            match &repo.summary {
+                Some(summary) => println!("  {} ({}, summarized): {} files, {} directories, {} bytes",
+                    repo.path.display(), repo.kind, summary.files, summary.dirs, summary.total_size),
+                None => println!("  {} ({})", repo.path.display(), repo.kind),
+            }
+        }
+
+        if global.enhanced {
+            println!("Enhanced analysis: enabled");
+        }
+
+        let format_options = FormatOptions {
+            palette: global.palette(),
+            paths: global.path_style(),
+            sort: args.sort.map(Into::into),
+            group_by: args.group_by.map(Into::into),
+            absolute_times: args.absolute_times,
+        };
+        // The summary lists no files, so it gets no structure header
+        if matches!(args.format, OutputFormatArg::Summary) {
+            println!();
+        } else {
+            println!("\nFile structure:");
+        }
+        print!("{}", OutputFormatter::format_result_with(&result, &args.format.into(), &format_options));
+
+        print_errors(&result);
+    }
+
+    check_thresholds(&thresholds, &result, &scan_path);
+}
+
+#[cfg(feature = "templates")]
+fn render_report(template: &Path, result: &ScanResult) -> String {
+    crate::template::render_template(template, result)
+        .unwrap_or_else(|e| fail(format!("Failed to render template {}: {}", template.display(), e)))
+}
+
+#[cfg(not(feature = "templates"))]
+fn render_report(_template: &Path, _result: &ScanResult) -> String {
+    usage_error("This build of projscan has no template support; rebuild with the `templates` feature")
+}
+
+// Runs after the output is written so CI logs keep the full report
+fn check_thresholds(thresholds: &Thresholds, result: &ScanResult, scan_path: &str) {
+    if thresholds.is_empty() {
+        return;
+    }
+    let mut violations = thresholds.check(result);
+    violations.extend(with_content_source(scan_path, |fs| thresholds.check_layers(result, fs)));
+    if violations.is_empty() {
+        return;
+    }
+    eprintln!("\n{} threshold violation(s):", violations.len());
+    for violation in &violations {
+        eprintln!("  {}", violation);
+    }
+    std::process::exit(3);
+}
+
+fn read_path_list(list: &Path) -> PathListFileSystem {
+    let text = if list.as_os_str() == "-" {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text).map(|_| text)
+    } else {
+        std::fs::read_to_string(list)
+    };
+    let text = text.unwrap_or_else(|e| fail(format!("Failed to read path list {}: {}", list.display(), e)));
+    PathListFileSystem::from_lines(&text)
+}
+
+fn changed_since(scan_path: &str, since: &str) -> PathListFileSystem {
+    let changed = git_changed_files(Path::new(scan_path), since)
+        .unwrap_or_else(|e| fail(format!("Failed to list files changed since {}: {}", since, e)));
+    tracing::info!(since, files = changed.len(), "limiting the scan to changed files");
+    PathListFileSystem::new(changed)
+}
+
+pub(crate) fn file_history(scan_path: &str) -> HashMap<PathBuf, Vec<FileCommit>> {
+    git_file_history(Path::new(scan_path)).unwrap_or_else(|e| fail(format!("Failed to read the git history of {}: {}", scan_path, e)))
+}
+
+pub(crate) fn assign_stability(result: &mut ScanResult, scan_path: &str) {
+    let classified = result.assign_stability(&file_history(scan_path), SystemTime::now());
+    tracing::info!(files = classified, "classified files by stability");
+}
+
+pub(crate) fn print_errors(result: &ScanResult) {
+    if !result.errors.is_empty() {
+        println!("\nErrors encountered:");
+        for error in &result.errors {
+            println!("  {}", error);
+        }
+    }
+    if !result.findings.is_empty() {
+        println!("\nSecurity findings:");
+        for finding in &result.findings {
+            println!("  {}", finding);
+        }
+    }
+}
+
+pub(crate) fn record_snapshot(history: &ScanHistory, result: &ScanResult) {
+    if let Err(e) = history.append(&ScanSnapshot::from_result(result)) {
+        fail(format!("Failed to record snapshot in {}: {}", history.path().display(), e));
+    }
+}
+
+fn explain(global: &GlobalArgs, options: &ScanOptions, scan_path: &str, path: &Path) {
+    let decision = explain_path(Path::new(scan_path), path, options)
+        .unwrap_or_else(|e| fail(format!("Cannot explain {}: {}", path.display(), e)));
+    if emit_structured(global, &decision, "decision") {
+        return;
+    }
+
+    match &decision.skip {
+        None => println!("{} would be scanned", decision.path.display()),
+        Some(reason) => println!("{} would be skipped: {}", decision.path.display(), reason),
+    }
+}
+
+fn dry_run(global: &GlobalArgs, options: &ScanOptions, scan_path: &str) {
+    let plan = plan_walk(Path::new(scan_path), options);
+    if emit_structured(global, &plan, "plan") {
+        return;
+    }
+
+    for decision in &plan {
+        match &decision.skip {
+            None => println!("scan  {}", decision.path.display()),
+            Some(reason) => println!("skip  {}  ({})", decision.path.display(), reason),
+        }
+    }
+    let skipped = plan.iter().filter(|decision| decision.skip.is_some()).count();
+    eprintln!("{} would be scanned, {} skipped", plan.len() - skipped, skipped);
+}
@@ -0,0 +1,71 @@
+use crate::cli::{GlobalArgs, SearchArgs};
+
+#[cfg(feature = "search")]
+pub fn run_search(args: SearchArgs, global: &GlobalArgs) {
+    use std::path::Path;
+    use thinkeloquent_tools_chunking_directory_mapping_core::{is_archive, ScanOptions, SearchIndex};
+
+    use super::chunk::with_content_source;
+    use super::{emit_structured, fail, scan_or_exit, usage_error};
+
+    let scan_path = args.path.unwrap_or_else(|| ".".to_string());
+    let dir = match &global.cache_dir {
+        Some(cache_dir) => SearchIndex::dir_in(cache_dir),
+        None if is_archive(Path::new(&scan_path)) => usage_error("An archive cannot hold its own search index; pass --cache-dir"),
+        None => SearchIndex::dir_for_root(Path::new(&scan_path)),
+    };
+
+    let index = if args.reindex || !dir.exists() {
+        let options = ScanOptions {
+            // Summaries, purposes, exports and symbols come from enhanced analysis
+            enhanced_analysis: true,
+            ..global.scan_options()
+        };
+        let result = scan_or_exit(global, options, &scan_path);
+        let index = with_content_source(&scan_path, |fs| SearchIndex::build(&dir, &result, fs))
+            .unwrap_or_else(|e| fail(format!("Failed to build search index in {}: {}", dir.display(), e)));
+        eprintln!("Indexed {} files in {}", result.files.iter().filter(|file| !file.is_dir).count(), dir.display());
+        index
+    } else {
+        SearchIndex::open(&dir).unwrap_or_else(|e| {
+            fail(format!("Failed to open search index in {}: {}; rebuild it with --reindex", dir.display(), e))
+        })
+    };
+
+    let hits = index.search(&args.query, args.limit)
+        .unwrap_or_else(|e| fail(format!("Search failed: {}", e)));
+    if emit_structured(global, &hits, "search hits") {
+        return;
+    }
+
+    let palette = global.palette();
+    for hit in &hits {
+        println!("{:>7.2}  {}", hit.score, palette.bold(&hit.path.display().to_string()));
+        if !hit.snippet.is_empty() {
+            println!("         {}", highlighted(hit, &palette).replace('\n', " "));
+        }
+    }
+    eprintln!("{} files matched", hits.len());
+}
+
+// The snippet with its matched terms in bold
+#[cfg(feature = "search")]
+fn highlighted(
+    hit: &thinkeloquent_tools_chunking_directory_mapping_core::SearchHit,
+    palette: &thinkeloquent_tools_chunking_directory_mapping_core::Palette,
+) -> String {
+    let mut text = String::new();
+    let mut last = 0;
+    for range in &hit.highlights {
+        text.push_str(&hit.snippet[last..range.start]);
+        text.push_str(&palette.bold(&hit.snippet[range.clone()]));
+        last = range.end;
+    }
+    text.push_str(&hit.snippet[last..]);
+    text
+}
+
+#[cfg(not(feature = "search"))]
+pub fn run_search(_args: SearchArgs, _global: &GlobalArgs) {
+    super::usage_error("This build of projscan has no search index; rebuild with the `search` feature");
+}
@@ -0,0 +1,41 @@
+use thinkeloquent_tools_chunking_directory_mapping_core::{select_chunks_for_query, ChunkOptions, ScanOptions};
+
+use super::chunk::chunk_result;
+use super::{emit_structured, scan_or_exit};
+use crate::cli::{GlobalArgs, SelectArgs};
+
+pub fn run_select(args: SelectArgs, global: &GlobalArgs) {
+    let scan_path = args.path.unwrap_or_else(|| ".".to_string());
+    let keywords: Vec<&str> = args.query.iter().map(String::as_str).collect();
+    let options = ScanOptions {
+        // Summaries, exports and symbols are only produced by enhanced analysis
+        enhanced_analysis: true,
+        ..global.scan_options()
+    };
+    let result = scan_or_exit(global, options, &scan_path);
+
+    if args.chunks {
+        let chunks = chunk_result(&result, &scan_path, ChunkOptions { secrets: global.secrets.into(), ..Default::default() }, &global.path_style());
+        let selected = select_chunks_for_query(&chunks, &keywords, args.budget);
+        if emit_structured(global, &selected, "chunks") {
+            return;
+        }
+        for chunk in &selected {
+            println!("{:<60} {:>6} tokens", chunk.id, chunk.token_count);
+        }
+        let total: usize = selected.iter().map(|chunk| chunk.token_count).sum();
+        eprintln!("{} of {} chunks, ~{} of {} tokens", selected.len(), chunks.len(), total, args.budget);
+        return;
+    }
+
+    let selected = result.select_for_query(&keywords, args.budget);
+    if emit_structured(global, &selected, "selection") {
+        return;
+    }
+    println!("{:>4}  {:>7}  {:>7}  Path", "#", "Score", "Tokens");
+    for (rank, file) in selected.iter().enumerate() {
+        println!("{:>4}  {:>7.2}  {:>7}  {}", rank + 1, file.score, file.tokens, file.path.display());
+    }
+    let total: usize = selected.iter().map(|file| file.tokens).sum();
+    eprintln!("{} files, ~{} of {} tokens", selected.len(), total, args.budget);
+}
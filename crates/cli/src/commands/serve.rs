@@ -0,0 +1,17 @@
+use crate::cli::{GlobalArgs, ServeArgs};
+
+#[cfg(feature = "serve")]
+pub fn run_serve(args: ServeArgs, global: &GlobalArgs) {
+    let root = args.root.unwrap_or_else(|| ".".to_string());
+    if global.output.is_some() {
+        super::usage_error("serve returns results over HTTP; --output is not supported");
+    }
+    if let Err(e) = crate::server::serve(std::path::Path::new(&root), args.bind, global.scan_options(), args.max_cached) {
+        super::fail(format!("Server failed: {}", e));
+    }
+}
+
+#[cfg(not(feature = "serve"))]
+pub fn run_serve(_args: ServeArgs, _global: &GlobalArgs) {
+    super::usage_error("This build of projscan has no HTTP server; rebuild with the `serve` feature");
+}
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+use super::scan::print_errors;
+use super::{emit_structured, print_resources, scan_or_exit};
+use crate::cli::{GlobalArgs, PathArgs};
+
+pub fn run_stats(args: PathArgs, global: &GlobalArgs) {
+    let scan_path = args.path.unwrap_or_else(|| ".".to_string());
+    let result = scan_or_exit(global, global.scan_options(), &scan_path);
+
+    if emit_structured(global, &result.stats, "statistics") {
+        return;
+    }
+
+    let mut tag_counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for file in result.files.iter().filter(|file| !file.is_dir) {
+        for tag in &file.tags {
+            *tag_counts.entry(tag.as_str()).or_default() += 1;
+        }
+    }
+
+    println!("Statistics for: {}", scan_path);
+    println!("Files: {}", result.stats.total_files);
+    println!("Directories: {}", result.stats.total_dirs);
+    println!("Total size: {} bytes", result.stats.total_size);
+    println!("Scan duration: {}ms", result.stats.scan_duration_ms);
+    println!("Files per second: {:.2}", result.stats.files_per_second);
+    print_resources(&result.stats);
+
+    if !tag_counts.is_empty() {
+        println!("\nFiles per tag:");
+        for (tag, count) in &tag_counts {
+            println!("  {:<20} {:>6}", tag, count);
+        }
+    }
+
+    let distribution = &result.stats.distribution;
+    let buckets = [("File sizes", &distribution.sizes), ("Complexity", &distribution.complexity)];
+    for (title, buckets) in buckets.into_iter().filter(|(_, buckets)| !buckets.is_empty()) {
+        println!("\n{}:", title);
+        for bucket in buckets {
+            println!("  {:<20} {:>6}", bucket.label, bucket.count);
+        }
+    }
+    if !distribution.depths.is_empty() {
+        println!("\nFiles per depth:");
+        for (depth, count) in &distribution.depths {
+            println!("  {:<20} {:>6}", depth, count);
+        }
+    }
+    if !distribution.extensions.is_empty() {
+        println!("\nFiles per extension:");
+        for (extension, count) in &distribution.extensions {
+            println!("  {:<20} {:>6}", extension, count);
+        }
+    }
+
+    if !result.stats.directories.is_empty() {
+        println!("\nPer directory:");
+        println!("  {:<20} {:>6} {:>12} {:>8} {:>10} {:>6} {:>6} {:>7}", "DIRECTORY", "FILES", "SIZE", "LINES", "COMPLEXITY", "ERRORS", "FAN-IN", "FAN-OUT");
+        for (dir, stats) in &result.stats.directories {
+            let complexity = stats.average_complexity.map_or_else(|| "-".to_string(), |complexity| format!("{:.2}", complexity));
+            let dir = if stats.partial { format!("{} (partial)", dir) } else { dir.clone() };
+            println!("  {:<20} {:>6} {:>12} {:>8} {:>10} {:>6} {:>6} {:>7}", dir, stats.files, stats.size, stats.lines, complexity, stats.errors, stats.fan_in, stats.fan_out);
+        }
+    }
+
+    print_errors(&result);
+}
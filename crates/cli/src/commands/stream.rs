@@ -0,0 +1,95 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use thinkeloquent_tools_chunking_directory_mapping_core::{FileEntry, FilterExpr, PathStyle, ScanOptions, ScanResult};
+
+use super::{fail, stream_or_exit};
+use crate::cli::{ExportFormat, GlobalArgs};
+use crate::output::{infer_format, write_atomic};
+
+// `scan --stream`: entries are written as they are scanned, as NDJSON or into
+// SQLite, so memory stays flat on huge trees. NDJSON gets one entry per line and
+// a last line holding the result without files (stats, issues and findings).
+pub(crate) fn stream_scan(global: &GlobalArgs, options: ScanOptions, scan_path: &str, filter: Option<&FilterExpr>) -> ScanResult {
+    let root = PathBuf::from(scan_path);
+    let style = global.path_style();
+    let display = |mut entry: FileEntry| {
+        if filter.is_some_and(|filter| !filter.matches(&entry, &root)) {
+            return None;
+        }
+        entry.path = PathBuf::from(style.render(&entry.path, &root));
+        Some(entry)
+    };
+
+    let mut result = None;
+    let written = match &global.output {
+        Some(path) if matches!(infer_format(path), Some(ExportFormat::Sqlite)) => write_atomic(path, global.force, |temp| {
+            result = Some(to_sqlite(global, options, scan_path, &style, temp, display)?);
+            Ok(())
+        }),
+        Some(path) => write_atomic(path, global.force, |temp| {
+            let mut out = BufWriter::new(File::create(temp)?);
+            result = Some(to_ndjson(global, options, scan_path, &style, &mut out, display)?);
+            out.flush()
+        }),
+        None => to_ndjson(global, options, scan_path, &style, &mut io::stdout().lock(), display)
+            .map(|streamed| result = Some(streamed)),
+    };
+    if let Err(e) = written {
+        fail(format!("Failed to stream scan of {}: {}", scan_path, e));
+    }
+    if let Some(path) = &global.output {
+        eprintln!("Wrote {}", path.display());
+    }
+    result.expect("a successful write produced a result")
+}
+
+fn to_ndjson(
+    global: &GlobalArgs,
+    options: ScanOptions,
+    scan_path: &str,
+    style: &PathStyle,
+    out: &mut dyn Write,
+    display: impl Fn(FileEntry) -> Option<FileEntry>,
+) -> io::Result<ScanResult> {
+    let result = stream_or_exit(global, options, scan_path, |entry| {
+        if let Some(entry) = display(entry) {
+            serde_json::to_writer(&mut *out, &entry).map_err(io::Error::other)?;
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    });
+    serde_json::to_writer(&mut *out, &result.with_path_style(style)).map_err(io::Error::other)?;
+    out.write_all(b"\n")?;
+    Ok(result)
+}
+
+#[cfg(feature = "sqlite")]
+fn to_sqlite(
+    global: &GlobalArgs,
+    options: ScanOptions,
+    scan_path: &str,
+    style: &PathStyle,
+    path: &Path,
+    display: impl Fn(FileEntry) -> Option<FileEntry>,
+) -> io::Result<ScanResult> {
+    let writer = crate::sqlite::SqliteWriter::create(path).map_err(io::Error::other)?;
+    let result = stream_or_exit(global, options, scan_path, |entry| match display(entry) {
+        Some(entry) => writer.insert_file(&entry).map_err(|e| io::Error::other(e).into()),
+        None => Ok(()),
+    });
+    writer.finish(&result.with_path_style(style)).map_err(io::Error::other)?;
+    Ok(result)
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn to_sqlite(
+    _global: &GlobalArgs,
+    _options: ScanOptions,
+    _scan_path: &str,
+    _style: &PathStyle,
+    _path: &Path,
+    _display: impl Fn(FileEntry) -> Option<FileEntry>,
+) -> io::Result<ScanResult> {
+    super::usage_error("This build of projscan has no SQLite support; rebuild with the `sqlite` feature");
+}
@@ -0,0 +1,58 @@
+use serde::Serialize;
+use std::path::Path;
+use thinkeloquent_tools_chunking_directory_mapping_core::{
+    fix_counts, git_commit_counts, rank_files, RankMetric, RankedFile, ScanOptions,
+};
+
+use super::scan::file_history;
+use super::{emit_structured, fail, scan_or_exit};
+use crate::cli::{GlobalArgs, TopArgs};
+
+#[derive(Serialize)]
+struct TopReport {
+    metric: RankMetric,
+    files: Vec<RankedFile>,
+}
+
+pub fn run_top(args: TopArgs, global: &GlobalArgs) {
+    let metric = RankMetric::from(args.metric);
+    let scan_path = args.path.unwrap_or_else(|| ".".to_string());
+
+    // Read history first so a missing repository fails before the scan
+    let commits = match metric {
+        RankMetric::Churn => Some(git_commit_counts(Path::new(&scan_path))
+            .unwrap_or_else(|e| fail(format!("Churn needs a git repository: {}", e)))),
+        RankMetric::Fixes => Some(fix_counts(&file_history(&scan_path), None)),
+        _ => None,
+    };
+
+    let options = ScanOptions {
+        enhanced_analysis: global.enhanced
+            || matches!(metric, RankMetric::Complexity | RankMetric::Importance | RankMetric::Imported),
+        ..global.scan_options()
+    };
+    let result = scan_or_exit(global, options, &scan_path);
+    let files = rank_files(&result, metric, args.limit, commits.as_ref());
+
+    let report = TopReport { metric, files };
+    if emit_structured(global, &report, "ranking") {
+        return;
+    }
+
+    let header = match metric {
+        RankMetric::Size => "Bytes",
+        RankMetric::Complexity => "Complexity",
+        RankMetric::Importance => "Importance",
+        RankMetric::Imported => "Importers",
+        RankMetric::Churn => "Commits",
+        RankMetric::Fixes => "Fixes",
+    };
+    println!("{:>4}  {:>10}  Path", "#", header);
+    for (rank, file) in report.files.iter().enumerate() {
+        let value = match metric {
+            RankMetric::Complexity | RankMetric::Importance => format!("{:.2}", file.value),
+            _ => format!("{}", file.value as u64),
+        };
+        println!("{:>4}  {:>10}  {}", rank + 1, value, file.path.display());
+    }
+}
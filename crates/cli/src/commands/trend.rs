@@ -0,0 +1,67 @@
+use std::path::Path;
+use std::time::SystemTime;
+use thinkeloquent_tools_chunking_directory_mapping_core::{ScanHistory, ScanOptions, TrendReport};
+
+use super::scan::record_snapshot;
+use super::{emit_structured, fail, scan_or_exit};
+use crate::cli::{GlobalArgs, TrendArgs};
+
+pub fn run_trend(args: TrendArgs, global: &GlobalArgs) {
+    let root = args.path.unwrap_or_else(|| ".".to_string());
+    let history = match args.history {
+        Some(path) => ScanHistory::new(path),
+        None => global.history(Path::new(&root)),
+    };
+
+    if args.record {
+        // Complexity metrics are only available with enhanced analysis
+        let options = ScanOptions { enhanced_analysis: true, ..global.scan_options() };
+        record_snapshot(&history, &scan_or_exit(global, options, &root));
+    }
+
+    let snapshots = history.load()
+        .unwrap_or_else(|e| fail(format!("Failed to read history {}: {}", history.path().display(), e)));
+
+    if snapshots.is_empty() {
+        fail(format!("No snapshots recorded in {}. Run `projscan trend --record` or `projscan --record` first.",
+            history.path().display()));
+    }
+
+    let report = TrendReport::from_snapshots(&snapshots);
+
+    if emit_structured(global, &report, "trend report") {
+        return;
+    }
+
+    let format_complexity = |value: Option<f64>| value.map_or("-".to_string(), |value| format!("{:.2}", value));
+
+    println!("Trend for: {} ({} snapshots)", root, report.points.len());
+    println!("{:<10} {:>7} {:>9} {:>10} {:>8} {:>7}", "When", "Files", "Lines", "Avg cplx", "Max", "Churn");
+    for point in &report.points {
+        println!("{:<10} {:>7} {:>9} {:>10} {:>8} {:>7}",
+            format_age(point.timestamp),
+            point.total_files,
+            point.total_lines,
+            format_complexity(point.average_complexity),
+            format_complexity(point.max_complexity),
+            point.churn());
+    }
+
+    println!();
+    println!("File growth: {:+}", report.file_growth);
+    if let Some(change) = report.complexity_change {
+        println!("Average complexity change: {:+.2}", change);
+    }
+    println!("Total churn: {} files", report.total_churn);
+    println!("Direction: {:?}", report.direction);
+}
+
+fn format_age(timestamp: SystemTime) -> String {
+    let seconds = SystemTime::now().duration_since(timestamp).map(|age| age.as_secs()).unwrap_or(0);
+    match seconds {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        3600..=86399 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
@@ -0,0 +1,41 @@
+use std::thread;
+use std::time::Duration;
+use thinkeloquent_tools_chunking_directory_mapping_core::{ScanDiff, ScanSession};
+
+use super::diff::print_diff;
+use super::{fail, rescan_or_exit, usage_error};
+use crate::cli::{GlobalArgs, WatchArgs};
+
+// Polls rather than subscribing to file events so it behaves the same on every
+// platform and over network mounts; runs until interrupted
+pub fn run_watch(args: WatchArgs, global: &GlobalArgs) {
+    if global.output.is_some() {
+        usage_error("watch streams changes to stdout and does not support --output");
+    }
+    let scan_path = args.path.unwrap_or_else(|| ".".to_string());
+    let interval = Duration::from_secs(args.interval.max(1));
+
+    // Only files changed since the previous round are analyzed again
+    let session = ScanSession::new();
+    let mut previous = rescan_or_exit(global, &session, global.scan_options(), &scan_path);
+    eprintln!("Watching {} ({} files), rescanning every {}s", scan_path, previous.stats.total_files, interval.as_secs());
+
+    loop {
+        thread::sleep(interval);
+        let current = rescan_or_exit(global, &session, global.scan_options(), &scan_path);
+        let diff = ScanDiff::between(&previous, &current);
+
+        if !diff.is_empty() {
+            if global.json {
+                // One compact object per change set, so output can be piped line by line
+                match serde_json::to_string(&diff) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => fail(format!("Failed to serialize diff: {}", e)),
+                }
+            } else {
+                print_diff(&diff);
+            }
+        }
+        previous = current;
+    }
+}
@@ -1,113 +1,83 @@
-use clap::Parser;
-use thinkeloquent_tools_chunking_directory_mapping_core::{ScanOptions, DirectoryScanner, OutputFormat, OutputFormatter};
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches};
+use std::io::IsTerminal;
+use tracing_subscriber::filter::LevelFilter;
 
-#[derive(Parser)]
-#[command(name = "projscan")]
-#[command(about = "A directory scanner for project analysis with enhanced LLM RAG support")]
-pub struct Args {
-    /// The directory to scan
-    pub path: Option<String>,
-    
-    /// Mapper profile to use
-    #[arg(long, default_value = "generic")]
-    pub profile: String,
-    
-    /// Output format as JSON
-    #[arg(long)]
-    pub json: bool,
-    
-    /// Output format as YAML
-    #[arg(long)]
-    pub yaml: bool,
-    
-    /// Enable enhanced content analysis
-    #[arg(long)]
-    pub enhanced: bool,
-    
-    /// Output format for enhanced display
-    #[arg(long, value_enum, default_value_t = OutputFormatArg::Basic)]
-    pub format: OutputFormatArg,
-}
+use cli::{Args, Command, GlobalArgs};
 
-#[derive(clap::ValueEnum, Clone, Debug)]
-pub enum OutputFormatArg {
-    Basic,
-    Compact,
-    Detailed,
-    Hierarchical,
-}
-
-impl From<OutputFormatArg> for OutputFormat {
-    fn from(arg: OutputFormatArg) -> Self {
-        match arg {
-            OutputFormatArg::Basic => OutputFormat::Basic,
-            OutputFormatArg::Compact => OutputFormat::Compact,
-            OutputFormatArg::Detailed => OutputFormat::Detailed,
-            OutputFormatArg::Hierarchical => OutputFormat::Hierarchical,
-        }
-    }
-}
+mod cli;
+mod commands;
+mod output;
+mod progress;
+#[cfg(feature = "serve")]
+mod server;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "templates")]
+mod template;
 
 fn main() {
-    let args = Args::parse();
-    
-    let scan_path = args.path.unwrap_or_else(|| ".".to_string());
+    let mut command = Args::command();
+    let matches = command.get_matches_mut();
     
-    let mut options = ScanOptions::default();
-    options.mapper_profile = args.profile;
-    options.enhanced_analysis = args.enhanced;
-    options.output_format = args.format.clone().into();
+    // Scan options given before another subcommand would be silently ignored
+    if let Some(subcommand) = matches.subcommand_name() {
+        let misplaced = command.get_arguments()
+            .filter(|arg| !arg.is_global_set())
+            .find(|arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine));
+        if let Some(arg) = misplaced {
+            command.error(
+                ErrorKind::ArgumentConflict,
+                format!("'{}' cannot be used with the '{}' subcommand", arg, subcommand),
+            ).exit();
+        }
+    }
     
-    let scanner = DirectoryScanner::new(options);
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let global = args.global;
+    init_logging(&global);
     
-    match scanner.scan(&scan_path) {
-        Ok(result) => {
-            if args.json {
-                match serde_json::to_string_pretty(&result) {
-                    Ok(json) => println!("{}", json),
-                    Err(e) => {
-                        eprintln!("Failed to serialize result: {}", e);
-                        std::process::exit(1);
-                    }
-                }
-            } else if args.yaml {
-                match serde_yaml::to_string(&result) {
-                    Ok(yaml) => println!("{}", yaml),
-                    Err(e) => {
-                        eprintln!("Failed to serialize result: {}", e);
-                        std::process::exit(1);
-                    }
-                }
-            } else {
-                // Print basic stats
-                println!("Scan completed for: {}", scan_path);
-                println!("Files found: {}", result.stats.total_files);
-                println!("Directories: {}", result.stats.total_dirs);
-                println!("Total size: {} bytes", result.stats.total_size);
-                println!("Scan duration: {}ms", result.stats.scan_duration_ms);
-                println!("Files per second: {:.2}", result.stats.files_per_second);
-                
-                if args.enhanced {
-                    println!("Enhanced analysis: enabled");
-                }
-                
-                println!("\nFile structure:");
-                
-                // Use the new output formatter
-                let formatted_output = OutputFormatter::format_result(&result, &args.format.into());
-                print!("{}", formatted_output);
-                
-                if !result.errors.is_empty() {
-                    println!("\nErrors encountered:");
-                    for error in &result.errors {
-                        println!("  {}", error);
-                    }
-                }
-            }
-        },
-        Err(e) => {
-            eprintln!("Scan failed: {}", e);
-            std::process::exit(1);
-        }
+    // A bare `projscan [PATH]` runs the scan command
+    match args.command.unwrap_or(Command::Scan(args.scan)) {
+        Command::Scan(scan_args) => commands::run_scan(scan_args, &global),
+        Command::Analyze(analyze_args) => commands::run_analyze(analyze_args, &global),
+        Command::Chunk(chunk_args) => commands::run_chunk(chunk_args, &global),
+        Command::Diff(diff_args) => commands::run_diff(diff_args, &global),
+        Command::Dedupe(dedupe_args) => commands::run_dedupe(dedupe_args, &global),
+        Command::Stats(stats_args) => commands::run_stats(stats_args, &global),
+        Command::Top(top_args) => commands::run_top(top_args, &global),
+        Command::Select(select_args) => commands::run_select(select_args, &global),
+        Command::Search(search_args) => commands::run_search(search_args, &global),
+        Command::Health(health_args) => commands::run_health(health_args, &global),
+        Command::Flags(flags_args) => commands::run_flags(flags_args, &global),
+        Command::Audit(audit_args) => commands::run_audit(audit_args, &global),
+        Command::Outdated(outdated_args) => commands::run_outdated(outdated_args, &global),
+        Command::Ownership(ownership_args) => commands::run_ownership(ownership_args, &global),
+        Command::Query(query_args) => commands::run_query(query_args, &global),
+        Command::Watch(watch_args) => commands::run_watch(watch_args, &global),
+        Command::Export(export_args) => commands::run_export(export_args, &global),
+        Command::Trend(trend_args) => commands::run_trend(trend_args, &global),
+        Command::Serve(serve_args) => commands::run_serve(serve_args, &global),
+        Command::GenFixture(gen_fixture_args) => commands::run_gen_fixture(gen_fixture_args, &global),
+        Command::Bench(bench_args) => commands::run_bench(bench_args, &global),
     }
-}
\ No newline at end of file
+}
+
+fn init_logging(global: &GlobalArgs) {
+    let level = match (global.quiet, global.verbose) {
+        (true, _) => LevelFilter::ERROR,
+        (false, 0) => LevelFilter::WARN,
+        (false, 1) => LevelFilter::INFO,
+        (false, 2) => LevelFilter::DEBUG,
+        (false, _) => LevelFilter::TRACE,
+    };
+    
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::stderr().is_terminal())
+        .with_target(false)
+        .without_time()
+        .init();
+}
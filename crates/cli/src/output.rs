@@ -0,0 +1,147 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::cli::ExportFormat;
+
+pub fn infer_format(path: &Path) -> Option<ExportFormat> {
+    if path.file_name().is_some_and(|name| name.eq_ignore_ascii_case("llms.txt")) {
+        return Some(ExportFormat::LlmsTxt);
+    }
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "json" => Some(ExportFormat::Json),
+        "yaml" | "yml" => Some(ExportFormat::Yaml),
+        "csv" => Some(ExportFormat::Csv),
+        "md" | "markdown" => Some(ExportFormat::Markdown),
+        "html" | "htm" => Some(ExportFormat::Html),
+        "db" | "sqlite" | "sqlite3" => Some(ExportFormat::Sqlite),
+        "jsonl" => Some(ExportFormat::Chunks),
+        "ndjson" => Some(ExportFormat::Ndjson),
+        "sarif" => Some(ExportFormat::Sarif),
+        _ => None,
+    }
+}
+
+// Writes through a temporary sibling file that is moved into place, so readers
+// never see a partial file and a failed run leaves an existing file untouched
+pub fn write_atomic<F>(path: &Path, force: bool, write: F) -> io::Result<()>
+where
+    F: FnOnce(&Path) -> io::Result<()>,
+{
+    // Fails before doing the work; `move_new` also refuses a file created since
+    if path.exists() && !force {
+        return Err(already_exists(path));
+    }
+
+    let file_name = path.file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a file path", path.display())))?;
+    let temp = path.with_file_name(format!(".{}.{}.tmp", file_name.to_string_lossy(), std::process::id()));
+
+    let outcome = write(&temp).and_then(|_| if force { fs::rename(&temp, path) } else { move_new(&temp, path) });
+    if outcome.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    outcome
+}
+
+// Moves `temp` to `path` only if `path` does not exist. Unlike a rename, a hard
+// link fails when the target exists. Filesystems without hard links (FAT, some
+// network shares) fall back to a rename after checking again, which leaves a
+// short window for another process to create the file in between.
+fn move_new(temp: &Path, path: &Path) -> io::Result<()> {
+    match fs::hard_link(temp, path) {
+        Ok(()) => {
+            let _ = fs::remove_file(temp);
+            Ok(())
+        }
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Err(already_exists(path)),
+        Err(_) if !path.exists() => fs::rename(temp, path),
+        Err(_) => Err(already_exists(path)),
+    }
+}
+
+fn already_exists(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists; pass --force to overwrite it", path.display()))
+}
+
+pub fn write_text(path: &Path, force: bool, text: &str) -> io::Result<()> {
+    write_atomic(path, force, |temp| {
+        let mut file = File::create(temp)?;
+        file.write_all(text.as_bytes())?;
+        file.sync_all()
+    })
+}
+
+// Like `write_text`, for output serialized to the file while it is produced
+pub fn write_streamed<F>(path: &Path, force: bool, write: F) -> io::Result<()>
+where
+    F: FnOnce(&mut dyn Write) -> io::Result<()>,
+{
+    write_atomic(path, force, |temp| {
+        let mut out = BufWriter::new(File::create(temp)?);
+        write(&mut out)?;
+        out.into_inner().map_err(|e| e.into_error())?.sync_all()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Files left in `dir`, to check that no temporary file survives a write
+    fn entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_existing_file_needs_force() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.json");
+        write_text(&path, false, "first").unwrap();
+
+        let error = write_text(&path, false, "second").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert!(error.to_string().contains("pass --force"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+
+        write_text(&path, true, "second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(entries(dir.path()), ["out.json"]);
+    }
+
+    #[test]
+    fn test_file_created_while_writing_is_not_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.json");
+
+        let error = write_atomic(&path, false, |temp| {
+            fs::write(&path, "other process")?;
+            fs::write(temp, "ours")
+        }).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "other process");
+        assert_eq!(entries(dir.path()), ["out.json"]);
+    }
+
+    #[test]
+    fn test_failed_write_leaves_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.jsonl");
+        write_text(&path, false, "complete\n").unwrap();
+
+        let error = write_streamed(&path, true, |out| {
+            out.write_all(b"partial")?;
+            Err(io::Error::other("serialization failed"))
+        }).unwrap_err();
+
+        assert_eq!(error.to_string(), "serialization failed");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "complete\n");
+        assert_eq!(entries(dir.path()), ["out.jsonl"]);
+    }
+}
@@ -0,0 +1,26 @@
+use indicatif::{HumanBytes, ProgressBar, ProgressStyle};
+use std::time::Duration;
+use thinkeloquent_tools_chunking_directory_mapping_core::ScanProgress;
+
+// Entries between message refreshes; formatting the message per entry is
+// measurable on large trees and the eye cannot follow it anyway
+const UPDATE_EVERY: usize = 64;
+
+// A spinner on stderr with running totals; indicatif hides it when stderr is
+// not a terminal, so piped and CI output stays clean
+pub fn scan_spinner() -> ProgressBar {
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_style(ProgressStyle::with_template("{spinner} {msg}").expect("static template is valid"));
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    spinner
+}
+
+pub fn update(spinner: &ProgressBar, progress: &ScanProgress) {
+    if progress.entries_scanned() % UPDATE_EVERY != 1 {
+        return;
+    }
+    let current = progress.current_path.as_ref()
+        .map_or(String::new(), |path| format!(" {}", path.display()));
+    spinner.set_message(format!("{} files, {} dirs, {}{}",
+        progress.files_scanned, progress.dirs_scanned, HumanBytes(progress.bytes_scanned), current));
+}
@@ -0,0 +1,370 @@
+use axum::body::Body;
+use axum::extract::{Path as UrlPath, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{self, BufWriter, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use thinkeloquent_tools_chunking_directory_mapping_core::{
+    write_json, ChunkOptions, Chunker, DirectoryScanner, FileEntry, OsFileSystem, ScanOptions, ScanResult, ScanStats,
+};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+// Clients can only scan below `root`; `defaults` are the options from the
+// command line, which a scan request may partly override
+struct AppState {
+    root: PathBuf,
+    defaults: ScanOptions,
+    cache: RwLock<ScanCache>,
+}
+
+// The most recent scans, oldest evicted first
+struct ScanCache {
+    next_id: u64,
+    capacity: usize,
+    scans: VecDeque<(u64, Arc<ScanResult>)>,
+}
+
+impl ScanCache {
+    fn insert(&mut self, result: ScanResult) -> (u64, Arc<ScanResult>) {
+        self.next_id += 1;
+        let entry = (self.next_id, Arc::new(result));
+        self.scans.push_back(entry.clone());
+        while self.scans.len() > self.capacity {
+            self.scans.pop_front();
+        }
+        entry
+    }
+
+    fn get(&self, id: u64) -> Option<Arc<ScanResult>> {
+        self.scans.iter().find(|(scan_id, _)| *scan_id == id).map(|(_, result)| result.clone())
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ScanRequest {
+    path: Option<String>,
+    profile: Option<String>,
+    enhanced: Option<bool>,
+}
+
+#[derive(Serialize)]
+struct ScanSummary {
+    id: u64,
+    root_path: PathBuf,
+    stats: ScanStats,
+    issues: usize,
+}
+
+impl ScanSummary {
+    fn new(id: u64, result: &ScanResult) -> Self {
+        Self { id, root_path: result.root_path.clone(), stats: result.stats.clone(), issues: result.errors.len() }
+    }
+}
+
+#[derive(Deserialize)]
+struct FilesQuery {
+    q: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChunksQuery {
+    max_tokens: Option<usize>,
+    overlap: Option<usize>,
+}
+
+// Filters are parsed and matched off the async runtime, but a request can
+// still only ask for so much work
+const MAX_FILTER_LEN: usize = 4096;
+const MAX_CHUNK_TOKENS: usize = 32_768;
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+pub fn serve(root: &Path, bind: SocketAddr, defaults: ScanOptions, max_cached: usize) -> io::Result<()> {
+    let root = root.canonicalize()?;
+    let app = router(root.clone(), defaults, max_cached);
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind(bind).await?;
+        eprintln!("Serving scans of {} on http://{}", root.display(), listener.local_addr()?);
+        axum::serve(listener, app)
+            .with_graceful_shutdown(async {
+                let _ = tokio::signal::ctrl_c().await;
+            })
+            .await
+    })
+}
+
+// `root` must already be canonical, as requested paths are checked against it
+fn router(root: PathBuf, defaults: ScanOptions, max_cached: usize) -> Router {
+    let state = Arc::new(AppState {
+        root,
+        defaults,
+        cache: RwLock::new(ScanCache { next_id: 0, capacity: max_cached.max(1), scans: VecDeque::new() }),
+    });
+    Router::new()
+        .route("/health", get(|| async { "ok" }))
+        .route("/scans", get(list_scans).post(create_scan))
+        .route("/scans/:id", get(get_scan))
+        .route("/scans/:id/files", get(get_files))
+        .route("/scans/:id/chunks", get(stream_chunks))
+        .with_state(state)
+}
+
+async fn create_scan(
+    State(state): State<Arc<AppState>>,
+    request: Option<Json<ScanRequest>>,
+) -> Result<(StatusCode, Json<ScanSummary>), ApiError> {
+    let Json(request) = request.unwrap_or_default();
+    let path = resolve(&state.root, request.path.as_deref().unwrap_or("."))?;
+    let options = ScanOptions {
+        mapper_profile: request.profile.unwrap_or_else(|| state.defaults.mapper_profile.clone()),
+        enhanced_analysis: request.enhanced.unwrap_or(state.defaults.enhanced_analysis),
+        ..state.defaults.clone()
+    };
+
+    tracing::info!(path = %path.display(), "scan requested");
+    let result = tokio::task::spawn_blocking(move || DirectoryScanner::new(options).scan(path))
+        .await
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(|e| ApiError(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+
+    let (id, result) = state.cache.write().expect("scan cache lock poisoned").insert(result);
+    Ok((StatusCode::CREATED, Json(ScanSummary::new(id, &result))))
+}
+
+async fn list_scans(State(state): State<Arc<AppState>>) -> Json<Vec<ScanSummary>> {
+    let cache = state.cache.read().expect("scan cache lock poisoned");
+    Json(cache.scans.iter().map(|(id, result)| ScanSummary::new(*id, result)).collect())
+}
+
+// Serialized into the response body as it is sent, so a huge result is not
+// also held in memory as one buffer
+async fn get_scan(State(state): State<Arc<AppState>>, UrlPath(id): UrlPath<u64>) -> Result<Response, ApiError> {
+    let result = cached(&state, id)?;
+    let (sender, receiver) = mpsc::channel::<Result<Vec<u8>, io::Error>>(16);
+    tokio::task::spawn_blocking(move || {
+        let mut out = BufWriter::with_capacity(64 * 1024, ChannelWriter(sender.clone()));
+        if let Err(e) = write_json(&result, &mut out).and_then(|_| out.flush()) {
+            let _ = sender.blocking_send(Err(e));
+        }
+    });
+
+    Ok(([(header::CONTENT_TYPE, "application/json")], Body::from_stream(ReceiverStream::new(receiver))).into_response())
+}
+
+// Sends each buffer written to it as a chunk of a streamed response body
+struct ChannelWriter(mpsc::Sender<Result<Vec<u8>, io::Error>>);
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The client went away; stop serializing
+        self.0.blocking_send(Ok(buf.to_vec())).map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+async fn get_files(
+    State(state): State<Arc<AppState>>,
+    UrlPath(id): UrlPath<u64>,
+    Query(query): Query<FilesQuery>,
+) -> Result<Json<Vec<FileEntry>>, ApiError> {
+    let result = cached(&state, id)?;
+    let Some(expression) = query.q else {
+        return Ok(Json(result.files.clone()));
+    };
+    if expression.len() > MAX_FILTER_LEN {
+        return Err(ApiError(StatusCode::BAD_REQUEST, format!("Filter is longer than {} bytes", MAX_FILTER_LEN)));
+    }
+
+    let files = tokio::task::spawn_blocking(move || {
+        result.query(&expression).map(|files| files.into_iter().cloned().collect::<Vec<_>>())
+    })
+    .await
+    .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.to_string()))?;
+    Ok(Json(files))
+}
+
+// Chunks are produced file by file and sent as JSON lines while the rest are
+// still being read, so large projects start streaming immediately
+async fn stream_chunks(
+    State(state): State<Arc<AppState>>,
+    UrlPath(id): UrlPath<u64>,
+    Query(query): Query<ChunksQuery>,
+) -> Result<Response, ApiError> {
+    let result = cached(&state, id)?;
+    let defaults = ChunkOptions::default();
+    let max_tokens = query.max_tokens.unwrap_or(defaults.max_tokens).clamp(1, MAX_CHUNK_TOKENS);
+    let overlap_lines = query.overlap.unwrap_or(defaults.overlap_lines);
+    if overlap_lines >= max_tokens {
+        return Err(ApiError(
+            StatusCode::BAD_REQUEST,
+            format!("overlap ({}) must be smaller than max_tokens ({})", overlap_lines, max_tokens),
+        ));
+    }
+    let chunker = Chunker::new(ChunkOptions {
+        max_tokens,
+        overlap_lines,
+        secrets: state.defaults.secrets,
+        ..defaults
+    });
+
+    let (sender, receiver) = mpsc::channel::<Result<String, io::Error>>(64);
+    tokio::task::spawn_blocking(move || {
+        let mut sieve = chunker.sieve();
+        for file in &result.files {
+            for chunk in chunker.chunk_file(file, &OsFileSystem, &mut sieve) {
+                let line = serde_json::to_string(&chunk).map(|json| json + "\n").map_err(io::Error::other);
+                // The client went away; stop reading files
+                if sender.blocking_send(line).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    Ok(([(header::CONTENT_TYPE, "application/x-ndjson")], Body::from_stream(ReceiverStream::new(receiver))).into_response())
+}
+
+fn cached(state: &AppState, id: u64) -> Result<Arc<ScanResult>, ApiError> {
+    state.cache.read().expect("scan cache lock poisoned")
+        .get(id)
+        .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, format!("No cached scan with id {}", id)))
+}
+
+// Resolves a requested path against the served root, refusing anything outside it
+fn resolve(root: &Path, requested: &str) -> Result<PathBuf, ApiError> {
+    let path = root.join(requested).canonicalize()
+        .map_err(|e| ApiError(StatusCode::NOT_FOUND, format!("{}: {}", requested, e)))?;
+    if !path.starts_with(root) {
+        return Err(ApiError(StatusCode::FORBIDDEN, format!("{} is outside the served directory", requested)));
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    // A served `root` directory next to an `outside` one holding a secret
+    fn served(max_cached: usize) -> (tempfile::TempDir, Router) {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(temp.path().join("root/src")).unwrap();
+        std::fs::write(temp.path().join("root/src/lib.rs"), "pub fn lib() {}\n").unwrap();
+        std::fs::create_dir_all(temp.path().join("outside")).unwrap();
+        std::fs::write(temp.path().join("outside/secret.txt"), "secret\n").unwrap();
+        let root = temp.path().join("root").canonicalize().unwrap();
+        (temp, router(root, ScanOptions::default(), max_cached))
+    }
+
+    async fn send(app: &Router, method: &str, uri: &str, body: Option<serde_json::Value>) -> (StatusCode, serde_json::Value) {
+        let request = Request::builder().method(method).uri(uri);
+        let request = match body {
+            Some(body) => request.header(header::CONTENT_TYPE, "application/json").body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        };
+        let response = app.clone().oneshot(request.unwrap()).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_scan_outside_root_is_forbidden() {
+        let (_temp, app) = served(4);
+
+        let (status, body) = send(&app, "POST", "/scans", Some(serde_json::json!({ "path": "../outside" }))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(body["error"].as_str().unwrap().contains("outside the served directory"));
+
+        let (status, _) = send(&app, "POST", "/scans", Some(serde_json::json!({ "path": "src" }))).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_filter_is_bad_request() {
+        let (_temp, app) = served(4);
+        let (status, scan) = send(&app, "POST", "/scans", None).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let id = scan["id"].as_u64().unwrap();
+
+        let (status, body) = send(&app, "GET", &format!("/scans/{}/files?q=colour:red", id), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("colour"));
+
+        let (status, files) = send(&app, "GET", &format!("/scans/{}/files?q=name:lib.rs", id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(files.as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_filter_is_bad_request() {
+        let (_temp, app) = served(4);
+        let (_, scan) = send(&app, "POST", "/scans", None).await;
+        let id = scan["id"].as_u64().unwrap();
+
+        let long = "name:a".repeat(MAX_FILTER_LEN);
+        let (status, body) = send(&app, "GET", &format!("/scans/{}/files?q={}", id, long), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("longer than"));
+
+        let (status, files) = send(&app, "GET", &format!("/scans/{}/files?q=path:**/*.rs", id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(files.as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_chunk_overlap_must_be_below_max_tokens() {
+        let (_temp, app) = served(4);
+        let (_, scan) = send(&app, "POST", "/scans", None).await;
+        let id = scan["id"].as_u64().unwrap();
+
+        let (status, body) = send(&app, "GET", &format!("/scans/{}/chunks?max_tokens=4&overlap=4", id), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("overlap"));
+
+        // Clamped rather than rejected, so a huge budget still streams chunks
+        let (status, _) = send(&app, "GET", &format!("/scans/{}/chunks?max_tokens={}&overlap=2", id, usize::MAX), None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_and_evicted_scans_are_not_found() {
+        let (_temp, app) = served(1);
+
+        let (status, body) = send(&app, "GET", "/scans/99", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "No cached scan with id 99");
+
+        let (_, first) = send(&app, "POST", "/scans", None).await;
+        let (_, second) = send(&app, "POST", "/scans", None).await;
+        let (status, _) = send(&app, "GET", &format!("/scans/{}/files", first["id"]), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(&app, "GET", &format!("/scans/{}/files", second["id"]), None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (_, scans) = send(&app, "GET", "/scans", None).await;
+        assert_eq!(scans.as_array().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,111 @@
+use rusqlite::{params, Connection};
+use std::path::Path;
+use std::time::SystemTime;
+use thinkeloquent_tools_chunking_directory_mapping_core::{FileEntry, ScanResult};
+
+const SCHEMA: &str = "
+CREATE TABLE scan (
+    root_path TEXT NOT NULL,
+    total_files INTEGER NOT NULL,
+    total_dirs INTEGER NOT NULL,
+    total_size INTEGER NOT NULL,
+    scan_duration_ms INTEGER NOT NULL
+);
+CREATE TABLE files (
+    path TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    is_dir INTEGER NOT NULL,
+    size INTEGER NOT NULL,
+    modified INTEGER,
+    language TEXT,
+    line_count INTEGER,
+    complexity REAL,
+    importance REAL,
+    purpose TEXT
+);
+CREATE TABLE file_tags (
+    path TEXT NOT NULL REFERENCES files(path),
+    tag TEXT NOT NULL
+);
+CREATE INDEX file_tags_tag ON file_tags(tag);
+CREATE TABLE issues (
+    path TEXT,
+    kind TEXT NOT NULL,
+    severity TEXT NOT NULL,
+    message TEXT NOT NULL
+);
+";
+
+// One row per entry plus a tag table, so results can be explored with plain SQL
+pub fn write_sqlite(result: &ScanResult, path: &Path) -> rusqlite::Result<()> {
+    let writer = SqliteWriter::create(path)?;
+    for file in &result.files {
+        writer.insert_file(file)?;
+    }
+    writer.finish(result)
+}
+
+// Writes entries one at a time for streamed scans; everything lands in one
+// transaction, committed by `finish` once the scan-level rows are known
+pub struct SqliteWriter {
+    connection: Connection,
+}
+
+impl SqliteWriter {
+    pub fn create(path: &Path) -> rusqlite::Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        connection.execute_batch("BEGIN")?;
+        Ok(Self { connection })
+    }
+
+    pub fn insert_file(&self, file: &FileEntry) -> rusqlite::Result<()> {
+        let mut insert_file = self.connection.prepare_cached("INSERT OR REPLACE INTO files VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)")?;
+        let mut insert_tag = self.connection.prepare_cached("INSERT INTO file_tags VALUES (?1, ?2)")?;
+        let path = file.path.to_string_lossy();
+        let info = file.enhanced_info.as_ref();
+        insert_file.execute(params![
+            path,
+            file.name,
+            file.is_dir,
+            file.size as i64,
+            file.modified.duration_since(SystemTime::UNIX_EPOCH).ok().map(|age| age.as_secs() as i64),
+            info.and_then(|info| info.language.as_deref()),
+            info.and_then(|info| info.line_count).map(|lines| lines as i64),
+            info.and_then(|info| info.complexity_score),
+            info.and_then(|info| info.importance_score),
+            info.and_then(|info| info.purpose.as_deref()),
+        ])?;
+        for tag in &file.tags {
+            insert_tag.execute(params![path, tag])?;
+        }
+        Ok(())
+    }
+
+    // Adds the scan summary and issues of `result`, whose files were inserted already
+    pub fn finish(self, result: &ScanResult) -> rusqlite::Result<()> {
+        self.connection.execute(
+            "INSERT INTO scan VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                result.root_path.to_string_lossy(),
+                result.stats.total_files as i64,
+                result.stats.total_dirs as i64,
+                result.stats.total_size as i64,
+                result.stats.scan_duration_ms as i64,
+            ],
+        )?;
+
+        let mut insert_issue = self.connection.prepare_cached("INSERT INTO issues VALUES (?1, ?2, ?3, ?4)")?;
+        for issue in &result.errors {
+            insert_issue.execute(params![
+                issue.path.as_ref().map(|path| path.to_string_lossy().to_string()),
+                issue.kind.as_str(),
+                format!("{:?}", issue.severity).to_lowercase(),
+                issue.message,
+            ])?;
+        }
+        drop(insert_issue);
+
+        self.connection.execute_batch("COMMIT")
+    }
+}
@@ -0,0 +1,58 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::SystemTime;
+use tera::{Context, Tera};
+use thinkeloquent_tools_chunking_directory_mapping_core::{format_rfc3339, OutputFormatter, ScanResult};
+
+// Renders a scan through a user's Tera template. The context holds the whole
+// `result` plus shortcuts: `files` (files only), `dirs`, `stats`, `errors`,
+// `root` and `generated_at`.
+pub fn render_template(template: &Path, result: &ScanResult) -> Result<String, String> {
+    let mut tera = Tera::default();
+    tera.add_template_file(template, Some("report")).map_err(describe)?;
+    tera.register_filter("filesize", filesize);
+    tera.register_filter("with_tag", |value: &Value, args: &HashMap<String, Value>| tag_filter(value, args, true));
+    tera.register_filter("without_tag", |value: &Value, args: &HashMap<String, Value>| tag_filter(value, args, false));
+
+    let serialized = serde_json::to_value(result).map_err(|e| e.to_string())?;
+    let entries = serialized["files"].as_array().cloned().unwrap_or_default();
+    let (dirs, files): (Vec<Value>, Vec<Value>) = entries.into_iter().partition(|entry| entry["is_dir"] == true);
+
+    let mut context = Context::new();
+    context.insert("root", &serialized["root_path"]);
+    context.insert("stats", &serialized["stats"]);
+    context.insert("errors", &serialized["errors"]);
+    context.insert("files", &files);
+    context.insert("dirs", &dirs);
+    context.insert("generated_at", &format_rfc3339(SystemTime::now()));
+    context.insert("result", &serialized);
+
+    tera.render("report", &context).map_err(describe)
+}
+
+// `{{ file.size | filesize }}` renders 2048 as 2.0KB
+fn filesize(value: &Value, _args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let bytes = value.as_u64().ok_or_else(|| tera::Error::msg(format!("filesize expects a byte count, got {}", value)))?;
+    Ok(Value::from(OutputFormatter::format_size(bytes)))
+}
+
+// `{% for file in files | with_tag(tag="test") %}` keeps entries carrying the tag
+fn tag_filter(value: &Value, args: &HashMap<String, Value>, keep: bool) -> tera::Result<Value> {
+    let tag = args.get("tag").and_then(Value::as_str)
+        .ok_or_else(|| tera::Error::msg("with_tag and without_tag need a tag argument, e.g. with_tag(tag=\"source\")"))?;
+    let entries = value.as_array().ok_or_else(|| tera::Error::msg("with_tag and without_tag apply to a list of files"))?;
+    let has_tag = |entry: &Value| entry["tags"].as_array().is_some_and(|tags| tags.iter().any(|t| t == tag));
+    Ok(Value::Array(entries.iter().filter(|entry| has_tag(entry) == keep).cloned().collect()))
+}
+
+// Tera nests the useful message (line, unknown variable) in the error's sources
+fn describe(error: tera::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(&error);
+    while let Some(cause) = source {
+        message.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+    message
+}
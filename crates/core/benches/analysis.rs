@@ -0,0 +1,51 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use thinkeloquent_tools_chunking_directory_mapping_core::{
+    ContentAnalyzer, DirectoryScanner, FileEntry, Fixture, FixtureSpec, MemoryFileSystem, ScanOptions,
+};
+
+const LINES: usize = 20_000;
+
+// One seeded fixture file of `LINES` lines, scanned without analysis so the
+// bench times only the analyzer
+fn large_file(language: &str) -> (MemoryFileSystem, FileEntry, String) {
+    let spec = FixtureSpec {
+        seed: 7,
+        files: 1,
+        max_depth: 0,
+        languages: BTreeMap::from([(language.to_string(), 1)]),
+        min_lines: LINES,
+        max_lines: LINES,
+        ..Default::default()
+    };
+    let fixture = Fixture::generate(&spec).unwrap();
+    let content = fixture.files[0].content.clone();
+    let fs = fixture.to_memory(Path::new("bench"));
+    let result = DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "bench").unwrap();
+    let entry = result.files.into_iter().find(|entry| !entry.is_dir).unwrap();
+    (fs, entry, content)
+}
+
+fn enhanced_analysis(c: &mut Criterion) {
+    let analyzer = ContentAnalyzer::new();
+    let mut group = c.benchmark_group("enhanced_analysis");
+    group.sample_size(20);
+
+    for language in ["rust", "python"] {
+        let (fs, entry, content) = large_file(language);
+        let language = Some(language.to_string());
+        group.throughput(Throughput::Bytes(entry.size));
+        group.bench_with_input(BenchmarkId::new("analyze_file", &entry.name), &entry, |b, entry| {
+            b.iter(|| analyzer.analyze_file_with(entry, &fs).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("branching_details", &entry.name), &content, |b, content| {
+            b.iter(|| analyzer.analyze_branching_details(content, &language))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, enhanced_analysis);
+criterion_main!(benches);
@@ -0,0 +1,395 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::manifests::compare_versions;
+use crate::{declared_dependencies, DeclaredDependency, Ecosystem, FileSystem, FindingKind, ScanError, ScanResult, SecurityFinding, Severity};
+
+// The OSV query API, which serves RustSec, GitHub, PyPA and Go advisories
+#[cfg(feature = "osv")]
+pub const OSV_QUERY_ENDPOINT: &str = "https://api.osv.dev/v1/query";
+
+// The parts of an OSV record (https://ossf.github.io/osv-schema/) we read
+#[derive(Deserialize)]
+struct OsvRecord {
+    id: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    details: Option<String>,
+    #[serde(default)]
+    severity: Vec<OsvSeverity>,
+    #[serde(default)]
+    affected: Vec<OsvAffected>,
+    #[serde(default)]
+    database_specific: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct OsvSeverity {
+    score: String,
+}
+
+#[derive(Deserialize)]
+struct OsvAffected {
+    package: Option<OsvPackage>,
+    #[serde(default)]
+    ranges: Vec<OsvRange>,
+    #[serde(default)]
+    versions: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct OsvPackage {
+    ecosystem: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct OsvRange {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    events: Vec<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AffectedPackage {
+    ecosystem: String,
+    name: String,
+    // (introduced, fixed, last affected) per range; "0" introduces from the start
+    ranges: Vec<(String, Option<String>, Option<String>)>,
+    versions: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advisory {
+    // e.g. "RUSTSEC-2021-0078" or "GHSA-jfh8-c2jp-5v3q"
+    pub id: String,
+    pub aliases: Vec<String>,
+    pub summary: String,
+    pub severity: Severity,
+    affected: Vec<AffectedPackage>,
+}
+
+impl Advisory {
+    fn from_record(record: OsvRecord) -> Self {
+        let affected = record.affected.into_iter()
+            .filter_map(|affected| {
+                let package = affected.package?;
+                Some(AffectedPackage {
+                    ecosystem: package.ecosystem,
+                    name: package.name,
+                    ranges: affected.ranges.iter()
+                        // GIT ranges are commit hashes, which manifests do not pin
+                        .filter(|range| range.kind != "GIT")
+                        .flat_map(|range| ranges(&range.events))
+                        .collect(),
+                    versions: affected.versions,
+                })
+            })
+            .collect();
+        let summary = record.summary
+            .or_else(|| record.details.and_then(|details| details.lines().next().map(str::to_string)))
+            .unwrap_or_default();
+        let label = record.database_specific.as_ref()
+            .and_then(|specific| specific.get("severity")?.as_str().map(str::to_ascii_uppercase));
+        let severity = match label.as_deref() {
+            Some("CRITICAL" | "HIGH") => Severity::Error,
+            Some(_) => Severity::Warning,
+            // Without a label, a CVSS vector with a high impact on
+            // confidentiality, integrity or availability counts as an error
+            None if record.severity.iter().any(|severity| high_impact(&severity.score)) => Severity::Error,
+            None => Severity::Warning,
+        };
+        Self { id: record.id, aliases: record.aliases, summary, severity, affected }
+    }
+
+    // Whether the advisory covers `version` of the package
+    pub fn affects(&self, ecosystem: Ecosystem, name: &str, version: &str) -> bool {
+        self.affected.iter()
+            .filter(|affected| affected.ecosystem.split(':').next() == Some(ecosystem.as_str()))
+            .filter(|affected| package_name(ecosystem, &affected.name) == package_name(ecosystem, name))
+            .any(|affected| {
+                affected.versions.iter().any(|listed| compare_versions(listed, version) == Ordering::Equal)
+                    || affected.ranges.iter().any(|(introduced, fixed, last_affected)| {
+                        compare_versions(version, introduced) != Ordering::Less
+                            && fixed.as_ref().is_none_or(|fixed| compare_versions(version, fixed) == Ordering::Less)
+                            && last_affected.as_ref().is_none_or(|last| compare_versions(version, last) != Ordering::Greater)
+                    })
+            })
+    }
+}
+
+// Known advisories, read from OSV JSON: a bundled export such as RustSec's or
+// the osv.dev per-ecosystem download, or the responses of the query API
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AdvisoryDatabase {
+    advisories: Vec<Advisory>,
+    // Ids already in `advisories`, as exports and query responses overlap
+    ids: HashSet<String>,
+}
+
+impl AdvisoryDatabase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // One record, an array of records, or a query response `{"vulns": [...]}`
+    pub fn from_osv_json(json: &str) -> Result<Self, ScanError> {
+        let value: Value = serde_json::from_str(json).map_err(|e| advisory_error(format!("invalid OSV JSON: {}", e)))?;
+        let records = match value {
+            Value::Array(records) => records,
+            Value::Object(mut object) if !object.contains_key("id") => match object.remove("vulns") {
+                Some(Value::Array(records)) => records,
+                _ => Vec::new(),
+            },
+            record => vec![record],
+        };
+        let mut database = Self::new();
+        for record in records {
+            let record: OsvRecord = serde_json::from_value(record).map_err(|e| advisory_error(format!("invalid OSV record: {}", e)))?;
+            database.insert(Advisory::from_record(record));
+        }
+        Ok(database)
+    }
+
+    // A JSON file, or every .json file under a directory such as an
+    // extracted advisory export
+    pub fn load(path: &Path) -> Result<Self, ScanError> {
+        if !path.exists() {
+            return Err(ScanError::PathNotFound { path: path.to_path_buf() });
+        }
+        let mut database = Self::new();
+        let files = walkdir::WalkDir::new(path).sort_by_file_name().into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_file())
+            .filter(|entry| path.is_file() || entry.path().extension().is_some_and(|extension| extension == "json"));
+        for entry in files {
+            let json = std::fs::read_to_string(entry.path())?;
+            let records = Self::from_osv_json(&json)
+                .map_err(|e| advisory_error(format!("{}: {}", entry.path().display(), e)))?;
+            database.extend(records);
+        }
+        Ok(database)
+    }
+
+    // Adds advisories not already known by ID
+    pub fn extend(&mut self, other: AdvisoryDatabase) {
+        for advisory in other.advisories {
+            self.insert(advisory);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.advisories.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.advisories.is_empty()
+    }
+
+    pub fn advisories(&self) -> &[Advisory] {
+        &self.advisories
+    }
+
+    // Advisories covering the lowest version the dependency allows; none when
+    // the requirement names no version
+    pub fn matching(&self, dependency: &DeclaredDependency) -> Vec<&Advisory> {
+        let Some(version) = dependency.version() else { return Vec::new() };
+        self.advisories.iter()
+            .filter(|advisory| advisory.affects(dependency.ecosystem, &dependency.name, &version))
+            .collect()
+    }
+
+    fn insert(&mut self, advisory: Advisory) {
+        if self.ids.insert(advisory.id.clone()) {
+            self.advisories.push(advisory);
+        }
+    }
+}
+
+impl ScanResult {
+    // Checks the dependencies declared by the scanned manifests against the
+    // database and adds a finding for each known-vulnerable version. Returns
+    // the number of findings added.
+    pub fn flag_vulnerable_dependencies(&mut self, database: &AdvisoryDatabase, fs: &dyn FileSystem) -> usize {
+        let mut added = 0;
+        for dependency in declared_dependencies(self, fs) {
+            for advisory in database.matching(&dependency) {
+                let finding = vulnerable_finding(&dependency, advisory);
+                if !self.findings.contains(&finding) {
+                    self.findings.push(finding);
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+}
+
+// Queries the OSV API for each dependency with a known version
+#[cfg(feature = "osv")]
+pub async fn fetch_advisories(endpoint: &str, dependencies: &[DeclaredDependency]) -> Result<AdvisoryDatabase, ScanError> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(|e| advisory_error(format!("failed to create HTTP client: {}", e)))?;
+    let mut database = AdvisoryDatabase::new();
+    for dependency in dependencies {
+        let Some(version) = dependency.version() else { continue };
+        let body = serde_json::json!({
+            "version": version,
+            "package": { "name": dependency.name, "ecosystem": dependency.ecosystem.as_str() },
+        });
+        let response = client.post(endpoint).json(&body).send().await
+            .map_err(|e| advisory_error(format!("request to {} failed: {}", endpoint, e)))?;
+        let status = response.status();
+        let text = response.text().await
+            .map_err(|e| advisory_error(format!("failed to read response: {}", e)))?;
+        if !status.is_success() {
+            return Err(advisory_error(format!("{} returned {}: {}", endpoint, status, text.trim())));
+        }
+        database.extend(AdvisoryDatabase::from_osv_json(&text)?);
+    }
+    Ok(database)
+}
+
+fn vulnerable_finding(dependency: &DeclaredDependency, advisory: &Advisory) -> SecurityFinding {
+    let version = dependency.version().unwrap_or_default();
+    let mut message = format!("{} {} is affected by {}", dependency.name, version, advisory.id);
+    if !advisory.summary.is_empty() {
+        message.push_str(&format!(": {}", advisory.summary));
+    }
+    SecurityFinding {
+        path: dependency.manifest.clone(),
+        kind: FindingKind::VulnerableDependency,
+        severity: advisory.severity,
+        message,
+        mode: 0,
+        advisory: Some(advisory.id.clone()),
+    }
+}
+
+// Events open a range with "introduced" and close it with "fixed" or
+// "last_affected"; a range left open covers every later version
+fn ranges(events: &[Value]) -> Vec<(String, Option<String>, Option<String>)> {
+    let mut ranges = Vec::new();
+    let mut introduced: Option<String> = None;
+    for event in events {
+        let text = |key: &str| event.get(key).and_then(Value::as_str).map(str::to_string);
+        if let Some(start) = text("introduced") {
+            if let Some(open) = introduced.replace(start) {
+                ranges.push((open, None, None));
+            }
+        } else if let Some(fixed) = text("fixed") {
+            ranges.push((introduced.take().unwrap_or_else(|| "0".to_string()), Some(fixed), None));
+        } else if let Some(last) = text("last_affected") {
+            ranges.push((introduced.take().unwrap_or_else(|| "0".to_string()), None, Some(last)));
+        }
+    }
+    ranges.extend(introduced.map(|open| (open, None, None)));
+    ranges
+}
+
+// PyPI names compare case-insensitively with runs of -, _ and . alike
+fn package_name(ecosystem: Ecosystem, name: &str) -> String {
+    match ecosystem {
+        Ecosystem::PyPi => name.to_lowercase().replace(['_', '.'], "-"),
+        _ => name.to_string(),
+    }
+}
+
+// "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"
+fn high_impact(vector: &str) -> bool {
+    vector.split('/').any(|metric| matches!(metric, "C:H" | "I:H" | "A:H"))
+}
+
+fn advisory_error(message: String) -> ScanError {
+    ScanError::Advisory { message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+    use std::path::PathBuf;
+
+    const SMALLVEC: &str = r#"{
+        "id": "RUSTSEC-2021-0003",
+        "aliases": ["CVE-2021-25900"],
+        "summary": "Buffer overflow in SmallVec::insert_many",
+        "severity": [{"type": "CVSS_V3", "score": "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"}],
+        "affected": [{
+            "package": {"ecosystem": "crates.io", "name": "smallvec"},
+            "ranges": [{"type": "SEMVER", "events": [
+                {"introduced": "0.6.3"}, {"fixed": "0.6.14"},
+                {"introduced": "1.0.0"}, {"fixed": "1.6.1"}
+            ]}]
+        }]
+    }"#;
+
+    fn dependency(ecosystem: Ecosystem, name: &str, requirement: &str) -> DeclaredDependency {
+        DeclaredDependency {
+            manifest: PathBuf::from("app/Cargo.toml"),
+            ecosystem,
+            name: name.to_string(),
+            requirement: Some(requirement.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_matches_version_ranges() {
+        let database = AdvisoryDatabase::from_osv_json(SMALLVEC).unwrap();
+        assert_eq!(database.len(), 1);
+        assert_eq!(database.advisories()[0].severity, Severity::Error);
+
+        let affected = |requirement: &str| !database.matching(&dependency(Ecosystem::CratesIo, "smallvec", requirement)).is_empty();
+        assert!(affected("1.4"));
+        assert!(affected("0.6.10"));
+        assert!(!affected("1.6.1"));
+        assert!(!affected("0.6.2"));
+        assert!(!affected("*"));
+        assert!(database.matching(&dependency(Ecosystem::Npm, "smallvec", "1.4")).is_empty());
+    }
+
+    #[test]
+    fn test_reads_record_lists_and_query_responses() {
+        let pypi = r#"{"vulns": [{
+            "id": "GHSA-x84v-xcm2-53pg",
+            "summary": "Requests leaks credentials on redirect",
+            "affected": [{"package": {"ecosystem": "PyPI", "name": "requests"}, "versions": ["2.31.0"]}],
+            "database_specific": {"severity": "MODERATE"}
+        }]}"#;
+        let mut database = AdvisoryDatabase::from_osv_json(&format!("[{}]", SMALLVEC)).unwrap();
+        database.extend(AdvisoryDatabase::from_osv_json(pypi).unwrap());
+        database.extend(AdvisoryDatabase::from_osv_json(SMALLVEC).unwrap());
+        assert_eq!(database.len(), 2);
+
+        let matched = database.matching(&dependency(Ecosystem::PyPi, "Requests", "==2.31"));
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].severity, Severity::Warning);
+        assert!(AdvisoryDatabase::from_osv_json("{not json").is_err());
+        assert!(AdvisoryDatabase::from_osv_json(r#"{"vulns": []}"#).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_flags_vulnerable_dependencies_as_findings() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/Cargo.toml", "[package]\nname = \"app\"\n\n[dependencies]\nsmallvec = \"1.4\"\nserde = \"1\"\n");
+        let mut result = DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "app").unwrap();
+        let database = AdvisoryDatabase::from_osv_json(SMALLVEC).unwrap();
+
+        assert_eq!(result.flag_vulnerable_dependencies(&database, &fs), 1);
+        assert_eq!(result.flag_vulnerable_dependencies(&database, &fs), 0);
+        let finding = &result.findings[0];
+        assert_eq!(finding.kind, FindingKind::VulnerableDependency);
+        assert_eq!(finding.advisory.as_deref(), Some("RUSTSEC-2021-0003"));
+        assert_eq!(finding.path, PathBuf::from("app/Cargo.toml"));
+        assert_eq!(finding.message, "smallvec 1.4 is affected by RUSTSEC-2021-0003: Buffer overflow in SmallVec::insert_many");
+    }
+}
@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::FileEntry;
+
+// A public item whose declaration differs between two scans
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiItemChange {
+    pub name: String,
+    pub before: String,
+    pub after: String,
+}
+
+// Public items of one file that were added, removed or changed, from the
+// symbols, API surface and exports enhanced analysis extracts. Removed and
+// added files list all their items.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiChange {
+    pub path: PathBuf,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changed: Vec<ApiItemChange>,
+}
+
+// The version bump the API changes call for, by semver rules: removing or
+// changing a public item breaks callers, adding one does not
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SemverBump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl fmt::Display for SemverBump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SemverBump::Patch => "patch",
+            SemverBump::Minor => "minor",
+            SemverBump::Major => "major",
+        })
+    }
+}
+
+// Declarations of a file's public items by name. Names declared several times,
+// such as methods of different types, keep every declaration.
+fn public_items(file: &FileEntry) -> BTreeMap<String, BTreeSet<String>> {
+    let mut items: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let Some(info) = &file.enhanced_info else { return items };
+    let normalized = |declaration: &str| declaration.split_whitespace().collect::<Vec<_>>().join(" ");
+    for symbol in &info.symbols {
+        items.entry(symbol.name.clone()).or_default().insert(normalized(&symbol.signature));
+    }
+    // Exports and API surface lines fill in items the symbol extractor missed
+    for export in &info.exports {
+        let name = export.trim_end_matches(['(', '{', ';', ',']).to_string();
+        if !name.is_empty() && !items.contains_key(&name) {
+            let declaration = info.api_surface.iter()
+                .find(|line| line.split(|c: char| !(c.is_alphanumeric() || c == '_')).any(|word| word == name))
+                .map_or_else(|| name.clone(), |line| normalized(line.trim_end_matches('{').trim_end()));
+            items.entry(name).or_default().insert(declaration);
+        }
+    }
+    items
+}
+
+// API changes between the same file in two scans; None when its public items match
+pub(crate) fn api_change(path: PathBuf, old: Option<&FileEntry>, new: Option<&FileEntry>) -> Option<ApiChange> {
+    let before = old.map(public_items).unwrap_or_default();
+    let after = new.map(public_items).unwrap_or_default();
+    let mut change = ApiChange { path, added: Vec::new(), removed: Vec::new(), changed: Vec::new() };
+    for (name, declarations) in &before {
+        match after.get(name) {
+            None => change.removed.push(name.clone()),
+            Some(new_declarations) if new_declarations != declarations => change.changed.push(ApiItemChange {
+                name: name.clone(),
+                before: declarations.iter().cloned().collect::<Vec<_>>().join("; "),
+                after: new_declarations.iter().cloned().collect::<Vec<_>>().join("; "),
+            }),
+            Some(_) => {}
+        }
+    }
+    change.added = after.keys().filter(|name| !before.contains_key(*name)).cloned().collect();
+    (!change.added.is_empty() || !change.removed.is_empty() || !change.changed.is_empty()).then_some(change)
+}
+
+pub(crate) fn semver_bump(changes: &[ApiChange]) -> SemverBump {
+    if changes.iter().any(|change| !change.removed.is_empty() || !change.changed.is_empty()) {
+        SemverBump::Major
+    } else if changes.iter().any(|change| !change.added.is_empty()) {
+        SemverBump::Minor
+    } else {
+        SemverBump::Patch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Symbol;
+
+    fn file(symbols: &[(&str, &str)], exports: &[&str], api_surface: &[&str]) -> FileEntry {
+        FileEntry::test("p/lib.rs").with_info(|info| {
+            info.symbols = symbols.iter()
+                .map(|(name, signature)| Symbol { name: name.to_string(), kind: "function".to_string(), line: 1, signature: signature.to_string() })
+                .collect();
+            info.exports = exports.iter().map(|export| export.to_string()).collect();
+            info.api_surface = api_surface.iter().map(|line| line.to_string()).collect();
+        })
+    }
+
+    #[test]
+    fn test_reports_added_removed_and_changed_items() {
+        let old = file(&[("parse", "pub fn parse(text: &str) -> Config"), ("load", "pub fn load()")], &[], &[]);
+        let new = file(&[("parse", "pub fn parse(text: &str, strict: bool) -> Config"), ("save", "pub fn save()")], &[], &[]);
+
+        let change = api_change(PathBuf::from("lib.rs"), Some(&old), Some(&new)).unwrap();
+        assert_eq!(change.added, ["save"]);
+        assert_eq!(change.removed, ["load"]);
+        assert_eq!(change.changed, [ApiItemChange {
+            name: "parse".to_string(),
+            before: "pub fn parse(text: &str) -> Config".to_string(),
+            after: "pub fn parse(text: &str, strict: bool) -> Config".to_string(),
+        }]);
+        assert_eq!(semver_bump(&[change]), SemverBump::Major);
+    }
+
+    #[test]
+    fn test_exports_fill_in_and_whitespace_is_ignored() {
+        let old = file(&[("run", "pub fn run()")], &["Config", "helper"], &["pub struct Config {"]);
+        let new = file(&[("run", "pub fn  run()")], &["Config", "helper", "extra"], &["pub struct Config"]);
+
+        let change = api_change(PathBuf::from("lib.rs"), Some(&old), Some(&new)).unwrap();
+        assert_eq!(change.added, ["extra"]);
+        assert!(change.removed.is_empty() && change.changed.is_empty());
+        assert_eq!(semver_bump(&[change]), SemverBump::Minor);
+        assert_eq!(api_change(PathBuf::from("lib.rs"), Some(&old), Some(&old)), None);
+    }
+
+    #[test]
+    fn test_new_and_deleted_files_list_every_item() {
+        let module = file(&[("run", "pub fn run()")], &[], &[]);
+
+        assert_eq!(api_change(PathBuf::from("a.rs"), None, Some(&module)).unwrap().added, ["run"]);
+        assert_eq!(api_change(PathBuf::from("a.rs"), Some(&module), None).unwrap().removed, ["run"]);
+        assert_eq!(semver_bump(&[]), SemverBump::Patch);
+    }
+}
@@ -0,0 +1,198 @@
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Component, Path};
+use std::time::{Duration, SystemTime};
+
+use crate::{MemoryFileSystem, ScanError};
+use crate::timestamp::days_from_civil;
+
+// Upper bound on decompressed content held in memory for a single archive
+const MAX_UNCOMPRESSED_BYTES: u64 = 512 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+fn archive_kind(path: &Path) -> Option<ArchiveKind> {
+    let name = path.file_name()?.to_str()?.to_lowercase();
+    if name.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(ArchiveKind::TarGz)
+    } else if name.ends_with(".tar") {
+        Some(ArchiveKind::Tar)
+    } else {
+        None
+    }
+}
+
+pub fn is_archive(path: &Path) -> bool {
+    archive_kind(path).is_some()
+}
+
+// Reads every regular file of the archive into memory, rooted at `path`
+pub fn load_archive(path: &Path) -> Result<MemoryFileSystem, ScanError> {
+    let kind = archive_kind(path).ok_or_else(|| ScanError::InvalidConfig {
+        message: format!("Unsupported archive format: {}", path.display()),
+    })?;
+    let file = BufReader::new(File::open(path)?);
+
+    let mut fs = MemoryFileSystem::new();
+    match kind {
+        ArchiveKind::Zip => load_zip(file, path, &mut fs)?,
+        ArchiveKind::Tar => load_tar(file, path, &mut fs)?,
+        ArchiveKind::TarGz => load_tar(GzDecoder::new(file), path, &mut fs)?,
+    }
+    Ok(fs)
+}
+
+fn load_zip<R: Read + io::Seek>(reader: R, root: &Path, fs: &mut MemoryFileSystem) -> Result<(), ScanError> {
+    let mut archive = zip::ZipArchive::new(reader).map_err(invalid_archive)?;
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index).map_err(invalid_archive)?;
+        // `enclosed_name` rejects absolute paths and `..` traversal
+        let name = match file.enclosed_name() {
+            Some(name) if !file.is_dir() => name,
+            _ => continue,
+        };
+        let modified = file.last_modified().map_or(SystemTime::UNIX_EPOCH, |time| {
+            civil_to_system_time(time.year() as i64, time.month() as i64, time.day() as i64,
+                time.hour() as u64, time.minute() as u64, time.second() as u64)
+        });
+
+        let content = read_limited(&mut file, fs.total_size())?;
+        fs.insert_with_modified(root.join(name), content, modified);
+    }
+    Ok(())
+}
+
+fn load_tar<R: Read>(reader: R, root: &Path, fs: &mut MemoryFileSystem) -> Result<(), ScanError> {
+    let mut archive = tar::Archive::new(reader);
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path()?.into_owned();
+        if !is_enclosed(&name) {
+            continue;
+        }
+        let modified = entry.header().mtime()
+            .map_or(SystemTime::UNIX_EPOCH, |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+
+        let content = read_limited(&mut entry, fs.total_size())?;
+        fs.insert_with_modified(root.join(name), content, modified);
+    }
+    Ok(())
+}
+
+fn read_limited<R: Read>(reader: &mut R, used: u64) -> Result<Vec<u8>, ScanError> {
+    let remaining = MAX_UNCOMPRESSED_BYTES.saturating_sub(used);
+    let mut content = Vec::new();
+    // Read one byte past the budget to detect overflow without trusting headers
+    reader.take(remaining + 1).read_to_end(&mut content)?;
+    if content.len() as u64 > remaining {
+        return Err(ScanError::LimitExceeded { limit: MAX_UNCOMPRESSED_BYTES as usize });
+    }
+    Ok(content)
+}
+
+fn is_enclosed(path: &Path) -> bool {
+    path.components().all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+fn invalid_archive(error: zip::result::ZipError) -> ScanError {
+    ScanError::Io(io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+// Zip timestamps are civil dates without a zone; treat them as UTC
+fn civil_to_system_time(year: i64, month: i64, day: i64, hour: u64, minute: u64, second: u64) -> SystemTime {
+    let days = days_from_civil(year, month, day);
+    let secs = days.max(0) as u64 * 86_400 + hour * 3600 + minute * 60 + second;
+    SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, FileSystem, ScanOptions};
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn write_zip(path: &Path, files: &[(&str, &str)]) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in files {
+            writer.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    fn write_tar_gz(path: &Path, files: &[(&str, &str)]) {
+        let encoder = GzEncoder::new(File::create(path).unwrap(), Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mtime(1_700_000_000);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, content.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn test_scans_zip_without_extracting() {
+        let dir = TempDir::new().unwrap();
+        let archive = dir.path().join("release.zip");
+        write_zip(&archive, &[
+            ("src/main.rs", "fn main() {\n    if ready() { run(); }\n}\n"),
+            ("README.md", "# Release"),
+        ]);
+
+        let options = ScanOptions { enhanced_analysis: true, ..Default::default() };
+        let result = DirectoryScanner::new(options).scan(&archive).unwrap();
+
+        assert_eq!(result.root_path, archive);
+        let main = result.find(archive.join("src/main.rs")).unwrap();
+        assert!(main.tags.contains(&"source".to_string()));
+        assert_eq!(main.enhanced_info.as_ref().unwrap().line_count, Some(3));
+        assert!(result.find("README.md").is_some());
+        assert_eq!(result.stats.total_dirs, 2);
+        assert!(std::fs::read_dir(dir.path()).unwrap().count() == 1, "nothing extracted");
+    }
+
+    #[test]
+    fn test_loads_tar_gz_with_mtimes() {
+        let dir = TempDir::new().unwrap();
+        let archive = dir.path().join("src.tar.gz");
+        write_tar_gz(&archive, &[("pkg/lib.py", "def f():\n    return 1\n")]);
+
+        let fs = load_archive(&archive).unwrap();
+        let entries: Vec<_> = fs.walk(&archive, &ScanOptions::default()).map(Result::unwrap).collect();
+        let lib = entries.iter().find(|entry| entry.name == "lib.py").unwrap();
+
+        assert_eq!(lib.path, archive.join("pkg/lib.py"));
+        assert_eq!(lib.modified, SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+    }
+
+    #[test]
+    fn test_archive_detection_and_zip_dates() {
+        assert!(is_archive(Path::new("a/b.ZIP")));
+        assert!(is_archive(Path::new("b.tgz")));
+        assert!(is_archive(Path::new("b.tar")));
+        assert!(!is_archive(Path::new("b.gz")));
+
+        let time = civil_to_system_time(2024, 3, 1, 12, 0, 0);
+        assert_eq!(time, SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_294_400));
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use crate::{ContentAnalyzer, DirectoryScanner, FileSystem, OutputFormat, OutputFormatter, ScanError, ScanOptions};
+
+// Timing of one phase of a benchmarked scan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchPhase {
+    // "walk", "analysis" or "format"
+    pub name: String,
+    pub duration_ms: f64,
+    // Entries walked, files analyzed, or lines formatted
+    pub items: usize,
+    // File bytes the phase read, or output bytes it produced
+    pub bytes: u64,
+    // Estimated memory the phase's result holds, from its serialized size
+    pub retained_bytes: u64,
+}
+
+impl BenchPhase {
+    pub fn items_per_second(&self) -> f64 {
+        if self.duration_ms > 0.0 { self.items as f64 / (self.duration_ms / 1000.0) } else { 0.0 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchReport {
+    pub root: PathBuf,
+    pub files: usize,
+    pub dirs: usize,
+    pub phases: Vec<BenchPhase>,
+    pub total_ms: f64,
+    // Estimated peak memory: everything the phases retain is alive at the end
+    pub peak_memory_bytes: u64,
+}
+
+fn elapsed_ms(start: Instant) -> f64 {
+    start.elapsed().as_secs_f64() * 1000.0
+}
+
+fn serialized_size<T: Serialize>(value: &T) -> u64 {
+    serde_json::to_vec(value).map_or(0, |bytes| bytes.len() as u64)
+}
+
+// Scans `root` in three separately timed phases: the walk and classification
+// without content analysis, content analysis of every file, then formatting
+// the analyzed result as `format`.
+pub fn bench_scan(
+    fs: &dyn FileSystem,
+    root: impl Into<PathBuf>,
+    options: &ScanOptions,
+    format: &OutputFormat,
+) -> Result<BenchReport, ScanError> {
+    let root = root.into();
+
+    let start = Instant::now();
+    let walk_options = ScanOptions { enhanced_analysis: false, ..options.clone() };
+    let mut result = DirectoryScanner::new(walk_options).scan_with(fs, root.clone())?;
+    let walk = BenchPhase {
+        name: "walk".to_string(),
+        duration_ms: elapsed_ms(start),
+        items: result.files.len(),
+        bytes: 0,
+        retained_bytes: serialized_size(&result),
+    };
+
+    let start = Instant::now();
+    let analyzer = ContentAnalyzer::with_caps(options.complexity_caps).with_languages(options.languages.clone());
+    let mut analyzed = 0;
+    let mut read = 0;
+    for file in result.files.iter_mut().filter(|file| !file.is_dir) {
+        file.enhanced_info = Some(analyzer.analyze_file_with(file, fs)?);
+        analyzed += 1;
+        read += file.size;
+    }
+    let duration_ms = elapsed_ms(start);
+    let infos: Vec<_> = result.files.iter().filter_map(|file| file.enhanced_info.as_ref()).collect();
+    let analysis = BenchPhase {
+        name: "analysis".to_string(),
+        duration_ms,
+        items: analyzed,
+        bytes: read,
+        retained_bytes: serialized_size(&infos),
+    };
+
+    let start = Instant::now();
+    let output = OutputFormatter::format_result(&result, format);
+    let format = BenchPhase {
+        name: "format".to_string(),
+        duration_ms: elapsed_ms(start),
+        items: output.lines().count(),
+        bytes: output.len() as u64,
+        retained_bytes: output.len() as u64,
+    };
+
+    let phases = vec![walk, analysis, format];
+    Ok(BenchReport {
+        root,
+        files: result.stats.total_files - result.stats.total_dirs,
+        dirs: result.stats.total_dirs,
+        total_ms: phases.iter().map(|phase| phase.duration_ms).sum(),
+        peak_memory_bytes: phases.iter().map(|phase| phase.retained_bytes).sum(),
+        phases,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Fixture, FixtureSpec};
+    use std::path::Path;
+
+    #[test]
+    fn test_reports_each_phase() {
+        let fixture = Fixture::generate(&FixtureSpec { files: 20, ..Default::default() }).unwrap();
+        let fs = fixture.to_memory(Path::new("app"));
+        let report = bench_scan(&fs, "app", &ScanOptions::default(), &OutputFormat::Detailed).unwrap();
+
+        let names: Vec<&str> = report.phases.iter().map(|phase| phase.name.as_str()).collect();
+        assert_eq!(names, ["walk", "analysis", "format"]);
+        assert_eq!(report.files, 20);
+        assert_eq!(report.phases[0].items, report.files + report.dirs);
+        assert_eq!((report.phases[1].items, report.phases[1].bytes), (20, fixture.total_size()));
+        assert!(report.phases[2].bytes > 0);
+        assert_eq!(report.peak_memory_bytes, report.phases.iter().map(|phase| phase.retained_bytes).sum::<u64>());
+    }
+
+    #[test]
+    fn test_items_per_second() {
+        let phase = BenchPhase { name: "walk".to_string(), duration_ms: 250.0, items: 100, bytes: 0, retained_bytes: 0 };
+        assert_eq!(phase.items_per_second(), 400.0);
+        assert_eq!(BenchPhase { duration_ms: 0.0, ..phase }.items_per_second(), 0.0);
+    }
+}
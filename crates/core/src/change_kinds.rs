@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::{FileCommit, ScanResult};
+
+// Tags for files whose commits are mostly of one kind
+pub const FIX_HEAVY_TAG: &str = "fix-heavy";
+pub const FEATURE_HEAVY_TAG: &str = "feature-heavy";
+pub const REFACTOR_HEAVY_TAG: &str = "refactor-heavy";
+// Commits of a kind a file needs, and the share of its commits they must make
+// up, before it is tagged as heavy in that kind
+const HEAVY_COMMITS: usize = 3;
+const HEAVY_SHARE: f64 = 0.5;
+
+// What a commit did, by its conventional-commit prefix ("fix(parser)!: ...")
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommitKind {
+    Fix,
+    Feature,
+    Refactor,
+    Other,
+}
+
+impl CommitKind {
+    pub fn of_subject(subject: &str) -> Self {
+        let Some((prefix, _)) = subject.split_once(':') else { return CommitKind::Other };
+        let kind = prefix.trim_end_matches('!');
+        let kind = kind.split_once('(').map_or(kind, |(kind, _)| kind).trim().to_ascii_lowercase();
+        match kind.as_str() {
+            "fix" | "bugfix" | "hotfix" => CommitKind::Fix,
+            "feat" | "feature" => CommitKind::Feature,
+            "refactor" => CommitKind::Refactor,
+            _ => CommitKind::Other,
+        }
+    }
+
+    fn heavy_tag(self) -> Option<&'static str> {
+        match self {
+            CommitKind::Fix => Some(FIX_HEAVY_TAG),
+            CommitKind::Feature => Some(FEATURE_HEAVY_TAG),
+            CommitKind::Refactor => Some(REFACTOR_HEAVY_TAG),
+            CommitKind::Other => None,
+        }
+    }
+}
+
+impl fmt::Display for CommitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CommitKind::Fix => "fix",
+            CommitKind::Feature => "feature",
+            CommitKind::Refactor => "refactor",
+            CommitKind::Other => "other",
+        })
+    }
+}
+
+fn recent(commits: &[FileCommit], since: Option<SystemTime>) -> impl Iterator<Item = &FileCommit> {
+    commits.iter().filter(move |commit| since.is_none_or(|since| commit.time >= since))
+}
+
+// Fix commits touching each file since `since` (all history when None), keyed
+// like the history; files never fixed are left out. Bugfix magnets rank
+// highest.
+pub fn fix_counts(history: &HashMap<PathBuf, Vec<FileCommit>>, since: Option<SystemTime>) -> HashMap<PathBuf, usize> {
+    history.iter()
+        .map(|(path, commits)| {
+            let fixes = recent(commits, since).filter(|commit| CommitKind::of_subject(&commit.subject) == CommitKind::Fix).count();
+            (path.clone(), fixes)
+        })
+        .filter(|(_, fixes)| *fixes > 0)
+        .collect()
+}
+
+impl ScanResult {
+    // Tags files fix-heavy, feature-heavy or refactor-heavy when at least
+    // three of their commits since `since`, and half or more of them, are of
+    // that kind. History is keyed by paths relative to the root, as
+    // `git_file_history` returns it. Returns the number of files tagged.
+    pub fn tag_change_kinds(&mut self, history: &HashMap<PathBuf, Vec<FileCommit>>, since: Option<SystemTime>) -> usize {
+        let mut tagged = 0;
+        for file in self.files.iter_mut().filter(|file| !file.is_dir) {
+            let relative = file.path.strip_prefix(&self.root_path).unwrap_or(&file.path);
+            let Some(commits) = history.get(relative) else { continue };
+            let mut counts: HashMap<CommitKind, usize> = HashMap::new();
+            let mut total = 0;
+            for commit in recent(commits, since) {
+                *counts.entry(CommitKind::of_subject(&commit.subject)).or_insert(0) += 1;
+                total += 1;
+            }
+            let mut heavy = false;
+            for kind in [CommitKind::Fix, CommitKind::Feature, CommitKind::Refactor] {
+                let Some(tag) = kind.heavy_tag() else { continue };
+                let count = counts.get(&kind).copied().unwrap_or(0);
+                if count >= HEAVY_COMMITS && count as f64 >= total as f64 * HEAVY_SHARE {
+                    if !file.tags.iter().any(|existing| existing == tag) {
+                        file.tags.push(tag.to_string());
+                    }
+                    heavy = true;
+                }
+            }
+            tagged += usize::from(heavy);
+        }
+        tagged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn commits(subjects: &[&str]) -> Vec<FileCommit> {
+        subjects.iter().enumerate().map(|(index, subject)| FileCommit {
+            commit: index.to_string(),
+            author: "dev".to_string(),
+            time: UNIX_EPOCH + Duration::from_secs(index as u64 * 86_400),
+            subject: subject.to_string(),
+        }).collect()
+    }
+
+    fn scan() -> ScanResult {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/src/parser.rs", "");
+        fs.insert("app/src/api.rs", "");
+        DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "app").unwrap()
+    }
+
+    fn has_tag(result: &ScanResult, path: &str, tag: &str) -> bool {
+        result.find(path).unwrap().tags.iter().any(|existing| existing == tag)
+    }
+
+    #[test]
+    fn test_reads_conventional_prefixes() {
+        assert_eq!(CommitKind::of_subject("fix(parser)!: handle empty input"), CommitKind::Fix);
+        assert_eq!(CommitKind::of_subject("Feat: add export"), CommitKind::Feature);
+        assert_eq!(CommitKind::of_subject("refactor: split module"), CommitKind::Refactor);
+        assert_eq!(CommitKind::of_subject("Fix crash on startup"), CommitKind::Other);
+        assert_eq!(CommitKind::of_subject("docs: typo"), CommitKind::Other);
+    }
+
+    #[test]
+    fn test_tags_files_dominated_by_one_kind() {
+        let history = HashMap::from([
+            (PathBuf::from("src/parser.rs"), commits(&["feat: parser", "fix: a", "fix(lexer): b", "fix: c", "docs: d"])),
+            // Three refactors are not half of seven commits
+            (PathBuf::from("src/api.rs"), commits(&["refactor: a", "refactor: b", "refactor: c", "feat: d", "feat: e", "chore: f", "test: g"])),
+        ]);
+        let mut result = scan();
+
+        assert_eq!(result.tag_change_kinds(&history, None), 1);
+        assert!(has_tag(&result, "app/src/parser.rs", FIX_HEAVY_TAG));
+        assert!(!has_tag(&result, "app/src/api.rs", REFACTOR_HEAVY_TAG));
+        // From the third day on, parser.rs has only two fixes
+        let mut result = scan();
+        assert_eq!(result.tag_change_kinds(&history, Some(UNIX_EPOCH + Duration::from_secs(2 * 86_400))), 0);
+    }
+
+    #[test]
+    fn test_fix_counts_rank_bugfix_magnets() {
+        let history = HashMap::from([
+            (PathBuf::from("src/parser.rs"), commits(&["fix: a", "fix: b", "feat: c"])),
+            (PathBuf::from("src/api.rs"), commits(&["feat: a"])),
+        ]);
+
+        let counts = fix_counts(&history, None);
+        assert_eq!(counts, HashMap::from([(PathBuf::from("src/parser.rs"), 2)]));
+        let ranked = crate::rank_files(&scan(), crate::RankMetric::Fixes, 10, Some(&counts));
+        assert_eq!(ranked.iter().map(|file| file.path.as_path()).collect::<Vec<_>>(), [std::path::Path::new("src/parser.rs")]);
+    }
+}
@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::similarity::MAX_POSTINGS;
+use crate::{estimate_tokens, Chunk, ContentFingerprint};
+
+const LICENSE_MARKERS: &[&str] = &[
+    "copyright", "license", "licence", "spdx-license-identifier", "all rights reserved", "permission is hereby granted",
+];
+const GENERATED_MARKERS: &[&str] = &[
+    "@generated", "do not edit", "auto-generated", "autogenerated", "code generated by", "this file was generated",
+];
+pub(crate) const COMMENT_PREFIXES: &[&str] = &["//", "#", "/*", "*", "--", ";", "<!--", "\"\"\"", "'''"];
+// Generated-code markers only count this close to the top of a file
+const GENERATED_HEADER_LINES: usize = 10;
+// Chunks with a larger share of blank lines are mostly whitespace
+const MAX_BLANK_SHARE: f64 = 0.75;
+const MIN_WORD_LEN: usize = 2;
+
+// Which chunks to leave out of an embedding corpus. Nothing is dropped by default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChunkFilter {
+    // Drop chunks that are mostly blank, only a license header, or from a file
+    // whose header marks it as generated
+    pub drop_boilerplate: bool,
+    // Drop chunks whose `information_density` is below this
+    pub min_density: Option<f64>,
+    // Drop chunks more similar than this (0 to 1) to a chunk already kept
+    pub max_similarity: Option<f64>,
+}
+
+impl ChunkFilter {
+    pub fn is_empty(&self) -> bool {
+        !self.drop_boilerplate && self.min_density.is_none() && self.max_similarity.is_none()
+    }
+}
+
+// Distinct words per estimated token, from 0 for punctuation-only text up to 1.
+// Prose and ordinary code land around 0.3-0.6; closing braces, separator lines
+// and repeated data rows fall well below.
+pub fn information_density(text: &str) -> f64 {
+    let tokens = estimate_tokens(text);
+    if tokens == 0 {
+        return 0.0;
+    }
+    let words: HashSet<String> = text
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| word.chars().count() >= MIN_WORD_LEN)
+        .map(|word| word.to_lowercase())
+        .collect();
+    (words.len() as f64 / tokens as f64).min(1.0)
+}
+
+// Applies a `ChunkFilter` across the files of one corpus, remembering kept
+// chunks so near-duplicates in later files are dropped too
+#[derive(Debug, Default)]
+pub struct ChunkSieve {
+    filter: ChunkFilter,
+    kept: Vec<ContentFingerprint>,
+    // Identifier sketch value to the kept chunks holding it
+    postings: HashMap<u64, Vec<usize>>,
+}
+
+impl ChunkSieve {
+    pub fn new(filter: ChunkFilter) -> Self {
+        Self { filter, ..Default::default() }
+    }
+
+    // The chunks of one file that pass, in order; `content` is the whole file,
+    // whose header decides whether every chunk counts as generated
+    pub fn retain(&mut self, content: &str, chunks: Vec<Chunk>) -> Vec<Chunk> {
+        if self.filter.is_empty() {
+            return chunks;
+        }
+        if self.filter.drop_boilerplate && is_generated(content) {
+            return Vec::new();
+        }
+        chunks.into_iter().filter(|chunk| self.keep(chunk)).collect()
+    }
+
+    fn keep(&mut self, chunk: &Chunk) -> bool {
+        if self.filter.drop_boilerplate && (is_mostly_blank(&chunk.content) || is_license_header(&chunk.content)) {
+            return false;
+        }
+        if self.filter.min_density.is_some_and(|min| information_density(&chunk.content) < min) {
+            return false;
+        }
+        let Some(max_similarity) = self.filter.max_similarity else {
+            return true;
+        };
+
+        let fingerprint = ContentFingerprint::from_content(&chunk.content);
+        if fingerprint.is_empty() {
+            return true;
+        }
+        // Only kept chunks sharing an identifier can be near-duplicates
+        let candidates: HashSet<usize> = fingerprint.identifier_sketch().iter()
+            .filter_map(|value| self.postings.get(value))
+            .filter(|indices| indices.len() <= MAX_POSTINGS)
+            .flatten()
+            .copied()
+            .collect();
+        if candidates.iter().any(|&index| self.kept[index].similarity(&fingerprint) > max_similarity) {
+            return false;
+        }
+
+        let index = self.kept.len();
+        for value in fingerprint.identifier_sketch() {
+            self.postings.entry(*value).or_default().push(index);
+        }
+        self.kept.push(fingerprint);
+        true
+    }
+}
+
+// The text of a comment line without its markers, if the line is a comment
+pub(crate) fn comment_text(line: &str) -> Option<&str> {
+    let line = line.trim();
+    let prefix = COMMENT_PREFIXES.iter().find(|prefix| line.starts_with(*prefix))?;
+    let text = line[prefix.len()..].trim_start_matches(['/', '!', '*', '#']).trim_end_matches(['*', '/', '-', '>']).trim();
+    (!text.is_empty()).then_some(text)
+}
+
+fn is_generated(content: &str) -> bool {
+    content.lines().take(GENERATED_HEADER_LINES).any(|line| {
+        let line = line.to_lowercase();
+        GENERATED_MARKERS.iter().any(|marker| line.contains(marker))
+    })
+}
+
+fn is_mostly_blank(content: &str) -> bool {
+    let lines: Vec<&str> = content.lines().collect();
+    let blank = lines.iter().filter(|line| line.trim().is_empty()).count();
+    blank == lines.len() || blank as f64 / lines.len() as f64 > MAX_BLANK_SHARE
+}
+
+// Only comments and blank lines, mentioning a license or copyright
+fn is_license_header(content: &str) -> bool {
+    let all_comments = content.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .all(|line| COMMENT_PREFIXES.iter().any(|prefix| line.starts_with(prefix)) || line.ends_with("*/") || line.ends_with("-->"));
+    let lowercase = content.to_lowercase();
+    all_comments && LICENSE_MARKERS.iter().any(|marker| lowercase.contains(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChunkOptions, Chunker, DirectoryScanner, MemoryFileSystem, ScanOptions};
+    use std::path::Path;
+
+    fn chunk(content: &str) -> Chunk {
+        Chunker::default().chunk_content(Path::new("src/lib.rs"), content).remove(0)
+    }
+
+    fn boilerplate() -> ChunkSieve {
+        ChunkSieve::new(ChunkFilter { drop_boilerplate: true, ..Default::default() })
+    }
+
+    #[test]
+    fn test_boilerplate_drops_license_blank_and_generated_chunks() {
+        let license = "// Copyright 2024 Example Corp.\n// Licensed under the MIT License.\n";
+        let code = "pub fn parse(input: &str) -> Vec<String> {\n    input.split(',').map(String::from).collect()\n}";
+        let chunks = vec![chunk(license), chunk("\n\n\n\n}"), chunk(code)];
+
+        let kept = boilerplate().retain(&format!("{}{}", license, code), chunks);
+        assert_eq!(kept.len(), 1);
+        assert!(kept[0].content.starts_with("pub fn parse"));
+
+        let generated = "// Code generated by protoc. DO NOT EDIT.\npub struct Message {}\n";
+        assert!(boilerplate().retain(generated, vec![chunk(generated)]).is_empty());
+    }
+
+    #[test]
+    fn test_min_density_drops_low_information_chunks() {
+        let braces = "        }\n    }\n}\n});\n";
+        let code = "let total = items.iter().map(|item| item.price * item.quantity).sum();";
+        assert!(information_density(braces) < 0.05);
+        assert!(information_density(code) > 0.3);
+
+        let mut sieve = ChunkSieve::new(ChunkFilter { min_density: Some(0.1), ..Default::default() });
+        let kept = sieve.retain("", vec![chunk(braces), chunk(code)]);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].content, code);
+    }
+
+    #[test]
+    fn test_max_similarity_drops_near_duplicates_across_files() {
+        let original = "pub fn connect(host: &str, port: u16) -> Connection {\n    let address = resolve_address(host, port);\n    Connection::open(address, default_timeout())\n}";
+        let copy = original.replace("-> Connection", "-> Result<Connection>");
+        let other = "pub fn render(template: &Template, context: &Context) -> String {\n    template.expand(context)\n}";
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/a.rs", original);
+        fs.insert("app/b.rs", copy);
+        fs.insert("app/c.rs", other);
+        let result = DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "app").unwrap();
+
+        let filter = ChunkFilter { max_similarity: Some(0.7), ..Default::default() };
+        let chunks = Chunker::new(ChunkOptions { filter, ..Default::default() }).chunk_result(&result, &fs);
+        let paths: Vec<&Path> = chunks.iter().map(|chunk| chunk.path.as_path()).collect();
+        assert_eq!(paths, [Path::new("app/a.rs"), Path::new("app/c.rs")]);
+        assert_eq!(Chunker::default().chunk_result(&result, &fs).len(), 3);
+    }
+}
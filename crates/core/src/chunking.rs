@@ -0,0 +1,408 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use crate::secrets::redact_matches;
+use crate::stability::stability_rank;
+use crate::{
+    content_hash, extract_symbols, find_secrets, ChunkFilter, ChunkSieve, ContentAnalyzer, FileEntry, FileSystem, PathStyle,
+    ScanResult, SecretsMode, Symbol,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chunk {
+    pub id: String,
+    pub path: PathBuf,
+    pub start_line: usize, // 1-based, inclusive
+    pub end_line: usize,
+    pub token_count: usize,
+    pub language: Option<String>,
+    pub content: String,
+}
+
+impl Chunk {
+    // A copy for output with its path, and the path its id starts with,
+    // rendered like the paths of a scan result
+    pub fn with_path_style(&self, style: &PathStyle, root: &Path) -> Chunk {
+        let (id, path) = restyle(&self.id, &self.path, style, root);
+        Chunk { id, path, ..self.clone() }
+    }
+}
+
+fn restyle(id: &str, path: &Path, style: &PathStyle, root: &Path) -> (String, PathBuf) {
+    let rendered = style.render(path, root);
+    let lines = id.strip_prefix(&path.display().to_string()).unwrap_or_default();
+    (format!("{}{}", rendered, lines), PathBuf::from(rendered))
+}
+
+// Where a chunk came from, so retrieval layers can cite the exact source and
+// drop chunks once their file's hash changes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkManifestEntry {
+    pub id: String,
+    pub path: PathBuf,
+    pub start_line: usize,
+    pub end_line: usize,
+    // Last symbol declared at or before the first line, else the first one inside
+    pub symbol: Option<String>,
+    pub tags: Vec<String>,
+    // Hex SHA-256 of the chunk text, and of the whole file
+    pub content_hash: String,
+    pub file_hash: String,
+    // Filled in by `EmbeddingClient::embed_manifest`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<Vec<f32>>,
+}
+
+impl ChunkManifestEntry {
+    fn new(chunk: &Chunk, symbols: &[Symbol], tags: &[String], file_hash: &str) -> Self {
+        let before = symbols.iter().filter(|symbol| symbol.line <= chunk.start_line).max_by_key(|symbol| symbol.line);
+        let inside = || symbols.iter().filter(|symbol| symbol.line <= chunk.end_line).min_by_key(|symbol| symbol.line);
+        Self {
+            id: chunk.id.clone(),
+            path: chunk.path.clone(),
+            start_line: chunk.start_line,
+            end_line: chunk.end_line,
+            symbol: before.or_else(inside).map(|symbol| symbol.name.clone()),
+            tags: tags.to_vec(),
+            content_hash: content_hash(chunk.content.as_bytes()),
+            file_hash: file_hash.to_string(),
+            embedding: None,
+        }
+    }
+}
+
+impl ChunkManifestEntry {
+    pub fn with_path_style(&self, style: &PathStyle, root: &Path) -> ChunkManifestEntry {
+        let (id, path) = restyle(&self.id, &self.path, style, root);
+        ChunkManifestEntry { id, path, ..self.clone() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChunkOptions {
+    pub max_tokens: usize,
+    // Lines repeated from the end of one chunk at the start of the next
+    pub overlap_lines: usize,
+    // Files larger than this are skipped when chunking a scan result
+    pub max_file_size: u64,
+    // Chunks left out of the results as noise
+    pub filter: ChunkFilter,
+    // Chunk stable files first, then active, new and unclassified ones, so
+    // budgets and the duplicate sieve favour settled code
+    pub prefer_stable: bool,
+    // Redact secrets from chunk content, or with `Strict` leave out the
+    // chunks that held them
+    pub secrets: SecretsMode,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        Self {
+            max_tokens: 512,
+            overlap_lines: 2,
+            max_file_size: 1024 * 1024,
+            filter: ChunkFilter::default(),
+            prefer_stable: false,
+            secrets: SecretsMode::Off,
+        }
+    }
+}
+
+// Rough token estimate (~4 characters per token for code and English text)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+// Splits file content into line-aligned chunks under a token budget, preferring
+// to cut where a symbol or markdown heading starts, then at blank lines
+#[derive(Debug, Clone, Default)]
+pub struct Chunker {
+    options: ChunkOptions,
+}
+
+impl Chunker {
+    pub fn new(options: ChunkOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn options(&self) -> &ChunkOptions {
+        &self.options
+    }
+
+    pub fn chunk_content(&self, path: &Path, content: &str) -> Vec<Chunk> {
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+        let language = ContentAnalyzer::new().detect_language(name);
+        self.sieve().retain(content, self.chunk_with_language(path, content, language))
+    }
+
+    // Splits without applying `ChunkOptions::filter`; secrets are still
+    // redacted or dropped as `ChunkOptions::secrets` says
+    pub fn chunk_with_language(&self, path: &Path, content: &str, language: Option<String>) -> Vec<Chunk> {
+        let secrets = if self.options.secrets.is_enabled() { find_secrets(content) } else { Vec::new() };
+        if secrets.is_empty() {
+            return self.split(path, content, language);
+        }
+        let chunks = self.split(path, &redact_matches(content, &secrets), language);
+        if self.options.secrets != SecretsMode::Strict {
+            return chunks;
+        }
+        chunks.into_iter()
+            .filter(|chunk| !secrets.iter().any(|secret| secret.start_line <= chunk.end_line && chunk.start_line <= secret.end_line))
+            .collect()
+    }
+
+    fn split(&self, path: &Path, content: &str, language: Option<String>) -> Vec<Chunk> {
+        let lines: Vec<&str> = content.lines().collect();
+        if lines.is_empty() {
+            return Vec::new();
+        }
+
+        let boundaries = self.boundaries(&lines, content, &language);
+        let max_tokens = self.options.max_tokens.max(1);
+        let mut chunks = Vec::new();
+        let mut start = 0;
+
+        while start < lines.len() {
+            // Grow the chunk line by line until the budget is spent
+            let mut end = start;
+            let mut tokens = estimate_tokens(lines[start]) + 1;
+            while end + 1 < lines.len() {
+                let next = estimate_tokens(lines[end + 1]) + 1;
+                if tokens + next > max_tokens {
+                    break;
+                }
+                tokens += next;
+                end += 1;
+            }
+
+            // Back off to a natural boundary if one falls in the second half
+            if end + 1 < lines.len() {
+                let midpoint = start + (end - start) / 2;
+                if let Some(&boundary) = boundaries.range(midpoint + 1..=end + 1).next_back() {
+                    end = boundary - 1;
+                }
+            }
+
+            chunks.push(self.make_chunk(path, &lines, start, end, &language));
+            if end + 1 >= lines.len() {
+                break;
+            }
+            // Overlap never rewinds past the current chunk's start
+            start = (end + 1).saturating_sub(self.options.overlap_lines).max(start + 1);
+        }
+
+        chunks
+    }
+
+    // Chunks every readable text file in the result, reading content through `fs`
+    pub fn chunk_result(&self, result: &ScanResult, fs: &dyn FileSystem) -> Vec<Chunk> {
+        let mut sieve = self.sieve();
+        self.ordered(result).into_iter().flat_map(|file| self.chunk_file(file, fs, &mut sieve)).collect()
+    }
+
+    // Same chunks as `chunk_result`, with a manifest entry for each
+    pub fn chunk_result_with_manifest(&self, result: &ScanResult, fs: &dyn FileSystem) -> (Vec<Chunk>, Vec<ChunkManifestEntry>) {
+        let mut sieve = self.sieve();
+        let mut chunks = Vec::new();
+        let mut manifest = Vec::new();
+        for file in self.ordered(result) {
+            let Some((content, language)) = self.chunkable_content(file, fs) else {
+                continue;
+            };
+            let file_chunks = sieve.retain(&content, self.chunk_with_language(&file.path, &content, language.clone()));
+            let symbols = extract_symbols(&content, &language);
+            let file_hash = content_hash(content.as_bytes());
+            manifest.extend(file_chunks.iter().map(|chunk| ChunkManifestEntry::new(chunk, &symbols, &file.tags, &file_hash)));
+            chunks.extend(file_chunks);
+        }
+        (chunks, manifest)
+    }
+
+    // Chunks one scanned entry; directories, oversized and unreadable files yield nothing.
+    // Callers chunking many files pass the same sieve so duplicates across files are dropped.
+    pub fn chunk_file(&self, file: &FileEntry, fs: &dyn FileSystem, sieve: &mut ChunkSieve) -> Vec<Chunk> {
+        match self.chunkable_content(file, fs) {
+            Some((content, language)) => sieve.retain(&content, self.chunk_with_language(&file.path, &content, language)),
+            None => Vec::new(),
+        }
+    }
+
+    // Applies `ChunkOptions::filter`; one sieve should see a whole corpus
+    pub fn sieve(&self) -> ChunkSieve {
+        ChunkSieve::new(self.options.filter.clone())
+    }
+
+    fn ordered<'a>(&self, result: &'a ScanResult) -> Vec<&'a FileEntry> {
+        let mut files: Vec<&FileEntry> = result.files.iter().collect();
+        if self.options.prefer_stable {
+            files.sort_by_key(|file| stability_rank(file));
+        }
+        files
+    }
+
+    fn chunkable_content(&self, file: &FileEntry, fs: &dyn FileSystem) -> Option<(String, Option<String>)> {
+        if file.is_dir || file.size > self.options.max_file_size {
+            return None;
+        }
+        let content = fs.read_to_string(&file.path).ok()?;
+        let language = file.enhanced_info.as_ref()
+            .and_then(|info| info.language.clone())
+            .or_else(|| ContentAnalyzer::new().detect_language(&file.name));
+        Some((content, language))
+    }
+
+    // Zero-based line indexes where a new chunk may start
+    fn boundaries(&self, lines: &[&str], content: &str, language: &Option<String>) -> BTreeSet<usize> {
+        let mut boundaries: BTreeSet<usize> = extract_symbols(content, language).iter()
+            .map(|symbol| symbol.line.saturating_sub(1))
+            .collect();
+
+        if language.as_deref() == Some("markdown") {
+            boundaries.extend(lines.iter().enumerate()
+                .filter(|(_, line)| line.starts_with('#'))
+                .map(|(index, _)| index));
+        }
+
+        // Blank lines are weaker boundaries, only used when nothing structural is available
+        if boundaries.is_empty() {
+            boundaries.extend(lines.iter().enumerate()
+                .filter(|(_, line)| line.trim().is_empty())
+                .map(|(index, _)| index + 1));
+        }
+
+        boundaries
+    }
+
+    fn make_chunk(&self, path: &Path, lines: &[&str], start: usize, end: usize, language: &Option<String>) -> Chunk {
+        let content = lines[start..=end].join("\n");
+        Chunk {
+            id: format!("{}#L{}-L{}", path.display(), start + 1, end + 1),
+            path: path.to_path_buf(),
+            start_line: start + 1,
+            end_line: end + 1,
+            token_count: estimate_tokens(&content),
+            language: language.clone(),
+            content,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunker(max_tokens: usize, overlap_lines: usize) -> Chunker {
+        Chunker::new(ChunkOptions { max_tokens, overlap_lines, ..Default::default() })
+    }
+
+    #[test]
+    fn test_small_file_is_single_chunk() {
+        let chunks = Chunker::default().chunk_content(Path::new("src/lib.rs"), "fn a() {}\nfn b() {}\n");
+
+        assert_eq!(chunks.len(), 1);
+        assert_eq!((chunks[0].start_line, chunks[0].end_line), (1, 2));
+        assert_eq!(chunks[0].id, "src/lib.rs#L1-L2");
+        assert_eq!(chunks[0].language.as_deref(), Some("rust"));
+    }
+
+    #[test]
+    fn test_path_style_rewrites_id_and_path() {
+        let chunk = &Chunker::default().chunk_content(Path::new("fx/api/lib.rs"), "fn a() {}\n")[0];
+
+        let relative = chunk.with_path_style(&PathStyle::relative(), Path::new("fx"));
+        assert_eq!(relative.id, "api/lib.rs#L1-L1");
+        assert_eq!(relative.path, PathBuf::from("api/lib.rs"));
+        assert_eq!(relative.content, chunk.content);
+        assert_eq!(chunk.with_path_style(&PathStyle::default(), Path::new("fx")), *chunk);
+    }
+
+    #[test]
+    fn test_splits_at_symbol_boundaries_within_budget() {
+        let content = (0..6)
+            .map(|i| format!("fn function_{}() {{\n    let value = {};\n    value + 1\n}}\n", i, i))
+            .collect::<String>();
+
+        let chunks = chunker(40, 0).chunk_content(Path::new("src/lib.rs"), &content);
+
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(chunk.token_count <= 40, "{:?}", chunk);
+            assert!(chunk.content.starts_with("fn function_"), "{:?}", chunk.content);
+        }
+        let covered: usize = chunks.iter().map(|c| c.end_line - c.start_line + 1).sum();
+        assert_eq!(covered, content.lines().count());
+    }
+
+    #[test]
+    fn test_overlap_repeats_trailing_lines() {
+        let content = (1..=20).map(|i| format!("line number {}", i)).collect::<Vec<_>>().join("\n");
+
+        let chunks = chunker(30, 2).chunk_content(Path::new("notes.txt"), &content);
+
+        assert!(chunks.len() > 1);
+        for pair in chunks.windows(2) {
+            assert_eq!(pair[1].start_line, pair[0].end_line - 1);
+        }
+        assert_eq!(chunks.last().unwrap().end_line, 20);
+    }
+
+    #[test]
+    fn test_manifest_links_chunks_to_symbols_and_hashes() {
+        let mut fs = crate::MemoryFileSystem::new();
+        let content = "use std::fmt;\n\npub fn first() {\n    let a = 1;\n}\n\npub fn second() {\n    let b = 2;\n}\n";
+        fs.insert("app/lib.rs", content);
+        let result = crate::DirectoryScanner::new(crate::ScanOptions::default()).scan_with(&fs, "app").unwrap();
+
+        let (chunks, manifest) = chunker(8, 0).chunk_result_with_manifest(&result, &fs);
+        assert_eq!(chunks, chunker(8, 0).chunk_result(&result, &fs));
+        assert_eq!(manifest.len(), chunks.len());
+        let symbol_at = |line: usize| {
+            manifest.iter().find(|entry| (entry.start_line..=entry.end_line).contains(&line)).unwrap().symbol.as_deref()
+        };
+        assert_eq!((symbol_at(1), symbol_at(4), symbol_at(8)), (None, Some("first"), Some("second")));
+        assert_eq!(manifest[0].file_hash, content_hash(content.as_bytes()));
+        assert_eq!(manifest[0].content_hash, content_hash(chunks[0].content.as_bytes()));
+        assert_eq!(manifest[0].tags, result.find("app/lib.rs").unwrap().tags);
+        assert_eq!(manifest[0].id, chunks[0].id);
+    }
+
+    #[test]
+    fn test_prefer_stable_chunks_settled_files_first() {
+        let mut fs = crate::MemoryFileSystem::new();
+        fs.insert("app/a_new.rs", "pub fn fresh() {}\n");
+        fs.insert("app/b_old.rs", "pub fn settled() {}\n");
+        let options = crate::ScanOptions { enhanced_analysis: true, ..Default::default() };
+        let mut result = crate::DirectoryScanner::new(options).scan_with(&fs, "app").unwrap();
+        let now = std::time::SystemTime::now();
+        let commit = |days: u64| vec![crate::FileCommit {
+            commit: days.to_string(),
+            author: "dev".to_string(),
+            time: now - std::time::Duration::from_secs(days * 86_400),
+            subject: String::new(),
+        }];
+        let history = std::collections::HashMap::from([(PathBuf::from("a_new.rs"), commit(1)), (PathBuf::from("b_old.rs"), commit(400))]);
+        result.assign_stability(&history, now);
+
+        let paths = |chunks: Vec<Chunk>| chunks.into_iter().map(|chunk| chunk.path).collect::<Vec<_>>();
+        let preferring = Chunker::new(ChunkOptions { prefer_stable: true, ..Default::default() });
+        assert_eq!(paths(preferring.chunk_result(&result, &fs)), [PathBuf::from("app/b_old.rs"), PathBuf::from("app/a_new.rs")]);
+        assert_eq!(paths(Chunker::new(ChunkOptions::default()).chunk_result(&result, &fs))[0], PathBuf::from("app/a_new.rs"));
+    }
+
+    #[test]
+    fn test_secrets_are_redacted_or_their_chunks_dropped() {
+        let content = "fn connect() {\n    let password = \"hunter2hunter2\";\n}\n\nfn close() {\n    drop(1);\n}\n";
+        let chunks = |secrets: SecretsMode| Chunker::new(ChunkOptions { max_tokens: 20, overlap_lines: 0, secrets, ..Default::default() })
+            .chunk_content(Path::new("src/db.rs"), content);
+
+        let redacted = chunks(SecretsMode::Redact);
+        assert!(redacted[0].content.contains("let password = \"[REDACTED]\";"));
+        assert!(redacted.iter().all(|chunk| !chunk.content.contains("hunter2")));
+        let strict = chunks(SecretsMode::Strict);
+        assert_eq!(strict.len(), redacted.len() - 1);
+        assert!(strict.iter().all(|chunk| chunk.start_line > 3));
+        assert!(chunks(SecretsMode::Off)[0].content.contains("hunter2hunter2"));
+    }
+}
@@ -0,0 +1,132 @@
+// ANSI styling and icons for the text formatters. A plain palette leaves text
+// untouched, so uncolored output stays byte-for-byte what it always was.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Palette {
+    enabled: bool,
+}
+
+// Complexity scores at or above these are drawn red and yellow respectively
+pub const HIGH_COMPLEXITY: f64 = 7.0;
+pub const MEDIUM_COMPLEXITY: f64 = 4.0;
+
+const RED: &str = "31";
+const GREEN: &str = "32";
+const YELLOW: &str = "33";
+const BLUE: &str = "34";
+const MAGENTA: &str = "35";
+const CYAN: &str = "36";
+
+impl Palette {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    pub fn plain() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.enabled {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    pub fn bold(&self, text: &str) -> String {
+        self.paint("1", text)
+    }
+
+    pub fn dim(&self, text: &str) -> String {
+        self.paint("2", text)
+    }
+
+    pub fn dir(&self, text: &str) -> String {
+        self.paint("1;34", text)
+    }
+
+    // Common tags get fixed colors; others are spread over the palette by name
+    // so the same tag is always drawn the same way
+    pub fn tag(&self, tag: &str) -> String {
+        let code = match tag {
+            "source" => GREEN,
+            "test" => YELLOW,
+            "configuration" => MAGENTA,
+            "documentation" => CYAN,
+            "high-complexity" => RED,
+            _ => [GREEN, YELLOW, BLUE, MAGENTA, CYAN][tag.bytes().map(usize::from).sum::<usize>() % 5],
+        };
+        self.paint(code, tag)
+    }
+
+    pub fn tags(&self, tags: &[String]) -> String {
+        tags.iter().map(|tag| self.tag(tag)).collect::<Vec<_>>().join(", ")
+    }
+
+    // Paints `text` by how complex the file is; files without a score stay plain
+    pub fn by_complexity(&self, complexity: Option<f64>, text: &str) -> String {
+        match complexity {
+            Some(score) if score >= HIGH_COMPLEXITY => self.paint(RED, text),
+            Some(score) if score >= MEDIUM_COMPLEXITY => self.paint(YELLOW, text),
+            _ => text.to_string(),
+        }
+    }
+
+    // The entry marker: bracketed labels when plain, icons when styled
+    pub fn marker(&self, is_dir: bool) -> &'static str {
+        match (self.enabled, is_dir) {
+            (false, true) => "[DIR]",
+            (false, false) => "[FILE]",
+            (true, true) => "📁",
+            (true, false) => "📄",
+        }
+    }
+
+    // Tree connector before an entry; plain output keeps its historical `├──`
+    pub fn branch(&self, last: bool) -> &'static str {
+        if self.enabled && last {
+            "└──"
+        } else {
+            "├──"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_palette_leaves_text_alone() {
+        let palette = Palette::plain();
+
+        assert_eq!(palette.tags(&["source".to_string(), "rust".to_string()]), "source, rust");
+        assert_eq!(palette.by_complexity(Some(9.0), "lib.rs"), "lib.rs");
+        assert_eq!(palette.marker(true), "[DIR]");
+        assert_eq!(palette.branch(true), "├──");
+    }
+
+    #[test]
+    fn test_complexity_thresholds() {
+        let palette = Palette::new(true);
+
+        assert_eq!(palette.by_complexity(Some(HIGH_COMPLEXITY), "a"), "\x1b[31ma\x1b[0m");
+        assert_eq!(palette.by_complexity(Some(MEDIUM_COMPLEXITY), "a"), "\x1b[33ma\x1b[0m");
+        assert_eq!(palette.by_complexity(Some(1.0), "a"), "a");
+        assert_eq!(palette.by_complexity(None, "a"), "a");
+    }
+
+    #[test]
+    fn test_tag_colors_are_stable() {
+        let palette = Palette::new(true);
+
+        assert_eq!(palette.tag("source"), "\x1b[32msource\x1b[0m");
+        assert_eq!(palette.tag("custom"), palette.tag("custom"));
+        assert_eq!(palette.marker(false), "📄");
+        assert_eq!(palette.branch(true), "└──");
+    }
+}
@@ -0,0 +1,137 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+// Keys kept per file, so a large data file such as a translation table does
+// not bloat the result
+const MAX_CONFIG_KEYS: usize = 50;
+
+// Top-level keys, sorted, and nesting depth of a JSON, YAML or TOML file. Depth counts
+// the levels of mappings and lists: `{"a": 1}` is 1, `{"a": {"b": [1]}}` is 3.
+// None for other languages and content that does not parse.
+pub(crate) fn config_structure(content: &str, language: &Option<String>) -> Option<(Vec<String>, usize)> {
+    let (mut keys, depth) = match language.as_deref()? {
+        "json" => {
+            let value: Value = serde_json::from_str(content).ok()?;
+            (top_level_keys(&value), depth(&value))
+        }
+        "yaml" => {
+            let mut keys = Vec::new();
+            let mut deepest = 0;
+            // Every document of a multi-document file such as a Kubernetes manifest
+            for document in serde_yaml::Deserializer::from_str(content) {
+                let value = Value::deserialize(document).ok()?;
+                for key in top_level_keys(&value) {
+                    if !keys.contains(&key) {
+                        keys.push(key);
+                    }
+                }
+                deepest = deepest.max(depth(&value));
+            }
+            (keys, deepest)
+        }
+        "toml" => toml_structure(content),
+        _ => return None,
+    };
+    keys.sort();
+    keys.truncate(MAX_CONFIG_KEYS);
+    Some((keys, depth))
+}
+
+fn top_level_keys(value: &Value) -> Vec<String> {
+    match value {
+        Value::Object(map) => map.keys().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn depth(value: &Value) -> usize {
+    match value {
+        Value::Object(map) => 1 + map.values().map(depth).max().unwrap_or(0),
+        Value::Array(items) => 1 + items.iter().map(depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+// TOML is read line by line: table headers and dotted keys give the nesting,
+// and an inline table or array value adds a level
+fn toml_structure(content: &str) -> (Vec<String>, usize) {
+    let mut keys: Vec<String> = Vec::new();
+    let mut table: Vec<String> = Vec::new();
+    let mut deepest = 0;
+    let mut add_key = |key: &str| {
+        if !keys.iter().any(|known| known == key) {
+            keys.push(key.to_string());
+        }
+    };
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.trim_start_matches('[');
+            let header = header.split(']').next().unwrap_or_default();
+            table = dotted(header);
+            let Some(first) = table.first() else { continue };
+            add_key(first);
+            // An array of tables is a list of mappings
+            let array = line.starts_with("[[") as usize;
+            deepest = deepest.max(table.len() + array);
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let key = dotted(key);
+        let Some(first) = key.first() else { continue };
+        if table.is_empty() {
+            add_key(first);
+        }
+        let nested = matches!(value.trim_start().chars().next(), Some('{' | '[')) as usize;
+        deepest = deepest.max(table.len() + key.len() + nested);
+    }
+    (keys, deepest)
+}
+
+fn dotted(key: &str) -> Vec<String> {
+    key.split('.')
+        .map(|part| part.trim().trim_matches(['"', '\'']).to_string())
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn structure(content: &str, language: &str) -> Option<(Vec<String>, usize)> {
+        config_structure(content, &Some(language.to_string()))
+    }
+
+    #[test]
+    fn test_json_and_yaml_structure() {
+        let json = r#"{"name": "app", "scripts": {"build": "tsc"}, "files": [{"path": "dist"}]}"#;
+        assert_eq!(structure(json, "json"), Some((vec!["files".into(), "name".into(), "scripts".into()], 3)));
+        assert_eq!(structure("[1, 2]", "json"), Some((Vec::new(), 1)));
+        assert_eq!(structure("{not json", "json"), None);
+
+        let yaml = "apiVersion: v1\nkind: Service\nspec:\n  ports:\n    - port: 80\n---\napiVersion: apps/v1\nkind: Deployment\nmetadata:\n  name: web\n";
+        assert_eq!(structure(yaml, "yaml"), Some((vec!["apiVersion".into(), "kind".into(), "metadata".into(), "spec".into()], 4)));
+    }
+
+    #[test]
+    fn test_toml_structure() {
+        let toml = "# Build settings\nedition = \"2021\"\nprofile.release.lto = true\n\n[package]\nname = \"app\"\nauthors = [\"a\"]\n\n[dependencies.serde]\nversion = \"1\"\nfeatures = [\"derive\"]\n\n[[bin]]\nname = \"app\"\n";
+        let (keys, depth) = toml_structure(toml);
+        assert_eq!(keys, ["edition", "profile", "package", "dependencies", "bin"]);
+        // dependencies.serde.features holds a list
+        assert_eq!(depth, 4);
+    }
+
+    #[test]
+    fn test_other_languages_and_key_limit() {
+        assert_eq!(structure("fn main() {}", "rust"), None);
+        assert_eq!(config_structure("{}", &None), None);
+        let many: Vec<String> = (0..80).map(|index| format!("\"k{}\": {}", index, index)).collect();
+        let (keys, depth) = structure(&format!("{{{}}}", many.join(", ")), "json").unwrap();
+        assert_eq!(keys.len(), MAX_CONFIG_KEYS);
+        assert_eq!(depth, 1);
+    }
+}
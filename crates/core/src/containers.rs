@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+
+use crate::entry_points::is_dockerfile;
+use crate::{FileEntry, FileSystem};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ContainerKind {
+    // Dockerfile, Dockerfile.*, *.dockerfile or Containerfile
+    Dockerfile,
+    // docker-compose.yml, compose.yaml and their overrides
+    Compose,
+    // .devcontainer/devcontainer.json or .devcontainer.json
+    Devcontainer,
+    Dockerignore,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContainerArtifact {
+    pub path: PathBuf,
+    pub kind: ContainerKind,
+    // Images built from or run, e.g. "rust:1.79-slim"; a Dockerfile's FROM of
+    // one of its own stages is not listed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<String>,
+    // As declared: "8080", "53/udp", or a compose mapping such as "80:8080"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<String>,
+    // Named build stages of a multi-stage Dockerfile, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stages: Vec<String>,
+    // Services of a compose file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub services: Vec<String>,
+}
+
+// Dockerfiles, compose files, dev container configs and .dockerignore files in
+// the scan, with what each builds, runs and exposes. Files that do not parse
+// are still listed, without details. Sorted by path.
+pub(crate) fn detect_containers(files: &[FileEntry], fs: &dyn FileSystem) -> Vec<ContainerArtifact> {
+    let mut artifacts = Vec::new();
+    for file in files.iter().filter(|file| !file.is_dir) {
+        let Some(kind) = container_kind(file) else { continue };
+        let mut artifact = ContainerArtifact {
+            path: file.path.clone(),
+            kind,
+            images: Vec::new(),
+            ports: Vec::new(),
+            stages: Vec::new(),
+            services: Vec::new(),
+        };
+        if kind != ContainerKind::Dockerignore {
+            if let Ok(content) = fs.read_to_string(&file.path) {
+                match kind {
+                    ContainerKind::Dockerfile => read_dockerfile(&content, &mut artifact),
+                    ContainerKind::Compose => read_compose(&content, &mut artifact),
+                    ContainerKind::Devcontainer => read_devcontainer(&content, &mut artifact),
+                    ContainerKind::Dockerignore => {}
+                }
+            }
+        }
+        artifacts.push(artifact);
+    }
+    artifacts.sort_by(|a, b| a.path.cmp(&b.path));
+    artifacts
+}
+
+fn container_kind(file: &FileEntry) -> Option<ContainerKind> {
+    let name = file.name.as_str();
+    let in_devcontainer_dir = file.path.parent()
+        .and_then(|dir| dir.file_name())
+        .is_some_and(|dir| dir == ".devcontainer");
+    if is_dockerfile(name) || name == "Containerfile" {
+        Some(ContainerKind::Dockerfile)
+    } else if is_compose_file(name) {
+        Some(ContainerKind::Compose)
+    } else if name == ".devcontainer.json" || name == "devcontainer.json" && in_devcontainer_dir {
+        Some(ContainerKind::Devcontainer)
+    } else if name.ends_with(".dockerignore") {
+        Some(ContainerKind::Dockerignore)
+    } else {
+        None
+    }
+}
+
+fn is_compose_file(name: &str) -> bool {
+    let Some(stem) = name.strip_suffix(".yml").or_else(|| name.strip_suffix(".yaml")) else { return false };
+    ["docker-compose", "compose"].iter()
+        .any(|base| stem == *base || stem.strip_prefix(base).is_some_and(|rest| rest.starts_with('.')))
+}
+
+fn read_dockerfile(content: &str, artifact: &mut ContainerArtifact) {
+    // Instructions continue onto the next line after a trailing backslash
+    let joined = content.lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .replace("\\\n", " ");
+    for line in joined.lines().map(str::trim) {
+        let Some((instruction, arguments)) = line.split_once(char::is_whitespace) else { continue };
+        let words: Vec<&str> = arguments.split_whitespace().filter(|word| !word.starts_with("--")).collect();
+        if instruction.eq_ignore_ascii_case("FROM") {
+            let Some(image) = words.first() else { continue };
+            let is_stage = artifact.stages.iter().any(|stage| stage.eq_ignore_ascii_case(image));
+            if !is_stage && !artifact.images.iter().any(|known| known == image) {
+                artifact.images.push(image.to_string());
+            }
+            if let [_, keyword, stage, ..] = words.as_slice() {
+                if keyword.eq_ignore_ascii_case("AS") {
+                    artifact.stages.push(stage.to_string());
+                }
+            }
+        } else if instruction.eq_ignore_ascii_case("EXPOSE") {
+            artifact.ports.extend(words.iter().map(|port| port.to_string()));
+        }
+    }
+}
+
+fn read_compose(content: &str, artifact: &mut ContainerArtifact) {
+    let Ok(compose) = serde_yaml::from_str::<Value>(content) else { return };
+    let Some(services) = compose.get("services").and_then(Value::as_object) else { return };
+    let mut names: Vec<&String> = services.keys().collect();
+    names.sort();
+    for name in names {
+        let service = &services[name];
+        artifact.services.push(name.clone());
+        if let Some(image) = service.get("image").and_then(Value::as_str) {
+            if !artifact.images.iter().any(|known| known == image) {
+                artifact.images.push(image.to_string());
+            }
+        }
+        for port in service.get("ports").and_then(Value::as_array).into_iter().flatten() {
+            artifact.ports.extend(compose_port(port));
+        }
+    }
+}
+
+// "80:8080", 8080, or the long form {target: 8080, published: 80}
+fn compose_port(port: &Value) -> Option<String> {
+    match port {
+        Value::String(port) => Some(port.clone()),
+        Value::Number(port) => Some(port.to_string()),
+        Value::Object(port) => {
+            let text = |key: &str| port.get(key).map(|value| value.as_str().map_or_else(|| value.to_string(), str::to_string));
+            let target = text("target")?;
+            Some(match text("published") {
+                Some(published) => format!("{}:{}", published, target),
+                None => target,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn read_devcontainer(content: &str, artifact: &mut ContainerArtifact) {
+    // devcontainer.json allows comments, which serde_json does not
+    let json: String = content.lines()
+        .filter(|line| !line.trim_start().starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let Ok(config) = serde_json::from_str::<Value>(&json) else { return };
+    if let Some(image) = config.get("image").and_then(Value::as_str) {
+        artifact.images.push(image.to_string());
+    }
+    if let Some(service) = config.get("service").and_then(Value::as_str) {
+        artifact.services.push(service.to_string());
+    }
+    for port in config.get("forwardPorts").and_then(Value::as_array).into_iter().flatten() {
+        artifact.ports.extend(compose_port(port));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+    use std::path::Path;
+
+    fn artifact(kind: ContainerKind) -> ContainerArtifact {
+        ContainerArtifact { path: PathBuf::new(), kind, images: Vec::new(), ports: Vec::new(), stages: Vec::new(), services: Vec::new() }
+    }
+
+    #[test]
+    fn test_reads_multi_stage_dockerfile() {
+        let dockerfile = "# syntax=docker/dockerfile:1\nFROM --platform=$BUILDPLATFORM rust:1.79 AS build\nRUN cargo build --release\n\nFROM build as test\nRUN cargo test\n\nFROM gcr.io/distroless/cc\nCOPY --from=build /app/target/release/app /app\nEXPOSE 8080 \\\n    9090/udp\nENTRYPOINT [\"/app\"]\n";
+        let mut dockerfile_artifact = artifact(ContainerKind::Dockerfile);
+        read_dockerfile(dockerfile, &mut dockerfile_artifact);
+        assert_eq!(dockerfile_artifact.images, ["rust:1.79", "gcr.io/distroless/cc"]);
+        assert_eq!(dockerfile_artifact.stages, ["build", "test"]);
+        assert_eq!(dockerfile_artifact.ports, ["8080", "9090/udp"]);
+    }
+
+    #[test]
+    fn test_reads_compose_and_devcontainer() {
+        let compose = "services:\n  web:\n    build: .\n    ports:\n      - \"80:8080\"\n      - target: 443\n        published: 8443\n  db:\n    image: postgres:16\n    ports: [5432]\n";
+        let mut compose_artifact = artifact(ContainerKind::Compose);
+        read_compose(compose, &mut compose_artifact);
+        assert_eq!(compose_artifact.services, ["db", "web"]);
+        assert_eq!(compose_artifact.images, ["postgres:16"]);
+        assert_eq!(compose_artifact.ports, ["5432", "80:8080", "8443:443"]);
+
+        let devcontainer = "{\n  // Rust toolchain\n  \"image\": \"mcr.microsoft.com/devcontainers/rust:1\",\n  \"forwardPorts\": [3000]\n}\n";
+        let mut devcontainer_artifact = artifact(ContainerKind::Devcontainer);
+        read_devcontainer(devcontainer, &mut devcontainer_artifact);
+        assert_eq!(devcontainer_artifact.images, ["mcr.microsoft.com/devcontainers/rust:1"]);
+        assert_eq!(devcontainer_artifact.ports, ["3000"]);
+    }
+
+    #[test]
+    fn test_scan_lists_container_files() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/Dockerfile", "FROM node:20\nEXPOSE 3000\n");
+        fs.insert("app/docker-compose.override.yml", "services:\n  app:\n    image: app:dev\n");
+        fs.insert("app/.dockerignore", "node_modules\n");
+        fs.insert("app/.devcontainer/devcontainer.json", "{\"image\": \"node:20\"}");
+        fs.insert("app/config/compose.txt", "services: {}\n");
+        fs.insert("app/devcontainer.json", "{}");
+        let options = ScanOptions { include_hidden: true, ..Default::default() };
+        let result = DirectoryScanner::new(options).scan_with(&fs, "app").unwrap();
+
+        let found: Vec<(&Path, ContainerKind)> = result.containers.iter().map(|artifact| (artifact.path.as_path(), artifact.kind)).collect();
+        assert_eq!(found, [
+            (Path::new("app/.devcontainer/devcontainer.json"), ContainerKind::Devcontainer),
+            (Path::new("app/.dockerignore"), ContainerKind::Dockerignore),
+            (Path::new("app/Dockerfile"), ContainerKind::Dockerfile),
+            (Path::new("app/docker-compose.override.yml"), ContainerKind::Compose),
+        ]);
+        assert_eq!(result.containers[2].ports, ["3000"]);
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::dir_stats::top_level;
+use crate::ranking::import_edges;
+use crate::{DirStats, FileEntry, HIGH_COMPLEXITY};
+
+// Tag for files many others import that are also hard to follow, so a change
+// to them is both likely to break things and easy to get wrong
+pub const GOD_MODULE_TAG: &str = "god-module";
+// Importers a file needs before it counts as a god module
+pub const GOD_MODULE_FAN_IN: usize = 10;
+
+// Sets fan-in (files importing this one, afferent coupling) and fan-out (files
+// this one imports, efferent coupling) on every analyzed file from the import
+// graph, and tags god modules: a fan-in of GOD_MODULE_FAN_IN or more with a
+// complexity of HIGH_COMPLEXITY or more
+pub(crate) fn assign_coupling(files: &mut [FileEntry]) {
+    let mut fan_in = vec![0; files.len()];
+    let mut fan_out = vec![0; files.len()];
+    for (importer, imported) in import_edges(files) {
+        fan_out[importer] += 1;
+        fan_in[imported] += 1;
+    }
+    for (position, file) in files.iter_mut().enumerate() {
+        let Some(info) = file.enhanced_info.as_mut().filter(|_| !file.is_dir) else { continue };
+        info.fan_in = Some(fan_in[position]);
+        info.fan_out = Some(fan_out[position]);
+        let complex = info.complexity_score.is_some_and(|complexity| complexity >= HIGH_COMPLEXITY);
+        if fan_in[position] >= GOD_MODULE_FAN_IN && complex && !file.tags.iter().any(|tag| tag == GOD_MODULE_TAG) {
+            file.tags.push(GOD_MODULE_TAG.to_string());
+        }
+    }
+}
+
+// Fan-in and fan-out of each top-level directory: files elsewhere importing
+// one of its files, and files elsewhere its files import
+pub(crate) fn assign_directory_coupling(root: &Path, files: &[FileEntry], directories: &mut BTreeMap<String, DirStats>) {
+    let mut importers: BTreeMap<String, BTreeSet<usize>> = BTreeMap::new();
+    let mut imported: BTreeMap<String, BTreeSet<usize>> = BTreeMap::new();
+    for (importer, target) in import_edges(files) {
+        let from = top_level(root, &files[importer].path);
+        let to = top_level(root, &files[target].path);
+        if from != to {
+            imported.entry(from).or_default().insert(target);
+            importers.entry(to).or_default().insert(importer);
+        }
+    }
+    for (dir, stats) in directories.iter_mut() {
+        stats.fan_in = importers.get(dir).map_or(0, BTreeSet::len);
+        stats.fan_out = imported.get(dir).map_or(0, BTreeSet::len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+
+    fn scan(fs: &MemoryFileSystem) -> crate::ScanResult {
+        let options = ScanOptions { enhanced_analysis: true, ..Default::default() };
+        DirectoryScanner::new(options).scan_with(fs, "app").unwrap()
+    }
+
+    fn coupling(result: &crate::ScanResult, path: &str) -> (Option<usize>, Option<usize>) {
+        let info = result.find(path).unwrap().enhanced_info.as_ref().unwrap();
+        (info.fan_in, info.fan_out)
+    }
+
+    #[test]
+    fn test_counts_importers_and_imports() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/src/main.rs", "use crate::config;\nuse crate::store;\nfn main() {}\n");
+        fs.insert("app/src/store.rs", "use crate::config;\npub fn save() {}\n");
+        fs.insert("app/src/config.rs", "pub fn load() {}\n");
+        let result = scan(&fs);
+
+        assert_eq!(coupling(&result, "app/src/config.rs"), (Some(2), Some(0)));
+        assert_eq!(coupling(&result, "app/src/store.rs"), (Some(1), Some(1)));
+        assert_eq!(coupling(&result, "app/src/main.rs"), (Some(0), Some(2)));
+    }
+
+    #[test]
+    fn test_directory_coupling_counts_files_across_directories() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/api/routes.rs", "use crate::core::models;\nuse crate::api::auth;\n");
+        fs.insert("app/api/auth.rs", "use crate::core::models;\n");
+        fs.insert("app/core/models.rs", "pub struct User;\n");
+        let result = scan(&fs);
+
+        let directories = &result.stats.directories;
+        assert_eq!((directories["api"].fan_in, directories["api"].fan_out), (0, 1));
+        assert_eq!((directories["core"].fan_in, directories["core"].fan_out), (2, 0));
+    }
+
+    #[test]
+    fn test_tags_complex_widely_imported_files_as_god_modules() {
+        let mut fs = MemoryFileSystem::new();
+        let branches: String = (0..40).map(|index| format!("    if x == {} && y {{ for i in 0..x {{ while y {{ if i > 2 {{ return; }} }} }} }}\n", index)).collect();
+        fs.insert("app/src/engine.rs", format!("pub fn run(x: u32, y: bool) {{\n{}}}\n", branches));
+        fs.insert("app/src/util.rs", "pub fn noop() {}\n");
+        for index in 0..GOD_MODULE_FAN_IN {
+            fs.insert(format!("app/src/user{}.rs", index), "use crate::engine;\nuse crate::util;\n");
+        }
+        let result = scan(&fs);
+
+        let engine = result.find("app/src/engine.rs").unwrap();
+        assert!(engine.enhanced_info.as_ref().unwrap().complexity_score.unwrap() >= HIGH_COMPLEXITY);
+        assert!(engine.tags.iter().any(|tag| tag == GOD_MODULE_TAG));
+        assert!(!result.find("app/src/util.rs").unwrap().tags.iter().any(|tag| tag == GOD_MODULE_TAG));
+    }
+}
@@ -0,0 +1,367 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::{FileEntry, FileSystem};
+
+// Schema files are hand-written; anything larger is a data dump
+const MAX_SCHEMA_SIZE: u64 = 1024 * 1024;
+// Directory names holding migrations for Rails, Django, Diesel, sqlx, Flyway,
+// Laravel, Knex and golang-migrate
+const MIGRATION_DIRS: &[&str] = &["migrations", "migration", "migrate"];
+const MIGRATION_EXTENSIONS: &[&str] = &["sql", "py", "rb", "php", "js", "ts", "mjs", "cjs", "go"];
+// Lines after a Django CreateModel( that may hold its name=
+const NAME_LOOKAHEAD: usize = 200;
+// Leading words of CREATE TABLE body items that are constraints, not columns
+const CONSTRAINT_WORDS: &[&str] = &["CONSTRAINT", "PRIMARY", "FOREIGN", "UNIQUE", "CHECK", "KEY", "INDEX", "EXCLUDE", "LIKE"];
+// ORM calls creating or dropping a table in Rails, Alembic, Knex, Laravel and
+// Django migrations, and whether each creates
+const ORM_CALLS: &[(&str, bool)] = &[
+    ("create_table", true),
+    ("createTable(", true),
+    ("Schema::create(", true),
+    ("CreateModel(", true),
+    ("drop_table", false),
+    ("dropTable(", false),
+    ("dropTableIfExists(", false),
+    ("Schema::drop(", false),
+    ("Schema::dropIfExists(", false),
+    ("DeleteModel(", false),
+];
+
+// Persistent entities the codebase defines, from SQL files and migrations
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseSchema {
+    // Tables that exist once every migration has run, sorted by name
+    pub tables: Vec<SchemaTable>,
+    // In the order they apply
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub migrations: Vec<Migration>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaTable {
+    // As written, without quotes; Django migrations give the model name
+    pub name: String,
+    // Column names as created; empty for tables created through an ORM
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<String>,
+    // The file creating the table and the 1-based line of the statement
+    pub path: PathBuf,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Migration {
+    pub path: PathBuf,
+    // Leading number or timestamp of the name, e.g. "20240101120000", or "1.2"
+    // for Flyway's V1_2__; none for unversioned files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    // The rest of the name, e.g. "create_users"
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub creates: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub drops: Vec<String>,
+}
+
+impl DatabaseSchema {
+    // Tables of both, keeping this schema's definition of a table both
+    // create, and migrations of both in order
+    pub(crate) fn merge(mut self, other: Self) -> Self {
+        for table in other.tables {
+            if !self.tables.iter().any(|known| known.name.eq_ignore_ascii_case(&table.name)) {
+                self.tables.push(table);
+            }
+        }
+        for migration in other.migrations {
+            if !self.migrations.iter().any(|known| known.path == migration.path) {
+                self.migrations.push(migration);
+            }
+        }
+        self.tables.sort_by(|a, b| a.name.cmp(&b.name));
+        self.migrations.sort_by(migration_order);
+        self
+    }
+}
+
+// The schema defined by the scanned migrations, applied in order, then by any
+// other SQL files such as a schema dump. Down migrations are skipped. None when
+// the scan has neither.
+pub(crate) fn detect_schema(root: &Path, files: &[FileEntry], fs: &dyn FileSystem) -> Option<DatabaseSchema> {
+    let mut migrations = Vec::new();
+    let mut others = Vec::new();
+    for file in files.iter().filter(|file| !file.is_dir && file.size <= MAX_SCHEMA_SIZE) {
+        let relative = file.path.strip_prefix(root).unwrap_or(&file.path);
+        let extension = file.path.extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+        if is_migration(relative, extension) {
+            if let Some((version, name)) = migration_name(&file.path) {
+                migrations.push((Migration { path: file.path.clone(), version, name, creates: Vec::new(), drops: Vec::new() }, extension == "sql"));
+            }
+        } else if extension == "sql" {
+            others.push(file.path.clone());
+        }
+    }
+    if migrations.is_empty() && others.is_empty() {
+        return None;
+    }
+    migrations.sort_by(|(a, _), (b, _)| migration_order(a, b));
+    others.sort();
+
+    let mut tables: Vec<SchemaTable> = Vec::new();
+    let mut apply = |path: &Path, statements: Vec<Statement>| {
+        for statement in statements {
+            let position = tables.iter().position(|table| table.name.eq_ignore_ascii_case(&statement.table));
+            match (statement.creates, position) {
+                (true, None) => tables.push(SchemaTable {
+                    name: statement.table,
+                    columns: statement.columns,
+                    path: path.to_path_buf(),
+                    line: statement.line,
+                }),
+                (false, Some(position)) => {
+                    tables.remove(position);
+                }
+                _ => {}
+            }
+        }
+    };
+    for (migration, sql) in &mut migrations {
+        let Ok(content) = fs.read_to_string(&migration.path) else { continue };
+        let statements = if *sql { sql_statements(&content) } else { orm_statements(&content) };
+        for statement in &statements {
+            let names = if statement.creates { &mut migration.creates } else { &mut migration.drops };
+            names.push(statement.table.clone());
+        }
+        apply(&migration.path, statements);
+    }
+    for path in &others {
+        if let Ok(content) = fs.read_to_string(path) {
+            apply(path, sql_statements(&content));
+        }
+    }
+    tables.sort_by(|a, b| a.name.cmp(&b.name));
+    Some(DatabaseSchema { tables, migrations: migrations.into_iter().map(|(migration, _)| migration).collect() })
+}
+
+fn is_migration(relative: &Path, extension: &str) -> bool {
+    let Some(dirs) = relative.parent() else { return false };
+    let names: Vec<&str> = dirs.iter().filter_map(|name| name.to_str()).collect();
+    let in_migrations = names.iter().any(|name| MIGRATION_DIRS.contains(name))
+        || names.contains(&"alembic") && names.contains(&"versions");
+    let file_name = relative.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    in_migrations && MIGRATION_EXTENSIONS.contains(&extension) && file_name != "__init__.py"
+}
+
+// Version and name of a migration file, or None for a down migration. Diesel's
+// `<version>_<name>/up.sql` takes both from the directory.
+fn migration_name(path: &Path) -> Option<(Option<String>, String)> {
+    let stem = path.file_stem()?.to_str()?;
+    if stem == "down" || stem.ends_with(".down") {
+        return None;
+    }
+    let stem = stem.trim_end_matches(".up");
+    let label = if stem == "up" { path.parent()?.file_name()?.to_str()? } else { stem };
+
+    // Flyway: V1_2__create_users
+    if let Some((version, name)) = label.strip_prefix('V').and_then(|rest| rest.split_once("__")) {
+        if version.starts_with(|c: char| c.is_ascii_digit()) {
+            return Some((Some(version.replace('_', ".")), name.to_string()));
+        }
+    }
+    let version_end = label.find(|c: char| !(c.is_ascii_digit() || c == '-')).unwrap_or(label.len());
+    let version = label[..version_end].trim_end_matches('-');
+    let name = label[version_end..].trim_start_matches(['_', '-']);
+    if version.is_empty() {
+        Some((None, label.to_string()))
+    } else {
+        Some((Some(version.to_string()), if name.is_empty() { label.to_string() } else { name.to_string() }))
+    }
+}
+
+// Versioned migrations by version, compared number by number, then the
+// unversioned; ties by path
+fn migration_order(a: &Migration, b: &Migration) -> std::cmp::Ordering {
+    let key = |migration: &Migration| {
+        migration.version.as_deref().map(|version| {
+            version.split(|c: char| !c.is_ascii_digit())
+                .filter(|part| !part.is_empty())
+                .map(|part| part.parse::<u64>().unwrap_or(u64::MAX))
+                .collect::<Vec<_>>()
+        })
+    };
+    match (key(a), key(b)) {
+        (Some(a_key), Some(b_key)) => a_key.cmp(&b_key),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    }
+    .then_with(|| a.path.cmp(&b.path))
+}
+
+struct Statement {
+    table: String,
+    creates: bool,
+    columns: Vec<String>,
+    line: usize,
+}
+
+// CREATE TABLE and DROP TABLE statements; temporary tables are left out
+fn sql_statements(content: &str) -> Vec<Statement> {
+    let code: String = content.lines()
+        .map(|line| line.find("--").map_or(line, |comment| &line[..comment]))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut statements = Vec::new();
+    let mut offset = 0;
+    for text in code.split(';') {
+        let start = offset + (text.len() - text.trim_start().len());
+        offset += text.len() + 1;
+        let line = code[..start].matches('\n').count() + 1;
+        let head = text.split('(').next().unwrap_or_default();
+        let words: Vec<String> = head.split_whitespace().map(str::to_uppercase).collect();
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        let creates = match words.as_slice() {
+            ["CREATE", "TABLE", ..] | ["CREATE", "UNLOGGED", "TABLE", ..] => true,
+            ["DROP", "TABLE", ..] => false,
+            _ => continue,
+        };
+        let names: Vec<String> = head.split_whitespace()
+            .skip_while(|word| !word.eq_ignore_ascii_case("TABLE"))
+            .skip(1)
+            .filter(|word| !["IF", "NOT", "EXISTS"].contains(&word.to_uppercase().as_str()))
+            .take_while(|word| !["CASCADE", "RESTRICT", "AS"].contains(&word.to_uppercase().as_str()))
+            .flat_map(|word| word.split(','))
+            .map(unquote)
+            .filter(|name| !name.is_empty())
+            .collect();
+        let columns = if creates { columns(text) } else { Vec::new() };
+        for name in names.into_iter().take(if creates { 1 } else { usize::MAX }) {
+            statements.push(Statement { table: name, creates, columns: columns.clone(), line });
+        }
+    }
+    statements
+}
+
+// Column names in the parenthesized body of a CREATE TABLE
+fn columns(statement: &str) -> Vec<String> {
+    let Some(open) = statement.find('(') else { return Vec::new() };
+    let mut depth = 0;
+    let mut item = String::new();
+    let mut items = Vec::new();
+    for c in statement[open + 1..].chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => break,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(std::mem::take(&mut item));
+                continue;
+            }
+            _ => {}
+        }
+        item.push(c);
+    }
+    items.push(item);
+    items.iter()
+        .filter_map(|item| item.split_whitespace().next())
+        .filter(|word| !CONSTRAINT_WORDS.contains(&word.to_uppercase().as_str()))
+        .map(unquote)
+        .collect()
+}
+
+fn unquote(name: &str) -> String {
+    name.split('.')
+        .map(|part| part.trim_matches(['"', '`', '[', ']', '\'']))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+// Tables created and dropped through ORM migration calls, in file order
+fn orm_statements(content: &str) -> Vec<Statement> {
+    let mut found: Vec<(usize, Statement)> = Vec::new();
+    for (call, creates) in ORM_CALLS {
+        for (position, _) in content.match_indices(call) {
+            let mut rest = &content[position + call.len()..];
+            rest = &rest[..rest.len().min(NAME_LOOKAHEAD)];
+            rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '(' || c == ':');
+            if let Some(argument) = rest.strip_prefix("name") {
+                rest = argument.trim_start().trim_start_matches('=').trim_start();
+            }
+            let name = match rest.chars().next() {
+                Some(quote @ ('"' | '\'')) => rest[1..].split(quote).next().unwrap_or_default(),
+                _ => rest.split(|c: char| !(c.is_alphanumeric() || c == '_')).next().unwrap_or_default(),
+            };
+            if !name.is_empty() {
+                let line = content[..position].matches('\n').count() + 1;
+                found.push((position, Statement { table: name.to_string(), creates: *creates, columns: Vec::new(), line }));
+            }
+        }
+    }
+    found.sort_by_key(|(position, _)| *position);
+    found.into_iter().map(|(_, statement)| statement).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+
+    fn names(tables: &[SchemaTable]) -> Vec<&str> {
+        tables.iter().map(|table| table.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_sql_statements() {
+        let sql = "-- users of the app\nCREATE TABLE IF NOT EXISTS \"users\" (\n  id SERIAL PRIMARY KEY,\n  email TEXT NOT NULL, -- unique\n  created_at TIMESTAMP DEFAULT now(),\n  CONSTRAINT email_unique UNIQUE (email)\n);\n\ncreate temporary table scratch (x int);\nCREATE TABLE app.orders(id int, total NUMERIC(10, 2));\nDROP TABLE IF EXISTS legacy, old_sessions CASCADE;\n";
+        let statements = sql_statements(sql);
+        let summary: Vec<(&str, bool, usize)> = statements.iter().map(|s| (s.table.as_str(), s.creates, s.line)).collect();
+        assert_eq!(summary, [("users", true, 2), ("app.orders", true, 10), ("legacy", false, 11), ("old_sessions", false, 11)]);
+        assert_eq!(statements[0].columns, ["id", "email", "created_at"]);
+        assert_eq!(statements[1].columns, ["id", "total"]);
+    }
+
+    #[test]
+    fn test_migration_names_and_orm_calls() {
+        let name = |path: &str| migration_name(Path::new(path));
+        assert_eq!(name("db/migrate/20240101120000_create_users.rb"), Some((Some("20240101120000".to_string()), "create_users".to_string())));
+        assert_eq!(name("src/main/resources/db/migration/V1_2__add_orders.sql"), Some((Some("1.2".to_string()), "add_orders".to_string())));
+        assert_eq!(name("migrations/2024-01-01-000000_create_posts/up.sql"), Some((Some("2024-01-01-000000".to_string()), "create_posts".to_string())));
+        assert_eq!(name("migrations/000002_index.up.sql"), Some((Some("000002".to_string()), "index".to_string())));
+        assert_eq!(name("migrations/000002_index.down.sql"), None);
+        assert_eq!(name("app/migrations/seed.py"), Some((None, "seed".to_string())));
+
+        let django = "class Migration(migrations.Migration):\n    operations = [\n        migrations.CreateModel(\n            name='Author',\n            fields=[],\n        ),\n        migrations.DeleteModel(name=\"Draft\"),\n    ]\n";
+        let statements = orm_statements(django);
+        assert_eq!(statements.iter().map(|s| (s.table.as_str(), s.creates, s.line)).collect::<Vec<_>>(), [("Author", true, 3), ("Draft", false, 7)]);
+        let rails = "class CreateUsers < ActiveRecord::Migration[7.0]\n  def change\n    create_table :users do |t|\n    end\n  end\nend\n";
+        assert_eq!(orm_statements(rails)[0].table, "users");
+        assert_eq!(orm_statements("op.drop_table('accounts')")[0].table, "accounts");
+    }
+
+    #[test]
+    fn test_schema_applies_migrations_in_order() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/db/migrations/010_drop_drafts.sql", "DROP TABLE drafts;\n");
+        fs.insert("app/db/migrations/002_create_posts.sql", "CREATE TABLE drafts (id int);\nCREATE TABLE posts (id int, title text);\n");
+        fs.insert("app/db/migrations/001_create_users.sql", "CREATE TABLE users (id int);\n");
+        fs.insert("app/db/migrations/001_create_users.down.sql", "DROP TABLE users;\n");
+        fs.insert("app/db/schema.sql", "CREATE TABLE users (id int, email text);\nCREATE TABLE audit_log (id int);\n");
+        fs.insert("app/src/main.rs", "fn main() {}\n");
+        let result = DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "app").unwrap();
+
+        let schema = result.schema.unwrap();
+        let order: Vec<&str> = schema.migrations.iter().map(|migration| migration.name.as_str()).collect();
+        assert_eq!(order, ["create_users", "create_posts", "drop_drafts"]);
+        assert_eq!(schema.migrations[1].creates, ["drafts", "posts"]);
+        assert_eq!(schema.migrations[2].drops, ["drafts"]);
+        assert_eq!(names(&schema.tables), ["audit_log", "posts", "users"]);
+        // The migration defines users; the schema dump only adds what it lacks
+        assert_eq!(schema.tables[2].path, Path::new("app/db/migrations/001_create_users.sql"));
+        assert_eq!(schema.tables[2].columns, ["id"]);
+
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("plain/src/lib.rs", "");
+        assert!(DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "plain").unwrap().schema.is_none());
+    }
+}
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::FileEntry;
+
+// Hex SHA-256 of a file's bytes, as stored in `FileEntry::content_hash`
+pub fn content_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// Files with identical content; keeping one copy frees `wasted_bytes`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    pub hash: String,
+    pub size: u64,
+    pub paths: Vec<PathBuf>,
+    pub wasted_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateReport {
+    // Most wasted bytes first
+    pub groups: Vec<DuplicateGroup>,
+    // Copies beyond the first of each group
+    pub duplicate_files: usize,
+    pub wasted_bytes: u64,
+}
+
+impl DuplicateReport {
+    // Groups files by `content_hash`, so only entries from scans with
+    // `hash_contents` take part. Empty files are not reported.
+    pub fn from_files<'a>(files: impl IntoIterator<Item = &'a FileEntry>) -> Self {
+        let mut by_hash: HashMap<&str, (u64, Vec<PathBuf>)> = HashMap::new();
+        for file in files.into_iter().filter(|file| !file.is_dir && file.size > 0) {
+            if let Some(hash) = &file.content_hash {
+                by_hash.entry(hash).or_insert_with(|| (file.size, Vec::new())).1.push(file.path.clone());
+            }
+        }
+
+        let mut groups: Vec<DuplicateGroup> = by_hash.into_iter()
+            .filter(|(_, (_, paths))| paths.len() > 1)
+            .map(|(hash, (size, mut paths))| {
+                paths.sort();
+                DuplicateGroup { hash: hash.to_string(), size, wasted_bytes: size * (paths.len() as u64 - 1), paths }
+            })
+            .collect();
+        groups.sort_by(|a, b| b.wasted_bytes.cmp(&a.wasted_bytes).then_with(|| a.paths.cmp(&b.paths)));
+
+        Self {
+            duplicate_files: groups.iter().map(|group| group.paths.len() - 1).sum(),
+            wasted_bytes: groups.iter().map(|group| group.wasted_bytes).sum(),
+            groups,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+
+    fn scan(fs: &MemoryFileSystem) -> crate::ScanResult {
+        let options = ScanOptions { hash_contents: true, ..Default::default() };
+        DirectoryScanner::new(options).scan_with(fs, "app").unwrap()
+    }
+
+    #[test]
+    fn test_content_hash_is_sha256() {
+        assert_eq!(content_hash(b"abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
+
+    #[test]
+    fn test_groups_identical_files_by_wasted_bytes() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/a/logo.txt", "x".repeat(100));
+        fs.insert("app/b/logo.txt", "x".repeat(100));
+        fs.insert("app/c/logo.txt", "x".repeat(100));
+        fs.insert("app/one.rs", "fn f() {}");
+        fs.insert("app/two.rs", "fn f() {}");
+        fs.insert("app/unique.rs", "fn g() {}");
+        fs.insert("app/empty1", "");
+        fs.insert("app/empty2", "");
+
+        let report = DuplicateReport::from_files(&scan(&fs).files);
+        assert_eq!(report.groups.len(), 2);
+        assert_eq!(report.groups[0].paths, ["app/a/logo.txt", "app/b/logo.txt", "app/c/logo.txt"].map(PathBuf::from));
+        assert_eq!((report.groups[0].wasted_bytes, report.groups[1].wasted_bytes), (200, 9));
+        assert_eq!((report.duplicate_files, report.wasted_bytes), (3, 209));
+    }
+
+    #[test]
+    fn test_unhashed_scans_report_nothing() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/one.rs", "fn f() {}");
+        fs.insert("app/two.rs", "fn f() {}");
+
+        let result = DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "app").unwrap();
+        assert!(result.find("app/one.rs").unwrap().content_hash.is_none());
+        assert_eq!(DuplicateReport::from_files(&result.files), DuplicateReport::default());
+        assert_eq!(DuplicateReport::from_files(&scan(&fs).files).groups.len(), 1);
+    }
+}
@@ -0,0 +1,262 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::api_diff::{api_change, semver_bump, ApiChange, SemverBump};
+use crate::{FileEntry, ScanResult};
+
+// Per-file differences between two scans of the same project. Paths are
+// relative to each scan's root so snapshots taken at different locations compare.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub size_before: u64,
+    pub size_after: u64,
+    pub lines_before: Option<usize>,
+    pub lines_after: Option<usize>,
+    pub complexity_before: Option<f64>,
+    pub complexity_after: Option<f64>,
+    pub tags_added: Vec<String>,
+    pub tags_removed: Vec<String>,
+}
+
+impl FileChange {
+    pub fn complexity_delta(&self) -> Option<f64> {
+        Some(self.complexity_after? - self.complexity_before?)
+    }
+
+    pub fn size_delta(&self) -> i64 {
+        self.size_after as i64 - self.size_before as i64
+    }
+}
+
+// A file whose content reappears unchanged under another path
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileMove {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScanDiff {
+    pub added: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+    // Removed and added paths paired up by content hash
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub moved: Vec<FileMove>,
+    pub modified: Vec<FileChange>,
+    pub unchanged: usize,
+    // Public items added, removed or changed, by file; needs enhanced scans
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_changes: Vec<ApiChange>,
+}
+
+impl ScanDiff {
+    // Files count as modified when their size, line count, complexity, tags or
+    // exports differ; modification times alone are ignored since checkouts reset them
+    pub fn between(old: &ScanResult, new: &ScanResult) -> Self {
+        let before = files_by_relative_path(old);
+        let after = files_by_relative_path(new);
+        let mut diff = Self::default();
+
+        for (path, old_file) in &before {
+            match after.get(path) {
+                None => diff.removed.push(path.clone()),
+                Some(new_file) if is_modified(old_file, new_file) => {
+                    diff.modified.push(file_change(path, old_file, new_file));
+                }
+                Some(_) => diff.unchanged += 1,
+            }
+        }
+        diff.added = after.keys().filter(|path| !before.contains_key(*path)).cloned().collect();
+        diff.pair_moves(&before, &after);
+
+        // Moved files have the same content, so the same API
+        let moved: HashSet<&PathBuf> = diff.moved.iter().flat_map(|file_move| [&file_move.from, &file_move.to]).collect();
+        let paths: BTreeSet<&PathBuf> = before.keys().chain(after.keys()).filter(|path| !moved.contains(path)).collect();
+        diff.api_changes = paths.into_iter()
+            .filter_map(|path| api_change(path.clone(), before.get(path).copied(), after.get(path).copied()))
+            .collect();
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty() && self.modified.is_empty() && self.api_changes.is_empty()
+    }
+
+    // A poor man's semver check: major when a public item was removed or its
+    // declaration changed, minor when items were only added
+    pub fn semver_bump(&self) -> SemverBump {
+        semver_bump(&self.api_changes)
+    }
+
+    // Only scans with `hash_contents` on both sides have hashes to compare.
+    // When several removed files share the content, one with the same name is
+    // preferred as the source.
+    fn pair_moves(&mut self, before: &BTreeMap<PathBuf, &FileEntry>, after: &BTreeMap<PathBuf, &FileEntry>) {
+        let hash = |file: &FileEntry| file.content_hash.clone().filter(|_| file.size > 0);
+        let mut removed_by_hash: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        for path in &self.removed {
+            if let Some(hash) = hash(before[path]) {
+                removed_by_hash.entry(hash).or_default().push(path.clone());
+            }
+        }
+
+        let mut moved = Vec::new();
+        self.added.retain(|path| {
+            let sources = hash(after[path]).and_then(|hash| removed_by_hash.get_mut(&hash));
+            let Some(sources) = sources.filter(|sources| !sources.is_empty()) else {
+                return true;
+            };
+            let index = sources.iter().position(|from| from.file_name() == path.file_name()).unwrap_or(0);
+            moved.push(FileMove { from: sources.remove(index), to: path.clone() });
+            false
+        });
+        let sources: HashSet<&PathBuf> = moved.iter().map(|file_move| &file_move.from).collect();
+        self.removed.retain(|path| !sources.contains(path));
+        self.moved = moved;
+    }
+}
+
+fn relative_path(root: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(root).unwrap_or(path).to_path_buf()
+}
+
+fn files_by_relative_path(result: &ScanResult) -> BTreeMap<PathBuf, &FileEntry> {
+    result.files.iter()
+        .filter(|file| !file.is_dir)
+        .map(|file| (relative_path(&result.root_path, &file.path), file))
+        .collect()
+}
+
+fn is_modified(old: &FileEntry, new: &FileEntry) -> bool {
+    let info = |file: &FileEntry| file.enhanced_info.as_ref()
+        .map(|info| (info.line_count, info.complexity_score, info.exports.clone()));
+    old.size != new.size || old.tags != new.tags || info(old) != info(new)
+}
+
+fn file_change(path: &Path, old: &FileEntry, new: &FileEntry) -> FileChange {
+    let old_tags: BTreeSet<&String> = old.tags.iter().collect();
+    let new_tags: BTreeSet<&String> = new.tags.iter().collect();
+    let lines = |file: &FileEntry| file.enhanced_info.as_ref().and_then(|info| info.line_count);
+    let complexity = |file: &FileEntry| file.enhanced_info.as_ref().and_then(|info| info.complexity_score);
+
+    FileChange {
+        path: path.to_path_buf(),
+        size_before: old.size,
+        size_after: new.size,
+        lines_before: lines(old),
+        lines_after: lines(new),
+        complexity_before: complexity(old),
+        complexity_after: complexity(new),
+        tags_added: new_tags.difference(&old_tags).map(|tag| tag.to_string()).collect(),
+        tags_removed: old_tags.difference(&new_tags).map(|tag| tag.to_string()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScanStats;
+    use std::time::SystemTime;
+
+    fn entry(root: &str, path: &str, size: u64, tags: &[&str], complexity: Option<f64>) -> FileEntry {
+        FileEntry::test(Path::new(root).join(path))
+            .with_size(size)
+            .with_modified(SystemTime::now())
+            .with_tags(tags)
+            .with_info(|info| info.complexity_score = complexity)
+    }
+
+    fn result(root: &str, files: Vec<FileEntry>) -> ScanResult {
+        ScanResult {
+            schema_version: crate::SCHEMA_VERSION,
+            root_path: PathBuf::from(root),
+            files,
+            stats: ScanStats {
+                total_files: 0,
+                total_dirs: 0,
+                total_size: 0,
+                scan_duration_ms: 0,
+                files_per_second: 0.0,
+                directories: Default::default(),
+                distribution: Default::default(),
+                cache: None,
+                sampling: None,
+                resources: None,
+            },
+            roots: Vec::new(),
+            errors: Vec::new(),
+            findings: Vec::new(),
+            test_coverage_structure: None,
+            entry_points: Vec::new(),
+            endpoints: Vec::new(),
+            schema: None,
+            containers: Vec::new(),
+            nested_repositories: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_added_removed_and_unchanged() {
+        let old = result("v1", vec![entry("v1", "src/a.rs", 10, &["source"], None), entry("v1", "src/b.rs", 5, &[], None)]);
+        let new = result("v2", vec![entry("v2", "src/a.rs", 10, &["source"], None), entry("v2", "src/c.rs", 7, &[], None)]);
+
+        let diff = ScanDiff::between(&old, &new);
+
+        assert_eq!(diff.added, vec![PathBuf::from("src/c.rs")]);
+        assert_eq!(diff.removed, vec![PathBuf::from("src/b.rs")]);
+        assert!(diff.modified.is_empty());
+        assert_eq!(diff.unchanged, 1);
+    }
+
+    #[test]
+    fn test_modified_reports_deltas_and_tags() {
+        let old = result("p", vec![entry("p", "lib.rs", 100, &["source"], Some(2.0))]);
+        let new = result("p", vec![entry("p", "lib.rs", 160, &["source", "high-complexity"], Some(6.5))]);
+
+        let diff = ScanDiff::between(&old, &new);
+
+        let change = &diff.modified[0];
+        assert_eq!(change.path, PathBuf::from("lib.rs"));
+        assert_eq!(change.size_delta(), 60);
+        assert_eq!(change.complexity_delta(), Some(4.5));
+        assert_eq!(change.tags_added, vec!["high-complexity"]);
+        assert!(change.tags_removed.is_empty());
+    }
+
+    #[test]
+    fn test_moves_pair_by_content_hash() {
+        let hashed = |root: &str, path: &str, hash: &str| entry(root, path, 10, &[], None).with_content_hash(hash);
+        let old = result("p", vec![
+            hashed("p", "src/a.rs", "aaa"),
+            hashed("p", "src/util/mod.rs", "ccc"),
+            hashed("p", "tests/mod.rs", "ccc"),
+            hashed("p", "old.rs", "ddd"),
+        ]);
+        let new = result("p", vec![
+            hashed("p", "core/a.rs", "aaa"),
+            hashed("p", "core/util.rs", "ccc"),
+            hashed("p", "core/mod.rs", "ccc"),
+            hashed("p", "new.rs", "eee"),
+        ]);
+
+        let diff = ScanDiff::between(&old, &new);
+
+        let moves: Vec<(&str, &str)> = diff.moved.iter().map(|m| (m.from.to_str().unwrap(), m.to.to_str().unwrap())).collect();
+        assert_eq!(moves, [("src/a.rs", "core/a.rs"), ("src/util/mod.rs", "core/mod.rs"), ("tests/mod.rs", "core/util.rs")]);
+        assert_eq!(diff.added, [PathBuf::from("new.rs")]);
+        assert_eq!(diff.removed, [PathBuf::from("old.rs")]);
+    }
+
+    #[test]
+    fn test_identical_scans_have_empty_diff() {
+        let scan = result("p", vec![entry("p", "a.rs", 1, &[], Some(1.0))]);
+
+        let diff = ScanDiff::between(&scan, &scan.clone());
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.unchanged, 1);
+    }
+}
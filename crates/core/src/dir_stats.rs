@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+
+use crate::coupling::assign_directory_coupling;
+use crate::{FileEntry, ScanIssue, PARTIAL_TAG};
+
+// Totals for one top-level directory of a scan; files directly in the root
+// are grouped under "."
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DirStats {
+    pub files: usize,
+    pub size: u64,
+    // Lines of the files enhanced analysis counted
+    pub lines: usize,
+    // Mean over files with a complexity score
+    pub average_complexity: Option<f64>,
+    pub errors: usize,
+    // Files in other directories importing one of ours, and files in other
+    // directories ours import; from enhanced analysis
+    #[serde(default)]
+    pub fan_in: usize,
+    #[serde(default)]
+    pub fan_out: usize,
+    // Set when a directory under it could not be read, so the totals miss its entries
+    #[serde(default)]
+    pub partial: bool,
+}
+
+// Built up one entry at a time, so streamed scans get the same totals
+#[derive(Debug, Default)]
+pub(crate) struct DirStatsBuilder {
+    totals: BTreeMap<String, (DirStats, f64, usize)>,
+}
+
+impl DirStatsBuilder {
+    pub(crate) fn add_file(&mut self, root: &Path, file: &FileEntry) {
+        if file.is_dir {
+            if file.tags.iter().any(|tag| tag == PARTIAL_TAG) {
+                self.add_partial(root, &file.path);
+            }
+            return;
+        }
+        let (stats, complexity_sum, scored) = self.totals.entry(top_level(root, &file.path)).or_default();
+        stats.files += 1;
+        stats.size += file.size;
+        let info = file.enhanced_info.as_ref();
+        stats.lines += info.and_then(|info| info.line_count).unwrap_or(0);
+        if let Some(complexity) = info.and_then(|info| info.complexity_score) {
+            *complexity_sum += complexity;
+            *scored += 1;
+        }
+    }
+
+    pub(crate) fn add_issue(&mut self, root: &Path, issue: &ScanIssue) {
+        if let Some(path) = &issue.path {
+            self.totals.entry(top_level(root, path)).or_default().0.errors += 1;
+        }
+    }
+
+    // A directory the scan could not read completely
+    pub(crate) fn add_partial(&mut self, root: &Path, dir: &Path) {
+        let relative = dir.strip_prefix(root).unwrap_or(dir);
+        let top = match relative.components().find(|component| matches!(component, Component::Normal(_))) {
+            Some(component) => component.as_os_str().to_string_lossy().to_string(),
+            None => ".".to_string(),
+        };
+        self.totals.entry(top).or_default().0.partial = true;
+    }
+
+    pub(crate) fn build(self) -> BTreeMap<String, DirStats> {
+        self.totals.into_iter()
+            .map(|(dir, (mut stats, complexity_sum, scored))| {
+                stats.average_complexity = (scored > 0).then(|| complexity_sum / scored as f64);
+                (dir, stats)
+            })
+            .collect()
+    }
+}
+
+// Totals for entries and issues that were all collected already, e.g. merged results
+pub(crate) fn directory_stats(root: &Path, files: &[FileEntry], errors: &[ScanIssue]) -> BTreeMap<String, DirStats> {
+    let mut builder = DirStatsBuilder::default();
+    for file in files {
+        builder.add_file(root, file);
+    }
+    for issue in errors {
+        builder.add_issue(root, issue);
+    }
+    let mut directories = builder.build();
+    assign_directory_coupling(root, files, &mut directories);
+    directories
+}
+
+pub(crate) fn top_level(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let mut components = relative.components().filter(|component| matches!(component, Component::Normal(_)));
+    match (components.next(), components.next()) {
+        (Some(dir), Some(_)) => dir.as_os_str().to_string_lossy().to_string(),
+        _ => ".".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+
+    fn file(path: &str, size: u64, lines: Option<usize>, complexity: Option<f64>) -> FileEntry {
+        FileEntry::test(path).with_size(size).with_info(|info| {
+            info.line_count = lines;
+            info.complexity_score = complexity;
+        })
+    }
+
+    #[test]
+    fn test_totals_per_top_level_directory() {
+        let files = [
+            file("proj/src/lib.rs", 100, Some(10), Some(2.0)),
+            file("proj/src/deep/mod.rs", 50, Some(5), Some(4.0)),
+            file("proj/docs/guide.md", 30, None, None),
+            file("proj/Cargo.toml", 20, Some(3), None),
+        ];
+        let errors = [ScanIssue::analysis_failed(Path::new("proj/docs/guide.md"), &crate::ScanError::InvalidConfig {
+            message: "bad".to_string(),
+        })];
+
+        let stats = directory_stats(Path::new("proj"), &files, &errors);
+        assert_eq!(stats.keys().collect::<Vec<_>>(), [".", "docs", "src"]);
+        assert_eq!(stats["src"], DirStats { files: 2, size: 150, lines: 15, average_complexity: Some(3.0), errors: 0, fan_in: 0, fan_out: 0, partial: false });
+        assert_eq!((stats["docs"].errors, stats["docs"].average_complexity), (1, None));
+        assert_eq!(stats["."].files, 1);
+    }
+
+    #[test]
+    fn test_directories_are_not_counted_as_files() {
+        let dir = file("proj/src", 0, None, None).with_dir(true);
+
+        assert!(directory_stats(Path::new("proj"), &[dir], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_scans_report_directory_stats() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/src/main.rs", "fn main() {}\n");
+        fs.insert("app/tests/it.rs", "#[test]\nfn it() {}\n");
+
+        let options = ScanOptions { enhanced_analysis: true, ..Default::default() };
+        let result = DirectoryScanner::new(options).scan_with(&fs, "app").unwrap();
+        let directories = &result.stats.directories;
+        assert_eq!((directories["src"].files, directories["src"].lines), (1, 1));
+        assert_eq!(directories["tests"].lines, 2);
+    }
+}
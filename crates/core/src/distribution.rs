@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::FileEntry;
+
+const SIZE_BUCKETS: [(u64, &str); 5] = [
+    (1024, "< 1KB"),
+    (10 * 1024, "1-10KB"),
+    (100 * 1024, "10-100KB"),
+    (1024 * 1024, "100KB-1MB"),
+    (u64::MAX, ">= 1MB"),
+];
+const COMPLEXITY_BUCKETS: [(f64, &str); 6] = [
+    (2.0, "0-2"),
+    (4.0, "2-4"),
+    (6.0, "4-6"),
+    (8.0, "6-8"),
+    (10.0, "8-10"),
+    (f64::INFINITY, ">= 10"),
+];
+
+// Extension key for files without one
+pub(crate) const NO_EXTENSION: &str = "(none)";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistogramBucket {
+    pub label: String,
+    pub count: usize,
+}
+
+// How the scanned files spread over sizes, extensions, depths and complexity,
+// enough to chart a repo without the file list. Directories are not counted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Distribution {
+    // Fixed buckets from "< 1KB" to ">= 1MB"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sizes: Vec<HistogramBucket>,
+    // Lowercased extension to file count
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extensions: BTreeMap<String, usize>,
+    // Depth below the root (1 for files directly in it) to file count
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub depths: BTreeMap<usize, usize>,
+    // Buckets of two points up to ">= 10"; empty unless files were scored
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub complexity: Vec<HistogramBucket>,
+}
+
+impl Distribution {
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty() && self.extensions.is_empty() && self.depths.is_empty() && self.complexity.is_empty()
+    }
+
+    pub(crate) fn from_files<'a>(root: &Path, files: impl IntoIterator<Item = &'a FileEntry>) -> Self {
+        let mut builder = DistributionBuilder::default();
+        for file in files {
+            builder.add_file(root, file);
+        }
+        builder.build()
+    }
+}
+
+// Counted one entry at a time so streamed scans get the same figures
+#[derive(Debug, Default)]
+pub(crate) struct DistributionBuilder {
+    files: usize,
+    sizes: [usize; SIZE_BUCKETS.len()],
+    extensions: BTreeMap<String, usize>,
+    depths: BTreeMap<usize, usize>,
+    complexity: [usize; COMPLEXITY_BUCKETS.len()],
+    scored: usize,
+}
+
+impl DistributionBuilder {
+    pub(crate) fn add_file(&mut self, root: &Path, file: &FileEntry) {
+        if file.is_dir {
+            return;
+        }
+        self.files += 1;
+        self.sizes[SIZE_BUCKETS.iter().position(|(limit, _)| file.size < *limit).unwrap_or(SIZE_BUCKETS.len() - 1)] += 1;
+        let extension = file.path.extension().map_or_else(|| NO_EXTENSION.to_string(), |ext| ext.to_string_lossy().to_lowercase());
+        *self.extensions.entry(extension).or_default() += 1;
+        let depth = file.path.strip_prefix(root).unwrap_or(&file.path).components().count();
+        *self.depths.entry(depth).or_default() += 1;
+        if let Some(score) = file.enhanced_info.as_ref().and_then(|info| info.complexity_score) {
+            self.complexity[COMPLEXITY_BUCKETS.iter().position(|(limit, _)| score < *limit).unwrap_or(COMPLEXITY_BUCKETS.len() - 1)] += 1;
+            self.scored += 1;
+        }
+    }
+
+    pub(crate) fn build(self) -> Distribution {
+        Distribution {
+            sizes: if self.files > 0 { buckets(&SIZE_BUCKETS, &self.sizes) } else { Vec::new() },
+            extensions: self.extensions,
+            depths: self.depths,
+            complexity: if self.scored > 0 { buckets(&COMPLEXITY_BUCKETS, &self.complexity) } else { Vec::new() },
+        }
+    }
+}
+
+fn buckets<T>(limits: &[(T, &str)], counts: &[usize]) -> Vec<HistogramBucket> {
+    limits.iter().zip(counts).map(|((_, label), count)| HistogramBucket { label: label.to_string(), count: *count }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+
+    fn file(path: &str, size: u64, complexity: Option<f64>) -> FileEntry {
+        FileEntry::test(path).with_size(size).with_complexity(complexity)
+    }
+
+    fn counts(buckets: &[HistogramBucket]) -> Vec<usize> {
+        buckets.iter().map(|bucket| bucket.count).collect()
+    }
+
+    #[test]
+    fn test_buckets_sizes_extensions_and_depths() {
+        let files = [
+            file("proj/src/lib.rs", 2048, Some(3.0)),
+            file("proj/src/net/http.RS", 200_000, Some(12.0)),
+            file("proj/Makefile", 10, None),
+        ];
+
+        let distribution = Distribution::from_files(Path::new("proj"), &files);
+        assert_eq!(distribution.sizes[0].label, "< 1KB");
+        assert_eq!(counts(&distribution.sizes), [1, 1, 0, 1, 0]);
+        assert_eq!(distribution.extensions, BTreeMap::from([(NO_EXTENSION.to_string(), 1), ("rs".to_string(), 2)]));
+        assert_eq!(distribution.depths, BTreeMap::from([(1, 1), (2, 1), (3, 1)]));
+        assert_eq!(counts(&distribution.complexity), [0, 1, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_empty_without_files_or_scores() {
+        let dir = file("proj/src", 0, None).with_dir(true);
+        assert!(Distribution::from_files(Path::new("proj"), [&dir]).is_empty());
+
+        let distribution = Distribution::from_files(Path::new("proj"), [&file("proj/a.txt", 1, None)]);
+        assert!(distribution.complexity.is_empty());
+        assert!(!distribution.is_empty());
+    }
+
+    #[test]
+    fn test_scans_report_distribution() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/main.rs", "fn main() {}");
+        fs.insert("app/docs/guide.md", "# Guide");
+
+        let result = DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "app").unwrap();
+        let distribution = &result.stats.distribution;
+        assert_eq!(counts(&distribution.sizes)[0], 2);
+        assert_eq!(distribution.depths, BTreeMap::from([(1, 1), (2, 1)]));
+        assert_eq!(distribution.extensions["md"], 1);
+    }
+}
@@ -0,0 +1,308 @@
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::{Chunk, ChunkManifestEntry, ScanError};
+
+// Request and response shapes spoken by the embedding endpoint
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingApi {
+    // `{model, input}` in, `{data: [{index, embedding}]}` out; also served by
+    // llama.cpp, vLLM and LM Studio
+    #[default]
+    OpenAi,
+    // Ollama's /api/embed: `{model, input}` in, `{embeddings}` out
+    Ollama,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingOptions {
+    pub endpoint: String,
+    pub api: EmbeddingApi,
+    pub model: String,
+    // Sent as a bearer token; never written out with the options
+    #[serde(skip_serializing)]
+    pub api_key: Option<String>,
+    // Chunks sent per request
+    pub batch_size: usize,
+    // Attempts after the first for timeouts, 429 and 5xx responses
+    pub max_retries: u32,
+    // Wait before the first retry, doubled for each one after unless the
+    // server sends Retry-After
+    pub retry_delay_ms: u64,
+    pub requests_per_minute: Option<u32>,
+    pub timeout_secs: u64,
+}
+
+impl Default for EmbeddingOptions {
+    fn default() -> Self {
+        Self {
+            endpoint: "https://api.openai.com/v1/embeddings".to_string(),
+            api: EmbeddingApi::OpenAi,
+            model: "text-embedding-3-small".to_string(),
+            api_key: None,
+            batch_size: 64,
+            max_retries: 3,
+            retry_delay_ms: 500,
+            requests_per_minute: None,
+            timeout_secs: 60,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct OpenAiResponse {
+    data: Vec<OpenAiEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[derive(Deserialize)]
+struct OllamaResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+// Sends text to an embedding endpoint in batches, retrying transient failures
+// and spacing requests to stay under the rate limit
+pub struct EmbeddingClient {
+    options: EmbeddingOptions,
+    client: Client,
+    // Earliest time the next request may be sent
+    next_request: Mutex<Instant>,
+}
+
+impl EmbeddingClient {
+    pub fn new(options: EmbeddingOptions) -> Result<Self, ScanError> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(options.timeout_secs.max(1)))
+            .build()
+            .map_err(|e| embedding_error(format!("failed to create HTTP client: {}", e)))?;
+        Ok(Self { options, client, next_request: Mutex::new(Instant::now()) })
+    }
+
+    pub fn options(&self) -> &EmbeddingOptions {
+        &self.options
+    }
+
+    // One vector per text, in the same order
+    pub async fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>, ScanError> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.options.batch_size.max(1)) {
+            vectors.extend(self.embed_batch(batch).await?);
+        }
+        Ok(vectors)
+    }
+
+    // Embeds each chunk and stores the vector on the manifest entry with the same ID
+    pub async fn embed_manifest(&self, chunks: &[Chunk], manifest: &mut [ChunkManifestEntry]) -> Result<(), ScanError> {
+        let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
+        let mut vectors: HashMap<&str, Vec<f32>> = chunks.iter()
+            .map(|chunk| chunk.id.as_str())
+            .zip(self.embed(&texts).await?)
+            .collect();
+        for entry in manifest {
+            entry.embedding = vectors.remove(entry.id.as_str());
+        }
+        Ok(())
+    }
+
+    async fn embed_batch(&self, batch: &[&str]) -> Result<Vec<Vec<f32>>, ScanError> {
+        let body = serde_json::json!({ "model": self.options.model, "input": batch });
+        let mut attempt = 0;
+        loop {
+            self.wait_for_rate_limit().await;
+            let mut request = self.client.post(&self.options.endpoint).json(&body);
+            if let Some(key) = &self.options.api_key {
+                request = request.bearer_auth(key);
+            }
+
+            let (retry_after, failure) = match request.send().await {
+                Ok(response) if response.status().is_success() => {
+                    let text = response.text().await
+                        .map_err(|e| embedding_error(format!("failed to read response: {}", e)))?;
+                    return parse_response(self.options.api, &text, batch.len());
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let retry_after = retry_after(response.headers());
+                    let text = response.text().await.unwrap_or_default();
+                    let failure = embedding_error(format!("{} returned {}: {}", self.options.endpoint, status, text.trim()));
+                    if !is_retryable(status) {
+                        return Err(failure);
+                    }
+                    (retry_after, failure)
+                }
+                Err(e) => (None, embedding_error(format!("request to {} failed: {}", self.options.endpoint, e))),
+            };
+
+            if attempt >= self.options.max_retries {
+                return Err(failure);
+            }
+            tracing::debug!("retrying embedding request: {}", failure);
+            tokio::time::sleep(backoff(self.options.retry_delay_ms, attempt, retry_after)).await;
+            attempt += 1;
+        }
+    }
+
+    async fn wait_for_rate_limit(&self) {
+        let Some(per_minute) = self.options.requests_per_minute.filter(|limit| *limit > 0) else {
+            return;
+        };
+        let interval = Duration::from_secs(60) / per_minute;
+        // Reserve the next slot, then sleep without holding the lock
+        let wait = {
+            let mut next = self.next_request.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let now = Instant::now();
+            let start = (*next).max(now);
+            *next = start + interval;
+            start - now
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+fn parse_response(api: EmbeddingApi, body: &str, expected: usize) -> Result<Vec<Vec<f32>>, ScanError> {
+    let invalid = |e: serde_json::Error| embedding_error(format!("unexpected response: {}", e));
+    let vectors = match api {
+        EmbeddingApi::OpenAi => {
+            let mut data = serde_json::from_str::<OpenAiResponse>(body).map_err(invalid)?.data;
+            data.sort_by_key(|embedding| embedding.index);
+            data.into_iter().map(|embedding| embedding.embedding).collect()
+        }
+        EmbeddingApi::Ollama => serde_json::from_str::<OllamaResponse>(body).map_err(invalid)?.embeddings,
+    };
+    if vectors.len() != expected {
+        return Err(embedding_error(format!("expected {} embeddings, got {}", expected, vectors.len())));
+    }
+    Ok(vectors)
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::REQUEST_TIMEOUT || status.is_server_error()
+}
+
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = headers.get(RETRY_AFTER)?.to_str().ok()?.trim().parse::<u64>().ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+fn backoff(retry_delay_ms: u64, attempt: u32, retry_after: Option<Duration>) -> Duration {
+    retry_after.unwrap_or_else(|| Duration::from_millis(retry_delay_ms.saturating_mul(1 << attempt.min(16))))
+}
+
+fn embedding_error(message: String) -> ScanError {
+    ScanError::Embedding { message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::path::PathBuf;
+
+    // Answers one request per canned response and returns the request bodies
+    fn serve(responses: Vec<(u16, &'static str)>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1/embeddings", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            responses.into_iter().map(|(status, body)| {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                let mut request = vec![0; length];
+                reader.read_exact(&mut request).unwrap();
+                let response = format!(
+                    "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, body.len(), body,
+                );
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+                String::from_utf8(request).unwrap()
+            }).collect()
+        });
+        (url, handle)
+    }
+
+    fn chunk(id: &str, content: &str) -> Chunk {
+        Chunk {
+            id: id.to_string(),
+            path: PathBuf::from("src/lib.rs"),
+            start_line: 1,
+            end_line: 1,
+            token_count: 1,
+            language: None,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parses_openai_and_ollama_responses() {
+        let openai = r#"{"data": [{"index": 1, "embedding": [0.5]}, {"index": 0, "embedding": [0.25, 1.0]}]}"#;
+        assert_eq!(parse_response(EmbeddingApi::OpenAi, openai, 2).unwrap(), vec![vec![0.25, 1.0], vec![0.5]]);
+
+        let ollama = r#"{"model": "nomic-embed-text", "embeddings": [[1.0, 2.0]]}"#;
+        assert_eq!(parse_response(EmbeddingApi::Ollama, ollama, 1).unwrap(), vec![vec![1.0, 2.0]]);
+        assert!(matches!(parse_response(EmbeddingApi::Ollama, ollama, 2), Err(ScanError::Embedding { .. })));
+    }
+
+    #[test]
+    fn test_backoff_doubles_unless_server_says_when() {
+        assert_eq!(backoff(100, 0, None), Duration::from_millis(100));
+        assert_eq!(backoff(100, 3, None), Duration::from_millis(800));
+        assert_eq!(backoff(100, 3, Some(Duration::from_secs(2))), Duration::from_secs(2));
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS) && is_retryable(StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable(StatusCode::UNAUTHORIZED));
+    }
+
+    #[tokio::test]
+    async fn test_embeds_manifest_in_batches_with_retries() {
+        let (endpoint, server) = serve(vec![
+            (429, r#"{"error": "slow down"}"#),
+            (200, r#"{"data": [{"index": 0, "embedding": [1.0]}, {"index": 1, "embedding": [2.0]}]}"#),
+            (200, r#"{"data": [{"index": 0, "embedding": [3.0]}]}"#),
+        ]);
+        let options = EmbeddingOptions { endpoint, batch_size: 2, retry_delay_ms: 1, requests_per_minute: Some(6000), ..Default::default() };
+        let client = EmbeddingClient::new(options).unwrap();
+
+        let chunks = [chunk("a#L1-L1", "alpha"), chunk("b#L1-L1", "beta"), chunk("c#L1-L1", "gamma")];
+        let mut manifest: Vec<ChunkManifestEntry> = chunks.iter().map(|chunk| ChunkManifestEntry {
+            id: chunk.id.clone(),
+            path: chunk.path.clone(),
+            start_line: 1,
+            end_line: 1,
+            symbol: None,
+            tags: Vec::new(),
+            content_hash: String::new(),
+            file_hash: String::new(),
+            embedding: None,
+        }).collect();
+        client.embed_manifest(&chunks, &mut manifest).await.unwrap();
+
+        let embeddings: Vec<_> = manifest.iter().map(|entry| entry.embedding.clone().unwrap()).collect();
+        assert_eq!(embeddings, [vec![1.0], vec![2.0], vec![3.0]]);
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[2].contains(r#""input":["gamma"]"#), "{}", requests[2]);
+    }
+}
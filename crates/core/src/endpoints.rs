@@ -0,0 +1,363 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::{FileEntry, FileSystem};
+
+// Route files are hand-written source; anything larger is generated or bundled
+const MAX_SOURCE_SIZE: u64 = 1024 * 1024;
+// Lines joined onto a route call that spans several, e.g. a chained builder
+const CONTINUATION_LINES: usize = 5;
+// Lines between an attribute or decorator and the function it applies to
+const HANDLER_LOOKAHEAD: usize = 4;
+const HTTP_METHODS: [&str; 7] = ["get", "post", "put", "delete", "patch", "head", "options"];
+
+// One HTTP route declared in source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Endpoint {
+    // Uppercase, or ANY for routes accepting every method
+    pub method: String,
+    // As declared, e.g. "/users/{id}" or "/users/:id"
+    pub route: String,
+    pub path: PathBuf,
+    // 1-based line of the declaration
+    pub line: usize,
+    // "axum", "actix-web", "express", "flask", "fastapi" or "spring"
+    pub framework: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handler: Option<String>,
+}
+
+// Routes declared in the scanned source files of the web frameworks they
+// import. Declarations are recognized line by line, so routes built from
+// variables or spread across helper functions are missed. Sorted by route,
+// then method.
+pub(crate) fn extract_endpoints(files: &[FileEntry], fs: &dyn FileSystem) -> Vec<Endpoint> {
+    let mut endpoints = Vec::new();
+    for file in files.iter().filter(|file| !file.is_dir && file.size <= MAX_SOURCE_SIZE) {
+        let Some(extension) = file.path.extension().and_then(|extension| extension.to_str()) else { continue };
+        let extract: fn(&str) -> Vec<Route> = match extension {
+            "rs" => rust_routes,
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" => express_routes,
+            "py" => python_routes,
+            "java" | "kt" => spring_routes,
+            _ => continue,
+        };
+        let Ok(content) = fs.read_to_string(&file.path) else { continue };
+        endpoints.extend(extract(&content).into_iter().map(|route| route.at(&file.path)));
+    }
+    endpoints.sort_by(|a, b| a.route.cmp(&b.route).then_with(|| a.method.cmp(&b.method)).then_with(|| a.path.cmp(&b.path)));
+    endpoints
+}
+
+struct Route {
+    method: String,
+    route: String,
+    line: usize,
+    framework: &'static str,
+    handler: Option<String>,
+}
+
+impl Route {
+    fn new(method: &str, route: String, line: usize, framework: &'static str, handler: Option<String>) -> Self {
+        Self { method: method.to_uppercase(), route, line: line + 1, framework, handler }
+    }
+
+    fn at(self, path: &Path) -> Endpoint {
+        Endpoint {
+            method: self.method,
+            route: self.route,
+            path: path.to_path_buf(),
+            line: self.line,
+            framework: self.framework.to_string(),
+            handler: self.handler,
+        }
+    }
+}
+
+// axum's `.route("/p", get(list).post(create))` and actix-web's `#[get("/p")]`
+// attributes and `.route("/p", web::get().to(handler))`
+fn rust_routes(content: &str) -> Vec<Route> {
+    let axum = content.contains("axum");
+    let actix = content.contains("actix_web");
+    if !axum && !actix {
+        return Vec::new();
+    }
+    let lines: Vec<&str> = content.lines().collect();
+    let mut routes = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if is_comment(trimmed) {
+            continue;
+        }
+        if actix {
+            if let Some(attribute) = trimmed.strip_prefix("#[").and_then(|rest| rest.strip_suffix(']')) {
+                let (name, arguments) = attribute.split_once('(').unwrap_or((attribute, ""));
+                let methods: Vec<String> = if name == "route" {
+                    quoted_after_all(arguments, "method")
+                } else if HTTP_METHODS.contains(&name) {
+                    vec![name.to_string()]
+                } else {
+                    continue;
+                };
+                let Some((route, _)) = string_literal(arguments) else { continue };
+                let handler = handler_after(&lines, index, "fn ");
+                routes.extend(methods.iter().map(|method| Route::new(method, route.clone(), index, "actix-web", handler.clone())));
+                continue;
+            }
+        }
+
+        let Some(start) = trimmed.find(".route(") else { continue };
+        let call = joined(&lines, index, &trimmed[start + ".route(".len()..]);
+        let Some((route, rest)) = string_literal(&call) else { continue };
+        if actix && rest.contains("web::") {
+            for method in HTTP_METHODS.iter().filter(|method| rest.contains(&format!("web::{}()", method))) {
+                let handler = rest.split_once(".to(").and_then(|(_, handler)| identifier(handler));
+                routes.push(Route::new(method, route.clone(), index, "actix-web", handler));
+            }
+        } else if axum {
+            for (method, handler) in method_calls(rest, &["get", "post", "put", "delete", "patch", "head", "options", "any"]) {
+                routes.push(Route::new(&method, route.clone(), index, "axum", handler));
+            }
+        }
+    }
+    routes
+}
+
+// `app.get('/p', ...)` and `router.post("/p", ...)` in files importing express
+fn express_routes(content: &str) -> Vec<Route> {
+    if !content.contains("express") {
+        return Vec::new();
+    }
+    let mut routes = Vec::new();
+    for (index, line) in content.lines().enumerate().filter(|(_, line)| !is_comment(line)) {
+        for method in HTTP_METHODS.iter().copied().chain(["all"]) {
+            let call = format!(".{}(", method);
+            let Some(start) = line.find(&call) else { continue };
+            let receiver_is_identifier = line[..start].chars().next_back().is_some_and(|c| c.is_alphanumeric() || c == '_' || c == ')');
+            let Some((route, _)) = string_literal(&line[start + call.len()..]) else { continue };
+            if receiver_is_identifier && route.starts_with('/') {
+                let method = if method == "all" { "any" } else { method };
+                routes.push(Route::new(method, route, index, "express", None));
+            }
+        }
+    }
+    routes
+}
+
+// Flask's `@app.route("/p", methods=["POST"])` and `@bp.get("/p")`, and
+// FastAPI's `@app.get("/p")` and `@router.post("/p")`
+fn python_routes(content: &str) -> Vec<Route> {
+    let framework = if content.contains("fastapi") {
+        "fastapi"
+    } else if content.contains("flask") {
+        "flask"
+    } else {
+        return Vec::new();
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    let mut routes = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let Some(decorator) = line.trim().strip_prefix('@') else { continue };
+        let Some((target, arguments)) = decorator.split_once('(') else { continue };
+        let Some((_, name)) = target.rsplit_once('.') else { continue };
+        let methods = match name {
+            "route" | "api_route" => {
+                let methods = quoted_after_all(arguments, "methods");
+                if methods.is_empty() { vec!["get".to_string()] } else { methods }
+            }
+            name if HTTP_METHODS.contains(&name) => vec![name.to_string()],
+            _ => continue,
+        };
+        let Some((route, _)) = string_literal(arguments) else { continue };
+        let handler = handler_after(&lines, index, "def ");
+        routes.extend(methods.iter().map(|method| Route::new(method, route.clone(), index, framework, handler.clone())));
+    }
+    routes
+}
+
+// `@GetMapping("/p")` and `@RequestMapping(value = "/p", method = RequestMethod.POST)`,
+// under the path of a class-level `@RequestMapping`
+fn spring_routes(content: &str) -> Vec<Route> {
+    if !content.contains("springframework") {
+        return Vec::new();
+    }
+    let lines: Vec<&str> = content.lines().collect();
+    let mut prefix = String::new();
+    let mut routes = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let Some(annotation) = line.trim().strip_prefix('@') else { continue };
+        let (name, arguments) = annotation.split_once('(').unwrap_or((annotation, ""));
+        let route = string_literal(arguments).map(|(route, _)| route).unwrap_or_default();
+        let method = match name.strip_suffix("Mapping") {
+            Some("Request") => {
+                let declares_class = lines[index + 1..].iter().take(HANDLER_LOOKAHEAD)
+                    .find(|line| !line.trim().starts_with('@'))
+                    .is_some_and(|line| line.contains("class "));
+                if declares_class {
+                    prefix = route.trim_end_matches('/').to_string();
+                    continue;
+                }
+                arguments.split_once("RequestMethod.")
+                    .and_then(|(_, method)| identifier(method))
+                    .unwrap_or_else(|| "any".to_string())
+            }
+            Some(method) if HTTP_METHODS.contains(&method.to_lowercase().as_str()) => method.to_string(),
+            _ => continue,
+        };
+        let handler = lines[index + 1..].iter().take(HANDLER_LOOKAHEAD)
+            .map(|line| line.trim())
+            .find(|line| !line.starts_with('@') && line.contains('('))
+            .and_then(|line| line.split('(').next()?.split_whitespace().last().map(str::to_string));
+        let route = if route.is_empty() || route.starts_with('/') { format!("{}{}", prefix, route) } else { format!("{}/{}", prefix, route) };
+        routes.push(Route::new(&method, if route.is_empty() { "/".to_string() } else { route }, index, "spring", handler));
+    }
+    routes
+}
+
+pub(crate) fn is_comment(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("//") || line.starts_with("/*") || line.starts_with('*')
+}
+
+// The first quoted string in `text`, and what follows it
+pub(crate) fn string_literal(text: &str) -> Option<(String, &str)> {
+    let start = text.find(['"', '\'', '`'])?;
+    let quote = text[start..].chars().next()?;
+    let body = &text[start + 1..];
+    let end = body.find(quote)?;
+    Some((body[..end].to_string(), &body[end + 1..]))
+}
+
+// Every quoted string after `key`, e.g. the methods in `methods=["GET", "POST"]`
+// or each `method = "GET"` of an actix `#[route]`
+fn quoted_after_all(text: &str, key: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    for (_, after) in text.match_indices(key).map(|(position, _)| ((), &text[position + key.len()..])) {
+        let Some(after) = after.trim_start().strip_prefix('=') else { continue };
+        let list = after.trim_start();
+        if let Some(list) = list.strip_prefix('[') {
+            let mut rest = list.split(']').next().unwrap_or_default();
+            while let Some((value, remaining)) = string_literal(rest) {
+                values.push(value.to_lowercase());
+                rest = remaining;
+            }
+        } else if let Some((value, _)) = string_literal(list) {
+            values.push(value.to_lowercase());
+        }
+    }
+    values
+}
+
+// `get(list).post(create)` as (method, handler) pairs
+fn method_calls(text: &str, methods: &[&str]) -> Vec<(String, Option<String>)> {
+    let mut calls = Vec::new();
+    for (position, _) in text.match_indices('(') {
+        let before = &text[..position];
+        let name_start = before.rfind(|c: char| !(c.is_alphanumeric() || c == '_')).map_or(0, |index| index + 1);
+        let name = &before[name_start..];
+        if methods.contains(&name) {
+            calls.push((name.to_string(), identifier(&text[position + 1..])));
+        }
+    }
+    calls
+}
+
+// The leading identifier or path of `text`, e.g. `handlers::list` in `handlers::list)`
+fn identifier(text: &str) -> Option<String> {
+    let text = text.trim_start();
+    let end = text.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':')).unwrap_or(text.len());
+    (end > 0).then(|| text[..end].trim_end_matches(':').to_string())
+}
+
+// `start` plus the following lines, until the call's parentheses balance
+fn joined(lines: &[&str], index: usize, start: &str) -> String {
+    let mut call = start.to_string();
+    let mut depth = 1 + call.matches('(').count() as isize - call.matches(')').count() as isize;
+    for line in lines.iter().skip(index + 1).take(CONTINUATION_LINES) {
+        if depth <= 0 {
+            break;
+        }
+        call.push(' ');
+        call.push_str(line.trim());
+        depth += line.matches('(').count() as isize - line.matches(')').count() as isize;
+    }
+    call
+}
+
+// Name of the function declared by `keyword` just below a decorator or attribute
+fn handler_after(lines: &[&str], index: usize, keyword: &str) -> Option<String> {
+    lines.iter().skip(index + 1).take(HANDLER_LOOKAHEAD)
+        .find_map(|line| line.split_once(keyword))
+        .and_then(|(_, rest)| identifier(rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(routes: Vec<Route>) -> Vec<(String, String, usize, Option<String>)> {
+        routes.into_iter().map(|route| (route.method, route.route, route.line, route.handler)).collect()
+    }
+
+    fn route(method: &str, route: &str, line: usize, handler: Option<&str>) -> (String, String, usize, Option<String>) {
+        (method.to_string(), route.to_string(), line, handler.map(str::to_string))
+    }
+
+    #[test]
+    fn test_rust_routes() {
+        let axum = "use axum::{routing::{get, post}, Router};\n\nfn app() -> Router {\n    Router::new()\n        .route(\"/users\", get(list_users).post(create_user))\n        .route(\n            \"/users/{id}\",\n            get(handlers::show),\n        )\n}\n";
+        assert_eq!(summary(rust_routes(axum)), [
+            route("GET", "/users", 5, Some("list_users")),
+            route("POST", "/users", 5, Some("create_user")),
+            route("GET", "/users/{id}", 6, Some("handlers::show")),
+        ]);
+
+        let actix = "use actix_web::{get, web, App};\n\n#[get(\"/health\")]\nasync fn health() -> &'static str { \"ok\" }\n\n#[route(\"/items\", method = \"GET\", method = \"HEAD\")]\nasync fn items() {}\n\nfn config(app: App) {\n    // app.route(\"/old\", web::get().to(old));\n    app.route(\"/login\", web::post().to(login));\n}\n";
+        assert_eq!(summary(rust_routes(actix)), [
+            route("GET", "/health", 3, Some("health")),
+            route("GET", "/items", 6, Some("items")),
+            route("HEAD", "/items", 6, Some("items")),
+            route("POST", "/login", 11, Some("login")),
+        ]);
+        assert!(rust_routes("fn main() { router.route(\"/x\", get(y)); }").is_empty());
+    }
+
+    #[test]
+    fn test_express_and_python_routes() {
+        let express = "const express = require('express');\nconst router = express.Router();\nrouter.get('/orders', listOrders);\n// app.get('/old', legacy);\napp.post(`/orders/:id/pay`, pay);\nmap.get('key');\n";
+        assert_eq!(summary(express_routes(express)), [
+            route("GET", "/orders", 3, None),
+            route("POST", "/orders/:id/pay", 5, None),
+        ]);
+
+        let flask = "from flask import Flask\napp = Flask(__name__)\n\n@app.route(\"/\")\ndef index():\n    pass\n\n@app.route(\"/login\", methods=[\"GET\", \"POST\"])\ndef login():\n    pass\n";
+        assert_eq!(summary(python_routes(flask)), [
+            route("GET", "/", 4, Some("index")),
+            route("GET", "/login", 8, Some("login")),
+            route("POST", "/login", 8, Some("login")),
+        ]);
+        let fastapi = "from fastapi import APIRouter\nrouter = APIRouter()\n\n@router.delete(\"/items/{item_id}\")\nasync def remove(item_id: int):\n    ...\n";
+        let routes = python_routes(fastapi);
+        assert_eq!(routes[0].framework, "fastapi");
+        assert_eq!(summary(routes), [route("DELETE", "/items/{item_id}", 4, Some("remove"))]);
+    }
+
+    #[test]
+    fn test_spring_routes_and_scan_listing() {
+        let java = "import org.springframework.web.bind.annotation.*;\n\n@RestController\n@RequestMapping(\"/api/users\")\npublic class UserController {\n    @GetMapping\n    public List<User> all() { return repo.findAll(); }\n\n    @PostMapping(\"/{id}/roles\")\n    public void addRole(@PathVariable long id) {}\n\n    @RequestMapping(value = \"/search\", method = RequestMethod.PUT)\n    public void search() {}\n}\n";
+        assert_eq!(summary(spring_routes(java)), [
+            route("GET", "/api/users", 6, Some("all")),
+            route("POST", "/api/users/{id}/roles", 9, Some("addRole")),
+            route("PUT", "/api/users/search", 12, Some("search")),
+        ]);
+
+        let mut fs = crate::MemoryFileSystem::new();
+        fs.insert("svc/app.py", "from flask import Flask\n@app.get(\"/ping\")\ndef ping():\n    return 'pong'\n");
+        fs.insert("svc/README.md", "@app.get(\"/not-code\")\n");
+        let options = crate::ScanOptions { enhanced_analysis: true, ..Default::default() };
+        let result = crate::DirectoryScanner::new(options).scan_with(&fs, "svc").unwrap();
+        assert_eq!(result.endpoints.len(), 1);
+        assert_eq!(result.endpoints[0].path, Path::new("svc/app.py"));
+        assert_eq!(result.endpoints[0].line, 2);
+    }
+}
@@ -0,0 +1,311 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::{FileEntry, FileSystem};
+
+pub const ENTRYPOINT_TAG: &str = "entrypoint";
+
+// Files that start a program by convention in their language
+const MAIN_FILES: &[&str] = &[
+    "main.rs", "main.go", "main.py", "__main__.py", "main.c", "main.cpp", "main.kt", "main.swift", "Main.java",
+    "Program.cs",
+];
+// Entry files of a JavaScript package when package.json does not name one;
+// only counted in the package directory or its src/
+const INDEX_FILES: &[&str] = &["index.js", "index.mjs", "index.cjs", "index.ts", "index.jsx", "index.tsx"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EntryPointKind {
+    // A Cargo binary target, from [[bin]], src/main.rs or src/bin/
+    CargoBin,
+    // package.json `main`
+    PackageMain,
+    // package.json `bin`
+    PackageBin,
+    // Django's manage.py
+    DjangoManage,
+    // The script a Dockerfile's ENTRYPOINT or CMD runs, else the Dockerfile
+    Docker,
+    // A main.* or index.* file found by naming convention alone
+    Main,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryPoint {
+    pub path: PathBuf,
+    pub kind: EntryPointKind,
+    // Binary or command name, or the Docker command line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    // Manifest or Dockerfile declaring it; none for conventions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub declared_in: Option<PathBuf>,
+}
+
+// Finds where programs in the scan start, from Cargo and npm manifests,
+// manage.py, Dockerfiles and main/index naming conventions, and tags each
+// of those files `entrypoint` when `tag` is set. A file declared by a manifest
+// is listed once, under the manifest. Sorted by path.
+pub(crate) fn detect_entry_points(files: &mut [FileEntry], fs: &dyn FileSystem, tag: bool) -> Vec<EntryPoint> {
+    let positions: HashMap<PathBuf, usize> = files.iter().enumerate()
+        .filter(|(_, file)| !file.is_dir)
+        .map(|(position, file)| (file.path.clone(), position))
+        .collect();
+    let mut found = Vec::new();
+    for file in files.iter().filter(|file| !file.is_dir) {
+        let dir = file.path.parent().unwrap_or(Path::new(""));
+        let read = || fs.read_to_string(&file.path).ok();
+        match file.name.as_str() {
+            "Cargo.toml" => found.extend(read().map(|content| cargo_bins(&file.path, dir, &content, &positions)).unwrap_or_default()),
+            "package.json" => found.extend(read().map(|content| package_entries(&file.path, dir, &content, &positions)).unwrap_or_default()),
+            "manage.py" if read().is_some_and(|content| content.contains("django")) => found.push(EntryPoint {
+                path: file.path.clone(),
+                kind: EntryPointKind::DjangoManage,
+                name: None,
+                declared_in: None,
+            }),
+            name if is_dockerfile(name) => found.extend(read().and_then(|content| docker_entry(file, dir, &content, files))),
+            _ => {}
+        }
+    }
+    for file in files.iter().filter(|file| !file.is_dir) {
+        let dir = file.path.parent().unwrap_or(Path::new(""));
+        let by_convention = MAIN_FILES.contains(&file.name.as_str())
+            || INDEX_FILES.contains(&file.name.as_str()) && is_package_dir(dir, &positions);
+        if by_convention && !found.iter().any(|entry| entry.path == file.path) {
+            found.push(EntryPoint { path: file.path.clone(), kind: EntryPointKind::Main, name: None, declared_in: None });
+        }
+    }
+
+    found.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.kind.cmp(&b.kind)));
+    found.dedup_by(|a, b| a.path == b.path && a.kind == b.kind);
+    for entry in found.iter().filter(|_| tag) {
+        if let Some(file) = positions.get(&entry.path).map(|&position| &mut files[position]) {
+            if !file.tags.iter().any(|tag| tag == ENTRYPOINT_TAG) {
+                file.tags.push(ENTRYPOINT_TAG.to_string());
+            }
+        }
+    }
+    found
+}
+
+fn cargo_bins(manifest: &Path, dir: &Path, content: &str, positions: &HashMap<PathBuf, usize>) -> Vec<EntryPoint> {
+    let mut package = None;
+    let mut bins: Vec<(Option<String>, Option<String>)> = Vec::new();
+    let mut section = "";
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            section = line;
+            if line == "[[bin]]" {
+                bins.push((None, None));
+            }
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let value = value.trim().trim_matches('"').to_string();
+        match (section, key.trim()) {
+            ("[package]", "name") => package = Some(value),
+            ("[[bin]]", "name") => bins.last_mut().into_iter().for_each(|bin| bin.0 = Some(value.clone())),
+            ("[[bin]]", "path") => bins.last_mut().into_iter().for_each(|bin| bin.1 = Some(value.clone())),
+            _ => {}
+        }
+    }
+
+    let bin = |path: PathBuf, name: Option<String>| EntryPoint {
+        path,
+        kind: EntryPointKind::CargoBin,
+        name,
+        declared_in: Some(manifest.to_path_buf()),
+    };
+    let mut found = Vec::new();
+    for (name, path) in bins {
+        let path = match (&path, &name) {
+            (Some(path), _) => dir.join(path),
+            (None, Some(name)) if Some(name) == package.as_ref() => dir.join("src/main.rs"),
+            (None, Some(name)) => dir.join(format!("src/bin/{}.rs", name)),
+            (None, None) => continue,
+        };
+        if positions.contains_key(&path) {
+            found.push(bin(path, name));
+        }
+    }
+    // Targets Cargo discovers on its own
+    let main = dir.join("src/main.rs");
+    if package.is_some() && positions.contains_key(&main) && !found.iter().any(|entry| entry.path == main) {
+        found.push(bin(main, package.clone()));
+    }
+    let bin_dir = dir.join("src/bin");
+    for path in positions.keys() {
+        let Ok(relative) = path.strip_prefix(&bin_dir) else { continue };
+        let name = match relative.components().count() {
+            1 if path.extension().is_some_and(|extension| extension == "rs") => relative.file_stem(),
+            2 if relative.ends_with("main.rs") => relative.parent().and_then(Path::file_name),
+            _ => None,
+        };
+        if let Some(name) = name.filter(|_| !found.iter().any(|entry| entry.path == *path)) {
+            found.push(bin(path.clone(), Some(name.to_string_lossy().to_string())));
+        }
+    }
+    found
+}
+
+fn package_entries(manifest: &Path, dir: &Path, content: &str, positions: &HashMap<PathBuf, usize>) -> Vec<EntryPoint> {
+    let Ok(package) = serde_json::from_str::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+    let package_name = package.get("name").and_then(|name| name.as_str()).map(|name| name.rsplit('/').next().unwrap_or(name).to_string());
+    let mut declared: Vec<(EntryPointKind, Option<String>, &str)> = Vec::new();
+    if let Some(main) = package.get("main").and_then(|main| main.as_str()) {
+        declared.push((EntryPointKind::PackageMain, None, main));
+    }
+    match package.get("bin") {
+        Some(serde_json::Value::String(path)) => declared.push((EntryPointKind::PackageBin, package_name.clone(), path)),
+        Some(serde_json::Value::Object(bins)) => {
+            for (name, path) in bins {
+                if let Some(path) = path.as_str() {
+                    declared.push((EntryPointKind::PackageBin, Some(name.clone()), path));
+                }
+            }
+        }
+        _ => {}
+    }
+
+    declared.into_iter()
+        .filter_map(|(kind, name, path)| {
+            let path = dir.join(path.trim_start_matches("./"));
+            // `"main": "lib"` means lib/index.js
+            let path = [path.clone(), path.with_extension("js"), path.join("index.js")].into_iter()
+                .find(|candidate| positions.contains_key(candidate))?;
+            Some(EntryPoint { path, kind, name, declared_in: Some(manifest.to_path_buf()) })
+        })
+        .collect()
+}
+
+pub(crate) fn is_dockerfile(name: &str) -> bool {
+    name == "Dockerfile" || name.starts_with("Dockerfile.") || name.ends_with(".dockerfile")
+}
+
+// The last ENTRYPOINT, else the last CMD. Points at the script it runs when one
+// with that name is in the Dockerfile's directory tree, else the Dockerfile.
+fn docker_entry(dockerfile: &FileEntry, dir: &Path, content: &str, files: &[FileEntry]) -> Option<EntryPoint> {
+    let instruction = |keyword: &str| {
+        content.lines()
+            .map(str::trim)
+            .filter_map(|line| {
+                let (word, rest) = line.split_once(char::is_whitespace)?;
+                word.eq_ignore_ascii_case(keyword).then(|| rest.trim())
+            })
+            .next_back()
+    };
+    let command = instruction("ENTRYPOINT").or_else(|| instruction("CMD"))?;
+    // Exec form ["python", "app.py"] or shell form python app.py
+    let words: Vec<String> = match serde_json::from_str::<Vec<String>>(command) {
+        Ok(words) => words,
+        Err(_) => command.split_whitespace().map(str::to_string).collect(),
+    };
+
+    let script = words.iter()
+        .filter_map(|word| Path::new(word).file_name())
+        .find_map(|name| {
+            files.iter()
+                .filter(|file| !file.is_dir && file.path.starts_with(dir) && file.path.file_name() == Some(name))
+                .min_by_key(|file| file.path.components().count())
+        });
+    Some(EntryPoint {
+        path: script.map_or_else(|| dockerfile.path.clone(), |file| file.path.clone()),
+        kind: EntryPointKind::Docker,
+        name: Some(words.join(" ")),
+        declared_in: Some(dockerfile.path.clone()),
+    })
+}
+
+fn is_package_dir(dir: &Path, positions: &HashMap<PathBuf, usize>) -> bool {
+    let package_dir = if dir.file_name().is_some_and(|name| name == "src") {
+        dir.parent().unwrap_or(dir)
+    } else {
+        dir
+    };
+    positions.contains_key(&package_dir.join("package.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions, ScanResult};
+
+    fn scan(files: &[(&str, &str)]) -> ScanResult {
+        let mut fs = MemoryFileSystem::new();
+        for (path, content) in files {
+            fs.insert(path, *content);
+        }
+        DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "repo").unwrap()
+    }
+
+    fn entries(result: &ScanResult) -> Vec<(&Path, EntryPointKind, Option<&str>)> {
+        result.entry_points.iter()
+            .map(|entry| (entry.path.strip_prefix("repo").unwrap(), entry.kind, entry.name.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn test_cargo_bin_targets() {
+        let result = scan(&[
+            ("repo/Cargo.toml", "[package]\nname = \"tool\"\n\n[[bin]]\nname = \"helper\"\npath = \"tools/helper.rs\"\n"),
+            ("repo/src/main.rs", "fn main() {}\n"),
+            ("repo/src/lib.rs", "pub fn run() {}\n"),
+            ("repo/src/bin/migrate.rs", "fn main() {}\n"),
+            ("repo/tools/helper.rs", "fn main() {}\n"),
+        ]);
+
+        assert_eq!(entries(&result), [
+            (Path::new("src/bin/migrate.rs"), EntryPointKind::CargoBin, Some("migrate")),
+            (Path::new("src/main.rs"), EntryPointKind::CargoBin, Some("tool")),
+            (Path::new("tools/helper.rs"), EntryPointKind::CargoBin, Some("helper")),
+        ]);
+        assert!(result.find("repo/src/main.rs").unwrap().tags.iter().any(|tag| tag == ENTRYPOINT_TAG));
+        assert!(!result.find("repo/src/lib.rs").unwrap().tags.iter().any(|tag| tag == ENTRYPOINT_TAG));
+    }
+
+    #[test]
+    fn test_package_json_django_and_docker() {
+        let result = scan(&[
+            ("repo/web/package.json", r#"{"name": "@acme/web", "main": "./server.js", "bin": {"acme": "bin/cli.js"}}"#),
+            ("repo/web/server.js", "require('http')\n"),
+            ("repo/web/bin/cli.js", "#!/usr/bin/env node\n"),
+            ("repo/web/src/index.ts", "export {}\n"),
+            ("repo/api/manage.py", "import os\nfrom django.core.management import execute_from_command_line\n"),
+            ("repo/api/Dockerfile", "FROM python:3.12\nCOPY . /app\nENTRYPOINT [\"/app/docker-entrypoint.sh\"]\nCMD [\"serve\"]\n"),
+            ("repo/api/docker-entrypoint.sh", "#!/bin/sh\nexec \"$@\"\n"),
+            ("repo/worker/Dockerfile", "FROM alpine\nCMD sleep infinity\n"),
+        ]);
+
+        assert_eq!(entries(&result), [
+            (Path::new("api/docker-entrypoint.sh"), EntryPointKind::Docker, Some("/app/docker-entrypoint.sh")),
+            (Path::new("api/manage.py"), EntryPointKind::DjangoManage, None),
+            (Path::new("web/bin/cli.js"), EntryPointKind::PackageBin, Some("acme")),
+            (Path::new("web/server.js"), EntryPointKind::PackageMain, None),
+            (Path::new("web/src/index.ts"), EntryPointKind::Main, None),
+            (Path::new("worker/Dockerfile"), EntryPointKind::Docker, Some("sleep infinity")),
+        ]);
+        let docker = &result.entry_points[0];
+        assert_eq!(docker.declared_in.as_deref(), Some(Path::new("repo/api/Dockerfile")));
+    }
+
+    #[test]
+    fn test_main_conventions() {
+        let files = [
+            ("repo/cmd/server/main.go", "package main\n"),
+            ("repo/app/__main__.py", "print('hi')\n"),
+            ("repo/components/index.js", "export {}\n"),
+        ];
+        let result = scan(&files);
+        assert_eq!(entries(&result), [
+            (Path::new("app/__main__.py"), EntryPointKind::Main, None),
+            (Path::new("cmd/server/main.go"), EntryPointKind::Main, None),
+        ]);
+        // index.js outside a package directory is just a module
+        assert!(result.find("repo/components/index.js").unwrap().tags.iter().all(|tag| tag != ENTRYPOINT_TAG));
+    }
+}
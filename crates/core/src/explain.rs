@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+use crate::{format_rfc3339, IgnoreStack, ScanError, ScanOptions};
+
+// Why the walk leaves an entry out of a scan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+pub enum SkipReason {
+    Hidden,
+    IgnorePattern { pattern: String },
+    IgnoreFile { file: PathBuf, rule: String },
+    NotIncluded,
+    SizeOutOfRange { size: u64 },
+    ModifiedOutOfRange {
+        #[serde(flatten, with = "crate::timestamp::modified")]
+        modified: SystemTime,
+    },
+    DepthLimit { max_depth: usize },
+    InsideSkippedDirectory { dir: PathBuf, cause: Box<SkipReason> },
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::Hidden => write!(f, "hidden name"),
+            SkipReason::IgnorePattern { pattern } => write!(f, "path contains ignore pattern \"{}\"", pattern),
+            SkipReason::IgnoreFile { file, rule } => write!(f, "rule \"{}\" in {}", rule, file.display()),
+            SkipReason::NotIncluded => write!(f, "matches no include pattern"),
+            SkipReason::SizeOutOfRange { size } => write!(f, "size {} bytes is outside the size filter", size),
+            SkipReason::ModifiedOutOfRange { modified } => {
+                write!(f, "modified {} is outside the modification time filter", format_rfc3339(*modified))
+            }
+            SkipReason::DepthLimit { max_depth } => write!(f, "deeper than max depth {}", max_depth),
+            SkipReason::InsideSkippedDirectory { dir, cause } => {
+                write!(f, "inside skipped directory {} ({})", dir.display(), cause)
+            }
+        }
+    }
+}
+
+// One entry of a dry run; `skip` is None for entries the scan would report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WalkDecision {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub skip: Option<SkipReason>,
+}
+
+// The walk-time decision shared by the on-disk walk and its dry run
+pub(crate) fn walk_skip_reason(
+    options: &ScanOptions,
+    ignore_files: &mut IgnoreStack,
+    path: &Path,
+    name: &str,
+    is_dir: bool,
+) -> Option<SkipReason> {
+    options.ignore_reason(path, name)
+        .or_else(|| options.use_ignore_files.then(|| ignore_files.ignore_reason(path, is_dir)).flatten())
+}
+
+// Walks `root` like a scan would without analyzing anything. Skipped
+// directories are listed once and not descended into; walk errors are left out.
+pub fn plan_walk(root: &Path, options: &ScanOptions) -> Vec<WalkDecision> {
+    let skipped = RefCell::new(Vec::new());
+    let mut ignore_files = IgnoreStack::default();
+    let walker = WalkDir::new(root)
+        .follow_links(options.follow_symlinks)
+        .max_depth(options.max_depth.unwrap_or(usize::MAX))
+        .into_iter()
+        .filter_entry(|entry| {
+            let is_dir = entry.file_type().is_dir();
+            if entry.depth() > 0 {
+                let name = entry.file_name().to_str().unwrap_or("");
+                if let Some(reason) = walk_skip_reason(options, &mut ignore_files, entry.path(), name, is_dir) {
+                    skipped.borrow_mut().push(WalkDecision { path: entry.path().to_path_buf(), is_dir, skip: Some(reason) });
+                    return false;
+                }
+            }
+            if is_dir && options.use_ignore_files {
+                ignore_files.enter(entry.path(), |file| std::fs::read_to_string(file).ok());
+            }
+            true
+        });
+
+    let mut decisions: Vec<WalkDecision> = walker.flatten()
+        .map(|entry| {
+            let is_dir = entry.file_type().is_dir();
+            let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+            let skip = if is_dir {
+                None
+            } else if !options.is_included(relative, entry.file_name().to_str().unwrap_or("")) {
+                Some(SkipReason::NotIncluded)
+            } else {
+                entry.metadata().ok().and_then(|metadata| file_filter_reason(options, &metadata))
+            };
+            WalkDecision { path: entry.path().to_path_buf(), is_dir, skip }
+        })
+        .collect();
+    decisions.extend(skipped.into_inner());
+    decisions.sort_by(|a, b| a.path.cmp(&b.path));
+    decisions
+}
+
+// Whether a scan of `root` would report `path`, checking each directory on the
+// way down as the walk would. `path` may be relative to `root` or include it.
+pub fn explain_path(root: &Path, path: &Path, options: &ScanOptions) -> Result<WalkDecision, ScanError> {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let target = root.join(relative);
+    let metadata = std::fs::metadata(&target).map_err(|_| ScanError::PathNotFound { path: target.clone() })?;
+    let decision = |skip| Ok(WalkDecision { path: target.clone(), is_dir: metadata.is_dir(), skip });
+
+    let mut ignore_files = IgnoreStack::default();
+    if options.use_ignore_files {
+        ignore_files.enter(root, |file| std::fs::read_to_string(file).ok());
+    }
+    let mut current = root.to_path_buf();
+    for (depth, component) in relative.components().enumerate() {
+        current.push(component);
+        if options.max_depth.is_some_and(|max_depth| depth + 1 > max_depth) {
+            return decision(Some(SkipReason::DepthLimit { max_depth: options.max_depth.unwrap_or_default() }));
+        }
+
+        let is_dir = current.is_dir();
+        let name = component.as_os_str().to_str().unwrap_or("");
+        if let Some(reason) = walk_skip_reason(options, &mut ignore_files, &current, name, is_dir) {
+            if current == target {
+                return decision(Some(reason));
+            }
+            return decision(Some(SkipReason::InsideSkippedDirectory { dir: current, cause: Box::new(reason) }));
+        }
+        if is_dir && options.use_ignore_files {
+            ignore_files.enter(&current, |file| std::fs::read_to_string(file).ok());
+        }
+    }
+
+    let name = target.file_name().and_then(|name| name.to_str()).unwrap_or("");
+    if metadata.is_dir() {
+        return decision(None);
+    }
+    if !options.is_included(relative, name) {
+        return decision(Some(SkipReason::NotIncluded));
+    }
+    decision(file_filter_reason(options, &metadata))
+}
+
+fn file_filter_reason(options: &ScanOptions, metadata: &std::fs::Metadata) -> Option<SkipReason> {
+    options.filter_reason(metadata.len(), metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn project() -> tempfile::TempDir {
+        let temp = tempfile::tempdir().unwrap();
+        for (path, content) in [
+            ("src/main.rs", ""),
+            ("src/.secret", ""),
+            ("node_modules/pkg/index.js", ""),
+            ("team/.projscanignore", "fixtures/\n"),
+            ("team/fixtures/data.json", ""),
+            ("docs/guide.md", ""),
+        ] {
+            fs::create_dir_all(temp.path().join(path).parent().unwrap()).unwrap();
+            fs::write(temp.path().join(path), content).unwrap();
+        }
+        temp
+    }
+
+    #[test]
+    fn test_explain_names_the_deciding_rule() {
+        let temp = project();
+        let root = temp.path();
+        let options = ScanOptions { include_patterns: vec!["*.rs".to_string(), "*.json".to_string()], ..Default::default() };
+        let skip = |path: &str| explain_path(root, Path::new(path), &options).unwrap().skip;
+
+        assert_eq!(skip("src/main.rs"), None);
+        assert_eq!(skip("src/.secret"), Some(SkipReason::Hidden));
+        assert_eq!(skip("docs/guide.md"), Some(SkipReason::NotIncluded));
+        assert_eq!(skip("node_modules/pkg/index.js"), Some(SkipReason::InsideSkippedDirectory {
+            dir: root.join("node_modules"),
+            cause: Box::new(SkipReason::IgnorePattern { pattern: "node_modules".to_string() }),
+        }));
+        assert_eq!(skip("team/fixtures"), Some(SkipReason::IgnoreFile {
+            file: root.join("team/.projscanignore"),
+            rule: "fixtures/".to_string(),
+        }));
+        assert!(matches!(explain_path(root, Path::new("missing.rs"), &options), Err(ScanError::PathNotFound { .. })));
+    }
+
+    #[test]
+    fn test_explain_respects_depth_limit() {
+        let temp = project();
+        let options = ScanOptions { max_depth: Some(1), ..Default::default() };
+
+        assert_eq!(explain_path(temp.path(), Path::new("src"), &options).unwrap().skip, None);
+        assert_eq!(
+            explain_path(temp.path(), &temp.path().join("src/main.rs"), &options).unwrap().skip,
+            Some(SkipReason::DepthLimit { max_depth: 1 })
+        );
+    }
+
+    #[test]
+    fn test_plan_lists_skipped_directories_once() {
+        let temp = project();
+        let plan = plan_walk(temp.path(), &ScanOptions::default());
+        let listed = |path: &str| plan.iter().find(|decision| decision.path == temp.path().join(path));
+
+        assert_eq!(listed("src/main.rs").unwrap().skip, None);
+        assert_eq!(listed("src/.secret").unwrap().skip, Some(SkipReason::Hidden));
+        assert!(matches!(listed("node_modules").unwrap().skip, Some(SkipReason::IgnorePattern { .. })));
+        assert!(listed("node_modules/pkg").is_none());
+        assert!(matches!(listed("team/fixtures").unwrap().skip, Some(SkipReason::IgnoreFile { .. })));
+        assert!(listed("team/fixtures/data.json").is_none());
+    }
+
+    #[test]
+    fn test_size_filter_reason() {
+        let temp = project();
+        fs::write(temp.path().join("docs/big.md"), "x".repeat(2048)).unwrap();
+        let options = ScanOptions { max_size: Some(1024), ..Default::default() };
+
+        assert_eq!(explain_path(temp.path(), Path::new("docs/guide.md"), &options).unwrap().skip, None);
+        let skip = explain_path(temp.path(), Path::new("docs/big.md"), &options).unwrap().skip;
+        assert_eq!(skip, Some(SkipReason::SizeOutOfRange { size: 2048 }));
+        let plan = plan_walk(temp.path(), &ScanOptions { modified_before: Some(SystemTime::UNIX_EPOCH), ..Default::default() });
+        let skipped = plan.iter().find(|decision| decision.path == temp.path().join("src/main.rs")).unwrap();
+        assert!(matches!(skipped.skip, Some(SkipReason::ModifiedOutOfRange { .. })));
+        assert!(serde_json::to_string(&skipped.skip).unwrap().contains(r#""reason":"modified-out-of-range","modified":"#));
+    }
+}
@@ -0,0 +1,371 @@
+use std::fmt::Write;
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::{portable_path, FileEntry, FindingKind, ScanResult, Severity, GOD_MODULE_TAG};
+
+// Tabular renderings of a scan result for spreadsheets, docs and browsers.
+// Every format lists the same per-file columns.
+const COLUMNS: [&str; 10] = [
+    "path", "size", "modified", "tags", "language", "lines", "complexity", "importance", "purpose", "is_dir",
+];
+
+fn relative(root: &Path, path: &Path) -> String {
+    match path.strip_prefix(root) {
+        Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+        Ok(relative) => portable_path(relative),
+        Err(_) => portable_path(path),
+    }
+}
+
+fn row(root: &Path, file: &FileEntry) -> [String; 10] {
+    let info = file.enhanced_info.as_ref();
+    let number = |value: Option<f64>| value.map_or(String::new(), |value| format!("{:.2}", value));
+    [
+        relative(root, &file.path),
+        file.size.to_string(),
+        file.modified.duration_since(SystemTime::UNIX_EPOCH).map_or(String::new(), |age| age.as_secs().to_string()),
+        file.tags.join(";"),
+        info.and_then(|info| info.language.clone()).unwrap_or_default(),
+        info.and_then(|info| info.line_count).map_or(String::new(), |lines| lines.to_string()),
+        number(info.and_then(|info| info.complexity_score)),
+        number(info.and_then(|info| info.importance_score)),
+        info.and_then(|info| info.purpose.clone()).unwrap_or_default(),
+        file.is_dir.to_string(),
+    ]
+}
+
+pub fn to_csv(result: &ScanResult) -> String {
+    let mut csv = Vec::new();
+    write_csv(result, &mut csv).expect("writing to a Vec cannot fail");
+    String::from_utf8(csv).expect("CSV fields are UTF-8")
+}
+
+// The writers below serialize a result straight to `out` rather than building
+// a string first, so a multi-gigabyte result is not held twice. They write in
+// small pieces; pass a `BufWriter` for files and sockets.
+
+// RFC 4180: fields containing separators, quotes or newlines are quoted
+pub fn write_csv<W: io::Write>(result: &ScanResult, mut out: W) -> io::Result<()> {
+    let quote = |field: &str| {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    };
+
+    write!(out, "{}\r\n", COLUMNS.join(","))?;
+    for file in &result.files {
+        let fields: Vec<String> = row(&result.root_path, file).iter().map(|field| quote(field)).collect();
+        write!(out, "{}\r\n", fields.join(","))?;
+    }
+    Ok(())
+}
+
+// Pretty-printed, as `serde_json::to_string_pretty` would, with a final newline
+pub fn write_json<W: io::Write>(result: &ScanResult, mut out: W) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut out, result)?;
+    out.write_all(b"\n")
+}
+
+pub fn write_yaml<W: io::Write>(result: &ScanResult, out: W) -> io::Result<()> {
+    serde_yaml::to_writer(out, result).map_err(io::Error::other)
+}
+
+// One entry per line, then a last line holding the result without files, the
+// same layout `scan --stream` writes
+pub fn write_ndjson<W: io::Write>(result: &ScanResult, mut out: W) -> io::Result<()> {
+    for file in &result.files {
+        serde_json::to_writer(&mut out, file)?;
+        out.write_all(b"\n")?;
+    }
+    serde_json::to_writer(&mut out, &without_files(result))?;
+    out.write_all(b"\n")
+}
+
+fn without_files(result: &ScanResult) -> ScanResult {
+    ScanResult {
+        schema_version: result.schema_version,
+        root_path: result.root_path.clone(),
+        files: Vec::new(),
+        stats: result.stats.clone(),
+        roots: result.roots.clone(),
+        errors: result.errors.clone(),
+        findings: result.findings.clone(),
+        entry_points: result.entry_points.clone(),
+        endpoints: result.endpoints.clone(),
+        schema: result.schema.clone(),
+        containers: result.containers.clone(),
+        nested_repositories: result.nested_repositories.clone(),
+        test_coverage_structure: result.test_coverage_structure.clone(),
+    }
+}
+
+pub fn to_markdown(result: &ScanResult) -> String {
+    let cell = |field: &str| field.replace('|', "\\|").replace('\n', " ");
+    let stats = &result.stats;
+
+    let mut markdown = String::new();
+    let _ = writeln!(markdown, "# Scan of `{}`\n", result.root_path.display());
+    let _ = writeln!(markdown, "| Files | Directories | Total size (bytes) | Issues |");
+    let _ = writeln!(markdown, "|------:|------------:|-------------------:|-------:|");
+    let _ = writeln!(markdown, "| {} | {} | {} | {} |\n", stats.total_files, stats.total_dirs, stats.total_size, result.errors.len());
+
+    let _ = writeln!(markdown, "| {} |", COLUMNS[..9].join(" | "));
+    let _ = writeln!(markdown, "|{}", "---|".repeat(9));
+    for file in result.files.iter().filter(|file| !file.is_dir) {
+        let fields: Vec<String> = row(&result.root_path, file)[..9].iter().map(|field| cell(field)).collect();
+        let _ = writeln!(markdown, "| {} |", fields.join(" | "));
+    }
+
+    if !result.endpoints.is_empty() {
+        let _ = writeln!(markdown, "\n## Endpoints\n");
+        let _ = writeln!(markdown, "| Method | Route | Location | Handler | Framework |");
+        let _ = writeln!(markdown, "|---|---|---|---|---|");
+        for endpoint in &result.endpoints {
+            let location = format!("{}:{}", relative(&result.root_path, &endpoint.path), endpoint.line);
+            let _ = writeln!(markdown, "| {} | {} | {} | {} | {} |", endpoint.method, cell(&endpoint.route),
+                cell(&location), cell(endpoint.handler.as_deref().unwrap_or_default()), endpoint.framework);
+        }
+    }
+
+    if let Some(schema) = result.schema.as_ref().filter(|schema| !schema.tables.is_empty()) {
+        let _ = writeln!(markdown, "\n## Database schema\n");
+        let _ = writeln!(markdown, "| Table | Columns | Defined in |");
+        let _ = writeln!(markdown, "|---|---|---|");
+        for table in &schema.tables {
+            let location = format!("{}:{}", relative(&result.root_path, &table.path), table.line);
+            let _ = writeln!(markdown, "| {} | {} | {} |", cell(&table.name), cell(&table.columns.join(", ")), cell(&location));
+        }
+    }
+
+    if !result.containers.is_empty() {
+        let _ = writeln!(markdown, "\n## Containers\n");
+        let _ = writeln!(markdown, "| Path | Kind | Images | Ports | Stages or services |");
+        let _ = writeln!(markdown, "|---|---|---|---|---|");
+        for artifact in &result.containers {
+            let parts = if artifact.stages.is_empty() { &artifact.services } else { &artifact.stages };
+            let _ = writeln!(markdown, "| {} | {:?} | {} | {} | {} |", cell(&relative(&result.root_path, &artifact.path)), artifact.kind,
+                cell(&artifact.images.join(", ")), cell(&artifact.ports.join(", ")), cell(&parts.join(", ")));
+        }
+    }
+
+    let god_modules: Vec<&FileEntry> = result.files.iter().filter(|file| file.tags.iter().any(|tag| tag == GOD_MODULE_TAG)).collect();
+    if !god_modules.is_empty() {
+        let _ = writeln!(markdown, "\n## God modules\n");
+        let _ = writeln!(markdown, "| Path | Fan-in | Fan-out | Complexity |");
+        let _ = writeln!(markdown, "|---|---:|---:|---:|");
+        for file in god_modules {
+            let Some(info) = &file.enhanced_info else { continue };
+            let _ = writeln!(markdown, "| {} | {} | {} | {:.2} |", cell(&relative(&result.root_path, &file.path)),
+                info.fan_in.unwrap_or(0), info.fan_out.unwrap_or(0), info.complexity_score.unwrap_or(0.0));
+        }
+    }
+
+    if !result.findings.is_empty() {
+        let _ = writeln!(markdown, "\n## Security findings\n");
+        let _ = writeln!(markdown, "| Severity | Finding | Path | Mode |");
+        let _ = writeln!(markdown, "|---|---|---|---:|");
+        for finding in &result.findings {
+            let description = match &finding.advisory {
+                Some(advisory) => format!("{} ({})", finding.kind.description(), advisory),
+                None => finding.kind.description().to_string(),
+            };
+            // Dependency findings carry no permission bits
+            let mode = if finding.mode == 0 { String::new() } else { format!("{:04o}", finding.mode) };
+            let _ = writeln!(markdown, "| {:?} | {} | {} | {} |",
+                finding.severity, cell(&description), cell(&relative(&result.root_path, &finding.path)), mode);
+        }
+    }
+    markdown
+}
+
+// SARIF 2.1.0 for code-scanning dashboards. Security findings are the results,
+// one rule per finding kind, with locations relative to the scan root.
+pub fn to_sarif(result: &ScanResult) -> String {
+    const KINDS: [FindingKind; 6] = [
+        FindingKind::WorldWritable,
+        FindingKind::Setuid,
+        FindingKind::Setgid,
+        FindingKind::LoosePrivateKey,
+        FindingKind::VulnerableDependency,
+        FindingKind::HardcodedSecret,
+    ];
+    let level = |severity: Severity| match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    };
+
+    let rules: Vec<serde_json::Value> = KINDS.iter()
+        .map(|kind| serde_json::json!({
+            "id": kind.rule_id(),
+            "name": kind.as_str(),
+            "shortDescription": { "text": kind.description() },
+        }))
+        .collect();
+    let results: Vec<serde_json::Value> = result.findings.iter()
+        .map(|finding| serde_json::json!({
+            "ruleId": finding.kind.rule_id(),
+            "ruleIndex": KINDS.iter().position(|kind| *kind == finding.kind),
+            "level": level(finding.severity),
+            "message": { "text": finding.message },
+            "locations": [{
+                "physicalLocation": {
+                    "artifactLocation": { "uri": relative(&result.root_path, &finding.path), "uriBaseId": "%SRCROOT%" },
+                },
+            }],
+        }))
+        .collect();
+
+    let sarif = serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "projscan",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&sarif).unwrap_or_default() + "\n"
+}
+
+// A standalone page with no external assets, so it can be attached or archived as-is
+pub fn to_html(result: &ScanResult) -> String {
+    let escape = |text: &str| {
+        text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+    };
+    let root = escape(&result.root_path.to_string_lossy());
+    let stats = &result.stats;
+
+    let mut html = String::new();
+    let _ = writeln!(html, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(html, "<title>Scan of {}</title>", root);
+    let _ = writeln!(html, "<style>body{{font-family:sans-serif;margin:2em}}table{{border-collapse:collapse}}\
+        th,td{{border:1px solid #ccc;padding:4px 8px;text-align:left}}th{{background:#f4f4f4}}</style>");
+    let _ = writeln!(html, "</head>\n<body>\n<h1>Scan of {}</h1>", root);
+    let _ = writeln!(html, "<p>{} files, {} directories, {} bytes, {} issues</p>",
+        stats.total_files, stats.total_dirs, stats.total_size, result.errors.len());
+
+    let _ = writeln!(html, "<table>\n<thead><tr>{}</tr></thead>\n<tbody>",
+        COLUMNS[..9].iter().map(|column| format!("<th>{}</th>", column)).collect::<String>());
+    for file in result.files.iter().filter(|file| !file.is_dir) {
+        let cells: String = row(&result.root_path, file)[..9].iter()
+            .map(|field| format!("<td>{}</td>", escape(field)))
+            .collect();
+        let _ = writeln!(html, "<tr>{}</tr>", cells);
+    }
+    let _ = writeln!(html, "</tbody>\n</table>\n</body>\n</html>");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScanStats;
+    use std::path::PathBuf;
+
+    fn result() -> ScanResult {
+        let file = |path: &str, tags: &[&str], purpose: Option<&str>| FileEntry::test(Path::new("proj").join(path))
+            .with_size(120)
+            .with_tags(tags)
+            .with_language("rust")
+            .with_complexity(Some(2.5))
+            .with_info(|info| info.purpose = purpose.map(str::to_string));
+        ScanResult {
+            schema_version: crate::SCHEMA_VERSION,
+            root_path: PathBuf::from("proj"),
+            files: vec![
+                file("src/lib.rs", &["source", "core"], Some("Parses \"a, b\"")),
+                file("src/a|b.rs", &[], None),
+            ],
+            stats: ScanStats { total_files: 2, total_dirs: 1, total_size: 240, scan_duration_ms: 0, files_per_second: 0.0, directories: Default::default(), distribution: Default::default(), cache: None, sampling: None, resources: None },
+            roots: Vec::new(),
+            errors: Vec::new(),
+            findings: Vec::new(),
+            test_coverage_structure: None,
+            entry_points: Vec::new(),
+            endpoints: Vec::new(),
+            schema: None,
+            containers: Vec::new(),
+            nested_repositories: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_csv_quotes_fields() {
+        let csv = to_csv(&result());
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+
+        assert_eq!(lines[0], "path,size,modified,tags,language,lines,complexity,importance,purpose,is_dir");
+        assert_eq!(lines[1], "src/lib.rs,120,0,source;core,rust,,2.50,,\"Parses \"\"a, b\"\"\",false");
+    }
+
+    #[test]
+    fn test_writers_match_in_memory_output() {
+        let result = result();
+        let mut json = Vec::new();
+        write_json(&result, &mut json).unwrap();
+        assert_eq!(String::from_utf8(json).unwrap(), serde_json::to_string_pretty(&result).unwrap() + "\n");
+        let mut yaml = Vec::new();
+        write_yaml(&result, &mut yaml).unwrap();
+        assert_eq!(String::from_utf8(yaml).unwrap(), serde_yaml::to_string(&result).unwrap());
+
+        let mut ndjson = Vec::new();
+        write_ndjson(&result, &mut ndjson).unwrap();
+        let lines: Vec<serde_json::Value> = String::from_utf8(ndjson).unwrap().lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["path"], "proj/src/lib.rs");
+        assert_eq!(lines[2]["files"], serde_json::json!([]));
+        assert_eq!(lines[2]["stats"]["total_size"], 240);
+    }
+
+    #[test]
+    fn test_markdown_escapes_pipes() {
+        let markdown = to_markdown(&result());
+
+        assert!(markdown.starts_with("# Scan of `proj`"));
+        assert!(markdown.contains("| src/a\\|b.rs | 120 |"));
+    }
+
+    #[test]
+    fn test_security_findings_in_markdown_and_sarif() {
+        let mut result = result();
+        result.findings.push(crate::SecurityFinding {
+            path: PathBuf::from("proj/keys/id_rsa"),
+            kind: FindingKind::LoosePrivateKey,
+            severity: Severity::Error,
+            message: "Private key is readable".to_string(),
+            mode: 0o644,
+            advisory: None,
+        });
+
+        let markdown = to_markdown(&result);
+        assert!(markdown.contains("## Security findings"));
+        assert!(markdown.contains("| Error | Private key is readable or writable by group or others | keys/id_rsa | 0644 |"));
+
+        let sarif: serde_json::Value = serde_json::from_str(&to_sarif(&result)).unwrap();
+        let finding = &sarif["runs"][0]["results"][0];
+        assert_eq!(finding["ruleId"], "PS004");
+        assert_eq!(finding["level"], "error");
+        assert_eq!(finding["locations"][0]["physicalLocation"]["artifactLocation"]["uri"], "keys/id_rsa");
+        assert_eq!(sarif["runs"][0]["tool"]["driver"]["rules"][3]["name"], "loose-private-key");
+    }
+
+    #[test]
+    fn test_html_escapes_content() {
+        let html = to_html(&result());
+
+        assert!(html.contains("<td>Parses &quot;a, b&quot;</td>"));
+        assert!(html.contains("2 files, 1 directories, 240 bytes, 0 issues"));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+}
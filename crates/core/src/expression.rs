@@ -0,0 +1,526 @@
+use std::path::Path;
+
+use crate::{portable_path, FileEntry, ScanError, ScanResult};
+
+// Filter expressions over scan entries, e.g.
+//   tag:source AND complexity>5 AND path:src/**
+//   (lang:rust OR lang:go) AND NOT tag:test
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterExpr {
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+    Not(Box<FilterExpr>),
+    Text { field: TextField, value: String },
+    Compare { field: NumericField, op: CompareOp, value: f64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextField {
+    Tag,
+    Path,
+    Name,
+    Ext,
+    Language,
+    Framework,
+    Purpose,
+    Type,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumericField {
+    Complexity,
+    Importance,
+    Percentile,
+    Size,
+    Lines,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+// Parentheses and NOT recurse in the parser, and every AND/OR adds a level to
+// the tree that matching recurses through; expressions come from HTTP queries
+// too, so both are bounded rather than left to the stack
+const MAX_NESTING: usize = 64;
+const MAX_TERMS: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(CompareOp),
+    Colon,
+    LParen,
+    RParen,
+}
+
+impl FilterExpr {
+    pub fn parse(input: &str) -> Result<Self, ScanError> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, position: 0, depth: 0, terms: 0 };
+        let expr = parser.parse_or()?;
+        match parser.tokens.get(parser.position) {
+            None => Ok(expr),
+            Some(token) => Err(invalid(format!("unexpected {:?} after complete expression", token))),
+        }
+    }
+
+    // `root` lets path globs be written relative to the scanned directory
+    pub fn matches(&self, entry: &FileEntry, root: &Path) -> bool {
+        match self {
+            FilterExpr::And(left, right) => left.matches(entry, root) && right.matches(entry, root),
+            FilterExpr::Or(left, right) => left.matches(entry, root) || right.matches(entry, root),
+            FilterExpr::Not(inner) => !inner.matches(entry, root),
+            FilterExpr::Text { field, value } => Self::matches_text(*field, value, entry, root),
+            FilterExpr::Compare { field, op, value } => Self::numeric_value(*field, entry)
+                .is_some_and(|actual| op.apply(actual, *value)),
+        }
+    }
+
+    fn matches_text(field: TextField, value: &str, entry: &FileEntry, root: &Path) -> bool {
+        let enhanced = entry.enhanced_info.as_ref();
+        let equals = |actual: Option<&str>| actual.is_some_and(|actual| actual.eq_ignore_ascii_case(value));
+        match field {
+            TextField::Tag => entry.tags.iter().any(|tag| tag.eq_ignore_ascii_case(value)),
+            TextField::Path => {
+                let relative = entry.path.strip_prefix(root).unwrap_or(&entry.path);
+                let relative = portable_path(relative);
+                let full = portable_path(&entry.path);
+                if has_wildcards(value) {
+                    glob_match(value, &relative) || glob_match(value, &full)
+                } else {
+                    full.contains(value)
+                }
+            }
+            TextField::Name => {
+                if has_wildcards(value) {
+                    glob_match(value, &entry.name)
+                } else {
+                    entry.name == value
+                }
+            }
+            TextField::Ext => equals(entry.path.extension().and_then(|ext| ext.to_str())),
+            TextField::Language => equals(enhanced.and_then(|info| info.language.as_deref())),
+            TextField::Framework => equals(enhanced.and_then(|info| info.framework.as_deref())),
+            TextField::Purpose => enhanced
+                .and_then(|info| info.purpose.as_deref())
+                .is_some_and(|purpose| purpose.to_lowercase().contains(&value.to_lowercase())),
+            TextField::Type => match value {
+                "dir" | "directory" => entry.is_dir,
+                "file" => !entry.is_dir,
+                _ => false,
+            },
+        }
+    }
+
+    fn numeric_value(field: NumericField, entry: &FileEntry) -> Option<f64> {
+        let enhanced = entry.enhanced_info.as_ref();
+        match field {
+            NumericField::Complexity => enhanced.and_then(|info| info.complexity_score),
+            NumericField::Importance => enhanced.and_then(|info| info.importance_score),
+            NumericField::Percentile => enhanced.and_then(|info| info.complexity_percentile),
+            NumericField::Size => Some(entry.size as f64),
+            NumericField::Lines => enhanced.and_then(|info| info.line_count).map(|lines| lines as f64),
+        }
+    }
+}
+
+impl std::str::FromStr for FilterExpr {
+    type Err = ScanError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::parse(input)
+    }
+}
+
+impl CompareOp {
+    fn apply(self, actual: f64, expected: f64) -> bool {
+        match self {
+            CompareOp::Eq => actual == expected,
+            CompareOp::Ne => actual != expected,
+            CompareOp::Gt => actual > expected,
+            CompareOp::Ge => actual >= expected,
+            CompareOp::Lt => actual < expected,
+            CompareOp::Le => actual <= expected,
+        }
+    }
+}
+
+impl ScanResult {
+    pub fn query(&self, expression: &str) -> Result<Vec<&FileEntry>, ScanError> {
+        let expr = FilterExpr::parse(expression)?;
+        Ok(self.filter(|entry| expr.matches(entry, &self.root_path)))
+    }
+
+    // Keeps only the entries matching `expr`, e.g. for `--filter` on scan output
+    pub fn retain_matching(&mut self, expr: &FilterExpr) {
+        let root = self.root_path.clone();
+        self.files.retain(|entry| expr.matches(entry, &root));
+    }
+}
+
+fn invalid(message: String) -> ScanError {
+    ScanError::InvalidFilter { message }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ScanError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+        match ch {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            ':' => {
+                tokens.push(Token::Colon);
+                i += 1;
+            }
+            '"' | '\'' => {
+                let end = chars[i + 1..].iter().position(|&c| c == ch)
+                    .ok_or_else(|| invalid(format!("unterminated string starting at column {}", i + 1)))?;
+                tokens.push(Token::Quoted(chars[i + 1..i + 1 + end].iter().collect()));
+                i += end + 2;
+            }
+            '>' | '<' | '=' | '!' => {
+                let next_is_eq = chars.get(i + 1) == Some(&'=');
+                let op = match (ch, next_is_eq) {
+                    ('>', true) => CompareOp::Ge,
+                    ('>', false) => CompareOp::Gt,
+                    ('<', true) => CompareOp::Le,
+                    ('<', false) => CompareOp::Lt,
+                    ('!', true) => CompareOp::Ne,
+                    ('=', _) => CompareOp::Eq,
+                    _ => return Err(invalid(format!("unexpected '!' at column {}", i + 1))),
+                };
+                tokens.push(Token::Op(op));
+                i += if next_is_eq { 2 } else { 1 };
+            }
+            _ => {
+                let start = i;
+                while i < chars.len() && !chars[i].is_whitespace() && !"():<>=!\"'".contains(chars[i]) {
+                    i += 1;
+                }
+                tokens.push(Token::Word(chars[start..i].iter().collect()));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize,
+    terms: usize,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.position), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn enter(&mut self) -> Result<(), ScanError> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            return Err(invalid(format!("expression nests deeper than {} levels", MAX_NESTING)));
+        }
+        Ok(())
+    }
+
+    fn parse_or(&mut self) -> Result<FilterExpr, ScanError> {
+        let mut expr = self.parse_and()?;
+        while self.peek_keyword("OR") {
+            self.position += 1;
+            expr = FilterExpr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    // Adjacent terms without an operator are ANDed together
+    fn parse_and(&mut self) -> Result<FilterExpr, ScanError> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            if self.peek_keyword("AND") {
+                self.position += 1;
+            } else if self.peek_keyword("OR") || matches!(self.tokens.get(self.position), None | Some(Token::RParen)) {
+                break;
+            }
+            expr = FilterExpr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<FilterExpr, ScanError> {
+        if self.peek_keyword("NOT") {
+            self.position += 1;
+            self.enter()?;
+            let inner = self.parse_unary()?;
+            self.depth -= 1;
+            return Ok(FilterExpr::Not(Box::new(inner)));
+        }
+        match self.next() {
+            Some(Token::LParen) => {
+                self.enter()?;
+                let expr = self.parse_or()?;
+                self.depth -= 1;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err(invalid("missing closing ')'".to_string())),
+                }
+            }
+            Some(Token::Word(field)) => self.parse_term(&field),
+            Some(token) => Err(invalid(format!("expected a field, found {:?}", token))),
+            None => Err(invalid("unexpected end of expression".to_string())),
+        }
+    }
+
+    fn parse_term(&mut self, field: &str) -> Result<FilterExpr, ScanError> {
+        self.terms += 1;
+        if self.terms > MAX_TERMS {
+            return Err(invalid(format!("expression has more than {} terms", MAX_TERMS)));
+        }
+        let field_name = field.to_lowercase();
+        let operator = self.next();
+        let value = match self.next() {
+            Some(Token::Word(value)) | Some(Token::Quoted(value)) => value,
+            _ => return Err(invalid(format!("missing value for '{}'", field))),
+        };
+
+        if let Some(numeric) = numeric_field(&field_name) {
+            let op = match operator {
+                Some(Token::Op(op)) => op,
+                Some(Token::Colon) => CompareOp::Eq,
+                _ => return Err(invalid(format!("expected a comparison after '{}'", field))),
+            };
+            let value = parse_number(&value)
+                .ok_or_else(|| invalid(format!("'{}' is not a number for '{}'", value, field)))?;
+            return Ok(FilterExpr::Compare { field: numeric, op, value });
+        }
+
+        let text = text_field(&field_name)
+            .ok_or_else(|| invalid(format!("unknown field '{}'", field)))?;
+        match operator {
+            Some(Token::Colon) | Some(Token::Op(CompareOp::Eq)) => Ok(FilterExpr::Text { field: text, value }),
+            Some(Token::Op(CompareOp::Ne)) => Ok(FilterExpr::Not(Box::new(FilterExpr::Text { field: text, value }))),
+            _ => Err(invalid(format!("'{}' only supports ':', '=' and '!='", field))),
+        }
+    }
+}
+
+fn text_field(name: &str) -> Option<TextField> {
+    Some(match name {
+        "tag" => TextField::Tag,
+        "path" => TextField::Path,
+        "name" => TextField::Name,
+        "ext" | "extension" => TextField::Ext,
+        "lang" | "language" => TextField::Language,
+        "framework" => TextField::Framework,
+        "purpose" => TextField::Purpose,
+        "type" => TextField::Type,
+        _ => return None,
+    })
+}
+
+fn numeric_field(name: &str) -> Option<NumericField> {
+    Some(match name {
+        "complexity" => NumericField::Complexity,
+        "importance" => NumericField::Importance,
+        "percentile" => NumericField::Percentile,
+        "size" => NumericField::Size,
+        "lines" => NumericField::Lines,
+        _ => return None,
+    })
+}
+
+// A byte count, optionally with a unit suffix such as 512kb or 2MB
+pub fn parse_size(value: &str) -> Option<u64> {
+    parse_number(value).filter(|size| size.is_finite() && *size >= 0.0).map(|size| size as u64)
+}
+
+// Accepts plain numbers and sizes with a unit suffix such as 10kb or 2MB
+fn parse_number(value: &str) -> Option<f64> {
+    let lower = value.to_lowercase();
+    let (number, multiplier) = [("kb", 1024.0), ("mb", 1024.0 * 1024.0), ("gb", 1024.0 * 1024.0 * 1024.0), ("b", 1.0)]
+        .iter()
+        .find_map(|(suffix, multiplier)| lower.strip_suffix(suffix).map(|number| (number.to_string(), *multiplier)))
+        .unwrap_or((lower, 1.0));
+    number.parse::<f64>().ok().map(|number| number * multiplier)
+}
+
+fn has_wildcards(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+#[derive(Clone, Copy)]
+enum GlobToken {
+    Char(char),
+    // `?`
+    Any,
+    // `*`
+    Star,
+    // `**`
+    GlobStar,
+    // `**/`, which may also match zero directories
+    GlobStarSlash,
+}
+
+fn glob_tokens(pattern: &str) -> Vec<GlobToken> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let token = match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') && chars.get(i + 2) == Some(&'/') => {
+                i += 2;
+                GlobToken::GlobStarSlash
+            }
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                i += 1;
+                GlobToken::GlobStar
+            }
+            '*' => GlobToken::Star,
+            '?' => GlobToken::Any,
+            c => GlobToken::Char(c),
+        };
+        tokens.push(token);
+        i += 1;
+    }
+    tokens
+}
+
+// `*` matches within a path segment, `**` across segments, `?` any single character.
+// One row per pattern token over the text, so matching takes O(pattern × text)
+// however many stars the pattern has.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let text: Vec<char> = text.chars().collect();
+    // matched[j]: the tokens so far match the first j characters
+    let mut matched = vec![false; text.len() + 1];
+    matched[0] = true;
+    for token in glob_tokens(pattern) {
+        let mut next = vec![false; text.len() + 1];
+        // Whether any shorter prefix matched, for `**/`
+        let mut earlier = false;
+        for j in 0..=text.len() {
+            let previous = j.checked_sub(1).map(|k| text[k]);
+            next[j] = match token {
+                GlobToken::Char(c) => previous == Some(c) && matched[j - 1],
+                GlobToken::Any => previous.is_some_and(|c| c != '/') && matched[j - 1],
+                GlobToken::Star => matched[j] || (previous.is_some_and(|c| c != '/') && next[j - 1]),
+                GlobToken::GlobStar => matched[j] || (j > 0 && next[j - 1]),
+                GlobToken::GlobStarSlash => matched[j] || (previous == Some('/') && earlier),
+            };
+            earlier |= matched[j];
+        }
+        matched = next;
+    }
+    matched[text.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, tags: &[&str], language: &str, complexity: f64) -> FileEntry {
+        FileEntry::test(path)
+            .with_size(2048)
+            .with_tags(tags)
+            .with_language(language)
+            .with_complexity(Some(complexity))
+    }
+
+    fn matching(expression: &str, files: &[FileEntry]) -> Vec<String> {
+        let expr = FilterExpr::parse(expression).unwrap();
+        files.iter()
+            .filter(|file| expr.matches(file, Path::new("repo")))
+            .map(|file| file.name.clone())
+            .collect()
+    }
+
+    fn files() -> Vec<FileEntry> {
+        vec![
+            entry("repo/src/lib.rs", &["source"], "rust", 8.0),
+            entry("repo/src/util/strings.rs", &["source"], "rust", 2.0),
+            entry("repo/tests/test_api.py", &["source", "test"], "python", 6.0),
+            entry("repo/web/app.ts", &["source"], "typescript", 5.5),
+        ]
+    }
+
+    #[test]
+    fn test_and_or_not_and_grouping() {
+        let files = files();
+
+        assert_eq!(matching("tag:source AND complexity>5 AND path:src/**", &files), vec!["lib.rs"]);
+        assert_eq!(matching("(lang:rust OR lang:python) AND NOT tag:test", &files), vec!["lib.rs", "strings.rs"]);
+        assert_eq!(matching("complexity>=5.5 complexity<8", &files), vec!["test_api.py", "app.ts"]);
+        assert_eq!(matching("lang!=rust AND size>1kb", &files), vec!["test_api.py", "app.ts"]);
+    }
+
+    #[test]
+    fn test_pathological_glob_is_linear() {
+        let pattern = format!("{}*b", "*a".repeat(12));
+        let text = "a".repeat(200);
+
+        let start = std::time::Instant::now();
+        assert!(!glob_match(&pattern, &text));
+        assert!(glob_match(&pattern, &format!("{}b", text)));
+        assert!(!glob_match(&"**/a".repeat(12), &"a/".repeat(100)));
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_glob_patterns() {
+        assert!(glob_match("src/**", "src/util/strings.rs"));
+        assert!(glob_match("**/test_*.py", "test_api.py"));
+        assert!(glob_match("**/test_*.py", "tests/unit/test_api.py"));
+        assert!(glob_match("src/*.rs", "src/lib.rs"));
+        assert!(!glob_match("src/*.rs", "src/util/strings.rs"));
+        assert!(glob_match("name?.ts", "name1.ts"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(FilterExpr::parse("colour:red"), Err(ScanError::InvalidFilter { .. })));
+        assert!(FilterExpr::parse("complexity>high").is_err());
+        assert!(FilterExpr::parse("(tag:source").is_err());
+        assert!(FilterExpr::parse("tag:").is_err());
+        assert!(FilterExpr::parse("path:\"unterminated").is_err());
+    }
+
+    #[test]
+    fn test_nesting_and_term_limits() {
+        let nested = |depth: usize| format!("{}tag:source{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(FilterExpr::parse(&nested(MAX_NESTING)).is_ok());
+        assert!(matches!(FilterExpr::parse(&nested(20_000)), Err(ScanError::InvalidFilter { .. })));
+
+        let negated = |depth: usize| format!("{}tag:source", "NOT ".repeat(depth));
+        assert!(FilterExpr::parse(&negated(MAX_NESTING)).is_ok());
+        assert!(matches!(FilterExpr::parse(&negated(20_000)), Err(ScanError::InvalidFilter { .. })));
+
+        let chained = |terms: usize| vec!["tag:source"; terms].join(" AND ");
+        assert_eq!(matching(&chained(MAX_TERMS), &files()).len(), 4);
+        assert!(matches!(FilterExpr::parse(&chained(20_000)), Err(ScanError::InvalidFilter { .. })));
+    }
+}
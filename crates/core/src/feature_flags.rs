@@ -0,0 +1,287 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use crate::endpoints::{is_comment, string_literal};
+use crate::retrieval::terms;
+use crate::{format_rfc3339, FileSystem, ScanResult};
+
+const MAX_SOURCE_SIZE: u64 = 1024 * 1024;
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "rs", "py", "rb", "js", "jsx", "mjs", "cjs", "ts", "tsx", "java", "kt", "go", "php", "cs", "swift", "scala",
+];
+// A flag whose every use sits in files unchanged for this long looks forgotten
+const UNTOUCHED_AFTER: Duration = Duration::from_secs(180 * 24 * 60 * 60);
+// Words in a flag name that say it was meant to go away
+const RETIRED_WORDS: &[&str] = &["old", "legacy", "deprecated", "temp", "tmp", "temporary", "unused", "remove"];
+const MAX_NAME_LEN: usize = 100;
+
+// Calls taking a flag name, by the SDK they belong to. LaunchDarkly's and
+// Unleash's only count in files that mention the SDK; Unleash's is_enabled is
+// otherwise taken for a homemade check.
+const CALLS: &[(&str, FlagProvider)] = &[
+    ("variation(", FlagProvider::LaunchDarkly),
+    ("variationDetail(", FlagProvider::LaunchDarkly),
+    ("variation_detail(", FlagProvider::LaunchDarkly),
+    ("boolVariation(", FlagProvider::LaunchDarkly),
+    ("bool_variation(", FlagProvider::LaunchDarkly),
+    ("stringVariation(", FlagProvider::LaunchDarkly),
+    ("string_variation(", FlagProvider::LaunchDarkly),
+    ("intVariation(", FlagProvider::LaunchDarkly),
+    ("int_variation(", FlagProvider::LaunchDarkly),
+    ("numberVariation(", FlagProvider::LaunchDarkly),
+    ("doubleVariation(", FlagProvider::LaunchDarkly),
+    ("float_variation(", FlagProvider::LaunchDarkly),
+    ("jsonVariation(", FlagProvider::LaunchDarkly),
+    ("json_variation(", FlagProvider::LaunchDarkly),
+    ("isEnabled(", FlagProvider::Unleash),
+    ("is_enabled(", FlagProvider::Unleash),
+    ("useFlag(", FlagProvider::Unleash),
+    ("getVariant(", FlagProvider::Unleash),
+    ("get_variant(", FlagProvider::Unleash),
+    ("Flipper.enabled?(", FlagProvider::Flipper),
+    ("flag_is_active(", FlagProvider::Waffle),
+    ("switch_is_active(", FlagProvider::Waffle),
+    ("feature_enabled(", FlagProvider::Custom),
+    ("featureEnabled(", FlagProvider::Custom),
+    ("is_feature_enabled(", FlagProvider::Custom),
+    ("isFeatureEnabled(", FlagProvider::Custom),
+    ("feature_flags.contains(", FlagProvider::Custom),
+    ("beta_features.enabled(", FlagProvider::Custom),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FlagProvider {
+    LaunchDarkly,
+    Unleash,
+    Flipper,
+    Waffle,
+    // is_enabled("..."), feature_enabled("...") and similar homemade checks
+    Custom,
+}
+
+impl fmt::Display for FlagProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FlagProvider::LaunchDarkly => "launchdarkly",
+            FlagProvider::Unleash => "unleash",
+            FlagProvider::Flipper => "flipper",
+            FlagProvider::Waffle => "waffle",
+            FlagProvider::Custom => "custom",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StaleReason {
+    // The name holds a year before last year, e.g. "new_checkout_2021"
+    DatedName,
+    // The name says it was temporary, e.g. "legacy_search" or "tmp_banner"
+    RetiredName,
+    // Every file using it has been unchanged for half a year
+    Untouched,
+}
+
+impl fmt::Display for StaleReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            StaleReason::DatedName => "dated-name",
+            StaleReason::RetiredName => "retired-name",
+            StaleReason::Untouched => "untouched",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlagLocation {
+    pub path: PathBuf,
+    // 1-based
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureFlag {
+    pub name: String,
+    pub provider: FlagProvider,
+    pub locations: Vec<FlagLocation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale: Option<StaleReason>,
+}
+
+// Every feature flag the source checks, by name, and how many look stale
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureFlagReport {
+    pub flags: Vec<FeatureFlag>,
+    pub stale_count: usize,
+}
+
+impl FeatureFlagReport {
+    // Flag checks in the scanned source files, read through `fs`. Staleness is
+    // judged against `now`; files whose modification time is unknown never
+    // count as untouched.
+    pub fn compute(result: &ScanResult, fs: &dyn FileSystem, now: SystemTime) -> Self {
+        let mut flags: BTreeMap<String, FeatureFlag> = BTreeMap::new();
+        let mut untouched: BTreeMap<String, bool> = BTreeMap::new();
+        for file in result.files.iter().filter(|file| !file.is_dir && file.size <= MAX_SOURCE_SIZE) {
+            let extension = file.path.extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+            if !SOURCE_EXTENSIONS.contains(&extension) {
+                continue;
+            }
+            let Ok(content) = fs.read_to_string(&file.path) else { continue };
+            let file_untouched = file.modified != SystemTime::UNIX_EPOCH
+                && now.duration_since(file.modified).is_ok_and(|age| age >= UNTOUCHED_AFTER);
+            for (name, provider, line) in flag_checks(&content) {
+                let flag = flags.entry(name.clone()).or_insert_with(|| FeatureFlag {
+                    name: name.clone(),
+                    provider,
+                    locations: Vec::new(),
+                    stale: None,
+                });
+                flag.locations.push(FlagLocation { path: file.path.clone(), line });
+                *untouched.entry(name).or_insert(true) &= file_untouched;
+            }
+        }
+
+        let this_year: i64 = format_rfc3339(now)[..4].parse().unwrap_or_default();
+        let mut flags: Vec<FeatureFlag> = flags.into_values().collect();
+        for flag in &mut flags {
+            flag.stale = stale_reason(&flag.name, this_year, untouched[&flag.name]);
+        }
+        let stale_count = flags.iter().filter(|flag| flag.stale.is_some()).count();
+        FeatureFlagReport { flags, stale_count }
+    }
+
+    pub fn stale(&self) -> impl Iterator<Item = &FeatureFlag> {
+        self.flags.iter().filter(|flag| flag.stale.is_some())
+    }
+}
+
+// (name, provider, 1-based line) of each flag check in `content`
+fn flag_checks(content: &str) -> Vec<(String, FlagProvider, usize)> {
+    let lowercase = content.to_lowercase();
+    let launchdarkly = lowercase.contains("launchdarkly") || lowercase.contains("ldclient");
+    let unleash = lowercase.contains("unleash");
+    let waffle = lowercase.contains("waffle");
+
+    let mut checks = Vec::new();
+    let lines = content.lines().enumerate().filter(|(_, line)| !is_comment(line) && !line.trim_start().starts_with('#'));
+    for (index, line) in lines {
+        for (call, provider) in CALLS {
+            let provider = match provider {
+                FlagProvider::LaunchDarkly if !launchdarkly => continue,
+                FlagProvider::Waffle if !waffle => continue,
+                FlagProvider::Unleash if !unleash => match *call {
+                    "isEnabled(" | "is_enabled(" => FlagProvider::Custom,
+                    _ => continue,
+                },
+                provider => *provider,
+            };
+            for (position, _) in line.match_indices(call) {
+                let starts_word = line[..position].chars().next_back().is_none_or(|c| !(c.is_alphanumeric() || c == '_'));
+                if !starts_word {
+                    continue;
+                }
+                let arguments = &line[position + call.len()..];
+                let arguments = arguments.split(')').next().unwrap_or_default();
+                if let Some(name) = flag_name(arguments) {
+                    checks.push((name, provider, index + 1));
+                }
+            }
+        }
+    }
+    checks
+}
+
+// The first quoted string of a call's arguments, or a leading Ruby symbol
+fn flag_name(arguments: &str) -> Option<String> {
+    let name = match arguments.trim_start().strip_prefix(':') {
+        Some(symbol) => symbol.split(|c: char| !(c.is_alphanumeric() || c == '_')).next()?.to_string(),
+        None => string_literal(arguments)?.0,
+    };
+    let valid = name.chars().any(char::is_alphanumeric)
+        && name.len() <= MAX_NAME_LEN
+        && name.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ':' | '/'));
+    valid.then_some(name)
+}
+
+fn stale_reason(name: &str, this_year: i64, untouched: bool) -> Option<StaleReason> {
+    let words = terms(&name.replace(['-', '.', ':', '/'], "_"));
+    let dated = words.iter()
+        .filter_map(|word| word.parse::<i64>().ok())
+        .any(|year| (2000..this_year - 1).contains(&year));
+    if dated {
+        Some(StaleReason::DatedName)
+    } else if words.iter().any(|word| RETIRED_WORDS.contains(&word.as_str())) {
+        Some(StaleReason::RetiredName)
+    } else if untouched {
+        Some(StaleReason::Untouched)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_rfc3339, DirectoryScanner, MemoryFileSystem, ScanOptions};
+
+    fn names(checks: &[(String, FlagProvider, usize)]) -> Vec<(&str, FlagProvider, usize)> {
+        checks.iter().map(|(name, provider, line)| (name.as_str(), *provider, *line)).collect()
+    }
+
+    #[test]
+    fn test_recognizes_sdk_and_homemade_checks() {
+        let launchdarkly = "import * as LaunchDarkly from 'launchdarkly-node-server-sdk';\nif (await client.variation('new-checkout', context, false)) {}\nconst theme = client.stringVariation(\"theme\", context, \"dark\");\n";
+        assert_eq!(names(&flag_checks(launchdarkly)), [
+            ("new-checkout", FlagProvider::LaunchDarkly, 2),
+            ("theme", FlagProvider::LaunchDarkly, 3),
+        ]);
+        // Without the SDK, variation() is somebody else's method
+        assert!(flag_checks("score.variation('x')").is_empty());
+
+        let unleash = "from UnleashClient import UnleashClient\nif client.is_enabled(\"search_v2\"):\n    pass\n";
+        assert_eq!(names(&flag_checks(unleash)), [("search_v2", FlagProvider::Unleash, 2)]);
+        assert!(flag_checks("// is_enabled(\"commented_out\")\n# is_enabled(\"also\")\nis_enabled(\"...\")\n").is_empty());
+        let homemade = "if flags.is_enabled(\"dark_mode\") && is_feature_enabled(\"beta\") && logger.is_enabled(Level::Debug) {}\n";
+        assert_eq!(names(&flag_checks(homemade)), [("dark_mode", FlagProvider::Custom, 1), ("beta", FlagProvider::Custom, 1)]);
+        assert_eq!(names(&flag_checks("if Flipper.enabled?(:search, current_user)\n")), [("search", FlagProvider::Flipper, 1)]);
+    }
+
+    #[test]
+    fn test_stale_reasons() {
+        assert_eq!(stale_reason("new_ui_2021", 2026, false), Some(StaleReason::DatedName));
+        assert_eq!(stale_reason("checkout-2025", 2026, false), None);
+        assert_eq!(stale_reason("legacySearch", 2026, false), Some(StaleReason::RetiredName));
+        assert_eq!(stale_reason("template_editor", 2026, false), None);
+        assert_eq!(stale_reason("dark_mode", 2026, true), Some(StaleReason::Untouched));
+        assert_eq!(stale_reason("dark_mode", 2026, false), None);
+    }
+
+    #[test]
+    fn test_report_groups_uses_by_flag() {
+        let now = parse_rfc3339("2026-06-01T00:00:00Z").unwrap();
+        let long_ago = parse_rfc3339("2025-01-01T00:00:00Z").unwrap();
+        let mut fs = MemoryFileSystem::new();
+        fs.insert_with_modified("app/src/cart.rs", "if flags.is_enabled(\"fast_cart\") {}\nif flags.is_enabled(\"tmp_banner\") {}\n", now);
+        fs.insert_with_modified("app/src/pay.rs", "if flags.is_enabled(\"fast_cart\") {}\n", now);
+        fs.insert_with_modified("app/src/old.rs", "if feature_enabled(\"gift_cards\") {}\n", long_ago);
+        fs.insert("app/README.md", "Call `is_enabled(\"documented\")` to check a flag.\n");
+        let result = DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "app").unwrap();
+
+        let report = FeatureFlagReport::compute(&result, &fs, now);
+        let flags: Vec<&str> = report.flags.iter().map(|flag| flag.name.as_str()).collect();
+        assert_eq!(flags, ["fast_cart", "gift_cards", "tmp_banner"]);
+        assert_eq!(report.flags[0].locations.len(), 2);
+        assert_eq!(report.flags[0].stale, None);
+        assert_eq!(report.flags[1].stale, Some(StaleReason::Untouched));
+        assert_eq!(report.flags[2].locations[0].line, 2);
+        assert_eq!(report.stale_count, 2);
+        assert_eq!(report.stale().count(), 2);
+    }
+}
@@ -0,0 +1,635 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+use crate::explain::walk_skip_reason;
+use crate::symlink;
+use crate::{IgnoreStack, IssueKind, ScanError, ScanIssue, ScanOptions, Severity, SymlinkInfo};
+
+// Metadata for one walked entry, independent of where the bytes live
+#[derive(Debug, Clone, PartialEq)]
+pub struct FsEntry {
+    pub path: PathBuf,
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: SystemTime,
+    // Set for symbolic links on filesystems that have them
+    pub symlink: Option<SymlinkInfo>,
+    // Unix permission bits, on filesystems that have them
+    pub mode: Option<u32>,
+}
+
+// Source of entries and content for a scan: the local disk, an archive loaded
+// into memory, or files handed over by a host such as a browser
+pub trait FileSystem {
+    fn exists(&self, path: &Path) -> bool;
+
+    // Every non-ignored entry under `root`, including `root` itself
+    fn walk<'a>(&'a self, root: &Path, options: &'a ScanOptions) -> Box<dyn Iterator<Item = Result<FsEntry, ScanIssue>> + 'a>;
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    // At most the first `len` bytes, for identifying content by its header
+    fn read_prefix(&self, path: &Path, len: usize) -> io::Result<Vec<u8>> {
+        let mut bytes = self.read(path)?;
+        bytes.truncate(len);
+        Ok(bytes)
+    }
+}
+
+// Counts entries against `ScanOptions::max_entries_per_dir` and collects the
+// directories the walk limits cut short, reported as warnings once the walk ends
+#[derive(Debug, Default)]
+struct WalkLimits {
+    max_entries: Option<usize>,
+    entries: HashMap<PathBuf, usize>,
+    // Directory -> entries left out
+    capped: BTreeMap<PathBuf, usize>,
+    // Non-empty directories at the depth guard, with their depth
+    deep: Vec<(PathBuf, usize)>,
+}
+
+impl WalkLimits {
+    fn new(options: &ScanOptions) -> Self {
+        Self { max_entries: options.max_entries_per_dir, ..Default::default() }
+    }
+
+    // Counts an entry against its directory's cap; false once the cap is spent
+    fn admit(&mut self, path: &Path) -> bool {
+        let (Some(max), Some(parent)) = (self.max_entries, path.parent()) else {
+            return true;
+        };
+        let count = match self.entries.get_mut(parent) {
+            Some(count) => count,
+            None => self.entries.entry(parent.to_path_buf()).or_insert(0),
+        };
+        *count += 1;
+        if *count <= max {
+            return true;
+        }
+        *self.capped.entry(parent.to_path_buf()).or_insert(0) += 1;
+        false
+    }
+
+    fn issues(&mut self) -> Vec<ScanIssue> {
+        let max = self.max_entries.unwrap_or(0);
+        let deep = self.deep.drain(..).map(|(dir, depth)| ScanIssue::depth_limit(&dir, depth));
+        let capped = std::mem::take(&mut self.capped).into_iter().map(|(dir, skipped)| ScanIssue::entry_limit(&dir, max, skipped));
+        deep.chain(capped).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsFileSystem;
+
+impl OsFileSystem {
+    fn entry_from(entry: walkdir::DirEntry, root: &Path) -> Result<FsEntry, ScanIssue> {
+        let path = entry.path().to_path_buf();
+        let metadata = entry.metadata().map_err(|e| {
+            let error = match e.io_error() {
+                Some(io_err) => ScanError::Io(io::Error::new(io_err.kind(), format!("{}", e))),
+                None => ScanError::InvalidConfig { message: format!("Failed to get metadata: {}", e) },
+            };
+            ScanIssue::from_scan_error(&path, &error)
+        })?;
+
+        Ok(FsEntry {
+            name: entry.file_name().to_str().unwrap_or("unknown").to_string(),
+            is_dir: metadata.is_dir(),
+            size: if metadata.is_file() { metadata.len() } else { 0 },
+            modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            symlink: entry.path_is_symlink().then(|| Self::symlink_info(&path, root)).flatten(),
+            mode: mode_of(&metadata),
+            path,
+        })
+    }
+
+    fn symlink_info(path: &Path, root: &Path) -> Option<SymlinkInfo> {
+        symlink::inspect(path, root)
+            .inspect_err(|e| tracing::debug!(path = %path.display(), "cannot read symlink: {}", e))
+            .ok()
+    }
+
+    // Following links, walkdir fails on one whose target is missing; it is
+    // reported as a broken link instead of an unreadable file
+    fn broken_link(error: &walkdir::Error, root: &Path) -> Option<FsEntry> {
+        if error.io_error()?.kind() != io::ErrorKind::NotFound {
+            return None;
+        }
+        let path = error.path()?;
+        let metadata = fs::symlink_metadata(path).ok().filter(|metadata| metadata.is_symlink())?;
+        Some(FsEntry {
+            path: path.to_path_buf(),
+            name: path.file_name().and_then(|name| name.to_str()).unwrap_or("").to_string(),
+            is_dir: false,
+            size: 0,
+            modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            symlink: Self::symlink_info(path, root),
+            mode: mode_of(&metadata),
+        })
+    }
+}
+
+impl FileSystem for OsFileSystem {
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn walk<'a>(&'a self, root: &Path, options: &'a ScanOptions) -> Box<dyn Iterator<Item = Result<FsEntry, ScanIssue>> + 'a> {
+        let mut ignore_files = IgnoreStack::default();
+        let canonical_root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+        let limits = Rc::new(RefCell::new(WalkLimits::new(options)));
+        let counted = Rc::clone(&limits);
+        let reported = Rc::clone(&limits);
+        // Ignored directories are pruned rather than walked; the root itself is
+        // always descended into and only dropped from the results. Entries over
+        // a directory's cap are pruned the same way.
+        let walker = WalkDir::new(root)
+            .follow_links(options.follow_symlinks)
+            .max_depth(options.walk_depth())
+            .into_iter()
+            .filter_entry(move |entry| {
+                let is_dir = entry.file_type().is_dir();
+                if entry.depth() > 0 {
+                    let name = entry.file_name().to_str().unwrap_or("");
+                    if let Some(reason) = walk_skip_reason(options, &mut ignore_files, entry.path(), name, is_dir) {
+                        tracing::debug!(path = %entry.path().display(), "skipping entry: {}", reason);
+                        return false;
+                    }
+                    if !counted.borrow_mut().admit(entry.path()) {
+                        return false;
+                    }
+                }
+                if is_dir && options.use_ignore_files {
+                    ignore_files.enter(entry.path(), |file| fs::read_to_string(file).ok());
+                }
+                true
+            });
+
+        let entries = walker.filter_map(move |entry| match entry {
+            Ok(entry) if entry.depth() == 0 && options.is_ignored(entry.path(), entry.file_name().to_str().unwrap_or("")) => None,
+            Ok(entry) => {
+                let guarded = entry.file_type().is_dir() && options.stops_at_depth_guard(entry.depth());
+                if guarded && fs::read_dir(entry.path()).is_ok_and(|mut children| children.next().is_some()) {
+                    limits.borrow_mut().deep.push((entry.path().to_path_buf(), entry.depth()));
+                }
+                Some(Self::entry_from(entry, &canonical_root))
+            }
+            Err(e) => match Self::broken_link(&e, &canonical_root) {
+                Some(entry) => Some(Ok(entry)),
+                None => Some(Err(ScanIssue::from_walk_error(&e))),
+            },
+        });
+        let issues = std::iter::once_with(move || reported.borrow_mut().issues()).flatten().map(Err);
+        Box::new(entries.chain(issues))
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn read_prefix(&self, path: &Path, len: usize) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(len);
+        fs::File::open(path)?.take(len as u64).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(unix)]
+fn mode_of(metadata: &fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode())
+}
+
+#[cfg(not(unix))]
+fn mode_of(_metadata: &fs::Metadata) -> Option<u32> {
+    None
+}
+
+// Exactly the listed paths instead of a directory walk, e.g. the output of
+// `git diff --name-only`; content is read from disk. Relative paths are taken
+// relative to the scan root. Listed directories are reported but not descended into.
+#[derive(Debug, Clone, Default)]
+pub struct PathListFileSystem {
+    paths: BTreeSet<PathBuf>,
+}
+
+impl PathListFileSystem {
+    pub fn new<I: IntoIterator<Item = P>, P: Into<PathBuf>>(paths: I) -> Self {
+        Self { paths: paths.into_iter().map(Into::into).collect() }
+    }
+
+    // One path per line; surrounding whitespace and blank lines are ignored
+    pub fn from_lines(text: &str) -> Self {
+        Self::new(text.lines().map(str::trim).filter(|line| !line.is_empty()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    fn stat(path: &Path) -> Result<FsEntry, ScanIssue> {
+        let metadata = fs::metadata(path).map_err(|e| ScanIssue::from_scan_error(path, &ScanError::Io(e)))?;
+        Ok(FsEntry {
+            path: path.to_path_buf(),
+            name: path.file_name().and_then(|name| name.to_str()).unwrap_or("").to_string(),
+            is_dir: metadata.is_dir(),
+            size: if metadata.is_file() { metadata.len() } else { 0 },
+            modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            symlink: None,
+            mode: None,
+        })
+    }
+}
+
+impl FileSystem for PathListFileSystem {
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn walk<'a>(&'a self, root: &Path, options: &'a ScanOptions) -> Box<dyn Iterator<Item = Result<FsEntry, ScanIssue>> + 'a> {
+        let root_entry = Self::stat(root);
+        let listed: BTreeSet<PathBuf> = self.paths.iter()
+            .map(|path| if path.is_absolute() { path.clone() } else { root.join(path) })
+            .collect();
+        let root = root.to_path_buf();
+
+        Box::new(std::iter::once(root_entry).chain(listed.into_iter().filter_map(move |path| {
+            if !path.starts_with(&root) {
+                let message = format!("{} is outside the scan root {}", path.display(), root.display());
+                return Some(Err(ScanIssue::new(Some(path), IssueKind::Other, message, Severity::Warning)));
+            }
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+            (!options.is_ignored(&path, name)).then(|| Self::stat(&path))
+        })))
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MemoryFile {
+    content: Vec<u8>,
+    modified: SystemTime,
+}
+
+// In-memory file tree; directories are implied by the file paths
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFileSystem {
+    files: BTreeMap<PathBuf, MemoryFile>,
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert<P: Into<PathBuf>, C: Into<Vec<u8>>>(&mut self, path: P, content: C) {
+        self.insert_with_modified(path, content, SystemTime::UNIX_EPOCH);
+    }
+
+    pub fn insert_with_modified<P: Into<PathBuf>, C: Into<Vec<u8>>>(&mut self, path: P, content: C, modified: SystemTime) {
+        self.files.insert(path.into(), MemoryFile { content: content.into(), modified });
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.files.values().map(|file| file.content.len() as u64).sum()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.files.keys().any(|file| file != path && file.starts_with(path))
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn exists(&self, path: &Path) -> bool {
+        self.files.contains_key(path) || self.is_dir(path)
+    }
+
+    fn walk<'a>(&'a self, root: &Path, options: &'a ScanOptions) -> Box<dyn Iterator<Item = Result<FsEntry, ScanIssue>> + 'a> {
+        let max_depth = options.walk_depth();
+
+        let mut directories: BTreeSet<PathBuf> = BTreeSet::new();
+        if self.is_dir(root) {
+            directories.insert(root.to_path_buf());
+        }
+        for path in self.files.keys().filter(|path| path.starts_with(root)) {
+            let mut parent = path.parent();
+            while let Some(dir) = parent.filter(|dir| dir.starts_with(root)) {
+                directories.insert(dir.to_path_buf());
+                parent = dir.parent();
+            }
+        }
+
+        let name_of = |path: &Path| path.file_name().and_then(|name| name.to_str()).unwrap_or("").to_string();
+        let depth_of = |path: &Path| path.strip_prefix(root).map(|rest| rest.components().count()).unwrap_or(0);
+        // Directories at the depth guard with anything below them
+        let guarded: BTreeSet<PathBuf> = directories.iter().chain(self.files.keys().filter(|path| path.starts_with(root)))
+            .filter(|path| depth_of(path) > 0 && options.stops_at_depth_guard(depth_of(path) - 1))
+            .filter_map(|path| path.parent().map(Path::to_path_buf))
+            .collect();
+
+        let mut entries: Vec<FsEntry> = directories.into_iter()
+            .map(|path| FsEntry {
+                name: name_of(&path),
+                is_dir: true,
+                size: 0,
+                modified: SystemTime::UNIX_EPOCH,
+                symlink: None,
+                path,
+                mode: None,
+            })
+            .chain(self.files.iter()
+                .filter(|(path, _)| path.starts_with(root))
+                .map(|(path, file)| FsEntry {
+                    path: path.clone(),
+                    name: name_of(path),
+                    is_dir: false,
+                    size: file.content.len() as u64,
+                    modified: file.modified,
+                    symlink: None,
+                    mode: None,
+                }))
+            .filter(|entry| depth_of(&entry.path) <= max_depth)
+            .filter(|entry| !options.is_ignored(&entry.path, &entry.name))
+            .collect();
+        // Parents before children, as a directory walk would produce them
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+        if options.use_ignore_files {
+            let mut ignore_files = IgnoreStack::default();
+            let mut pruned: Option<PathBuf> = None;
+            entries.retain(|entry| {
+                if pruned.as_ref().is_some_and(|dir| entry.path.starts_with(dir)) {
+                    return false;
+                }
+                if entry.path != root && ignore_files.is_ignored(&entry.path, entry.is_dir) {
+                    pruned = entry.is_dir.then(|| entry.path.clone());
+                    return false;
+                }
+                if entry.is_dir {
+                    ignore_files.enter(&entry.path, |file| self.read_to_string(file).ok());
+                }
+                true
+            });
+        }
+
+        let mut limits = WalkLimits::new(options);
+        let mut pruned: Option<PathBuf> = None;
+        entries.retain(|entry| {
+            if entry.path == root || pruned.as_ref().is_some_and(|dir| entry.path.starts_with(dir)) {
+                return entry.path == root;
+            }
+            if !limits.admit(&entry.path) {
+                pruned = entry.is_dir.then(|| entry.path.clone());
+                return false;
+            }
+            if entry.is_dir && guarded.contains(&entry.path) {
+                limits.deep.push((entry.path.clone(), depth_of(&entry.path)));
+            }
+            true
+        });
+
+        let issues = limits.issues();
+        Box::new(entries.into_iter().map(Ok).chain(issues.into_iter().map(Err)))
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files.get(path)
+            .map(|file| file.content.clone())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} not found", path.display())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory_fs() -> MemoryFileSystem {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/src/main.rs", "fn main() {}");
+        fs.insert("app/src/util/mod.rs", "pub fn helper() {}");
+        fs.insert("app/.env", "SECRET=1");
+        fs.insert("app/README.md", "# App");
+        fs
+    }
+
+    #[test]
+    fn test_memory_walk_includes_implied_directories() {
+        let fs = memory_fs();
+        let options = ScanOptions::default();
+
+        let paths: Vec<PathBuf> = fs.walk(Path::new("app"), &options)
+            .map(|entry| entry.unwrap().path)
+            .collect();
+
+        assert_eq!(paths, vec![
+            PathBuf::from("app"),
+            PathBuf::from("app/README.md"),
+            PathBuf::from("app/src"),
+            PathBuf::from("app/src/main.rs"),
+            PathBuf::from("app/src/util"),
+            PathBuf::from("app/src/util/mod.rs"),
+        ]);
+    }
+
+    #[test]
+    fn test_memory_walk_respects_depth_and_hidden() {
+        let fs = memory_fs();
+        let options = ScanOptions { max_depth: Some(1), include_hidden: true, ..Default::default() };
+
+        let names: Vec<String> = fs.walk(Path::new("app"), &options)
+            .map(|entry| entry.unwrap().name)
+            .collect();
+
+        assert_eq!(names, vec!["app", ".env", "README.md", "src"]);
+    }
+
+    #[test]
+    fn test_walks_honor_projscanignore_files() {
+        let files = [
+            ("app/.projscanignore", "*.log\n"),
+            ("app/debug.log", ""),
+            ("app/team/.projscanignore", "fixtures/\n!keep.log\n"),
+            ("app/team/fixtures/data.json", "{}"),
+            ("app/team/keep.log", ""),
+            ("app/team/src/lib.rs", ""),
+        ];
+        let temp = tempfile::tempdir().unwrap();
+        let mut memory = MemoryFileSystem::new();
+        for (path, content) in files {
+            let on_disk = temp.path().join(path);
+            fs::create_dir_all(on_disk.parent().unwrap()).unwrap();
+            fs::write(on_disk, content).unwrap();
+            memory.insert(path, content);
+        }
+
+        // Directory listing order on disk is unspecified
+        let relative_paths = |fs: &dyn FileSystem, root: &Path, options: &ScanOptions| -> Vec<String> {
+            let mut paths: Vec<String> = fs.walk(&root.join("app"), options)
+                .map(|entry| entry.unwrap().path.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/"))
+                .collect();
+            paths.sort();
+            paths
+        };
+        let expected = vec!["app", "app/team", "app/team/keep.log", "app/team/src", "app/team/src/lib.rs"];
+        let options = ScanOptions::default();
+        assert_eq!(relative_paths(&OsFileSystem, temp.path(), &options), expected);
+        assert_eq!(relative_paths(&memory, Path::new(""), &options), expected);
+
+        let options = ScanOptions { use_ignore_files: false, ..Default::default() };
+        assert_eq!(relative_paths(&memory, Path::new(""), &options).len(), 8);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_tagged_and_broken_links_optional() {
+        use crate::DirectoryScanner;
+
+        let temp = tempfile::tempdir().unwrap();
+        fs::write(temp.path().join("lib.rs"), "fn main() {}").unwrap();
+        std::os::unix::fs::symlink("lib.rs", temp.path().join("alias.rs")).unwrap();
+        std::os::unix::fs::symlink("missing.rs", temp.path().join("broken.rs")).unwrap();
+        let names = |result: &crate::ScanResult| -> Vec<String> {
+            result.files.iter().filter(|file| file.symlink.is_some()).map(|file| file.name.clone()).collect()
+        };
+
+        for follow_symlinks in [false, true] {
+            let options = ScanOptions { follow_symlinks, ..Default::default() };
+            let result = DirectoryScanner::new(options).scan(temp.path()).unwrap();
+            assert_eq!(names(&result), ["alias.rs"]);
+            assert!(result.errors.is_empty());
+            let alias = result.find(temp.path().join("alias.rs")).unwrap();
+            assert!(alias.tags.contains(&"symlink".to_string()));
+            assert_eq!(alias.symlink, Some(SymlinkInfo { target: PathBuf::from("lib.rs"), resolves: true, outside_root: false }));
+
+            let options = ScanOptions { follow_symlinks, include_broken_symlinks: true, ..Default::default() };
+            let result = DirectoryScanner::new(options).scan(temp.path()).unwrap();
+            assert_eq!(names(&result), ["alias.rs", "broken.rs"]);
+        }
+    }
+
+    #[test]
+    fn test_memory_read() {
+        let fs = memory_fs();
+
+        assert!(fs.exists(Path::new("app/src")));
+        assert_eq!(fs.read_to_string(Path::new("app/README.md")).unwrap(), "# App");
+        assert_eq!(fs.read(Path::new("app/missing")).unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_path_list_walks_only_listed_paths() {
+        let temp = tempfile::tempdir().unwrap();
+        fs::create_dir_all(temp.path().join("src/nested")).unwrap();
+        fs::write(temp.path().join("src/a.rs"), "fn a() {}").unwrap();
+        fs::write(temp.path().join("src/b.rs"), "fn b() {}").unwrap();
+        fs::write(temp.path().join("src/nested/c.rs"), "fn c() {}").unwrap();
+
+        let list = PathListFileSystem::from_lines("src/b.rs\n\n  src/nested \nsrc/gone.rs\n/elsewhere/d.rs\nsrc/b.rs\n");
+        assert_eq!(list.len(), 4);
+
+        let entries: Vec<Result<FsEntry, ScanIssue>> = list.walk(temp.path(), &ScanOptions::default()).collect();
+        let walked: Vec<&Path> = entries.iter().filter_map(|entry| entry.as_ref().ok()).map(|entry| entry.path.as_path()).collect();
+        assert_eq!(walked, vec![temp.path(), &temp.path().join("src/b.rs"), &temp.path().join("src/nested")]);
+
+        let issues: Vec<&ScanIssue> = entries.iter().filter_map(|entry| entry.as_ref().err()).collect();
+        assert_eq!(issues.len(), 2);
+        assert!(issues.iter().any(|issue| issue.path.as_deref() == Some(temp.path().join("src/gone.rs").as_path())));
+        assert!(issues.iter().any(|issue| issue.message.contains("outside the scan root")));
+    }
+
+    #[test]
+    fn test_path_list_applies_ignore_patterns() {
+        let temp = tempfile::tempdir().unwrap();
+        fs::create_dir_all(temp.path().join("node_modules/pkg")).unwrap();
+        fs::write(temp.path().join("node_modules/pkg/index.js"), "").unwrap();
+        fs::write(temp.path().join("main.js"), "").unwrap();
+
+        let list = PathListFileSystem::new(["main.js", "node_modules/pkg/index.js"]);
+        let names: Vec<String> = list.walk(temp.path(), &ScanOptions::default()).map(|entry| entry.unwrap().name).collect();
+
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[1], "main.js");
+        assert_eq!(list.read_to_string(&temp.path().join("main.js")).unwrap(), "");
+    }
+
+    #[test]
+    fn test_entry_cap_and_depth_guard_warn_instead_of_walking_on() {
+        let mut fs = MemoryFileSystem::new();
+        for index in 0..5 {
+            fs.insert(format!("app/huge/{}/f.txt", index), "");
+        }
+        fs.insert("app/a/b/c/d/deep.txt", "");
+        fs.insert("app/a/b/shallow.txt", "");
+        let options = ScanOptions { max_entries_per_dir: Some(3), depth_guard: Some(3), ..Default::default() };
+
+        let entries: Vec<Result<FsEntry, ScanIssue>> = fs.walk(Path::new("app"), &options).collect();
+        let walked: Vec<&Path> = entries.iter().filter_map(|entry| entry.as_ref().ok()).map(|entry| entry.path.as_path()).collect();
+        assert_eq!(walked.iter().filter(|path| path.starts_with("app/huge") && path.components().count() == 3).count(), 3);
+        assert!(walked.contains(&Path::new("app/a/b/shallow.txt")) && !walked.contains(&Path::new("app/a/b/c/d")));
+
+        let issues: Vec<(IssueKind, &Path)> = entries.iter().filter_map(|entry| entry.as_ref().err())
+            .map(|issue| (issue.kind, issue.path.as_deref().unwrap()))
+            .collect();
+        assert_eq!(issues, [(IssueKind::DepthLimit, Path::new("app/a/b/c")), (IssueKind::EntryLimit, Path::new("app/huge"))]);
+        assert!(entries.iter().any(|entry| entry.as_ref().err().is_some_and(|issue| issue.message.contains("2 more were not scanned"))));
+    }
+
+    #[test]
+    fn test_os_walk_applies_the_same_limits() {
+        let temp = tempfile::tempdir().unwrap();
+        fs::create_dir_all(temp.path().join("a/b/c")).unwrap();
+        fs::write(temp.path().join("a/b/c/deep.txt"), "").unwrap();
+        fs::create_dir(temp.path().join("many")).unwrap();
+        for index in 0..4 {
+            fs::write(temp.path().join(format!("many/file{}.txt", index)), "").unwrap();
+        }
+        let options = ScanOptions { max_entries_per_dir: Some(2), depth_guard: Some(2), ..Default::default() };
+
+        let entries: Vec<Result<FsEntry, ScanIssue>> = OsFileSystem.walk(temp.path(), &options).collect();
+        let issues: Vec<&ScanIssue> = entries.iter().filter_map(|entry| entry.as_ref().err()).collect();
+        assert_eq!(issues.len(), 2);
+        assert!(issues.iter().all(|issue| issue.severity == Severity::Warning));
+        assert!(issues.iter().any(|issue| issue.kind == IssueKind::EntryLimit && issue.path == Some(temp.path().join("many"))));
+        assert!(issues.iter().any(|issue| issue.kind == IssueKind::DepthLimit && issue.path == Some(temp.path().join("a/b"))));
+        // Unlimited, the same tree walks whole
+        let unlimited = ScanOptions { max_entries_per_dir: None, depth_guard: None, ..Default::default() };
+        assert_eq!(OsFileSystem.walk(temp.path(), &unlimited).filter(|entry| entry.as_ref().is_ok_and(|entry| !entry.is_dir)).count(), 5);
+        // max_depth below the guard stops the walk silently
+        let shallow = ScanOptions { max_depth: Some(1), depth_guard: Some(2), max_entries_per_dir: None, ..Default::default() };
+        assert!(OsFileSystem.walk(temp.path(), &shallow).all(|entry| entry.is_ok()));
+    }
+}
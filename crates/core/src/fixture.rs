@@ -0,0 +1,310 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{MemoryFileSystem, ScanError};
+
+// Languages a fixture can mix, with their file extensions
+pub const FIXTURE_LANGUAGES: [(&str, &str); 7] = [
+    ("rust", "rs"),
+    ("python", "py"),
+    ("javascript", "js"),
+    ("typescript", "ts"),
+    ("go", "go"),
+    ("markdown", "md"),
+    ("json", "json"),
+];
+// Directory, file and function names are built from these
+const WORDS: [&str; 16] = [
+    "core", "api", "util", "model", "service", "handler", "config", "store",
+    "parser", "client", "server", "cache", "auth", "report", "queue", "index",
+];
+
+// Shape of a synthetic source tree. The same spec always generates the same
+// tree, byte for byte, on every platform.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FixtureSpec {
+    pub seed: u64,
+    pub files: usize,
+    // Directory levels below the root a file may sit at
+    pub max_depth: usize,
+    // Subdirectories per directory
+    pub fan_out: usize,
+    // Relative weight of each language, by the names in `FIXTURE_LANGUAGES`
+    pub languages: BTreeMap<String, u32>,
+    // Lines per file, inclusive
+    pub min_lines: usize,
+    pub max_lines: usize,
+}
+
+impl Default for FixtureSpec {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            files: 100,
+            max_depth: 3,
+            fan_out: 4,
+            languages: BTreeMap::from([
+                ("rust".to_string(), 3),
+                ("python".to_string(), 2),
+                ("javascript".to_string(), 2),
+                ("typescript".to_string(), 1),
+                ("markdown".to_string(), 1),
+                ("json".to_string(), 1),
+            ]),
+            min_lines: 10,
+            max_lines: 200,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixtureFile {
+    // Relative to the fixture root
+    pub path: PathBuf,
+    pub language: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Fixture {
+    pub files: Vec<FixtureFile>,
+}
+
+impl Fixture {
+    pub fn generate(spec: &FixtureSpec) -> Result<Self, ScanError> {
+        let invalid = |message: String| ScanError::InvalidConfig { message };
+        if spec.min_lines > spec.max_lines {
+            return Err(invalid(format!("min_lines {} is above max_lines {}", spec.min_lines, spec.max_lines)));
+        }
+        if spec.max_depth > 0 && spec.fan_out == 0 {
+            return Err(invalid("fan_out must be at least 1 when max_depth is above 0".to_string()));
+        }
+        let mut languages = Vec::new();
+        for (name, &weight) in &spec.languages {
+            let Some(&(language, extension)) = FIXTURE_LANGUAGES.iter().find(|(language, _)| language == name) else {
+                let known: Vec<&str> = FIXTURE_LANGUAGES.iter().map(|(language, _)| *language).collect();
+                return Err(invalid(format!("unknown fixture language '{}'; expected one of {}", name, known.join(", "))));
+            };
+            if weight > 0 {
+                languages.push((language, extension, weight as u64));
+            }
+        }
+        let total_weight: u64 = languages.iter().map(|(_, _, weight)| weight).sum();
+        if total_weight == 0 && spec.files > 0 {
+            return Err(invalid("the language mix needs at least one language with a weight above 0".to_string()));
+        }
+
+        let mut rng = SplitMix64(spec.seed);
+        let files = (0..spec.files)
+            .map(|index| {
+                let mut pick = rng.next() % total_weight;
+                let &(language, extension, _) = languages.iter()
+                    .find(|(_, _, weight)| {
+                        let found = pick < *weight;
+                        pick = pick.saturating_sub(*weight);
+                        found
+                    })
+                    .expect("pick is below the total weight");
+                let mut path = PathBuf::new();
+                for _ in 0..rng.between(0, spec.max_depth) {
+                    let child = rng.below(spec.fan_out);
+                    path.push(numbered(WORDS[child % WORDS.len()], child / WORDS.len()));
+                }
+                path.push(format!("{}_{}.{}", WORDS[rng.below(WORDS.len())], index, extension));
+                let lines = rng.between(spec.min_lines, spec.max_lines);
+                FixtureFile { path, language: language.to_string(), content: source(language, lines, &mut rng) }
+            })
+            .collect();
+        Ok(Self { files })
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(|file| file.content.len() as u64).sum()
+    }
+
+    // Directories holding files, below the root
+    pub fn directories(&self) -> BTreeSet<PathBuf> {
+        self.files.iter()
+            .flat_map(|file| file.path.ancestors().skip(1))
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(Path::to_path_buf)
+            .collect()
+    }
+
+    // The tree under `root` in memory, for tests that scan without touching disk
+    pub fn to_memory(&self, root: &Path) -> MemoryFileSystem {
+        let mut fs = MemoryFileSystem::new();
+        for file in &self.files {
+            fs.insert(root.join(&file.path), file.content.as_bytes());
+        }
+        fs
+    }
+
+    // Writes the tree under `dir`, creating directories as needed
+    pub fn write_to(&self, dir: &Path) -> io::Result<()> {
+        for file in &self.files {
+            let path = dir.join(&file.path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, &file.content)?;
+        }
+        Ok(())
+    }
+}
+
+// Directory names past the word list get a number, e.g. "core_1"
+fn numbered(word: &str, number: usize) -> String {
+    if number == 0 { word.to_string() } else { format!("{}_{}", word, number) }
+}
+
+// SplitMix64: tiny and the same on every platform, which is all a fixture needs
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in 0..n; 0 when n is 0
+    fn below(&mut self, n: usize) -> usize {
+        if n == 0 { 0 } else { (self.next() % n as u64) as usize }
+    }
+
+    // Uniform in low..=high
+    fn between(&mut self, low: usize, high: usize) -> usize {
+        low + self.below(high - low + 1)
+    }
+}
+
+// `lines` lines of plausible source: a header comment, whole functions or
+// sections while they fit, then comment lines to make up the count
+fn source(language: &str, lines: usize, rng: &mut SplitMix64) -> String {
+    let mut word = || WORDS[rng.below(WORDS.len())];
+    let mut out: Vec<String> = Vec::with_capacity(lines);
+    match language {
+        "json" => {
+            if lines < 2 {
+                return "{}\n".repeat(lines);
+            }
+            out.push("{".to_string());
+            let entries = lines - 2;
+            for index in 0..entries {
+                let comma = if index + 1 < entries { "," } else { "" };
+                out.push(format!("  \"{}_{}\": \"{}\"{}", word(), index, word(), comma));
+            }
+            out.push("}".to_string());
+        }
+        _ => {
+            let comment = match language {
+                "python" => "#",
+                "markdown" => "",
+                _ => "//",
+            };
+            out.push(match language {
+                "markdown" => format!("# {} {}", capitalized(word()), word()),
+                "go" => format!("package {}", word()),
+                _ => format!("{} Generated {} fixture", comment, word()),
+            });
+            let mut index = 0;
+            loop {
+                let block = block(language, &format!("{}_{}", word(), index), index + 1);
+                if out.len() + block.len() > lines {
+                    break;
+                }
+                out.extend(block);
+                index += 1;
+            }
+            while out.len() < lines {
+                let text = format!("{} {} {}", word(), word(), word());
+                out.push(if comment.is_empty() { text } else { format!("{} {}", comment, text) });
+            }
+        }
+    }
+    out.truncate(lines);
+    out.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+fn capitalized(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map(|first| first.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+}
+
+// One function (or markdown section) with a branch, after a blank line
+fn block(language: &str, name: &str, n: usize) -> Vec<String> {
+    let text = match language {
+        "rust" => format!("\npub fn {name}(value: u64) -> u64 {{\n    if value > {n} {{\n        value - {n}\n    }} else {{\n        value + {n}\n    }}\n}}"),
+        "python" => format!("\ndef {name}(value):\n    if value > {n}:\n        return value - {n}\n    return value + {n}"),
+        "javascript" => format!("\nexport function {name}(value) {{\n  if (value > {n}) {{\n    return value - {n};\n  }}\n  return value + {n};\n}}"),
+        "typescript" => format!("\nexport function {name}(value: number): number {{\n  if (value > {n}) {{\n    return value - {n};\n  }}\n  return value + {n};\n}}"),
+        "go" => format!("\nfunc {}(value int) int {{\n\tif value > {n} {{\n\t\treturn value - {n}\n\t}}\n\treturn value + {n}\n}}", capitalized(name)),
+        _ => format!("\n## {}\n\nSection {n} covers the {name} step.", capitalized(name)),
+    };
+    text.lines().map(str::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, ScanOptions};
+
+    #[test]
+    fn test_same_seed_same_tree() {
+        let spec = FixtureSpec { files: 40, ..Default::default() };
+
+        assert_eq!(Fixture::generate(&spec).unwrap(), Fixture::generate(&spec).unwrap());
+        let other = Fixture::generate(&FixtureSpec { seed: 1, ..spec }).unwrap();
+        assert_ne!(Fixture::generate(&FixtureSpec { files: 40, ..Default::default() }).unwrap(), other);
+    }
+
+    #[test]
+    fn test_follows_the_spec() {
+        let spec = FixtureSpec {
+            seed: 7,
+            files: 60,
+            max_depth: 2,
+            fan_out: 3,
+            languages: BTreeMap::from([("go".to_string(), 1), ("json".to_string(), 1), ("python".to_string(), 0)]),
+            min_lines: 5,
+            max_lines: 30,
+        };
+        let fixture = Fixture::generate(&spec).unwrap();
+
+        assert_eq!(fixture.files.len(), 60);
+        for file in &fixture.files {
+            assert!(file.path.components().count() <= 3, "{:?}", file.path);
+            assert!((5..=30).contains(&file.content.lines().count()), "{:?}", file.path);
+            assert!(file.language == "go" || file.language == "json");
+        }
+        let json = fixture.files.iter().find(|file| file.language == "json").unwrap();
+        assert!(serde_json::from_str::<serde_json::Value>(&json.content).is_ok(), "{}", json.content);
+        assert!(fixture.directories().len() <= 3 + 9);
+
+        let bad = FixtureSpec { languages: BTreeMap::from([("cobol".to_string(), 1)]), ..Default::default() };
+        assert!(matches!(Fixture::generate(&bad), Err(ScanError::InvalidConfig { .. })));
+    }
+
+    #[test]
+    fn test_scans_like_a_real_tree() {
+        let fixture = Fixture::generate(&FixtureSpec { files: 25, seed: 3, ..Default::default() }).unwrap();
+        let fs = fixture.to_memory(Path::new("fixture"));
+        let options = ScanOptions { enhanced_analysis: true, ..Default::default() };
+        let result = DirectoryScanner::new(options).scan_with(&fs, "fixture").unwrap();
+
+        assert_eq!(result.files.iter().filter(|file| !file.is_dir).count(), 25);
+        assert_eq!(result.stats.total_size, fixture.total_size());
+        let rust = fixture.files.iter().find(|file| file.language == "rust").unwrap();
+        let scanned = result.find(Path::new("fixture").join(&rust.path)).unwrap();
+        let info = scanned.enhanced_info.as_ref().unwrap();
+        assert_eq!(info.language.as_deref(), Some("rust"));
+        assert_eq!(info.symbols.len(), rust.content.matches("pub fn ").count());
+    }
+}
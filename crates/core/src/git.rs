@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::{FileCommit, ScanError};
+
+// Thin wrappers over the `git` binary; shelling out keeps libgit2 out of the build
+fn git(dir: &Path, args: &[&str]) -> Result<String, ScanError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| ScanError::Git { message: format!("failed to run git: {}", e) })?;
+    if !output.status.success() {
+        return Err(ScanError::Git { message: String::from_utf8_lossy(&output.stderr).trim().to_string() });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// The top-level directory of the repository containing `path`, if any
+pub fn git_repo_root(path: &Path) -> Option<PathBuf> {
+    let dir = if path.is_dir() { path } else { path.parent()? };
+    git(dir, &["rev-parse", "--show-toplevel"]).ok().map(|root| PathBuf::from(root.trim()))
+}
+
+// Number of commits touching each file under `dir`, keyed by path relative to
+// `dir`. Renames are not followed, so a moved file starts counting afresh.
+pub fn git_commit_counts(dir: &Path) -> Result<HashMap<PathBuf, usize>, ScanError> {
+    let log = git(dir, &["log", "--format=", "--name-only", "--no-renames", "--relative", "--", "."])?;
+    let mut counts = HashMap::new();
+    for line in log.lines().filter(|line| !line.is_empty()) {
+        *counts.entry(PathBuf::from(line)).or_insert(0) += 1;
+    }
+    Ok(counts)
+}
+
+// Commits touching each file under `dir`, newest first, keyed by path relative
+// to `dir`. Authors are as the mailmap names them; renames are not followed.
+pub fn git_file_history(dir: &Path) -> Result<HashMap<PathBuf, Vec<FileCommit>>, ScanError> {
+    let log = git(dir, &["log", "--format=%x01%H%x09%at%x09%aN%x09%s", "--name-only", "--no-renames", "--relative", "--", "."])?;
+    let mut history: HashMap<PathBuf, Vec<FileCommit>> = HashMap::new();
+    let mut current: Option<FileCommit> = None;
+    for line in log.lines().filter(|line| !line.is_empty()) {
+        if let Some(header) = line.strip_prefix('\u{1}') {
+            let mut fields = header.splitn(4, '\t');
+            let (commit, seconds, author, subject) = (fields.next(), fields.next(), fields.next(), fields.next());
+            current = commit.zip(seconds.and_then(|seconds| seconds.parse().ok())).map(|(commit, seconds)| FileCommit {
+                commit: commit.to_string(),
+                author: author.unwrap_or_default().to_string(),
+                time: UNIX_EPOCH + Duration::from_secs(seconds),
+                subject: subject.unwrap_or_default().to_string(),
+            });
+        } else if let Some(commit) = &current {
+            history.entry(PathBuf::from(line)).or_default().push(commit.clone());
+        }
+    }
+    Ok(history)
+}
+
+// Files under `dir` changed since `since` (a commit, tag or branch), relative
+// to `dir`: committed, staged and unstaged changes plus untracked files.
+// Branches are compared from their merge base, so changes made on `since`
+// after the current branch forked are not included. Deleted files are left out.
+pub fn git_changed_files(dir: &Path, since: &str) -> Result<Vec<PathBuf>, ScanError> {
+    let base = git(dir, &["merge-base", since, "HEAD"])?;
+    let mut changed: Vec<PathBuf> = git(dir, &["diff", "--name-only", "--relative", "--diff-filter=d", base.trim(), "--", "."])?
+        .lines()
+        .chain(git(dir, &["ls-files", "--others", "--exclude-standard"])?.lines())
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect();
+    changed.sort();
+    changed.dedup();
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git_available() -> bool {
+        Command::new("git").arg("--version").output().is_ok_and(|output| output.status.success())
+    }
+
+    fn commit(dir: &Path, file: &str, content: &str) {
+        std::fs::create_dir_all(dir.join(file).parent().unwrap()).unwrap();
+        std::fs::write(dir.join(file), content).unwrap();
+        git(dir, &["add", "."]).unwrap();
+        git(dir, &["-c", "user.name=test", "-c", "user.email=test@example.com", "commit", "-qm", file]).unwrap();
+    }
+
+    #[test]
+    fn test_commit_counts_per_file() {
+        if !git_available() {
+            return;
+        }
+        let temp = tempfile::tempdir().unwrap();
+        git(temp.path(), &["init", "-q"]).unwrap();
+        commit(temp.path(), "src/a.rs", "1");
+        commit(temp.path(), "src/a.rs", "2");
+        commit(temp.path(), "README.md", "docs");
+
+        let counts = git_commit_counts(temp.path()).unwrap();
+        assert_eq!(counts.get(Path::new("src/a.rs")), Some(&2));
+        assert_eq!(counts.get(Path::new("README.md")), Some(&1));
+
+        // Paths are relative to the directory asked about, not the repository root
+        let counts = git_commit_counts(&temp.path().join("src")).unwrap();
+        assert_eq!(counts.keys().collect::<Vec<_>>(), vec![Path::new("a.rs")]);
+    }
+
+    #[test]
+    fn test_file_history_records_authors() {
+        if !git_available() {
+            return;
+        }
+        let temp = tempfile::tempdir().unwrap();
+        git(temp.path(), &["init", "-q"]).unwrap();
+        commit(temp.path(), "src/a.rs", "1");
+        commit(temp.path(), "src/a.rs", "2");
+
+        let history = git_file_history(temp.path()).unwrap();
+        let commits = &history[Path::new("src/a.rs")];
+        assert_eq!(commits.len(), 2);
+        assert!(commits.iter().all(|commit| commit.author == "test" && commit.commit.len() == 40));
+        // Each commit in these tests is named after the file it writes
+        assert_eq!(commits[0].subject, "src/a.rs");
+        assert_ne!(commits[0].commit, commits[1].commit);
+    }
+
+    #[test]
+    fn test_changed_files_since_ref() {
+        if !git_available() {
+            return;
+        }
+        let temp = tempfile::tempdir().unwrap();
+        git(temp.path(), &["init", "-q"]).unwrap();
+        commit(temp.path(), "old.rs", "1");
+        commit(temp.path(), "gone.rs", "1");
+        git(temp.path(), &["tag", "base"]).unwrap();
+        commit(temp.path(), "src/committed.rs", "1");
+        std::fs::remove_file(temp.path().join("gone.rs")).unwrap();
+        std::fs::write(temp.path().join("old.rs"), "2").unwrap();
+        std::fs::write(temp.path().join("untracked.rs"), "new").unwrap();
+
+        let changed = git_changed_files(temp.path(), "base").unwrap();
+        assert_eq!(changed, vec![PathBuf::from("old.rs"), PathBuf::from("src/committed.rs"), PathBuf::from("untracked.rs")]);
+        assert!(matches!(git_changed_files(temp.path(), "no-such-ref"), Err(ScanError::Git { .. })));
+    }
+
+    #[test]
+    fn test_outside_a_repository() {
+        if !git_available() {
+            return;
+        }
+        let temp = tempfile::tempdir().unwrap();
+
+        assert!(matches!(git_commit_counts(temp.path()), Err(ScanError::Git { .. })));
+    }
+
+    #[test]
+    fn test_repo_root_from_subdirectory() {
+        if !git_available() {
+            return;
+        }
+        let temp = tempfile::tempdir().unwrap();
+        git(temp.path(), &["init", "-q"]).unwrap();
+        std::fs::create_dir(temp.path().join("nested")).unwrap();
+
+        let root = git_repo_root(&temp.path().join("nested")).unwrap();
+        assert_eq!(root.canonicalize().unwrap(), temp.path().canonicalize().unwrap());
+    }
+}
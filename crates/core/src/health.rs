@@ -0,0 +1,331 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::{ContentAnalyzer, FileEntry, FileSystem, ScanResult, TestCoverageStructure, HIGH_COMPLEXITY};
+
+// One documentation file per this many source files earns full marks for docs
+const SOURCE_FILES_PER_DOC: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HealthCategory {
+    Complexity,
+    Churn,
+    Tests,
+    Documentation,
+    HardcodedValues,
+}
+
+impl fmt::Display for HealthCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HealthCategory::Complexity => "complexity",
+            HealthCategory::Churn => "churn",
+            HealthCategory::Tests => "tests",
+            HealthCategory::Documentation => "documentation",
+            HealthCategory::HardcodedValues => "hardcoded-values",
+        };
+        f.write_str(name)
+    }
+}
+
+// A subscore from 0 to 100, with the numbers behind it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryScore {
+    pub category: HealthCategory,
+    pub score: f64,
+    pub detail: String,
+}
+
+// The overall score is the mean of the categories there was data for; a repo
+// without git history or tests is not marked down for the missing numbers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub score: f64,
+    pub grade: String,
+    pub categories: Vec<CategoryScore>,
+}
+
+impl HealthReport {
+    // Complexity needs enhanced analysis; hardcoded values are counted from the
+    // content read through `fs`. Churn is keyed by paths relative to the root,
+    // as `git_commit_counts` returns them.
+    pub fn compute(result: &ScanResult, fs: &dyn FileSystem, churn: Option<&HashMap<PathBuf, usize>>) -> Self {
+        let categories: Vec<CategoryScore> = [
+            complexity_score(result),
+            churn.and_then(|churn| churn_score(result, churn)),
+            tests_score(result),
+            documentation_score(result),
+            hardcoded_score(result, fs),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        let score = if categories.is_empty() {
+            0.0
+        } else {
+            round(categories.iter().map(|category| category.score).sum::<f64>() / categories.len() as f64)
+        };
+        HealthReport { score, grade: grade(score).to_string(), categories }
+    }
+
+    pub fn category(&self, category: HealthCategory) -> Option<&CategoryScore> {
+        self.categories.iter().find(|score| score.category == category)
+    }
+
+    // The shields.io colour name for the score
+    pub fn color(&self) -> &'static str {
+        match self.score {
+            score if score >= 90.0 => "brightgreen",
+            score if score >= 80.0 => "green",
+            score if score >= 70.0 => "yellowgreen",
+            score if score >= 60.0 => "yellow",
+            score if score >= 50.0 => "orange",
+            _ => "red",
+        }
+    }
+
+    fn message(&self) -> String {
+        format!("{} ({:.0})", self.grade, self.score)
+    }
+
+    // JSON for a shields.io endpoint badge: https://shields.io/badges/endpoint-badge
+    pub fn shields_endpoint(&self) -> serde_json::Value {
+        serde_json::json!({
+            "schemaVersion": 1,
+            "label": "health",
+            "message": self.message(),
+            "color": self.color(),
+        })
+    }
+
+    // A flat badge in the shields.io style that needs no network to render.
+    // Text widths are estimated, which is close enough for the short strings used.
+    pub fn badge_svg(&self) -> String {
+        let label = "health";
+        let message = self.message();
+        let fill = match self.color() {
+            "brightgreen" => "#4c1",
+            "green" => "#97ca00",
+            "yellowgreen" => "#a4a61d",
+            "yellow" => "#dfb317",
+            "orange" => "#fe7d37",
+            _ => "#e05d44",
+        };
+        let width = |text: &str| text.chars().count() * 7 + 10;
+        let (left, right) = (width(label), width(&message));
+        let total = left + right;
+
+        format!(
+            concat!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{total}\" height=\"20\" role=\"img\" aria-label=\"{label}: {message}\">",
+                "<title>{label}: {message}</title>",
+                "<linearGradient id=\"s\" x2=\"0\" y2=\"100%\"><stop offset=\"0\" stop-color=\"#bbb\" stop-opacity=\".1\"/><stop offset=\"1\" stop-opacity=\".1\"/></linearGradient>",
+                "<clipPath id=\"r\"><rect width=\"{total}\" height=\"20\" rx=\"3\" fill=\"#fff\"/></clipPath>",
+                "<g clip-path=\"url(#r)\"><rect width=\"{left}\" height=\"20\" fill=\"#555\"/><rect x=\"{left}\" width=\"{right}\" height=\"20\" fill=\"{fill}\"/><rect width=\"{total}\" height=\"20\" fill=\"url(#s)\"/></g>",
+                "<g fill=\"#fff\" text-anchor=\"middle\" font-family=\"Verdana,Geneva,DejaVu Sans,sans-serif\" font-size=\"11\">",
+                "<text x=\"{label_x}\" y=\"14\">{label}</text><text x=\"{message_x}\" y=\"14\">{message}</text></g></svg>\n",
+            ),
+            total = total,
+            left = left,
+            right = right,
+            fill = fill,
+            label = label,
+            message = message,
+            label_x = left / 2,
+            message_x = left + right / 2,
+        )
+    }
+}
+
+fn round(score: f64) -> f64 {
+    (score * 10.0).round() / 10.0
+}
+
+fn percent(part: usize, whole: usize) -> f64 {
+    round(100.0 * part as f64 / whole as f64)
+}
+
+fn grade(score: f64) -> &'static str {
+    match score {
+        score if score >= 90.0 => "A",
+        score if score >= 80.0 => "B",
+        score if score >= 70.0 => "C",
+        score if score >= 60.0 => "D",
+        _ => "F",
+    }
+}
+
+fn has_tag(file: &FileEntry, tag: &str) -> bool {
+    file.tags.iter().any(|t| t == tag)
+}
+
+fn is_complex(file: &FileEntry) -> bool {
+    file.enhanced_info.as_ref()
+        .and_then(|info| info.complexity_score)
+        .is_some_and(|complexity| complexity >= HIGH_COMPLEXITY)
+}
+
+// Share of analysed files below the high-complexity mark
+fn complexity_score(result: &ScanResult) -> Option<CategoryScore> {
+    let scored: Vec<&FileEntry> = result.files.iter()
+        .filter(|file| !file.is_dir && file.enhanced_info.as_ref().is_some_and(|info| info.complexity_score.is_some()))
+        .collect();
+    if scored.is_empty() {
+        return None;
+    }
+    let complex = scored.iter().filter(|file| is_complex(file)).count();
+    Some(CategoryScore {
+        category: HealthCategory::Complexity,
+        score: percent(scored.len() - complex, scored.len()),
+        detail: format!("{} of {} files at complexity {} or above", complex, scored.len(), HIGH_COMPLEXITY),
+    })
+}
+
+// Changes are riskiest in complex files, so this is the share of commits
+// touching files that are not highly complex
+fn churn_score(result: &ScanResult, churn: &HashMap<PathBuf, usize>) -> Option<CategoryScore> {
+    let (mut total, mut complex) = (0, 0);
+    for file in result.files.iter().filter(|file| !file.is_dir) {
+        let path = file.path.strip_prefix(&result.root_path).unwrap_or(&file.path);
+        let Some(&commits) = churn.get(path) else { continue };
+        total += commits;
+        if is_complex(file) {
+            complex += commits;
+        }
+    }
+    if total == 0 {
+        return None;
+    }
+    Some(CategoryScore {
+        category: HealthCategory::Churn,
+        score: percent(total - complex, total),
+        detail: format!("{} of {} file changes in high-complexity files", complex, total),
+    })
+}
+
+fn tests_score(result: &ScanResult) -> Option<CategoryScore> {
+    let coverage = match &result.test_coverage_structure {
+        Some(coverage) => coverage.clone(),
+        None => TestCoverageStructure::build(&result.files),
+    };
+    if coverage.source_files == 0 {
+        return None;
+    }
+    Some(CategoryScore {
+        category: HealthCategory::Tests,
+        score: percent(coverage.covered_source_files, coverage.source_files),
+        detail: format!("{} of {} source files have tests", coverage.covered_source_files, coverage.source_files),
+    })
+}
+
+// Up to 40 points for a README at the root and 60 for documentation
+// files in proportion to the amount of source
+fn documentation_score(result: &ScanResult) -> Option<CategoryScore> {
+    let files: Vec<&FileEntry> = result.files.iter().filter(|file| !file.is_dir).collect();
+    let sources = files.iter().filter(|file| has_tag(file, "source")).count();
+    if sources == 0 {
+        return None;
+    }
+    let docs = files.iter().filter(|file| has_tag(file, "documentation")).count();
+    let readme = files.iter().any(|file| {
+        file.path.parent() == Some(result.root_path.as_path()) && file.name.to_lowercase().starts_with("readme")
+    });
+
+    let coverage = (docs as f64 * SOURCE_FILES_PER_DOC / sources as f64).min(1.0);
+    let score = if readme { 40.0 } else { 0.0 } + 60.0 * coverage;
+    Some(CategoryScore {
+        category: HealthCategory::Documentation,
+        score: round(score),
+        detail: format!(
+            "{}, {} documentation files for {} source files",
+            if readme { "README present" } else { "no README" },
+            docs,
+            sources
+        ),
+    })
+}
+
+// Hardcoded dates and values per branch, read from source content. A density
+// of one or more per branch scores zero.
+fn hardcoded_score(result: &ScanResult, fs: &dyn FileSystem) -> Option<CategoryScore> {
+    let analyzer = ContentAnalyzer::new();
+    let (mut branches, mut hardcoded) = (0, 0);
+    for file in result.files.iter().filter(|file| !file.is_dir && has_tag(file, "source")) {
+        let Ok(content) = fs.read_to_string(&file.path) else { continue };
+        let language = file.enhanced_info.as_ref()
+            .and_then(|info| info.language.clone())
+            .or_else(|| analyzer.detect_language(&file.name));
+        let details = analyzer.analyze_branching_details(&content, &language);
+        branches += details.total_branches;
+        hardcoded += details.hardcoded_dates_count + details.hardcoded_values_count;
+    }
+    if branches == 0 {
+        return None;
+    }
+    let density = hardcoded as f64 / branches as f64;
+    Some(CategoryScore {
+        category: HealthCategory::HardcodedValues,
+        score: round(100.0 * (1.0 - density.min(1.0))),
+        detail: format!("{} hardcoded values across {} branches", hardcoded, branches),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+
+    fn scanned() -> (ScanResult, MemoryFileSystem) {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/README.md", "# App\n");
+        fs.insert("app/src/lib.rs", "fn check(x: u32) -> bool {\n    if x > 42 {\n        return true;\n    }\n    false\n}\n");
+        fs.insert("app/src/util.rs", "fn id(x: u32) -> u32 {\n    x\n}\n");
+        fs.insert("app/tests/lib_test.rs", "#[test]\nfn works() {}\n");
+        let options = ScanOptions { enhanced_analysis: true, ..Default::default() };
+        let result = DirectoryScanner::new(options).scan_with(&fs, "app").unwrap();
+        (result, fs)
+    }
+
+    #[test]
+    fn test_scores_available_categories() {
+        let (result, fs) = scanned();
+        let report = HealthReport::compute(&result, &fs, None);
+
+        // No history, so churn is left out rather than scored as zero
+        assert!(report.category(HealthCategory::Churn).is_none());
+        assert!(report.category(HealthCategory::Documentation).is_some_and(|docs| docs.score == 100.0));
+        assert!(report.category(HealthCategory::HardcodedValues).is_some());
+        let mean = report.categories.iter().map(|category| category.score).sum::<f64>() / report.categories.len() as f64;
+        assert_eq!(report.score, round(mean));
+        assert_eq!(report.grade, grade(report.score));
+    }
+
+    #[test]
+    fn test_churn_counts_commits_outside_complex_files() {
+        let (result, fs) = scanned();
+        let churn = HashMap::from([(PathBuf::from("src/lib.rs"), 3), (PathBuf::from("src/util.rs"), 1)]);
+        let report = HealthReport::compute(&result, &fs, Some(&churn));
+
+        let churn = report.category(HealthCategory::Churn).unwrap();
+        assert_eq!(churn.detail, "0 of 4 file changes in high-complexity files");
+        assert_eq!(churn.score, 100.0);
+    }
+
+    #[test]
+    fn test_badge_outputs() {
+        let report = HealthReport { score: 84.6, grade: "B".to_string(), categories: Vec::new() };
+
+        assert_eq!(
+            report.shields_endpoint(),
+            serde_json::json!({ "schemaVersion": 1, "label": "health", "message": "B (85)", "color": "green" })
+        );
+        let svg = report.badge_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.contains("<title>health: B (85)</title>"));
+        assert!(svg.contains("fill=\"#97ca00\""));
+    }
+}
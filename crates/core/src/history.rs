@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::{ScanError, ScanResult, ScanStats};
+
+// Relative change in average complexity below which a codebase counts as stable
+const STABLE_COMPLEXITY_RATIO: f64 = 0.05;
+const HISTORY_DIR: &str = ".projscan";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileMetrics {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: SystemTime,
+    pub line_count: Option<usize>,
+    pub complexity_score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanSnapshot {
+    pub timestamp: SystemTime,
+    pub root_path: PathBuf,
+    pub stats: ScanStats,
+    pub files: Vec<FileMetrics>,
+}
+
+impl ScanSnapshot {
+    pub fn from_result(result: &ScanResult) -> Self {
+        let files = result.files.iter()
+            .filter(|file| !file.is_dir)
+            // The history itself lives inside the project and must not count as churn
+            .filter(|file| !file.path.components().any(|component| component.as_os_str() == HISTORY_DIR))
+            .map(|file| FileMetrics {
+                path: file.path.clone(),
+                size: file.size,
+                modified: file.modified,
+                line_count: file.enhanced_info.as_ref().and_then(|info| info.line_count),
+                complexity_score: file.enhanced_info.as_ref().and_then(|info| info.complexity_score),
+            })
+            .collect();
+
+        Self {
+            timestamp: SystemTime::now(),
+            root_path: result.root_path.clone(),
+            stats: result.stats.clone(),
+            files,
+        }
+    }
+}
+
+// Append-only store of scan snapshots, one JSON document per line
+pub struct ScanHistory {
+    path: PathBuf,
+}
+
+impl ScanHistory {
+    pub const DEFAULT_FILE: &'static str = ".projscan/history.jsonl";
+
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+
+    // History file kept inside the scanned project
+    pub fn for_root(root: &Path) -> Self {
+        Self::new(root.join(Self::DEFAULT_FILE))
+    }
+
+    // History file inside a state directory kept elsewhere, e.g. a CI cache
+    pub fn in_dir(dir: &Path) -> Self {
+        Self::new(dir.join(Path::new(Self::DEFAULT_FILE).file_name().unwrap_or_default()))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, snapshot: &ScanSnapshot) -> Result<(), ScanError> {
+        if let Some(parent) = self.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let line = serde_json::to_string(snapshot).map_err(|e| ScanError::InvalidConfig {
+            message: format!("Failed to serialize snapshot: {}", e),
+        })?;
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+
+    pub fn load(&self) -> Result<Vec<ScanSnapshot>, ScanError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let reader = BufReader::new(fs::File::open(&self.path)?);
+        let mut snapshots = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let snapshot = serde_json::from_str(&line).map_err(|e| ScanError::InvalidConfig {
+                message: format!("Corrupt history entry at {}:{}: {}", self.path.display(), index + 1, e),
+            })?;
+            snapshots.push(snapshot);
+        }
+        Ok(snapshots)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrendDirection {
+    Improving,
+    Stable,
+    Worsening,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendPoint {
+    pub timestamp: SystemTime,
+    pub total_files: usize,
+    pub total_size: u64,
+    pub total_lines: usize,
+    pub average_complexity: Option<f64>,
+    pub max_complexity: Option<f64>,
+    // Changes relative to the previous snapshot
+    pub files_added: usize,
+    pub files_removed: usize,
+    pub files_modified: usize,
+}
+
+impl TrendPoint {
+    pub fn churn(&self) -> usize {
+        self.files_added + self.files_removed + self.files_modified
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendReport {
+    pub points: Vec<TrendPoint>,
+    pub file_growth: i64,
+    pub complexity_change: Option<f64>,
+    pub total_churn: usize,
+    pub direction: TrendDirection,
+}
+
+impl TrendReport {
+    pub fn from_snapshots(snapshots: &[ScanSnapshot]) -> Self {
+        let mut points = Vec::with_capacity(snapshots.len());
+        let mut previous: Option<HashMap<&Path, &FileMetrics>> = None;
+
+        for snapshot in snapshots {
+            let current: HashMap<&Path, &FileMetrics> = snapshot.files.iter()
+                .map(|file| (file.path.as_path(), file))
+                .collect();
+
+            let (mut added, mut removed, mut modified) = (0, 0, 0);
+            if let Some(previous) = &previous {
+                for (path, file) in &current {
+                    match previous.get(path) {
+                        None => added += 1,
+                        Some(before) if before.size != file.size || before.modified != file.modified => modified += 1,
+                        Some(_) => {}
+                    }
+                }
+                removed = previous.keys().filter(|path| !current.contains_key(*path)).count();
+            }
+
+            let complexities: Vec<f64> = snapshot.files.iter().filter_map(|file| file.complexity_score).collect();
+            let average_complexity = (!complexities.is_empty())
+                .then(|| complexities.iter().sum::<f64>() / complexities.len() as f64);
+
+            points.push(TrendPoint {
+                timestamp: snapshot.timestamp,
+                total_files: snapshot.files.len(),
+                total_size: snapshot.files.iter().map(|file| file.size).sum(),
+                total_lines: snapshot.files.iter().filter_map(|file| file.line_count).sum(),
+                average_complexity,
+                max_complexity: complexities.iter().copied().reduce(f64::max),
+                files_added: added,
+                files_removed: removed,
+                files_modified: modified,
+            });
+            previous = Some(current);
+        }
+
+        let file_growth = match (points.first(), points.last()) {
+            (Some(first), Some(last)) => last.total_files as i64 - first.total_files as i64,
+            _ => 0,
+        };
+        let first_complexity = points.iter().find_map(|point| point.average_complexity);
+        let last_complexity = points.iter().rev().find_map(|point| point.average_complexity);
+        let complexity_change = first_complexity.zip(last_complexity).map(|(first, last)| last - first);
+
+        let direction = match (complexity_change, first_complexity) {
+            (Some(change), Some(first)) if change.abs() > first.abs() * STABLE_COMPLEXITY_RATIO => {
+                if change < 0.0 { TrendDirection::Improving } else { TrendDirection::Worsening }
+            }
+            _ => TrendDirection::Stable,
+        };
+
+        Self {
+            total_churn: points.iter().map(TrendPoint::churn).sum(),
+            points,
+            file_growth,
+            complexity_change,
+            direction,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn metrics(path: &str, size: u64, complexity: f64) -> FileMetrics {
+        FileMetrics {
+            path: PathBuf::from(path),
+            size,
+            modified: SystemTime::UNIX_EPOCH + Duration::from_secs(size),
+            line_count: Some(size as usize / 10),
+            complexity_score: Some(complexity),
+        }
+    }
+
+    fn snapshot(files: Vec<FileMetrics>) -> ScanSnapshot {
+        ScanSnapshot {
+            timestamp: SystemTime::UNIX_EPOCH,
+            root_path: PathBuf::from("."),
+            stats: ScanStats {
+                total_files: files.len(),
+                total_dirs: 0,
+                total_size: files.iter().map(|file| file.size).sum(),
+                scan_duration_ms: 0,
+                files_per_second: 0.0,
+                directories: Default::default(),
+                distribution: Default::default(),
+                cache: None,
+                sampling: None,
+                resources: None,
+            },
+            files,
+        }
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let history = ScanHistory::for_root(dir.path());
+
+        assert!(history.load().unwrap().is_empty());
+        history.append(&snapshot(vec![metrics("a.rs", 100, 2.0)])).unwrap();
+        history.append(&snapshot(vec![metrics("a.rs", 100, 2.0), metrics("b.rs", 50, 1.0)])).unwrap();
+
+        let snapshots = history.load().unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[1].files[1].path, PathBuf::from("b.rs"));
+        assert_eq!(ScanHistory::in_dir(Path::new("ci-cache")).path(), Path::new("ci-cache/history.jsonl"));
+    }
+
+    #[test]
+    fn test_trend_tracks_growth_churn_and_direction() {
+        let snapshots = vec![
+            snapshot(vec![metrics("a.rs", 100, 4.0), metrics("b.rs", 200, 6.0)]),
+            snapshot(vec![metrics("a.rs", 120, 3.0), metrics("c.rs", 80, 2.0), metrics("d.rs", 40, 1.0)]),
+        ];
+
+        let report = TrendReport::from_snapshots(&snapshots);
+
+        assert_eq!(report.file_growth, 1);
+        assert_eq!(report.points[1].files_added, 2);
+        assert_eq!(report.points[1].files_removed, 1);
+        assert_eq!(report.points[1].files_modified, 1);
+        assert_eq!(report.total_churn, 4);
+        assert_eq!(report.complexity_change, Some(-3.0));
+        assert_eq!(report.direction, TrendDirection::Improving);
+    }
+
+    #[test]
+    fn test_single_snapshot_is_stable() {
+        let report = TrendReport::from_snapshots(&[snapshot(vec![metrics("a.rs", 100, 4.0)])]);
+
+        assert_eq!(report.total_churn, 0);
+        assert_eq!(report.direction, TrendDirection::Stable);
+    }
+}
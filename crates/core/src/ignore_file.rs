@@ -0,0 +1,166 @@
+use std::path::{Path, PathBuf};
+
+use crate::expression::glob_match;
+use crate::SkipReason;
+
+// Per-directory exclusions in gitignore syntax, read during the walk on top of
+// `ScanOptions::ignore_patterns`
+pub const IGNORE_FILE_NAME: &str = ".projscanignore";
+
+#[derive(Debug, Clone, PartialEq)]
+struct IgnoreRule {
+    line: String,
+    glob: String,
+    negated: bool,
+    dir_only: bool,
+}
+
+// The rules of one ignore file; patterns match paths relative to its directory
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IgnoreFile {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreFile {
+    // `#` comments, `!` negation, a trailing `/` for directories only, and a
+    // `/` anywhere else anchoring the pattern to the file's directory
+    pub fn parse(text: &str) -> Self {
+        let rules = text.lines()
+            .filter_map(|line| {
+                let line = line.trim_end();
+                if line.is_empty() || line.starts_with('#') {
+                    return None;
+                }
+                let line_text = line;
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line.strip_prefix('\\').unwrap_or(line)),
+                };
+                let (dir_only, line) = match line.strip_suffix('/') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                if line.is_empty() {
+                    return None;
+                }
+                let glob = if line.contains('/') {
+                    line.trim_start_matches('/').to_string()
+                } else {
+                    format!("**/{}", line)
+                };
+                Some(IgnoreRule { line: line_text.to_string(), glob, negated, dir_only })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    // Some(true) when the last matching rule ignores the path, Some(false)
+    // when it re-includes it, None when no rule matches
+    pub fn matches(&self, relative_path: &str, is_dir: bool) -> Option<bool> {
+        self.matching_rule(relative_path, is_dir).map(|(_, ignored)| ignored)
+    }
+
+    // The last rule matching the path, as written in the file, and whether it ignores
+    pub fn matching_rule(&self, relative_path: &str, is_dir: bool) -> Option<(&str, bool)> {
+        self.rules.iter().rev()
+            .find(|rule| (is_dir || !rule.dir_only) && glob_match(&rule.glob, relative_path))
+            .map(|rule| (rule.line.as_str(), !rule.negated))
+    }
+}
+
+// The ignore files in effect at the current position of a depth-first walk.
+// Walkers check each entry, then `enter` directories they descend into, and
+// must not descend into ignored directories: as with gitignore, files below
+// an ignored directory cannot be re-included.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreStack {
+    frames: Vec<(PathBuf, IgnoreFile)>,
+}
+
+impl IgnoreStack {
+    pub fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+        match self.ignore_reason(path, is_dir) {
+            Some(reason) => {
+                tracing::debug!(path = %path.display(), "skipping entry: {}", reason);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn ignore_reason(&mut self, path: &Path, is_dir: bool) -> Option<SkipReason> {
+        // Files of directories the walk has left no longer apply
+        while self.frames.last().is_some_and(|(dir, _)| !path.starts_with(dir) || path == dir) {
+            self.frames.pop();
+        }
+
+        // The deepest file with a matching rule decides
+        let (dir, rule, ignored) = self.frames.iter().rev().find_map(|(dir, file)| {
+            let relative = crate::portable_path(path.strip_prefix(dir).ok()?);
+            file.matching_rule(&relative, is_dir).map(|(rule, ignored)| (dir, rule, ignored))
+        })?;
+        ignored.then(|| SkipReason::IgnoreFile { file: dir.join(IGNORE_FILE_NAME), rule: rule.to_string() })
+    }
+
+    // Loads the ignore file of a directory being descended into; `read`
+    // returns None when the directory has none
+    pub fn enter(&mut self, dir: &Path, read: impl FnOnce(&Path) -> Option<String>) {
+        if let Some(text) = read(&dir.join(IGNORE_FILE_NAME)) {
+            let file = IgnoreFile::parse(&text);
+            if !file.is_empty() {
+                self.frames.push((dir.to_path_buf(), file));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gitignore_syntax() {
+        let file = IgnoreFile::parse("# fixtures\nfixtures/\n*.snap\n!keep.snap\n/build\ndocs/*.tmp\n\n");
+
+        assert_eq!(file.matches("fixtures", true), Some(true));
+        assert_eq!(file.matches("fixtures", false), None);
+        assert_eq!(file.matches("nested/fixtures", true), Some(true));
+        assert_eq!(file.matches("a/b.snap", false), Some(true));
+        assert_eq!(file.matches("a/keep.snap", false), Some(false));
+        assert_eq!(file.matches("build", true), Some(true));
+        assert_eq!(file.matches("src/build", true), None);
+        assert_eq!(file.matches("docs/x.tmp", false), Some(true));
+        assert_eq!(file.matches("main.rs", false), None);
+    }
+
+    #[test]
+    fn test_stack_scopes_files_to_their_directory() {
+        let mut stack = IgnoreStack::default();
+        stack.enter(Path::new("proj"), |_| Some("*.log\n".to_string()));
+        assert!(!stack.is_ignored(Path::new("proj/team"), true));
+        stack.enter(Path::new("proj/team"), |_| Some("out/\n!keep.log\n".to_string()));
+
+        assert!(stack.is_ignored(Path::new("proj/team/out"), true));
+        assert!(!stack.is_ignored(Path::new("proj/team/keep.log"), false));
+        assert!(stack.is_ignored(Path::new("proj/team/debug.log"), false));
+
+        // Leaving proj/team drops its rules but keeps the root's
+        assert!(!stack.is_ignored(Path::new("proj/other/out"), true));
+        assert!(stack.is_ignored(Path::new("proj/other/keep.log"), false));
+    }
+
+    #[test]
+    fn test_enter_without_a_file() {
+        let mut stack = IgnoreStack::default();
+        stack.enter(Path::new("proj"), |path| {
+            assert_eq!(path, Path::new("proj/.projscanignore"));
+            None
+        });
+
+        assert!(!stack.is_ignored(Path::new("proj/anything"), false));
+    }
+}
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::paths::glob_matches_file;
+use crate::ScanError;
+
+// Extra importance for files with a role in a framework's architecture, such
+// as route tables, state stores or dependency-injection wiring. A file matches
+// when one of `patterns` matches it and one of `markers` is in its content;
+// either list may be left empty, but not both.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportanceBoost {
+    // e.g. "route", "store" or "wiring"
+    pub role: String,
+    // Globs on the file name, or on the end of its path when they have a '/'
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patterns: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markers: Vec<String>,
+    pub boost: f64,
+}
+
+impl ImportanceBoost {
+    fn new(role: &str, patterns: &[&str], markers: &[&str], boost: f64) -> Self {
+        Self {
+            role: role.to_string(),
+            patterns: patterns.iter().map(|pattern| pattern.to_string()).collect(),
+            markers: markers.iter().map(|marker| marker.to_string()).collect(),
+            boost,
+        }
+    }
+
+    // Route tables, state stores and dependency-injection wiring of common
+    // web, mobile and backend frameworks
+    pub fn defaults() -> Vec<ImportanceBoost> {
+        vec![
+            ImportanceBoost::new("route", &["urls.py", "routes.rb", "*routes.*", "*Routes.*", "*router.*", "*Router.*", "routes/*"], &[], 1.5),
+            ImportanceBoost::new("route", &[], &["@app.route(", "@router.", "@RequestMapping", "@Controller", "createBrowserRouter("], 1.0),
+            ImportanceBoost::new("store", &["*reducer.*", "*reducers.*", "*Reducer.*", "*slice.*", "*Slice.*", "*store.*", "*Store.*", "stores/*"], &[], 1.0),
+            ImportanceBoost::new("store", &[], &["createSlice(", "createStore(", "configureStore(", "defineStore(", "combineReducers("], 1.0),
+            ImportanceBoost::new("wiring", &["*.module.ts", "*Module.java", "*Module.kt", "*Config.java", "*Configuration.java", "container.*"], &[], 1.0),
+            ImportanceBoost::new("wiring", &[], &["@Configuration", "@NgModule(", "@Module(", "@Provides", "@Bean"], 1.0),
+        ]
+    }
+
+    fn matches(&self, path: &Path, content: Option<&str>) -> bool {
+        if self.patterns.is_empty() && self.markers.is_empty() {
+            return false;
+        }
+        (self.patterns.is_empty() || self.patterns.iter().any(|pattern| glob_matches_file(pattern, path)))
+            && (self.markers.is_empty() || content.is_some_and(|content| self.markers.iter().any(|marker| content.contains(marker.as_str()))))
+    }
+}
+
+pub(crate) fn default_boosts() -> &'static [ImportanceBoost] {
+    static DEFAULTS: OnceLock<Vec<ImportanceBoost>> = OnceLock::new();
+    DEFAULTS.get_or_init(ImportanceBoost::defaults)
+}
+
+// The largest boost a file matches, so a file in several roles is not counted twice
+pub(crate) fn boost_for(boosts: &[ImportanceBoost], path: &Path, content: Option<&str>) -> f64 {
+    boosts.iter()
+        .filter(|boost| boost.matches(path, content))
+        .map(|boost| boost.boost)
+        .fold(0.0, f64::max)
+}
+
+// Boosts per mapper profile, each replacing that profile's built-in ones; a
+// YAML or JSON file such as:
+//
+//     generic:
+//       - role: route
+//         patterns: ["urls.py", "api/*.py"]
+//         boost: 2.0
+//       - role: wiring
+//         markers: ["@inject"]
+//         boost: 1.0
+pub fn load_importance_boosts(path: &Path) -> Result<BTreeMap<String, Vec<ImportanceBoost>>, ScanError> {
+    let text = std::fs::read_to_string(path)?;
+    serde_yaml::from_str(&text).map_err(|e| ScanError::InvalidConfig {
+        message: format!("{}: {}", path.display(), e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+
+    #[test]
+    fn test_default_boosts_match_framework_files() {
+        let boosts = default_boosts();
+
+        assert_eq!(boost_for(boosts, Path::new("app/web/urls.py"), Some("urlpatterns = []")), 1.5);
+        assert_eq!(boost_for(boosts, Path::new("src/features/cartSlice.ts"), None), 1.0);
+        assert_eq!(boost_for(boosts, Path::new("src/app.module.ts"), Some("@NgModule({})")), 1.0);
+        assert_eq!(boost_for(boosts, Path::new("src/Beans.java"), Some("@Configuration\nclass Beans {}")), 1.0);
+        assert_eq!(boost_for(boosts, Path::new("src/util.py"), Some("def helper(): pass")), 0.0);
+        assert!(!ImportanceBoost::new("empty", &[], &[], 5.0).matches(Path::new("any"), Some("")));
+    }
+
+    #[test]
+    fn test_profiles_replace_the_default_boosts() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/web/urls.py", "urlpatterns = []\n");
+        fs.insert("app/web/signals.py", "def on_save(): pass\n");
+        let importance = |options: ScanOptions, path: &str| {
+            let result = DirectoryScanner::new(options).scan_with(&fs, "app").unwrap();
+            result.find(path).unwrap().enhanced_info.as_ref().unwrap().importance_score.unwrap()
+        };
+        let enhanced = ScanOptions { enhanced_analysis: true, ..Default::default() };
+        let signals = vec![ImportanceBoost::new("signals", &["signals.py"], &[], 2.0)];
+        let configured = ScanOptions { importance_boosts: BTreeMap::from([("generic".to_string(), signals)]), ..enhanced.clone() };
+
+        assert!(importance(enhanced.clone(), "app/web/urls.py") > importance(enhanced.clone(), "app/web/signals.py"));
+        assert!(importance(configured.clone(), "app/web/signals.py") > importance(configured.clone(), "app/web/urls.py"));
+        // Other profiles keep the defaults
+        let other = ScanOptions { mapper_profile: "enhanced".to_string(), ..configured };
+        assert!(importance(other.clone(), "app/web/urls.py") > importance(other, "app/web/signals.py"));
+    }
+
+    #[test]
+    fn test_loads_boosts_per_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("boosts.yaml");
+        std::fs::write(&path, "generic:\n  - role: route\n    patterns: [\"api/*.py\"]\n    boost: 2.0\n").unwrap();
+
+        let profiles = load_importance_boosts(&path).unwrap();
+        assert_eq!(profiles["generic"], [ImportanceBoost::new("route", &["api/*.py"], &[], 2.0)]);
+        std::fs::write(&path, "generic: [{ role: route }]\n").unwrap();
+        assert!(load_importance_boosts(&path).is_err());
+    }
+}
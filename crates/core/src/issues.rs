@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::{FileSystem, ScanError};
+
+// Tag for directories the scan could not read completely, e.g. one denied to
+// the scanning user; their readable siblings are still scanned
+pub const PARTIAL_TAG: &str = "partially-scanned";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IssueKind {
+    PermissionDenied,
+    Unreadable,
+    NonUtf8,
+    AnalysisFailed,
+    SymlinkLoop,
+    // A walk limit cut a directory short
+    EntryLimit,
+    DepthLimit,
+    Other,
+}
+
+impl IssueKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IssueKind::PermissionDenied => "permission-denied",
+            IssueKind::Unreadable => "unreadable",
+            IssueKind::NonUtf8 => "non-utf8",
+            IssueKind::AnalysisFailed => "analysis-failed",
+            IssueKind::SymlinkLoop => "symlink-loop",
+            IssueKind::EntryLimit => "entry-limit",
+            IssueKind::DepthLimit => "depth-limit",
+            IssueKind::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "IssueRepr")]
+pub struct ScanIssue {
+    pub path: Option<PathBuf>,
+    pub kind: IssueKind,
+    pub message: String,
+    pub severity: Severity,
+}
+
+// Scan results saved before issues were structured stored plain strings
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IssueRepr {
+    Structured {
+        path: Option<PathBuf>,
+        kind: IssueKind,
+        message: String,
+        severity: Severity,
+    },
+    Legacy(String),
+}
+
+impl From<IssueRepr> for ScanIssue {
+    fn from(repr: IssueRepr) -> Self {
+        match repr {
+            IssueRepr::Structured { path, kind, message, severity } => Self { path, kind, message, severity },
+            IssueRepr::Legacy(message) => Self { path: None, kind: IssueKind::Other, message, severity: Severity::Error },
+        }
+    }
+}
+
+impl ScanIssue {
+    pub fn new(path: Option<PathBuf>, kind: IssueKind, message: impl Into<String>, severity: Severity) -> Self {
+        Self { path, kind, message: message.into(), severity }
+    }
+
+    pub fn from_scan_error(path: &Path, error: &ScanError) -> Self {
+        let kind = match error {
+            ScanError::PermissionDenied { .. } => IssueKind::PermissionDenied,
+            ScanError::Io(io) => Self::kind_for_io(io.kind()),
+            _ => IssueKind::Other,
+        };
+        Self::new(Some(path.to_path_buf()), kind, error.to_string(), Severity::Error)
+    }
+
+    pub fn from_walk_error(error: &walkdir::Error) -> Self {
+        if let (Some(path), Some(ancestor)) = (error.path(), error.loop_ancestor()) {
+            return Self::symlink_loop(path, ancestor);
+        }
+        let kind = error.io_error().map_or(IssueKind::Other, |io| Self::kind_for_io(io.kind()));
+        if let (IssueKind::PermissionDenied, Some(path)) = (kind, error.path()) {
+            return Self::permission_denied(path);
+        }
+        Self::new(error.path().map(Path::to_path_buf), kind, error.to_string(), Severity::Error)
+    }
+
+    // A link resolving to a directory it sits under; the walk does not follow
+    // it, so the loop is skipped rather than aborting the scan
+    pub fn symlink_loop(path: &Path, ancestor: &Path) -> Self {
+        Self::new(
+            Some(path.to_path_buf()),
+            IssueKind::SymlinkLoop,
+            format!("Symlink points back to {}; not followed", ancestor.display()),
+            Severity::Warning,
+        )
+    }
+
+    // A directory with more entries than `ScanOptions::max_entries_per_dir`;
+    // the first ones walked are kept
+    pub fn entry_limit(path: &Path, cap: usize, skipped: usize) -> Self {
+        Self::new(
+            Some(path.to_path_buf()),
+            IssueKind::EntryLimit,
+            format!("Directory has more than {} entries; {} more were not scanned", cap, skipped),
+            Severity::Warning,
+        )
+    }
+
+    // A directory the walk may not list; the rest of the tree is still walked
+    pub fn permission_denied(path: &Path) -> Self {
+        Self::new(
+            Some(path.to_path_buf()),
+            IssueKind::PermissionDenied,
+            "Permission denied; the directory's entries were not scanned",
+            Severity::Error,
+        )
+    }
+
+    // A non-empty directory at `ScanOptions::depth_guard`, not descended into
+    pub fn depth_limit(path: &Path, depth: usize) -> Self {
+        Self::new(
+            Some(path.to_path_buf()),
+            IssueKind::DepthLimit,
+            format!("Directory is nested {} levels deep; its contents were not scanned", depth),
+            Severity::Warning,
+        )
+    }
+
+    pub fn analysis_failed(path: &Path, error: &ScanError) -> Self {
+        Self::new(
+            Some(path.to_path_buf()),
+            IssueKind::AnalysisFailed,
+            format!("Enhanced analysis failed: {}", error),
+            Severity::Warning,
+        )
+    }
+
+    // Explains why a file with a recognised language produced no content analysis
+    pub(crate) fn for_unanalyzed_content(path: &Path, fs: &dyn FileSystem) -> Option<Self> {
+        let issue = |kind, message: String| Some(Self::new(Some(path.to_path_buf()), kind, message, Severity::Warning));
+        match fs.read(path) {
+            Ok(bytes) => match String::from_utf8(bytes) {
+                Ok(_) => None,
+                Err(e) => issue(IssueKind::NonUtf8, format!("Content is not valid UTF-8: {}", e.utf8_error())),
+            },
+            Err(e) => issue(Self::kind_for_io(e.kind()), format!("Failed to read content: {}", e)),
+        }
+    }
+
+    fn kind_for_io(kind: ErrorKind) -> IssueKind {
+        match kind {
+            ErrorKind::PermissionDenied => IssueKind::PermissionDenied,
+            ErrorKind::InvalidData => IssueKind::NonUtf8,
+            _ => IssueKind::Unreadable,
+        }
+    }
+}
+
+impl fmt::Display for ScanIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "[{}] {}: {}", self.kind.as_str(), path.display(), self.message),
+            None => write!(f, "[{}] {}", self.kind.as_str(), self.message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OsFileSystem;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_io_errors_map_to_kinds() {
+        let denied = ScanError::Io(std::io::Error::new(ErrorKind::PermissionDenied, "denied"));
+        let missing = ScanError::Io(std::io::Error::new(ErrorKind::NotFound, "gone"));
+
+        assert_eq!(ScanIssue::from_scan_error(Path::new("a"), &denied).kind, IssueKind::PermissionDenied);
+        assert_eq!(ScanIssue::from_scan_error(Path::new("a"), &missing).kind, IssueKind::Unreadable);
+    }
+
+    #[test]
+    fn test_detects_non_utf8_content() {
+        let dir = TempDir::new().unwrap();
+        let text = dir.path().join("ok.rs");
+        let latin1 = dir.path().join("legacy.rs");
+        std::fs::write(&text, "fn main() {}").unwrap();
+        std::fs::write(&latin1, b"// caf\xe9\nfn main() {}").unwrap();
+
+        assert!(ScanIssue::for_unanalyzed_content(&text, &OsFileSystem).is_none());
+        let issue = ScanIssue::for_unanalyzed_content(&latin1, &OsFileSystem).unwrap();
+        assert_eq!(issue.kind, IssueKind::NonUtf8);
+        assert_eq!(issue.severity, Severity::Warning);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_loops_are_reported_once() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "fn main() {}").unwrap();
+        std::os::unix::fs::symlink("..", dir.path().join("src/up")).unwrap();
+        std::os::unix::fs::symlink(".", dir.path().join("src/here")).unwrap();
+
+        let options = crate::ScanOptions { follow_symlinks: true, ..Default::default() };
+        let result = crate::DirectoryScanner::new(options).scan(dir.path()).unwrap();
+
+        let loops: Vec<&ScanIssue> = result.errors.iter().filter(|issue| issue.kind == IssueKind::SymlinkLoop).collect();
+        assert_eq!(loops.len(), 2);
+        assert_eq!(loops[0], &ScanIssue::symlink_loop(&dir.path().join("src/here"), &dir.path().join("src")));
+        assert_eq!(loops[1].severity, Severity::Warning);
+        assert_eq!(result.files.iter().filter(|file| file.name == "lib.rs").count(), 1);
+    }
+
+    #[test]
+    fn test_serializes_structured_and_reads_legacy_strings() {
+        let issue = ScanIssue::new(Some(PathBuf::from("src/x.rs")), IssueKind::SymlinkLoop, "loop", Severity::Warning);
+        let json = serde_json::to_string(&issue).unwrap();
+        assert!(json.contains("\"kind\":\"symlink-loop\""));
+        assert_eq!(serde_json::from_str::<ScanIssue>(&json).unwrap(), issue);
+
+        let legacy: Vec<ScanIssue> = serde_json::from_str(r#"["Walk error: boom"]"#).unwrap();
+        assert_eq!(legacy[0].kind, IssueKind::Other);
+        assert_eq!(legacy[0].message, "Walk error: boom");
+    }
+}
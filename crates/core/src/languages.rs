@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::paths::glob_matches_file;
+
+// A language forced on the files matching `pattern`, whatever their extension
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LanguageOverride {
+    pub pattern: String,
+    pub language: String,
+}
+
+// Languages for files the built-in extension table gets wrong or does not
+// know, e.g. `.mjs` as javascript or `.tf` as terraform. Overrides are tried
+// first, in order; globs without a '/' match the file name and those with one
+// match the end of its path, so `proto/*.gen` applies under any `proto` directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LanguageMap {
+    // Extension, with or without its dot -> language
+    pub extensions: BTreeMap<String, String>,
+    pub overrides: Vec<LanguageOverride>,
+}
+
+impl LanguageMap {
+    pub fn is_empty(&self) -> bool {
+        self.extensions.is_empty() && self.overrides.is_empty()
+    }
+
+    // Maps `extension` ("mjs" or ".mjs") to `language`, replacing an earlier mapping
+    pub fn map_extension(&mut self, extension: &str, language: &str) {
+        self.extensions.insert(extension.trim_start_matches('.').to_lowercase(), language.to_lowercase());
+    }
+
+    pub fn add_override(&mut self, pattern: &str, language: &str) {
+        self.overrides.push(LanguageOverride { pattern: pattern.to_string(), language: language.to_lowercase() });
+    }
+
+    // The language an override forces on `path`
+    pub(crate) fn forced(&self, path: &Path) -> Option<&str> {
+        self.overrides.iter()
+            .find(|rule| glob_matches_file(&rule.pattern, path))
+            .map(|rule| rule.language.as_str())
+    }
+
+    // The language mapped to `extension`, compared without dots or case
+    pub(crate) fn for_extension(&self, extension: &str) -> Option<&str> {
+        self.extensions.iter()
+            .find(|(mapped, _)| mapped.trim_start_matches('.').eq_ignore_ascii_case(extension))
+            .map(|(_, language)| language.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ContentAnalyzer, DirectoryScanner, MemoryFileSystem, ScanOptions};
+
+    fn languages() -> LanguageMap {
+        let mut languages = LanguageMap::default();
+        languages.map_extension(".mjs", "javascript");
+        languages.map_extension("TF", "terraform");
+        languages.map_extension("h", "cpp");
+        languages.add_override("proto/*.gen", "protobuf");
+        languages.add_override("BUILD", "starlark");
+        languages
+    }
+
+    #[test]
+    fn test_extensions_extend_and_replace_the_builtin_table() {
+        let analyzer = ContentAnalyzer::new().with_languages(languages());
+
+        assert_eq!(analyzer.detect_language("app.mjs").as_deref(), Some("javascript"));
+        assert_eq!(analyzer.detect_language("main.tf").as_deref(), Some("terraform"));
+        assert_eq!(analyzer.detect_language("util.h").as_deref(), Some("cpp"));
+        assert_eq!(analyzer.detect_language("lib.rs").as_deref(), Some("rust"));
+        assert_eq!(analyzer.resolve_language("mjs"), "javascript");
+        assert_eq!(ContentAnalyzer::new().detect_language("util.h").as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn test_overrides_match_names_and_path_ends() {
+        let languages = languages();
+
+        assert_eq!(languages.forced(Path::new("/repo/api/proto/user.gen")), Some("protobuf"));
+        assert_eq!(languages.forced(Path::new("/repo/api/user.gen")), None);
+        assert_eq!(languages.forced(Path::new("/repo/tools/BUILD")), Some("starlark"));
+        assert!(LanguageMap::default().is_empty() && !languages.is_empty());
+    }
+
+    #[test]
+    fn test_scans_use_the_language_map() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/index.mjs", "export function run() {}\n");
+        fs.insert("app/proto/user.gen", "message User {}\n");
+        fs.insert("app/main.rs", "fn main() {}\n");
+        let options = ScanOptions { enhanced_analysis: true, languages: languages(), ..Default::default() };
+
+        for threads in [1, 2] {
+            let result = DirectoryScanner::new(ScanOptions { analysis_threads: threads, ..options.clone() }).scan_with(&fs, "app").unwrap();
+            let language = |path: &str| result.find(path).unwrap().enhanced_info.as_ref().unwrap().language.clone();
+            assert_eq!(language("app/index.mjs").as_deref(), Some("javascript"));
+            assert_eq!(language("app/proto/user.gen").as_deref(), Some("protobuf"));
+            assert_eq!(language("app/main.rs").as_deref(), Some("rust"));
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::dir_stats::top_level;
+use crate::ranking::{import_edges, resolves_to};
+use crate::{FileSystem, ScanError, ScanResult};
+
+// An allowed or forbidden dependency direction between two directories,
+// written "api -> core" or "core !-> api". Directories are relative to the
+// scan root, and a nested one such as "src/core" takes precedence over "src".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct LayerRule {
+    pub from: String,
+    pub to: String,
+    pub allowed: bool,
+}
+
+impl FromStr for LayerRule {
+    type Err = ScanError;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let (from, to, allowed) = match rule.split_once("!->") {
+            Some((from, to)) => (from, to, false),
+            None => match rule.split_once("->") {
+                Some((from, to)) => (from, to, true),
+                None => return Err(invalid_rule(rule)),
+            },
+        };
+        let directory = |part: &str| part.trim().trim_matches('/').to_string();
+        let (from, to) = (directory(from), directory(to));
+        if from.is_empty() || to.is_empty() || from == to {
+            return Err(invalid_rule(rule));
+        }
+        Ok(Self { from, to, allowed })
+    }
+}
+
+// Deserialization errors are wrapped by the caller, so they carry only the message
+impl TryFrom<String> for LayerRule {
+    type Error = String;
+
+    fn try_from(rule: String) -> Result<Self, Self::Error> {
+        rule.parse().map_err(|e| match e {
+            ScanError::InvalidConfig { message } => message,
+            other => other.to_string(),
+        })
+    }
+}
+
+impl From<LayerRule> for String {
+    fn from(rule: LayerRule) -> Self {
+        rule.to_string()
+    }
+}
+
+impl fmt::Display for LayerRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.from, if self.allowed { "->" } else { "!->" }, self.to)
+    }
+}
+
+// An import crossing directories against the rules. Paths are relative to
+// the scan root.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerViolation {
+    pub path: PathBuf,
+    // Line of the import statement, when it could be found in the file
+    pub line: Option<usize>,
+    // The import line as written, or the import as analysis recorded it
+    pub import: String,
+    pub imported: PathBuf,
+    pub from: String,
+    pub to: String,
+}
+
+impl fmt::Display for LayerViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}", self.path.display(), line)?,
+            None => write!(f, "{}", self.path.display())?,
+        }
+        write!(f, ": `{}` imports {}, but {} may not depend on {}", self.import, self.imported.display(), self.from, self.to)
+    }
+}
+
+// Checks the import graph of an enhanced scan against the rules. An import
+// from one directory into another breaks them when a rule forbids that
+// direction, or when the importing directory has allowed directions and this
+// is not one of them. Imports from directories no rule names are unconstrained.
+// Files are read through `fs` to find the line of each offending import.
+pub(crate) fn check_layers(rules: &[LayerRule], result: &ScanResult, fs: &dyn FileSystem) -> Vec<LayerViolation> {
+    if rules.is_empty() {
+        return Vec::new();
+    }
+    let mut layers: Vec<&str> = rules.iter().flat_map(|rule| [rule.from.as_str(), rule.to.as_str()]).collect();
+    layers.sort_unstable();
+    layers.dedup();
+    // Deepest first, so a file belongs to the most specific directory
+    layers.sort_by_key(|layer| std::cmp::Reverse(layer.matches('/').count()));
+    let relative = |path: &Path| path.strip_prefix(&result.root_path).unwrap_or(path).to_path_buf();
+    let layer_of = |path: &Path| layers.iter().copied().find(|layer| path.starts_with(layer));
+
+    let mut violations = Vec::new();
+    for (importer, imported) in import_edges(&result.files) {
+        let (importer, imported) = (&result.files[importer], &result.files[imported]);
+        let (path, imported_path) = (relative(&importer.path), relative(&imported.path));
+        let Some(from) = layer_of(&path) else { continue };
+        // A directory no rule names goes by its top-level directory
+        let to = layer_of(&imported_path).map_or_else(|| top_level(&result.root_path, &imported.path), str::to_string);
+        if from == to || imported_path.starts_with(from) || permitted(rules, from, &to) {
+            continue;
+        }
+        let imports = importer.enhanced_info.iter().flat_map(|info| &info.imports);
+        let mut import = imports.into_iter().find(|import| resolves_to(import, &imported.path)).cloned().unwrap_or_default();
+        let mut line = None;
+        if let Ok(content) = fs.read_to_string(&importer.path) {
+            if let Some((index, text)) = content.lines().enumerate().find(|(_, text)| !import.is_empty() && text.contains(import.trim())) {
+                line = Some(index + 1);
+                import = text.trim().to_string();
+            }
+        }
+        violations.push(LayerViolation { path, line, import, imported: imported_path, from: from.to_string(), to });
+    }
+    violations.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.line.cmp(&b.line)));
+    violations
+}
+
+fn permitted(rules: &[LayerRule], from: &str, to: &str) -> bool {
+    let mut rules_from = rules.iter().filter(|rule| rule.from == from).peekable();
+    if rules_from.peek().is_none() {
+        return true;
+    }
+    let mut any_allowed = false;
+    for rule in rules_from {
+        if rule.to == to {
+            return rule.allowed;
+        }
+        any_allowed |= rule.allowed;
+    }
+    // Forbidding some directions leaves the rest open; allowing some closes them
+    !any_allowed
+}
+
+fn invalid_rule(rule: &str) -> ScanError {
+    ScanError::InvalidConfig { message: format!("invalid layer rule {:?}; expected \"from -> to\" or \"from !-> to\"", rule) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+
+    fn rules(rules: &[&str]) -> Vec<LayerRule> {
+        rules.iter().map(|rule| rule.parse().unwrap()).collect()
+    }
+
+    fn project() -> (MemoryFileSystem, ScanResult) {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/api/routes.rs", "// HTTP routes\nuse crate::models;\nuse crate::auth;\n");
+        fs.insert("app/api/auth.rs", "pub fn check() {}\n");
+        fs.insert("app/core/models.rs", "use crate::routes;\npub struct User;\n");
+        fs.insert("app/web/page.rs", "use crate::models;\nuse crate::routes;\n");
+        let options = ScanOptions { enhanced_analysis: true, ..Default::default() };
+        let result = DirectoryScanner::new(options).scan_with(&fs, "app").unwrap();
+        (fs, result)
+    }
+
+    #[test]
+    fn test_parses_rules() {
+        let rule: LayerRule = " src/api/ -> core ".parse().unwrap();
+        assert_eq!(rule, LayerRule { from: "src/api".into(), to: "core".into(), allowed: true });
+        assert_eq!("core !-> api".parse::<LayerRule>().unwrap().to_string(), "core !-> api");
+        assert!("core api".parse::<LayerRule>().is_err());
+        assert!("core -> core".parse::<LayerRule>().is_err());
+        assert!(serde_json::from_str::<Vec<LayerRule>>(r#"["api -> core", "nope"]"#).is_err());
+    }
+
+    #[test]
+    fn test_forbidden_direction_reports_import_line() {
+        let (fs, result) = project();
+        let violations = check_layers(&rules(&["api -> core", "core !-> api"]), &result, &fs);
+
+        assert_eq!(violations.len(), 1);
+        let violation = &violations[0];
+        assert_eq!((violation.path.as_path(), violation.line), (Path::new("core/models.rs"), Some(1)));
+        assert_eq!(violation.imported, PathBuf::from("api/routes.rs"));
+        assert_eq!(violation.to_string(), "core/models.rs:1: `use crate::routes;` imports api/routes.rs, but core may not depend on api");
+    }
+
+    #[test]
+    fn test_allowed_directions_close_the_rest() {
+        let (fs, result) = project();
+        // web may only use api, so its import of core breaks the rules; api's
+        // own imports are unconstrained
+        let violations = check_layers(&rules(&["web -> api"]), &result, &fs);
+        let found: Vec<(&Path, &str)> = violations.iter().map(|violation| (violation.path.as_path(), violation.to.as_str())).collect();
+        assert_eq!(found, [(Path::new("web/page.rs"), "core")]);
+        assert!(check_layers(&[], &result, &fs).is_empty());
+    }
+}
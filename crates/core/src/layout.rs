@@ -0,0 +1,219 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{portable_path, ContentAnalyzer, FileEntry, Palette, PathStyle, ScanResult};
+
+// How the text formatters order entries. Numeric keys and mtime sort largest or
+// newest first; ties and `Path` fall back to the path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    Size,
+    Complexity,
+    Importance,
+    Mtime,
+    Path,
+}
+
+// How the text formatters split a result into sections, each formatted on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    Tag,
+    Language,
+    Directory,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FormatOptions {
+    pub palette: Palette,
+    pub paths: PathStyle,
+    // RFC 3339 modification times instead of "3d ago"
+    pub absolute_times: bool,
+    pub sort: Option<SortKey>,
+    pub group_by: Option<GroupBy>,
+}
+
+impl SortKey {
+    pub fn sort(self, files: &mut [FileEntry]) {
+        let score = |file: &FileEntry| -> f64 {
+            let info = file.enhanced_info.as_ref();
+            match self {
+                SortKey::Size => file.size as f64,
+                SortKey::Complexity => info.and_then(|info| info.complexity_score).unwrap_or(f64::NEG_INFINITY),
+                SortKey::Importance => info.and_then(|info| info.importance_score).unwrap_or(f64::NEG_INFINITY),
+                SortKey::Mtime | SortKey::Path => 0.0,
+            }
+        };
+        files.sort_by(|a, b| {
+            let order = match self {
+                SortKey::Mtime => b.modified.cmp(&a.modified),
+                _ => score(b).total_cmp(&score(a)),
+            };
+            order.then_with(|| a.path.cmp(&b.path))
+        });
+    }
+}
+
+impl GroupBy {
+    pub fn label(self) -> &'static str {
+        match self {
+            GroupBy::Tag => "tag",
+            GroupBy::Language => "language",
+            GroupBy::Directory => "directory",
+        }
+    }
+
+    // Files keyed by group, in name order. A file with several tags appears under
+    // each; directory entries are left out since every group lists files.
+    pub fn group(self, result: &ScanResult) -> BTreeMap<String, Vec<FileEntry>> {
+        let analyzer = ContentAnalyzer::new();
+        let mut groups: BTreeMap<String, Vec<FileEntry>> = BTreeMap::new();
+        for file in result.files.iter().filter(|file| !file.is_dir) {
+            let keys = match self {
+                GroupBy::Tag if file.tags.is_empty() => vec!["untagged".to_string()],
+                GroupBy::Tag => file.tags.clone(),
+                GroupBy::Language => vec![language_of(file, &analyzer)],
+                GroupBy::Directory => {
+                    let relative = file.path.strip_prefix(&result.root_path).unwrap_or(&file.path);
+                    let parent = relative.parent().map(portable_path);
+                    vec![parent.filter(|parent| !parent.is_empty()).unwrap_or_else(|| ".".to_string())]
+                }
+            };
+            for key in keys {
+                groups.entry(key).or_default().push(file.clone());
+            }
+        }
+        groups
+    }
+}
+
+// The analyzed language, else one guessed from the extension, else "unknown"
+pub(crate) fn language_of(file: &FileEntry, analyzer: &ContentAnalyzer) -> String {
+    file.enhanced_info.as_ref()
+        .and_then(|info| info.language.clone())
+        .or_else(|| analyzer.detect_language(&file.name))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+impl ScanResult {
+    // The same scan restricted to `files`, for formatting one group at a time
+    pub(crate) fn with_files(&self, files: Vec<FileEntry>) -> ScanResult {
+        ScanResult {
+            schema_version: crate::SCHEMA_VERSION,
+            root_path: self.root_path.clone(),
+            files,
+            stats: self.stats.clone(),
+            roots: Vec::new(),
+            errors: Vec::new(),
+            findings: Vec::new(),
+            test_coverage_structure: None,
+            entry_points: Vec::new(),
+            endpoints: Vec::new(),
+            schema: None,
+            containers: Vec::new(),
+            nested_repositories: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScanStats;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+
+    fn file(path: &str, size: u64, complexity: Option<f64>, tags: &[&str]) -> FileEntry {
+        FileEntry::test(Path::new("proj").join(path))
+            .with_size(size)
+            .with_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(size))
+            .with_tags(tags)
+            .with_complexity(complexity)
+    }
+
+    fn names(files: &[FileEntry]) -> Vec<&str> {
+        files.iter().map(|file| file.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let mut files = vec![
+            file("b.rs", 10, Some(2.0), &[]),
+            file("a.rs", 30, None, &[]),
+            file("c.rs", 20, Some(5.0), &[]),
+        ];
+
+        SortKey::Size.sort(&mut files);
+        assert_eq!(names(&files), ["a.rs", "c.rs", "b.rs"]);
+        SortKey::Complexity.sort(&mut files);
+        assert_eq!(names(&files), ["c.rs", "b.rs", "a.rs"]);
+        SortKey::Path.sort(&mut files);
+        assert_eq!(names(&files), ["a.rs", "b.rs", "c.rs"]);
+        SortKey::Mtime.sort(&mut files);
+        assert_eq!(names(&files), ["a.rs", "c.rs", "b.rs"]);
+    }
+
+    #[test]
+    fn test_group_by_tag_and_language() {
+        let result = ScanResult {
+            schema_version: crate::SCHEMA_VERSION,
+            root_path: PathBuf::from("proj"),
+            files: vec![
+                file("src/lib.rs", 1, None, &["source", "core-api"]),
+                file("README.md", 1, None, &[]),
+                file("notes.xyz", 1, None, &["source"]),
+            ],
+            stats: ScanStats { total_files: 3, total_dirs: 0, total_size: 3, scan_duration_ms: 0, files_per_second: 0.0, directories: Default::default(), distribution: Default::default(), cache: None, sampling: None, resources: None },
+            roots: Vec::new(),
+            errors: Vec::new(),
+            findings: Vec::new(),
+            test_coverage_structure: None,
+            entry_points: Vec::new(),
+            endpoints: Vec::new(),
+            schema: None,
+            containers: Vec::new(),
+            nested_repositories: Vec::new(),
+        };
+
+        let by_tag = GroupBy::Tag.group(&result);
+        assert_eq!(by_tag.keys().collect::<Vec<_>>(), ["core-api", "source", "untagged"]);
+        assert_eq!(names(&by_tag["source"]), ["lib.rs", "notes.xyz"]);
+
+        let by_language = GroupBy::Language.group(&result);
+        assert_eq!(by_language.keys().collect::<Vec<_>>(), ["markdown", "rust", "unknown"]);
+
+        let by_directory = GroupBy::Directory.group(&result);
+        assert_eq!(by_directory.keys().collect::<Vec<_>>(), [".", "src"]);
+    }
+
+    #[test]
+    fn test_formatter_prints_sorted_groups() {
+        let result = ScanResult {
+            schema_version: crate::SCHEMA_VERSION,
+            root_path: PathBuf::from("proj"),
+            files: vec![file("src/a.rs", 1, None, &[]), file("src/b.rs", 5, None, &[]), file("c.rs", 3, None, &[])],
+            stats: ScanStats { total_files: 3, total_dirs: 0, total_size: 9, scan_duration_ms: 0, files_per_second: 0.0, directories: Default::default(), distribution: Default::default(), cache: None, sampling: None, resources: None },
+            roots: Vec::new(),
+            errors: Vec::new(),
+            findings: Vec::new(),
+            test_coverage_structure: None,
+            entry_points: Vec::new(),
+            endpoints: Vec::new(),
+            schema: None,
+            containers: Vec::new(),
+            nested_repositories: Vec::new(),
+        };
+        let options = FormatOptions { sort: Some(SortKey::Size), group_by: Some(GroupBy::Directory), ..Default::default() };
+        let output = crate::OutputFormatter::format_result_with(&result, &crate::OutputFormat::Basic, &options);
+
+        assert_eq!(output, "\
+directory: . (1 file)
+  [FILE] proj/c.rs
+
+directory: src (2 files)
+  [FILE] proj/src/b.rs
+  [FILE] proj/src/a.rs
+");
+    }
+}
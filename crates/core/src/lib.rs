@@ -1,29 +1,201 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
-use std::time::{SystemTime, Instant};
-use std::fs;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
 use thiserror::Error;
-use walkdir::{DirEntry, WalkDir};
+
+mod advisories;
+mod api_diff;
+#[cfg(feature = "archives")]
+mod archive;
+mod bench;
+mod change_kinds;
+mod chunk_filter;
+mod chunking;
+mod color;
+mod config_keys;
+mod containers;
+mod coupling;
+mod db_schema;
+mod dedupe;
+mod diff;
+mod dir_stats;
+mod distribution;
+#[cfg(feature = "embeddings")]
+mod embeddings;
+mod endpoints;
+mod entry_points;
+mod export;
+mod explain;
+mod expression;
+mod feature_flags;
+mod filesystem;
+mod fixture;
+#[cfg(feature = "git")]
+mod git;
+mod health;
+mod history;
+mod ignore_file;
+mod importance;
+mod issues;
+mod languages;
+mod layering;
+mod layout;
+mod llms_txt;
+mod manifests;
+mod media;
+mod merge;
+mod metrics;
+mod nested_repos;
+mod outdated;
+mod overview;
+mod ownership;
+mod paths;
+mod pipeline;
+mod progress;
+mod query;
+mod ranking;
+mod resources;
+mod retrieval;
+mod sampling;
+mod schema;
+#[cfg(feature = "search")]
+mod search_index;
+mod secrets;
+mod security;
+mod session;
+mod similarity;
+mod stability;
+mod summary;
+mod symbols;
+mod symlink;
+mod test_mapping;
+#[cfg(test)]
+mod test_support;
+mod thresholds;
+mod timestamp;
+mod throttle;
+mod tree;
+mod vector_export;
+mod vocabulary;
+
+pub use advisories::{Advisory, AdvisoryDatabase};
+#[cfg(feature = "osv")]
+pub use advisories::{fetch_advisories, OSV_QUERY_ENDPOINT};
+pub use api_diff::{ApiChange, ApiItemChange, SemverBump};
+#[cfg(feature = "archives")]
+pub use archive::{is_archive, load_archive};
+pub use bench::{bench_scan, BenchPhase, BenchReport};
+pub use change_kinds::{fix_counts, CommitKind, FEATURE_HEAVY_TAG, FIX_HEAVY_TAG, REFACTOR_HEAVY_TAG};
+pub use chunk_filter::{information_density, ChunkFilter, ChunkSieve};
+pub use chunking::{estimate_tokens, Chunk, ChunkManifestEntry, ChunkOptions, Chunker};
+pub use color::{Palette, HIGH_COMPLEXITY, MEDIUM_COMPLEXITY};
+pub use containers::{ContainerArtifact, ContainerKind};
+pub use coupling::{GOD_MODULE_FAN_IN, GOD_MODULE_TAG};
+pub use db_schema::{DatabaseSchema, Migration, SchemaTable};
+pub use dedupe::{content_hash, DuplicateGroup, DuplicateReport};
+pub use diff::{FileChange, FileMove, ScanDiff};
+pub use dir_stats::DirStats;
+pub use distribution::{Distribution, HistogramBucket};
+#[cfg(feature = "embeddings")]
+pub use embeddings::{EmbeddingApi, EmbeddingClient, EmbeddingOptions};
+pub use endpoints::Endpoint;
+pub use entry_points::{EntryPoint, EntryPointKind, ENTRYPOINT_TAG};
+pub use export::{to_csv, to_html, to_markdown, to_sarif, write_csv, write_json, write_ndjson, write_yaml};
+pub use explain::{explain_path, plan_walk, SkipReason, WalkDecision};
+pub use expression::{parse_size, CompareOp, FilterExpr, NumericField, TextField};
+pub use feature_flags::{FeatureFlag, FeatureFlagReport, FlagLocation, FlagProvider, StaleReason};
+pub use filesystem::{FileSystem, FsEntry, MemoryFileSystem, OsFileSystem, PathListFileSystem};
+pub use fixture::{Fixture, FixtureFile, FixtureSpec, FIXTURE_LANGUAGES};
+#[cfg(feature = "git")]
+pub use git::{git_changed_files, git_commit_counts, git_file_history, git_repo_root};
+pub use health::{CategoryScore, HealthCategory, HealthReport};
+pub use history::{FileMetrics, ScanHistory, ScanSnapshot, TrendDirection, TrendPoint, TrendReport};
+pub use ignore_file::{IgnoreFile, IgnoreStack, IGNORE_FILE_NAME};
+pub use importance::{load_importance_boosts, ImportanceBoost};
+pub use issues::{IssueKind, ScanIssue, Severity, PARTIAL_TAG};
+pub use languages::{LanguageMap, LanguageOverride};
+pub use layering::{LayerRule, LayerViolation};
+pub use layout::{FormatOptions, GroupBy, SortKey};
+pub use llms_txt::to_llms_txt;
+pub use manifests::{declared_dependencies, DeclaredDependency, Ecosystem};
+pub use media::{sniff, MediaInfo, MediaKind};
+pub use merge::RootStats;
+pub use metrics::{MetricsCallback, ScanMetrics};
+pub use nested_repos::{
+    NestedRepoKind, NestedRepoMode, NestedRepoSummary, NestedRepository, NESTED_REPO_TAG, SUBMODULE_TAG,
+};
+pub use outdated::{OutdatedDependency, OutdatedReport, ProjectOutdatedness, VersionIndex, VersionLag};
+#[cfg(feature = "registry")]
+pub use outdated::{fetch_latest_versions, DEPS_DEV_ENDPOINT};
+pub use overview::{DirectoryOverview, LanguageCount, ProjectOverview};
+pub use ownership::{AuthorShare, DirectoryOwnership, FileCommit, OwnershipReport};
+pub use paths::{portable_path, PathMode, PathStyle};
+pub use progress::{ScanProgress, ScanRun};
+pub use query::ScanIndex;
+pub use ranking::{import_counts, rank_files, RankMetric, RankedFile};
+pub use resources::{peak_rss_bytes, ResourceUsage};
+pub use retrieval::{select_chunks_for_query, QueryMatch};
+pub use sampling::{Estimate, Sampling, SamplingSummary};
+pub use schema::SCHEMA_VERSION;
+#[cfg(feature = "search")]
+pub use search_index::{SearchHit, SearchIndex};
+pub use secrets::{find_secrets, redact_secrets, SecretKind, SecretMatch, SecretsMode, REDACTED, SECRET_TAG};
+pub use security::{FindingKind, SecurityFinding};
+pub use session::{CacheStats, ScanSession};
+pub use similarity::{ContentFingerprint, SimilarityIndex};
+pub use stability::{FileStability, Stability};
+pub use symbols::{extract_symbols, Symbol, SymbolIndex, SymbolLocation};
+pub use symlink::SymlinkInfo;
+pub use test_mapping::{TestCoverageStructure, TestMapping};
+pub use thresholds::{ThresholdViolation, Thresholds};
+pub use throttle::IoThrottle;
+pub use timestamp::{epoch_millis, format_rfc3339, parse_rfc3339, parse_time_spec};
+#[cfg(feature = "parquet")]
+pub use vector_export::write_chunks_parquet;
+pub use vector_export::{chunk_point_id, to_pgvector_csv, to_qdrant_points, PGVECTOR_COLUMNS};
+pub use vocabulary::{extract_vocabulary, VocabularyTerm};
+
+use expression::glob_match;
+
+// Minimum similarity score and number of similar files suggested in related_files
+const RELATED_FILES_MIN_SCORE: f64 = 0.3;
+const RELATED_FILES_LIMIT: usize = 5;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileEntry {
     pub path: PathBuf,
     pub name: String,
     pub size: u64,
+    // Serialized as an RFC 3339 `modified` string and `modified_ms` epoch milliseconds
+    #[serde(flatten, with = "timestamp::modified")]
     pub modified: SystemTime,
     pub is_dir: bool,
     pub tags: Vec<String>,
     pub metadata: Option<HashMap<String, serde_json::Value>>,
     pub enhanced_info: Option<EnhancedFileInfo>,
+    // Where the entry points when it is a symbolic link
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symlink: Option<SymlinkInfo>,
+    // MIME type and media details for non-text content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media: Option<MediaInfo>,
+    // Hex SHA-256 of the content, filled in when the scan has `hash_contents`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EnhancedFileInfo {
     pub language: Option<String>,
     pub framework: Option<String>,
     pub line_count: Option<usize>,
     pub complexity_score: Option<f64>,
+    // Percentile rank (0-100) of complexity_score across the scanned repo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub complexity_percentile: Option<f64>,
     pub importance_score: Option<f64>,
     pub content_summary: Option<String>,
     pub exports: Vec<String>,
@@ -32,11 +204,33 @@ pub struct EnhancedFileInfo {
     pub related_files: Vec<PathBuf>,
     pub purpose: Option<String>,
     pub last_author: Option<String>,
+    // "stable", "active" or "new", from git history; see `stability`
     pub change_frequency: Option<String>,
     pub api_surface: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub symbols: Vec<Symbol>,
+    // Top-level keys of a JSON, YAML or TOML file, sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub config_keys: Vec<String>,
+    // Levels of nested mappings and lists in a JSON, YAML or TOML file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_depth: Option<usize>,
+    // Files importing this one (afferent coupling) and files it imports
+    // (efferent coupling), resolved across the scan
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fan_in: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fan_out: Option<usize>,
+    // Filled in by `ScanResult::assign_stability`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stability: Option<FileStability>,
+    // Sketch of the content taken during analysis, from which the scan links
+    // similar files without reading them again; not part of the output
+    #[serde(skip)]
+    pub fingerprint: Option<ContentFingerprint>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BranchingDetails {
     pub conditional_count: usize,
     pub loop_count: usize,
@@ -56,6 +250,12 @@ pub struct BranchingDetails {
     pub total_branches: usize,
 }
 
+impl Default for BranchingDetails {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BranchingDetails {
     pub fn new() -> Self {
         Self {
@@ -225,26 +425,196 @@ pub struct ScanStats {
     pub total_size: u64,
     pub scan_duration_ms: u64,
     pub files_per_second: f64,
+    // Keyed by top-level directory name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub directories: BTreeMap<String, DirStats>,
+    #[serde(default, skip_serializing_if = "Distribution::is_empty")]
+    pub distribution: Distribution,
+    // Set when the scan ran in a `ScanSession` with content analysis
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheStats>,
+    // Set when the scan sampled files for content analysis
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<SamplingSummary>,
+    // Set when the options asked for `collect_resource_usage`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<ResourceUsage>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanResult {
+    // Read through `schema_version()`; see schema.rs for the stability rules
+    #[serde(default = "schema::default_schema_version")]
+    schema_version: u32,
     pub root_path: PathBuf,
     pub files: Vec<FileEntry>,
     pub stats: ScanStats,
-    pub errors: Vec<String>,
+    // One entry per root when several were scanned together with `scan_many`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roots: Vec<RootStats>,
+    pub errors: Vec<ScanIssue>,
+    // Permission problems such as world-writable files or exposed private keys
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub findings: Vec<SecurityFinding>,
+    // Where programs start: manifest targets, Docker entrypoints, main files
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entry_points: Vec<EntryPoint>,
+    // HTTP routes declared for axum, actix-web, Express, Flask, FastAPI or
+    // Spring; only found with enhanced analysis
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endpoints: Vec<Endpoint>,
+    // Tables defined by SQL files and migrations, and the migration order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<DatabaseSchema>,
+    // Dockerfiles, compose files and dev container configs, with their images,
+    // ports and build stages
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub containers: Vec<ContainerArtifact>,
+    // Git submodules and other repositories found inside the scanned tree
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nested_repositories: Vec<NestedRepository>,
+    // Which tests cover which sources; only built with enhanced analysis
+    #[serde(rename = "test-coverage-structure", default, skip_serializing_if = "Option::is_none")]
+    pub test_coverage_structure: Option<TestCoverageStructure>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanOptions {
     pub max_depth: Option<usize>,
     pub ignore_patterns: Vec<String>,
+    // Globs a file must match to be reported (directories are still walked);
+    // empty includes everything
+    #[serde(default)]
+    pub include_patterns: Vec<String>,
     pub follow_symlinks: bool,
     pub include_hidden: bool,
     pub mapper_profile: String,
     pub collect_metadata: bool,
     pub enhanced_analysis: bool,
     pub output_format: OutputFormat,
+    #[serde(default)]
+    pub complexity_caps: ComplexityCaps,
+    #[serde(default)]
+    pub normalize_complexity: bool,
+    // Honor .projscanignore files found during the walk
+    #[serde(default = "default_use_ignore_files")]
+    pub use_ignore_files: bool,
+    // Sort files and issues by path so results are identical across platforms
+    // and runs; off keeps walk order for callers consuming entries as they come
+    #[serde(default = "default_sort_by_path")]
+    pub sort_by_path: bool,
+    // Report symlinks whose target is missing; otherwise they are left out
+    #[serde(default)]
+    pub include_broken_symlinks: bool,
+    // Read the header of files that are not known text types to find their
+    // MIME type, image dimensions and media duration
+    #[serde(default = "default_sniff_media")]
+    pub sniff_media: bool,
+    // Limit and space out file reads so background scans leave the disk to others
+    #[serde(default)]
+    pub io_throttle: Option<IoThrottle>,
+    // Files outside these bounds are left out; directories are still walked
+    #[serde(default)]
+    pub min_size: Option<u64>,
+    #[serde(default)]
+    pub max_size: Option<u64>,
+    #[serde(default, with = "timestamp::optional")]
+    pub modified_after: Option<SystemTime>,
+    #[serde(default, with = "timestamp::optional")]
+    pub modified_before: Option<SystemTime>,
+    // Record a SHA-256 of every file, for duplicate and rename detection
+    #[serde(default)]
+    pub hash_contents: bool,
+    // Whether the files of git submodules and nested repositories are reported
+    #[serde(default)]
+    pub nested_repos: NestedRepoMode,
+    // Look for secrets in file content, tagging and reporting the files and
+    // redacting the matched text from summaries and signatures
+    #[serde(default)]
+    pub secrets: SecretsMode,
+    // Entries reported per directory; the rest are left out with an
+    // `entry-limit` warning, so directories with millions of entries cannot
+    // stall a scan. None lifts the cap.
+    #[serde(default = "default_max_entries_per_dir")]
+    pub max_entries_per_dir: Option<usize>,
+    // Nesting below which directories are not descended into, with a
+    // `depth-limit` warning, unlike the silent `max_depth`. None lifts the guard.
+    #[serde(default = "default_depth_guard")]
+    pub depth_guard: Option<usize>,
+    // Threads analyzing file content while the walk goes on; 1 analyzes on
+    // the scanning thread and 0 uses one per CPU. Only mappers with a
+    // `parallel_analyzer` use them, and results keep walk order either way.
+    #[serde(default = "default_analysis_threads")]
+    pub analysis_threads: usize,
+    // Analyze only a sample of files in huge trees, with aggregates
+    // extrapolated in `ScanStats::sampling`; None analyzes every file
+    #[serde(default)]
+    pub sampling: Option<Sampling>,
+    // Measure the process's memory, reads and CPU time during the scan, into
+    // `ScanStats::resources`
+    #[serde(default)]
+    pub collect_resource_usage: bool,
+    // Extension mappings and per-glob overrides ahead of the built-in language
+    // detection, for content analysis
+    #[serde(default)]
+    pub languages: LanguageMap,
+    // Importance boosts per mapper profile, replacing the built-in route,
+    // store and wiring boosts of the profiles listed
+    #[serde(default)]
+    pub importance_boosts: BTreeMap<String, Vec<ImportanceBoost>>,
+}
+
+fn default_use_ignore_files() -> bool {
+    true
+}
+
+fn default_sort_by_path() -> bool {
+    true
+}
+
+fn default_sniff_media() -> bool {
+    true
+}
+
+fn default_max_entries_per_dir() -> Option<usize> {
+    Some(100_000)
+}
+
+fn default_depth_guard() -> Option<usize> {
+    Some(128)
+}
+
+fn default_analysis_threads() -> usize {
+    1
+}
+
+// Upper bounds applied to complexity scores; `None` leaves a score uncapped
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ComplexityCaps {
+    pub complexity: Option<f64>,
+    pub branching: Option<f64>,
+}
+
+impl Default for ComplexityCaps {
+    fn default() -> Self {
+        Self {
+            complexity: Some(10.0),
+            branching: Some(8.0),
+        }
+    }
+}
+
+impl ComplexityCaps {
+    pub fn uncapped() -> Self {
+        Self {
+            complexity: None,
+            branching: None,
+        }
+    }
+    
+    fn apply(cap: Option<f64>, value: f64) -> f64 {
+        cap.map_or(value, |cap| value.min(cap))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -253,6 +623,9 @@ pub enum OutputFormat {
     Compact,
     Detailed,
     Hierarchical,
+    RepoMap,
+    Tree,
+    Summary,
 }
 
 impl Default for ScanOptions {
@@ -265,16 +638,106 @@ impl Default for ScanOptions {
                 "target".to_string(),
                 ".DS_Store".to_string(),
             ],
+            include_patterns: Vec::new(),
             follow_symlinks: false,
             include_hidden: false,
             mapper_profile: "generic".to_string(),
             collect_metadata: false,
             enhanced_analysis: false,
             output_format: OutputFormat::Basic,
+            complexity_caps: ComplexityCaps::default(),
+            normalize_complexity: false,
+            use_ignore_files: true,
+            sort_by_path: true,
+            include_broken_symlinks: false,
+            sniff_media: true,
+            io_throttle: None,
+            min_size: None,
+            max_size: None,
+            modified_after: None,
+            modified_before: None,
+            hash_contents: false,
+            nested_repos: NestedRepoMode::Scan,
+            secrets: SecretsMode::Off,
+            max_entries_per_dir: default_max_entries_per_dir(),
+            depth_guard: default_depth_guard(),
+            analysis_threads: default_analysis_threads(),
+            sampling: None,
+            collect_resource_usage: false,
+            languages: LanguageMap::default(),
+            importance_boosts: BTreeMap::new(),
         }
     }
 }
 
+impl ScanOptions {
+    // Whether a walked entry is skipped: hidden names unless allowed, or a path
+    // containing any ignore pattern. Used by every `FileSystem::walk`
+    pub fn is_ignored(&self, path: &Path, name: &str) -> bool {
+        match self.ignore_reason(path, name) {
+            Some(reason) => {
+                tracing::debug!(path = %path.display(), "skipping entry: {}", reason);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn ignore_reason(&self, path: &Path, name: &str) -> Option<SkipReason> {
+        if !self.include_hidden && name.starts_with('.') && name != "." && name != ".." {
+            return Some(SkipReason::Hidden);
+        }
+        
+        self.ignore_patterns.iter()
+            .find(|pattern| paths::contains_pattern(path, pattern))
+            .map(|pattern| SkipReason::IgnorePattern { pattern: pattern.clone() })
+    }
+
+    // Boosts configured for the selected mapper profile, if any
+    pub fn profile_boosts(&self) -> Option<&Vec<ImportanceBoost>> {
+        self.importance_boosts.get(&self.mapper_profile)
+    }
+
+    // Deepest level a walk descends to: `max_depth` or the depth guard,
+    // whichever is shallower
+    pub fn walk_depth(&self) -> usize {
+        self.max_depth.unwrap_or(usize::MAX).min(self.depth_guard.unwrap_or(usize::MAX))
+    }
+
+    // Whether a directory `depth` levels below the root is where the depth
+    // guard, rather than `max_depth`, stops the walk
+    pub fn stops_at_depth_guard(&self, depth: usize) -> bool {
+        self.depth_guard.is_some_and(|guard| depth == guard && guard < self.max_depth.unwrap_or(usize::MAX))
+    }
+
+    // Why a file's size or modification time falls outside the filters, if it does
+    pub fn filter_reason(&self, size: u64, modified: SystemTime) -> Option<SkipReason> {
+        if self.min_size.is_some_and(|min| size < min) || self.max_size.is_some_and(|max| size > max) {
+            return Some(SkipReason::SizeOutOfRange { size });
+        }
+        if self.modified_after.is_some_and(|after| modified < after) || self.modified_before.is_some_and(|before| modified > before) {
+            return Some(SkipReason::ModifiedOutOfRange { modified });
+        }
+        None
+    }
+
+    // Patterns containing `/` match the path relative to the scan root, others the file name
+    pub fn is_included(&self, relative_path: &Path, name: &str) -> bool {
+        if self.include_patterns.is_empty() {
+            return true;
+        }
+        
+        let relative = portable_path(relative_path);
+        self.include_patterns.iter().any(|pattern| {
+            if pattern.contains('/') {
+                glob_match(pattern.trim_start_matches("./"), &relative)
+            } else {
+                glob_match(pattern, name)
+            }
+        })
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum ScanError {
     #[error("IO error: {0}")]
@@ -291,16 +754,113 @@ pub enum ScanError {
     
     #[error("Invalid configuration: {message}")]
     InvalidConfig { message: String },
+    
+    #[error("Invalid filter expression: {message}")]
+    InvalidFilter { message: String },
+    
+    #[error("Git error: {message}")]
+    Git { message: String },
+    
+    #[error("Embedding error: {message}")]
+    Embedding { message: String },
+    
+    #[error("Search index error: {message}")]
+    Search { message: String },
+    
+    #[error("Advisory error: {message}")]
+    Advisory { message: String },
+    
+    #[error("Package registry error: {message}")]
+    Registry { message: String },
+    
+    #[error("Invalid scan result: {message}")]
+    InvalidResult { message: String },
+    
+    #[error("Scan result schema version {found} is newer than this version supports ({supported}); upgrade projscan")]
+    UnsupportedSchema { found: u32, supported: u32 },
 }
 
 pub trait Mapper {
     fn classify(&self, entry: &FileEntry) -> Vec<String>;
     fn name(&self) -> &str;
+    
+    // Content analysis capability; mappers that only classify keep the defaults.
+    // When supported, `analyze` runs before `classify` so tags can use its results.
+    fn analyzes_content(&self) -> bool {
+        false
+    }
+    
+    // Content is read through `fs` so archives and in-memory trees analyze like disk
+    fn analyze(&self, _entry: &mut FileEntry, _fs: &dyn FileSystem) -> Result<(), ScanError> {
+        Ok(())
+    }
+    
+    // Whether detected entry points get the `entrypoint` tag on top of the
+    // mapper's own; off by default so a custom mapper's tags are all its own
+    fn tags_entry_points(&self) -> bool {
+        false
+    }
+    
+    // An analyzer producing what `analyze` would, for worker threads when
+    // `ScanOptions::analysis_threads` asks for them; without one every file
+    // is analyzed on the scanning thread
+    fn parallel_analyzer(&self) -> Option<ContentAnalyzer> {
+        None
+    }
 }
 
-pub trait EnhancedMapper {
-    fn analyze(&self, entry: &mut FileEntry) -> Result<(), ScanError>;
-    fn name(&self) -> &str;
+pub type MapperFactory = Box<dyn Fn(&ScanOptions) -> Box<dyn Mapper>>;
+
+// Maps profile names to mapper constructors so custom mappers can be selected
+// through `ScanOptions::mapper_profile` like the built-in ones
+pub struct MapperRegistry {
+    factories: HashMap<String, MapperFactory>,
+}
+
+impl Default for MapperRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register("generic", |options| {
+            if options.enhanced_analysis {
+                Box::new(EnhancedGenericMapper::for_options(options))
+            } else {
+                Box::new(GenericMapper)
+            }
+        });
+        registry.register("enhanced", |options| {
+            Box::new(EnhancedGenericMapper::for_options(options))
+        });
+        registry
+    }
+}
+
+impl MapperRegistry {
+    // Registry with the built-in "generic" and "enhanced" profiles
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn empty() -> Self {
+        Self { factories: HashMap::new() }
+    }
+    
+    pub fn register<F>(&mut self, profile: &str, factory: F) -> &mut Self
+    where
+        F: Fn(&ScanOptions) -> Box<dyn Mapper> + 'static,
+    {
+        self.factories.insert(profile.to_string(), Box::new(factory));
+        self
+    }
+    
+    pub fn create(&self, options: &ScanOptions) -> Option<Box<dyn Mapper>> {
+        self.factories.get(&options.mapper_profile).map(|factory| factory(options))
+    }
+    
+    pub fn profiles(&self) -> Vec<&str> {
+        let mut profiles: Vec<&str> = self.factories.keys().map(String::as_str).collect();
+        profiles.sort_unstable();
+        profiles
+    }
 }
 
 pub struct GenericMapper;
@@ -388,47 +948,202 @@ impl Mapper for GenericMapper {
     fn name(&self) -> &str {
         "generic"
     }
+    
+    fn tags_entry_points(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ContentAnalyzer {
+    caps: ComplexityCaps,
+    languages: LanguageMap,
+    // None uses the built-in boosts
+    boosts: Option<Vec<ImportanceBoost>>,
+}
+
+// A logical statement assembled from one or more physical lines; borrowed from
+// the file content unless lines were joined or masked
+struct LogicalStatement<'a> {
+    text: Cow<'a, str>,
+    indent: usize,
+}
+
+// Comment/string state that carries over from one physical line to the next
+#[derive(Default)]
+struct MultilineState {
+    block_comment_depth: usize,
+    triple_quote: Option<&'static str>,
+}
+
+// A line with some byte ranges replaced, which stays borrowed until the first edit
+struct EditedLine<'a> {
+    line: &'a str,
+    output: String,
+    copied: usize,
+    edited: bool,
+}
+
+impl<'a> EditedLine<'a> {
+    fn new(line: &'a str) -> Self {
+        Self { line, output: String::new(), copied: 0, edited: false }
+    }
+    
+    // Ranges must be replaced in order and start on character boundaries
+    fn replace(&mut self, start: usize, end: usize, with: &str) {
+        if !self.edited {
+            self.output.reserve(self.line.len());
+            self.edited = true;
+        }
+        self.output.push_str(&self.line[self.copied..start]);
+        self.output.push_str(with);
+        self.copied = end;
+    }
+    
+    fn finish(mut self) -> Cow<'a, str> {
+        if !self.edited {
+            return Cow::Borrowed(self.line);
+        }
+        self.output.push_str(&self.line[self.copied..]);
+        Cow::Owned(self.output)
+    }
+}
+
+// Byte length of the Rust char literal (`'a'`, `'\''`) that `rest` starts with;
+// None for lifetimes, which have no closing quote
+fn char_literal_len(rest: &str) -> Option<usize> {
+    let mut chars = rest.char_indices().skip(1);
+    if chars.next()?.1 == '\\' {
+        chars.next()?;
+    }
+    let (end, close) = chars.next()?;
+    (close == '\'').then_some(end + 1)
 }
 
-pub struct ContentAnalyzer;
+// Analyzes a buffer with no file behind it, such as an editor's unsaved text
+// or a request body. `name` may be a bare file name or a path; the language is
+// resolved from `language` ("rust" or "rs") when given, else from the name.
+pub fn analyze_content(name: &str, content: &str, language: Option<&str>) -> EnhancedFileInfo {
+    let analyzer = ContentAnalyzer::new();
+    let path = PathBuf::from(name);
+    let entry = FileEntry {
+        name: path.file_name().map_or_else(|| name.to_string(), |name| name.to_string_lossy().to_string()),
+        path,
+        size: content.len() as u64,
+        modified: SystemTime::UNIX_EPOCH,
+        is_dir: false,
+        tags: Vec::new(),
+        metadata: None,
+        enhanced_info: None,
+        symlink: None,
+        media: None,
+        content_hash: None,
+    };
+    let language = match language {
+        Some(language) => Some(analyzer.resolve_language(language)),
+        None => analyzer.detect_language(&entry.name),
+    };
+    analyzer.analyze_content(&entry, content, language)
+}
 
 impl ContentAnalyzer {
     pub fn new() -> Self {
-        ContentAnalyzer
+        Self::default()
+    }
+    
+    pub fn with_caps(caps: ComplexityCaps) -> Self {
+        Self { caps, ..Default::default() }
+    }
+    
+    // Detects languages with `languages` ahead of the built-in extension table
+    pub fn with_languages(mut self, languages: LanguageMap) -> Self {
+        self.languages = languages;
+        self
+    }
+    
+    // Scores importance with `boosts` instead of the built-in ones
+    pub fn with_boosts(mut self, boosts: Vec<ImportanceBoost>) -> Self {
+        self.boosts = Some(boosts);
+        self
     }
     
     pub fn analyze_file(&self, entry: &FileEntry) -> Result<EnhancedFileInfo, ScanError> {
+        self.analyze_file_with(entry, &OsFileSystem)
+    }
+    
+    pub fn analyze_file_with(&self, entry: &FileEntry, fs: &dyn FileSystem) -> Result<EnhancedFileInfo, ScanError> {
         if entry.is_dir {
             return Ok(EnhancedFileInfo::default());
         }
 
-        let mut enhanced_info = EnhancedFileInfo::default();
-        
-        // Detect language based on extension
-        enhanced_info.language = self.detect_language(&entry.name);
+        let language = self.detect_language_for(entry);
         
         // Try to read file content for analysis
-        if let Ok(content) = fs::read_to_string(&entry.path) {
+        let content = fs.read_to_string(&entry.path).ok();
+        
+        Ok(self.analyze(entry, content.as_deref(), language))
+    }
+    
+    // Analyzes content that did not come from a file system (e.g. stdin) under
+    // the given language instead of one detected from the entry's name
+    pub fn analyze_content(&self, entry: &FileEntry, content: &str, language: Option<String>) -> EnhancedFileInfo {
+        self.analyze(entry, Some(content), language)
+    }
+    
+    // Accepts a language name ("rust") or a file extension ("rs"); unknown
+    // names are kept as given so generic heuristics still apply
+    pub fn resolve_language(&self, language: &str) -> String {
+        let language = language.trim_start_matches('.').to_lowercase();
+        self.detect_language(&format!("file.{}", language)).unwrap_or(language)
+    }
+    
+    fn analyze(&self, entry: &FileEntry, content: Option<&str>, language: Option<String>) -> EnhancedFileInfo {
+        let mut enhanced_info = EnhancedFileInfo {
+            language,
+            ..Default::default()
+        };
+        
+        if let Some(content) = content {
             enhanced_info.line_count = Some(content.lines().count());
-            enhanced_info.complexity_score = Some(self.calculate_complexity(&content, &enhanced_info.language));
-            enhanced_info.content_summary = Some(self.generate_summary(&content, &enhanced_info.language));
-            enhanced_info.exports = self.extract_exports(&content, &enhanced_info.language);
-            enhanced_info.imports = self.extract_imports(&content, &enhanced_info.language);
-            enhanced_info.api_surface = self.extract_api_surface(&content, &enhanced_info.language);
-            enhanced_info.purpose = Some(self.infer_purpose(&entry.path, &content, &enhanced_info.language));
+            enhanced_info.complexity_score = Some(self.calculate_complexity(content, &enhanced_info.language));
+            enhanced_info.content_summary = Some(self.generate_summary(content, &enhanced_info.language));
+            enhanced_info.exports = self.extract_exports(content, &enhanced_info.language);
+            enhanced_info.imports = self.extract_imports(content, &enhanced_info.language);
+            enhanced_info.api_surface = self.extract_api_surface(content, &enhanced_info.language);
+            enhanced_info.symbols = extract_symbols(content, &enhanced_info.language);
+            enhanced_info.purpose = Some(self.infer_purpose(&entry.path, content, &enhanced_info.language));
+            if let Some((keys, depth)) = config_keys::config_structure(content, &enhanced_info.language) {
+                enhanced_info.config_keys = keys;
+                enhanced_info.config_depth = Some(depth);
+            }
+            if enhanced_info.language.is_some() && similarity::should_fingerprint(&entry.path, entry.size) {
+                enhanced_info.fingerprint = Some(ContentFingerprint::from_content(content));
+            }
         }
         
         // Calculate importance based on various factors
-        enhanced_info.importance_score = Some(self.calculate_importance(entry, &enhanced_info));
+        enhanced_info.importance_score = Some(self.calculate_importance(entry, content, &enhanced_info));
         
-        Ok(enhanced_info)
+        enhanced_info
+    }
+    
+    // The language an override in the language map forces on the entry's
+    // path, else the one its name's extension maps to
+    pub fn detect_language_for(&self, entry: &FileEntry) -> Option<String> {
+        match self.languages.forced(&entry.path) {
+            Some(language) => Some(language.to_string()),
+            None => self.detect_language(&entry.name),
+        }
     }
     
-    fn detect_language(&self, filename: &str) -> Option<String> {
+    pub fn detect_language(&self, filename: &str) -> Option<String> {
         let extension = std::path::Path::new(filename)
             .extension()
             .and_then(|ext| ext.to_str())
             .unwrap_or("");
+        if let Some(language) = self.languages.for_extension(extension) {
+            return Some(language.to_string());
+        }
             
         match extension {
             "rs" => Some("rust".to_string()),
@@ -479,27 +1194,37 @@ impl ContentAnalyzer {
             }
         }
         
-        complexity.min(10.0) // Cap at 10
+        ComplexityCaps::apply(self.caps.complexity, complexity)
     }
     
     fn calculate_branching_complexity(&self, content: &str, language: &Option<String>) -> f64 {
         let mut cyclomatic_complexity = 1.0; // Base complexity
         let mut cognitive_complexity = 0.0;
         
-        let lines: Vec<&str> = content.lines().collect();
+        let statements = self.join_logical_statements(content, language);
+        let indentation_nesting = Self::uses_indentation_nesting(language);
+        let mut indent_stack = Vec::new();
         let mut nesting_level = 0;
         let mut max_nesting = 0;
         
-        for line in &lines {
-            let trimmed = line.trim();
+        for statement in &statements {
+            let trimmed: &str = &statement.text;
             
             // Update nesting level based on indentation and braces
-            if trimmed.contains('{') {
-                nesting_level += 1;
-                max_nesting = max_nesting.max(nesting_level);
-            }
-            if trimmed.contains('}') && nesting_level > 0 {
-                nesting_level -= 1;
+            if indentation_nesting {
+                if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                    let level = Self::indentation_level(&mut indent_stack, statement.indent);
+                    nesting_level = if trimmed.ends_with(':') { level + 1 } else { level };
+                    max_nesting = max_nesting.max(nesting_level);
+                }
+            } else {
+                if trimmed.contains('{') {
+                    nesting_level += 1;
+                    max_nesting = max_nesting.max(nesting_level);
+                }
+                if trimmed.contains('}') && nesting_level > 0 {
+                    nesting_level -= 1;
+                }
             }
             
             // Language-specific conditional detection
@@ -549,7 +1274,7 @@ impl ContentAnalyzer {
             (cognitive_complexity * 0.4) + 
             (nesting_penalty * 0.2);
         
-        total_branching_complexity.min(8.0) // Cap branching complexity at 8
+        ComplexityCaps::apply(self.caps.branching, total_branching_complexity)
     }
     
     fn count_rust_conditionals(&self, line: &str) -> f64 {
@@ -895,43 +1620,30 @@ impl ContentAnalyzer {
     }
     
     fn detect_hardcoded_dates(&self, line: &str) -> bool {
-        // Simple date patterns using string matching
-        if line.matches("-").count() >= 2 && (
-            line.contains("2019") || line.contains("2020") || line.contains("2021") ||
-            line.contains("2022") || line.contains("2023") || line.contains("2024") ||
-            line.contains("2025") || line.contains("2026") || line.contains("2027")
-        ) {
-            return true;
-        }
-        
-        // Common date separators
-        if line.matches("/").count() >= 2 && (
-            line.contains("2019") || line.contains("2020") || line.contains("2021") ||
-            line.contains("2022") || line.contains("2023") || line.contains("2024") ||
-            line.contains("2025") || line.contains("2026") || line.contains("2027")
-        ) {
+        // Simple date patterns using string matching, with '-' or '/' separators
+        let separators = |separator: u8| line.bytes().filter(|&byte| byte == separator).count();
+        if (separators(b'-') >= 2 || separators(b'/') >= 2) && Self::contains_year(line, 2019..=2027) {
             return true;
         }
         
         // Year patterns in conditionals
-        if line.contains(" if ") || line.contains("==") || line.contains("!=") || 
-            line.contains(">") || line.contains("<") {
-            for year in 1990..=2030 {
-                if line.contains(&year.to_string()) {
-                    return true;
-                }
-            }
+        if (line.contains(" if ") || line.contains("==") || line.contains("!=") || 
+            line.contains('>') || line.contains('<')) && Self::contains_year(line, 1990..=2030) {
+            return true;
         }
         
         // Common timestamp patterns (starts with 1 and has many digits)
-        let words: Vec<&str> = line.split_whitespace().collect();
-        for word in words {
-            if word.starts_with('1') && word.len() >= 10 && word.chars().all(|c| c.is_ascii_digit()) {
-                return true;
-            }
-        }
-        
-        false
+        line.split_whitespace().any(|word| {
+            word.starts_with('1') && word.len() >= 10 && word.bytes().all(|byte| byte.is_ascii_digit())
+        })
+    }
+    
+    // Whether any four consecutive digits in `line` spell a year in `years`
+    fn contains_year(line: &str, years: std::ops::RangeInclusive<u32>) -> bool {
+        line.as_bytes().windows(4).any(|window| {
+            window.iter().all(u8::is_ascii_digit)
+                && years.contains(&window.iter().fold(0, |year, digit| year * 10 + u32::from(digit - b'0')))
+        })
     }
     
     fn count_hardcoded_values(&self, line: &str) -> usize {
@@ -945,24 +1657,22 @@ impl ContentAnalyzer {
             let common_numbers = ["0", "1", "2", "4", "8", "16", "32", "64", "128", "256", "512", "1024", "-1"];
             
             // Look for numeric literals (including floats)
-            let words: Vec<&str> = line.split_whitespace().collect();
-            for word in words {
+            for word in line.split_whitespace() {
                 let clean_word = word.trim_matches(|c| !char::is_ascii_digit(&c) && c != '-' && c != '.');
                 if !clean_word.is_empty() {
                     // Handle both integers and floats
-                    if clean_word.chars().all(|c| c.is_ascii_digit() || c == '-' || c == '.') {
-                        if clean_word.len() >= 2 && !common_numbers.contains(&clean_word) {
-                            // Try parsing as float first, then integer
-                            if let Ok(_) = clean_word.parse::<f64>() {
-                                // Check if it's not a year (already handled by date detection)
-                                if let Ok(num) = clean_word.parse::<i32>() {
-                                    if !(1900..=2100).contains(&num) {
-                                        count += 1;
-                                    }
-                                } else {
-                                    // It's a float or too large for i32
+                    if clean_word.chars().all(|c| c.is_ascii_digit() || c == '-' || c == '.')
+                        && clean_word.len() >= 2 && !common_numbers.contains(&clean_word) {
+                        // Try parsing as float first, then integer
+                        if clean_word.parse::<f64>().is_ok() {
+                            // Check if it's not a year (already handled by date detection)
+                            if let Ok(num) = clean_word.parse::<i32>() {
+                                if !(1900..=2100).contains(&num) {
                                     count += 1;
                                 }
+                            } else {
+                                // It's a float or too large for i32
+                                count += 1;
                             }
                         }
                     }
@@ -978,53 +1688,299 @@ impl ContentAnalyzer {
         count
     }
     
-    fn remove_strings_and_comments(&self, line: &str) -> String {
-        let mut result = String::new();
-        let mut chars = line.chars().enumerate().peekable();
-        let mut in_string = false;
-        let mut string_char = '"';
+    // Group physical lines into logical statements so conditions split across
+    // lines (`if foo &&\n bar {`) are analyzed as a single unit
+    fn join_logical_statements<'a>(&self, content: &'a str, language: &Option<String>) -> Vec<LogicalStatement<'a>> {
+        let mut statements: Vec<LogicalStatement> = Vec::new();
+        let mut multiline_state = MultilineState::default();
+        let mut paren_depth: i32 = 0;
+        let mut open_statement = false;
         
-        while let Some((_i, ch)) = chars.next() {
-            if !in_string {
-                // Check for start of comment
-                if ch == '/' && chars.peek().map(|(_, c)| *c) == Some('/') {
-                    // Rest of line is comment
-                    break;
+        for line in content.lines() {
+            let indent = Self::indentation_width(line);
+            let trimmed = match Self::mask_multiline_constructs(line, &mut multiline_state, language) {
+                Cow::Borrowed(masked) => Cow::Borrowed(masked.trim()),
+                Cow::Owned(masked) => Cow::Owned(masked.trim().to_string()),
+            };
+            
+            // Comment-only lines never contribute to a statement
+            if trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with("/*") || trimmed.starts_with('*') || trimmed.starts_with('#') {
+                if !open_statement {
+                    statements.push(LogicalStatement { text: trimmed, indent });
                 }
-                if ch == '/' && chars.peek().map(|(_, c)| *c) == Some('*') {
-                    // Block comment start - skip until */
-                    chars.next(); // consume '*'
-                    let mut found_end = false;
-                    while let Some((_, c)) = chars.next() {
-                        if c == '*' && chars.peek().map(|(_, c)| *c) == Some('/') {
-                            chars.next(); // consume '/'
-                            found_end = true;
-                            break;
-                        }
+                continue;
+            }
+            
+            let bracket_delta = Self::bracket_delta(&trimmed, language);
+            
+            let continues_previous = open_statement || Self::starts_with_continuation(&trimmed);
+            let joins = continues_previous && statements.last().is_some_and(|last| !last.text.is_empty());
+            if !joins {
+                paren_depth = 0;
+            }
+            paren_depth = (paren_depth + bracket_delta).max(0);
+            
+            // Strings and comments only matter when no bracket keeps the statement open
+            open_statement = paren_depth > 0 || Self::ends_with_continuation(self.remove_strings_and_comments(&trimmed).trim_end());
+            
+            match statements.last_mut() {
+                Some(last) if joins => {
+                    // Trailing comments would swallow the rest of the joined statement
+                    let kept = Self::strip_trailing_comment(&last.text).len();
+                    let text = last.text.to_mut();
+                    text.truncate(kept);
+                    text.push(' ');
+                    text.push_str(Self::strip_trailing_comment(&trimmed));
+                }
+                _ => statements.push(LogicalStatement { text: trimmed, indent }),
+            }
+        }
+        
+        statements
+    }
+    
+    // Blanks out block comments and triple-quoted strings, which may span several
+    // physical lines, so keywords inside them never reach the branch counters.
+    // Every delimiter is ASCII, so the line is scanned byte by byte and only
+    // copied when something is masked.
+    fn mask_multiline_constructs<'a>(line: &'a str, state: &mut MultilineState, language: &Option<String>) -> Cow<'a, str> {
+        let python = language.as_deref() == Some("python");
+        let rust = language.as_deref() == Some("rust");
+        let backtick_strings = matches!(language.as_deref(), Some("javascript") | Some("typescript") | Some("go"));
+        
+        let bytes = line.as_bytes();
+        let starts_with_at = |i: usize, pattern: &[u8]| bytes[i..].starts_with(pattern);
+        
+        // Only an opening block comment or triple quote masks anything on a line
+        // that starts outside one
+        let clear = state.triple_quote.is_none() && state.block_comment_depth == 0;
+        if clear && (if python { !line.contains("\"\"\"") && !line.contains("'''") } else { !line.contains("/*") }) {
+            return Cow::Borrowed(line);
+        }
+        
+        let mut output = EditedLine::new(line);
+        let mut in_string: Option<u8> = None;
+        let mut i = 0;
+        
+        while i < bytes.len() {
+            let byte = bytes[i];
+            
+            if let Some(quote) = state.triple_quote {
+                let end = match bytes[i..].windows(quote.len()).position(|window| window == quote.as_bytes()) {
+                    Some(at) => {
+                        let close = i + at;
+                        state.triple_quote = None;
+                        close + quote.len()
                     }
-                    if found_end {
-                        result.push(' '); // Replace comment with space
+                    None => bytes.len(),
+                };
+                output.replace(i, end, "");
+                i = end;
+                continue;
+            }
+            
+            if state.block_comment_depth > 0 {
+                let start = i;
+                while i < bytes.len() && state.block_comment_depth > 0 {
+                    if starts_with_at(i, b"*/") {
+                        state.block_comment_depth -= 1;
+                        i += 2;
+                    } else if rust && starts_with_at(i, b"/*") {
+                        // Rust block comments nest
+                        state.block_comment_depth += 1;
+                        i += 2;
+                    } else {
+                        i += 1;
                     }
-                    continue;
                 }
-                // Check for start of string
-                if ch == '"' || ch == '\'' {
-                    in_string = true;
-                    string_char = ch;
-                    result.push(' '); // Replace string with space
+                output.replace(start, i, if state.block_comment_depth == 0 { " " } else { "" });
+                continue;
+            }
+            
+            if let Some(quote) = in_string {
+                if byte == b'\\' {
+                    i += 1;
+                } else if byte == quote {
+                    in_string = None;
+                }
+                i += 1;
+                continue;
+            }
+            
+            if python {
+                if byte == b'#' {
+                    break;
+                }
+                if starts_with_at(i, b"\"\"\"") || starts_with_at(i, b"'''") {
+                    state.triple_quote = Some(if byte == b'"' { "\"\"\"" } else { "'''" });
+                    output.replace(i + 2, i + 3, "");
+                    i += 3;
                     continue;
                 }
-                result.push(ch);
             } else {
-                // Inside string, look for end (simple version - ignore escape sequences)
-                if ch == string_char {
-                    in_string = false;
+                if starts_with_at(i, b"//") {
+                    break;
+                }
+                if starts_with_at(i, b"/*") {
+                    state.block_comment_depth = 1;
+                    output.replace(i, i + 2, "");
+                    i += 2;
+                    continue;
+                }
+            }
+            
+            if rust && byte == b'\'' {
+                // Char literals like '"' must not open a string; lifetimes are left alone
+                if let Some(literal_len) = char_literal_len(&line[i..]) {
+                    i += literal_len;
+                    continue;
+                }
+            } else if byte == b'"' || byte == b'\'' || (backtick_strings && byte == b'`') {
+                in_string = Some(byte);
+            }
+            
+            i += 1;
+        }
+        
+        output.finish()
+    }
+    
+    // Net change in open parentheses/brackets, ignoring string and char literals
+    // (including escaped quotes) and trailing line comments
+    fn bracket_delta(line: &str, language: &Option<String>) -> i32 {
+        let python = language.as_deref() == Some("python");
+        let rust = language.as_deref() == Some("rust");
+        let bytes = line.as_bytes();
+        let mut in_string: Option<u8> = None;
+        let mut delta = 0;
+        let mut i = 0;
+        
+        while i < bytes.len() {
+            let byte = bytes[i];
+            if let Some(quote) = in_string {
+                if byte == b'\\' {
+                    i += 1;
+                } else if byte == quote {
+                    in_string = None;
+                }
+            } else if rust && byte == b'\'' {
+                // Skip char literals such as '(' or '\''; lifetimes have no closing quote
+                if let Some(literal_len) = char_literal_len(&line[i..]) {
+                    i += literal_len;
+                    continue;
+                }
+            } else if (python && byte == b'#') || (!python && byte == b'/' && bytes.get(i + 1) == Some(&b'/')) {
+                break;
+            } else if matches!(byte, b'"' | b'\'' | b'`') {
+                in_string = Some(byte);
+            } else if matches!(byte, b'(' | b'[') {
+                delta += 1;
+            } else if matches!(byte, b')' | b']') {
+                delta -= 1;
+            }
+            i += 1;
+        }
+        
+        delta
+    }
+    
+    fn uses_indentation_nesting(language: &Option<String>) -> bool {
+        matches!(language.as_deref(), Some("python") | Some("yaml"))
+    }
+    
+    fn indentation_width(line: &str) -> usize {
+        line.chars()
+            .take_while(|c| c.is_whitespace())
+            .map(|c| if c == '\t' { 4 } else { 1 })
+            .sum()
+    }
+    
+    // Returns the block depth for a statement at the given indentation, relative
+    // to the shallowest indentation seen so far
+    fn indentation_level(stack: &mut Vec<usize>, indent: usize) -> usize {
+        while stack.last().is_some_and(|&top| indent < top) {
+            stack.pop();
+        }
+        if stack.last().is_none_or(|&top| indent > top) {
+            stack.push(indent);
+        }
+        stack.len() - 1
+    }
+    
+    fn starts_with_continuation(line: &str) -> bool {
+        line.starts_with("&&") || line.starts_with("||") ||
+        line.starts_with("and ") || line.starts_with("or ") ||
+        line == "{"
+    }
+    
+    fn ends_with_continuation(line: &str) -> bool {
+        line.ends_with("&&") || line.ends_with("||") ||
+        line.ends_with(" and") || line.ends_with(" or") ||
+        line.ends_with('\\')
+    }
+    
+    fn strip_trailing_comment(line: &str) -> &str {
+        let mut in_string: Option<char> = None;
+        let mut previous = '\0';
+        
+        for (i, ch) in line.char_indices() {
+            match in_string {
+                Some(quote) => {
+                    if ch == quote && previous != '\\' {
+                        in_string = None;
+                    }
+                }
+                None => {
+                    if ch == '"' || ch == '\'' {
+                        in_string = Some(ch);
+                    } else if ch == '/' && previous == '/' {
+                        return line[..i - 1].trim_end();
+                    }
+                }
+            }
+            previous = ch;
+        }
+        
+        line
+    }
+    
+    fn remove_strings_and_comments<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        let bytes = line.as_bytes();
+        let mut result = EditedLine::new(line);
+        let mut i = 0;
+        
+        while i < bytes.len() {
+            match bytes[i] {
+                b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                    // Rest of line is comment
+                    result.replace(i, bytes.len(), "");
+                    break;
+                }
+                b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                    // Block comment - replaced with a space when it closes on this line
+                    match bytes[i + 2..].windows(2).position(|window| window == b"*/") {
+                        Some(at) => {
+                            let close = i + 2 + at;
+                            result.replace(i, close + 2, " ");
+                            i = close + 2;
+                        }
+                        None => {
+                            result.replace(i, bytes.len(), "");
+                            break;
+                        }
+                    }
+                }
+                quote @ (b'"' | b'\'') => {
+                    // Strings become a space (simple version - ignore escape sequences)
+                    let end = bytes[i + 1..].iter().position(|&byte| byte == quote).map_or(bytes.len(), |offset| i + offset + 2);
+                    result.replace(i, end, " ");
+                    i = end;
                 }
-                // Don't add string content to result
+                _ => i += 1,
             }
         }
         
-        result
+        result.finish()
     }
     
     fn analyze_branch_purity(&self, line: &str, _language: &Option<String>) -> bool {
@@ -1103,7 +2059,7 @@ impl ContentAnalyzer {
                     .trim_start_matches("/*")
                     .trim();
                 if comment.len() > 10 {
-                    return format!("{}", comment.chars().take(100).collect::<String>());
+                    return comment.chars().take(100).collect::<String>();
                 }
             }
         }
@@ -1192,25 +2148,20 @@ impl ContentAnalyzer {
     fn extract_api_surface(&self, content: &str, language: &Option<String>) -> Vec<String> {
         let mut api = Vec::new();
         
-        if let Some(lang) = language {
-            match lang.as_str() {
-                "rust" => {
-                    for line in content.lines() {
-                        let trimmed = line.trim();
-                        if trimmed.starts_with("pub fn ") || trimmed.starts_with("pub struct ") || 
-                           trimmed.starts_with("pub enum ") || trimmed.starts_with("pub trait ") {
-                            api.push(trimmed.to_string());
-                        }
-                    }
+        if language.as_deref() == Some("rust") {
+            for line in content.lines() {
+                let trimmed = line.trim();
+                if trimmed.starts_with("pub fn ") || trimmed.starts_with("pub struct ") || 
+                   trimmed.starts_with("pub enum ") || trimmed.starts_with("pub trait ") {
+                    api.push(trimmed.to_string());
                 }
-                _ => {}
             }
         }
         
         api
     }
     
-    fn infer_purpose(&self, path: &PathBuf, content: &str, language: &Option<String>) -> String {
+    fn infer_purpose(&self, path: &Path, content: &str, language: &Option<String>) -> String {
         let path_str = path.to_string_lossy().to_lowercase();
         
         // Purpose based on path patterns
@@ -1244,7 +2195,7 @@ impl ContentAnalyzer {
         }
     }
     
-    fn calculate_importance(&self, entry: &FileEntry, enhanced_info: &EnhancedFileInfo) -> f64 {
+    fn calculate_importance(&self, entry: &FileEntry, content: Option<&str>, enhanced_info: &EnhancedFileInfo) -> f64 {
         let mut importance = 1.0;
         
         // Size factor
@@ -1267,6 +2218,10 @@ impl ContentAnalyzer {
             importance += 0.5;
         }
         
+        // Architectural roles: routes, stores, dependency-injection wiring
+        let boosts = self.boosts.as_deref().unwrap_or_else(|| importance::default_boosts());
+        importance += importance::boost_for(boosts, &entry.path, content);
+        
         importance.min(10.0) // Cap at 10
     }
     
@@ -1289,11 +2244,13 @@ impl ContentAnalyzer {
             total_branches: 0,
         };
         
-        let lines: Vec<&str> = content.lines().collect();
+        let statements = self.join_logical_statements(content, language);
+        let indentation_nesting = Self::uses_indentation_nesting(language);
+        let mut indent_stack = Vec::new();
         let mut nesting_level = 0;
         
-        for line in &lines {
-            let trimmed = line.trim();
+        for statement in &statements {
+            let trimmed: &str = &statement.text;
             
             // Skip empty lines and comments
             if trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with("/*") || trimmed.starts_with("*") || trimmed.starts_with("#") {
@@ -1304,15 +2261,25 @@ impl ContentAnalyzer {
             let cleaned_line = self.remove_strings_and_comments(trimmed);
             
             // Update nesting level and track distribution (but exclude loops)  
-            let has_opening_brace = cleaned_line.contains('{');
-            let nesting_level_for_distribution = if has_opening_brace { nesting_level + 1 } else { nesting_level };
-            if has_opening_brace {
-                nesting_level += 1;
+            let (opens_block, nesting_level_for_distribution) = if indentation_nesting {
+                // Whitespace-delimited languages: a trailing ':' opens a block one level deeper
+                let level = Self::indentation_level(&mut indent_stack, statement.indent);
+                let opens_block = cleaned_line.trim_end().ends_with(':');
+                nesting_level = if opens_block { level + 1 } else { level };
                 details.max_nesting = details.max_nesting.max(nesting_level);
-            }
-            if cleaned_line.contains('}') && nesting_level > 0 {
-                nesting_level -= 1;
-            }
+                (opens_block, nesting_level)
+            } else {
+                let has_opening_brace = cleaned_line.contains('{');
+                let nesting_level_for_distribution = if has_opening_brace { nesting_level + 1 } else { nesting_level };
+                if has_opening_brace {
+                    nesting_level += 1;
+                    details.max_nesting = details.max_nesting.max(nesting_level);
+                }
+                if cleaned_line.contains('}') && nesting_level > 0 {
+                    nesting_level -= 1;
+                }
+                (has_opening_brace, nesting_level_for_distribution)
+            };
             
             // Check for branching constructs and analyze their properties
             let mut is_branch = false;
@@ -1329,12 +2296,10 @@ impl ContentAnalyzer {
                             if_count += 1;
                         }
                         // Count "if " preceded by whitespace or certain punctuation
-                        for i in 1..cleaned_line.len().saturating_sub(2) {
-                            if &cleaned_line[i..i+3] == "if " {
-                                let prev_char = cleaned_line.chars().nth(i-1).unwrap();
-                                if prev_char.is_whitespace() || prev_char == '{' || prev_char == '(' || prev_char == ')' || prev_char == ';' {
-                                    if_count += 1;
-                                }
+                        for i in cleaned_line.match_indices("if ").map(|(i, _)| i).filter(|&i| i > 0) {
+                            let prev_char = cleaned_line[..i].chars().next_back().unwrap();
+                            if prev_char.is_whitespace() || prev_char == '{' || prev_char == '(' || prev_char == ')' || prev_char == ';' {
+                                if_count += 1;
                             }
                         }
                         details.conditional_count += if_count;
@@ -1356,12 +2321,10 @@ impl ContentAnalyzer {
                                 loop_count += 1;
                             }
                             // Count keyword preceded by whitespace or certain punctuation
-                            for i in 1..trimmed.len().saturating_sub(keyword.len()-1) {
-                                if &trimmed[i..i+keyword.len()] == *keyword {
-                                    let prev_char = trimmed.chars().nth(i-1).unwrap();
-                                    if prev_char.is_whitespace() || prev_char == '{' || prev_char == '(' || prev_char == ')' || prev_char == ';' {
-                                        loop_count += 1;
-                                    }
+                            for i in trimmed.match_indices(keyword).map(|(i, _)| i).filter(|&i| i > 0) {
+                                let prev_char = trimmed[..i].chars().next_back().unwrap();
+                                if prev_char.is_whitespace() || prev_char == '{' || prev_char == '(' || prev_char == ')' || prev_char == ';' {
+                                    loop_count += 1;
                                 }
                             }
                         }
@@ -1488,7 +2451,7 @@ impl ContentAnalyzer {
             }
             
             // Track nesting distribution for conditional branches (exclude loops)
-            if has_opening_brace && is_conditional_branch && !is_loop {
+            if opens_block && is_conditional_branch && !is_loop {
                 // Use the nesting level at the time of the opening brace
                 *details.nesting_distribution.entry(nesting_level_for_distribution).or_insert(0) += 1;
             }
@@ -1507,39 +2470,47 @@ impl ContentAnalyzer {
     }
 }
 
-impl Default for EnhancedFileInfo {
-    fn default() -> Self {
-        Self {
-            language: None,
-            framework: None,
-            line_count: None,
-            complexity_score: None,
-            importance_score: None,
-            content_summary: None,
-            exports: Vec::new(),
-            imports: Vec::new(),
-            dependencies: Vec::new(),
-            related_files: Vec::new(),
-            purpose: None,
-            last_author: None,
-            change_frequency: None,
-            api_surface: Vec::new(),
-        }
-    }
-}
-
 pub struct EnhancedGenericMapper {
     analyzer: ContentAnalyzer,
     basic_mapper: GenericMapper,
 }
 
+impl Default for EnhancedGenericMapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl EnhancedGenericMapper {
     pub fn new() -> Self {
+        Self::with_caps(ComplexityCaps::default())
+    }
+    
+    pub fn with_caps(caps: ComplexityCaps) -> Self {
         Self {
-            analyzer: ContentAnalyzer,
+            analyzer: ContentAnalyzer::with_caps(caps),
             basic_mapper: GenericMapper,
         }
     }
+    
+    pub fn with_languages(mut self, languages: LanguageMap) -> Self {
+        self.analyzer = self.analyzer.with_languages(languages);
+        self
+    }
+    
+    pub fn with_boosts(mut self, boosts: Vec<ImportanceBoost>) -> Self {
+        self.analyzer = self.analyzer.with_boosts(boosts);
+        self
+    }
+    
+    // The mapper the built-in profiles build for enhanced analysis
+    fn for_options(options: &ScanOptions) -> Self {
+        let mapper = Self::with_caps(options.complexity_caps).with_languages(options.languages.clone());
+        match options.profile_boosts() {
+            Some(boosts) => mapper.with_boosts(boosts.clone()),
+            None => mapper,
+        }
+    }
 }
 
 impl Mapper for EnhancedGenericMapper {
@@ -1587,181 +2558,195 @@ impl Mapper for EnhancedGenericMapper {
     fn name(&self) -> &str {
         "enhanced-generic"
     }
-}
-
-impl EnhancedMapper for EnhancedGenericMapper {
-    fn analyze(&self, entry: &mut FileEntry) -> Result<(), ScanError> {
+    
+    fn tags_entry_points(&self) -> bool {
+        true
+    }
+    
+    fn analyzes_content(&self) -> bool {
+        true
+    }
+    
+    fn parallel_analyzer(&self) -> Option<ContentAnalyzer> {
+        Some(self.analyzer.clone())
+    }
+    
+    fn analyze(&self, entry: &mut FileEntry, fs: &dyn FileSystem) -> Result<(), ScanError> {
         if entry.enhanced_info.is_none() {
-            let enhanced_info = self.analyzer.analyze_file(entry)?;
+            let enhanced_info = self.analyzer.analyze_file_with(entry, fs)?;
             entry.enhanced_info = Some(enhanced_info);
         }
         Ok(())
     }
-    
-    fn name(&self) -> &str {
-        "enhanced-generic"
-    }
 }
 
+pub type ProgressCallback = Box<dyn Fn(&ScanProgress)>;
+
 pub struct DirectoryScanner {
     options: ScanOptions,
     mapper: Box<dyn Mapper>,
-    enhanced_mapper: Option<Box<dyn EnhancedMapper>>,
+    progress: Option<ProgressCallback>,
+    metrics: Option<MetricsCallback>,
+    // Shared with the `ScanSession` that created the scanner, if any
+    cache: Option<Arc<session::AnalysisCache>>,
 }
 
 impl DirectoryScanner {
     pub fn new(options: ScanOptions) -> Self {
-        let (mapper, enhanced_mapper): (Box<dyn Mapper>, Option<Box<dyn EnhancedMapper>>) = 
-            if options.enhanced_analysis {
-                let enhanced = EnhancedGenericMapper::new();
-                (Box::new(EnhancedGenericMapper::new()), Some(Box::new(enhanced)))
-            } else {
-                match options.mapper_profile.as_str() {
-                    "generic" => (Box::new(GenericMapper), None),
-                    _ => (Box::new(GenericMapper), None), // Default fallback
-                }
-            };
-        
-        Self { options, mapper, enhanced_mapper }
+        // Unknown profiles fall back to the generic mapper
+        let mapper = MapperRegistry::default().create(&options).unwrap_or_else(|| {
+            tracing::warn!(profile = %options.mapper_profile, "unknown mapper profile, using generic");
+            let fallback = ScanOptions { mapper_profile: "generic".to_string(), ..options.clone() };
+            MapperRegistry::default().create(&fallback).expect("generic profile is built in")
+        });
+        Self { options, mapper, progress: None, metrics: None, cache: None }
+    }
+    
+    // Scanner using a caller-provided mapper regardless of `mapper_profile`
+    pub fn with_mapper(options: ScanOptions, mapper: Box<dyn Mapper>) -> Self {
+        Self { options, mapper, progress: None, metrics: None, cache: None }
+    }
+    
+    // Resolves `mapper_profile` against a registry that may include custom mappers
+    pub fn with_registry(options: ScanOptions, registry: &MapperRegistry) -> Result<Self, ScanError> {
+        let mapper = registry.create(&options).ok_or_else(|| ScanError::InvalidConfig {
+            message: format!("Unknown mapper profile '{}', expected one of: {}",
+                options.mapper_profile, registry.profiles().join(", ")),
+        })?;
+        Ok(Self { options, mapper, progress: None, metrics: None, cache: None })
+    }
+    
+    // Called after every walked entry during `scan` and `scan_with`
+    pub fn with_progress<F: Fn(&ScanProgress) + 'static>(mut self, callback: F) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+    
+    // Called once per finished scan with its throughput and cache use
+    pub fn with_metrics<F: Fn(&ScanMetrics) + 'static>(mut self, callback: F) -> Self {
+        self.metrics = Some(Box::new(callback));
+        self
+    }
+    
+    pub(crate) fn with_cache(mut self, cache: Arc<session::AnalysisCache>) -> Self {
+        self.cache = Some(cache);
+        self
     }
     
     pub fn scan<P: Into<PathBuf>>(&self, path: P) -> Result<ScanResult, ScanError> {
         let root_path = path.into();
-        let start_time = Instant::now();
         
-        if !root_path.exists() {
-            return Err(ScanError::PathNotFound { path: root_path });
+        #[cfg(feature = "archives")]
+        if root_path.is_file() && is_archive(&root_path) {
+            return self.scan_archive(root_path);
         }
         
-        let mut files = Vec::new();
-        let mut errors = Vec::new();
-        let mut total_size = 0u64;
-        let mut dir_count = 0;
-        
-        let walker = WalkDir::new(&root_path)
-            .follow_links(self.options.follow_symlinks)
-            .max_depth(self.options.max_depth.unwrap_or(usize::MAX))
-            .into_iter();
-        
-        for entry_result in walker {
-            match entry_result {
-                Ok(entry) => {
-                    if self.should_ignore(&entry) {
-                        continue;
-                    }
-                    
-                    match self.process_entry(entry) {
-                        Ok(mut file_entry) => {
-                            if file_entry.is_dir {
-                                dir_count += 1;
-                            } else {
-                                total_size += file_entry.size;
-                            }
-                            
-                            // Apply enhanced analysis if available
-                            if let Some(enhanced_mapper) = &self.enhanced_mapper {
-                                if let Err(e) = enhanced_mapper.analyze(&mut file_entry) {
-                                    errors.push(format!("Enhanced analysis failed for {}: {}", 
-                                        file_entry.path.display(), e));
-                                }
-                            }
-                            
-                            // Apply classification
-                            file_entry.tags = self.mapper.classify(&file_entry);
-                            files.push(file_entry);
-                        },
-                        Err(e) => {
-                            errors.push(format!("{}", e));
-                        }
-                    }
-                }
-                Err(e) => {
-                    errors.push(format!("Walk error: {}", e));
-                }
+        self.scan_with(&OsFileSystem, root_path)
+    }
+    
+    // Scans a .zip, .tar or .tar.gz without extracting it; entry paths are
+    // rooted at the archive path, e.g. `release.zip/src/main.rs`
+    #[cfg(feature = "archives")]
+    pub fn scan_archive<P: Into<PathBuf>>(&self, path: P) -> Result<ScanResult, ScanError> {
+        let archive_path = path.into();
+        let fs = load_archive(&archive_path)?;
+        self.scan_with(&fs, archive_path)
+    }
+    
+    pub fn scan_with<P: Into<PathBuf>>(&self, fs: &dyn FileSystem, path: P) -> Result<ScanResult, ScanError> {
+        let mut run = self.start(fs, path)?;
+        while run.step() {
+            if let Some(callback) = &self.progress {
+                callback(run.progress());
             }
         }
-        
-        let duration = start_time.elapsed();
-        let duration_ms = duration.as_millis() as u64;
-        let files_per_second = if duration_ms > 0 {
-            (files.len() as f64) / (duration_ms as f64 / 1000.0)
-        } else {
-            0.0
-        };
-        
-        let stats = ScanStats {
-            total_files: files.len(),
-            total_dirs: dir_count,
-            total_size,
-            scan_duration_ms: duration_ms,
-            files_per_second,
-        };
-        
-        Ok(ScanResult {
-            root_path,
-            files,
-            stats,
-            errors,
-        })
+        Ok(run.finish())
     }
     
-    fn should_ignore(&self, entry: &DirEntry) -> bool {
-        let path_str = entry.path().to_string_lossy();
+    // Bounded-memory scan: each entry goes to `sink` as soon as it is classified
+    // and is not kept, so memory stays flat however large the tree. The returned
+    // result has stats, issues and findings but no files; see `ScanRun::take_files`.
+    pub fn scan_streaming<P, F>(&self, path: P, sink: F) -> Result<ScanResult, ScanError>
+    where
+        P: Into<PathBuf>,
+        F: FnMut(FileEntry) -> Result<(), ScanError>,
+    {
+        let root_path = path.into();
         
-        // Skip hidden files/dirs unless explicitly allowed
-        if !self.options.include_hidden {
-            if let Some(name) = entry.file_name().to_str() {
-                if name.starts_with('.') && name != "." && name != ".." {
-                    return true;
-                }
-            }
+        #[cfg(feature = "archives")]
+        if root_path.is_file() && is_archive(&root_path) {
+            let fs = load_archive(&root_path)?;
+            return self.scan_streaming_with(&fs, root_path, sink);
         }
         
-        // Check ignore patterns
-        for pattern in &self.options.ignore_patterns {
-            if path_str.contains(pattern) {
-                return true;
+        self.scan_streaming_with(&OsFileSystem, root_path, sink)
+    }
+    
+    pub fn scan_streaming_with<P, F>(&self, fs: &dyn FileSystem, path: P, mut sink: F) -> Result<ScanResult, ScanError>
+    where
+        P: Into<PathBuf>,
+        F: FnMut(FileEntry) -> Result<(), ScanError>,
+    {
+        let mut run = self.start(fs, path)?;
+        while run.step() {
+            if let Some(callback) = &self.progress {
+                callback(run.progress());
+            }
+            for entry in run.take_files() {
+                sink(entry)?;
             }
         }
-        
-        false
+        Ok(run.finish())
     }
     
-    fn process_entry(&self, entry: DirEntry) -> Result<FileEntry, ScanError> {
-        let path = entry.path().to_path_buf();
-        let metadata = entry.metadata().map_err(|e| {
-            if let Some(io_err) = e.io_error() {
-                ScanError::Io(std::io::Error::new(io_err.kind(), format!("{}", e)))
-            } else {
-                ScanError::InvalidConfig { message: format!("Failed to get metadata: {}", e) }
+    // Begins a scan that the caller advances with `ScanRun::step`
+    pub fn start<'a, P: Into<PathBuf>>(&'a self, fs: &'a dyn FileSystem, path: P) -> Result<ScanRun<'a>, ScanError> {
+        let root_path = path.into();
+        if !fs.exists(&root_path) {
+            return Err(ScanError::PathNotFound { path: root_path });
+        }
+        Ok(ScanRun::new(self, fs, root_path))
+    }
+    
+    // A source file whose content could not be read as text during analysis
+    fn content_skipped(entry: &FileEntry) -> bool {
+        !entry.is_dir && entry.size > 0 && entry.enhanced_info.as_ref()
+            .is_some_and(|info| info.language.is_some() && info.line_count.is_none())
+    }
+    
+    // Uses the fingerprints taken during analysis, dropping them from the
+    // entries once indexed
+    fn link_related_files(files: &mut [FileEntry]) {
+        let mut index = SimilarityIndex::new();
+        for file in files.iter_mut() {
+            if let Some(fingerprint) = file.enhanced_info.as_mut().and_then(|info| info.fingerprint.take()) {
+                index.insert_fingerprint(file.path.clone(), fingerprint);
             }
-        })?;
-        
-        let name = entry.file_name()
-            .to_str()
-            .unwrap_or("unknown")
-            .to_string();
-        
-        let size = if metadata.is_file() {
-            metadata.len()
-        } else {
-            0
-        };
+        }
         
-        let modified = metadata
-            .modified()
-            .unwrap_or_else(|_| SystemTime::UNIX_EPOCH);
-        
-        Ok(FileEntry {
-            path,
-            name,
-            size,
-            modified,
-            is_dir: metadata.is_dir(),
-            tags: Vec::new(), // Will be filled by mapper
-            metadata: None,
-            enhanced_info: None, // Will be filled by enhanced mapper
-        })
+        similarity::link_related_files(files, &index, RELATED_FILES_MIN_SCORE, RELATED_FILES_LIMIT);
+    }
+}
+
+// Ranks each file's complexity against the rest of the scan so scores stay
+// comparable across repos regardless of caps
+fn assign_complexity_percentiles(files: &mut [FileEntry]) {
+    let mut scores: Vec<f64> = files.iter()
+        .filter_map(|file| file.enhanced_info.as_ref().and_then(|info| info.complexity_score))
+        .collect();
+    if scores.is_empty() {
+        return;
+    }
+    scores.sort_by(f64::total_cmp);
+    
+    let total = scores.len() as f64;
+    for enhanced_info in files.iter_mut().filter_map(|file| file.enhanced_info.as_mut()) {
+        if let Some(score) = enhanced_info.complexity_score {
+            // Mid-rank percentile: ties share the average of their positions
+            let below = scores.partition_point(|value| *value < score) as f64;
+            let equal = scores.partition_point(|value| *value <= score) as f64 - below;
+            enhanced_info.complexity_percentile = Some((below + equal / 2.0) / total * 100.0);
+        }
     }
 }
 
@@ -1769,18 +2754,61 @@ pub struct OutputFormatter;
 
 impl OutputFormatter {
     pub fn format_result(result: &ScanResult, format: &OutputFormat) -> String {
+        Self::format_result_with(result, format, &FormatOptions::default())
+    }
+    
+    // Grouped output formats each group as its own result under a header line
+    pub fn format_result_with(result: &ScanResult, format: &OutputFormat, options: &FormatOptions) -> String {
+        let _span = tracing::debug_span!("format", ?format, files = result.files.len()).entered();
+        let Some(group_by) = options.group_by else {
+            return Self::format_sorted(result, format, options);
+        };
+        
+        let groups: Vec<String> = group_by.group(result).into_iter()
+            .map(|(key, files)| {
+                let count = if files.len() == 1 { "1 file".to_string() } else { format!("{} files", files.len()) };
+                let header = options.palette.bold(&format!("{}: {} ({})", group_by.label(), key, count));
+                format!("{}\n{}", header, Self::format_sorted(&result.with_files(files), format, options))
+            })
+            .collect();
+        groups.join("\n")
+    }
+    
+    // Output is in path order unless told otherwise, including for results
+    // scanned in walk order or loaded from elsewhere
+    fn format_sorted(result: &ScanResult, format: &OutputFormat, options: &FormatOptions) -> String {
+        let in_path_order = result.files.is_sorted_by(|a, b| a.path <= b.path);
+        let sort = options.sort.or((!in_path_order).then_some(SortKey::Path));
+        match sort {
+            Some(key) => {
+                let mut sorted = result.with_files(result.files.clone());
+                key.sort(&mut sorted.files);
+                Self::format_styled(&sorted, format, options)
+            }
+            None => Self::format_styled(result, format, options),
+        }
+    }
+    
+    fn format_styled(result: &ScanResult, format: &OutputFormat, options: &FormatOptions) -> String {
         match format {
-            OutputFormat::Basic => Self::format_basic(result),
-            OutputFormat::Compact => Self::format_compact(result),
-            OutputFormat::Detailed => Self::format_detailed(result),
-            OutputFormat::Hierarchical => Self::format_hierarchical(result),
+            OutputFormat::Basic => Self::format_basic(result, options),
+            OutputFormat::Compact => Self::format_compact(result, options),
+            OutputFormat::Detailed => Self::format_detailed(result, options),
+            OutputFormat::Hierarchical => Self::format_hierarchical(result, &options.palette),
+            OutputFormat::RepoMap => Self::format_repo_map(result, options),
+            OutputFormat::Tree => tree::format_tree(result, options),
+            OutputFormat::Summary => summary::format_summary(result, options),
         }
     }
     
+    fn complexity(file: &FileEntry) -> Option<f64> {
+        file.enhanced_info.as_ref().and_then(|info| info.complexity_score)
+    }
+    
     fn get_branching_breakdown(file: &FileEntry, enhanced_info: &EnhancedFileInfo) -> String {
         // Re-analyze file content to provide enhanced branching complexity breakdown
         if let Ok(content) = std::fs::read_to_string(&file.path) {
-            let analyzer = ContentAnalyzer;
+            let analyzer = ContentAnalyzer::new();
             let branching_details = analyzer.analyze_branching_details(&content, &enhanced_info.language);
             
             let mut breakdown_parts = Vec::new();
@@ -1846,28 +2874,59 @@ impl OutputFormatter {
             } else {
                 format!("Enhanced Branching Analysis: {}", breakdown_parts.join(" | "))
             }
-        } else {
-            String::new()
+        } else {
+            String::new()
+        }
+    }
+    
+    // Each file followed by its public symbols, or the top-level keys of a config
+    // file, ordered by importance so the most central files come first when the
+    // map is truncated for a context window
+    fn format_repo_map(result: &ScanResult, options: &FormatOptions) -> String {
+        let palette = &options.palette;
+        let index = SymbolIndex::build(&result.files);
+        let symbols = index.by_file();
+        let config_keys = |file: &FileEntry| file.enhanced_info.as_ref().filter(|info| !info.config_keys.is_empty()).map(|info| info.config_keys.join(", "));
+        let mut files: Vec<&FileEntry> = result.files.iter()
+            .filter(|file| symbols.contains_key(file.path.as_path()) || config_keys(file).is_some())
+            .collect();
+        files.sort_by(|a, b| {
+            let importance = |file: &FileEntry| file.enhanced_info.as_ref().and_then(|info| info.importance_score).unwrap_or(0.0);
+            importance(b).total_cmp(&importance(a)).then_with(|| a.path.cmp(&b.path))
+        });
+        
+        let mut output = String::new();
+        for file in files {
+            output.push_str(&format!("{}:\n", palette.bold(&options.paths.render(&file.path, &result.root_path))));
+            for symbol in symbols.get(file.path.as_path()).into_iter().flatten() {
+                output.push_str(&format!("  {}: {}\n", palette.dim(&format!("{:>5}", symbol.line)), symbol.signature));
+            }
+            if let Some(keys) = config_keys(file) {
+                output.push_str(&format!("  {} {}\n", palette.dim("configures:"), keys));
+            }
         }
+        
+        output
     }
     
-    fn format_basic(result: &ScanResult) -> String {
+    fn format_basic(result: &ScanResult, options: &FormatOptions) -> String {
+        let palette = &options.palette;
         let mut output = String::new();
         
         for file in &result.files {
-            let file_type = if file.is_dir { "[DIR]" } else { "[FILE]" };
             let tags = if file.tags.is_empty() { 
                 String::new() 
             } else { 
-                format!(" ({})", file.tags.join(", ")) 
+                format!(" ({})", palette.tags(&file.tags)) 
             };
-            output.push_str(&format!("  {} {}{}\n", file_type, file.path.display(), tags));
+            output.push_str(&format!("  {} {}{}\n", palette.marker(file.is_dir), Self::styled_path(file, result, options), tags));
         }
         
         output
     }
     
-    fn format_compact(result: &ScanResult) -> String {
+    fn format_compact(result: &ScanResult, options: &FormatOptions) -> String {
+        let palette = &options.palette;
         let mut output = String::new();
         
         for file in &result.files {
@@ -1876,38 +2935,39 @@ impl OutputFormatter {
             }
             
             let size_str = Self::format_size(file.size);
-            let time_str = Self::format_time_ago(&file.modified);
+            let time_str = Self::format_time(&file.modified, options);
             let tags = if file.tags.is_empty() { 
                 String::new() 
             } else { 
-                format!(" ({})", file.tags.join(", ")) 
+                format!(" ({})", palette.tags(&file.tags)) 
             };
             
-            output.push_str(&format!("[FILE] {}{} | {}, {}\n", 
-                file.path.display(), tags, size_str, time_str));
+            output.push_str(&format!("{} {}{} | {}\n", palette.marker(false),
+                Self::styled_path(file, result, options), tags, palette.dim(&format!("{}, {}", size_str, time_str))));
         }
         
         output
     }
     
-    fn format_detailed(result: &ScanResult) -> String {
+    fn format_detailed(result: &ScanResult, options: &FormatOptions) -> String {
+        let palette = &options.palette;
         let mut output = String::new();
         
         for file in &result.files {
             if file.is_dir {
-                output.push_str(&format!("📁 {}\n", file.path.display()));
+                output.push_str(&format!("📁 {}\n", Self::styled_path(file, result, options)));
                 continue;
             }
             
             let size_str = Self::format_size(file.size);
-            let time_str = Self::format_time_ago(&file.modified);
+            let time_str = Self::format_time(&file.modified, options);
             let tags = if file.tags.is_empty() { 
-                "unclassified".to_string() 
+                palette.dim("unclassified") 
             } else { 
-                file.tags.join(", ") 
+                palette.tags(&file.tags) 
             };
             
-            output.push_str(&format!("[FILE] {} ({})\n", file.path.display(), tags));
+            output.push_str(&format!("{} {} ({})\n", palette.marker(false), Self::styled_path(file, result, options), tags));
             output.push_str(&format!("  Size: {} | Modified: {}", size_str, time_str));
             
             if let Some(enhanced_info) = &file.enhanced_info {
@@ -1924,6 +2984,16 @@ impl OutputFormatter {
                     output.push_str(&format!("  Exports: {}\n", enhanced_info.exports.join(", ")));
                 }
                 
+                if !enhanced_info.config_keys.is_empty() {
+                    output.push_str(&format!("  Keys: {}\n", enhanced_info.config_keys.join(", ")));
+                }
+                
+                if let (Some(fan_in), Some(fan_out)) = (enhanced_info.fan_in, enhanced_info.fan_out) {
+                    if fan_in + fan_out > 0 {
+                        output.push_str(&format!("  Coupling: imported by {}, imports {}\n", fan_in, fan_out));
+                    }
+                }
+                
                 if !enhanced_info.imports.is_empty() && enhanced_info.imports.len() <= 3 {
                     output.push_str(&format!("  Imports: {}\n", enhanced_info.imports.join(", ")));
                 } else if !enhanced_info.imports.is_empty() {
@@ -1935,7 +3005,11 @@ impl OutputFormatter {
                 }
                 
                 if let (Some(complexity), Some(importance)) = (enhanced_info.complexity_score, enhanced_info.importance_score) {
-                    output.push_str(&format!("  Complexity: {:.1} | Importance: {:.1}\n", complexity, importance));
+                    let score = palette.by_complexity(Some(complexity), &format!("{:.1}", complexity));
+                    match enhanced_info.complexity_percentile {
+                        Some(percentile) => output.push_str(&format!("  Complexity: {} (p{:.0}) | Importance: {:.1}\n", score, percentile, importance)),
+                        None => output.push_str(&format!("  Complexity: {} | Importance: {:.1}\n", score, importance)),
+                    }
                     
                     // Show enhanced branching complexity breakdown for any files with branching logic
                     let branching_detail = Self::get_branching_breakdown(file, enhanced_info);
@@ -1953,10 +3027,20 @@ impl OutputFormatter {
         output
     }
     
-    fn format_hierarchical(result: &ScanResult) -> String {
+    fn format_hierarchical(result: &ScanResult, palette: &Palette) -> String {
         // Build a tree structure from the flat file list
         let tree = Self::build_tree(&result.files);
-        Self::render_tree(&tree, 0)
+        Self::render_tree(&tree, 0, palette)
+    }
+    
+    // Paths are drawn by kind: directories bold blue, files by complexity
+    fn styled_path(file: &FileEntry, result: &ScanResult, options: &FormatOptions) -> String {
+        let path = options.paths.render(&file.path, &result.root_path);
+        if file.is_dir {
+            options.palette.dir(&path)
+        } else {
+            options.palette.by_complexity(Self::complexity(file), &path)
+        }
     }
     
     fn build_tree(files: &[FileEntry]) -> DirectoryNode {
@@ -1968,7 +3052,7 @@ impl OutputFormatter {
         };
         
         // Simple implementation - group files by parent directory
-        let mut dirs: HashMap<PathBuf, DirectoryNode> = HashMap::new();
+        let mut dirs: BTreeMap<PathBuf, DirectoryNode> = BTreeMap::new();
         
         for file in files {
             if let Some(parent) = file.path.parent() {
@@ -2011,21 +3095,27 @@ impl OutputFormatter {
         root
     }
     
-    fn render_tree(node: &DirectoryNode, depth: usize) -> String {
+    fn render_tree(node: &DirectoryNode, depth: usize, palette: &Palette) -> String {
         let mut output = String::new();
         let indent = "  ".repeat(depth);
         
-        // Render current directory if not root
+        // Render current directory if not root, with totals for its files
         if depth > 0 {
-            output.push_str(&format!("{}📁 {}/\n", indent, node.name));
+            let analyzer = ContentAnalyzer::new();
+            let mut rollup = tree::Rollup::default();
+            for file in &node.children {
+                rollup.add(file, &analyzer);
+            }
+            output.push_str(&format!("{}📁 {} {}\n", indent, palette.dir(&format!("{}/", node.name)),
+                palette.dim(&format!("({})", rollup.describe()))));
         }
         
         // Render files in this directory
-        for file in &node.children {
+        for (index, file) in node.children.iter().enumerate() {
             let tags = if file.tags.is_empty() { 
                 String::new() 
             } else { 
-                format!(" ({})", file.tags.join(", ")) 
+                format!(" ({})", palette.tags(&file.tags)) 
             };
             
             let size_str = Self::format_size(file.size);
@@ -2039,19 +3129,21 @@ impl OutputFormatter {
                 String::new()
             };
             
-            output.push_str(&format!("{}├── [FILE] {}{} | {}{}\n", 
-                indent, file.name, tags, size_str, enhanced_info));
+            let branch = palette.branch(index + 1 == node.children.len());
+            let name = palette.by_complexity(Self::complexity(file), &file.name);
+            output.push_str(&format!("{}{} {} {}{} | {}{}\n", 
+                indent, branch, palette.marker(false), name, tags, size_str, enhanced_info));
         }
         
         // Render subdirectories
         for subdir in &node.subdirs {
-            output.push_str(&Self::render_tree(subdir, depth + 1));
+            output.push_str(&Self::render_tree(subdir, depth + 1, palette));
         }
         
         output
     }
     
-    fn format_size(bytes: u64) -> String {
+    pub fn format_size(bytes: u64) -> String {
         if bytes < 1024 {
             format!("{}B", bytes)
         } else if bytes < 1024 * 1024 {
@@ -2061,6 +3153,14 @@ impl OutputFormatter {
         }
     }
     
+    fn format_time(time: &SystemTime, options: &FormatOptions) -> String {
+        if options.absolute_times {
+            format_rfc3339(*time)
+        } else {
+            Self::format_time_ago(time)
+        }
+    }
+    
     fn format_time_ago(time: &SystemTime) -> String {
         if let Ok(duration) = time.elapsed() {
             let seconds = duration.as_secs();
@@ -2797,8 +3897,8 @@ mod tests {
                 0.0
             };
             
-            assert!(pure_percentage >= 0.0 && pure_percentage <= 100.0);
-            assert!(hardcoded_percentage >= 0.0 && hardcoded_percentage <= 100.0);
+            assert!((0.0..=100.0).contains(&pure_percentage));
+            assert!((0.0..=100.0).contains(&hardcoded_percentage));
             
             // With our test case, expect high purity but some hardcoded values
             assert!(pure_percentage >= 75.0); // Most should be pure
@@ -2824,6 +3924,33 @@ mod tests {
             assert_eq!(details.past_logic_count, 0);
             assert!(details.nesting_distribution.is_empty());
         }
+        
+        #[test]
+        fn test_analyze_content_uses_given_language() {
+            let analyzer = ContentAnalyzer::new();
+            let entry = FileEntry::test("-").with_name("<stdin>");
+            let info = analyzer.analyze_content(&entry, "pub fn run() {\n    if ready { go(); }\n}\n", Some(analyzer.resolve_language("rs")));
+            
+            assert_eq!(info.language.as_deref(), Some("rust"));
+            assert_eq!(info.line_count, Some(3));
+            assert!(info.exports.iter().any(|export| export.contains("run")));
+            assert_eq!(analyzer.resolve_language("Python"), "python");
+            assert_eq!(analyzer.resolve_language("zig"), "zig");
+        }
+        
+        #[test]
+        fn test_analyze_content_without_a_file() {
+            let content = "def load(path):\n    if path:\n        return open(path)\n";
+            let info = analyze_content("src/loader.py", content, None);
+            
+            assert_eq!(info.language.as_deref(), Some("python"));
+            assert_eq!(info.line_count, Some(3));
+            assert!(info.complexity_score.is_some_and(|score| score > 0.0));
+            assert!(info.content_summary.is_some() && info.purpose.is_some());
+            // A given language wins over the name
+            assert_eq!(analyze_content("untitled", content, Some("py")).language.as_deref(), Some("python"));
+            assert_eq!(analyze_content("untitled", content, None).language, None);
+        }
     }
     
     mod edge_cases_and_boundaries {
@@ -2926,6 +4053,454 @@ mod tests {
         }
     }
     
+    mod multiline_statements {
+        use super::*;
+        
+        #[test]
+        fn test_trailing_operator_condition_counted_once() {
+            let content = r#"
+                if foo &&
+                    bar {
+                    execute();
+                }
+            "#;
+            
+            let analyzer = ContentAnalyzer::new();
+            let details = analyzer.analyze_branching_details(content, &Some("rust".to_string()));
+            
+            assert_eq!(details.conditional_count, 1);
+            assert_eq!(details.logical_operators, 1);
+            assert_eq!(details.nesting_distribution.get(&1), Some(&1));
+        }
+        
+        #[test]
+        fn test_leading_operator_condition_with_brace_on_own_line() {
+            let content = r#"
+                if first_condition
+                    && second_condition
+                    || third_condition
+                {
+                    if inner { x(); }
+                }
+            "#;
+            
+            let analyzer = ContentAnalyzer::new();
+            let details = analyzer.analyze_branching_details(content, &Some("rust".to_string()));
+            
+            assert_eq!(details.conditional_count, 2);
+            assert_eq!(details.logical_operators, 2);
+            assert_eq!(details.nesting_distribution.get(&1), Some(&1));
+            assert_eq!(details.nesting_distribution.get(&2), Some(&1));
+        }
+        
+        #[test]
+        fn test_braces_on_continuation_lines_do_not_drift_nesting() {
+            let content = r#"
+                if check(
+                    first,
+                    second) {
+                    if inner { x(); }
+                }
+                if after { y(); }
+            "#;
+            
+            let analyzer = ContentAnalyzer::new();
+            let details = analyzer.analyze_branching_details(content, &Some("rust".to_string()));
+            
+            assert_eq!(details.conditional_count, 3);
+            assert_eq!(details.max_nesting, 2);
+            assert_eq!(details.nesting_distribution.get(&1), Some(&2)); // check and after
+            assert_eq!(details.nesting_distribution.get(&2), Some(&1)); // inner
+        }
+        
+        #[test]
+        fn test_trailing_comments_inside_joined_statement() {
+            let content = r#"
+                if (ready && // primary check
+                    fallback) {
+                    go();
+                }
+            "#;
+            
+            let analyzer = ContentAnalyzer::new();
+            let details = analyzer.analyze_branching_details(content, &Some("rust".to_string()));
+            
+            assert_eq!(details.conditional_count, 1);
+            assert_eq!(details.nesting_distribution.get(&1), Some(&1));
+        }
+        
+        #[test]
+        fn test_python_parenthesized_condition() {
+            let content = r#"
+if (first_value and
+        second_value):
+    run()
+"#;
+            
+            let analyzer = ContentAnalyzer::new();
+            let details = analyzer.analyze_branching_details(content, &Some("python".to_string()));
+            
+            assert_eq!(details.conditional_count, 1);
+            assert_eq!(details.logical_operators, 1);
+        }
+
+        #[test]
+        fn test_quote_literals_do_not_join_statements() {
+            let content = r#"
+let quotes = line.matches('"').count() + line.matches("\"(").count();
+let open = text.matches(['(', '[']).count();
+if ready { start(); }
+for item in items { process(item); }
+"#;
+
+            let analyzer = ContentAnalyzer::new();
+            let statements = analyzer.join_logical_statements(content, &Some("rust".to_string()));
+            let details = analyzer.analyze_branching_details(content, &Some("rust".to_string()));
+            
+            assert_eq!(statements.iter().filter(|s| !s.text.is_empty()).count(), 4);
+            assert_eq!(details.conditional_count, 1);
+            assert_eq!(details.loop_count, 1);
+        }
+
+        #[test]
+        fn test_non_ascii_lines_are_scanned() {
+            let content = "let label = \"📁 files\"; for entry in entries { if entry.ok { show(); } }\n";
+            
+            let analyzer = ContentAnalyzer::new();
+            let details = analyzer.analyze_branching_details(content, &Some("rust".to_string()));
+            
+            assert_eq!(details.loop_count, 1);
+            assert_eq!(details.conditional_count, 1);
+        }
+        
+        #[test]
+        fn test_lines_are_copied_only_when_masked() {
+            let rust = Some("rust".to_string());
+            let mut state = MultilineState::default();
+            let mut mask = |line| ContentAnalyzer::mask_multiline_constructs(line, &mut state, &rust);
+            
+            assert!(matches!(mask("let s = \"/* 📁 */\"; // if done"), Cow::Borrowed(_)));
+            assert_eq!(mask("start(); /* if a"), "start(); ");
+            assert_eq!(mask("still /* nested */ inside"), "");
+            assert_eq!(mask("b */ end();"), "  end();");
+            
+            let analyzer = ContentAnalyzer::new();
+            assert!(matches!(analyzer.remove_strings_and_comments("if ready {"), Cow::Borrowed(_)));
+            assert_eq!(analyzer.remove_strings_and_comments("if a == 'x' /* c */ { \"s // t"), "if a ==     {  ");
+            assert!(ContentAnalyzer::contains_year("v12024", 2019..=2027));
+            assert!(!ContentAnalyzer::contains_year("1989 2031 20x24", 1990..=2030));
+        }
+    }
+
+    mod indentation_nesting {
+        use super::*;
+        
+        #[test]
+        fn test_python_nested_conditionals() {
+            let content = r#"
+def handle(items):
+    for item in items:
+        if item.ready:
+            if item.valid:
+                process(item)
+        if item.failed:
+            retry(item)
+"#;
+            
+            let analyzer = ContentAnalyzer::new();
+            let details = analyzer.analyze_branching_details(content, &Some("python".to_string()));
+            
+            assert_eq!(details.max_nesting, 4);
+            assert_eq!(details.nesting_distribution.get(&3), Some(&2)); // ready, failed
+            assert_eq!(details.nesting_distribution.get(&4), Some(&1)); // valid
+        }
+        
+        #[test]
+        fn test_python_base_indentation_is_relative() {
+            let content = r#"
+                if first:
+                    run()
+                if second:
+                    if third:
+                        run()
+            "#;
+            
+            let analyzer = ContentAnalyzer::new();
+            let details = analyzer.analyze_branching_details(content, &Some("python".to_string()));
+            
+            assert_eq!(details.nesting_distribution.get(&1), Some(&2));
+            assert_eq!(details.nesting_distribution.get(&2), Some(&1));
+            assert_eq!(details.max_nesting, 2);
+        }
+        
+        #[test]
+        fn test_python_dict_braces_ignored() {
+            let content = r#"
+config = {
+    "key": "value",
+}
+if config:
+    apply(config)
+"#;
+            
+            let analyzer = ContentAnalyzer::new();
+            let details = analyzer.analyze_branching_details(content, &Some("python".to_string()));
+            
+            assert_eq!(details.max_nesting, 1);
+            assert_eq!(details.nesting_distribution.get(&1), Some(&1));
+        }
+        
+        #[test]
+        fn test_yaml_nesting_depth() {
+            let content = r#"
+services:
+  web:
+    build:
+      context: .
+  db:
+    image: postgres
+"#;
+            
+            let analyzer = ContentAnalyzer::new();
+            let details = analyzer.analyze_branching_details(content, &Some("yaml".to_string()));
+            
+            assert_eq!(details.max_nesting, 3);
+        }
+        
+        #[test]
+        fn test_deep_python_nesting_raises_cognitive_complexity() {
+            let flat = "if a:\n    x()\nif b:\n    y()\n";
+            let nested = "if a:\n    if b:\n        y()\n";
+            
+            let analyzer = ContentAnalyzer::new();
+            let flat_details = analyzer.analyze_branching_details(flat, &Some("python".to_string()));
+            let nested_details = analyzer.analyze_branching_details(nested, &Some("python".to_string()));
+            
+            assert!(nested_details.cognitive_complexity > flat_details.cognitive_complexity);
+        }
+    }
+    
+    mod multiline_comment_state {
+        use super::*;
+
+        #[test]
+        fn test_block_comment_body_ignored() {
+            let content = r#"
+                /*
+                  if fake_condition { never(); }
+                  while waiting { spin(); }
+                */
+                if real_condition { execute(); }
+            "#;
+            
+            let analyzer = ContentAnalyzer::new();
+            let details = analyzer.analyze_branching_details(content, &Some("rust".to_string()));
+            
+            assert_eq!(details.conditional_count, 1);
+            assert_eq!(details.loop_count, 0);
+        }
+
+        #[test]
+        fn test_code_after_block_comment_close_counted() {
+            let content = r#"
+                let x = 1; /* start of note
+                   for each item we skip */ if x > y { run(); }
+            "#;
+            
+            let analyzer = ContentAnalyzer::new();
+            let details = analyzer.analyze_branching_details(content, &Some("javascript".to_string()));
+            
+            assert_eq!(details.conditional_count, 1);
+            assert_eq!(details.loop_count, 0);
+        }
+
+        #[test]
+        fn test_nested_rust_block_comments() {
+            let content = r#"
+                /* outer
+                   /* inner */
+                   if still_commented { no(); }
+                */
+                if visible { yes(); }
+            "#;
+            
+            let analyzer = ContentAnalyzer::new();
+            let details = analyzer.analyze_branching_details(content, &Some("rust".to_string()));
+            
+            assert_eq!(details.conditional_count, 1);
+        }
+
+        #[test]
+        fn test_comment_markers_inside_strings_ignored() {
+            let content = r#"
+                let pattern = "src/**/*.rs";
+                if matches(pattern) { found(); }
+                if other { also(); }
+            "#;
+            
+            let analyzer = ContentAnalyzer::new();
+            let details = analyzer.analyze_branching_details(content, &Some("rust".to_string()));
+            
+            assert_eq!(details.conditional_count, 2);
+        }
+
+        #[test]
+        fn test_python_docstrings_ignored() {
+            let content = r#"
+def process(items):
+    """
+    if the items are empty we return early,
+    for every other case we loop while work remains
+    """
+    if items:
+        return handle(items)
+    note = '''while this
+    looks like a loop'''
+"#;
+
+            let analyzer = ContentAnalyzer::new();
+            let details = analyzer.analyze_branching_details(content, &Some("python".to_string()));
+            
+            assert_eq!(details.conditional_count, 1);
+            assert_eq!(details.loop_count, 0);
+        }
+    }
+
+    mod mapper_selection {
+        use super::*;
+        use std::fs;
+        use tempfile::TempDir;
+
+        struct ExtensionMapper;
+
+        impl Mapper for ExtensionMapper {
+            fn classify(&self, entry: &FileEntry) -> Vec<String> {
+                let ext = entry.path.extension().and_then(|ext| ext.to_str()).unwrap_or("none");
+                vec![format!("ext-{}", ext)]
+            }
+
+            fn name(&self) -> &str {
+                "extension"
+            }
+        }
+
+        fn project() -> TempDir {
+            let dir = TempDir::new().unwrap();
+            fs::write(dir.path().join("main.rs"), "fn main() { if ready { run(); } }").unwrap();
+            dir
+        }
+
+        fn tags_for(result: &ScanResult, name: &str) -> Vec<String> {
+            result.files.iter().find(|file| file.name == name).unwrap().tags.clone()
+        }
+
+        #[test]
+        fn test_user_provided_mapper() {
+            let dir = project();
+            let scanner = DirectoryScanner::with_mapper(ScanOptions::default(), Box::new(ExtensionMapper));
+            
+            let result = scanner.scan(dir.path()).unwrap();
+            
+            assert_eq!(tags_for(&result, "main.rs"), vec!["ext-rs"]);
+            assert!(result.files.iter().all(|file| file.enhanced_info.is_none()));
+        }
+
+        #[test]
+        fn test_custom_profile_from_registry() {
+            let dir = project();
+            let mut registry = MapperRegistry::new();
+            registry.register("extensions", |_| Box::new(ExtensionMapper));
+            let options = ScanOptions { mapper_profile: "extensions".to_string(), ..Default::default() };
+            
+            let result = DirectoryScanner::with_registry(options, &registry).unwrap().scan(dir.path()).unwrap();
+            
+            assert_eq!(tags_for(&result, "main.rs"), vec!["ext-rs"]);
+            assert_eq!(registry.profiles(), vec!["enhanced", "extensions", "generic"]);
+        }
+
+        #[test]
+        fn test_unknown_profile() {
+            let options = ScanOptions { mapper_profile: "missing".to_string(), ..Default::default() };
+            
+            assert!(matches!(
+                DirectoryScanner::with_registry(options.clone(), &MapperRegistry::new()),
+                Err(ScanError::InvalidConfig { .. })
+            ));
+            // The plain constructor keeps falling back to the generic mapper
+            let result = DirectoryScanner::new(options).scan(project().path()).unwrap();
+            assert!(result.files.iter().any(|file| file.tags.contains(&"source".to_string())));
+        }
+
+        #[test]
+        fn test_enhanced_profile_analyzes_content() {
+            let options = ScanOptions { mapper_profile: "enhanced".to_string(), ..Default::default() };
+            
+            let result = DirectoryScanner::new(options).scan(project().path()).unwrap();
+            
+            let main = result.files.iter().find(|file| file.name == "main.rs").unwrap();
+            assert_eq!(main.enhanced_info.as_ref().unwrap().language.as_deref(), Some("rust"));
+            assert!(main.tags.contains(&"rust".to_string()));
+        }
+    }
+
+    mod complexity_caps {
+        use super::*;
+
+        fn huge_rust_file() -> String {
+            "if a && b { if c || d { while e { step(); } } }\n".repeat(400)
+        }
+
+        #[test]
+        fn test_default_caps_clamp_scores() {
+            let analyzer = ContentAnalyzer::new();
+            let language = Some("rust".to_string());
+            
+            assert_eq!(analyzer.calculate_complexity(&huge_rust_file(), &language), 10.0);
+            assert_eq!(analyzer.calculate_branching_complexity(&huge_rust_file(), &language), 8.0);
+        }
+
+        #[test]
+        fn test_uncapped_scores_keep_differences() {
+            let analyzer = ContentAnalyzer::with_caps(ComplexityCaps::uncapped());
+            let language = Some("rust".to_string());
+            let huge = huge_rust_file();
+            let huger = huge.repeat(2);
+            
+            let score = analyzer.calculate_complexity(&huge, &language);
+            assert!(score > 10.0);
+            assert!(analyzer.calculate_complexity(&huger, &language) > score);
+        }
+
+        #[test]
+        fn test_custom_caps() {
+            let caps = ComplexityCaps { complexity: Some(25.0), branching: None };
+            let analyzer = ContentAnalyzer::with_caps(caps);
+            
+            assert_eq!(analyzer.calculate_complexity(&huge_rust_file(), &Some("rust".to_string())), 25.0);
+        }
+
+        #[test]
+        fn test_percentiles_rank_across_files() {
+            let entry = |name: &str, complexity: Option<f64>| FileEntry::test(name).with_info(|info| info.complexity_score = complexity);
+            let mut files = vec![
+                entry("a.rs", Some(1.0)),
+                entry("b.rs", Some(4.0)),
+                entry("c.rs", Some(4.0)),
+                entry("d.rs", Some(50.0)),
+                entry("README", None),
+            ];
+            
+            assign_complexity_percentiles(&mut files);
+            
+            let percentile = |index: usize| files[index].enhanced_info.as_ref().unwrap().complexity_percentile;
+            assert_eq!(percentile(0), Some(12.5));
+            assert_eq!(percentile(1), Some(50.0));
+            assert_eq!(percentile(1), percentile(2));
+            assert_eq!(percentile(3), Some(87.5));
+            assert_eq!(percentile(4), None);
+        }
+    }
+
     mod property_based_tests {
         use super::*;
         use quickcheck_macros::quickcheck;
@@ -2941,7 +4516,7 @@ mod tests {
             }
             
             let percentage = (pure as f64 / total as f64) * 100.0;
-            percentage >= 0.0 && percentage <= 100.0
+            (0.0..=100.0).contains(&percentage)
         }
         
         #[quickcheck]
@@ -2972,7 +4547,7 @@ mod tests {
             }
             
             let percentage = (hardcoded as f64 / total as f64) * 100.0;
-            percentage >= 0.0 && percentage <= 100.0 && hardcoded <= total
+            (0.0..=100.0).contains(&percentage) && hardcoded <= total
         }
         
         #[test]
@@ -0,0 +1,253 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use crate::layout::language_of;
+use crate::{ContentAnalyzer, FileEntry, PathStyle, ScanResult};
+
+// Files listed under "Key files" and "Optional", most important first
+const KEY_FILES: usize = 15;
+const ENTRY_POINTS: usize = 10;
+const OPTIONAL_DOCS: usize = 20;
+const LANGUAGES: usize = 5;
+// Not what anyone would call a project's language
+pub(crate) const DATA_LANGUAGES: [&str; 5] = ["markdown", "json", "yaml", "toml", "text"];
+
+// An llms.txt index (https://llmstxt.org) of the scanned repository: a title,
+// a one-line description, then sections of links an agent can follow. Built
+// from enhanced analysis, so purposes, summaries and importance must be present
+// for anything beyond the file counts.
+pub fn to_llms_txt(result: &ScanResult) -> String {
+    let paths = PathStyle::relative();
+    let link = |file: &FileEntry| {
+        let path = paths.render(&file.path, &result.root_path);
+        format!("[{}]({})", path, path)
+    };
+    let files: Vec<&FileEntry> = result.files.iter().filter(|file| !file.is_dir).collect();
+    let mut by_importance = files.clone();
+    by_importance.sort_by(|a, b| importance(b).total_cmp(&importance(a)).then_with(|| a.path.cmp(&b.path)));
+
+    let name = project_name(result);
+    let languages = language_counts(&files);
+
+    let mut output = String::new();
+    let _ = writeln!(output, "# {}\n", name);
+    let readme = files.iter()
+        .filter(|file| file.name.to_lowercase().starts_with("readme") && file.path.parent() == Some(result.root_path.as_path()))
+        .find_map(|file| summary(file));
+    let primary = languages.iter().find(|(language, _)| !DATA_LANGUAGES.contains(&language.as_str()));
+    let description = match (readme, primary) {
+        (Some(readme), _) => readme.to_string(),
+        (None, Some((language, _))) => format!("A {} project", language),
+        (None, None) => "A project".to_string(),
+    };
+    let _ = writeln!(output, "> {}\n", description);
+
+    let dirs = result.files.iter().filter(|file| file.is_dir && file.path != result.root_path).count();
+    let _ = write!(output, "{} files in {} {}.", files.len(), dirs, if dirs == 1 { "directory" } else { "directories" });
+    if !languages.is_empty() {
+        let listed: Vec<String> = languages.iter().take(LANGUAGES).map(|(language, count)| format!("{} ({})", language, count)).collect();
+        let _ = write!(output, " Languages: {}.", listed.join(", "));
+    }
+    let mut frameworks: Vec<&str> = files.iter().filter_map(|file| file.enhanced_info.as_ref()?.framework.as_deref()).collect();
+    frameworks.sort_unstable();
+    frameworks.dedup();
+    if !frameworks.is_empty() {
+        let _ = write!(output, " Frameworks: {}.", frameworks.join(", "));
+    }
+    output.push('\n');
+
+    let entry_points: Vec<&FileEntry> = by_importance.iter().copied()
+        .filter(|file| is_entry_point(file))
+        .take(ENTRY_POINTS)
+        .collect();
+    section(&mut output, "Entry points", entry_points.iter().map(|file| described(&link(file), file)).collect());
+
+    let directory_lines = top_level_directories(result, &files).into_iter()
+        .map(|(dir, stats)| {
+            let mut line = format!("- [{}/]({}/): {} {}", dir, dir, stats.files, if stats.files == 1 { "file" } else { "files" });
+            if let Some(purpose) = stats.purpose() {
+                let _ = write!(line, ", mostly {}", purpose.to_lowercase());
+            }
+            if let Some(language) = stats.language() {
+                let _ = write!(line, " ({})", language);
+            }
+            line
+        })
+        .collect();
+    section(&mut output, "Directories", directory_lines);
+
+    let key_files: Vec<String> = by_importance.iter()
+        .filter(|file| importance(file) > 0.0 && !entry_points.iter().any(|entry| std::ptr::eq(*entry, **file)))
+        .filter(|file| !purpose(file).is_some_and(|purpose| purpose == "Test code" || purpose == "Documentation"))
+        .take(KEY_FILES)
+        .map(|file| described(&link(file), file))
+        .collect();
+    section(&mut output, "Key files", key_files);
+
+    // The llms.txt spec's section for links that can be skipped when context is short
+    let docs: Vec<String> = by_importance.iter()
+        .filter(|file| purpose(file) == Some("Documentation"))
+        .take(OPTIONAL_DOCS)
+        .map(|file| described(&link(file), file))
+        .collect();
+    section(&mut output, "Optional", docs);
+    output
+}
+
+// Per top-level directory: file count, purposes and languages
+#[derive(Default)]
+pub(crate) struct DirectoryStats {
+    pub(crate) files: usize,
+    purposes: HashMap<String, usize>,
+    languages: HashMap<String, usize>,
+}
+
+impl DirectoryStats {
+    // Only when it covers at least half the files, so "mostly" holds
+    pub(crate) fn purpose(&self) -> Option<String> {
+        let purpose = most_common(self.purposes.iter().map(|(purpose, count)| (purpose.clone(), *count)))?;
+        (self.purposes[&purpose] * 2 >= self.files).then_some(purpose)
+    }
+
+    pub(crate) fn language(&self) -> Option<String> {
+        most_common(self.languages.iter().filter(|(language, _)| *language != "unknown").map(|(language, count)| (language.clone(), *count)))
+    }
+}
+
+// Files directly in the root are not counted
+pub(crate) fn top_level_directories(result: &ScanResult, files: &[&FileEntry]) -> BTreeMap<String, DirectoryStats> {
+    let analyzer = ContentAnalyzer::new();
+    let mut directories: BTreeMap<String, DirectoryStats> = BTreeMap::new();
+    for file in files {
+        let relative = file.path.strip_prefix(&result.root_path).unwrap_or(&file.path);
+        let mut components = relative.components();
+        let (Some(top), Some(_)) = (components.next(), components.next()) else {
+            continue;
+        };
+        let stats = directories.entry(top.as_os_str().to_string_lossy().to_string()).or_default();
+        stats.files += 1;
+        if let Some(purpose) = purpose(file) {
+            *stats.purposes.entry(purpose.to_string()).or_default() += 1;
+        }
+        *stats.languages.entry(language_of(file, &analyzer)).or_default() += 1;
+    }
+    directories
+}
+
+// The scanned directory's own name
+pub(crate) fn project_name(result: &ScanResult) -> String {
+    result.root_path.canonicalize().unwrap_or_else(|_| result.root_path.clone())
+        .file_name()
+        .map_or_else(|| "Project".to_string(), |name| name.to_string_lossy().to_string())
+}
+
+// Files per known language, most first
+pub(crate) fn language_counts(files: &[&FileEntry]) -> Vec<(String, usize)> {
+    let analyzer = ContentAnalyzer::new();
+    let mut languages: HashMap<String, usize> = HashMap::new();
+    for file in files {
+        *languages.entry(language_of(file, &analyzer)).or_default() += 1;
+    }
+    languages.remove("unknown");
+    let mut languages: Vec<(String, usize)> = languages.into_iter().collect();
+    languages.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    languages
+}
+
+// A README showing `fn main()` in an example is not where execution starts
+pub(crate) fn is_entry_point(file: &FileEntry) -> bool {
+    let data = file.enhanced_info.as_ref()
+        .and_then(|info| info.language.as_deref())
+        .is_some_and(|language| DATA_LANGUAGES.contains(&language));
+    !data && (purpose(file).is_some_and(|purpose| purpose.contains("entry point")) || file.tags.iter().any(|tag| tag == "entrypoint"))
+}
+
+pub(crate) fn importance(file: &FileEntry) -> f64 {
+    file.enhanced_info.as_ref().and_then(|info| info.importance_score).unwrap_or(0.0)
+}
+
+pub(crate) fn purpose(file: &FileEntry) -> Option<&str> {
+    file.enhanced_info.as_ref()?.purpose.as_deref()
+}
+
+// Summaries taken from a leading attribute such as `#[derive(...)]` say nothing
+pub(crate) fn summary(file: &FileEntry) -> Option<&str> {
+    file.enhanced_info.as_ref()?.content_summary.as_deref()
+        .filter(|summary| !summary.is_empty() && *summary != "Empty file" && !summary.starts_with('['))
+}
+
+fn described(link: &str, file: &FileEntry) -> String {
+    match summary(file).or_else(|| purpose(file)) {
+        Some(note) => format!("- {}: {}", link, note),
+        None => format!("- {}", link),
+    }
+}
+
+// Highest count, ties by name
+fn most_common(counts: impl Iterator<Item = (String, usize)>) -> Option<String> {
+    counts.max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0))).map(|(name, _)| name)
+}
+
+fn section(output: &mut String, title: &str, lines: Vec<String>) {
+    if lines.is_empty() {
+        return;
+    }
+    let _ = writeln!(output, "\n## {}\n", title);
+    for line in lines {
+        let _ = writeln!(output, "{}", line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+
+    fn scan(fs: &MemoryFileSystem, enhanced: bool) -> ScanResult {
+        let options = ScanOptions { enhanced_analysis: enhanced, ..Default::default() };
+        DirectoryScanner::new(options).scan_with(fs, "shop").unwrap()
+    }
+
+    fn project() -> MemoryFileSystem {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("shop/README.md", "# Shop: a tiny storefront service\n\nSells things.\n");
+        fs.insert("shop/src/main.rs", "// Starts the storefront HTTP server\nfn main() {\n    serve();\n}\n");
+        fs.insert("shop/src/lib.rs", "// Cart and checkout logic\npub fn checkout(total: u32) -> u32 {\n    if total > 100 { total - 10 } else { total }\n}\n");
+        fs.insert("shop/docs/guide.md", "# Operating the shop\n");
+        fs
+    }
+
+    #[test]
+    fn test_lists_title_description_and_sections() {
+        let text = to_llms_txt(&scan(&project(), true));
+
+        assert!(text.starts_with("# shop\n\n> Shop: a tiny storefront service\n\n4 files in 2 directories."), "{}", text);
+        assert!(text.contains("## Entry points\n\n- [src/main.rs](src/main.rs): Starts the storefront HTTP server\n"), "{}", text);
+        assert!(text.contains("- [src/](src/): 2 files"), "{}", text);
+        assert!(text.contains("## Key files\n\n- [src/lib.rs](src/lib.rs): Cart and checkout logic\n"), "{}", text);
+        assert!(text.contains("## Optional\n\n"), "{}", text);
+        assert!(text.contains("[docs/guide.md](docs/guide.md)"), "{}", text);
+    }
+
+    #[test]
+    fn test_sections_follow_importance() {
+        let mut fs = project();
+        fs.insert("shop/src/big.rs", format!("// Pricing rules for every region\n{}", "pub fn rule() { if a { if b { x(); } } }\n".repeat(400)));
+        let text = to_llms_txt(&scan(&fs, true));
+
+        let big = text.find("[src/big.rs]").unwrap();
+        let lib = text.find("[src/lib.rs]").unwrap();
+        assert!(big < lib, "{}", text);
+    }
+
+    #[test]
+    fn test_without_enhanced_analysis_only_counts_files() {
+        let text = to_llms_txt(&scan(&project(), false));
+
+        assert!(text.contains("> A rust project\n"), "{}", text);
+        assert!(text.contains("- [src/](src/): 2 files (rust)\n"), "{}", text);
+        assert!(!text.contains("## Key files"), "{}", text);
+        // Tagged by convention, without a summary to describe it
+        assert!(text.contains("## Entry points\n\n- [src/main.rs](src/main.rs)\n"), "{}", text);
+    }
+}
@@ -0,0 +1,337 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::{FileSystem, ScanResult};
+
+// Package registries, named as OSV names them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Ecosystem {
+    #[serde(rename = "crates.io")]
+    CratesIo,
+    #[serde(rename = "npm")]
+    Npm,
+    #[serde(rename = "PyPI")]
+    PyPi,
+    #[serde(rename = "Go")]
+    Go,
+    #[serde(rename = "RubyGems")]
+    RubyGems,
+    #[serde(rename = "Packagist")]
+    Packagist,
+}
+
+impl Ecosystem {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Ecosystem::CratesIo => "crates.io",
+            Ecosystem::Npm => "npm",
+            Ecosystem::PyPi => "PyPI",
+            Ecosystem::Go => "Go",
+            Ecosystem::RubyGems => "RubyGems",
+            Ecosystem::Packagist => "Packagist",
+        }
+    }
+
+    // The registry of a manifest file name
+    pub fn of_manifest(name: &str) -> Option<Self> {
+        Some(match name {
+            "Cargo.toml" => Ecosystem::CratesIo,
+            "package.json" => Ecosystem::Npm,
+            "requirements.txt" | "pyproject.toml" => Ecosystem::PyPi,
+            "go.mod" => Ecosystem::Go,
+            "Gemfile" => Ecosystem::RubyGems,
+            "composer.json" => Ecosystem::Packagist,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for Ecosystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// An external dependency as a manifest declares it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeclaredDependency {
+    pub manifest: PathBuf,
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    // As written, e.g. "^1.2", ">=2.0,<3" or "v1.9.1"; none when the manifest
+    // leaves the version to a workspace or lock file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requirement: Option<String>,
+}
+
+impl DeclaredDependency {
+    // The lowest version the requirement allows, e.g. "1.2" for "^1.2" and
+    // "2.0" for ">=2.0,<3"; none for wildcards, tags and git sources
+    pub fn version(&self) -> Option<String> {
+        let requirement = self.requirement.as_deref()?;
+        let first = requirement.split([',', '|', ' ']).find(|part| part.chars().any(|c| c.is_ascii_digit()))?;
+        let version = first.trim_start_matches(['^', '~', '=', '>', '<', '!', 'v', ' ']);
+        let end = version.find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'))).unwrap_or(version.len());
+        let version = &version[..end];
+        (version.starts_with(|c: char| c.is_ascii_digit()) && !version.contains('*')).then(|| version.to_string())
+    }
+}
+
+// External dependencies declared by the scanned manifests, in path order.
+// Local path dependencies are parts of the project and left out, as are dev
+// and build dependencies.
+pub fn declared_dependencies(result: &ScanResult, fs: &dyn FileSystem) -> Vec<DeclaredDependency> {
+    let mut manifests: Vec<_> = result.files.iter()
+        .filter(|file| !file.is_dir)
+        .filter_map(|file| Some((file, Ecosystem::of_manifest(&file.name)?)))
+        .collect();
+    manifests.sort_by(|(a, _), (b, _)| a.path.cmp(&b.path));
+
+    let mut declared = Vec::new();
+    for (file, ecosystem) in manifests {
+        let Ok(content) = fs.read_to_string(&file.path) else { continue };
+        for (name, requirement) in requirements(&file.name, &content) {
+            declared.push(DeclaredDependency { manifest: file.path.clone(), ecosystem, name, requirement });
+        }
+    }
+    declared
+}
+
+// (name, requirement) of each external dependency one manifest declares
+pub(crate) fn requirements(name: &str, content: &str) -> Vec<(String, Option<String>)> {
+    match name {
+        "Cargo.toml" => cargo_requirements(content),
+        "package.json" | "composer.json" => json_requirements(content),
+        "requirements.txt" => content.lines().filter_map(requirement).collect(),
+        "pyproject.toml" => pyproject_requirements(content),
+        "go.mod" => go_requirements(content),
+        "Gemfile" => content.lines()
+            .filter_map(|line| line.trim().strip_prefix("gem "))
+            .filter_map(|rest| {
+                let mut quoted = rest.split(['"', '\'']).skip(1).step_by(2);
+                let name = quoted.next()?.to_string();
+                Some((name, quoted.next().map(str::to_string)))
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Compares dotted versions number by number, treating missing parts as zero
+// and ignoring pre-release and build suffixes, so "1.2" equals "1.2.0"
+pub(crate) fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a, b) = (version_parts(a), version_parts(b));
+    (0..a.len().max(b.len()))
+        .map(|index| a.get(index).unwrap_or(&0).cmp(b.get(index).unwrap_or(&0)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+// The numbers of a dotted version, e.g. [1, 2, 0] for "v1.2.0-rc.1"
+pub(crate) fn version_parts(version: &str) -> Vec<u64> {
+    version.trim_start_matches('v')
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+fn cargo_requirements(content: &str) -> Vec<(String, Option<String>)> {
+    let mut declared: Vec<(String, Option<String>)> = Vec::new();
+    let mut in_dependencies = false;
+    // Inside a [dependencies.name] table, whose version comes on a later line
+    let mut table: Option<usize> = None;
+    for line in content.lines().map(str::trim) {
+        if let Some(header) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            // [dependencies], [workspace.dependencies], [target.'cfg(..)'.dependencies]
+            // and [dependencies.name] tables
+            in_dependencies = header == "dependencies" || header.ends_with(".dependencies") && !header.contains("dev-") && !header.contains("build-");
+            table = header.strip_prefix("dependencies.").map(|name| {
+                declared.push((name.to_string(), None));
+                declared.len() - 1
+            });
+            continue;
+        }
+        if line.starts_with('#') || line.contains("path =") {
+            continue;
+        }
+        if let Some(position) = table {
+            if let Some(version) = line.strip_prefix("version").and_then(|rest| quoted(rest.trim_start().strip_prefix('=')?)) {
+                declared[position].1 = Some(version);
+            }
+            continue;
+        }
+        if !in_dependencies {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=').or(Some((line, ""))) else { continue };
+        let key = key.trim().split('.').next().unwrap_or_default().trim_matches('"');
+        if key.is_empty() {
+            continue;
+        }
+        let value = value.trim();
+        let requirement = if value.starts_with('{') {
+            value.split_once("version").and_then(|(_, rest)| quoted(rest.trim_start().strip_prefix('=')?))
+        } else {
+            quoted(value)
+        };
+        declared.push((key.to_string(), requirement));
+    }
+    declared
+}
+
+fn json_requirements(content: &str) -> Vec<(String, Option<String>)> {
+    let Ok(manifest) = serde_json::from_str::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+    ["dependencies", "require"].iter()
+        .filter_map(|key| manifest.get(key)?.as_object())
+        .flat_map(|dependencies| dependencies.iter())
+        .filter(|(name, _)| *name != "php" && !name.starts_with("ext-"))
+        .map(|(name, version)| (name.clone(), version.as_str().map(str::to_string)))
+        .collect()
+}
+
+// `Django>=4.2` or `requests[socks]==2.31 ; python_version > "3"`
+fn requirement(line: &str) -> Option<(String, Option<String>)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with(['#', '-']) {
+        return None;
+    }
+    let name_end = line.find(|c: char| "=<>~![; ".contains(c)).unwrap_or(line.len());
+    let name = line[..name_end].trim();
+    if name.is_empty() {
+        return None;
+    }
+    let rest = line[name_end..].trim_start();
+    let rest = rest.strip_prefix('[').and_then(|rest| rest.split_once(']')).map_or(rest, |(_, rest)| rest);
+    let version = rest.split(';').next().unwrap_or_default().trim();
+    Some((name.to_lowercase(), (!version.is_empty()).then(|| version.to_string())))
+}
+
+// PEP 621 `[project] dependencies = [...]` and Poetry's dependency table
+fn pyproject_requirements(content: &str) -> Vec<(String, Option<String>)> {
+    let mut declared = Vec::new();
+    let mut section = "";
+    let mut in_list = false;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') && !in_list {
+            section = line;
+            continue;
+        }
+        if section == "[project]" && line.starts_with("dependencies") && line.contains('[') {
+            in_list = true;
+        }
+        if in_list {
+            declared.extend(line.split('"').skip(1).step_by(2).filter_map(requirement));
+            in_list = !line.contains(']') || line.ends_with('[');
+        } else if section == "[tool.poetry.dependencies]" {
+            if let Some((key, value)) = line.split_once('=').map(|(key, value)| (key.trim(), value.trim())) {
+                if !key.is_empty() && key != "python" {
+                    let version = if value.starts_with('{') {
+                        value.split_once("version").and_then(|(_, rest)| quoted(rest.trim_start().strip_prefix('=')?))
+                    } else {
+                        quoted(value)
+                    };
+                    declared.push((key.to_string(), version));
+                }
+            }
+        }
+    }
+    declared
+}
+
+fn go_requirements(content: &str) -> Vec<(String, Option<String>)> {
+    let mut declared = Vec::new();
+    let mut in_block = false;
+    let mut add = |line: &str| {
+        let mut words = line.split_whitespace();
+        if let Some(name) = words.next() {
+            declared.push((name.to_string(), words.next().map(str::to_string)));
+        }
+    };
+    for line in content.lines().map(str::trim) {
+        if line == "require (" {
+            in_block = true;
+        } else if in_block && line == ")" {
+            in_block = false;
+        } else if in_block && !line.ends_with("// indirect") {
+            add(line);
+        } else if let Some(rest) = line.strip_prefix("require ") {
+            add(rest);
+        }
+    }
+    declared
+}
+
+// The first quoted string in `text`
+fn quoted(text: &str) -> Option<String> {
+    text.split(['"', '\'']).nth(1).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+
+    fn requirement_of(name: &str, manifest: &str, content: &str) -> Option<String> {
+        requirements(manifest, content).into_iter().find(|(declared, _)| declared == name)?.1
+    }
+
+    #[test]
+    fn test_reads_requirements() {
+        let cargo = "[dependencies]\nserde = { version = \"1.0.190\", features = [\"derive\"] }\nlog = \"0.4\"\nlocal = { path = \"../local\" }\nshared.workspace = true\n\n[dependencies.tokio]\nfeatures = [\"full\"]\nversion = \"1.35\"\n\n[dev-dependencies]\nproptest = \"1\"\n";
+        let names: Vec<String> = requirements("Cargo.toml", cargo).into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["serde", "log", "shared", "tokio"]);
+        assert_eq!(requirement_of("serde", "Cargo.toml", cargo).as_deref(), Some("1.0.190"));
+        assert_eq!(requirement_of("tokio", "Cargo.toml", cargo).as_deref(), Some("1.35"));
+        assert_eq!(requirement_of("shared", "Cargo.toml", cargo), None);
+
+        assert_eq!(requirement_of("lodash", "package.json", r#"{"dependencies": {"lodash": "^4.17.20"}}"#).as_deref(), Some("^4.17.20"));
+        assert_eq!(requirement_of("requests", "requirements.txt", "requests[socks]==2.31 ; python_version > \"3\"\n").as_deref(), Some("==2.31"));
+        assert_eq!(requirement_of("rails", "Gemfile", "gem 'rails', '~> 7.1'\n").as_deref(), Some("~> 7.1"));
+        assert_eq!(requirement_of("github.com/gin-gonic/gin", "go.mod", "require github.com/gin-gonic/gin v1.9.1\n").as_deref(), Some("v1.9.1"));
+    }
+
+    #[test]
+    fn test_lowest_version_and_comparison() {
+        let version = |requirement: &str| DeclaredDependency {
+            manifest: PathBuf::new(),
+            ecosystem: Ecosystem::Npm,
+            name: "x".to_string(),
+            requirement: Some(requirement.to_string()),
+        }.version();
+        assert_eq!(version("^1.2.3").as_deref(), Some("1.2.3"));
+        assert_eq!(version(">=2.0,<3").as_deref(), Some("2.0"));
+        assert_eq!(version("~> 7.1").as_deref(), Some("7.1"));
+        assert_eq!(version("v1.9.1").as_deref(), Some("1.9.1"));
+        assert_eq!(version("*"), None);
+        assert_eq!(version("github:user/repo"), None);
+
+        assert_eq!(compare_versions("1.2", "1.2.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.10.0", "1.9.9"), Ordering::Greater);
+        assert_eq!(compare_versions("v0.3.0-beta", "0.3.1"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_declared_dependencies_of_scan() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("mono/web/package.json", r#"{"dependencies": {"express": "4.17.1"}, "devDependencies": {"jest": "29"}}"#);
+        fs.insert("mono/api/Cargo.toml", "[package]\nname = \"api\"\n\n[dependencies]\naxum = \"0.7\"\n");
+        fs.insert("mono/README.md", "# Mono\n");
+        let result = DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "mono").unwrap();
+
+        let declared = declared_dependencies(&result, &fs);
+        let summary: Vec<(Ecosystem, &str, Option<String>)> = declared.iter()
+            .map(|dependency| (dependency.ecosystem, dependency.name.as_str(), dependency.version()))
+            .collect();
+        assert_eq!(summary, [
+            (Ecosystem::CratesIo, "axum", Some("0.7".to_string())),
+            (Ecosystem::Npm, "express", Some("4.17.1".to_string())),
+        ]);
+    }
+}
@@ -0,0 +1,381 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::FileSystem;
+
+// How much of a file is read to identify it. Enough for every header parsed
+// here, and for MP4 durations when the `moov` box comes first (fast start).
+pub(crate) const SNIFF_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaKind {
+    Image,
+    Audio,
+    Video,
+    Font,
+    Archive,
+    Document,
+    Executable,
+    Binary,
+}
+
+impl MediaKind {
+    // The tag given to files of this kind
+    pub fn tag(&self) -> &'static str {
+        match self {
+            MediaKind::Image => "image",
+            MediaKind::Audio => "audio",
+            MediaKind::Video => "video",
+            MediaKind::Font => "font",
+            MediaKind::Archive => "archive",
+            MediaKind::Document => "document",
+            MediaKind::Executable => "executable",
+            MediaKind::Binary => "binary",
+        }
+    }
+}
+
+// What a non-text file holds, from its leading bytes rather than its name.
+// Dimensions and duration are filled in where the header carries them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MediaInfo {
+    pub mime: String,
+    pub kind: MediaKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+impl MediaInfo {
+    fn new(mime: &str, kind: MediaKind) -> Self {
+        Self { mime: mime.to_string(), kind, width: None, height: None, duration_ms: None }
+    }
+
+    fn with_size(mut self, size: Option<(u32, u32)>) -> Self {
+        if let Some((width, height)) = size {
+            self.width = Some(width);
+            self.height = Some(height);
+        }
+        self
+    }
+
+    fn with_duration(mut self, duration_ms: Option<u64>) -> Self {
+        self.duration_ms = duration_ms;
+        self
+    }
+}
+
+pub(crate) fn sniff_file(path: &Path, fs: &dyn FileSystem) -> Option<MediaInfo> {
+    let bytes = fs.read_prefix(path, SNIFF_BYTES).ok()?;
+    sniff(&bytes)
+}
+
+// Identifies content by its magic bytes. Text yields `None`; content that
+// matches nothing but contains NUL bytes is reported as opaque binary.
+pub fn sniff(bytes: &[u8]) -> Option<MediaInfo> {
+    use MediaKind::*;
+
+    let starts = |magic: &[u8]| bytes.starts_with(magic);
+    let at = |offset: usize, magic: &[u8]| bytes.get(offset..offset + magic.len()) == Some(magic);
+
+    let info = if starts(b"\x89PNG\r\n\x1a\n") {
+        MediaInfo::new("image/png", Image).with_size(be32(bytes, 16).zip(be32(bytes, 20)))
+    } else if starts(b"GIF87a") || starts(b"GIF89a") {
+        MediaInfo::new("image/gif", Image).with_size(le16(bytes, 6).zip(le16(bytes, 8)))
+    } else if starts(b"\xff\xd8\xff") {
+        MediaInfo::new("image/jpeg", Image).with_size(jpeg_size(bytes))
+    } else if starts(b"BM") && bytes.len() >= 26 {
+        let size = le32(bytes, 18).zip(le32(bytes, 22)).map(|(w, h)| (w, (h as i32).unsigned_abs()));
+        MediaInfo::new("image/bmp", Image).with_size(size)
+    } else if starts(b"RIFF") && at(8, b"WEBP") {
+        MediaInfo::new("image/webp", Image).with_size(webp_size(bytes))
+    } else if starts(b"RIFF") && at(8, b"WAVE") {
+        MediaInfo::new("audio/wav", Audio).with_duration(wav_duration(bytes))
+    } else if starts(b"RIFF") && at(8, b"AVI ") {
+        let (size, duration) = avi_header(bytes).unzip();
+        MediaInfo::new("video/x-msvideo", Video).with_size(size).with_duration(duration.flatten())
+    } else if starts(b"\x00\x00\x01\x00") {
+        let dimension = |offset| bytes.get(offset).map(|&d| if d == 0 { 256 } else { u32::from(d) });
+        MediaInfo::new("image/x-icon", Image).with_size(dimension(6).zip(dimension(7)))
+    } else if starts(b"II*\x00") || starts(b"MM\x00*") {
+        MediaInfo::new("image/tiff", Image)
+    } else if at(4, b"ftyp") {
+        iso_media(bytes)
+    } else if starts(b"fLaC") {
+        MediaInfo::new("audio/flac", Audio).with_duration(flac_duration(bytes))
+    } else if starts(b"OggS") {
+        MediaInfo::new("audio/ogg", Audio)
+    } else if starts(b"ID3") || (bytes.len() > 1 && bytes[0] == 0xff && bytes[1] & 0xe6 == 0xe2) {
+        MediaInfo::new("audio/mpeg", Audio)
+    } else if starts(b"\x1a\x45\xdf\xa3") {
+        let webm = bytes.windows(4).take(64).any(|window| window == b"webm");
+        MediaInfo::new(if webm { "video/webm" } else { "video/x-matroska" }, Video)
+    } else if starts(b"%PDF-") {
+        MediaInfo::new("application/pdf", Document)
+    } else if starts(b"wOFF") {
+        MediaInfo::new("font/woff", Font)
+    } else if starts(b"wOF2") {
+        MediaInfo::new("font/woff2", Font)
+    } else if starts(b"\x00\x01\x00\x00\x00") {
+        MediaInfo::new("font/ttf", Font)
+    } else if starts(b"OTTO") {
+        MediaInfo::new("font/otf", Font)
+    } else if starts(b"PK\x03\x04") || starts(b"PK\x05\x06") {
+        MediaInfo::new("application/zip", Archive)
+    } else if starts(b"\x1f\x8b") {
+        MediaInfo::new("application/gzip", Archive)
+    } else if starts(b"7z\xbc\xaf\x27\x1c") {
+        MediaInfo::new("application/x-7z-compressed", Archive)
+    } else if starts(b"Rar!\x1a\x07") {
+        MediaInfo::new("application/vnd.rar", Archive)
+    } else if starts(b"\xfd7zXZ\x00") {
+        MediaInfo::new("application/x-xz", Archive)
+    } else if starts(b"BZh") {
+        MediaInfo::new("application/x-bzip2", Archive)
+    } else if starts(b"\x28\xb5\x2f\xfd") {
+        MediaInfo::new("application/zstd", Archive)
+    } else if at(257, b"ustar") {
+        MediaInfo::new("application/x-tar", Archive)
+    } else if starts(b"\x7fELF") {
+        MediaInfo::new("application/x-executable", Executable)
+    } else if starts(b"MZ") {
+        MediaInfo::new("application/vnd.microsoft.portable-executable", Executable)
+    } else if starts(b"\xfe\xed\xfa\xce") || starts(b"\xfe\xed\xfa\xcf") || starts(b"\xce\xfa\xed\xfe") || starts(b"\xcf\xfa\xed\xfe") {
+        MediaInfo::new("application/x-mach-binary", Executable)
+    } else if starts(b"\x00asm") {
+        MediaInfo::new("application/wasm", Executable)
+    } else if starts(b"SQLite format 3\x00") {
+        MediaInfo::new("application/vnd.sqlite3", Binary)
+    } else if is_svg(bytes) {
+        MediaInfo::new("image/svg+xml", Image)
+    } else if bytes.contains(&0) {
+        MediaInfo::new("application/octet-stream", Binary)
+    } else {
+        return None;
+    };
+    Some(info)
+}
+
+fn be16(bytes: &[u8], offset: usize) -> Option<u32> {
+    let b = bytes.get(offset..offset + 2)?;
+    Some(u32::from(u16::from_be_bytes([b[0], b[1]])))
+}
+
+fn le16(bytes: &[u8], offset: usize) -> Option<u32> {
+    let b = bytes.get(offset..offset + 2)?;
+    Some(u32::from(u16::from_le_bytes([b[0], b[1]])))
+}
+
+fn be32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(bytes.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn le32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn be64(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(bytes.get(offset..offset + 8)?.try_into().ok()?))
+}
+
+fn le24(bytes: &[u8], offset: usize) -> Option<u32> {
+    let b = bytes.get(offset..offset + 3)?;
+    Some(u32::from(b[0]) | u32::from(b[1]) << 8 | u32::from(b[2]) << 16)
+}
+
+fn millis(units: u64, per_second: u64) -> Option<u64> {
+    (per_second > 0).then(|| (u128::from(units) * 1000 / u128::from(per_second)) as u64)
+}
+
+// The frame size sits in the first start-of-frame segment
+fn jpeg_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    let mut pos = 2;
+    while pos + 4 <= bytes.len() {
+        if bytes[pos] != 0xff {
+            return None;
+        }
+        let marker = bytes[pos + 1];
+        match marker {
+            0xff => pos += 1,
+            0x01 | 0xd0..=0xd8 => pos += 2,
+            0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                return be16(bytes, pos + 7).zip(be16(bytes, pos + 5));
+            }
+            _ => pos += 2 + be16(bytes, pos + 2)? as usize,
+        }
+    }
+    None
+}
+
+fn webp_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    match bytes.get(12..16)? {
+        b"VP8 " => Some((le16(bytes, 26)? & 0x3fff, le16(bytes, 28)? & 0x3fff)),
+        b"VP8L" => {
+            let b = bytes.get(21..25)?;
+            let width = 1 + ((u32::from(b[1]) & 0x3f) << 8 | u32::from(b[0]));
+            let height = 1 + ((u32::from(b[3]) & 0x0f) << 10 | u32::from(b[2]) << 2 | (u32::from(b[1]) & 0xc0) >> 6);
+            Some((width, height))
+        }
+        b"VP8X" => Some((1 + le24(bytes, 24)?, 1 + le24(bytes, 27)?)),
+        _ => None,
+    }
+}
+
+// RIFF chunks from offset 12: the byte rate comes from `fmt `, the length from `data`
+fn wav_duration(bytes: &[u8]) -> Option<u64> {
+    let (mut pos, mut byte_rate) = (12, None);
+    while pos + 8 <= bytes.len() {
+        let size = le32(bytes, pos + 4)? as usize;
+        match &bytes[pos..pos + 4] {
+            b"fmt " => byte_rate = le32(bytes, pos + 16),
+            b"data" => return millis(size as u64, u64::from(byte_rate?)),
+            _ => {}
+        }
+        // Chunks are padded to an even length
+        pos += 8 + size + size % 2;
+    }
+    None
+}
+
+// The main AVI header: microseconds per frame, frame count and frame size
+fn avi_header(bytes: &[u8]) -> Option<((u32, u32), Option<u64>)> {
+    let pos = bytes.windows(4).position(|window| window == b"avih")? + 8;
+    let frame_us = u64::from(le32(bytes, pos)?);
+    let frames = u64::from(le32(bytes, pos + 16)?);
+    let size = (le32(bytes, pos + 32)?, le32(bytes, pos + 36)?);
+    Some((size, millis(frame_us * frames, 1_000_000)))
+}
+
+// STREAMINFO is always the first metadata block: a 20-bit sample rate
+// followed by a 36-bit sample count
+fn flac_duration(bytes: &[u8]) -> Option<u64> {
+    let b = bytes.get(18..26)?;
+    let sample_rate = u64::from(b[0]) << 12 | u64::from(b[1]) << 4 | u64::from(b[2]) >> 4;
+    let samples = (u64::from(b[3]) & 0x0f) << 32 | u64::from(be32(b, 4)?);
+    millis(samples, sample_rate)
+}
+
+// MP4, QuickTime and the image formats that share their box structure
+fn iso_media(bytes: &[u8]) -> MediaInfo {
+    let brand = bytes.get(8..12).unwrap_or_default();
+    let info = match brand {
+        b"avif" | b"avis" => return MediaInfo::new("image/avif", MediaKind::Image),
+        b"heic" | b"heix" | b"mif1" => return MediaInfo::new("image/heic", MediaKind::Image),
+        b"M4A " => MediaInfo::new("audio/mp4", MediaKind::Audio),
+        b"qt  " => MediaInfo::new("video/quicktime", MediaKind::Video),
+        _ => MediaInfo::new("video/mp4", MediaKind::Video),
+    };
+
+    let Some(moov) = find_box(bytes, b"moov") else { return info };
+    let duration = find_box(moov, b"mvhd").and_then(|mvhd| match mvhd.first()? {
+        1 => millis(be64(mvhd, 24)?, u64::from(be32(mvhd, 20)?)),
+        _ => millis(u64::from(be32(mvhd, 16)?), u64::from(be32(mvhd, 12)?)),
+    });
+    // The first track with a picture gives the frame size, as 16.16 fixed point
+    let size = boxes(moov)
+        .filter(|(kind, _)| kind == b"trak")
+        .filter_map(|(_, trak)| find_box(trak, b"tkhd"))
+        .filter_map(|tkhd| {
+            let offset = if tkhd.first() == Some(&1) { 88 } else { 76 };
+            Some((be32(tkhd, offset)? >> 16, be32(tkhd, offset + 4)? >> 16))
+        })
+        .find(|&(width, height)| width > 0 && height > 0);
+    let info = info.with_duration(duration);
+    if info.kind == MediaKind::Video { info.with_size(size) } else { info }
+}
+
+// Top-level boxes of `bytes` as (type, content), stopping at the first
+// truncated or malformed one
+fn boxes(bytes: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let size = be32(bytes, pos)? as usize;
+        let kind: [u8; 4] = bytes.get(pos + 4..pos + 8)?.try_into().ok()?;
+        let (header, size) = match size {
+            1 => (16, be64(bytes, pos + 8)? as usize),
+            0 => (8, bytes.len() - pos),
+            _ => (8, size),
+        };
+        let content = bytes.get(pos + header..pos + size)?;
+        pos += size;
+        Some((kind, content))
+    })
+}
+
+fn find_box<'a>(bytes: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    boxes(bytes).find(|(found, _)| found == kind).map(|(_, content)| content)
+}
+
+fn is_svg(bytes: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]);
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    (head.starts_with("<?xml") || head.starts_with("<svg") || head.starts_with("<!--")) && head.contains("<svg")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sized(info: &MediaInfo) -> (&str, Option<u32>, Option<u32>) {
+        (info.mime.as_str(), info.width, info.height)
+    }
+
+    #[test]
+    fn test_image_dimensions() {
+        let mut png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+        png.extend_from_slice(&640u32.to_be_bytes());
+        png.extend_from_slice(&480u32.to_be_bytes());
+        assert_eq!(sized(&sniff(&png).unwrap()), ("image/png", Some(640), Some(480)));
+
+        let gif = b"GIF89a\x20\x00\x10\x00";
+        assert_eq!(sized(&sniff(gif).unwrap()), ("image/gif", Some(32), Some(16)));
+
+        // SOI, an APP0 segment, then SOF0 with height 200 and width 300
+        let jpeg = b"\xff\xd8\xff\xe0\x00\x04ab\xff\xc0\x00\x11\x08\x00\xc8\x01\x2c\x03";
+        let info = sniff(jpeg).unwrap();
+        assert_eq!(sized(&info), ("image/jpeg", Some(300), Some(200)));
+        assert_eq!(info.kind.tag(), "image");
+    }
+
+    #[test]
+    fn test_audio_and_video_durations() {
+        // 16-bit stereo at 44.1kHz, with two seconds of samples declared
+        let mut wav = b"RIFF\x00\x00\x00\x00WAVEfmt \x10\x00\x00\x00\x01\x00\x02\x00".to_vec();
+        wav.extend_from_slice(&44_100u32.to_le_bytes());
+        wav.extend_from_slice(&176_400u32.to_le_bytes());
+        wav.extend_from_slice(b"\x04\x00\x10\x00data");
+        wav.extend_from_slice(&352_800u32.to_le_bytes());
+        assert_eq!(sniff(&wav).unwrap().duration_ms, Some(2000));
+
+        // ftyp, then moov holding mvhd (timescale 1000, duration 1500) and a 1280x720 track
+        let mut mvhd = vec![0u8; 100];
+        mvhd[12..16].copy_from_slice(&1000u32.to_be_bytes());
+        mvhd[16..20].copy_from_slice(&1500u32.to_be_bytes());
+        let mut tkhd = vec![0u8; 84];
+        tkhd[76..80].copy_from_slice(&(1280u32 << 16).to_be_bytes());
+        tkhd[80..84].copy_from_slice(&(720u32 << 16).to_be_bytes());
+        let wrap = |kind: &[u8], content: &[u8]| -> Vec<u8> {
+            let mut boxed = ((content.len() + 8) as u32).to_be_bytes().to_vec();
+            boxed.extend_from_slice(kind);
+            boxed.extend_from_slice(content);
+            boxed
+        };
+        let moov = [wrap(b"mvhd", &mvhd), wrap(b"trak", &wrap(b"tkhd", &tkhd))].concat();
+        let mp4 = [wrap(b"ftyp", b"isom\x00\x00\x02\x00"), wrap(b"moov", &moov)].concat();
+        let info = sniff(&mp4).unwrap();
+        assert_eq!(sized(&info), ("video/mp4", Some(1280), Some(720)));
+        assert_eq!(info.duration_ms, Some(1500));
+    }
+
+    #[test]
+    fn test_text_is_not_media() {
+        assert_eq!(sniff(b"fn main() {}\n"), None);
+        assert_eq!(sniff(b"<?xml version=\"1.0\"?>\n<svg width=\"10\"/>").unwrap().mime, "image/svg+xml");
+        assert_eq!(sniff(b"\x01\x02\x00\x03").unwrap().kind, MediaKind::Binary);
+        assert_eq!(sniff(b"%PDF-1.7\n").unwrap().kind.tag(), "document");
+    }
+}
@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+use crate::{dir_stats, DirectoryScanner, Distribution, FileEntry, ScanError, ScanResult, ScanStats, TestCoverageStructure};
+
+// The share of a multi-root scan contributed by one root
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RootStats {
+    pub path: PathBuf,
+    pub stats: ScanStats,
+}
+
+impl DirectoryScanner {
+    // Scans each root in turn into one result, e.g. the repos of a workspace.
+    // See `ScanResult::merge` for how the results are combined.
+    pub fn scan_many<P: AsRef<Path>>(&self, paths: &[P]) -> Result<ScanResult, ScanError> {
+        let mut merged: Option<ScanResult> = None;
+        for path in paths {
+            let result = self.scan(path.as_ref())?;
+            merged = Some(match merged {
+                Some(merged) => merged.merge(result),
+                None => result,
+            });
+        }
+        merged.ok_or_else(|| ScanError::InvalidConfig { message: "No paths to scan".to_string() })
+    }
+}
+
+impl ScanResult {
+    // Combines two results, e.g. from parallel or per-root scans. A path present
+    // in both is kept from `self`, and stats are recomputed from the merged
+    // entries with the durations added. The root becomes the roots' common
+    // ancestor, so relative output paths keep the part that tells them apart,
+    // and `roots` records each distinct root with its own stats.
+    pub fn merge(self, other: ScanResult) -> ScanResult {
+        let root_path = common_ancestor([self.root_path.as_path(), other.root_path.as_path()]);
+        let sorted = is_sorted(&self.files) && is_sorted(&other.files);
+        let scan_duration_ms = self.stats.scan_duration_ms + other.stats.scan_duration_ms;
+        let with_coverage = self.test_coverage_structure.is_some() || other.test_coverage_structure.is_some();
+        let cache = match (self.stats.cache.clone(), other.stats.cache.clone()) {
+            (Some(cache), Some(other)) => Some(cache.merge(other)),
+            (cache, other) => cache.or(other),
+        };
+        let sampling = match (self.stats.sampling.clone(), other.stats.sampling.clone()) {
+            (Some(sampling), Some(other)) => Some(sampling.merge(other)),
+            (sampling, other) => sampling.or(other),
+        };
+        let resources = match (self.stats.resources.clone(), other.stats.resources.clone()) {
+            (Some(resources), Some(other)) => Some(resources.merge(other)),
+            (resources, other) => resources.or(other),
+        };
+
+        let mut roots = Vec::new();
+        for result in [&self, &other] {
+            if result.roots.is_empty() {
+                roots.push(RootStats { path: result.root_path.clone(), stats: result.stats.clone() });
+            } else {
+                roots.extend(result.roots.iter().cloned());
+            }
+        }
+        let mut root_paths = HashSet::new();
+        roots.retain(|root| root_paths.insert(root.path.clone()));
+        // Shards of a single root need no breakdown
+        if roots.len() == 1 {
+            roots.clear();
+        }
+
+        let mut files = self.files;
+        let mut seen: HashSet<PathBuf> = files.iter().map(|file| file.path.clone()).collect();
+        files.extend(other.files.into_iter().filter(|file| seen.insert(file.path.clone())));
+        let mut errors = self.errors;
+        for issue in other.errors {
+            if !errors.contains(&issue) {
+                errors.push(issue);
+            }
+        }
+        let mut findings = self.findings;
+        for finding in other.findings {
+            if !findings.iter().any(|known| known.path == finding.path && known.kind == finding.kind && known.advisory == finding.advisory) {
+                findings.push(finding);
+            }
+        }
+        let mut entry_points = self.entry_points;
+        for entry in other.entry_points {
+            if !entry_points.iter().any(|known| known.path == entry.path && known.kind == entry.kind) {
+                entry_points.push(entry);
+            }
+        }
+        let mut endpoints = self.endpoints;
+        for endpoint in other.endpoints {
+            if !endpoints.contains(&endpoint) {
+                endpoints.push(endpoint);
+            }
+        }
+        let schema = match (self.schema, other.schema) {
+            (Some(schema), Some(other)) => Some(schema.merge(other)),
+            (schema, other) => schema.or(other),
+        };
+        let mut containers = self.containers;
+        for artifact in other.containers {
+            if !containers.iter().any(|known| known.path == artifact.path) {
+                containers.push(artifact);
+            }
+        }
+        let mut nested_repositories = self.nested_repositories;
+        for repo in other.nested_repositories {
+            if !nested_repositories.iter().any(|known| known.path == repo.path) {
+                nested_repositories.push(repo);
+            }
+        }
+        if sorted {
+            files.sort_by(|a, b| a.path.cmp(&b.path));
+            errors.sort_by(|a, b| a.path.cmp(&b.path));
+            findings.sort_by(|a, b| a.path.cmp(&b.path));
+        }
+
+        let stats = ScanStats {
+            total_files: files.len(),
+            total_dirs: files.iter().filter(|file| file.is_dir).count(),
+            total_size: files.iter().filter(|file| !file.is_dir).map(|file| file.size).sum(),
+            scan_duration_ms,
+            files_per_second: if scan_duration_ms > 0 {
+                files.len() as f64 / (scan_duration_ms as f64 / 1000.0)
+            } else {
+                0.0
+            },
+            directories: dir_stats::directory_stats(&root_path, &files, &errors),
+            distribution: Distribution::from_files(&root_path, &files),
+            cache,
+            sampling,
+            resources,
+        };
+        let test_coverage_structure = with_coverage.then(|| TestCoverageStructure::build(&files));
+
+        ScanResult {
+            schema_version: crate::SCHEMA_VERSION,
+            root_path,
+            files,
+            stats,
+            roots,
+            errors,
+            findings,
+            entry_points,
+            endpoints,
+            schema,
+            containers,
+            nested_repositories,
+            test_coverage_structure,
+        }
+    }
+}
+
+fn is_sorted(files: &[FileEntry]) -> bool {
+    files.is_sorted_by(|a, b| a.path <= b.path)
+}
+
+// The deepest directory containing every path, compared component by
+// component; "." when relative roots share nothing
+fn common_ancestor<'a>(paths: impl IntoIterator<Item = &'a Path>) -> PathBuf {
+    let mut paths = paths.into_iter();
+    let Some(first) = paths.next() else {
+        return PathBuf::from(".");
+    };
+    let mut common: Vec<Component> = first.components().filter(|c| *c != Component::CurDir).collect();
+    for path in paths {
+        let shared = path.components()
+            .filter(|c| *c != Component::CurDir)
+            .zip(&common)
+            .take_while(|(a, b)| a == *b)
+            .count();
+        common.truncate(shared);
+    }
+    if common.is_empty() {
+        PathBuf::from(".")
+    } else {
+        common.iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MemoryFileSystem, ScanOptions};
+
+    fn scan(fs: &MemoryFileSystem, path: &str) -> ScanResult {
+        DirectoryScanner::new(ScanOptions::default()).scan_with(fs, path).unwrap()
+    }
+
+    #[test]
+    fn test_common_ancestor() {
+        let ancestor = |paths: &[&str]| common_ancestor(paths.iter().map(Path::new));
+
+        assert_eq!(ancestor(&["ws/api", "ws/web/", "./ws/lib"]), PathBuf::from("ws"));
+        assert_eq!(ancestor(&["/ws/api"]), PathBuf::from("/ws/api"));
+        assert_eq!(ancestor(&["api", "web"]), PathBuf::from("."));
+    }
+
+    #[test]
+    fn test_combines_roots_with_per_root_stats() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("ws/api/main.rs", "fn main() {}");
+        fs.insert("ws/web/index.js", "export default 1;");
+        fs.insert("ws/web/app.js", "export {};");
+
+        let result = scan(&fs, "ws/web").merge(scan(&fs, "ws/api"));
+        assert_eq!(result.root_path, PathBuf::from("ws"));
+        assert_eq!(result.roots.iter().map(|root| root.path.to_str().unwrap()).collect::<Vec<_>>(), ["ws/web", "ws/api"]);
+        assert_eq!(result.roots[0].stats.total_size, 27);
+        assert_eq!(result.stats.total_size, 39);
+        assert_eq!(result.files.first().unwrap().path, PathBuf::from("ws/api"));
+        assert!(result.find("ws/web/app.js").is_some());
+    }
+
+    #[test]
+    fn test_overlapping_roots_report_paths_once() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("ws/api/main.rs", "fn main() {}");
+        fs.insert("ws/README.md", "# ws");
+
+        let whole = scan(&fs, "ws");
+        let result = whole.clone().merge(scan(&fs, "ws/api"));
+        let paths: Vec<&Path> = result.files.iter().map(|file| file.path.as_path()).collect();
+        let mut unique = paths.clone();
+        unique.dedup();
+        assert_eq!(paths, unique);
+        assert_eq!(result.stats.total_files, whole.stats.total_files);
+        assert_eq!(result.stats.total_size, whole.stats.total_size);
+        assert_eq!(result.roots.len(), 2);
+    }
+
+    #[test]
+    fn test_merging_shards_of_one_root() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("ws/a.rs", "fn a() {}");
+        fs.insert("ws/b.rs", "fn b() {}");
+        let whole = scan(&fs, "ws");
+        let mut first = whole.clone();
+        let mut second = whole.clone();
+        first.files.retain(|file| file.name == "a.rs");
+        second.files.retain(|file| file.name == "b.rs");
+
+        let merged = second.merge(first);
+        assert_eq!(merged.root_path, PathBuf::from("ws"));
+        assert!(merged.roots.is_empty());
+        assert_eq!(merged.files.iter().map(|file| file.name.as_str()).collect::<Vec<_>>(), ["a.rs", "b.rs"]);
+        assert_eq!(merged.stats.total_size, whole.stats.total_size);
+    }
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+// Throughput of one finished scan, for hosts that track scan performance.
+// Cache figures only count when the scanner belongs to a `ScanSession`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScanMetrics {
+    pub files: usize,
+    pub bytes: u64,
+    pub duration_ms: u64,
+    pub files_per_second: f64,
+    pub bytes_per_second: f64,
+    pub cache_hits: usize,
+    pub cache_misses: usize,
+}
+
+pub type MetricsCallback = Box<dyn Fn(&ScanMetrics)>;
+
+impl ScanMetrics {
+    pub(crate) fn new(files: usize, bytes: u64, duration_ms: u64, cache_hits: usize, cache_misses: usize) -> Self {
+        let per_second = |count: f64| if duration_ms > 0 { count / (duration_ms as f64 / 1000.0) } else { 0.0 };
+        Self {
+            files,
+            bytes,
+            duration_ms,
+            files_per_second: per_second(files as f64),
+            bytes_per_second: per_second(bytes as f64),
+            cache_hits,
+            cache_misses,
+        }
+    }
+
+    // Share of analyzed files served from the cache; None when nothing was looked up
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        let lookups = self.cache_hits + self.cache_misses;
+        (lookups > 0).then(|| self.cache_hits as f64 / lookups as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions, ScanSession};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_rates_per_second() {
+        let metrics = ScanMetrics::new(50, 2_000_000, 500, 0, 0);
+
+        assert_eq!(metrics.files_per_second, 100.0);
+        assert_eq!(metrics.bytes_per_second, 4_000_000.0);
+        assert_eq!(metrics.cache_hit_rate(), None);
+        assert_eq!(ScanMetrics::new(3, 10, 0, 0, 0).files_per_second, 0.0);
+    }
+
+    #[test]
+    fn test_callback_receives_metrics_after_scan() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/main.rs", "fn main() {}");
+        fs.insert("app/README.md", "# app");
+        let seen = Rc::new(RefCell::new(None));
+
+        let sink = Rc::clone(&seen);
+        DirectoryScanner::new(ScanOptions::default())
+            .with_metrics(move |metrics| *sink.borrow_mut() = Some(metrics.clone()))
+            .scan_with(&fs, "app")
+            .unwrap();
+        let metrics = seen.borrow().clone().unwrap();
+        assert_eq!((metrics.files, metrics.bytes), (2, 17));
+        assert_eq!(metrics.cache_hits + metrics.cache_misses, 0);
+    }
+
+    #[test]
+    fn test_session_scans_report_cache_hits() {
+        let session = ScanSession::new();
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/main.rs", "fn main() {}");
+        let options = ScanOptions { enhanced_analysis: true, ..Default::default() };
+        let seen = Rc::new(RefCell::new(Vec::new()));
+
+        for _ in 0..2 {
+            let sink = Rc::clone(&seen);
+            session.scanner(options.clone()).unwrap()
+                .with_metrics(move |metrics| sink.borrow_mut().push(metrics.cache_hit_rate()))
+                .scan_with(&fs, "app")
+                .unwrap();
+        }
+        assert_eq!(*seen.borrow(), [Some(0.0), Some(1.0)]);
+    }
+}
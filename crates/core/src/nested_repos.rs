@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::{ContentAnalyzer, FileSystem, FsEntry};
+
+// Tags for the root directory of a git submodule and of any other repository
+// nested in the scanned tree
+pub const SUBMODULE_TAG: &str = "git-submodule";
+pub const NESTED_REPO_TAG: &str = "nested-repo";
+
+// What a scan does with the contents of nested repositories; their root
+// directories are reported and tagged either way
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NestedRepoMode {
+    // Report their files like any other
+    #[default]
+    Scan,
+    // Leave their files out, counting them in `NestedRepository::summary`
+    Summarize,
+    // Leave their files out
+    Skip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NestedRepoKind {
+    // `.git` is a file pointing into the parent's git directory
+    Submodule,
+    // `.git` is a directory of its own, e.g. a vendored checkout
+    Repository,
+}
+
+impl NestedRepoKind {
+    pub fn tag(self) -> &'static str {
+        match self {
+            NestedRepoKind::Submodule => SUBMODULE_TAG,
+            NestedRepoKind::Repository => NESTED_REPO_TAG,
+        }
+    }
+}
+
+impl fmt::Display for NestedRepoKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            NestedRepoKind::Submodule => "submodule",
+            NestedRepoKind::Repository => "repository",
+        })
+    }
+}
+
+// Counts of the entries left out of a summarized repository
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NestedRepoSummary {
+    pub files: usize,
+    pub dirs: usize,
+    pub total_size: u64,
+    // Files per language, by extension
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub languages: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NestedRepository {
+    pub path: PathBuf,
+    pub kind: NestedRepoKind,
+    // Set when the scan summarized the repository instead of reporting its files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<NestedRepoSummary>,
+}
+
+impl NestedRepository {
+    // The repository rooted at `dir`, if it has a `.git` entry
+    pub(crate) fn detect(dir: &Path, fs: &dyn FileSystem, mode: NestedRepoMode) -> Option<Self> {
+        let git = dir.join(".git");
+        if !fs.exists(&git) {
+            return None;
+        }
+        // A submodule's `.git` is a one-line file; reading a directory fails
+        let kind = if fs.read_prefix(&git, 64).is_ok() { NestedRepoKind::Submodule } else { NestedRepoKind::Repository };
+        let summary = (mode == NestedRepoMode::Summarize).then(NestedRepoSummary::default);
+        Some(Self { path: dir.to_path_buf(), kind, summary })
+    }
+
+    // Counts an entry under the repository when it is summarized
+    pub(crate) fn absorb(&mut self, entry: &FsEntry) {
+        let Some(summary) = self.summary.as_mut() else { return };
+        if entry.is_dir {
+            summary.dirs += 1;
+        } else {
+            summary.files += 1;
+            summary.total_size += entry.size;
+            if let Some(language) = ContentAnalyzer::new().detect_language(&entry.name) {
+                *summary.languages.entry(language).or_insert(0) += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions, ScanResult};
+
+    fn scan(mode: NestedRepoMode) -> ScanResult {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/src/main.rs", "fn main() {}\n");
+        fs.insert("app/vendor/lib/.git", "gitdir: ../../.git/modules/lib\n");
+        fs.insert("app/vendor/lib/src/lib.rs", "pub fn lib() {}\n");
+        fs.insert("app/vendor/lib/README.md", "# lib\n");
+        fs.insert("app/tools/gen/.git/HEAD", "ref: refs/heads/main\n");
+        fs.insert("app/tools/gen/gen.py", "print('hi')\n");
+        let options = ScanOptions { nested_repos: mode, ..Default::default() };
+        DirectoryScanner::new(options).scan_with(&fs, "app").unwrap()
+    }
+
+    fn has_tag(result: &ScanResult, path: &str, tag: &str) -> bool {
+        result.find(path).is_some_and(|entry| entry.tags.iter().any(|existing| existing == tag))
+    }
+
+    #[test]
+    fn test_marks_submodules_and_nested_repositories() {
+        let result = scan(NestedRepoMode::Scan);
+
+        let kinds: Vec<(&str, NestedRepoKind)> = result.nested_repositories.iter()
+            .map(|repo| (repo.path.to_str().unwrap(), repo.kind))
+            .collect();
+        assert_eq!(kinds, [("app/tools/gen", NestedRepoKind::Repository), ("app/vendor/lib", NestedRepoKind::Submodule)]);
+        assert!(has_tag(&result, "app/vendor/lib", SUBMODULE_TAG));
+        assert!(has_tag(&result, "app/tools/gen", NESTED_REPO_TAG));
+        assert!(result.find("app/vendor/lib/src/lib.rs").is_some());
+    }
+
+    #[test]
+    fn test_skip_leaves_out_their_files() {
+        let result = scan(NestedRepoMode::Skip);
+
+        assert!(has_tag(&result, "app/vendor/lib", SUBMODULE_TAG));
+        let inside = |root: &str| result.files.iter().any(|entry| entry.path.starts_with(root) && entry.path != Path::new(root));
+        assert!(!inside("app/vendor/lib") && !inside("app/tools/gen"));
+        assert!(result.find("app/src/main.rs").is_some());
+        assert!(result.nested_repositories.iter().all(|repo| repo.summary.is_none()));
+    }
+
+    #[test]
+    fn test_summarize_counts_their_files() {
+        let result = scan(NestedRepoMode::Summarize);
+
+        let lib = result.nested_repositories.iter().find(|repo| repo.kind == NestedRepoKind::Submodule).unwrap();
+        let summary = lib.summary.as_ref().unwrap();
+        // `.git` is hidden, so the pointer file is not counted
+        assert_eq!((summary.files, summary.dirs), (2, 1));
+        assert_eq!(summary.languages.get("rust"), Some(&1));
+        assert!(result.find("app/vendor/lib/README.md").is_none());
+        assert_eq!(result.stats.total_files, result.files.len());
+    }
+}
@@ -0,0 +1,298 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+use crate::manifests::{compare_versions, version_parts};
+use crate::{declared_dependencies, Ecosystem, FileSystem, ScanError, ScanResult};
+
+// deps.dev serves the published versions of crates.io, npm, PyPI and Go packages
+#[cfg(feature = "registry")]
+pub const DEPS_DEV_ENDPOINT: &str = "https://api.deps.dev/v3/systems";
+
+// Weight of each lag in a project's score; a dependency a major version
+// behind counts fully
+const PATCH_WEIGHT: f64 = 0.1;
+const MINOR_WEIGHT: f64 = 0.4;
+const MAJOR_WEIGHT: f64 = 1.0;
+
+// How far a declared version is behind the latest release
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum VersionLag {
+    Current,
+    Patch,
+    Minor,
+    // Includes 0.x minor releases, which semver treats as breaking
+    Major,
+}
+
+impl VersionLag {
+    // `declared` behind `latest`; a declared version ahead of the index, such
+    // as a pre-release, is current
+    pub fn between(declared: &str, latest: &str) -> Self {
+        if compare_versions(declared, latest) != Ordering::Less {
+            return VersionLag::Current;
+        }
+        let (declared, latest) = (version_parts(declared), version_parts(latest));
+        let differs = (0..declared.len().max(latest.len()))
+            .find(|index| declared.get(*index).unwrap_or(&0) != latest.get(*index).unwrap_or(&0))
+            .unwrap_or(0);
+        // Leading zeros shift the breaking position right: 0.3 -> 0.4 and
+        // 0.0.3 -> 0.0.4 are major updates
+        let breaking = declared.iter().take(2).take_while(|part| **part == 0).count();
+        match differs {
+            index if index <= breaking => VersionLag::Major,
+            1 => VersionLag::Minor,
+            _ => VersionLag::Patch,
+        }
+    }
+
+    fn weight(&self) -> f64 {
+        match self {
+            VersionLag::Current => 0.0,
+            VersionLag::Patch => PATCH_WEIGHT,
+            VersionLag::Minor => MINOR_WEIGHT,
+            VersionLag::Major => MAJOR_WEIGHT,
+        }
+    }
+}
+
+// Latest published version of each package, by ecosystem. The JSON form is
+// `{"crates.io": {"serde": "1.0.210"}, "npm": {"express": "4.21.0"}}`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct VersionIndex {
+    latest: BTreeMap<Ecosystem, BTreeMap<String, String>>,
+}
+
+impl VersionIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ScanError> {
+        serde_json::from_str(json).map_err(|e| registry_error(format!("invalid version index: {}", e)))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ScanError> {
+        if !path.exists() {
+            return Err(ScanError::PathNotFound { path: path.to_path_buf() });
+        }
+        let json = std::fs::read_to_string(path)?;
+        Self::from_json(&json).map_err(|e| registry_error(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn insert(&mut self, ecosystem: Ecosystem, name: impl Into<String>, version: impl Into<String>) {
+        self.latest.entry(ecosystem).or_default().insert(name.into(), version.into());
+    }
+
+    // Entries of `other` replace ours
+    pub fn extend(&mut self, other: VersionIndex) {
+        for (ecosystem, versions) in other.latest {
+            self.latest.entry(ecosystem).or_default().extend(versions);
+        }
+    }
+
+    pub fn latest(&self, ecosystem: Ecosystem, name: &str) -> Option<&str> {
+        self.latest.get(&ecosystem)?.get(name).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.latest.values().map(BTreeMap::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutdatedDependency {
+    pub manifest: PathBuf,
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    // Lowest version the requirement allows
+    pub declared: String,
+    pub latest: String,
+    pub lag: VersionLag,
+}
+
+// One directory with manifests, e.g. each package of a monorepo
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectOutdatedness {
+    pub path: PathBuf,
+    // Dependencies with both a declared and a latest version
+    pub checked: usize,
+    pub outdated: usize,
+    // 0 when every checked dependency is current, 100 when each is a major
+    // version behind; patch and minor lags count for less
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OutdatedReport {
+    // Checked dependencies, most behind first
+    pub dependencies: Vec<OutdatedDependency>,
+    // Projects by path
+    pub projects: Vec<ProjectOutdatedness>,
+    // Declared dependencies without a version or missing from the index
+    pub unknown: usize,
+}
+
+impl OutdatedReport {
+    // Manifests are read through `fs`, the same source the scan used
+    pub fn compute(result: &ScanResult, fs: &dyn FileSystem, index: &VersionIndex) -> Self {
+        let mut report = Self::default();
+        let mut projects: BTreeMap<PathBuf, (usize, usize, f64)> = BTreeMap::new();
+        for dependency in declared_dependencies(result, fs) {
+            let project = dependency.manifest.parent().map(Path::to_path_buf).unwrap_or_default();
+            let latest = index.latest(dependency.ecosystem, &dependency.name);
+            let (Some(declared), Some(latest)) = (dependency.version(), latest) else {
+                report.unknown += 1;
+                projects.entry(project).or_default();
+                continue;
+            };
+            let lag = VersionLag::between(&declared, latest);
+            let (checked, outdated, weight) = projects.entry(project).or_default();
+            *checked += 1;
+            *outdated += (lag != VersionLag::Current) as usize;
+            *weight += lag.weight();
+            report.dependencies.push(OutdatedDependency {
+                manifest: dependency.manifest,
+                ecosystem: dependency.ecosystem,
+                name: dependency.name,
+                declared,
+                latest: latest.to_string(),
+                lag,
+            });
+        }
+        report.dependencies.sort_by(|a, b| b.lag.cmp(&a.lag).then_with(|| a.manifest.cmp(&b.manifest)).then_with(|| a.name.cmp(&b.name)));
+        report.projects = projects.into_iter()
+            .map(|(path, (checked, outdated, weight))| ProjectOutdatedness {
+                path,
+                checked,
+                outdated,
+                score: if checked == 0 { 0.0 } else { (1000.0 * weight / checked as f64).round() / 10.0 },
+            })
+            .collect();
+        report
+    }
+
+    pub fn outdated(&self) -> impl Iterator<Item = &OutdatedDependency> {
+        self.dependencies.iter().filter(|dependency| dependency.lag != VersionLag::Current)
+    }
+}
+
+// Looks up the latest release of each dependency on deps.dev. Ecosystems it
+// does not serve (RubyGems, Packagist) are left out of the index.
+#[cfg(feature = "registry")]
+pub async fn fetch_latest_versions(endpoint: &str, dependencies: &[crate::DeclaredDependency]) -> Result<VersionIndex, ScanError> {
+    #[derive(Deserialize)]
+    struct Package {
+        versions: Vec<PackageVersion>,
+    }
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct PackageVersion {
+        version_key: VersionKey,
+        #[serde(default)]
+        is_default: bool,
+    }
+    #[derive(Deserialize)]
+    struct VersionKey {
+        version: String,
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(|e| registry_error(format!("failed to create HTTP client: {}", e)))?;
+    let mut index = VersionIndex::new();
+    for dependency in dependencies {
+        let system = match dependency.ecosystem {
+            Ecosystem::CratesIo => "cargo",
+            Ecosystem::Npm => "npm",
+            Ecosystem::PyPi => "pypi",
+            Ecosystem::Go => "go",
+            Ecosystem::RubyGems | Ecosystem::Packagist => continue,
+        };
+        if index.latest(dependency.ecosystem, &dependency.name).is_some() {
+            continue;
+        }
+        let mut url = reqwest::Url::parse(endpoint).map_err(|e| registry_error(format!("invalid endpoint {}: {}", endpoint, e)))?;
+        url.path_segments_mut()
+            .map_err(|_| registry_error(format!("invalid endpoint {}", endpoint)))?
+            .extend([system, "packages", dependency.name.as_str()]);
+        let response = client.get(url.clone()).send().await
+            .map_err(|e| registry_error(format!("request to {} failed: {}", url, e)))?;
+        // Private and misspelled packages are unknown rather than an error
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            continue;
+        }
+        if !response.status().is_success() {
+            return Err(registry_error(format!("{} returned {}", url, response.status())));
+        }
+        let package: Package = response.json().await
+            .map_err(|e| registry_error(format!("failed to read response from {}: {}", url, e)))?;
+        if let Some(latest) = package.versions.into_iter().find(|version| version.is_default) {
+            index.insert(dependency.ecosystem, dependency.name.clone(), latest.version_key.version);
+        }
+    }
+    Ok(index)
+}
+
+fn registry_error(message: String) -> ScanError {
+    ScanError::Registry { message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+
+    #[test]
+    fn test_version_lag() {
+        assert_eq!(VersionLag::between("1.2.3", "1.2.3"), VersionLag::Current);
+        assert_eq!(VersionLag::between("1.2", "1.2.0"), VersionLag::Current);
+        assert_eq!(VersionLag::between("2.0.0-beta", "1.9.0"), VersionLag::Current);
+        assert_eq!(VersionLag::between("1.2.3", "1.2.9"), VersionLag::Patch);
+        assert_eq!(VersionLag::between("1.2", "1.5.0"), VersionLag::Minor);
+        assert_eq!(VersionLag::between("1.9.9", "2.0.0"), VersionLag::Major);
+        assert_eq!(VersionLag::between("0.3.1", "0.4.0"), VersionLag::Major);
+        assert_eq!(VersionLag::between("0.3.1", "0.3.2"), VersionLag::Patch);
+        assert_eq!(VersionLag::between("0.0.3", "0.0.4"), VersionLag::Major);
+    }
+
+    #[test]
+    fn test_index_json() {
+        let mut index = VersionIndex::from_json(r#"{"crates.io": {"serde": "1.0.210"}, "npm": {"express": "4.21.0"}}"#).unwrap();
+        assert_eq!(index.latest(Ecosystem::CratesIo, "serde"), Some("1.0.210"));
+        assert_eq!(index.latest(Ecosystem::PyPi, "serde"), None);
+        index.extend(VersionIndex::from_json(r#"{"npm": {"express": "5.0.0"}}"#).unwrap());
+        assert_eq!(index.latest(Ecosystem::Npm, "express"), Some("5.0.0"));
+        assert_eq!(index.len(), 2);
+        assert!(VersionIndex::from_json(r#"{"maven": {}}"#).is_err());
+    }
+
+    #[test]
+    fn test_scores_each_project() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("mono/web/package.json", r#"{"dependencies": {"express": "^4.17.1", "left-pad": "1.3.0", "private-lib": "2.0.0"}}"#);
+        fs.insert("mono/api/Cargo.toml", "[package]\nname = \"api\"\n\n[dependencies]\naxum = \"0.6\"\nserde = \"1.0.210\"\n");
+        let result = DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "mono").unwrap();
+        let mut index = VersionIndex::new();
+        index.insert(Ecosystem::CratesIo, "axum", "0.7.5");
+        index.insert(Ecosystem::CratesIo, "serde", "1.0.210");
+        index.insert(Ecosystem::Npm, "express", "4.21.0");
+        index.insert(Ecosystem::Npm, "left-pad", "1.3.0");
+
+        let report = OutdatedReport::compute(&result, &fs, &index);
+        let outdated: Vec<(&str, VersionLag)> = report.outdated().map(|dependency| (dependency.name.as_str(), dependency.lag)).collect();
+        assert_eq!(outdated, [("axum", VersionLag::Major), ("express", VersionLag::Minor)]);
+        assert_eq!(report.unknown, 1);
+
+        let scores: Vec<(&Path, usize, f64)> = report.projects.iter().map(|project| (project.path.as_path(), project.checked, project.score)).collect();
+        assert_eq!(scores, [(Path::new("mono/api"), 2, 50.0), (Path::new("mono/web"), 2, 20.0)]);
+    }
+}
@@ -0,0 +1,403 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use crate::llms_txt::{
+    importance, is_entry_point, language_counts, project_name, purpose, summary, top_level_directories, DATA_LANGUAGES,
+};
+use crate::manifests::requirements;
+use crate::{extract_vocabulary, import_counts, FileEntry, FileSystem, OutputFormatter, ScanResult, VocabularyTerm};
+
+const ENTRY_POINTS: usize = 3;
+const DIRECTORIES: usize = 5;
+const DEPENDENCIES: usize = 8;
+const MOST_IMPORTED: usize = 3;
+const OTHER_LANGUAGES: usize = 3;
+const VOCABULARY: usize = 20;
+// Terms named in the prose, of the vocabulary
+const PROSE_TERMS: usize = 10;
+// Manifests deeper than this are fixtures or vendored code, not the project's own
+const MAX_MANIFEST_DEPTH: usize = 3;
+
+// Dependency names that identify a framework worth naming
+const FRAMEWORKS: &[(&str, &str)] = &[
+    ("react", "React"), ("next", "Next.js"), ("vue", "Vue"), ("nuxt", "Nuxt"), ("@angular/core", "Angular"),
+    ("svelte", "Svelte"), ("express", "Express"), ("fastify", "Fastify"), ("@nestjs/core", "NestJS"),
+    ("electron", "Electron"), ("django", "Django"), ("flask", "Flask"), ("fastapi", "FastAPI"),
+    ("torch", "PyTorch"), ("tensorflow", "TensorFlow"), ("axum", "Axum"), ("actix-web", "Actix Web"),
+    ("rocket", "Rocket"), ("tokio", "Tokio"), ("clap", "clap"), ("wasm-bindgen", "wasm-bindgen"), ("bevy", "Bevy"),
+    ("tauri", "Tauri"), ("github.com/gin-gonic/gin", "Gin"), ("github.com/labstack/echo/v4", "Echo"),
+    ("github.com/gofiber/fiber/v2", "Fiber"), ("rails", "Rails"), ("sinatra", "Sinatra"),
+    ("laravel/framework", "Laravel"), ("symfony/framework-bundle", "Symfony"),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguageCount {
+    pub language: String,
+    pub files: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DirectoryOverview {
+    pub path: PathBuf,
+    pub files: usize,
+    // Most common purpose and language among its files
+    pub purpose: Option<String>,
+    pub language: Option<String>,
+}
+
+// The facts behind a prose description of a project, gathered from a scan and
+// the package manifests it found. Purposes, line counts, complexity and imports
+// come from enhanced analysis and are missing without it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectOverview {
+    pub name: String,
+    // Summary of the root README
+    pub description: Option<String>,
+    // From the shallowest package manifests, e.g. "Rust workspace", "Node.js package"
+    pub project_types: Vec<String>,
+    pub languages: Vec<LanguageCount>,
+    pub frameworks: Vec<String>,
+    pub entry_points: Vec<PathBuf>,
+    // Top-level directories, most files first
+    pub directories: Vec<DirectoryOverview>,
+    // External dependencies declared in manifests, most widely declared first
+    pub dependencies: Vec<String>,
+    // Files imported by the most other files
+    pub most_imported: Vec<PathBuf>,
+    // Domain terms of identifiers, comments and docs, most widespread first
+    #[serde(default)]
+    pub vocabulary: Vec<VocabularyTerm>,
+    pub files: usize,
+    pub dirs: usize,
+    pub total_size: u64,
+    pub total_lines: Option<usize>,
+    pub test_files: usize,
+    pub average_complexity: Option<f64>,
+}
+
+impl ProjectOverview {
+    // Manifests are read through `fs`, the same source the scan used
+    pub fn from_result(result: &ScanResult, fs: &dyn FileSystem) -> Self {
+        let files: Vec<&FileEntry> = result.files.iter().filter(|file| !file.is_dir).collect();
+        let relative = |file: &FileEntry| file.path.strip_prefix(&result.root_path).unwrap_or(&file.path).to_path_buf();
+
+        let mut by_importance = files.clone();
+        by_importance.sort_by(|a, b| importance(b).total_cmp(&importance(a)).then_with(|| a.path.cmp(&b.path)));
+        let entry_points = by_importance.iter()
+            .filter(|file| is_entry_point(file))
+            .take(ENTRY_POINTS)
+            .map(|file| relative(file))
+            .collect();
+
+        let mut directories: Vec<DirectoryOverview> = top_level_directories(result, &files).into_iter()
+            .map(|(dir, stats)| DirectoryOverview {
+                path: PathBuf::from(dir),
+                files: stats.files,
+                purpose: stats.purpose(),
+                language: stats.language(),
+            })
+            .collect();
+        directories.sort_by(|a, b| b.files.cmp(&a.files).then_with(|| a.path.cmp(&b.path)));
+        directories.truncate(DIRECTORIES);
+
+        let manifests = Manifests::read(result, &files, fs);
+        let mut frameworks: Vec<String> = manifests.dependencies.iter()
+            .filter_map(|dependency| FRAMEWORKS.iter().find(|(name, _)| name == dependency).map(|(_, framework)| framework.to_string()))
+            .chain(files.iter().filter_map(|file| file.enhanced_info.as_ref()?.framework.clone()))
+            .collect();
+        let mut seen = Vec::new();
+        frameworks.retain(|framework| {
+            let new = !seen.contains(framework);
+            seen.push(framework.clone());
+            new
+        });
+
+        let mut imported: Vec<(PathBuf, usize)> = import_counts(result).into_iter().collect();
+        imported.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let infos: Vec<_> = files.iter().filter_map(|file| file.enhanced_info.as_ref()).collect();
+        let complexities: Vec<f64> = infos.iter().filter_map(|info| info.complexity_score).collect();
+        ProjectOverview {
+            name: project_name(result),
+            description: files.iter()
+                .filter(|file| file.name.to_lowercase().starts_with("readme") && file.path.parent() == Some(result.root_path.as_path()))
+                .find_map(|file| summary(file))
+                .map(|summary| summary.trim_end_matches('.').to_string()),
+            project_types: manifests.project_types,
+            languages: language_counts(&files).into_iter().map(|(language, files)| LanguageCount { language, files }).collect(),
+            frameworks,
+            entry_points,
+            directories,
+            dependencies: manifests.dependencies.into_iter().take(DEPENDENCIES).collect(),
+            most_imported: imported.into_iter().take(MOST_IMPORTED).map(|(path, _)| path).collect(),
+            vocabulary: extract_vocabulary(result, fs, VOCABULARY),
+            files: files.len(),
+            dirs: result.files.iter().filter(|file| file.is_dir && file.path != result.root_path).count(),
+            total_size: files.iter().map(|file| file.size).sum(),
+            total_lines: (!infos.is_empty()).then(|| infos.iter().filter_map(|info| info.line_count).sum()),
+            test_files: files.iter().filter(|file| purpose(file) == Some("Test code")).count(),
+            average_complexity: (!complexities.is_empty()).then(|| complexities.iter().sum::<f64>() / complexities.len() as f64),
+        }
+    }
+
+    // A few paragraphs of plain prose: what the project is, how it is laid
+    // out, what it depends on and how big it is. The same scan always yields
+    // the same text.
+    pub fn to_prose(&self) -> String {
+        let mut paragraphs = Vec::new();
+
+        let mut intro = String::new();
+        let kind = match self.project_types.as_slice() {
+            [] => "project".to_string(),
+            types => join(types),
+        };
+        let source: Vec<&LanguageCount> = self.languages.iter()
+            .filter(|language| !DATA_LANGUAGES.contains(&language.language.as_str()))
+            .collect();
+        let _ = write!(intro, "{} is {} {}", self.name, article(&kind), kind);
+        if let Some((main, others)) = source.split_first() {
+            let _ = write!(intro, " written mainly in {} ({})", capitalize(&main.language), plural(main.files, "file"));
+            if !others.is_empty() {
+                let others: Vec<String> = others.iter().take(OTHER_LANGUAGES).map(|language| capitalize(&language.language)).collect();
+                let _ = write!(intro, ", with some {}", join(&others));
+            }
+        }
+        intro.push('.');
+        if let Some(description) = &self.description {
+            let _ = write!(intro, " Its README describes it as \"{}\".", description);
+        }
+        if !self.frameworks.is_empty() {
+            let _ = write!(intro, " It is built with {}.", join(&self.frameworks));
+        }
+        paragraphs.push(intro);
+
+        let mut layout = Vec::new();
+        if !self.directories.is_empty() {
+            let directories: Vec<String> = self.directories.iter()
+                .map(|dir| {
+                    let mut text = format!("{}/ ({}", dir.path.display(), plural(dir.files, "file"));
+                    if let Some(purpose) = &dir.purpose {
+                        let _ = write!(text, ", mostly {}", purpose.to_lowercase());
+                    }
+                    text.push(')');
+                    text
+                })
+                .collect();
+            layout.push(format!("The main top-level directories are {}.", join(&directories)));
+        }
+        if !self.entry_points.is_empty() {
+            let entry_points: Vec<String> = self.entry_points.iter().map(|path| path.display().to_string()).collect();
+            layout.push(format!("Execution starts in {}.", join(&entry_points)));
+        }
+        if self.test_files > 0 {
+            let verb = if self.test_files == 1 { "is a test" } else { "are tests" };
+            layout.push(format!("{} of the {} {}.", self.test_files, plural(self.files, "file"), verb));
+        }
+        if !layout.is_empty() {
+            paragraphs.push(layout.join(" "));
+        }
+
+        let mut dependencies = Vec::new();
+        if !self.dependencies.is_empty() {
+            dependencies.push(format!("Its most widely declared dependencies are {}.", join(&self.dependencies)));
+        }
+        if !self.most_imported.is_empty() {
+            let imported: Vec<String> = self.most_imported.iter().map(|path| path.display().to_string()).collect();
+            dependencies.push(format!("Within the codebase, the most imported {} {}.",
+                if imported.len() == 1 { "module is" } else { "modules are" }, join(&imported)));
+        }
+        if !dependencies.is_empty() {
+            paragraphs.push(dependencies.join(" "));
+        }
+
+        if !self.vocabulary.is_empty() {
+            let terms: Vec<&str> = self.vocabulary.iter().take(PROSE_TERMS).map(|term| term.term.as_str()).collect();
+            paragraphs.push(format!("Its code and documentation keep coming back to {}.", join(&terms)));
+        }
+
+        let mut stats = format!("In total the scan covers {} ({}", plural(self.files, "file"), OutputFormatter::format_size(self.total_size));
+        if let Some(lines) = self.total_lines {
+            let _ = write!(stats, ", {}", plural(lines, "line"));
+        }
+        let _ = write!(stats, ") in {}", plural(self.dirs, "directory"));
+        if let Some(complexity) = self.average_complexity {
+            let _ = write!(stats, ", with an average complexity of {:.1} per file", complexity);
+        }
+        stats.push('.');
+        paragraphs.push(stats);
+
+        paragraphs.join("\n\n") + "\n"
+    }
+}
+
+// What the package manifests in a scan say about the project
+#[derive(Default)]
+struct Manifests {
+    project_types: Vec<String>,
+    dependencies: Vec<String>,
+}
+
+impl Manifests {
+    fn read(result: &ScanResult, files: &[&FileEntry], fs: &dyn FileSystem) -> Self {
+        let depth = |file: &FileEntry| file.path.strip_prefix(&result.root_path).map_or(0, |path| path.components().count());
+        let manifests: Vec<(&FileEntry, String)> = files.iter()
+            .filter(|file| depth(file) <= MAX_MANIFEST_DEPTH && manifest_type(&file.name, "").is_some())
+            .filter_map(|file| Some((*file, fs.read_to_string(&file.path).ok()?)))
+            .collect();
+
+        // The shallowest manifests say what the project is; nested ones are its parts
+        let shallowest = manifests.iter().map(|(file, _)| depth(file)).min().unwrap_or(0);
+        let mut manifests_found = Self::default();
+        for (file, content) in &manifests {
+            if depth(file) == shallowest {
+                if let Some(kind) = manifest_type(&file.name, content) {
+                    if !manifests_found.project_types.iter().any(|known| known == kind) {
+                        manifests_found.project_types.push(kind.to_string());
+                    }
+                }
+            }
+        }
+
+        // Declared by the most manifests first, then in the order first seen
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for (file, content) in &manifests {
+            let mut declared = dependencies(&file.name, content);
+            declared.sort_unstable();
+            declared.dedup();
+            for name in declared {
+                let position = *index.entry(name.clone()).or_insert_with(|| {
+                    counts.push((name, 0));
+                    counts.len() - 1
+                });
+                counts[position].1 += 1;
+            }
+        }
+        let mut order: Vec<usize> = (0..counts.len()).collect();
+        order.sort_by(|a, b| counts[*b].1.cmp(&counts[*a].1).then_with(|| a.cmp(b)));
+        manifests_found.dependencies = order.into_iter().map(|position| counts[position].0.clone()).collect();
+        manifests_found
+    }
+}
+
+fn manifest_type(name: &str, content: &str) -> Option<&'static str> {
+    Some(match name {
+        "Cargo.toml" if content.contains("[workspace]") => "Rust workspace",
+        "Cargo.toml" => "Rust crate",
+        "package.json" => "Node.js package",
+        "pyproject.toml" | "setup.py" | "requirements.txt" => "Python project",
+        "go.mod" => "Go module",
+        "pom.xml" => "Maven project",
+        "build.gradle" | "build.gradle.kts" => "Gradle project",
+        "Gemfile" => "Ruby project",
+        "composer.json" => "PHP project",
+        _ => return None,
+    })
+}
+
+// External dependency names declared by one manifest; local path dependencies
+// are parts of the project and left out
+fn dependencies(name: &str, content: &str) -> Vec<String> {
+    requirements(name, content).into_iter().map(|(name, _)| name).collect()
+}
+
+fn join<S: AsRef<str>>(items: &[S]) -> String {
+    match items {
+        [] => String::new(),
+        [only] => only.as_ref().to_string(),
+        [rest @ .., last] => format!("{} and {}", rest.iter().map(AsRef::as_ref).collect::<Vec<_>>().join(", "), last.as_ref()),
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match (count, noun.strip_suffix('y')) {
+        (1, _) => format!("1 {}", noun),
+        (_, Some(stem)) => format!("{} {}ies", count, stem),
+        _ => format!("{} {}s", count, noun),
+    }
+}
+
+fn article(phrase: &str) -> &'static str {
+    if phrase.starts_with(['a', 'e', 'i', 'o', 'u', 'A', 'E', 'I', 'O', 'U']) {
+        "an"
+    } else {
+        "a"
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+    use std::path::Path;
+
+    fn scan(fs: &MemoryFileSystem) -> ScanResult {
+        let options = ScanOptions { enhanced_analysis: true, ..Default::default() };
+        DirectoryScanner::new(options).scan_with(fs, "shop").unwrap()
+    }
+
+    fn project() -> MemoryFileSystem {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("shop/README.md", "# Shop: a tiny storefront service\n");
+        fs.insert("shop/Cargo.toml", "[workspace]\nmembers = [\"server\"]\n\n[workspace.dependencies]\nserde = \"1\"\n");
+        fs.insert("shop/server/Cargo.toml", "[package]\nname = \"server\"\n\n[dependencies]\naxum = \"0.7\"\nserde.workspace = true\nshop-core = { path = \"../core\" }\n\n[dev-dependencies]\ntempfile = \"3\"\n");
+        fs.insert("shop/server/src/main.rs", "// Starts the storefront HTTP server\nuse crate::cart;\nfn main() {\n    serve();\n}\n");
+        fs.insert("shop/server/src/cart.rs", "// Cart logic\npub fn total(items: &[u32]) -> u32 {\n    items.iter().sum()\n}\n");
+        fs.insert("shop/server/tests/cart_test.rs", "#[test]\nfn adds() {}\n");
+        fs
+    }
+
+    #[test]
+    fn test_gathers_type_frameworks_and_dependencies() {
+        let fs = project();
+        let overview = ProjectOverview::from_result(&scan(&fs), &fs);
+
+        assert_eq!(overview.name, "shop");
+        assert_eq!(overview.description.as_deref(), Some("Shop: a tiny storefront service"));
+        assert_eq!(overview.project_types, ["Rust workspace"]);
+        assert_eq!(overview.frameworks, ["Axum"]);
+        // Declared in both manifests, so first; path and dev dependencies are left out
+        assert_eq!(overview.dependencies, ["serde", "axum"]);
+        assert_eq!(overview.entry_points, [Path::new("server/src/main.rs")]);
+        assert_eq!(overview.directories[0].path, Path::new("server"));
+        assert_eq!(overview.files, 6);
+        assert_eq!(overview.test_files, 1);
+        assert_eq!(overview.vocabulary[0].term, "storefront");
+    }
+
+    #[test]
+    fn test_prose_is_deterministic() {
+        let fs = project();
+        let prose = ProjectOverview::from_result(&scan(&fs), &fs).to_prose();
+
+        assert!(prose.starts_with("shop is a Rust workspace written mainly in Rust (3 files). Its README describes it as \"Shop: a tiny storefront service\". It is built with Axum.\n\n"), "{}", prose);
+        assert!(prose.contains("Execution starts in server/src/main.rs."), "{}", prose);
+        assert!(prose.contains("1 of the 6 files is a test."), "{}", prose);
+        assert!(prose.contains("Its most widely declared dependencies are serde and axum."), "{}", prose);
+        assert!(prose.contains("Its code and documentation keep coming back to storefront, "), "{}", prose);
+        assert!(prose.contains("in 3 directories, with an average complexity of"), "{}", prose);
+        assert_eq!(prose, ProjectOverview::from_result(&scan(&fs), &fs).to_prose());
+    }
+
+    #[test]
+    fn test_reads_other_manifests() {
+        assert_eq!(dependencies("package.json", r#"{"dependencies": {"react": "^18"}, "devDependencies": {"jest": "29"}}"#), ["react"]);
+        assert_eq!(dependencies("requirements.txt", "# web\nDjango>=4.2\nrequests[socks]==2.31\n-r base.txt\n"), ["django", "requests"]);
+        assert_eq!(
+            dependencies("pyproject.toml", "[project]\nname = \"x\"\ndependencies = [\n  \"fastapi>=0.100\",\n  \"pydantic\",\n]\n\n[tool.poetry.dependencies]\npython = \"^3.11\"\nflask = \"^3\"\n"),
+            ["fastapi", "pydantic", "flask"]
+        );
+        assert_eq!(
+            dependencies("go.mod", "module x\n\nrequire (\n\tgithub.com/gin-gonic/gin v1.9.1\n\tgolang.org/x/sys v0.1.0 // indirect\n)\n"),
+            ["github.com/gin-gonic/gin"]
+        );
+        assert_eq!(dependencies("Gemfile", "source 'https://rubygems.org'\ngem 'rails', '~> 7.1'\n"), ["rails"]);
+    }
+}
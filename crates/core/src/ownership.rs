@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::dir_stats::top_level;
+use crate::ScanResult;
+
+// Authors listed per directory, most commits first
+const TOP_AUTHORS: usize = 3;
+
+// One commit touching a file, as `git_file_history` reads it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileCommit {
+    pub commit: String,
+    pub author: String,
+    pub time: SystemTime,
+    // First line of the commit message
+    pub subject: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthorShare {
+    pub author: String,
+    pub commits: usize,
+    // Fraction of the directory's commits, from 0 to 1
+    pub share: f64,
+}
+
+// Who has worked on a top-level directory. Commits are counted once however
+// many of the directory's files they touch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DirectoryOwnership {
+    pub directory: String,
+    // Scanned files with any history
+    pub files: usize,
+    pub commits: usize,
+    pub contributors: usize,
+    pub top_authors: Vec<AuthorShare>,
+    // The fewest authors who together made more than half the commits; 1
+    // means one person holds most of the knowledge
+    pub bus_factor: usize,
+    #[serde(default, with = "crate::timestamp::optional")]
+    pub last_touched: Option<SystemTime>,
+}
+
+impl DirectoryOwnership {
+    pub fn top_author_share(&self) -> f64 {
+        self.top_authors.first().map_or(0.0, |author| author.share)
+    }
+
+    pub fn is_silo(&self) -> bool {
+        self.bus_factor == 1
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OwnershipReport {
+    pub directories: Vec<DirectoryOwnership>,
+}
+
+impl OwnershipReport {
+    // History is keyed by paths relative to the scan root, as
+    // `git_file_history` returns them. Only files in the scan are counted, so
+    // deleted and ignored files do not skew the numbers.
+    pub fn compute(result: &ScanResult, history: &HashMap<PathBuf, Vec<FileCommit>>) -> Self {
+        #[derive(Default)]
+        struct Tally<'a> {
+            files: usize,
+            commits: BTreeMap<&'a str, (&'a str, SystemTime)>,
+        }
+
+        let mut tallies: BTreeMap<String, Tally> = BTreeMap::new();
+        for file in result.files.iter().filter(|file| !file.is_dir) {
+            let relative = file.path.strip_prefix(&result.root_path).unwrap_or(&file.path);
+            let Some(commits) = history.get(relative).filter(|commits| !commits.is_empty()) else { continue };
+            let tally = tallies.entry(top_level(&result.root_path, &file.path)).or_default();
+            tally.files += 1;
+            for commit in commits {
+                tally.commits.insert(&commit.commit, (&commit.author, commit.time));
+            }
+        }
+
+        let directories = tallies
+            .into_iter()
+            .map(|(directory, tally)| {
+                let mut by_author: HashMap<&str, usize> = HashMap::new();
+                for (author, _) in tally.commits.values() {
+                    *by_author.entry(author).or_insert(0) += 1;
+                }
+                let mut authors: Vec<(&str, usize)> = by_author.into_iter().collect();
+                authors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+                let commits = tally.commits.len();
+                let mut covered = 0;
+                let bus_factor = authors.iter().take_while(|(_, count)| {
+                    let short = covered * 2 <= commits;
+                    covered += count;
+                    short
+                }).count();
+                let top_authors = authors.iter().take(TOP_AUTHORS).map(|(author, count)| AuthorShare {
+                    author: author.to_string(),
+                    commits: *count,
+                    share: *count as f64 / commits as f64,
+                }).collect();
+                let last_touched = tally.commits.values().map(|(_, time)| *time).max();
+                DirectoryOwnership { directory, files: tally.files, commits, contributors: authors.len(), top_authors, bus_factor, last_touched }
+            })
+            .collect();
+        OwnershipReport { directories }
+    }
+
+    // Directories where one author made most of the commits
+    pub fn silos(&self) -> impl Iterator<Item = &DirectoryOwnership> {
+        self.directories.iter().filter(|directory| directory.is_silo())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn commit(commit: &str, author: &str, day: u64) -> FileCommit {
+        FileCommit {
+            commit: commit.to_string(),
+            author: author.to_string(),
+            time: UNIX_EPOCH + Duration::from_secs(day * 86_400),
+            subject: String::new(),
+        }
+    }
+
+    fn scan() -> ScanResult {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/api/routes.rs", "");
+        fs.insert("app/api/auth.rs", "");
+        fs.insert("app/core/models.rs", "");
+        fs.insert("app/README.md", "");
+        DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "app").unwrap()
+    }
+
+    fn directory<'a>(report: &'a OwnershipReport, name: &str) -> &'a DirectoryOwnership {
+        report.directories.iter().find(|directory| directory.directory == name).unwrap()
+    }
+
+    #[test]
+    fn test_counts_contributors_and_commits_once_per_directory() {
+        let history = HashMap::from([
+            (PathBuf::from("api/routes.rs"), vec![commit("a1", "ana", 1), commit("b1", "bo", 3)]),
+            // One commit touching both files counts once
+            (PathBuf::from("api/auth.rs"), vec![commit("a1", "ana", 1), commit("c1", "cy", 2)]),
+            (PathBuf::from("README.md"), vec![commit("a2", "ana", 5)]),
+        ]);
+        let report = OwnershipReport::compute(&scan(), &history);
+
+        let api = directory(&report, "api");
+        assert_eq!((api.files, api.commits, api.contributors), (2, 3, 3));
+        assert_eq!(api.bus_factor, 2);
+        assert_eq!(api.last_touched, Some(UNIX_EPOCH + Duration::from_secs(3 * 86_400)));
+        assert_eq!(directory(&report, ".").commits, 1);
+        // Files without history leave their directory out
+        assert!(report.directories.iter().all(|directory| directory.directory != "core"));
+    }
+
+    #[test]
+    fn test_single_dominant_author_is_a_silo() {
+        let history = HashMap::from([
+            (PathBuf::from("core/models.rs"), vec![commit("1", "ana", 1), commit("2", "ana", 2), commit("3", "ana", 3), commit("4", "bo", 4)]),
+            (PathBuf::from("api/routes.rs"), vec![commit("5", "ana", 1), commit("6", "bo", 2)]),
+        ]);
+        let report = OwnershipReport::compute(&scan(), &history);
+
+        let core = directory(&report, "core");
+        assert_eq!(core.top_authors[0].author, "ana");
+        assert_eq!(core.top_author_share(), 0.75);
+        let silos: Vec<&str> = report.silos().map(|directory| directory.directory.as_str()).collect();
+        // An even split needs both authors for more than half
+        assert_eq!(silos, ["core"]);
+    }
+
+    #[test]
+    fn test_serializes_last_touched_as_rfc3339() {
+        let history = HashMap::from([(PathBuf::from("api/auth.rs"), vec![commit("1", "ana", 0)])]);
+        let report = OwnershipReport::compute(&scan(), &history);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["directories"][0]["last_touched"], "1970-01-01T00:00:00.000Z");
+        assert_eq!(serde_json::from_value::<OwnershipReport>(json).unwrap(), report);
+    }
+}
@@ -0,0 +1,220 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+use crate::expression::glob_match;
+use crate::ScanResult;
+
+// How output paths are written. The library keeps paths as scanned by default;
+// the CLI defaults to `Relative` so results are portable between machines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PathMode {
+    // Relative to the scan root with `/` separators; the root itself is "."
+    Relative,
+    // Absolute with `/` separators, resolved against the working directory
+    Absolute,
+    // Exactly as the walk produced them, i.e. the scan path joined with the entry
+    #[default]
+    AsScanned,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathStyle {
+    pub mode: PathMode,
+    // Prepended to relative paths, e.g. a repository name or URL base
+    pub prefix: Option<String>,
+}
+
+impl PathStyle {
+    pub fn relative() -> Self {
+        Self { mode: PathMode::Relative, prefix: None }
+    }
+
+    pub fn render(&self, path: &Path, root: &Path) -> String {
+        match self.mode {
+            PathMode::AsScanned => path.display().to_string(),
+            PathMode::Absolute => slashed(&std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())),
+            PathMode::Relative => {
+                let relative = path.strip_prefix(root).map(slashed).unwrap_or_else(|_| slashed(path));
+                match self.prefix.as_deref().map(|prefix| prefix.trim_end_matches('/')) {
+                    Some(prefix) if !prefix.is_empty() && relative.is_empty() => prefix.to_string(),
+                    Some(prefix) if !prefix.is_empty() => format!("{}/{}", prefix, relative),
+                    _ if relative.is_empty() => ".".to_string(),
+                    _ => relative,
+                }
+            }
+        }
+    }
+}
+
+// Windows paths as text with `/` separators. Verbatim prefixes, which
+// `canonicalize` returns and long paths need, are dropped: `\\?\C:\src` becomes
+// `C:/src` and `\\?\UNC\host\share` becomes `//host/share`.
+pub fn portable_path(path: &Path) -> String {
+    let text = path.to_string_lossy();
+    let text = match text.strip_prefix(r"\\?\UNC\") {
+        Some(share) => format!(r"\\{}", share),
+        None => match text.strip_prefix(r"\\?\") {
+            Some(rest) if rest.as_bytes().get(1) == Some(&b':') => rest.to_string(),
+            _ => text.to_string(),
+        },
+    };
+    text.replace('\\', "/")
+}
+
+// Glob match of a configured pattern: without a '/' against the file name,
+// with one against the end of the path, so `proto/*.gen` applies under any
+// `proto` directory
+pub(crate) fn glob_matches_file(pattern: &str, path: &Path) -> bool {
+    if pattern.contains('/') {
+        glob_match(&format!("**/{}", pattern.trim_start_matches("./")), &portable_path(path))
+    } else {
+        let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        glob_match(pattern, &name)
+    }
+}
+
+// Substring match of an ignore pattern against a path. Either may use `\`;
+// on Windows, where `Node_Modules` and `node_modules` are the same directory,
+// case is ignored too.
+pub(crate) fn contains_pattern(path: &Path, pattern: &str) -> bool {
+    pattern_in(&portable_path(path), &pattern.replace('\\', "/"), cfg!(windows))
+}
+
+fn pattern_in(path: &str, pattern: &str, ignore_case: bool) -> bool {
+    if ignore_case {
+        path.to_lowercase().contains(&pattern.to_lowercase())
+    } else {
+        path.contains(pattern)
+    }
+}
+
+// Joins the meaningful components with `/`, dropping `.` so "./src" and "src" agree
+fn slashed(path: &Path) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut absolute = false;
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => parts.push(portable_path(Path::new(prefix.as_os_str()))),
+            Component::RootDir => absolute = true,
+            Component::CurDir => {}
+            Component::ParentDir => parts.push("..".to_string()),
+            Component::Normal(name) => parts.push(name.to_string_lossy().to_string()),
+        }
+    }
+    let joined = parts.join("/");
+    match path.components().next() {
+        // A drive or share root keeps its trailing slash: `C:/`, not the relative `C:`
+        Some(Component::Prefix(_)) if absolute && parts.len() == 1 => format!("{}/", joined),
+        Some(Component::Prefix(_)) => joined,
+        _ if absolute => format!("/{}", joined),
+        _ => joined,
+    }
+}
+
+impl ScanResult {
+    // A copy for serialized output with entry and issue paths rewritten. The root
+    // stays as scanned (absolute in `Absolute` mode) so consumers can rejoin them.
+    pub fn with_path_style(&self, style: &PathStyle) -> ScanResult {
+        let mut result = self.clone();
+        let rewrite = |path: &Path| PathBuf::from(style.render(path, &self.root_path));
+        for file in &mut result.files {
+            file.path = rewrite(&file.path);
+        }
+        for issue in &mut result.errors {
+            issue.path = issue.path.as_deref().map(rewrite);
+        }
+        for finding in &mut result.findings {
+            finding.path = rewrite(&finding.path);
+        }
+        for entry in &mut result.entry_points {
+            entry.path = rewrite(&entry.path);
+            entry.declared_in = entry.declared_in.as_deref().map(rewrite);
+        }
+        for endpoint in &mut result.endpoints {
+            endpoint.path = rewrite(&endpoint.path);
+        }
+        if let Some(schema) = &mut result.schema {
+            for table in &mut schema.tables {
+                table.path = rewrite(&table.path);
+            }
+            for migration in &mut schema.migrations {
+                migration.path = rewrite(&migration.path);
+            }
+        }
+        for artifact in &mut result.containers {
+            artifact.path = rewrite(&artifact.path);
+        }
+        for root in &mut result.roots {
+            root.path = rewrite(&root.path);
+        }
+        if style.mode == PathMode::Absolute {
+            result.root_path = rewrite(&self.root_path);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_paths_use_forward_slashes() {
+        let style = PathStyle::relative();
+        let root = Path::new("./proj");
+
+        assert_eq!(style.render(Path::new("./proj/src/main.rs"), root), "src/main.rs");
+        assert_eq!(style.render(Path::new("./proj"), root), ".");
+        assert_eq!(style.render(Path::new("elsewhere/./a.rs"), root), "elsewhere/a.rs");
+    }
+
+    #[test]
+    fn test_prefix_is_prepended_once() {
+        let style = PathStyle { mode: PathMode::Relative, prefix: Some("github.com/org/repo/".to_string()) };
+        let root = Path::new("proj");
+
+        assert_eq!(style.render(Path::new("proj/src/lib.rs"), root), "github.com/org/repo/src/lib.rs");
+        assert_eq!(style.render(Path::new("proj"), root), "github.com/org/repo");
+        assert_eq!(PathStyle::default().render(Path::new("proj/src/lib.rs"), root), "proj/src/lib.rs");
+    }
+
+    #[test]
+    fn test_absolute_paths() {
+        let style = PathStyle { mode: PathMode::Absolute, prefix: None };
+        let rendered = style.render(Path::new("proj/src/lib.rs"), Path::new("proj"));
+
+        assert!(Path::new(&rendered).is_absolute());
+        assert!(rendered.ends_with("/proj/src/lib.rs"));
+        assert!(!rendered.contains('\\'));
+    }
+
+    #[test]
+    fn test_portable_path_drops_verbatim_prefixes() {
+        assert_eq!(portable_path(Path::new(r"\\?\C:\proj\src\lib.rs")), "C:/proj/src/lib.rs");
+        assert_eq!(portable_path(Path::new(r"\\?\UNC\host\share\proj")), "//host/share/proj");
+        assert_eq!(portable_path(Path::new(r"\\host\share\proj")), "//host/share/proj");
+        // Device paths are not filesystem paths and keep their prefix
+        assert_eq!(portable_path(Path::new(r"\\?\Volume{1}\proj")), "//?/Volume{1}/proj");
+        assert_eq!(portable_path(Path::new("proj/src")), "proj/src");
+    }
+
+    #[test]
+    fn test_ignore_patterns_match_either_separator() {
+        assert!(contains_pattern(Path::new(r"C:\proj\target\debug\app.exe"), "target/debug"));
+        assert!(contains_pattern(Path::new("proj/target/debug/app"), r"target\debug"));
+        assert!(pattern_in("c:/proj/Node_Modules/x.js", "node_modules", true));
+        assert!(!pattern_in("/proj/Node_Modules/x.js", "node_modules", false));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_roots_render_with_slashes() {
+        let style = PathStyle { mode: PathMode::Absolute, prefix: None };
+
+        assert_eq!(style.render(Path::new(r"\\?\C:\"), Path::new(r"C:\")), "C:/");
+        assert_eq!(style.render(Path::new(r"\\?\C:\proj\lib.rs"), Path::new(r"C:\proj")), "C:/proj/lib.rs");
+        assert_eq!(style.render(Path::new(r"\\server\share\lib.rs"), Path::new(r"\\server\share")), "//server/share/lib.rs");
+        assert_eq!(PathStyle::relative().render(Path::new(r"C:\proj\src\lib.rs"), Path::new(r"C:\proj")), "src/lib.rs");
+    }
+}
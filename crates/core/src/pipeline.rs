@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{ContentAnalyzer, EnhancedFileInfo, FileEntry};
+
+// Walked entries queued ahead of the scan per worker, so workers stay busy
+// while the scan thread classifies entries in walk order
+const LOOKAHEAD_PER_THREAD: usize = 8;
+
+struct Job {
+    id: u64,
+    entry: FileEntry,
+    content: Option<String>,
+}
+
+// None when the analysis panicked; the scan thread then analyzes the file itself
+type Done = (u64, Option<(EnhancedFileInfo, Duration)>);
+
+// Content analysis on worker threads, decoupled from the walk: the scan thread
+// reads each file and submits it, then collects the analyses in any order and
+// hands them back by id, so the result keeps walk order. Workers exit once the
+// pipeline is dropped.
+pub(crate) struct AnalysisPipeline {
+    jobs: SyncSender<Job>,
+    results: Receiver<Done>,
+    done: HashMap<u64, Option<(EnhancedFileInfo, Duration)>>,
+    next_id: u64,
+    capacity: usize,
+}
+
+impl AnalysisPipeline {
+    // None when `threads` asks for a single thread; 0 means one per CPU
+    pub(crate) fn start(analyzer: ContentAnalyzer, threads: usize) -> Option<Self> {
+        let threads = match threads {
+            0 => thread::available_parallelism().map_or(1, |count| count.get()),
+            threads => threads,
+        };
+        if threads < 2 || cfg!(target_arch = "wasm32") {
+            return None;
+        }
+
+        let capacity = threads * LOOKAHEAD_PER_THREAD;
+        let (jobs, queue) = mpsc::sync_channel::<Job>(capacity);
+        let (finished, results) = mpsc::channel::<Done>();
+        let queue = Arc::new(Mutex::new(queue));
+        for _ in 0..threads {
+            let (queue, finished, analyzer) = (queue.clone(), finished.clone(), analyzer.clone());
+            thread::spawn(move || work(&analyzer, &queue, &finished));
+        }
+        Some(Self { jobs, results, done: HashMap::new(), next_id: 0, capacity })
+    }
+
+    // Entries the scan may queue ahead of the one it is classifying
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // Queues a file for analysis; callers keep at most `capacity` outstanding,
+    // so this never waits on the workers
+    pub(crate) fn submit(&mut self, entry: FileEntry, content: Option<String>) -> Option<u64> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.jobs.send(Job { id, entry, content }).ok()?;
+        Some(id)
+    }
+
+    // Waits for the analysis submitted as `id`; None if it failed
+    pub(crate) fn take(&mut self, id: u64) -> Option<(EnhancedFileInfo, Duration)> {
+        while !self.done.contains_key(&id) {
+            let (done_id, analysis) = self.results.recv().ok()?;
+            self.done.insert(done_id, analysis);
+        }
+        self.done.remove(&id).flatten()
+    }
+}
+
+fn work(analyzer: &ContentAnalyzer, queue: &Mutex<Receiver<Job>>, finished: &Sender<Done>) {
+    loop {
+        let Ok(Ok(job)) = queue.lock().map(|queue| queue.recv()) else { return };
+        let start = Instant::now();
+        let analysis = panic::catch_unwind(AssertUnwindSafe(|| {
+            let language = analyzer.detect_language_for(&job.entry);
+            analyzer.analyze(&job.entry, job.content.as_deref(), language)
+        }));
+        if finished.send((job.id, analysis.ok().map(|info| (info, start.elapsed())))).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, Fixture, FixtureSpec, ScanOptions, ScanSession};
+    use std::path::Path;
+
+    fn options(threads: usize) -> ScanOptions {
+        ScanOptions { enhanced_analysis: true, analysis_threads: threads, ..Default::default() }
+    }
+
+    #[test]
+    fn test_parallel_scan_matches_serial_scan() {
+        let fixture = Fixture::generate(&FixtureSpec { files: 120, seed: 9, ..Default::default() }).unwrap();
+        let fs = fixture.to_memory(Path::new("app"));
+        let serial = DirectoryScanner::new(options(1)).scan_with(&fs, "app").unwrap();
+        let parallel = DirectoryScanner::new(options(4)).scan_with(&fs, "app").unwrap();
+
+        let summary = |result: &crate::ScanResult| -> Vec<_> {
+            result.files.iter().map(|file| (file.path.clone(), file.tags.clone(), serde_json::to_value(&file.enhanced_info).unwrap())).collect()
+        };
+        assert_eq!(summary(&serial), summary(&parallel));
+        assert_eq!(serial.errors, parallel.errors);
+    }
+
+    #[test]
+    fn test_takes_results_by_id_in_any_order() {
+        let mut pipeline = AnalysisPipeline::start(ContentAnalyzer::new(), 2).unwrap();
+        let entry = |name: &str| FileEntry::test(Path::new("app").join(name));
+        let first = pipeline.submit(entry("a.rs"), Some("fn a() {}\n".to_string())).unwrap();
+        let second = pipeline.submit(entry("b.py"), Some("def b():\n    pass\n".to_string())).unwrap();
+
+        let (b, _) = pipeline.take(second).unwrap();
+        let (a, _) = pipeline.take(first).unwrap();
+        assert_eq!((a.language.as_deref(), b.language.as_deref()), (Some("rust"), Some("python")));
+        assert_eq!(b.line_count, Some(2));
+    }
+
+    #[test]
+    fn test_session_cache_applies_to_parallel_scans() {
+        let fixture = Fixture::generate(&FixtureSpec { files: 30, ..Default::default() }).unwrap();
+        let fs = fixture.to_memory(Path::new("app"));
+        let session = ScanSession::new();
+        session.scanner(options(3)).unwrap().scan_with(&fs, "app").unwrap();
+
+        let cache = session.scanner(options(3)).unwrap().scan_with(&fs, "app").unwrap().stats.cache.unwrap();
+        assert_eq!((cache.hits, cache.misses), (30, 0));
+        assert!(AnalysisPipeline::start(ContentAnalyzer::new(), 1).is_none());
+    }
+}
@@ -0,0 +1,713 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use crate::{
+    assign_complexity_percentiles, CacheStats, content_hash, DirectoryScanner, FileEntry, FileSystem, FsEntry, NestedRepoMode,
+    IssueKind, NestedRepository, ScanError, ScanIssue, ScanResult, ScanMetrics, ScanStats, SecurityFinding, SymlinkInfo, SECRET_TAG,
+    TestCoverageStructure, PARTIAL_TAG,
+};
+use crate::pipeline::AnalysisPipeline;
+use crate::resources::UsageSnapshot;
+use crate::sampling::Sampler;
+use crate::throttle::ThrottledFileSystem;
+use crate::dir_stats::DirStatsBuilder;
+use crate::distribution::DistributionBuilder;
+use crate::containers::detect_containers;
+use crate::db_schema::detect_schema;
+use crate::endpoints::extract_endpoints;
+use crate::coupling::{assign_coupling, assign_directory_coupling};
+use crate::entry_points::detect_entry_points;
+use crate::{media, secrets, security, session};
+
+// Tags for files known to hold text, which are never sniffed for media
+const TEXT_TAGS: [&str; 4] = ["source", "documentation", "configuration", "script"];
+
+// Running totals reported while a scan walks the tree
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScanProgress {
+    pub files_scanned: usize,
+    pub dirs_scanned: usize,
+    pub bytes_scanned: u64,
+    pub issues: usize,
+    pub current_path: Option<PathBuf>,
+}
+
+impl ScanProgress {
+    pub fn entries_scanned(&self) -> usize {
+        self.files_scanned + self.dirs_scanned
+    }
+}
+
+// A walked entry, with the id of its analysis when that runs ahead on worker
+// threads, and whether sampling admitted it when that was decided ahead too
+type Walked = (Result<FsEntry, ScanIssue>, Option<u64>, Option<bool>);
+
+// A scan driven one entry at a time, so callers can report progress or yield
+// between entries (e.g. to a browser event loop) before calling `finish`
+pub struct ScanRun<'a> {
+    scanner: &'a DirectoryScanner,
+    fs: &'a dyn FileSystem,
+    // Reads go through this instead of `fs` when the options set an `io_throttle`
+    throttled: Option<ThrottledFileSystem<'a>>,
+    walker: Box<dyn Iterator<Item = Result<FsEntry, ScanIssue>> + 'a>,
+    root_path: PathBuf,
+    start_time: Instant,
+    files: Vec<FileEntry>,
+    errors: Vec<ScanIssue>,
+    findings: Vec<SecurityFinding>,
+    nested_repositories: Vec<NestedRepository>,
+    // Directories walked so far, to tell which one a permission error cut short
+    walked_dirs: HashSet<PathBuf>,
+    progress: ScanProgress,
+    // Kept as entries arrive, since streamed entries are gone by `finish`
+    dir_stats: DirStatsBuilder,
+    distribution: DistributionBuilder,
+    // Key for the session's analysis cache, when the scanner has one
+    analysis_config: Option<String>,
+    cache_stats: CacheStats,
+    // Content analysis running ahead on worker threads, and the walked entries
+    // waiting for it in walk order, with the ids of their analyses
+    pipeline: Option<AnalysisPipeline>,
+    lookahead: VecDeque<Walked>,
+    // Picks the files analyzed when the options ask for sampling
+    sampler: Option<Sampler>,
+    // Process counters at the start, when the options ask for resource usage
+    usage: Option<UsageSnapshot>,
+    // Set once entries have been handed out by `take_files`
+    streamed: bool,
+    // Entered around each step so walk and analyze spans nest under the scan
+    span: tracing::Span,
+}
+
+impl<'a> ScanRun<'a> {
+    pub(crate) fn new(scanner: &'a DirectoryScanner, fs: &'a dyn FileSystem, root_path: PathBuf) -> Self {
+        let span = tracing::info_span!("scan", root = %root_path.display(), profile = %scanner.options.mapper_profile);
+        Self {
+            throttled: scanner.options.io_throttle.map(|throttle| ThrottledFileSystem::new(fs, throttle)),
+            walker: span.in_scope(|| fs.walk(&root_path, &scanner.options)),
+            scanner,
+            fs,
+            root_path,
+            start_time: Instant::now(),
+            files: Vec::new(),
+            errors: Vec::new(),
+            findings: Vec::new(),
+            nested_repositories: Vec::new(),
+            walked_dirs: HashSet::new(),
+            progress: ScanProgress::default(),
+            dir_stats: DirStatsBuilder::default(),
+            distribution: DistributionBuilder::default(),
+            analysis_config: scanner.cache.as_ref().map(|_| session::analysis_config(&scanner.options)),
+            cache_stats: CacheStats::default(),
+            pipeline: scanner.mapper.parallel_analyzer()
+                .and_then(|analyzer| AnalysisPipeline::start(analyzer, scanner.options.analysis_threads)),
+            lookahead: VecDeque::new(),
+            sampler: scanner.options.sampling
+                .filter(|_| scanner.mapper.analyzes_content())
+                .map(Sampler::new),
+            usage: scanner.options.collect_resource_usage.then(UsageSnapshot::take),
+            streamed: false,
+            span,
+        }
+    }
+
+    pub fn progress(&self) -> &ScanProgress {
+        &self.progress
+    }
+
+    // Hands over the entries processed since the last call, so callers can write
+    // them out as they go instead of holding the whole tree. `finish` then returns
+    // no files, and skips related files, percentiles and test coverage, which
+    // need every file at once.
+    pub fn take_files(&mut self) -> Vec<FileEntry> {
+        self.streamed = true;
+        std::mem::take(&mut self.files)
+    }
+
+    // The next walked entry. When analysis runs ahead on worker threads, this
+    // keeps the lookahead full so workers stay busy.
+    fn next_entry(&mut self) -> Option<Walked> {
+        let Some(pipeline) = self.pipeline.as_mut() else {
+            return tracing::trace_span!("walk").in_scope(|| self.walker.next()).map(|entry| (entry, None, None));
+        };
+        let fs = self.throttled.as_ref().map_or(self.fs, |throttled| throttled as &dyn FileSystem);
+        let options = &self.scanner.options;
+        let cache = self.scanner.cache.as_deref().zip(self.analysis_config.as_deref());
+        while self.lookahead.len() < pipeline.capacity() {
+            let Some(walked) = tracing::trace_span!("walk").in_scope(|| self.walker.next()) else { break };
+            // Only files `step` will analyze, and not those the cache has
+            let eligible = walked.as_ref().ok().filter(|entry| {
+                let relative = entry.path.strip_prefix(&self.root_path).unwrap_or(&entry.path);
+                !entry.is_dir
+                    && options.is_included(relative, &entry.name)
+                    && options.filter_reason(entry.size, entry.modified).is_none()
+                    && !entry.symlink.as_ref().is_some_and(SymlinkInfo::is_broken)
+            });
+            let admitted = eligible.zip(self.sampler.as_mut()).map(|(entry, sampler)| {
+                sampler.admit(entry.path.strip_prefix(&self.root_path).unwrap_or(&entry.path))
+            });
+            let id = eligible
+                .filter(|_| admitted != Some(false))
+                .map(|entry| FileEntry {
+                    path: entry.path.clone(),
+                    name: entry.name.clone(),
+                    size: entry.size,
+                    modified: entry.modified,
+                    is_dir: false,
+                    tags: Vec::new(),
+                    metadata: None,
+                    enhanced_info: None,
+                    symlink: None,
+                    media: None,
+                    content_hash: None,
+                })
+                .filter(|entry| !cache.is_some_and(|(cache, config)| cache.contains(config, entry)))
+                .and_then(|entry| {
+                    let content = fs.read_to_string(&entry.path).ok();
+                    pipeline.submit(entry, content)
+                });
+            self.lookahead.push_back((walked, id, admitted));
+        }
+        self.lookahead.pop_front()
+    }
+
+    // Processes the next walked entry; returns false once the walk is exhausted
+    pub fn step(&mut self) -> bool {
+        let span = self.span.clone();
+        let _scan = span.enter();
+        let Some((entry_result, ahead, admitted)) = self.next_entry() else {
+            return false;
+        };
+
+        match entry_result {
+            Ok(entry) => {
+                // Waited for here, so skipped entries do not leave analyses behind
+                let ahead = ahead.and_then(|id| self.pipeline.as_mut()?.take(id));
+                let fs = self.throttled.as_ref().map_or(self.fs, |throttled| throttled as &dyn FileSystem);
+                let options = &self.scanner.options;
+                let relative = entry.path.strip_prefix(&self.root_path).unwrap_or(&entry.path);
+                if !entry.is_dir && !options.is_included(relative, &entry.name) {
+                    tracing::debug!(path = %entry.path.display(), "skipping file matching no include pattern");
+                    return true;
+                }
+                if !entry.is_dir {
+                    if let Some(reason) = options.filter_reason(entry.size, entry.modified) {
+                        tracing::debug!(path = %entry.path.display(), "skipping file: {}", reason);
+                        return true;
+                    }
+                }
+                if entry.symlink.as_ref().is_some_and(SymlinkInfo::is_broken) && !options.include_broken_symlinks {
+                    tracing::debug!(path = %entry.path.display(), "skipping broken symlink");
+                    return true;
+                }
+                // Asked here whether or not analysis runs ahead, so both pick the same files
+                let sampled = match (&mut self.sampler, admitted) {
+                    (_, Some(admitted)) => admitted,
+                    (Some(sampler), None) if !entry.is_dir => sampler.admit(relative),
+                    _ => true,
+                };
+                if options.nested_repos != NestedRepoMode::Scan {
+                    let inside = self.nested_repositories.iter_mut()
+                        .find(|repo| entry.path != repo.path && entry.path.starts_with(&repo.path));
+                    if let Some(repo) = inside {
+                        repo.absorb(&entry);
+                        if let Some(sampler) = self.sampler.as_mut().filter(|_| !entry.is_dir) {
+                            sampler.forget(relative);
+                        }
+                        return true;
+                    }
+                }
+                let nested = (entry.is_dir && entry.path != self.root_path)
+                    .then(|| NestedRepository::detect(&entry.path, fs, options.nested_repos))
+                    .flatten();
+                let metadata = options.collect_metadata.then(|| walk_metadata(&entry, relative));
+                self.findings.extend(security::audit(&entry, fs));
+
+                let mut file_entry = FileEntry {
+                    path: entry.path,
+                    name: entry.name,
+                    size: entry.size,
+                    modified: entry.modified,
+                    is_dir: entry.is_dir,
+                    tags: Vec::new(), // Will be filled by mapper
+                    metadata,
+                    enhanced_info: None, // Will be filled by enhanced mapper
+                    symlink: entry.symlink,
+                    media: None,
+                    content_hash: None,
+                };
+
+                if file_entry.is_dir {
+                    self.walked_dirs.insert(file_entry.path.clone());
+                    self.progress.dirs_scanned += 1;
+                } else {
+                    self.progress.files_scanned += 1;
+                    self.progress.bytes_scanned += file_entry.size;
+                }
+                if options.hash_contents && !file_entry.is_dir && !file_entry.symlink.as_ref().is_some_and(SymlinkInfo::is_broken) {
+                    match fs.read(&file_entry.path) {
+                        Ok(bytes) => file_entry.content_hash = Some(content_hash(&bytes)),
+                        Err(e) => self.errors.push(ScanIssue::from_scan_error(&file_entry.path, &ScanError::Io(e))),
+                    }
+                }
+
+                // Apply enhanced analysis if available
+                let mapper = &self.scanner.mapper;
+                if mapper.analyzes_content() && !sampled {
+                    tracing::trace!(path = %file_entry.path.display(), "leaving file out of the analysis sample");
+                } else if mapper.analyzes_content() {
+                    // Mappers leave analysis already present in place
+                    let cache = self.scanner.cache.as_deref().zip(self.analysis_config.as_deref());
+                    let cached = cache.and_then(|(cache, config)| cache.get(config, &file_entry));
+                    let hit = cached.is_some();
+                    // Directories are looked up too, but only files count
+                    let counted = cache.is_some() && !file_entry.is_dir;
+                    if let Some((info, duration)) = cached {
+                        file_entry.enhanced_info = Some(info);
+                        if counted {
+                            self.cache_stats.hits += 1;
+                            self.cache_stats.bytes_reused += file_entry.size;
+                            self.cache_stats.time_saved_ms += duration.as_secs_f64() * 1000.0;
+                        }
+                    } else if counted {
+                        self.cache_stats.misses += 1;
+                        self.cache_stats.bytes_reanalyzed += file_entry.size;
+                    }
+                    let analysis_start = Instant::now();
+                    let mut ahead_time = None;
+                    if let Some((info, duration)) = ahead.filter(|_| !hit) {
+                        file_entry.enhanced_info = Some(info);
+                        ahead_time = Some(duration);
+                    }
+                    let analyzed = tracing::debug_span!("analyze", path = %file_entry.path.display())
+                        .in_scope(|| mapper.analyze(&mut file_entry, fs));
+                    let analysis_time = ahead_time.unwrap_or_else(|| analysis_start.elapsed());
+                    if counted && !hit {
+                        self.cache_stats.analysis_ms += analysis_time.as_secs_f64() * 1000.0;
+                    }
+                    // Before caching, so secrets never reach the cache either
+                    if options.secrets.is_enabled() {
+                        file_entry.enhanced_info.iter_mut().for_each(secrets::redact_info);
+                    }
+                    if let Err(e) = analyzed {
+                        self.errors.push(ScanIssue::analysis_failed(&file_entry.path, &e));
+                    } else {
+                        // A hit keeps the time of the analysis it saved
+                        if let Some((cache, config)) = cache.filter(|_| !hit) {
+                            cache.insert(config, &file_entry, analysis_time);
+                        }
+                        if DirectoryScanner::content_skipped(&file_entry) {
+                            self.errors.extend(ScanIssue::for_unanalyzed_content(&file_entry.path, fs));
+                        }
+                    }
+                    if let Some(sampler) = self.sampler.as_mut().filter(|_| !file_entry.is_dir) {
+                        let relative = file_entry.path.strip_prefix(&self.root_path).unwrap_or(&file_entry.path);
+                        sampler.record(relative, file_entry.enhanced_info.as_ref());
+                    }
+                }
+
+                // Apply classification
+                file_entry.tags = mapper.classify(&file_entry);
+                if file_entry.symlink.is_some() {
+                    file_entry.tags.push("symlink".to_string());
+                }
+                if let Some(repo) = nested {
+                    tracing::debug!(path = %repo.path.display(), kind = %repo.kind, "found nested repository");
+                    file_entry.tags.push(repo.kind.tag().to_string());
+                    self.nested_repositories.push(repo);
+                }
+                if options.secrets.is_enabled() && !file_entry.is_dir && !file_entry.symlink.as_ref().is_some_and(SymlinkInfo::is_broken) {
+                    let found = fs.read_to_string(&file_entry.path).map(|content| secrets::find_secrets(&content)).unwrap_or_default();
+                    if !found.is_empty() {
+                        file_entry.tags.push(SECRET_TAG.to_string());
+                        self.findings.push(secrets::finding(&file_entry.path, &found));
+                    }
+                }
+                if options.sniff_media && !file_entry.is_dir && !file_entry.tags.iter().any(|tag| TEXT_TAGS.contains(&tag.as_str())) {
+                    if let Some(media) = media::sniff_file(&file_entry.path, fs) {
+                        file_entry.tags.retain(|tag| tag != "unclassified");
+                        file_entry.tags.push(media.kind.tag().to_string());
+                        file_entry.media = Some(media);
+                    }
+                }
+                tracing::trace!(path = %file_entry.path.display(), tags = ?file_entry.tags, "classified entry");
+                self.progress.current_path = Some(file_entry.path.clone());
+                self.dir_stats.add_file(&self.root_path, &file_entry);
+                self.distribution.add_file(&self.root_path, &file_entry);
+                self.files.push(file_entry);
+            }
+            Err(issue) => {
+                tracing::debug!(%issue, "walk issue");
+                if issue.kind == IssueKind::PermissionDenied {
+                    self.mark_partial(issue.path.as_deref());
+                }
+                self.progress.current_path = issue.path.clone();
+                self.errors.push(issue);
+            }
+        }
+
+        self.progress.issues = self.errors.len();
+        true
+    }
+
+    // Tags the directory a permission error left partly unread: the denied
+    // directory itself, or the parent of a denied entry. Directories already
+    // handed out by `take_files` keep their tags as they were.
+    fn mark_partial(&mut self, path: Option<&Path>) {
+        let Some(path) = path else { return };
+        let dir = if self.walked_dirs.contains(path) { Some(path) } else { path.parent() };
+        let Some(dir) = dir else { return };
+        self.dir_stats.add_partial(&self.root_path, dir);
+        let entry = self.files.iter_mut().rev().find(|file| file.is_dir && file.path == dir);
+        if let Some(entry) = entry.filter(|entry| !entry.tags.iter().any(|tag| tag == PARTIAL_TAG)) {
+            entry.tags.push(PARTIAL_TAG.to_string());
+        }
+    }
+
+    pub fn finish(mut self) -> ScanResult {
+        // Drain anything the caller did not step through
+        while self.step() {}
+        let span = self.span.clone();
+        let _scan = span.enter();
+
+        let mut files = self.files;
+        let mut errors = self.errors;
+        let mut findings = self.findings;
+        let mut nested_repositories = self.nested_repositories;
+        if self.scanner.options.sort_by_path {
+            files.sort_by(|a, b| a.path.cmp(&b.path));
+            errors.sort_by(|a, b| a.path.cmp(&b.path));
+            findings.sort_by(|a, b| a.path.cmp(&b.path));
+            nested_repositories.sort_by(|a, b| a.path.cmp(&b.path));
+        }
+        if self.scanner.mapper.analyzes_content() && !self.streamed {
+            DirectoryScanner::link_related_files(&mut files);
+            assign_coupling(&mut files);
+            if self.scanner.options.normalize_complexity {
+                assign_complexity_percentiles(&mut files);
+            }
+        }
+
+        let total_files = self.progress.entries_scanned();
+        let duration_ms = self.start_time.elapsed().as_millis() as u64;
+        let files_per_second = if duration_ms > 0 {
+            (total_files as f64) / (duration_ms as f64 / 1000.0)
+        } else {
+            0.0
+        };
+
+        let stats = ScanStats {
+            total_files,
+            total_dirs: self.progress.dirs_scanned,
+            total_size: self.progress.bytes_scanned,
+            scan_duration_ms: duration_ms,
+            files_per_second,
+            directories: {
+                let mut dir_stats = self.dir_stats;
+                for issue in &errors {
+                    dir_stats.add_issue(&self.root_path, issue);
+                }
+                let mut directories = dir_stats.build();
+                if self.scanner.mapper.analyzes_content() && !self.streamed {
+                    assign_directory_coupling(&self.root_path, &files, &mut directories);
+                }
+                directories
+            },
+            distribution: self.distribution.build(),
+            cache: self.analysis_config.is_some().then(|| self.cache_stats.clone()),
+            sampling: self.sampler.as_ref().map(Sampler::summary),
+            resources: self.usage.as_ref().map(UsageSnapshot::usage_since),
+        };
+
+        let test_coverage_structure = (self.scanner.mapper.analyzes_content() && !self.streamed)
+            .then(|| TestCoverageStructure::build(&files));
+        let fs = self.throttled.as_ref().map_or(self.fs, |throttled| throttled as &dyn FileSystem);
+        let entry_points = if self.streamed {
+            Vec::new()
+        } else {
+            detect_entry_points(&mut files, fs, self.scanner.mapper.tags_entry_points())
+        };
+        let endpoints = if self.streamed || !self.scanner.mapper.analyzes_content() {
+            Vec::new()
+        } else {
+            extract_endpoints(&files, fs)
+        };
+        let schema = if self.streamed { None } else { detect_schema(&self.root_path, &files, fs) };
+        let containers = if self.streamed { Vec::new() } else { detect_containers(&files, fs) };
+
+        if let Some(callback) = &self.scanner.metrics {
+            callback(&ScanMetrics::new(
+                self.progress.files_scanned,
+                self.progress.bytes_scanned,
+                duration_ms,
+                self.cache_stats.hits,
+                self.cache_stats.misses,
+            ));
+        }
+
+        ScanResult {
+            schema_version: crate::SCHEMA_VERSION,
+            root_path: self.root_path,
+            files,
+            stats,
+            roots: Vec::new(),
+            errors,
+            findings,
+            entry_points,
+            endpoints,
+            schema,
+            containers,
+            nested_repositories,
+            test_coverage_structure,
+        }
+    }
+}
+
+// Facts known from the walk alone, recorded when `collect_metadata` is set
+fn walk_metadata(entry: &FsEntry, relative: &Path) -> HashMap<String, serde_json::Value> {
+    let mut metadata = HashMap::new();
+    metadata.insert("depth".to_string(), relative.components().count().into());
+    if let Ok(age) = entry.modified.duration_since(SystemTime::UNIX_EPOCH) {
+        metadata.insert("modified_unix".to_string(), age.as_secs().into());
+    }
+    if let Some(extension) = entry.path.extension().filter(|_| !entry.is_dir) {
+        metadata.insert("extension".to_string(), extension.to_string_lossy().to_lowercase().into());
+    }
+    metadata
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MemoryFileSystem, ScanError, ScanOptions};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn memory_fs() -> MemoryFileSystem {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/src/main.rs", "fn main() {}");
+        fs.insert("app/src/lib.rs", "pub fn run() {}");
+        fs.insert("app/README.md", "# App");
+        fs
+    }
+
+    #[test]
+    fn test_step_reports_running_totals() {
+        let fs = memory_fs();
+        let scanner = DirectoryScanner::new(ScanOptions::default());
+        let mut run = scanner.start(&fs, "app").unwrap();
+
+        let mut seen = Vec::new();
+        while run.step() {
+            seen.push(run.progress().entries_scanned());
+        }
+
+        assert_eq!(seen, vec![1, 2, 3, 4, 5]);
+        assert_eq!(run.progress().files_scanned, 3);
+        assert_eq!(run.progress().current_path, Some(PathBuf::from("app/src/main.rs")));
+        assert_eq!(run.progress().bytes_scanned, 12 + 15 + 5);
+    }
+
+    #[test]
+    fn test_finish_matches_full_scan() {
+        let fs = memory_fs();
+        let scanner = DirectoryScanner::new(ScanOptions::default());
+
+        let mut run = scanner.start(&fs, "app").unwrap();
+        run.step();
+        let stepped = run.finish();
+        let full = scanner.scan_with(&fs, "app").unwrap();
+
+        assert_eq!(stepped.files.len(), full.files.len());
+        assert_eq!(stepped.stats.total_dirs, 2);
+        assert_eq!(stepped.stats.total_size, full.stats.total_size);
+    }
+
+    #[test]
+    fn test_progress_callback_sees_every_entry() {
+        let fs = memory_fs();
+        let updates = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&updates);
+        let scanner = DirectoryScanner::new(ScanOptions::default())
+            .with_progress(move |progress| sink.borrow_mut().push(progress.files_scanned));
+
+        scanner.scan_with(&fs, "app").unwrap();
+
+        assert_eq!(*updates.borrow(), vec![0, 1, 1, 2, 3]);
+    }
+
+    // Walks a memory tree backwards, like a filesystem with no name order
+    struct ReversedFileSystem(MemoryFileSystem);
+
+    impl FileSystem for ReversedFileSystem {
+        fn exists(&self, path: &Path) -> bool {
+            self.0.exists(path)
+        }
+
+        fn walk<'a>(&'a self, root: &Path, options: &'a ScanOptions) -> Box<dyn Iterator<Item = Result<FsEntry, ScanIssue>> + 'a> {
+            let mut entries: Vec<_> = self.0.walk(root, options).collect();
+            entries.reverse();
+            Box::new(entries.into_iter())
+        }
+
+        fn read(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+            self.0.read(path)
+        }
+    }
+
+    // Denies listing `app/secret`, the way the walk reports an unreadable directory
+    struct DeniedFileSystem(MemoryFileSystem);
+
+    impl FileSystem for DeniedFileSystem {
+        fn exists(&self, path: &Path) -> bool {
+            self.0.exists(path)
+        }
+
+        fn walk<'a>(&'a self, root: &Path, options: &'a ScanOptions) -> Box<dyn Iterator<Item = Result<FsEntry, ScanIssue>> + 'a> {
+            let denied = Path::new("app/secret");
+            Box::new(self.0.walk(root, options).flat_map(move |entry| match entry {
+                Ok(entry) if entry.path == denied => vec![Ok(entry), Err(ScanIssue::permission_denied(denied))],
+                Ok(entry) if entry.path.starts_with(denied) => Vec::new(),
+                other => vec![other],
+            }))
+        }
+
+        fn read(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+            self.0.read(path)
+        }
+    }
+
+    #[test]
+    fn test_similar_files_are_linked_from_their_analysis() {
+        let body = "pub fn parse_tokens(tokens: &[Token]) -> Result<SyntaxTree, ParseError> {\n    let mut parser = Parser::new(tokens);\n    parser.parse_program()\n}\n";
+        let mut fs = memory_fs();
+        fs.insert("app/src/parser.rs", body);
+        fs.insert("app/src/parser_copy.rs", body);
+        let options = ScanOptions { enhanced_analysis: true, ..Default::default() };
+
+        let result = DirectoryScanner::new(options).scan_with(&fs, "app").unwrap();
+
+        let info = result.find("app/src/parser.rs").unwrap().enhanced_info.as_ref().unwrap();
+        assert_eq!(info.related_files, [PathBuf::from("app/src/parser_copy.rs")]);
+        // Dropped once the files are linked
+        assert!(result.files.iter().filter_map(|file| file.enhanced_info.as_ref()).all(|info| info.fingerprint.is_none()));
+    }
+
+    #[test]
+    fn test_permission_denied_directory_is_partially_scanned() {
+        let mut fs = memory_fs();
+        fs.insert("app/secret/key.pem", "-----BEGIN");
+        fs.insert("app/secret/more/notes.txt", "notes");
+        let result = DirectoryScanner::new(ScanOptions::default()).scan_with(&DeniedFileSystem(fs), "app").unwrap();
+
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!((result.errors[0].kind, result.errors[0].path.as_deref()), (IssueKind::PermissionDenied, Some(Path::new("app/secret"))));
+        assert!(result.find("app/secret").unwrap().tags.iter().any(|tag| tag == PARTIAL_TAG));
+        assert!(!result.find("app/src").unwrap().tags.iter().any(|tag| tag == PARTIAL_TAG));
+        // Siblings are scanned as before
+        assert_eq!(result.files.iter().filter(|file| !file.is_dir).count(), 3);
+        assert!(result.stats.directories["secret"].partial && !result.stats.directories["src"].partial);
+    }
+
+    #[test]
+    fn test_results_sorted_by_path_unless_disabled() {
+        let fs = ReversedFileSystem(memory_fs());
+        let paths = |result: &ScanResult| -> Vec<String> {
+            result.files.iter().map(|file| file.path.to_string_lossy().into_owned()).collect()
+        };
+
+        let sorted = DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "app").unwrap();
+        assert_eq!(paths(&sorted), ["app", "app/README.md", "app/src", "app/src/lib.rs", "app/src/main.rs"]);
+
+        let options = ScanOptions { sort_by_path: false, ..Default::default() };
+        let walked = DirectoryScanner::new(options).scan_with(&fs, "app").unwrap();
+        assert_eq!(paths(&walked), ["app/src/main.rs", "app/src/lib.rs", "app/src", "app/README.md", "app"]);
+    }
+
+    #[test]
+    fn test_streaming_scan_keeps_no_files() {
+        let fs = memory_fs();
+        let scanner = DirectoryScanner::new(ScanOptions::default());
+        let whole = scanner.scan_with(&fs, "app").unwrap();
+
+        let mut streamed = Vec::new();
+        let result = scanner.scan_streaming_with(&fs, "app", |entry| {
+            streamed.push(entry.path);
+            Ok(())
+        }).unwrap();
+        streamed.sort();
+        assert_eq!(streamed, whole.files.iter().map(|file| file.path.clone()).collect::<Vec<_>>());
+        assert!(result.files.is_empty() && result.test_coverage_structure.is_none());
+        assert_eq!((result.stats.total_files, result.stats.total_size), (whole.stats.total_files, whole.stats.total_size));
+
+        // A failing sink stops the scan
+        let mut calls = 0;
+        let failed = scanner.scan_streaming_with(&fs, "app", |_| {
+            calls += 1;
+            Err(ScanError::InvalidConfig { message: "disk full".to_string() })
+        });
+        assert!(failed.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_sniffs_media_in_untagged_files() {
+        let mut fs = memory_fs();
+        fs.insert("app/assets/logo", b"GIF89a\x20\x00\x10\x00".to_vec());
+        fs.insert("app/src/binary.rs", b"\x00\x01".to_vec());
+
+        let result = DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "app").unwrap();
+        let logo = result.find("app/assets/logo").unwrap();
+        assert_eq!(logo.tags, ["image"]);
+        assert_eq!(logo.media.as_ref().map(|media| (media.mime.as_str(), media.width)), Some(("image/gif", Some(32))));
+        // Known text types are left alone
+        assert!(result.find("app/src/binary.rs").unwrap().media.is_none());
+
+        let options = ScanOptions { sniff_media: false, ..Default::default() };
+        let result = DirectoryScanner::new(options).scan_with(&fs, "app").unwrap();
+        assert_eq!(result.find("app/assets/logo").unwrap().tags, ["unclassified"]);
+    }
+
+    #[test]
+    fn test_include_patterns_and_metadata() {
+        let fs = memory_fs();
+        let options = ScanOptions {
+            include_patterns: vec!["*.md".to_string(), "src/lib.rs".to_string()],
+            collect_metadata: true,
+            ..Default::default()
+        };
+
+        let result = DirectoryScanner::new(options).scan_with(&fs, "app").unwrap();
+
+        let files: Vec<&str> = result.files.iter().filter(|file| !file.is_dir).map(|file| file.name.as_str()).collect();
+        assert_eq!(files, vec!["README.md", "lib.rs"]);
+        let metadata = result.find("app/src/lib.rs").unwrap().metadata.as_ref().unwrap();
+        assert_eq!(metadata["depth"], 2);
+        assert_eq!(metadata["extension"], "rs");
+    }
+
+    #[test]
+    fn test_size_and_modified_filters() {
+        let day = |days: u64| SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(days * 86_400);
+        let mut fs = MemoryFileSystem::new();
+        fs.insert_with_modified("app/src/old.rs", "fn old() {}", day(1));
+        fs.insert_with_modified("app/src/new.rs", "fn new() {}", day(30));
+        fs.insert_with_modified("app/data/dump.csv", "x".repeat(4096), day(30));
+        fs.insert_with_modified("app/empty.rs", "", day(30));
+        let names = |options: ScanOptions| -> Vec<String> {
+            let result = DirectoryScanner::new(options).scan_with(&fs, "app").unwrap();
+            result.files.iter().filter(|file| !file.is_dir).map(|file| file.name.clone()).collect()
+        };
+
+        assert_eq!(names(ScanOptions { min_size: Some(1), max_size: Some(1024), ..Default::default() }), ["new.rs", "old.rs"]);
+        assert_eq!(names(ScanOptions { modified_after: Some(day(10)), max_size: Some(1024), ..Default::default() }), ["empty.rs", "new.rs"]);
+        assert_eq!(names(ScanOptions { modified_before: Some(day(10)), ..Default::default() }), ["old.rs"]);
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::{FileEntry, ScanResult};
+
+impl ScanResult {
+    // Relative paths resolve against the scanned root; each call walks `files`,
+    // so use `index` for many lookups
+    pub fn find<P: AsRef<Path>>(&self, path: P) -> Option<&FileEntry> {
+        let path = path.as_ref();
+        let lookup = |path: &Path| self.files.iter().find(|entry| entry.path == path);
+        lookup(path).or_else(|| path.is_relative().then(|| lookup(&self.root_path.join(path))).flatten())
+    }
+
+    // A path -> entry map borrowing this result, so it cannot go stale while
+    // `files` is modified
+    pub fn index(&self) -> ScanIndex<'_> {
+        ScanIndex {
+            result: self,
+            positions: self.files.iter()
+                .enumerate()
+                .map(|(position, entry)| (entry.path.as_path(), position))
+                .collect(),
+        }
+    }
+
+    pub fn files_with_tag(&self, tag: &str) -> Vec<&FileEntry> {
+        self.filter(|entry| entry.tags.iter().any(|t| t == tag))
+    }
+
+    pub fn filter<F>(&self, predicate: F) -> Vec<&FileEntry>
+    where
+        F: Fn(&FileEntry) -> bool,
+    {
+        self.files.iter().filter(|entry| predicate(entry)).collect()
+    }
+
+    // The `n` entries with the highest key, skipping entries without one
+    pub fn top_by<F>(&self, key: F, n: usize) -> Vec<&FileEntry>
+    where
+        F: Fn(&FileEntry) -> Option<f64>,
+    {
+        let mut ranked: Vec<(f64, &FileEntry)> = self.files.iter()
+            .filter_map(|entry| key(entry).map(|value| (value, entry)))
+            .collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.path.cmp(&b.1.path)));
+        ranked.into_iter().take(n).map(|(_, entry)| entry).collect()
+    }
+}
+
+pub struct ScanIndex<'a> {
+    result: &'a ScanResult,
+    positions: HashMap<&'a Path, usize>,
+}
+
+impl<'a> ScanIndex<'a> {
+    pub fn find<P: AsRef<Path>>(&self, path: P) -> Option<&'a FileEntry> {
+        let path = path.as_ref();
+        self.lookup(path).or_else(|| {
+            // Allow paths relative to the scanned root
+            if path.is_relative() {
+                self.lookup(&self.result.root_path.join(path))
+            } else {
+                None
+            }
+        })
+    }
+
+    fn lookup(&self, path: &Path) -> Option<&'a FileEntry> {
+        self.positions.get(path).map(|&position| &self.result.files[position])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScanStats;
+    use std::path::PathBuf;
+
+    fn entry(path: &str, tags: &[&str], complexity: Option<f64>) -> FileEntry {
+        FileEntry::test(path)
+            .with_size(10)
+            .with_tags(tags)
+            .with_info(|info| info.complexity_score = complexity)
+    }
+
+    fn result(files: Vec<FileEntry>) -> ScanResult {
+        ScanResult {
+            schema_version: crate::SCHEMA_VERSION,
+            root_path: PathBuf::from("project"),
+            files,
+            stats: ScanStats {
+                total_files: 0,
+                total_dirs: 0,
+                total_size: 0,
+                scan_duration_ms: 0,
+                files_per_second: 0.0,
+                directories: Default::default(),
+                distribution: Default::default(),
+                cache: None,
+                sampling: None,
+                resources: None,
+            },
+            roots: Vec::new(),
+            errors: Vec::new(),
+            findings: Vec::new(),
+            test_coverage_structure: None,
+            entry_points: Vec::new(),
+            endpoints: Vec::new(),
+            schema: None,
+            containers: Vec::new(),
+            nested_repositories: Vec::new(),
+        }
+    }
+
+    fn complexity(entry: &FileEntry) -> Option<f64> {
+        entry.enhanced_info.as_ref().and_then(|info| info.complexity_score)
+    }
+
+    #[test]
+    fn test_find_by_full_and_relative_path() {
+        let result = result(vec![
+            entry("project/src/lib.rs", &["source"], Some(3.0)),
+            entry("project/README.md", &["documentation"], None),
+        ]);
+
+        assert_eq!(result.find("project/src/lib.rs").unwrap().name, "lib.rs");
+        assert_eq!(result.find("README.md").unwrap().name, "README.md");
+        assert!(result.find("project/missing.rs").is_none());
+    }
+
+    #[test]
+    fn test_index_lookups_match_find() {
+        let result = result(vec![
+            entry("project/src/lib.rs", &["source"], Some(3.0)),
+            entry("project/README.md", &["documentation"], None),
+        ]);
+        let index = result.index();
+
+        assert_eq!(index.find("project/src/lib.rs").unwrap().name, "lib.rs");
+        assert_eq!(index.find("README.md").unwrap().name, "README.md");
+        assert!(index.find("project/missing.rs").is_none());
+    }
+
+    #[test]
+    fn test_find_survives_mutation() {
+        let mut result = result(vec![entry("project/a.rs", &[], None), entry("project/b.rs", &[], None)]);
+        assert!(result.find("project/b.rs").is_some());
+
+        result.files.remove(0);
+
+        assert_eq!(result.find("project/b.rs").unwrap().name, "b.rs");
+        assert!(result.find("project/a.rs").is_none());
+    }
+
+    #[test]
+    fn test_tag_filter_and_top_by() {
+        let result = result(vec![
+            entry("project/a.rs", &["source"], Some(2.0)),
+            entry("project/b.rs", &["source", "test"], Some(7.5)),
+            entry("project/c.md", &["documentation"], None),
+            entry("project/d.rs", &["source"], Some(5.0)),
+        ]);
+
+        assert_eq!(result.files_with_tag("source").len(), 3);
+        assert_eq!(result.filter(|e| e.path.extension().is_some_and(|ext| ext == "md")).len(), 1);
+
+        let top: Vec<&str> = result.top_by(complexity, 2).iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(top, vec!["b.rs", "d.rs"]);
+    }
+}
@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::{FileEntry, ScanResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RankMetric {
+    Size,
+    Complexity,
+    Importance,
+    Imported,
+    Churn,
+    Fixes,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankedFile {
+    pub path: PathBuf,
+    pub value: f64,
+}
+
+// Module file names that stand for their directory when imported
+const DIRECTORY_MODULES: [&str; 4] = ["mod", "index", "lib", "__init__"];
+
+// Files ordered by `metric`, highest first, with ties broken by path so the
+// output is stable. Files without a value for the metric are left out. Churn
+// and fixes have no source in the scan itself, so commit counts (all commits,
+// or fix commits as `fix_counts` gives them) keyed by path relative to the
+// scan root are passed in.
+pub fn rank_files(
+    result: &ScanResult,
+    metric: RankMetric,
+    limit: usize,
+    commits: Option<&HashMap<PathBuf, usize>>,
+) -> Vec<RankedFile> {
+    let imported = (metric == RankMetric::Imported).then(|| import_counts(result));
+
+    let mut ranked: Vec<RankedFile> = result.files.iter()
+        .filter(|file| !file.is_dir)
+        .filter_map(|file| {
+            let path = relative_path(result, file);
+            let info = file.enhanced_info.as_ref();
+            let value = match metric {
+                RankMetric::Size => Some(file.size as f64),
+                RankMetric::Complexity => info.and_then(|info| info.complexity_score),
+                RankMetric::Importance => info.and_then(|info| info.importance_score),
+                RankMetric::Imported => imported.as_ref().and_then(|counts| counts.get(&path)).map(|&count| count as f64),
+                RankMetric::Churn | RankMetric::Fixes => commits.and_then(|commits| commits.get(&path)).map(|&count| count as f64),
+            }?;
+            Some(RankedFile { path, value })
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| b.value.partial_cmp(&a.value).unwrap_or(Ordering::Equal).then_with(|| a.path.cmp(&b.path)));
+    ranked.truncate(limit);
+    ranked
+}
+
+// How many other files import each file, keyed by relative path. Imports are
+// the raw statements from enhanced analysis, so a file counts as imported when
+// its module name appears as a segment of the statement; `mod.rs`, `index.js`
+// and the like go by their directory name. Name clashes between modules in
+// different directories are counted for both.
+pub fn import_counts(result: &ScanResult) -> HashMap<PathBuf, usize> {
+    let mut counts = HashMap::new();
+    for (_, target) in import_edges(&result.files) {
+        *counts.entry(relative_path(result, &result.files[target])).or_insert(0) += 1;
+    }
+    counts
+}
+
+// (importer, imported) positions in `files`, resolved as `import_counts`
+// describes. One file importing another several times is one edge.
+pub(crate) fn import_edges(files: &[FileEntry]) -> Vec<(usize, usize)> {
+    let mut by_module: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (position, file) in files.iter().enumerate().filter(|(_, file)| !file.is_dir && file.enhanced_info.is_some()) {
+        if let Some(module) = module_name(&file.path) {
+            by_module.entry(module).or_default().push(position);
+        }
+    }
+
+    let mut edges = Vec::new();
+    for (position, file) in files.iter().enumerate().filter(|(_, file)| !file.is_dir) {
+        let Some(info) = &file.enhanced_info else { continue };
+        let mut targets: Vec<usize> = info.imports.iter()
+            .flat_map(|import| import_segments(import))
+            .filter_map(|segment| by_module.get(segment))
+            .flatten()
+            .copied()
+            .filter(|target| *target != position)
+            .collect();
+        targets.sort_unstable();
+        targets.dedup();
+        edges.extend(targets.into_iter().map(|target| (position, target)));
+    }
+    edges
+}
+
+// Whether the import statement names the module of the file at `path`
+pub(crate) fn resolves_to(import: &str, path: &Path) -> bool {
+    module_name(path).is_some_and(|module| import_segments(import).any(|segment| segment == module))
+}
+
+fn module_name(path: &Path) -> Option<&str> {
+    let stem = path.file_stem()?.to_str()?;
+    if DIRECTORY_MODULES.contains(&stem) {
+        path.parent()?.file_name()?.to_str()
+    } else {
+        Some(stem)
+    }
+}
+
+fn import_segments(import: &str) -> impl Iterator<Item = &str> {
+    // A JS import's module specifier is the quoted part; Rust paths are used whole
+    let specifier = import.split(['"', '\'']).nth(1).unwrap_or(import);
+    specifier
+        .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-' || c == '.'))
+        .flat_map(|segment| segment.split('.').next())
+        .filter(|segment| !segment.is_empty())
+}
+
+fn relative_path(result: &ScanResult, file: &FileEntry) -> PathBuf {
+    file.path.strip_prefix(&result.root_path).unwrap_or(&file.path).to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScanStats;
+
+    fn file(path: &str, size: u64, complexity: Option<f64>, imports: &[&str]) -> FileEntry {
+        FileEntry::test(Path::new("proj").join(path)).with_size(size).with_info(|info| {
+            info.complexity_score = complexity;
+            info.imports = imports.iter().map(|import| import.to_string()).collect();
+        })
+    }
+
+    fn result(files: Vec<FileEntry>) -> ScanResult {
+        ScanResult {
+            schema_version: crate::SCHEMA_VERSION,
+            root_path: PathBuf::from("proj"),
+            files,
+            stats: ScanStats { total_files: 0, total_dirs: 0, total_size: 0, scan_duration_ms: 0, files_per_second: 0.0, directories: Default::default(), distribution: Default::default(), cache: None, sampling: None, resources: None },
+            roots: Vec::new(),
+            errors: Vec::new(),
+            findings: Vec::new(),
+            test_coverage_structure: None,
+            entry_points: Vec::new(),
+            endpoints: Vec::new(),
+            schema: None,
+            containers: Vec::new(),
+            nested_repositories: Vec::new(),
+        }
+    }
+
+    fn paths(ranked: &[RankedFile]) -> Vec<&str> {
+        ranked.iter().map(|file| file.path.to_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_ranks_highest_first_with_limit() {
+        let result = result(vec![
+            file("a.rs", 10, Some(2.0), &[]),
+            file("b.rs", 30, None, &[]),
+            file("c.rs", 20, Some(5.0), &[]),
+        ]);
+
+        assert_eq!(paths(&rank_files(&result, RankMetric::Size, 2, None)), vec!["b.rs", "c.rs"]);
+        // Files without a complexity score are not ranked
+        assert_eq!(paths(&rank_files(&result, RankMetric::Complexity, 10, None)), vec!["c.rs", "a.rs"]);
+    }
+
+    #[test]
+    fn test_counts_importing_files() {
+        let result = result(vec![
+            file("src/main.rs", 0, None, &["crate::scanner::Scanner", "crate::scanner::walk"]),
+            file("src/cli.rs", 0, None, &["crate::{scanner, util}"]),
+            file("src/scanner/mod.rs", 0, None, &["super::util::join"]),
+            file("src/util.rs", 0, None, &[]),
+            file("web/app.ts", 0, None, &["import { format } from './util.js';"]),
+        ]);
+        let counts = import_counts(&result);
+
+        assert_eq!(counts.get(Path::new("src/scanner/mod.rs")), Some(&2));
+        assert_eq!(counts.get(Path::new("src/util.rs")), Some(&3));
+        assert_eq!(counts.get(Path::new("src/main.rs")), None);
+        assert_eq!(paths(&rank_files(&result, RankMetric::Imported, 1, None)), vec!["src/util.rs"]);
+    }
+
+    #[test]
+    fn test_churn_uses_supplied_counts() {
+        let result = result(vec![file("a.rs", 0, None, &[]), file("b.rs", 0, None, &[])]);
+        let churn = HashMap::from([(PathBuf::from("a.rs"), 3), (PathBuf::from("b.rs"), 7)]);
+
+        let ranked = rank_files(&result, RankMetric::Churn, 10, Some(&churn));
+        assert_eq!(ranked[0], RankedFile { path: PathBuf::from("b.rs"), value: 7.0 });
+        assert!(rank_files(&result, RankMetric::Churn, 10, None).is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+
+// Clock ticks per second in /proc/self/stat; fixed at 100 by the Linux ABI
+const USER_HZ: f64 = 100.0;
+
+// What the scanning process used while a scan ran, so regressions in the
+// scanner's own performance show up across versions. Read from /proc on
+// Linux; figures a platform cannot report are left out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    // High-water mark of the process's resident memory, which covers whatever
+    // the process did before the scan too
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_rss_bytes: Option<u64>,
+    // Bytes read during the scan, whether they came from the page cache or disk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_bytes: Option<u64>,
+    // User and system time of all the process's threads during the scan
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_ms: Option<f64>,
+}
+
+impl ResourceUsage {
+    // Usage of scans run one after the other: reads and CPU time add up
+    pub(crate) fn merge(self, other: ResourceUsage) -> ResourceUsage {
+        ResourceUsage {
+            peak_rss_bytes: self.peak_rss_bytes.max(other.peak_rss_bytes),
+            read_bytes: add(self.read_bytes, other.read_bytes),
+            cpu_ms: add(self.cpu_ms, other.cpu_ms),
+        }
+    }
+}
+
+// None only when neither side has the figure
+fn add<T: std::ops::Add<Output = T> + Default>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (None, None) => None,
+        (a, b) => Some(a.unwrap_or_default() + b.unwrap_or_default()),
+    }
+}
+
+// Counters at the start of a scan, which the usage is measured from
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct UsageSnapshot {
+    read_bytes: Option<u64>,
+    cpu_ms: Option<f64>,
+}
+
+impl UsageSnapshot {
+    pub(crate) fn take() -> Self {
+        Self { read_bytes: proc_file("io").and_then(|io| read_chars(&io)), cpu_ms: proc_file("stat").and_then(|stat| cpu_ms(&stat)) }
+    }
+
+    pub(crate) fn usage_since(&self) -> ResourceUsage {
+        let now = Self::take();
+        ResourceUsage {
+            peak_rss_bytes: peak_rss_bytes(),
+            read_bytes: now.read_bytes.zip(self.read_bytes).map(|(now, start)| now.saturating_sub(start)),
+            cpu_ms: now.cpu_ms.zip(self.cpu_ms).map(|(now, start)| (now - start).max(0.0)),
+        }
+    }
+}
+
+// Peak resident set size of this process, where the platform reports it
+pub fn peak_rss_bytes() -> Option<u64> {
+    proc_file("status").and_then(|status| vm_hwm(&status))
+}
+
+fn proc_file(name: &str) -> Option<String> {
+    if cfg!(target_os = "linux") {
+        std::fs::read_to_string(format!("/proc/self/{}", name)).ok()
+    } else {
+        None
+    }
+}
+
+// `VmHWM:     1234 kB` in /proc/self/status
+fn vm_hwm(status: &str) -> Option<u64> {
+    let line = status.lines().find_map(|line| line.strip_prefix("VmHWM:"))?;
+    let kib: u64 = line.trim().trim_end_matches("kB").trim().parse().ok()?;
+    Some(kib * 1024)
+}
+
+// `rchar: 1234` in /proc/self/io; `read_bytes` there leaves out cached reads
+fn read_chars(io: &str) -> Option<u64> {
+    io.lines().find_map(|line| line.strip_prefix("rchar:"))?.trim().parse().ok()
+}
+
+// utime and stime, the 14th and 15th fields of /proc/self/stat. The command
+// name before them is in parentheses and may contain spaces.
+fn cpu_ms(stat: &str) -> Option<f64> {
+    let mut fields = stat.get(stat.rfind(')')? + 1..)?.split_whitespace().skip(11);
+    let user: u64 = fields.next()?.parse().ok()?;
+    let system: u64 = fields.next()?.parse().ok()?;
+    Some((user + system) as f64 * 1000.0 / USER_HZ)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+
+    #[test]
+    fn test_parses_proc_files() {
+        assert_eq!(vm_hwm("Name:\tprojscan\nVmPeak:\t  9000 kB\nVmHWM:\t    2048 kB\n"), Some(2048 * 1024));
+        assert_eq!(read_chars("rchar: 52301\nwchar: 12\nread_bytes: 0\n"), Some(52301));
+        let stat = "4242 (proj scan) R 1 4242 4242 0 -1 4194304 300 0 0 0 150 25 0 0 20 0 3 0 100";
+        assert_eq!(cpu_ms(stat), Some(1750.0));
+        assert_eq!(cpu_ms("4242 (projscan"), None);
+    }
+
+    #[test]
+    fn test_merge_adds_reads_and_cpu_time() {
+        let first = ResourceUsage { peak_rss_bytes: Some(100), read_bytes: Some(10), cpu_ms: Some(5.0) };
+        let second = ResourceUsage { peak_rss_bytes: Some(300), read_bytes: None, cpu_ms: Some(2.5) };
+
+        assert_eq!(first.merge(second), ResourceUsage { peak_rss_bytes: Some(300), read_bytes: Some(10), cpu_ms: Some(7.5) });
+        assert_eq!(ResourceUsage::default().merge(ResourceUsage::default()), ResourceUsage::default());
+    }
+
+    #[test]
+    fn test_scans_report_usage_when_asked() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/main.rs", "fn main() {}");
+        let options = ScanOptions { collect_resource_usage: true, ..Default::default() };
+        let resources = DirectoryScanner::new(options).scan_with(&fs, "app").unwrap().stats.resources;
+
+        let resources = resources.expect("usage is collected");
+        if cfg!(target_os = "linux") {
+            assert!(resources.peak_rss_bytes.is_some_and(|bytes| bytes > 0));
+            assert!(resources.cpu_ms.is_some() && resources.read_bytes.is_some());
+        }
+        assert!(DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "app").unwrap().stats.resources.is_none());
+    }
+}
@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::{Chunk, FileEntry, ScanResult};
+
+// Okapi BM25 parameters: term frequency saturation and length normalization
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+// File name terms count this many times, so a keyword in the name outranks
+// one buried in a summary
+const NAME_WEIGHT: usize = 3;
+const MIN_TERM_LEN: usize = 2;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryMatch {
+    // Relative to the scan root
+    pub path: PathBuf,
+    pub score: f64,
+    // Estimated tokens to read the whole file
+    pub tokens: usize,
+}
+
+impl ScanResult {
+    // Files relevant to `keywords`, most relevant first, whose estimated tokens
+    // add up to at most `budget`. Relevance is BM25 over each file's name, path,
+    // content summary, purpose, exports and symbol names, so anything beyond
+    // names needs enhanced analysis. A file too large for what is left of the
+    // budget is skipped in favour of smaller, less relevant ones.
+    pub fn select_for_query(&self, keywords: &[&str], budget: usize) -> Vec<QueryMatch> {
+        let files: Vec<&FileEntry> = self.files.iter().filter(|file| !file.is_dir).collect();
+        let documents: Vec<Vec<String>> = files.iter().map(|file| file_terms(self, file)).collect();
+        let scores = bm25(&documents, &query_terms(keywords));
+
+        let candidates = files.iter().zip(scores).map(|(file, score)| QueryMatch {
+            path: file.path.strip_prefix(&self.root_path).unwrap_or(&file.path).to_path_buf(),
+            score,
+            // The same four characters per token as `estimate_tokens`, from the size
+            tokens: file.size.div_ceil(4) as usize,
+        });
+        within_budget(candidates.collect(), budget, |file| file.tokens)
+    }
+}
+
+// Chunks relevant to `keywords`, most relevant first, whose tokens add up to at
+// most `budget`; BM25 over each chunk's path and content
+pub fn select_chunks_for_query<'a>(chunks: &'a [Chunk], keywords: &[&str], budget: usize) -> Vec<&'a Chunk> {
+    let documents: Vec<Vec<String>> = chunks.iter()
+        .map(|chunk| {
+            let mut document = terms(&chunk.path.to_string_lossy());
+            document.extend(terms(&chunk.content));
+            document
+        })
+        .collect();
+    let scores = bm25(&documents, &query_terms(keywords));
+
+    let candidates: Vec<(&Chunk, f64)> = chunks.iter().zip(scores).collect();
+    within_budget(candidates, budget, |(chunk, _)| chunk.token_count)
+        .into_iter()
+        .map(|(chunk, _)| chunk)
+        .collect()
+}
+
+trait Scored {
+    fn score(&self) -> f64;
+}
+
+impl Scored for QueryMatch {
+    fn score(&self) -> f64 {
+        self.score
+    }
+}
+
+impl Scored for (&Chunk, f64) {
+    fn score(&self) -> f64 {
+        self.1
+    }
+}
+
+// Highest score first, ties in input order, dropping items that match nothing
+// and items that no longer fit
+fn within_budget<T: Scored>(mut items: Vec<T>, budget: usize, tokens: impl Fn(&T) -> usize) -> Vec<T> {
+    items.retain(|item| item.score() > 0.0);
+    items.sort_by(|a, b| b.score().total_cmp(&a.score()));
+    let mut remaining = budget;
+    items.into_iter()
+        .filter(|item| {
+            let cost = tokens(item);
+            let fits = cost <= remaining;
+            if fits {
+                remaining -= cost;
+            }
+            fits
+        })
+        .collect()
+}
+
+fn bm25(documents: &[Vec<String>], query: &[String]) -> Vec<f64> {
+    if documents.is_empty() || query.is_empty() {
+        return vec![0.0; documents.len()];
+    }
+    let count = documents.len() as f64;
+    let average_length = documents.iter().map(Vec::len).sum::<usize>() as f64 / count;
+    let idf: Vec<f64> = query.iter()
+        .map(|term| {
+            let containing = documents.iter().filter(|document| document.contains(term)).count() as f64;
+            ((count - containing + 0.5) / (containing + 0.5) + 1.0).ln()
+        })
+        .collect();
+
+    documents.iter()
+        .map(|document| {
+            let mut frequencies: HashMap<&str, usize> = HashMap::new();
+            for term in document {
+                *frequencies.entry(term).or_default() += 1;
+            }
+            let length = document.len() as f64 / average_length.max(1.0);
+            query.iter().zip(&idf)
+                .map(|(term, idf)| {
+                    let frequency = frequencies.get(term.as_str()).copied().unwrap_or(0) as f64;
+                    idf * frequency * (K1 + 1.0) / (frequency + K1 * (1.0 - B + B * length))
+                })
+                .sum()
+        })
+        .collect()
+}
+
+fn file_terms(result: &ScanResult, file: &FileEntry) -> Vec<String> {
+    let relative = file.path.strip_prefix(&result.root_path).unwrap_or(&file.path);
+    let mut document = terms(&relative.to_string_lossy());
+    for _ in 1..NAME_WEIGHT {
+        document.extend(terms(&file.name));
+    }
+    if let Some(info) = &file.enhanced_info {
+        for text in info.content_summary.iter().chain(&info.purpose).chain(&info.exports) {
+            document.extend(terms(text));
+        }
+        for symbol in &info.symbols {
+            document.extend(terms(&symbol.name));
+        }
+    }
+    document
+}
+
+fn query_terms(keywords: &[&str]) -> Vec<String> {
+    let mut query: Vec<String> = keywords.iter().flat_map(|keyword| terms(keyword)).collect();
+    query.sort_unstable();
+    query.dedup();
+    query
+}
+
+// Lowercased words of `text`, with identifiers also split at underscores and
+// camelCase humps, so `parseConfig` and `parse_config` both match "config"
+pub(crate) fn terms(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for word in text.split(|c: char| !(c.is_alphanumeric() || c == '_')).filter(|word| !word.is_empty()) {
+        let parts = split_identifier(word);
+        if parts.len() > 1 {
+            terms.push(word.to_lowercase());
+        }
+        terms.extend(parts);
+    }
+    terms.retain(|term| term.chars().count() >= MIN_TERM_LEN);
+    terms
+}
+
+pub(crate) fn split_identifier(word: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in word.chars() {
+        if (c == '_' || (c.is_uppercase() && previous_lower)) && !current.is_empty() {
+            parts.push(std::mem::take(&mut current));
+        }
+        if c != '_' {
+            current.extend(c.to_lowercase());
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Chunker, DirectoryScanner, MemoryFileSystem, ScanOptions};
+    use std::path::Path;
+
+    fn project() -> MemoryFileSystem {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/src/config.rs", "// Loads settings from TOML files\npub fn parse_config(path: &str) -> Config {\n    todo!()\n}\n");
+        fs.insert("app/src/server.rs", "// HTTP server and routing\npub fn start_server(port: u16) {\n    listen(port);\n}\n");
+        fs.insert("app/src/cache.rs", "// In-memory cache for rendered pages\npub fn evictStale() {\n}\n");
+        fs.insert("app/README.md", "# App\n\nA small web service.\n");
+        fs
+    }
+
+    fn scan(fs: &MemoryFileSystem) -> ScanResult {
+        let options = ScanOptions { enhanced_analysis: true, ..Default::default() };
+        DirectoryScanner::new(options).scan_with(fs, "app").unwrap()
+    }
+
+    #[test]
+    fn test_ranks_files_by_names_summaries_and_symbols() {
+        let result = scan(&project());
+
+        let matches = result.select_for_query(&["config"], 10_000);
+        assert_eq!(matches[0].path, Path::new("src/config.rs"));
+        assert!(matches.iter().all(|file| file.score > 0.0));
+
+        let matches = result.select_for_query(&["http", "routing"], 10_000);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].path, Path::new("src/server.rs"));
+
+        // camelCase symbol names are split into words
+        let matches = result.select_for_query(&["stale"], 10_000);
+        assert_eq!(matches[0].path, Path::new("src/cache.rs"));
+        assert!(result.select_for_query(&["kubernetes"], 10_000).is_empty());
+    }
+
+    #[test]
+    fn test_stays_within_token_budget() {
+        let mut fs = project();
+        fs.insert("app/src/config_schema.rs", format!("// Config schema\n{}", "// padding\n".repeat(200)));
+        let result = scan(&fs);
+
+        let all = result.select_for_query(&["config"], usize::MAX);
+        assert_eq!(all.len(), 2);
+        let budget = all.iter().map(|file| file.tokens).min().unwrap();
+        let selected = result.select_for_query(&["config"], budget);
+        assert_eq!(selected.len(), 1);
+        assert!(selected[0].tokens <= budget);
+        assert!(result.select_for_query(&["config"], 0).is_empty());
+    }
+
+    #[test]
+    fn test_selects_chunks_for_query() {
+        let fs = project();
+        let result = scan(&fs);
+        let chunks = Chunker::default().chunk_result(&result, &fs);
+
+        let selected = select_chunks_for_query(&chunks, &["listen port"], 10_000);
+        assert_eq!(selected[0].path, Path::new("app/src/server.rs"));
+        let budget = selected[0].token_count;
+        assert_eq!(select_chunks_for_query(&chunks, &["listen port"], budget).len(), 1);
+    }
+}
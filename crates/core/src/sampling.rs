@@ -0,0 +1,270 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::EnhancedFileInfo;
+
+// Normal quantile for the two-sided 95% intervals of the estimates
+const Z_95: f64 = 1.96;
+
+// Content analysis of a sample of files, for quick triage scans of huge trees.
+// Once a scan has passed `above_files` files, each group of files sharing a
+// directory and extension keeps its first `min_per_group` files and a `rate`
+// share of the rest, picked by a hash of the path, so the same seed picks the
+// same files. Aggregates are then extrapolated per group into
+// `ScanStats::sampling`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sampling {
+    pub above_files: usize,
+    // Share of the files past a group's minimum that are analyzed, 0 to 1
+    pub rate: f64,
+    pub min_per_group: usize,
+    pub seed: u64,
+}
+
+impl Default for Sampling {
+    fn default() -> Self {
+        Self { above_files: 10_000, rate: 0.1, min_per_group: 3, seed: 0 }
+    }
+}
+
+// An extrapolated value with its 95% confidence interval
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Estimate {
+    pub value: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+impl Estimate {
+    fn new(value: f64, variance: f64) -> Self {
+        let margin = Z_95 * variance.max(0.0).sqrt();
+        Self { value, low: (value - margin).max(0.0), high: value + margin }
+    }
+
+    fn variance(&self) -> f64 {
+        ((self.high - self.value) / Z_95).powi(2)
+    }
+
+    fn per_file(&self, files: usize) -> Self {
+        let files = files.max(1) as f64;
+        Self { value: self.value / files, low: self.low / files, high: self.high / files }
+    }
+}
+
+// What a sampled scan analyzed, and the totals it extrapolated from that
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SamplingSummary {
+    // Files that reached content analysis, and how many of them were analyzed
+    pub files: usize,
+    pub analyzed: usize,
+    // Directory and extension groups the estimates are made in
+    pub groups: usize,
+    pub total_lines: Estimate,
+    pub total_complexity: Estimate,
+    // Per file, over analyzed and skipped files alike
+    pub mean_complexity: Estimate,
+}
+
+impl SamplingSummary {
+    pub fn analyzed_share(&self) -> f64 {
+        if self.files == 0 { 1.0 } else { self.analyzed as f64 / self.files as f64 }
+    }
+
+    // Estimates of disjoint trees: totals and their variances add up
+    pub(crate) fn merge(self, other: SamplingSummary) -> SamplingSummary {
+        let add = |a: Estimate, b: Estimate| Estimate::new(a.value + b.value, a.variance() + b.variance());
+        let total_complexity = add(self.total_complexity, other.total_complexity);
+        let files = self.files + other.files;
+        SamplingSummary {
+            files,
+            analyzed: self.analyzed + other.analyzed,
+            groups: self.groups + other.groups,
+            total_lines: add(self.total_lines, other.total_lines),
+            total_complexity,
+            mean_complexity: total_complexity.per_file(files),
+        }
+    }
+}
+
+// Sums of one metric over a group's analyzed files
+#[derive(Debug, Default)]
+struct Moments {
+    sum: f64,
+    sum_of_squares: f64,
+}
+
+impl Moments {
+    fn add(&mut self, value: f64) {
+        self.sum += value;
+        self.sum_of_squares += value * value;
+    }
+
+    // Estimated total over `files` from `analyzed` of them, with its variance
+    // shrunk by the share analyzed
+    fn total(&self, files: usize, analyzed: usize) -> (f64, f64) {
+        let (files, analyzed) = (files as f64, analyzed as f64);
+        let mean = self.sum / analyzed;
+        let sample_variance = if analyzed > 1.0 {
+            (self.sum_of_squares - analyzed * mean * mean).max(0.0) / (analyzed - 1.0)
+        } else {
+            0.0
+        };
+        (self.sum * files / analyzed, files * files * (1.0 - analyzed / files) * sample_variance / analyzed)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Group {
+    files: usize,
+    analyzed: usize,
+    lines: Moments,
+    complexity: Moments,
+}
+
+// Decides which files a scan analyzes and collects what the analyzed ones
+// measured. Each file is admitted once, in walk order.
+#[derive(Debug)]
+pub(crate) struct Sampler {
+    options: Sampling,
+    seen: usize,
+    // Ordered, so estimates add up the same way on every run
+    groups: BTreeMap<(String, String), Group>,
+}
+
+impl Sampler {
+    pub(crate) fn new(options: Sampling) -> Self {
+        Self { options, seen: 0, groups: BTreeMap::new() }
+    }
+
+    // Whether the file at `relative` (to the scan root) is analyzed
+    pub(crate) fn admit(&mut self, relative: &Path) -> bool {
+        self.seen += 1;
+        let group = self.groups.entry(group_key(relative)).or_default();
+        group.files += 1;
+        // Every group keeps at least one file to extrapolate from
+        self.seen <= self.options.above_files
+            || group.files <= self.options.min_per_group.max(1)
+            || picked(relative, self.options.seed, self.options.rate)
+    }
+
+    // Takes back a file admitted but then left out of the result
+    pub(crate) fn forget(&mut self, relative: &Path) {
+        self.seen -= 1;
+        if let Some(group) = self.groups.get_mut(&group_key(relative)) {
+            group.files -= 1;
+        }
+    }
+
+    // Adds what the analysis of an admitted file measured; files without
+    // content count as zero lines and complexity
+    pub(crate) fn record(&mut self, relative: &Path, info: Option<&EnhancedFileInfo>) {
+        let group = self.groups.entry(group_key(relative)).or_default();
+        group.analyzed += 1;
+        group.lines.add(info.and_then(|info| info.line_count).unwrap_or_default() as f64);
+        group.complexity.add(info.and_then(|info| info.complexity_score).unwrap_or_default());
+    }
+
+    pub(crate) fn summary(&self) -> SamplingSummary {
+        let (mut lines, mut lines_variance) = (0.0, 0.0);
+        let (mut complexity, mut complexity_variance) = (0.0, 0.0);
+        for group in self.groups.values().filter(|group| group.analyzed > 0) {
+            let (total, variance) = group.lines.total(group.files, group.analyzed);
+            lines += total;
+            lines_variance += variance;
+            let (total, variance) = group.complexity.total(group.files, group.analyzed);
+            complexity += total;
+            complexity_variance += variance;
+        }
+        let total_complexity = Estimate::new(complexity, complexity_variance);
+        SamplingSummary {
+            files: self.seen,
+            analyzed: self.groups.values().map(|group| group.analyzed).sum(),
+            groups: self.groups.values().filter(|group| group.files > 0).count(),
+            total_lines: Estimate::new(lines, lines_variance),
+            total_complexity,
+            mean_complexity: total_complexity.per_file(self.seen),
+        }
+    }
+}
+
+// Parent directory and lowercased extension
+fn group_key(relative: &Path) -> (String, String) {
+    let directory = relative.parent().map(|parent| parent.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = relative.extension().map(|extension| extension.to_string_lossy().to_lowercase()).unwrap_or_default();
+    (directory, extension)
+}
+
+// FNV-1a of the path's components, so picks are the same on every platform
+fn picked(relative: &Path, seed: u64, rate: f64) -> bool {
+    let mut hash = 0xcbf2_9ce4_8422_2325 ^ seed;
+    for (index, component) in relative.components().enumerate() {
+        let separator: &[u8] = if index > 0 { b"/" } else { b"" };
+        for &byte in separator.iter().chain(component.as_os_str().to_string_lossy().as_bytes()) {
+            hash = (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    ((hash >> 11) as f64 / (1u64 << 53) as f64) < rate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, Fixture, FixtureSpec, ScanOptions};
+
+    fn options(sampling: Sampling, threads: usize) -> ScanOptions {
+        ScanOptions { enhanced_analysis: true, sampling: Some(sampling), analysis_threads: threads, ..Default::default() }
+    }
+
+    #[test]
+    fn test_small_scans_analyze_every_file() {
+        let fixture = Fixture::generate(&FixtureSpec { files: 40, ..Default::default() }).unwrap();
+        let fs = fixture.to_memory(Path::new("app"));
+        let result = DirectoryScanner::new(options(Sampling::default(), 1)).scan_with(&fs, "app").unwrap();
+        let sampling = result.stats.sampling.unwrap();
+
+        let lines: usize = result.files.iter().filter_map(|file| file.enhanced_info.as_ref()?.line_count).sum();
+        assert_eq!((sampling.files, sampling.analyzed), (40, 40));
+        assert_eq!(sampling.total_lines, Estimate { value: lines as f64, low: lines as f64, high: lines as f64 });
+        assert!(DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "app").unwrap().stats.sampling.is_none());
+    }
+
+    #[test]
+    fn test_extrapolates_from_a_sample() {
+        let fixture = Fixture::generate(&FixtureSpec { files: 600, max_depth: 1, fan_out: 2, seed: 4, ..Default::default() }).unwrap();
+        let fs = fixture.to_memory(Path::new("app"));
+        let sampling = Sampling { above_files: 50, rate: 0.2, min_per_group: 2, seed: 1 };
+        let result = DirectoryScanner::new(options(sampling, 1)).scan_with(&fs, "app").unwrap();
+        let summary = result.stats.sampling.clone().unwrap();
+
+        let analyzed = result.files.iter().filter(|file| file.enhanced_info.is_some() && !file.is_dir).count();
+        assert_eq!(summary.analyzed, analyzed);
+        assert!(summary.analyzed < 250, "{}", summary.analyzed);
+        let lines = fixture.files.iter().map(|file| file.content.lines().count() as f64).sum::<f64>();
+        let estimate = summary.total_lines;
+        assert!(estimate.low < estimate.value && estimate.value < estimate.high);
+        assert!((estimate.value - lines).abs() / lines < 0.2, "{:?} vs {}", estimate, lines);
+
+        // Same seed, same sample, whether or not analysis runs on worker threads
+        let parallel = DirectoryScanner::new(options(sampling, 3)).scan_with(&fs, "app").unwrap();
+        assert_eq!(parallel.stats.sampling, Some(summary));
+    }
+
+    #[test]
+    fn test_merge_adds_totals_and_variances() {
+        let half = SamplingSummary {
+            files: 10,
+            analyzed: 4,
+            groups: 2,
+            total_lines: Estimate::new(100.0, 9.0),
+            total_complexity: Estimate::new(20.0, 0.0),
+            mean_complexity: Estimate::new(2.0, 0.0),
+        };
+        let merged = half.clone().merge(half);
+
+        assert_eq!((merged.files, merged.analyzed, merged.groups), (20, 8, 4));
+        assert!((merged.total_lines.variance() - 18.0).abs() < 1e-9);
+        assert_eq!(merged.mean_complexity.value, 2.0);
+    }
+}
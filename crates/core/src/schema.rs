@@ -0,0 +1,121 @@
+use serde_json::{Map, Value};
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::{epoch_millis, format_rfc3339, ScanError, ScanResult};
+
+// Version of the serialized ScanResult layout. Within a version fields are only
+// ever added, never renamed, removed or retyped; any such change bumps it and
+// adds a step to MIGRATIONS. Results written before versioning count as 0.
+pub const SCHEMA_VERSION: u32 = 1;
+
+// MIGRATIONS[n] rewrites a version n document into version n + 1
+const MIGRATIONS: [fn(&mut Map<String, Value>); SCHEMA_VERSION as usize] = [migrate_v0_to_v1];
+
+// v0 wrote `modified` as serde's `{secs_since_epoch, nanos_since_epoch}`
+fn migrate_v0_to_v1(document: &mut Map<String, Value>) {
+    let Some(Value::Array(files)) = document.get_mut("files") else {
+        return;
+    };
+    for file in files.iter_mut().filter_map(Value::as_object_mut) {
+        let Some(Value::Object(legacy)) = file.get("modified") else {
+            continue;
+        };
+        let secs = legacy.get("secs_since_epoch").and_then(Value::as_u64).unwrap_or(0);
+        let nanos = legacy.get("nanos_since_epoch").and_then(Value::as_u64).unwrap_or(0);
+        let time = UNIX_EPOCH + Duration::from_secs(secs) + Duration::from_nanos(nanos);
+        file.insert("modified".to_string(), Value::from(format_rfc3339(time)));
+        file.insert("modified_ms".to_string(), Value::from(epoch_millis(time)));
+    }
+}
+
+pub(crate) fn default_schema_version() -> u32 {
+    0
+}
+
+impl ScanResult {
+    // The version this result was read as; results built in memory are current
+    pub fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+
+    // Loads a saved result of any version up to SCHEMA_VERSION, migrating older
+    // layouts first. Prefer this over deserializing ScanResult directly.
+    pub fn from_json(json: &str) -> Result<ScanResult, ScanError> {
+        let value: Value = serde_json::from_str(json).map_err(|e| invalid(e.to_string()))?;
+        Self::from_json_value(value)
+    }
+
+    pub fn from_json_value(value: Value) -> Result<ScanResult, ScanError> {
+        let Value::Object(mut document) = value else {
+            return Err(invalid("expected a JSON object".to_string()));
+        };
+        let version = match document.get("schema_version") {
+            None => 0,
+            Some(version) => version.as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| invalid(format!("schema_version must be a non-negative integer, got {}", version)))?,
+        };
+        if version > SCHEMA_VERSION {
+            return Err(ScanError::UnsupportedSchema { found: version, supported: SCHEMA_VERSION });
+        }
+
+        for migrate in &MIGRATIONS[version as usize..] {
+            migrate(&mut document);
+        }
+        document.insert("schema_version".to_string(), Value::from(SCHEMA_VERSION));
+        serde_json::from_value(Value::Object(document)).map_err(|e| invalid(e.to_string()))
+    }
+}
+
+fn invalid(message: String) -> ScanError {
+    ScanError::InvalidResult { message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+
+    fn scanned() -> ScanResult {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/src/lib.rs", "pub fn a() {}");
+        DirectoryScanner::new(ScanOptions::default()).scan_with(&fs, "app").unwrap()
+    }
+
+    #[test]
+    fn test_results_are_written_with_the_current_version() {
+        let json = serde_json::to_string(&scanned()).unwrap();
+
+        assert!(json.starts_with(&format!("{{\"schema_version\":{},", SCHEMA_VERSION)));
+        assert_eq!(ScanResult::from_json(&json).unwrap().schema_version(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_unversioned_results_are_migrated() {
+        let legacy = r#"{
+            "root_path": ".",
+            "files": [{"path": "a.rs", "name": "a.rs", "size": 1, "is_dir": false, "tags": [],
+                "metadata": null, "enhanced_info": null,
+                "modified": {"secs_since_epoch": 1, "nanos_since_epoch": 500000000}}],
+            "stats": {"total_files": 1, "total_dirs": 0, "total_size": 1, "scan_duration_ms": 0, "files_per_second": 0.0},
+            "errors": ["could not read b.rs"]
+        }"#;
+        let result = ScanResult::from_json(legacy).unwrap();
+
+        assert_eq!(result.schema_version(), SCHEMA_VERSION);
+        assert_eq!(result.files[0].modified, UNIX_EPOCH + Duration::from_millis(1500));
+        assert_eq!(result.errors[0].message, "could not read b.rs");
+    }
+
+    #[test]
+    fn test_newer_versions_are_rejected() {
+        let mut value = serde_json::to_value(scanned()).unwrap();
+        value["schema_version"] = Value::from(SCHEMA_VERSION + 1);
+
+        assert!(matches!(
+            ScanResult::from_json_value(value),
+            Err(ScanError::UnsupportedSchema { found, supported }) if found == SCHEMA_VERSION + 1 && supported == SCHEMA_VERSION
+        ));
+        assert!(matches!(ScanResult::from_json("[]"), Err(ScanError::InvalidResult { .. })));
+    }
+}
@@ -0,0 +1,256 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use tantivy::collector::TopDocs;
+use tantivy::query::QueryParser;
+use tantivy::schema::{Field, Schema, Value, STORED, STRING, TEXT};
+use tantivy::snippet::SnippetGenerator;
+use tantivy::{doc, Index, IndexWriter, TantivyDocument};
+
+use crate::chunk_filter::comment_text;
+use crate::retrieval::terms;
+use crate::{FileEntry, FileSystem, ScanError, ScanResult};
+
+// Files larger than this are indexed by name, summary and exports only
+const MAX_COMMENT_SOURCE: u64 = 1024 * 1024;
+// Comment text kept per file, so one heavily documented file cannot dominate
+const MAX_COMMENT_CHARS: usize = 16 * 1024;
+const SNIPPET_CHARS: usize = 160;
+const WRITER_HEAP: usize = 50_000_000;
+const NAME_BOOST: f32 = 3.0;
+const IDENTIFIER_BOOST: f32 = 2.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    // Relative to the scan root
+    pub path: PathBuf,
+    pub score: f32,
+    // Summary or comment text around the matches; empty when only the name,
+    // exports or symbols matched
+    pub snippet: String,
+    // Byte ranges of `snippet` holding matched terms
+    pub highlights: Vec<Range<usize>>,
+}
+
+#[derive(Clone, Copy)]
+struct SearchFields {
+    path: Field,
+    name: Field,
+    identifiers: Field,
+    body: Field,
+}
+
+// Full-text index over file names, content summaries, purposes, exports, symbol
+// names and comments, stored in a directory so later searches skip the scan
+pub struct SearchIndex {
+    index: Index,
+    fields: SearchFields,
+}
+
+impl SearchIndex {
+    pub const DEFAULT_DIR: &'static str = ".projscan/search";
+
+    // Index directory kept inside the scanned project, next to the history file
+    pub fn dir_for_root(root: &Path) -> PathBuf {
+        root.join(Self::DEFAULT_DIR)
+    }
+
+    // Index directory inside a state directory kept elsewhere, e.g. a CI cache
+    pub fn dir_in(dir: &Path) -> PathBuf {
+        dir.join(Path::new(Self::DEFAULT_DIR).file_name().unwrap_or_default())
+    }
+
+    // Indexes `result` into `dir`, replacing whatever index was there. File
+    // content for comments is read through `fs`.
+    pub fn build(dir: &Path, result: &ScanResult, fs: &dyn FileSystem) -> Result<Self, ScanError> {
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+        fs::create_dir_all(dir)?;
+        let (schema, fields) = schema();
+        let index = Index::create_in_dir(dir, schema).map_err(search_error)?;
+        Self { index, fields }.with_files(result, fs)
+    }
+
+    // Indexes `result` without touching disk
+    pub fn build_in_memory(result: &ScanResult, fs: &dyn FileSystem) -> Result<Self, ScanError> {
+        let (schema, fields) = schema();
+        Self { index: Index::create_in_ram(schema), fields }.with_files(result, fs)
+    }
+
+    pub fn open(dir: &Path) -> Result<Self, ScanError> {
+        let index = Index::open_in_dir(dir).map_err(search_error)?;
+        let schema = index.schema();
+        let field = |name: &str| schema.get_field(name).map_err(search_error);
+        let fields = SearchFields {
+            path: field("path")?,
+            name: field("name")?,
+            identifiers: field("identifiers")?,
+            body: field("body")?,
+        };
+        Ok(Self { index, fields })
+    }
+
+    pub fn len(&self) -> Result<u64, ScanError> {
+        Ok(self.index.reader().map_err(search_error)?.searcher().num_docs())
+    }
+
+    pub fn is_empty(&self) -> Result<bool, ScanError> {
+        Ok(self.len()? == 0)
+    }
+
+    // Best `limit` files for `query`, highest score first. The query uses
+    // tantivy's syntax ("jwt AND validation", "name:auth", quoted phrases);
+    // anything it cannot parse is searched as plain words.
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>, ScanError> {
+        let fields = self.fields;
+        let searcher = self.index.reader().map_err(search_error)?.searcher();
+        let mut parser = QueryParser::for_index(&self.index, vec![fields.name, fields.identifiers, fields.body]);
+        parser.set_field_boost(fields.name, NAME_BOOST);
+        parser.set_field_boost(fields.identifiers, IDENTIFIER_BOOST);
+        let (query, _) = parser.parse_query_lenient(query);
+
+        let top = searcher.search(&query, &TopDocs::with_limit(limit)).map_err(search_error)?;
+        let mut snippets = SnippetGenerator::create(&searcher, &*query, fields.body).map_err(search_error)?;
+        snippets.set_max_num_chars(SNIPPET_CHARS);
+        top.into_iter()
+            .map(|(score, address)| {
+                let document: TantivyDocument = searcher.doc(address).map_err(search_error)?;
+                let path = document.get_first(fields.path).and_then(|value| value.as_str()).unwrap_or_default();
+                let snippet = snippets.snippet_from_doc(&document);
+                Ok(SearchHit {
+                    path: PathBuf::from(path),
+                    score,
+                    snippet: snippet.fragment().to_string(),
+                    highlights: snippet.highlighted().to_vec(),
+                })
+            })
+            .collect()
+    }
+
+    fn with_files(self, result: &ScanResult, fs: &dyn FileSystem) -> Result<Self, ScanError> {
+        let fields = self.fields;
+        let mut writer: IndexWriter = self.index.writer_with_num_threads(1, WRITER_HEAP).map_err(search_error)?;
+        for file in result.files.iter().filter(|file| !file.is_dir) {
+            let path = file.path.strip_prefix(&result.root_path).unwrap_or(&file.path);
+            writer.add_document(doc!(
+                fields.path => path.to_string_lossy().to_string(),
+                fields.name => terms(&path.to_string_lossy()).join(" "),
+                fields.identifiers => identifiers(file),
+                fields.body => body(file, fs),
+            )).map_err(search_error)?;
+        }
+        writer.commit().map_err(search_error)?;
+        Ok(self)
+    }
+}
+
+fn schema() -> (Schema, SearchFields) {
+    let mut builder = Schema::builder();
+    let fields = SearchFields {
+        path: builder.add_text_field("path", STRING | STORED),
+        name: builder.add_text_field("name", TEXT),
+        identifiers: builder.add_text_field("identifiers", TEXT),
+        body: builder.add_text_field("body", TEXT | STORED),
+    };
+    (builder.build(), fields)
+}
+
+// Exports and symbol names split into words, so `validateJwt` matches "jwt"
+fn identifiers(file: &FileEntry) -> String {
+    let Some(info) = &file.enhanced_info else {
+        return String::new();
+    };
+    info.exports.iter()
+        .chain(info.symbols.iter().map(|symbol| &symbol.name))
+        .flat_map(|identifier| terms(identifier))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Summary and purpose, then the file's comment lines
+fn body(file: &FileEntry, fs: &dyn FileSystem) -> String {
+    let mut body: Vec<String> = file.enhanced_info.iter()
+        .flat_map(|info| info.content_summary.iter().chain(&info.purpose))
+        .cloned()
+        .collect();
+    if file.size <= MAX_COMMENT_SOURCE && file.media.is_none() {
+        if let Ok(content) = fs.read_to_string(&file.path) {
+            let mut length = 0;
+            for comment in content.lines().filter_map(comment_text) {
+                length += comment.len();
+                if length > MAX_COMMENT_CHARS {
+                    break;
+                }
+                body.push(comment.to_string());
+            }
+        }
+    }
+    body.join("\n")
+}
+
+fn search_error(e: impl Display) -> ScanError {
+    ScanError::Search { message: e.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+
+    fn project() -> (MemoryFileSystem, ScanResult) {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("api/src/auth.rs", "// Checks the signature and expiry of incoming tokens\npub fn validateJwt(token: &str) -> bool {\n    // JWT validation happens before routing\n    verify(token)\n}\n");
+        fs.insert("api/src/routes.rs", "// Maps URLs to handlers\npub fn register_routes() {}\n");
+        fs.insert("api/src/jwt_keys.rs", "pub const KEYS: &[&str] = &[];\n");
+        let options = ScanOptions { enhanced_analysis: true, ..Default::default() };
+        let result = DirectoryScanner::new(options).scan_with(&fs, "api").unwrap();
+        (fs, result)
+    }
+
+    #[test]
+    fn test_ranks_files_with_snippets() {
+        let (fs, result) = project();
+        let index = SearchIndex::build_in_memory(&result, &fs).unwrap();
+        assert_eq!(index.len().unwrap(), 3);
+
+        let hits = index.search("jwt validation", 10).unwrap();
+        assert_eq!(hits[0].path, Path::new("src/auth.rs"));
+        assert!(hits[0].snippet.contains("JWT validation"), "{:?}", hits[0]);
+        assert!(hits[0].highlights.iter().any(|range| &hits[0].snippet[range.clone()] == "validation"));
+        // The file name alone matches too
+        assert!(hits.iter().any(|hit| hit.path == Path::new("src/jwt_keys.rs")));
+        assert!(index.search("kubernetes", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_matches_split_identifiers_and_comments() {
+        let (fs, result) = project();
+        let index = SearchIndex::build_in_memory(&result, &fs).unwrap();
+
+        assert_eq!(index.search("signature expiry", 10).unwrap()[0].path, Path::new("src/auth.rs"));
+        let hits = index.search("register", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].path, Path::new("src/routes.rs"));
+        // Unbalanced syntax is searched leniently instead of failing
+        assert!(!index.search("handlers AND (", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_persists_in_directory() {
+        let (fs, result) = project();
+        let temp = tempfile::tempdir().unwrap();
+        let dir = SearchIndex::dir_for_root(temp.path());
+        assert!(dir.ends_with(".projscan/search"));
+
+        SearchIndex::build(&dir, &result, &fs).unwrap();
+        // Rebuilding replaces the previous index instead of adding to it
+        SearchIndex::build(&dir, &result, &fs).unwrap();
+        let index = SearchIndex::open(&dir).unwrap();
+        assert_eq!(index.len().unwrap(), 3);
+        assert_eq!(index.search("handlers", 5).unwrap()[0].path, Path::new("src/routes.rs"));
+        assert!(SearchIndex::open(&temp.path().join("missing")).is_err());
+    }
+}
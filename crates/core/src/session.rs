@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use crate::{DirectoryScanner, EnhancedFileInfo, FileEntry, MapperRegistry, ScanError, ScanOptions, ScanResult};
+
+// Content analysis of files seen by earlier scans, reused while a file's size
+// and modification time are unchanged
+#[derive(Debug, Default)]
+pub(crate) struct AnalysisCache {
+    entries: Mutex<HashMap<PathBuf, CachedAnalysis>>,
+}
+
+#[derive(Debug)]
+struct CachedAnalysis {
+    // Mapper profile and caps the analysis was made with
+    config: String,
+    size: u64,
+    modified: SystemTime,
+    info: EnhancedFileInfo,
+    // How long the analysis took, which every hit saves
+    duration: Duration,
+}
+
+// How a scan used the session's analysis cache, so hosts can check that
+// unchanged files really are skipped
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub bytes_reused: u64,
+    pub bytes_reanalyzed: u64,
+    // Spent analyzing the misses
+    pub analysis_ms: f64,
+    // What the hits took to analyze when they were cached
+    pub time_saved_ms: f64,
+}
+
+impl CacheStats {
+    // None when no file was looked up
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
+
+    pub(crate) fn merge(self, other: CacheStats) -> CacheStats {
+        CacheStats {
+            hits: self.hits + other.hits,
+            misses: self.misses + other.misses,
+            bytes_reused: self.bytes_reused + other.bytes_reused,
+            bytes_reanalyzed: self.bytes_reanalyzed + other.bytes_reanalyzed,
+            analysis_ms: self.analysis_ms + other.analysis_ms,
+            time_saved_ms: self.time_saved_ms + other.time_saved_ms,
+        }
+    }
+}
+
+impl CachedAnalysis {
+    fn is_fresh(&self, config: &str, entry: &FileEntry) -> bool {
+        self.config == config && self.size == entry.size && self.modified == entry.modified
+    }
+}
+
+impl AnalysisCache {
+    pub(crate) fn get(&self, config: &str, entry: &FileEntry) -> Option<(EnhancedFileInfo, Duration)> {
+        let entries = self.entries.lock().ok()?;
+        entries.get(&entry.path)
+            .filter(|cached| cached.is_fresh(config, entry))
+            .map(|cached| (cached.info.clone(), cached.duration))
+    }
+
+    pub(crate) fn contains(&self, config: &str, entry: &FileEntry) -> bool {
+        self.entries.lock().is_ok_and(|entries| entries.get(&entry.path).is_some_and(|cached| cached.is_fresh(config, entry)))
+    }
+
+    pub(crate) fn insert(&self, config: &str, entry: &FileEntry, duration: Duration) {
+        let (Some(info), Ok(mut entries)) = (&entry.enhanced_info, self.entries.lock()) else {
+            return;
+        };
+        entries.insert(entry.path.clone(), CachedAnalysis {
+            config: config.to_string(),
+            size: entry.size,
+            modified: entry.modified,
+            info: info.clone(),
+            duration,
+        });
+    }
+
+    fn len(&self) -> usize {
+        self.entries.lock().map_or(0, |entries| entries.len())
+    }
+
+    fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
+
+// What a cached analysis depends on besides the file itself
+pub(crate) fn analysis_config(options: &ScanOptions) -> String {
+    format!(
+        "{}:{}:{:?}:{:?}:{:?}",
+        options.mapper_profile, options.enhanced_analysis, options.complexity_caps, options.languages, options.profile_boosts()
+    )
+}
+
+// State kept between scans by long-running hosts such as watch mode, servers
+// and editors: the mapper registry profiles resolve against, and the content
+// analysis of unchanged files, which is the bulk of an enhanced scan.
+// Relationships and percentiles span the whole result, so they are still
+// computed on every scan.
+#[derive(Default)]
+pub struct ScanSession {
+    registry: MapperRegistry,
+    cache: Arc<AnalysisCache>,
+}
+
+impl ScanSession {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_registry(registry: MapperRegistry) -> Self {
+        Self { registry, cache: Arc::default() }
+    }
+
+    // For registering custom mappers after the session is created
+    pub fn registry_mut(&mut self) -> &mut MapperRegistry {
+        &mut self.registry
+    }
+
+    // A scanner sharing the session's cache, for progress callbacks, `scan_with`
+    // or `start`; unknown profiles are an error as with `with_registry`
+    pub fn scanner(&self, options: ScanOptions) -> Result<DirectoryScanner, ScanError> {
+        Ok(DirectoryScanner::with_registry(options, &self.registry)?.with_cache(Arc::clone(&self.cache)))
+    }
+
+    pub fn scan<P: AsRef<Path>>(&self, options: ScanOptions, path: P) -> Result<ScanResult, ScanError> {
+        self.scanner(options)?.scan(path.as_ref())
+    }
+
+    // Number of files whose analysis is cached
+    pub fn cached_files(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn clear_cache(&self) {
+        self.cache.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryFileSystem;
+    use std::time::Duration;
+
+    fn enhanced() -> ScanOptions {
+        ScanOptions { enhanced_analysis: true, ..Default::default() }
+    }
+
+    fn line_count(result: &ScanResult, path: &str) -> Option<usize> {
+        result.find(path).and_then(|file| file.enhanced_info.as_ref()).and_then(|info| info.line_count)
+    }
+
+    #[test]
+    fn test_reuses_analysis_of_unchanged_files() {
+        let session = ScanSession::new();
+        let mut fs = MemoryFileSystem::new();
+        fs.insert_with_modified("app/main.rs", "fn main() {}\n", SystemTime::UNIX_EPOCH);
+
+        session.scanner(enhanced()).unwrap().scan_with(&fs, "app").unwrap();
+        assert!(session.cached_files() > 0);
+
+        // Same size and time: the stale cached analysis is served
+        fs.insert_with_modified("app/main.rs", "fn x() {}\n\n\n\n", SystemTime::UNIX_EPOCH);
+        let result = session.scanner(enhanced()).unwrap().scan_with(&fs, "app").unwrap();
+        assert_eq!(line_count(&result, "app/main.rs"), Some(1));
+
+        // A new modification time invalidates it
+        fs.insert_with_modified("app/main.rs", "fn x() {}\n\n\n\n", SystemTime::UNIX_EPOCH + Duration::from_secs(1));
+        let result = session.scanner(enhanced()).unwrap().scan_with(&fs, "app").unwrap();
+        assert_eq!(line_count(&result, "app/main.rs"), Some(4));
+    }
+
+    #[test]
+    fn test_reports_cache_stats() {
+        let session = ScanSession::new();
+        let mut fs = MemoryFileSystem::new();
+        fs.insert_with_modified("app/main.rs", "fn main() {}\n", SystemTime::UNIX_EPOCH);
+        fs.insert_with_modified("app/lib.rs", "pub fn lib() {}\n", SystemTime::UNIX_EPOCH);
+
+        let cold = session.scanner(enhanced()).unwrap().scan_with(&fs, "app").unwrap().stats.cache.unwrap();
+        assert_eq!((cold.hits, cold.misses, cold.bytes_reused), (0, 2, 0));
+        assert_eq!(cold.bytes_reanalyzed, 29);
+
+        fs.insert_with_modified("app/lib.rs", "pub fn lib() {}\n\n", SystemTime::UNIX_EPOCH + Duration::from_secs(1));
+        let warm = session.scanner(enhanced()).unwrap().scan_with(&fs, "app").unwrap().stats.cache.unwrap();
+        assert_eq!((warm.hits, warm.misses, warm.bytes_reused, warm.bytes_reanalyzed), (1, 1, 13, 17));
+        assert_eq!(warm.hit_rate(), Some(0.5));
+        assert!(warm.time_saved_ms >= 0.0);
+
+        // Scans outside a session have no cache to report
+        let plain = DirectoryScanner::new(enhanced()).scan_with(&fs, "app").unwrap();
+        assert!(plain.stats.cache.is_none());
+    }
+
+    #[test]
+    fn test_cache_is_keyed_by_analysis_config() {
+        let session = ScanSession::new();
+        let mut fs = MemoryFileSystem::new();
+        fs.insert_with_modified("app/main.rs", "fn main() {}\n", SystemTime::UNIX_EPOCH);
+        session.scanner(enhanced()).unwrap().scan_with(&fs, "app").unwrap();
+
+        let entry = session.scanner(enhanced()).unwrap().scan_with(&fs, "app").unwrap().find("app/main.rs").unwrap().clone();
+        let capped = ScanOptions { complexity_caps: crate::ComplexityCaps { complexity: Some(1.0), branching: None }, ..enhanced() };
+        assert!(session.cache.get(&analysis_config(&enhanced()), &entry).is_some());
+        assert!(session.cache.get(&analysis_config(&capped), &entry).is_none());
+
+        session.clear_cache();
+        assert_eq!(session.cached_files(), 0);
+    }
+
+    #[test]
+    fn test_custom_profiles_resolve_through_the_session_registry() {
+        let mut session = ScanSession::new();
+        assert!(session.scanner(ScanOptions { mapper_profile: "custom".to_string(), ..Default::default() }).is_err());
+
+        session.registry_mut().register("custom", |_| Box::new(crate::GenericMapper));
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/main.rs", "fn main() {}");
+        let options = ScanOptions { mapper_profile: "custom".to_string(), ..Default::default() };
+        let result = session.scanner(options).unwrap().scan_with(&fs, "app").unwrap();
+        assert!(result.find("app/main.rs").is_some());
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use crate::test_mapping::{import_tokens, module_key};
+use crate::FileEntry;
+
+// Number of minimum hashes kept per sketch (bottom-k MinHash)
+const SKETCH_SIZE: usize = 64;
+const SHINGLE_SIZE: usize = 4;
+const MIN_IDENTIFIER_LEN: usize = 3;
+// Hash values shared by more files than this are too common to suggest a relationship
+pub(crate) const MAX_POSTINGS: usize = 200;
+
+const STOP_WORDS: &[&str] = &[
+    "and", "as", "async", "await", "break", "case", "catch", "class", "const", "continue",
+    "def", "default", "else", "elif", "enum", "except", "export", "false", "final", "finally",
+    "for", "from", "func", "function", "if", "impl", "import", "in", "int", "let", "match",
+    "mut", "new", "nil", "none", "not", "null", "or", "package", "pass", "private", "protected",
+    "pub", "public", "return", "self", "static", "str", "string", "struct", "super", "switch",
+    "this", "throw", "trait", "true", "try", "type", "use", "var", "void", "while", "with",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentFingerprint {
+    identifiers: Vec<u64>,
+    shingles: Vec<u64>,
+}
+
+impl ContentFingerprint {
+    pub fn from_content(content: &str) -> Self {
+        let tokens: Vec<String> = content
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .filter(|token| !token.is_empty())
+            .map(|token| token.to_lowercase())
+            .collect();
+
+        let identifiers = tokens.iter()
+            .filter(|token| token.len() >= MIN_IDENTIFIER_LEN)
+            .filter(|token| !token.chars().next().is_some_and(|c| c.is_ascii_digit()))
+            .filter(|token| !STOP_WORDS.contains(&token.as_str()))
+            .map(hash_value)
+            .collect::<HashSet<u64>>();
+
+        let shingles = tokens.windows(SHINGLE_SIZE)
+            .map(|window| hash_value(&window))
+            .collect::<HashSet<u64>>();
+
+        Self {
+            identifiers: bottom_k(identifiers),
+            shingles: bottom_k(shingles),
+        }
+    }
+
+    // Blend of shared-identifier and shingle-overlap similarity in [0, 1]
+    pub fn similarity(&self, other: &ContentFingerprint) -> f64 {
+        let identifier_score = estimate_jaccard(&self.identifiers, &other.identifiers);
+        let shingle_score = estimate_jaccard(&self.shingles, &other.shingles);
+        0.6 * identifier_score + 0.4 * shingle_score
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.identifiers.is_empty() && self.shingles.is_empty()
+    }
+
+    // Sketch values for finding candidates that share identifiers
+    pub(crate) fn identifier_sketch(&self) -> &[u64] {
+        &self.identifiers
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SimilarityIndex {
+    entries: Vec<(PathBuf, ContentFingerprint)>,
+}
+
+impl SimilarityIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, path: PathBuf, content: &str) {
+        self.insert_fingerprint(path, ContentFingerprint::from_content(content));
+    }
+
+    pub fn insert_fingerprint(&mut self, path: PathBuf, fingerprint: ContentFingerprint) {
+        if !fingerprint.is_empty() {
+            self.entries.push((path, fingerprint));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // For every indexed file, the most similar other files scoring at least `min_score`
+    pub fn related(&self, min_score: f64, limit: usize) -> HashMap<PathBuf, Vec<(PathBuf, f64)>> {
+        let mut postings: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (index, (_, fingerprint)) in self.entries.iter().enumerate() {
+            for value in &fingerprint.identifiers {
+                postings.entry(*value).or_default().push(index);
+            }
+        }
+
+        // Only compare pairs that share at least one sketch value
+        let mut candidates: HashSet<(usize, usize)> = HashSet::new();
+        for indices in postings.values().filter(|indices| indices.len() <= MAX_POSTINGS) {
+            for (position, &a) in indices.iter().enumerate() {
+                for &b in &indices[position + 1..] {
+                    candidates.insert((a.min(b), a.max(b)));
+                }
+            }
+        }
+
+        let mut related: HashMap<PathBuf, Vec<(PathBuf, f64)>> = HashMap::new();
+        for (a, b) in candidates {
+            let score = self.entries[a].1.similarity(&self.entries[b].1);
+            if score >= min_score {
+                related.entry(self.entries[a].0.clone()).or_default().push((self.entries[b].0.clone(), score));
+                related.entry(self.entries[b].0.clone()).or_default().push((self.entries[a].0.clone(), score));
+            }
+        }
+
+        for matches in related.values_mut() {
+            matches.sort_by(|x, y| y.1.total_cmp(&x.1).then_with(|| x.0.cmp(&y.0)));
+            matches.truncate(limit);
+        }
+
+        related
+    }
+}
+
+// Populates related_files from resolved imports first, then from content similarity
+pub(crate) fn link_related_files(files: &mut [FileEntry], index: &SimilarityIndex, min_score: f64, limit: usize) {
+    let mut by_key: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for file in files.iter().filter(|file| !file.is_dir) {
+        if let Some(key) = module_key(&file.path) {
+            by_key.entry(key).or_default().push(file.path.clone());
+        }
+    }
+
+    let similar = index.related(min_score, limit);
+
+    for file in files.iter_mut() {
+        let path = file.path.clone();
+        let Some(enhanced) = file.enhanced_info.as_mut() else {
+            continue;
+        };
+
+        let mut related: Vec<PathBuf> = Vec::new();
+        for token in enhanced.imports.iter().flat_map(|import| import_tokens(import)) {
+            for candidate in by_key.get(&token).into_iter().flatten() {
+                if candidate != &path && !related.contains(candidate) {
+                    related.push(candidate.clone());
+                }
+            }
+        }
+        for (candidate, _) in similar.get(&path).into_iter().flatten() {
+            if !related.contains(candidate) {
+                related.push(candidate.clone());
+            }
+        }
+
+        enhanced.related_files = related;
+    }
+}
+
+pub(crate) fn should_fingerprint(path: &Path, size: u64) -> bool {
+    // Large generated or data files add noise and cost without helping clustering
+    size > 0 && size <= 512 * 1024 && path.extension().is_some()
+}
+
+fn hash_value<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn bottom_k(values: HashSet<u64>) -> Vec<u64> {
+    let mut values: Vec<u64> = values.into_iter().collect();
+    values.sort_unstable();
+    values.truncate(SKETCH_SIZE);
+    values
+}
+
+fn estimate_jaccard(a: &[u64], b: &[u64]) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    // Walk the k smallest values of the union and count those present in both sketches
+    let (mut i, mut j, mut seen, mut shared) = (0, 0, 0, 0);
+    while seen < SKETCH_SIZE && (i < a.len() || j < b.len()) {
+        match (a.get(i), b.get(j)) {
+            (Some(x), Some(y)) if x == y => {
+                shared += 1;
+                i += 1;
+                j += 1;
+            }
+            (Some(x), Some(y)) if x < y => i += 1,
+            (Some(_), Some(_)) => j += 1,
+            (Some(_), None) => i += 1,
+            (None, _) => j += 1,
+        }
+        seen += 1;
+    }
+
+    shared as f64 / seen as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARSER_RS: &str = r#"
+        pub fn parse_tokens(tokens: &[Token]) -> Result<SyntaxTree, ParseError> {
+            let mut cursor = TokenCursor::new(tokens);
+            while let Some(token) = cursor.next_token() {
+                tree_builder.push_node(token.kind, token.span);
+            }
+            tree_builder.finish()
+        }
+    "#;
+
+    const PARSER_TS: &str = r#"
+        export function parseTokens(tokens: Token[]): SyntaxTree {
+            const cursor = new TokenCursor(tokens);
+            let token = cursor.next_token();
+            while (token) {
+                tree_builder.push_node(token.kind, token.span);
+                token = cursor.next_token();
+            }
+            return tree_builder.finish();
+        }
+    "#;
+
+    const MAILER_PY: &str = r#"
+        def send_invoice_email(customer, invoice):
+            message = compose_message(customer.email_address, invoice.total_amount)
+            smtp_connection.deliver(message)
+    "#;
+
+    #[test]
+    fn test_identical_content_is_fully_similar() {
+        let a = ContentFingerprint::from_content(PARSER_RS);
+        let b = ContentFingerprint::from_content(PARSER_RS);
+
+        assert!((a.similarity(&b) - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_cross_language_files_share_identifiers() {
+        let rust = ContentFingerprint::from_content(PARSER_RS);
+        let typescript = ContentFingerprint::from_content(PARSER_TS);
+        let python = ContentFingerprint::from_content(MAILER_PY);
+
+        assert!(rust.similarity(&typescript) > 0.3);
+        assert!(rust.similarity(&typescript) > rust.similarity(&python));
+        assert!(rust.similarity(&python) < 0.1);
+    }
+
+    #[test]
+    fn test_index_reports_related_pairs_symmetrically() {
+        let mut index = SimilarityIndex::new();
+        index.insert(PathBuf::from("src/parser.rs"), PARSER_RS);
+        index.insert(PathBuf::from("web/parser.ts"), PARSER_TS);
+        index.insert(PathBuf::from("jobs/mailer.py"), MAILER_PY);
+
+        let related = index.related(0.3, 5);
+
+        assert_eq!(related[&PathBuf::from("src/parser.rs")][0].0, PathBuf::from("web/parser.ts"));
+        assert_eq!(related[&PathBuf::from("web/parser.ts")][0].0, PathBuf::from("src/parser.rs"));
+        assert!(!related.contains_key(&PathBuf::from("jobs/mailer.py")));
+    }
+}
@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use crate::{FileCommit, FileEntry, ScanResult};
+
+// Files first committed this recently are new
+const NEW_WITHIN: Duration = Duration::from_secs(30 * 86_400);
+// Files committed to at least ACTIVE_COMMITS times this recently are active
+const ACTIVE_WITHIN: Duration = Duration::from_secs(90 * 86_400);
+const ACTIVE_COMMITS: usize = 2;
+// Importance multipliers applied by `weigh_importance_by_stability`
+const STABLE_WEIGHT: f64 = 1.2;
+const NEW_WEIGHT: f64 = 0.8;
+
+// How settled a file is by its git history. Ordered most settled first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stability {
+    Stable,
+    Active,
+    New,
+}
+
+impl fmt::Display for Stability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Stability::Stable => "stable",
+            Stability::Active => "active",
+            Stability::New => "new",
+        })
+    }
+}
+
+// The git history behind a file's stability class
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileStability {
+    pub class: Stability,
+    #[serde(with = "crate::timestamp::required")]
+    pub first_commit: SystemTime,
+    #[serde(with = "crate::timestamp::required")]
+    pub last_commit: SystemTime,
+    pub commits: usize,
+    // Commits within the last 90 days
+    pub recent_commits: usize,
+}
+
+impl FileStability {
+    // New when first committed within the last 30 days, active when committed
+    // to twice or more within the last 90, otherwise stable. Commits dated
+    // after `now` count as recent.
+    fn from_history(commits: &[FileCommit], now: SystemTime) -> Option<Self> {
+        let within = |time: SystemTime, window: Duration| now.duration_since(time).map_or(true, |age| age < window);
+        let first_commit = commits.iter().map(|commit| commit.time).min()?;
+        let last_commit = commits.iter().map(|commit| commit.time).max()?;
+        let recent_commits = commits.iter().filter(|commit| within(commit.time, ACTIVE_WITHIN)).count();
+        let class = if within(first_commit, NEW_WITHIN) {
+            Stability::New
+        } else if recent_commits >= ACTIVE_COMMITS {
+            Stability::Active
+        } else {
+            Stability::Stable
+        };
+        Some(Self { class, first_commit, last_commit, commits: commits.len(), recent_commits })
+    }
+}
+
+// Files with known stability first, most settled first; the rest keep their order at the end
+pub(crate) fn stability_rank(file: &FileEntry) -> usize {
+    file.enhanced_info.as_ref().and_then(|info| info.stability.as_ref()).map_or(3, |stability| stability.class as usize)
+}
+
+impl ScanResult {
+    // Classifies every analyzed file from its git history, keyed by paths
+    // relative to the root as `git_file_history` returns them, judged against
+    // `now`. Sets `stability`, `change_frequency` (the class name) and
+    // `last_author`. Files without history, such as untracked ones, are left
+    // alone. Returns the number of files classified.
+    pub fn assign_stability(&mut self, history: &HashMap<PathBuf, Vec<FileCommit>>, now: SystemTime) -> usize {
+        let mut classified = 0;
+        for file in self.files.iter_mut().filter(|file| !file.is_dir) {
+            let relative = file.path.strip_prefix(&self.root_path).unwrap_or(&file.path);
+            let Some(commits) = history.get(relative) else { continue };
+            let (Some(info), Some(stability)) = (file.enhanced_info.as_mut(), FileStability::from_history(commits, now)) else { continue };
+            info.change_frequency = Some(stability.class.to_string());
+            info.last_author = commits.iter().max_by_key(|commit| commit.time).map(|commit| commit.author.clone());
+            info.stability = Some(stability);
+            classified += 1;
+        }
+        classified
+    }
+
+    // Raises the importance of stable files and lowers that of new ones, so
+    // rankings and selections favour code that has settled. Call after
+    // `assign_stability`.
+    pub fn weigh_importance_by_stability(&mut self) {
+        for info in self.files.iter_mut().filter_map(|file| file.enhanced_info.as_mut()) {
+            let weight = match info.stability.as_ref().map(|stability| stability.class) {
+                Some(Stability::Stable) => STABLE_WEIGHT,
+                Some(Stability::New) => NEW_WEIGHT,
+                _ => continue,
+            };
+            if let Some(importance) = info.importance_score.as_mut() {
+                *importance = (*importance * weight).min(10.0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+    use std::time::UNIX_EPOCH;
+
+    const DAY: u64 = 86_400;
+
+    fn now() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1000 * DAY)
+    }
+
+    fn commits(days_ago: &[u64]) -> Vec<FileCommit> {
+        days_ago.iter().enumerate().map(|(index, days)| FileCommit {
+            commit: index.to_string(),
+            author: format!("author{}", index),
+            time: now() - Duration::from_secs(days * DAY),
+            subject: String::new(),
+        }).collect()
+    }
+
+    fn scan() -> ScanResult {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/src/old.rs", "pub fn old() {}\n");
+        fs.insert("app/src/busy.rs", "pub fn busy() {}\n");
+        fs.insert("app/src/fresh.rs", "pub fn fresh() {}\n");
+        fs.insert("app/src/untracked.rs", "pub fn untracked() {}\n");
+        let options = ScanOptions { enhanced_analysis: true, ..Default::default() };
+        DirectoryScanner::new(options).scan_with(&fs, "app").unwrap()
+    }
+
+    fn class(result: &ScanResult, path: &str) -> Option<Stability> {
+        result.find(path)?.enhanced_info.as_ref()?.stability.as_ref().map(|stability| stability.class)
+    }
+
+    #[test]
+    fn test_classifies_by_age_and_recent_churn() {
+        let history = HashMap::from([
+            // One touch-up in the last 90 days leaves an old file stable
+            (PathBuf::from("src/old.rs"), commits(&[400, 300, 10])),
+            (PathBuf::from("src/busy.rs"), commits(&[400, 60, 5])),
+            (PathBuf::from("src/fresh.rs"), commits(&[20, 3])),
+        ]);
+        let mut result = scan();
+
+        assert_eq!(result.assign_stability(&history, now()), 3);
+        assert_eq!(class(&result, "app/src/old.rs"), Some(Stability::Stable));
+        assert_eq!(class(&result, "app/src/busy.rs"), Some(Stability::Active));
+        assert_eq!(class(&result, "app/src/fresh.rs"), Some(Stability::New));
+        assert_eq!(class(&result, "app/src/untracked.rs"), None);
+
+        let info = result.find("app/src/old.rs").unwrap().enhanced_info.as_ref().unwrap();
+        assert_eq!(info.change_frequency.as_deref(), Some("stable"));
+        assert_eq!(info.last_author.as_deref(), Some("author2"));
+        assert_eq!(info.stability.as_ref().map(|stability| (stability.commits, stability.recent_commits)), Some((3, 1)));
+    }
+
+    #[test]
+    fn test_importance_favours_stable_files() {
+        let history = HashMap::from([
+            (PathBuf::from("src/old.rs"), commits(&[400])),
+            (PathBuf::from("src/fresh.rs"), commits(&[2])),
+        ]);
+        let mut result = scan();
+        result.assign_stability(&history, now());
+        let importance = |result: &ScanResult, path: &str| result.find(path).unwrap().enhanced_info.as_ref().unwrap().importance_score.unwrap();
+        let before = (importance(&result, "app/src/old.rs"), importance(&result, "app/src/fresh.rs"), importance(&result, "app/src/untracked.rs"));
+
+        result.weigh_importance_by_stability();
+        assert!(importance(&result, "app/src/old.rs") > before.0);
+        assert!(importance(&result, "app/src/fresh.rs") < before.1);
+        assert_eq!(importance(&result, "app/src/untracked.rs"), before.2);
+    }
+
+    #[test]
+    fn test_round_trips_through_json() {
+        let mut result = scan();
+        result.assign_stability(&HashMap::from([(PathBuf::from("src/busy.rs"), commits(&[40, 1]))]), now());
+        let stability = result.find("app/src/busy.rs").unwrap().enhanced_info.as_ref().unwrap().stability.clone().unwrap();
+
+        let json = serde_json::to_value(&stability).unwrap();
+        assert_eq!(json["class"], "active");
+        assert_eq!(json["last_commit"], "1972-09-26T00:00:00.000Z");
+        assert_eq!(serde_json::from_value::<FileStability>(json).unwrap(), stability);
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::layout::language_of;
+use crate::{ContentAnalyzer, Distribution, FileEntry, FormatOptions, HistogramBucket, OutputFormatter, Palette, ScanResult};
+
+// Aggregates only, no per-file listing beyond the few worst offenders, so a
+// large repo still fits on one screen
+const BAR_WIDTH: usize = 30;
+const TOP_OFFENDERS: usize = 5;
+
+pub(crate) fn format_summary(result: &ScanResult, options: &FormatOptions) -> String {
+    let palette = &options.palette;
+    let files: Vec<&FileEntry> = result.files.iter().filter(|file| !file.is_dir).collect();
+    let dirs = result.files.len() - files.len();
+    let total_size: u64 = files.iter().map(|file| file.size).sum();
+
+    let mut output = String::new();
+    let _ = writeln!(output, "{} files, {} directories, {}, {} issues",
+        files.len(), dirs, OutputFormatter::format_size(total_size), result.errors.len());
+
+    if let Some(sampling) = &result.stats.sampling {
+        let lines = vec![
+            format!("{} of {} files analyzed ({:.0}%) in {} groups",
+                sampling.analyzed, sampling.files, sampling.analyzed_share() * 100.0, sampling.groups),
+            format!("{:<24} {:>10.0}  ({:.0} to {:.0})", "lines", sampling.total_lines.value, sampling.total_lines.low, sampling.total_lines.high),
+            format!("{:<24} {:>10.2}  ({:.2} to {:.2})", "complexity per file", sampling.mean_complexity.value, sampling.mean_complexity.low, sampling.mean_complexity.high),
+        ];
+        section(&mut output, palette, "Sampled estimates (95% intervals)", lines);
+    }
+
+    let mut tags: HashMap<&str, usize> = HashMap::new();
+    for tag in files.iter().flat_map(|file| &file.tags) {
+        *tags.entry(tag.as_str()).or_default() += 1;
+    }
+    section(&mut output, palette, "Tags", counted(tags.into_iter().map(|(tag, count)| (tag.to_string(), count)).collect()));
+
+    let analyzer = ContentAnalyzer::new();
+    let mut languages: HashMap<String, usize> = HashMap::new();
+    for file in &files {
+        *languages.entry(language_of(file, &analyzer)).or_default() += 1;
+    }
+    section(&mut output, palette, "Languages", counted(languages.into_iter().collect()));
+
+    let distribution = Distribution::from_files(&result.root_path, files.iter().copied());
+    section(&mut output, palette, "File sizes", histogram(&distribution.sizes));
+
+    if !distribution.complexity.is_empty() {
+        section(&mut output, palette, "Complexity", histogram(&distribution.complexity));
+
+        let offenders = result.top_by(|file| file.enhanced_info.as_ref().and_then(|info| info.complexity_score), TOP_OFFENDERS);
+        let lines = offenders.iter()
+            .map(|file| {
+                let score = file.enhanced_info.as_ref().and_then(|info| info.complexity_score);
+                format!("{:>6.1}  {}", score.unwrap_or_default(), palette.by_complexity(score, &options.paths.render(&file.path, &result.root_path)))
+            })
+            .collect();
+        section(&mut output, palette, "Most complex", lines);
+    }
+
+    let largest = result.top_by(|file| (!file.is_dir).then_some(file.size as f64), TOP_OFFENDERS);
+    let lines = largest.iter()
+        .map(|file| format!("{:>8}  {}", OutputFormatter::format_size(file.size), options.paths.render(&file.path, &result.root_path)))
+        .collect();
+    section(&mut output, palette, "Largest", lines);
+    output
+}
+
+fn section(output: &mut String, palette: &Palette, title: &str, lines: Vec<String>) {
+    if lines.is_empty() {
+        return;
+    }
+    let _ = writeln!(output, "\n{}", palette.bold(title));
+    for line in lines {
+        let _ = writeln!(output, "  {}", line);
+    }
+}
+
+// Most frequent first, ties by name
+fn counted(mut counts: Vec<(String, usize)>) -> Vec<String> {
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.into_iter().map(|(name, count)| format!("{:<24} {:>6}", name, count)).collect()
+}
+
+fn histogram(buckets: &[HistogramBucket]) -> Vec<String> {
+    let max = buckets.iter().map(|bucket| bucket.count).max().unwrap_or(0).max(1);
+    buckets.iter()
+        .map(|bucket| {
+            format!("{:<10} {:>6}  {}", bucket.label, bucket.count, "█".repeat(bucket.count * BAR_WIDTH / max)).trim_end().to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PathStyle, ScanStats};
+    use std::path::{Path, PathBuf};
+
+    fn result(files: &[(&str, u64, Option<f64>, &[&str])]) -> ScanResult {
+        ScanResult {
+            schema_version: crate::SCHEMA_VERSION,
+            root_path: PathBuf::from("proj"),
+            files: files.iter()
+                .map(|(path, size, complexity, tags)| {
+                    FileEntry::test(Path::new("proj").join(path)).with_size(*size).with_tags(tags).with_complexity(*complexity)
+                })
+                .collect(),
+            stats: ScanStats { total_files: files.len(), total_dirs: 0, total_size: 0, scan_duration_ms: 0, files_per_second: 0.0, directories: Default::default(), distribution: Default::default(), cache: None, sampling: None, resources: None },
+            roots: Vec::new(),
+            errors: Vec::new(),
+            findings: Vec::new(),
+            test_coverage_structure: None,
+            entry_points: Vec::new(),
+            endpoints: Vec::new(),
+            schema: None,
+            containers: Vec::new(),
+            nested_repositories: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_summary_counts_tags_and_languages() {
+        let summary = format_summary(&result(&[
+            ("src/lib.rs", 2048, None, &["source"]),
+            ("src/main.rs", 100, None, &["source", "entrypoint"]),
+            ("README.md", 10, None, &["documentation"]),
+        ]), &FormatOptions { paths: PathStyle::relative(), ..Default::default() });
+
+        assert!(summary.starts_with("3 files, 0 directories, 2.1KB, 0 issues\n"));
+        assert!(summary.contains(&format!("  {:<24} {:>6}\n", "source", 2)));
+        assert!(summary.contains(&format!("  {:<24} {:>6}\n", "rust", 2)));
+        assert!(summary.contains(&format!("  {:<10} {:>6}  {}\n", "< 1KB", 2, "█".repeat(BAR_WIDTH))));
+        assert!(!summary.contains("Complexity"));
+    }
+
+    #[test]
+    fn test_summary_lists_worst_offenders() {
+        let summary = format_summary(&result(&[
+            ("a.rs", 10, Some(1.0), &[]),
+            ("b.rs", 20, Some(9.5), &[]),
+            ("c.rs", 30, Some(3.0), &[]),
+        ]), &FormatOptions { paths: PathStyle::relative(), ..Default::default() });
+
+        assert!(summary.contains(&format!("  {:<10} {:>6}  {}\n", "8-10", 1, "█".repeat(BAR_WIDTH))));
+        let complex = summary.split("Most complex\n").nth(1).unwrap();
+        assert!(complex.starts_with("     9.5  b.rs\n     3.0  c.rs\n     1.0  a.rs\n"));
+        assert!(summary.contains("Largest\n       30B  c.rs\n"));
+    }
+
+    #[test]
+    fn test_summary_shows_sampled_estimates() {
+        let mut sampled = result(&[("a.rs", 10, Some(2.0), &["source"])]);
+        let estimate = |value: f64| crate::Estimate { value, low: value * 0.9, high: value * 1.1 };
+        sampled.stats.sampling = Some(crate::SamplingSummary {
+            files: 40,
+            analyzed: 10,
+            groups: 3,
+            total_lines: estimate(1200.0),
+            total_complexity: estimate(80.0),
+            mean_complexity: estimate(2.0),
+        });
+        let output = format_summary(&sampled, &FormatOptions::default());
+
+        assert!(output.contains("10 of 40 files analyzed (25%) in 3 groups"), "{}", output);
+        assert!(output.contains("1200  (1080 to 1320)"), "{}", output);
+        assert!(!format_summary(&result(&[]), &FormatOptions::default()).contains("Sampled"));
+    }
+
+    #[test]
+    fn test_summary_of_empty_result() {
+        let summary = format_summary(&result(&[]), &FormatOptions { paths: PathStyle::relative(), ..Default::default() });
+
+        assert!(summary.starts_with("0 files, 0 directories, 0B, 0 issues\n"));
+        assert!(!summary.contains("Largest"));
+    }
+}
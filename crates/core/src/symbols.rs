@@ -0,0 +1,268 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::FileEntry;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: String,
+    pub line: usize,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolLocation {
+    pub path: PathBuf,
+    pub line: usize,
+    pub kind: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SymbolIndex {
+    pub symbols: BTreeMap<String, Vec<SymbolLocation>>,
+}
+
+impl SymbolIndex {
+    pub fn build(files: &[FileEntry]) -> Self {
+        let mut index = Self::default();
+        for file in files {
+            if let Some(enhanced) = &file.enhanced_info {
+                for symbol in &enhanced.symbols {
+                    index.symbols.entry(symbol.name.clone()).or_default().push(SymbolLocation {
+                        path: file.path.clone(),
+                        line: symbol.line,
+                        kind: symbol.kind.clone(),
+                        signature: symbol.signature.clone(),
+                    });
+                }
+            }
+        }
+        index
+    }
+
+    pub fn lookup(&self, name: &str) -> &[SymbolLocation] {
+        self.symbols.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    // Case-insensitive substring search over symbol names
+    pub fn search(&self, query: &str) -> Vec<(&str, &SymbolLocation)> {
+        let query = query.to_lowercase();
+        self.symbols.iter()
+            .filter(|(name, _)| name.to_lowercase().contains(&query))
+            .flat_map(|(name, locations)| locations.iter().map(move |location| (name.as_str(), location)))
+            .collect()
+    }
+
+    // Every file's symbols in line order, for per-file listings such as the repo map
+    pub fn by_file(&self) -> BTreeMap<&Path, Vec<&SymbolLocation>> {
+        let mut files: BTreeMap<&Path, Vec<&SymbolLocation>> = BTreeMap::new();
+        for location in self.symbols.values().flatten() {
+            files.entry(location.path.as_path()).or_default().push(location);
+        }
+        for locations in files.values_mut() {
+            locations.sort_by_key(|location| location.line);
+        }
+        files
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    // Tags file in the classic ctags format: name<TAB>path<TAB>line;"<TAB>kind
+    pub fn to_ctags(&self) -> String {
+        let mut output = String::from("!_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted/\n");
+        for (name, locations) in &self.symbols {
+            for location in locations {
+                output.push_str(&format!("{}\t{}\t{};\"\t{}\n",
+                    name, location.path.display(), location.line, location.kind));
+            }
+        }
+        output
+    }
+}
+
+pub fn extract_symbols(content: &str, language: &Option<String>) -> Vec<Symbol> {
+    let Some(language) = language.as_deref() else {
+        return Vec::new();
+    };
+    
+    let mut symbols = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        let top_level = !line.starts_with(char::is_whitespace);
+        
+        let found = match language {
+            "rust" => rust_symbol(trimmed),
+            "javascript" | "typescript" => js_symbol(trimmed),
+            "python" if top_level => python_symbol(trimmed),
+            "go" if top_level => go_symbol(trimmed),
+            "java" => java_symbol(trimmed),
+            _ => None,
+        };
+        
+        if let Some((kind, name)) = found {
+            symbols.push(Symbol {
+                name,
+                kind: kind.to_string(),
+                line: index + 1,
+                signature: trimmed.trim_end_matches(['{', ':']).trim_end().to_string(),
+            });
+        }
+    }
+    
+    symbols
+}
+
+fn leading_identifier(text: &str) -> Option<String> {
+    let name: String = text.trim_start()
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+fn rust_symbol(line: &str) -> Option<(&'static str, String)> {
+    let rest = line.strip_prefix("pub ")?;
+    let rest = rest.strip_prefix("async ").unwrap_or(rest);
+    let rest = rest.strip_prefix("unsafe ").unwrap_or(rest);
+    const KINDS: &[(&str, &str)] = &[
+        ("fn ", "function"), ("struct ", "struct"), ("enum ", "enum"), ("trait ", "trait"),
+        ("type ", "type"), ("const ", "constant"), ("static ", "constant"), ("mod ", "module"),
+    ];
+    KINDS.iter().find_map(|(prefix, kind)| {
+        rest.strip_prefix(prefix).and_then(leading_identifier).map(|name| (*kind, name))
+    })
+}
+
+fn js_symbol(line: &str) -> Option<(&'static str, String)> {
+    let rest = line.strip_prefix("export ")?;
+    let rest = rest.strip_prefix("default ").unwrap_or(rest);
+    let rest = rest.strip_prefix("async ").unwrap_or(rest);
+    const KINDS: &[(&str, &str)] = &[
+        ("function* ", "function"), ("function ", "function"), ("class ", "class"),
+        ("interface ", "interface"), ("type ", "type"), ("enum ", "enum"),
+        ("const ", "constant"), ("let ", "variable"), ("var ", "variable"),
+    ];
+    KINDS.iter().find_map(|(prefix, kind)| {
+        rest.strip_prefix(prefix).and_then(leading_identifier).map(|name| (*kind, name))
+    })
+}
+
+fn python_symbol(line: &str) -> Option<(&'static str, String)> {
+    let rest = line.strip_prefix("async ").unwrap_or(line);
+    let (kind, name) = if let Some(rest) = rest.strip_prefix("def ") {
+        ("function", leading_identifier(rest)?)
+    } else if let Some(rest) = rest.strip_prefix("class ") {
+        ("class", leading_identifier(rest)?)
+    } else {
+        return None;
+    };
+    // Leading underscore marks a name as private by convention
+    (!name.starts_with('_')).then_some((kind, name))
+}
+
+fn go_symbol(line: &str) -> Option<(&'static str, String)> {
+    let (kind, name) = if let Some(rest) = line.strip_prefix("func ") {
+        // Skip the receiver of methods: func (s *Server) Start()
+        let rest = if rest.starts_with('(') {
+            rest.split_once(')').map(|(_, after)| after)?
+        } else {
+            rest
+        };
+        ("function", leading_identifier(rest)?)
+    } else if let Some(rest) = line.strip_prefix("type ") {
+        ("type", leading_identifier(rest)?)
+    } else {
+        return None;
+    };
+    // Only capitalized names are exported in Go
+    name.starts_with(char::is_uppercase).then_some((kind, name))
+}
+
+fn java_symbol(line: &str) -> Option<(&'static str, String)> {
+    if !line.starts_with("public ") {
+        return None;
+    }
+    let words: Vec<&str> = line.split_whitespace().collect();
+    for (position, word) in words.iter().enumerate() {
+        let kind = match *word {
+            "class" => "class",
+            "interface" => "interface",
+            "enum" => "enum",
+            "record" => "record",
+            _ => continue,
+        };
+        return words.get(position + 1).and_then(|next| leading_identifier(next)).map(|name| (kind, name));
+    }
+    // Methods: the identifier directly before the opening parenthesis
+    let before_paren = line.split_once('(')?.0;
+    let name = before_paren.split_whitespace().last()?;
+    leading_identifier(name).map(|name| ("method", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lang(name: &str) -> Option<String> {
+        Some(name.to_string())
+    }
+
+    #[test]
+    fn test_extracts_rust_public_items_with_lines() {
+        let content = "use std::fmt;\n\npub struct Scanner {\n}\n\nfn private() {}\n\npub async fn scan(path: &str) -> Result<(), Error> {\n}\n";
+        
+        let symbols = extract_symbols(content, &lang("rust"));
+        
+        let names: Vec<(&str, usize)> = symbols.iter().map(|s| (s.name.as_str(), s.line)).collect();
+        assert_eq!(names, vec![("Scanner", 3), ("scan", 8)]);
+        assert_eq!(symbols[1].kind, "function");
+        assert_eq!(symbols[1].signature, "pub async fn scan(path: &str) -> Result<(), Error>");
+    }
+
+    #[test]
+    fn test_extracts_symbols_for_other_languages() {
+        let typescript = "export default class Router {}\nexport const routes = [];\nconst hidden = 1;\n";
+        let python = "class Service:\n    def method(self):\n        pass\ndef _helper():\n    pass\ndef run():\n    pass\n";
+        let go = "func (s *Server) Start() error {\n}\nfunc helper() {}\ntype Config struct {}\n";
+        let java = "public class UserService {\n    public User findUser(String id) {\n    }\n}\n";
+        
+        let names = |symbols: Vec<Symbol>| symbols.into_iter().map(|s| s.name).collect::<Vec<_>>();
+        assert_eq!(names(extract_symbols(typescript, &lang("typescript"))), vec!["Router", "routes"]);
+        assert_eq!(names(extract_symbols(python, &lang("python"))), vec!["Service", "run"]);
+        assert_eq!(names(extract_symbols(go, &lang("go"))), vec!["Start", "Config"]);
+        assert_eq!(names(extract_symbols(java, &lang("java"))), vec!["UserService", "findUser"]);
+    }
+
+    #[test]
+    fn test_index_aggregates_across_files() {
+        let entry = |path: &str, symbols: Vec<Symbol>| FileEntry::test(path).with_info(|info| info.symbols = symbols);
+        let files = vec![
+            entry("a.rs", extract_symbols("pub fn parse() {}\npub struct Token;", &lang("rust"))),
+            entry("b.ts", extract_symbols("export function parse() {}", &lang("typescript"))),
+        ];
+        
+        let index = SymbolIndex::build(&files);
+        
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.lookup("parse").len(), 2);
+        assert_eq!(index.search("TOK")[0].0, "Token");
+        assert!(index.to_ctags().contains("Token\ta.rs\t2;\"\tstruct\n"));
+        assert!(index.to_json().unwrap().contains("\"parse\""));
+        let by_file = index.by_file();
+        let lines: Vec<(&str, usize)> = by_file[Path::new("a.rs")].iter().map(|location| (location.signature.as_str(), location.line)).collect();
+        assert_eq!(lines, [("pub fn parse() {}", 1), ("pub struct Token;", 2)]);
+    }
+}
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+// Where a symbolic link points. `target` is the link text as stored, so a
+// relative target stays relative to the link's directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymlinkInfo {
+    pub target: PathBuf,
+    pub resolves: bool,
+    pub outside_root: bool,
+}
+
+impl SymlinkInfo {
+    pub fn is_broken(&self) -> bool {
+        !self.resolves
+    }
+}
+
+// Reads the link at `link`; `root` should already be canonical. A broken link
+// is placed inside or outside the root by its target path alone.
+pub(crate) fn inspect(link: &Path, root: &Path) -> io::Result<SymlinkInfo> {
+    let target = fs::read_link(link)?;
+    let (resolves, resolved) = match fs::canonicalize(link) {
+        Ok(resolved) => (true, resolved),
+        Err(_) => {
+            let parent = link.parent().map(fs::canonicalize).transpose()?.unwrap_or_default();
+            (false, normalize(&parent.join(&target)))
+        }
+    };
+    Ok(SymlinkInfo { target, resolves, outside_root: !resolved.starts_with(root) })
+}
+
+// Resolves `.` and `..` without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    fn root() -> (TempDir, PathBuf) {
+        let dir = TempDir::new().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "fn main() {}").unwrap();
+        (dir, root)
+    }
+
+    #[test]
+    fn test_resolving_link_inside_root() {
+        let (_dir, root) = root();
+        symlink("src/lib.rs", root.join("lib.rs")).unwrap();
+
+        let info = inspect(&root.join("lib.rs"), &root).unwrap();
+        assert_eq!(info, SymlinkInfo { target: PathBuf::from("src/lib.rs"), resolves: true, outside_root: false });
+    }
+
+    #[test]
+    fn test_broken_links_placed_by_target_path() {
+        let (_dir, root) = root();
+        symlink("../gone.rs", root.join("src/gone.rs")).unwrap();
+        symlink("../../elsewhere.rs", root.join("src/elsewhere.rs")).unwrap();
+
+        let inside = inspect(&root.join("src/gone.rs"), &root).unwrap();
+        assert!(inside.is_broken() && !inside.outside_root);
+        let outside = inspect(&root.join("src/elsewhere.rs"), &root).unwrap();
+        assert!(outside.is_broken() && outside.outside_root);
+    }
+
+    #[test]
+    fn test_normalizes_parent_components() {
+        assert_eq!(normalize(Path::new("/a/b/../c/./d")), PathBuf::from("/a/c/d"));
+    }
+}
@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::FileEntry;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TestCoverageStructure {
+    pub test_files: usize,
+    pub source_files: usize,
+    pub covered_source_files: usize,
+    pub mappings: Vec<TestMapping>,
+    pub untested_sources: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestMapping {
+    pub test_file: PathBuf,
+    pub source_files: Vec<PathBuf>,
+}
+
+// File stems that say nothing about the module they belong to
+const GENERIC_STEMS: &[&str] = &["mod", "index", "lib", "main", "__init__"];
+
+// Words marking a test file when they start or end its stem at a word
+// boundary: `test_parser`, `parser-spec`, `ParserTest`, but not `contest`
+const TEST_WORDS: &[&str] = &["tests", "test", "spec"];
+
+const WORD_SEPARATORS: &[char] = &['_', '-', '.'];
+
+impl TestCoverageStructure {
+    pub fn build(files: &[FileEntry]) -> Self {
+        let is_test = |file: &FileEntry| file.tags.iter().any(|tag| tag == "test");
+        let is_source = |file: &FileEntry| file.tags.iter().any(|tag| tag == "source");
+
+        let tests: Vec<&FileEntry> = files.iter()
+            .filter(|file| !file.is_dir && is_test(file))
+            .collect();
+        let sources: Vec<&FileEntry> = files.iter()
+            .filter(|file| !file.is_dir && is_source(file) && !is_test(file))
+            .collect();
+
+        // Index sources by module key so tests can be resolved without a linear search
+        let mut by_key: HashMap<String, Vec<&Path>> = HashMap::new();
+        for source in &sources {
+            if let Some(key) = module_key(&source.path) {
+                by_key.entry(key).or_default().push(&source.path);
+            }
+        }
+
+        let mut covered: HashSet<&Path> = HashSet::new();
+        let mut mappings = Vec::new();
+
+        for test in &tests {
+            let mut keys = Vec::new();
+            if let Some(key) = tested_module_key(&test.path) {
+                keys.push(key);
+            }
+            if let Some(enhanced) = &test.enhanced_info {
+                for import in &enhanced.imports {
+                    keys.extend(import_tokens(import));
+                }
+            }
+
+            let mut matched: Vec<&Path> = Vec::new();
+            for key in keys {
+                if let Some(candidates) = by_key.get(&key) {
+                    for candidate in Self::closest_candidates(&test.path, candidates) {
+                        if !matched.contains(&candidate) {
+                            matched.push(candidate);
+                        }
+                    }
+                }
+            }
+
+            covered.extend(matched.iter().copied());
+            mappings.push(TestMapping {
+                test_file: test.path.clone(),
+                source_files: matched.into_iter().map(Path::to_path_buf).collect(),
+            });
+        }
+
+        let untested_sources = sources.iter()
+            .filter(|source| !covered.contains(source.path.as_path()))
+            .map(|source| source.path.clone())
+            .collect();
+
+        Self {
+            test_files: tests.len(),
+            source_files: sources.len(),
+            covered_source_files: covered.len(),
+            mappings,
+            untested_sources,
+        }
+    }
+
+    // Prefer the candidates sharing the longest path prefix with the test file
+    fn closest_candidates<'a>(test_path: &Path, candidates: &[&'a Path]) -> Vec<&'a Path> {
+        let shared = |candidate: &Path| {
+            test_path.components()
+                .zip(candidate.components())
+                .take_while(|(a, b)| a == b)
+                .count()
+        };
+        let best = candidates.iter().map(|candidate| shared(candidate)).max().unwrap_or(0);
+        candidates.iter()
+            .filter(|candidate| shared(candidate) == best)
+            .copied()
+            .collect()
+    }
+}
+
+pub(crate) fn module_key(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?.to_lowercase();
+    // Strip secondary extensions such as `.test` in `foo.test.ts`
+    let stem = stem.split('.').next().unwrap_or(&stem).to_string();
+    if GENERIC_STEMS.contains(&stem.as_str()) {
+        return path.parent()
+            .and_then(|parent| parent.file_name())
+            .and_then(|name| name.to_str())
+            .map(|name| name.to_lowercase());
+    }
+    Some(stem)
+}
+
+// The module key of the file a test is named after, e.g. `parser` for
+// `test_parser.py`, `parser_test.go`, `ParserTest.java` or `parser.test.ts`
+fn tested_module_key(path: &Path) -> Option<String> {
+    let key = module_key(path)?;
+    // The stem in its original case, so camel-case boundaries can be seen;
+    // generic stems were replaced by their directory's name
+    let stem = path.file_stem()?.to_str()?;
+    let stem = stem.split('.').next().unwrap_or(stem);
+    let name = if stem.eq_ignore_ascii_case(&key) { stem } else { key.as_str() };
+    Some(strip_test_affixes(name).to_lowercase())
+}
+
+fn strip_test_affixes(stem: &str) -> &str {
+    let capitalized = |text: &str| text.chars().next().is_some_and(char::is_uppercase);
+    for word in TEST_WORDS {
+        // `test_parser`, or `TestParser`
+        if let Some((head, rest)) = stem.split_at_checked(word.len()).filter(|(head, _)| head.eq_ignore_ascii_case(word)) {
+            let trimmed = rest.trim_start_matches(WORD_SEPARATORS);
+            if !trimmed.is_empty() && (trimmed.len() < rest.len() || (capitalized(head) && capitalized(rest))) {
+                return trimmed;
+            }
+        }
+        // `parser_test`, or `ParserTest`
+        let Some(split) = stem.len().checked_sub(word.len()) else { continue };
+        if let Some((rest, tail)) = stem.split_at_checked(split).filter(|(_, tail)| tail.eq_ignore_ascii_case(word)) {
+            let trimmed = rest.trim_end_matches(WORD_SEPARATORS);
+            let camel_case = capitalized(tail) && rest.ends_with(|c: char| c.is_lowercase() || c.is_ascii_digit());
+            if !trimmed.is_empty() && (trimmed.len() < rest.len() || camel_case) {
+                return trimmed;
+            }
+        }
+    }
+    stem
+}
+
+pub(crate) fn import_tokens(import: &str) -> Vec<String> {
+    import.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+        .filter(|token| !matches!(token.as_str(), "use" | "import" | "from" | "crate" | "super" | "self" | "std" | "as"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, tags: &[&str]) -> FileEntry {
+        FileEntry::test(path).with_tags(tags)
+    }
+
+    #[test]
+    fn test_maps_by_naming_convention() {
+        let files = vec![
+            entry("src/parser.py", &["source"]),
+            entry("src/lexer.py", &["source"]),
+            entry("tests/test_parser.py", &["source", "test"]),
+            entry("web/date.ts", &["source"]),
+            entry("web/date.test.ts", &["source", "test"]),
+        ];
+
+        let structure = TestCoverageStructure::build(&files);
+
+        assert_eq!(structure.test_files, 2);
+        assert_eq!(structure.source_files, 3);
+        assert_eq!(structure.covered_source_files, 2);
+        assert_eq!(structure.untested_sources, vec![PathBuf::from("src/lexer.py")]);
+        assert_eq!(structure.mappings[0].source_files, vec![PathBuf::from("src/parser.py")]);
+    }
+
+    #[test]
+    fn test_affixes_are_stripped_at_word_boundaries() {
+        let files = vec![
+            entry("src/con.rs", &["source"]),
+            entry("src/contest.rs", &["source", "test"]),
+            entry("src/Parser.java", &["source"]),
+            entry("src/ParserTest.java", &["source", "test"]),
+            entry("src/lexer.rs", &["source"]),
+            entry("src/lexer-spec.rs", &["source", "test"]),
+        ];
+
+        let structure = TestCoverageStructure::build(&files);
+
+        assert!(structure.mappings[0].source_files.is_empty());
+        assert_eq!(structure.mappings[1].source_files, vec![PathBuf::from("src/Parser.java")]);
+        assert_eq!(structure.mappings[2].source_files, vec![PathBuf::from("src/lexer.rs")]);
+        assert_eq!(structure.untested_sources, vec![PathBuf::from("src/con.rs")]);
+        assert_eq!(strip_test_affixes("testimony"), "testimony");
+        assert_eq!(strip_test_affixes("parser_tests"), "parser");
+        assert_eq!(strip_test_affixes("TestParser"), "Parser");
+        assert_eq!(strip_test_affixes("test_"), "test_");
+    }
+
+    #[test]
+    fn test_maps_through_imports() {
+        let integration = entry("tests/integration.rs", &["source", "test"])
+            .with_info(|info| info.imports = vec!["mycrate::scanner::Walker".to_string()]);
+        let files = vec![
+            entry("src/scanner/mod.rs", &["source"]),
+            entry("src/output.rs", &["source"]),
+            integration,
+        ];
+
+        let structure = TestCoverageStructure::build(&files);
+
+        assert_eq!(structure.mappings[0].source_files, vec![PathBuf::from("src/scanner/mod.rs")]);
+        assert_eq!(structure.untested_sources, vec![PathBuf::from("src/output.rs")]);
+    }
+
+    #[test]
+    fn test_prefers_closest_source_for_duplicate_stems() {
+        let files = vec![
+            entry("api/utils.go", &["source"]),
+            entry("worker/utils.go", &["source"]),
+            entry("worker/utils_test.go", &["source", "test"]),
+        ];
+
+        let structure = TestCoverageStructure::build(&files);
+
+        assert_eq!(structure.mappings[0].source_files, vec![PathBuf::from("worker/utils.go")]);
+        assert_eq!(structure.untested_sources, vec![PathBuf::from("api/utils.go")]);
+    }
+}
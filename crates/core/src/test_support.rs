@@ -0,0 +1,73 @@
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::{EnhancedFileInfo, FileEntry};
+
+// Scan entries for unit tests: a file with no tags, metadata or analysis,
+// modified at the epoch, that each test then fills in as far as it needs
+impl FileEntry {
+    pub(crate) fn test(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        FileEntry {
+            path: path.to_path_buf(),
+            name: path.file_name().map_or_else(String::new, |name| name.to_string_lossy().to_string()),
+            size: 0,
+            modified: SystemTime::UNIX_EPOCH,
+            is_dir: false,
+            tags: Vec::new(),
+            metadata: None,
+            enhanced_info: None,
+            symlink: None,
+            media: None,
+            content_hash: None,
+        }
+    }
+
+    pub(crate) fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub(crate) fn with_size(mut self, size: u64) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub(crate) fn with_modified(mut self, modified: SystemTime) -> Self {
+        self.modified = modified;
+        self
+    }
+
+    pub(crate) fn with_dir(mut self, is_dir: bool) -> Self {
+        self.is_dir = is_dir;
+        self
+    }
+
+    pub(crate) fn with_tags(mut self, tags: &[&str]) -> Self {
+        self.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        self
+    }
+
+    pub(crate) fn with_content_hash(mut self, hash: &str) -> Self {
+        self.content_hash = Some(hash.to_string());
+        self
+    }
+
+    // Edits the analysis, starting from an empty one
+    pub(crate) fn with_info(mut self, edit: impl FnOnce(&mut EnhancedFileInfo)) -> Self {
+        edit(self.enhanced_info.get_or_insert_with(EnhancedFileInfo::default));
+        self
+    }
+
+    pub(crate) fn with_language(self, language: &str) -> Self {
+        self.with_info(|info| info.language = Some(language.to_string()))
+    }
+
+    // Analyzed with this score, or left unanalyzed for `None`
+    pub(crate) fn with_complexity(self, complexity: Option<f64>) -> Self {
+        match complexity {
+            Some(score) => self.with_info(|info| info.complexity_score = Some(score)),
+            None => self,
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::layering::{check_layers, LayerRule, LayerViolation};
+use crate::{FileSystem, ScanError, ScanIssue, ScanResult, Severity};
+
+// Limits a scan must stay within, used to gate CI. Unset limits are not checked.
+// Also the policy file read by `projscan scan --policy`, in YAML or JSON:
+//
+//     max_complexity: 12
+//     layers:
+//       - api -> core
+//       - core !-> api
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Thresholds {
+    pub max_complexity: Option<f64>,
+    pub max_file_lines: Option<usize>,
+    pub fail_on_errors: bool,
+    // Dependency directions between directories, checked against the import graph
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<LayerRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum ThresholdViolation {
+    Complexity { path: PathBuf, complexity: f64, limit: f64 },
+    FileLines { path: PathBuf, lines: usize, limit: usize },
+    ScanError { issue: ScanIssue },
+    Layering { violation: LayerViolation },
+}
+
+impl fmt::Display for ThresholdViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThresholdViolation::Complexity { path, complexity, limit } => {
+                write!(f, "{}: complexity {:.2} exceeds {}", path.display(), complexity, limit)
+            }
+            ThresholdViolation::FileLines { path, lines, limit } => {
+                write!(f, "{}: {} lines exceeds {}", path.display(), lines, limit)
+            }
+            ThresholdViolation::ScanError { issue } => write!(f, "scan error: {}", issue),
+            ThresholdViolation::Layering { violation } => write!(f, "{}", violation),
+        }
+    }
+}
+
+impl Thresholds {
+    // A policy file; YAML, which also reads JSON
+    pub fn load(path: &Path) -> Result<Self, ScanError> {
+        let text = std::fs::read_to_string(path)?;
+        serde_yaml::from_str(&text).map_err(|e| ScanError::InvalidConfig {
+            message: format!("{}: {}", path.display(), e),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.max_complexity.is_none() && self.max_file_lines.is_none() && !self.fail_on_errors && self.layers.is_empty()
+    }
+
+    // Imports breaking the layer rules, with the line of each import read
+    // through `fs`; needs an enhanced scan
+    pub fn check_layers(&self, result: &ScanResult, fs: &dyn FileSystem) -> Vec<ThresholdViolation> {
+        check_layers(&self.layers, result, fs).into_iter()
+            .map(|violation| ThresholdViolation::Layering { violation })
+            .collect()
+    }
+
+    // Complexity and line counts come from enhanced analysis; files without
+    // them never violate a limit. Paths are relative to the scan root.
+    pub fn check(&self, result: &ScanResult) -> Vec<ThresholdViolation> {
+        let mut violations = Vec::new();
+
+        for file in result.files.iter().filter(|file| !file.is_dir) {
+            let Some(info) = &file.enhanced_info else { continue };
+            let path = || file.path.strip_prefix(&result.root_path).unwrap_or(&file.path).to_path_buf();
+
+            if let (Some(limit), Some(complexity)) = (self.max_complexity, info.complexity_score) {
+                if complexity > limit {
+                    violations.push(ThresholdViolation::Complexity { path: path(), complexity, limit });
+                }
+            }
+            if let (Some(limit), Some(lines)) = (self.max_file_lines, info.line_count) {
+                if lines > limit {
+                    violations.push(ThresholdViolation::FileLines { path: path(), lines, limit });
+                }
+            }
+        }
+
+        if self.fail_on_errors {
+            violations.extend(result.errors.iter()
+                .filter(|issue| issue.severity == Severity::Error)
+                .map(|issue| ThresholdViolation::ScanError { issue: issue.clone() }));
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FileEntry, IssueKind, ScanStats};
+
+    fn result(errors: Vec<ScanIssue>) -> ScanResult {
+        let file = |path: &str, lines: usize, complexity: f64| FileEntry::test(Path::new("proj").join(path))
+            .with_complexity(Some(complexity))
+            .with_info(|info| info.line_count = Some(lines));
+        ScanResult {
+            schema_version: crate::SCHEMA_VERSION,
+            root_path: PathBuf::from("proj"),
+            files: vec![file("src/small.rs", 40, 1.5), file("src/big.rs", 2000, 9.0)],
+            stats: ScanStats { total_files: 2, total_dirs: 0, total_size: 0, scan_duration_ms: 0, files_per_second: 0.0, directories: Default::default(), distribution: Default::default(), cache: None, sampling: None, resources: None },
+            roots: Vec::new(),
+            errors,
+            findings: Vec::new(),
+            test_coverage_structure: None,
+            entry_points: Vec::new(),
+            endpoints: Vec::new(),
+            schema: None,
+            containers: Vec::new(),
+            nested_repositories: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_reports_files_over_limits() {
+        let thresholds = Thresholds { max_complexity: Some(8.0), max_file_lines: Some(1500), ..Default::default() };
+        let violations = thresholds.check(&result(Vec::new()));
+
+        assert_eq!(violations, vec![
+            ThresholdViolation::Complexity { path: PathBuf::from("src/big.rs"), complexity: 9.0, limit: 8.0 },
+            ThresholdViolation::FileLines { path: PathBuf::from("src/big.rs"), lines: 2000, limit: 1500 },
+        ]);
+        assert_eq!(violations[1].to_string(), "src/big.rs: 2000 lines exceeds 1500");
+    }
+
+    #[test]
+    fn test_unset_limits_are_not_checked() {
+        let errors = vec![ScanIssue::new(None, IssueKind::Other, "boom", Severity::Error)];
+
+        assert!(Thresholds::default().is_empty());
+        assert!(Thresholds::default().check(&result(errors)).is_empty());
+    }
+
+    #[test]
+    fn test_fail_on_errors_ignores_warnings() {
+        let thresholds = Thresholds { fail_on_errors: true, ..Default::default() };
+        let errors = vec![
+            ScanIssue::new(Some(PathBuf::from("proj/a.bin")), IssueKind::NonUtf8, "not utf-8", Severity::Warning),
+            ScanIssue::new(None, IssueKind::PermissionDenied, "denied", Severity::Error),
+        ];
+        let violations = thresholds.check(&result(errors));
+
+        assert_eq!(violations.len(), 1);
+        assert!(matches!(&violations[0], ThresholdViolation::ScanError { issue } if issue.message == "denied"));
+    }
+}
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+use std::sync::{Condvar, Mutex};
+
+use crate::{FileSystem, FsEntry, ScanIssue, ScanOptions};
+
+// Limits on file content reads for scans that should stay out of the way,
+// e.g. watch mode or cron jobs on a shared machine. A scan reads files one at
+// a time on its own thread, so `delay_ms` is what slows it down;
+// `max_concurrent_reads` only matters to callers reading through the throttled
+// filesystem from several threads, and is per scan, not per process.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct IoThrottle {
+    pub max_concurrent_reads: usize,
+    // Pause after each read
+    pub delay_ms: u64,
+}
+
+impl Default for IoThrottle {
+    fn default() -> Self {
+        Self { max_concurrent_reads: 1, delay_ms: 2 }
+    }
+}
+
+// Reads in flight through one throttled filesystem
+#[derive(Default)]
+struct ReadSlots {
+    in_flight: Mutex<usize>,
+    finished: Condvar,
+}
+
+impl ReadSlots {
+    fn acquire(&self, limit: usize) -> ReadPermit<'_> {
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        while *in_flight >= limit.max(1) {
+            in_flight = self.finished.wait(in_flight).unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+        *in_flight += 1;
+        ReadPermit(self)
+    }
+}
+
+// Holds one of the read slots until dropped
+struct ReadPermit<'a>(&'a ReadSlots);
+
+impl Drop for ReadPermit<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self.0.in_flight.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *in_flight -= 1;
+        self.0.finished.notify_one();
+    }
+}
+
+// Applies an `IoThrottle` to the reads of another filesystem; the walk itself
+// only reads directory listings and is passed through
+pub(crate) struct ThrottledFileSystem<'a> {
+    inner: &'a dyn FileSystem,
+    throttle: IoThrottle,
+    slots: ReadSlots,
+}
+
+impl<'a> ThrottledFileSystem<'a> {
+    pub(crate) fn new(inner: &'a dyn FileSystem, throttle: IoThrottle) -> Self {
+        Self { inner, throttle, slots: ReadSlots::default() }
+    }
+
+    fn throttled<T>(&self, read: impl FnOnce() -> T) -> T {
+        let permit = self.slots.acquire(self.throttle.max_concurrent_reads);
+        let value = read();
+        drop(permit);
+        // Browsers cannot block the thread; the read limit still applies there
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.throttle.delay_ms > 0 {
+            std::thread::sleep(std::time::Duration::from_millis(self.throttle.delay_ms));
+        }
+        value
+    }
+}
+
+impl FileSystem for ThrottledFileSystem<'_> {
+    fn exists(&self, path: &Path) -> bool {
+        self.inner.exists(path)
+    }
+
+    fn walk<'b>(&'b self, root: &Path, options: &'b ScanOptions) -> Box<dyn Iterator<Item = Result<FsEntry, ScanIssue>> + 'b> {
+        self.inner.walk(root, options)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.throttled(|| self.inner.read(path))
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        self.throttled(|| self.inner.read_to_string(path))
+    }
+
+    fn read_prefix(&self, path: &Path, len: usize) -> io::Result<Vec<u8>> {
+        self.throttled(|| self.inner.read_prefix(path, len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_permits_cap_concurrent_reads() {
+        let slots = ReadSlots::default();
+        let active = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for _ in 0..6 {
+                scope.spawn(|| {
+                    let _permit = slots.acquire(2);
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(5));
+                    active.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[test]
+    fn test_scans_do_not_share_read_slots() {
+        let fs = MemoryFileSystem::new();
+        let throttle = IoThrottle { max_concurrent_reads: 1, delay_ms: 0 };
+        let first = ThrottledFileSystem::new(&fs, throttle);
+        let second = ThrottledFileSystem::new(&fs, throttle);
+
+        // Would block forever if the second scan waited on the first one's slot
+        let _held = first.slots.acquire(1);
+        drop(second.slots.acquire(1));
+    }
+
+    #[test]
+    fn test_reads_pause_between_files() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/a.rs", "fn a() {}");
+        fs.insert("app/b.rs", "fn b() {}");
+        let throttled = ThrottledFileSystem::new(&fs, IoThrottle { max_concurrent_reads: 1, delay_ms: 10 });
+
+        let start = Instant::now();
+        assert_eq!(throttled.read_to_string(Path::new("app/a.rs")).unwrap(), "fn a() {}");
+        throttled.read(Path::new("app/b.rs")).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_throttled_scan_matches_unthrottled() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("app/main.rs", "fn main() {\n    if true {}\n}\n");
+        let plain = ScanOptions { enhanced_analysis: true, ..Default::default() };
+        let nice = ScanOptions { io_throttle: Some(IoThrottle { max_concurrent_reads: 1, delay_ms: 0 }), ..plain.clone() };
+
+        let lines = |options: ScanOptions| {
+            let result = DirectoryScanner::new(options).scan_with(&fs, "app").unwrap();
+            result.find("app/main.rs").unwrap().enhanced_info.as_ref().unwrap().line_count
+        };
+        assert_eq!(lines(nice), lines(plain));
+    }
+}
@@ -0,0 +1,247 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// RFC 3339 timestamps in UTC with millisecond precision, e.g.
+// "2024-05-01T12:30:00.250Z", without pulling in a date library
+pub fn format_rfc3339(time: SystemTime) -> String {
+    let millis = epoch_millis(time);
+    let (days, day_millis) = (millis.div_euclid(86_400_000), millis.rem_euclid(86_400_000));
+    let (year, month, day) = civil_from_days(days);
+    let seconds = day_millis / 1000;
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year, month, day, seconds / 3600, seconds / 60 % 60, seconds % 60, day_millis % 1000)
+}
+
+// Accepts `Z` or a numeric offset and any number of fractional digits
+pub fn parse_rfc3339(text: &str) -> Option<SystemTime> {
+    let bytes = text.as_bytes();
+    let number = |range: std::ops::Range<usize>| -> Option<i64> {
+        let digits = text.get(range)?;
+        digits.bytes().all(|b| b.is_ascii_digit()).then(|| digits.parse().ok())?
+    };
+    if bytes.len() < 20 || bytes[4] != b'-' || bytes[7] != b'-' || !matches!(bytes[10], b'T' | b't' | b' ')
+        || bytes[13] != b':' || bytes[16] != b':'
+    {
+        return None;
+    }
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let mut rest = &text[19..];
+    let mut nanos = 0i64;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        let padded = format!("{:0<9}", &fraction[..digits.min(9)]);
+        nanos = padded.parse().ok()?;
+        rest = &fraction[digits..];
+    }
+    let offset_seconds = match rest.as_bytes() {
+        [b'Z' | b'z'] => 0,
+        [sign @ (b'+' | b'-'), _, _, b':', _, _] => {
+            let offset = number(text.len() - 5..text.len() - 3)? * 3600 + number(text.len() - 2..text.len())? * 60;
+            if *sign == b'+' { offset } else { -offset }
+        }
+        _ => return None,
+    };
+
+    let seconds = days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second - offset_seconds;
+    let since_epoch = Duration::new(seconds.unsigned_abs(), 0);
+    let whole = if seconds >= 0 { UNIX_EPOCH.checked_add(since_epoch)? } else { UNIX_EPOCH.checked_sub(since_epoch)? };
+    whole.checked_add(Duration::from_nanos(nanos as u64))
+}
+
+// A point in time given as RFC 3339, a date (midnight UTC) or an age before
+// `now` such as 90m, 12h, 30d or 2w
+pub fn parse_time_spec(text: &str, now: SystemTime) -> Option<SystemTime> {
+    let text = text.trim();
+    if text.len() == 10 {
+        return parse_rfc3339(&format!("{}T00:00:00Z", text));
+    }
+    if let Some(time) = parse_rfc3339(text) {
+        return Some(time);
+    }
+    let unit = match text.chars().last()? {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86_400,
+        'w' => 7 * 86_400,
+        _ => return None,
+    };
+    let count: u64 = text[..text.len() - 1].parse().ok()?;
+    now.checked_sub(Duration::from_secs(count.checked_mul(unit)?))
+}
+
+// Milliseconds since the Unix epoch, negative before it
+pub fn epoch_millis(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => after.as_millis() as i64,
+        Err(before) => -(before.duration().as_millis() as i64),
+    }
+}
+
+fn from_epoch_millis(millis: i64) -> Option<SystemTime> {
+    let offset = Duration::from_millis(millis.unsigned_abs());
+    if millis >= 0 { UNIX_EPOCH.checked_add(offset) } else { UNIX_EPOCH.checked_sub(offset) }
+}
+
+// Howard Hinnant's proleptic Gregorian conversions
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
+
+// Days since 1970-01-01; also used for zip timestamps
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+// `FileEntry::modified` is written as `modified` (RFC 3339) plus `modified_ms`
+// (epoch milliseconds). Reading also accepts the `{secs_since_epoch,
+// nanos_since_epoch}` struct that results saved by older versions contain.
+pub(crate) mod modified {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Written {
+        modified: String,
+        modified_ms: i64,
+    }
+
+    #[derive(Deserialize)]
+    struct Read {
+        modified: Option<TimeRepr>,
+        modified_ms: Option<i64>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum TimeRepr {
+        Text(String),
+        Legacy(SystemTime),
+    }
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        Written { modified: format_rfc3339(*time), modified_ms: epoch_millis(*time) }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        use serde::de::Error;
+
+        let read = Read::deserialize(deserializer)?;
+        match (read.modified, read.modified_ms) {
+            (Some(TimeRepr::Legacy(time)), _) => Ok(time),
+            (_, Some(millis)) => from_epoch_millis(millis).ok_or_else(|| D::Error::custom("modified_ms is out of range")),
+            (Some(TimeRepr::Text(text)), None) => parse_rfc3339(&text)
+                .ok_or_else(|| D::Error::custom(format!("invalid RFC 3339 timestamp: {}", text))),
+            (None, None) => Err(D::Error::missing_field("modified")),
+        }
+    }
+}
+
+// Optional times in options, written as RFC 3339 strings
+pub(crate) mod optional {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(time: &Option<SystemTime>, serializer: S) -> Result<S::Ok, S::Error> {
+        time.map(format_rfc3339).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<SystemTime>, D::Error> {
+        use serde::de::Error;
+
+        match Option::<String>::deserialize(deserializer)? {
+            Some(text) => parse_rfc3339(&text)
+                .map(Some)
+                .ok_or_else(|| D::Error::custom(format!("invalid RFC 3339 timestamp: {}", text))),
+            None => Ok(None),
+        }
+    }
+}
+
+// A plain RFC 3339 timestamp
+pub(crate) mod required {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        format_rfc3339(*time).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        use serde::de::Error;
+
+        let text = String::deserialize(deserializer)?;
+        parse_rfc3339(&text).ok_or_else(|| D::Error::custom(format!("invalid RFC 3339 timestamp: {}", text)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_known_dates() {
+        assert_eq!(format_rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        assert_eq!(format_rfc3339(UNIX_EPOCH + Duration::from_millis(1_709_210_096_789)), "2024-02-29T12:34:56.789Z");
+        assert_eq!(format_rfc3339(UNIX_EPOCH - Duration::from_secs(1)), "1969-12-31T23:59:59.000Z");
+    }
+
+    #[test]
+    fn test_parse_round_trips_and_offsets() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_709_210_096_789);
+
+        assert_eq!(parse_rfc3339(&format_rfc3339(time)), Some(time));
+        assert_eq!(parse_rfc3339("2024-02-29T14:34:56.789+02:00"), Some(time));
+        assert_eq!(parse_rfc3339("1970-01-01T00:00:00Z"), Some(UNIX_EPOCH));
+        assert_eq!(parse_rfc3339("2024-13-01T00:00:00Z"), None);
+        assert_eq!(parse_rfc3339("yesterday"), None);
+    }
+
+    #[test]
+    fn test_time_specs() {
+        let now = UNIX_EPOCH + Duration::from_secs(40 * 86_400);
+
+        assert_eq!(parse_time_spec("30d", now), Some(UNIX_EPOCH + Duration::from_secs(10 * 86_400)));
+        assert_eq!(parse_time_spec("2w", now), Some(UNIX_EPOCH + Duration::from_secs(26 * 86_400)));
+        assert_eq!(parse_time_spec("1970-01-02", now), Some(UNIX_EPOCH + Duration::from_secs(86_400)));
+        assert_eq!(parse_time_spec("1970-01-01T00:00:05Z", now), Some(UNIX_EPOCH + Duration::from_secs(5)));
+        assert_eq!(parse_time_spec("soon", now), None);
+        assert_eq!(parse_time_spec("d", now), None);
+    }
+
+    #[test]
+    fn test_modified_reads_all_representations() {
+        #[derive(Serialize, Deserialize)]
+        struct Entry {
+            #[serde(flatten, with = "modified")]
+            modified: SystemTime,
+        }
+        let time = UNIX_EPOCH + Duration::from_millis(1_500);
+
+        let json = serde_json::to_string(&Entry { modified: time }).unwrap();
+        assert_eq!(json, r#"{"modified":"1970-01-01T00:00:01.500Z","modified_ms":1500}"#);
+        let read = |json: &str| serde_json::from_str::<Entry>(json).map(|entry| entry.modified).ok();
+        assert_eq!(read(&json), Some(time));
+        assert_eq!(read(r#"{"modified":"1970-01-01T00:00:01.500Z"}"#), Some(time));
+        assert_eq!(read(r#"{"modified":{"secs_since_epoch":1,"nanos_since_epoch":500000000}}"#), Some(time));
+        assert_eq!(read("{}"), None);
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Component;
+
+use crate::layout::language_of;
+use crate::{ContentAnalyzer, FileEntry, FormatOptions, OutputFormatter, Palette, ScanResult, PARTIAL_TAG};
+
+// Totals for the files under a directory, printed next to it in tree views
+#[derive(Default)]
+pub(crate) struct Rollup {
+    files: usize,
+    size: u64,
+    languages: HashMap<String, usize>,
+    complexity_total: f64,
+    complexity_count: usize,
+}
+
+impl Rollup {
+    pub(crate) fn add(&mut self, file: &FileEntry, analyzer: &ContentAnalyzer) {
+        self.files += 1;
+        self.size += file.size;
+        let language = language_of(file, analyzer);
+        if language != "unknown" {
+            *self.languages.entry(language).or_default() += 1;
+        }
+        if let Some(score) = file.enhanced_info.as_ref().and_then(|info| info.complexity_score) {
+            self.complexity_total += score;
+            self.complexity_count += 1;
+        }
+    }
+
+    // The language of the most files, ties broken by name
+    fn dominant_language(&self) -> Option<&str> {
+        self.languages.iter()
+            .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(language, _)| language.as_str())
+    }
+
+    // e.g. "3 files, 12.0KB, rust, avg complexity 4.2"; language and complexity
+    // are left out when unknown
+    pub(crate) fn describe(&self) -> String {
+        let mut parts = vec![
+            if self.files == 1 { "1 file".to_string() } else { format!("{} files", self.files) },
+            OutputFormatter::format_size(self.size),
+        ];
+        if let Some(language) = self.dominant_language() {
+            parts.push(language.to_string());
+        }
+        if self.complexity_count > 0 {
+            parts.push(format!("avg complexity {:.1}", self.complexity_total / self.complexity_count as f64));
+        }
+        parts.join(", ")
+    }
+}
+
+// A nested view of the flat file list, keyed by path component relative to the
+// scan root. Rollups include everything below each directory.
+#[derive(Default)]
+struct TreeNode<'a> {
+    dirs: BTreeMap<String, TreeNode<'a>>,
+    files: Vec<&'a FileEntry>,
+    rollup: Rollup,
+    // The scan could not read all of the directory's entries
+    partial: bool,
+}
+
+impl<'a> TreeNode<'a> {
+    fn insert(&mut self, file: &'a FileEntry, components: &[String], analyzer: &ContentAnalyzer) {
+        let Some((last, parents)) = components.split_last() else {
+            return;
+        };
+        let mut node = self;
+        if !file.is_dir {
+            node.rollup.add(file, analyzer);
+        }
+        for name in parents {
+            node = node.dirs.entry(name.clone()).or_default();
+            if !file.is_dir {
+                node.rollup.add(file, analyzer);
+            }
+        }
+        if file.is_dir {
+            node.dirs.entry(last.clone()).or_default().partial = file.tags.iter().any(|tag| tag == PARTIAL_TAG);
+        } else {
+            node.files.push(file);
+        }
+    }
+
+    fn dir_count(&self) -> usize {
+        self.dirs.values().map(|dir| 1 + dir.dir_count()).sum()
+    }
+
+    // Directories first in name order, then files in the order they were given
+    fn render(&self, prefix: &str, palette: &Palette, output: &mut String) {
+        let files = &self.files;
+        let total = self.dirs.len() + files.len();
+
+        for (index, (name, dir)) in self.dirs.iter().enumerate() {
+            let last = index + 1 == total;
+            let partial = if dir.partial { ", partially scanned" } else { "" };
+            output.push_str(&format!("{}{} {} {}\n", prefix, connector(last), palette.dir(&format!("{}/", name)),
+                palette.dim(&format!("({}{})", dir.rollup.describe(), partial))));
+            dir.render(&format!("{}{}", prefix, if last { "    " } else { "│   " }), palette, output);
+        }
+        for (index, file) in files.iter().enumerate() {
+            let last = self.dirs.len() + index + 1 == total;
+            let complexity = file.enhanced_info.as_ref().and_then(|info| info.complexity_score);
+            output.push_str(&format!("{}{} {} {}\n", prefix, connector(last), palette.by_complexity(complexity, &file.name),
+                palette.dim(&format!("({})", OutputFormatter::format_size(file.size)))));
+        }
+    }
+}
+
+fn connector(last: bool) -> &'static str {
+    if last {
+        "└──"
+    } else {
+        "├──"
+    }
+}
+
+pub(crate) fn format_tree(result: &ScanResult, options: &FormatOptions) -> String {
+    let palette = &options.palette;
+    let analyzer = ContentAnalyzer::new();
+    let mut root = TreeNode::default();
+    for file in &result.files {
+        let relative = file.path.strip_prefix(&result.root_path).unwrap_or(&file.path);
+        let components: Vec<String> = relative.components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect();
+        root.insert(file, &components, &analyzer);
+    }
+
+    let mut output = format!("{}\n", palette.dir(&options.paths.render(&result.root_path, &result.root_path)));
+    root.render("", palette, &mut output);
+    let dirs = root.dir_count();
+    output.push_str(&format!("\n{} {}, {} {}\n",
+        dirs, if dirs == 1 { "directory" } else { "directories" },
+        root.rollup.files, if root.rollup.files == 1 { "file" } else { "files" }));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScanStats;
+    use std::path::{Path, PathBuf};
+
+    fn result(paths: &[(&str, u64)]) -> ScanResult {
+        let files = paths.iter()
+            .map(|(path, size)| {
+                FileEntry::test(Path::new("proj").join(path.trim_end_matches('/'))).with_size(*size).with_dir(path.ends_with('/'))
+            })
+            .collect();
+        ScanResult {
+            schema_version: crate::SCHEMA_VERSION,
+            root_path: PathBuf::from("proj"),
+            files,
+            stats: ScanStats { total_files: 0, total_dirs: 0, total_size: 0, scan_duration_ms: 0, files_per_second: 0.0, directories: Default::default(), distribution: Default::default(), cache: None, sampling: None, resources: None },
+            roots: Vec::new(),
+            errors: Vec::new(),
+            findings: Vec::new(),
+            test_coverage_structure: None,
+            entry_points: Vec::new(),
+            endpoints: Vec::new(),
+            schema: None,
+            containers: Vec::new(),
+            nested_repositories: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_tree_draws_connectors_dirs_first() {
+        let tree = format_tree(&result(&[
+            ("Cargo.toml", 20),
+            ("README.md", 100),
+            ("src/", 0),
+            ("src/main.rs", 2048),
+            ("src/util/", 0),
+            ("src/util/fmt.rs", 10),
+        ]), &FormatOptions::default());
+
+        assert_eq!(tree, "\
+proj
+├── src/ (2 files, 2.0KB, rust)
+│   ├── util/ (1 file, 10B, rust)
+│   │   └── fmt.rs (10B)
+│   └── main.rs (2.0KB)
+├── Cargo.toml (20B)
+└── README.md (100B)
+
+2 directories, 4 files
+");
+    }
+
+    #[test]
+    fn test_tree_keeps_empty_directories() {
+        let tree = format_tree(&result(&[("empty/", 0), ("a.txt", 1)]), &FormatOptions::default());
+
+        assert!(tree.contains("├── empty/ (0 files, 0B)\n└── a.txt (1B)\n"));
+        assert!(tree.ends_with("1 directory, 1 file\n"));
+    }
+
+    #[test]
+    fn test_rollup_reports_dominant_language_and_average_complexity() {
+        let analyzer = ContentAnalyzer::new();
+        let mut rollup = Rollup::default();
+        for (name, complexity) in [("a.py", Some(2.0)), ("b.py", Some(5.0)), ("c.rs", None), ("d.bin", None)] {
+            let mut file = result(&[(name, 1024)]).files.remove(0);
+            file.enhanced_info = complexity.map(|score| crate::EnhancedFileInfo { complexity_score: Some(score), ..Default::default() });
+            rollup.add(&file, &analyzer);
+        }
+
+        assert_eq!(rollup.describe(), "4 files, 4.0KB, python, avg complexity 3.5");
+        assert_eq!(Rollup::default().describe(), "0 files, 0B");
+    }
+
+    #[test]
+    fn test_tree_colors_directories() {
+        let tree = format_tree(&result(&[("src/", 0)]), &FormatOptions { palette: Palette::new(true), ..Default::default() });
+
+        assert!(tree.contains("└── \x1b[1;34msrc/\x1b[0m"));
+    }
+}
@@ -0,0 +1,326 @@
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+use crate::{content_hash, portable_path, Chunk, ChunkManifestEntry};
+
+// Chunks in the shapes vector stores load directly. Symbols, tags, hashes and
+// vectors come from the manifest entry with the same chunk ID when one is given.
+
+fn manifest_by_id(manifest: &[ChunkManifestEntry]) -> HashMap<&str, &ChunkManifestEntry> {
+    manifest.iter().map(|entry| (entry.id.as_str(), entry)).collect()
+}
+
+// Qdrant point IDs must be integers or UUIDs; this one is stable for a chunk ID,
+// so re-exporting a chunk overwrites its earlier point
+pub fn chunk_point_id(chunk_id: &str) -> String {
+    let mut hex: Vec<char> = content_hash(chunk_id.as_bytes()).chars().take(32).collect();
+    // Version 8 (custom) with the RFC 4122 variant
+    hex[12] = '8';
+    hex[16] = char::from_digit(8 | (hex[16].to_digit(16).unwrap_or(0) & 3), 16).unwrap_or('8');
+    let hex: String = hex.into_iter().collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
+
+// One Qdrant point per line: `{"id", "vector", "payload"}`, ready to batch into
+// `PUT /collections/{name}/points`. Chunks without an embedding have no "vector".
+pub fn to_qdrant_points(chunks: &[Chunk], manifest: &[ChunkManifestEntry]) -> String {
+    let manifest = manifest_by_id(manifest);
+    let mut lines = String::new();
+    for chunk in chunks {
+        let entry = manifest.get(chunk.id.as_str());
+        let mut payload = Map::new();
+        payload.insert("chunk_id".to_string(), json!(chunk.id));
+        payload.insert("path".to_string(), json!(portable_path(&chunk.path)));
+        payload.insert("start_line".to_string(), json!(chunk.start_line));
+        payload.insert("end_line".to_string(), json!(chunk.end_line));
+        payload.insert("token_count".to_string(), json!(chunk.token_count));
+        payload.insert("language".to_string(), json!(chunk.language));
+        payload.insert("content".to_string(), json!(chunk.content));
+        if let Some(entry) = entry {
+            payload.insert("symbol".to_string(), json!(entry.symbol));
+            payload.insert("tags".to_string(), json!(entry.tags));
+            payload.insert("content_hash".to_string(), json!(entry.content_hash));
+            payload.insert("file_hash".to_string(), json!(entry.file_hash));
+        }
+
+        let mut point = Map::new();
+        point.insert("id".to_string(), json!(chunk_point_id(&chunk.id)));
+        if let Some(vector) = entry.and_then(|entry| entry.embedding.as_ref()) {
+            point.insert("vector".to_string(), json!(vector));
+        }
+        point.insert("payload".to_string(), Value::Object(payload));
+        lines.push_str(&Value::Object(point).to_string());
+        lines.push('\n');
+    }
+    lines
+}
+
+pub const PGVECTOR_COLUMNS: [&str; 12] = [
+    "id", "path", "start_line", "end_line", "token_count", "language", "symbol", "tags", "content_hash", "file_hash",
+    "content", "embedding",
+];
+
+// CSV for `COPY chunks FROM STDIN WITH (FORMAT csv, HEADER true)`. Empty unquoted
+// fields load as NULL, tags as a text[] literal and vectors in pgvector's `[1,2,3]` form.
+pub fn to_pgvector_csv(chunks: &[Chunk], manifest: &[ChunkManifestEntry]) -> String {
+    let manifest = manifest_by_id(manifest);
+    let mut csv = PGVECTOR_COLUMNS.join(",");
+    csv.push('\n');
+    for chunk in chunks {
+        let entry = manifest.get(chunk.id.as_str());
+        let tags = entry.map(|entry| {
+            let items: Vec<String> = entry.tags.iter().map(|tag| format!("\"{}\"", tag.replace('\\', "\\\\").replace('"', "\\\""))).collect();
+            format!("{{{}}}", items.join(","))
+        });
+        let embedding = entry.and_then(|entry| entry.embedding.as_ref()).map(|vector| {
+            let values: Vec<String> = vector.iter().map(|value| value.to_string()).collect();
+            format!("[{}]", values.join(","))
+        });
+        let fields = [
+            Some(chunk.id.clone()),
+            Some(portable_path(&chunk.path)),
+            Some(chunk.start_line.to_string()),
+            Some(chunk.end_line.to_string()),
+            Some(chunk.token_count.to_string()),
+            chunk.language.clone(),
+            entry.and_then(|entry| entry.symbol.clone()),
+            tags,
+            entry.map(|entry| entry.content_hash.clone()),
+            entry.map(|entry| entry.file_hash.clone()),
+            Some(chunk.content.clone()),
+            embedding,
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| field.as_deref().map_or(String::new(), csv_field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+// Present values are always quoted so an empty string stays distinct from NULL
+fn csv_field(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+#[cfg(feature = "parquet")]
+pub use parquet_export::write_chunks_parquet;
+
+#[cfg(feature = "parquet")]
+mod parquet_export {
+    use parquet::data_type::{ByteArray, ByteArrayType, FloatType, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use std::io::{self, Write};
+    use std::sync::Arc;
+
+    use super::manifest_by_id;
+    use crate::{portable_path, Chunk, ChunkManifestEntry};
+
+    const SCHEMA: &str = "
+        message chunk {
+            required binary id (UTF8);
+            required binary path (UTF8);
+            required int64 start_line;
+            required int64 end_line;
+            required int64 token_count;
+            optional binary language (UTF8);
+            optional binary symbol (UTF8);
+            optional group tags (LIST) {
+                repeated group list {
+                    required binary element (UTF8);
+                }
+            }
+            optional binary content_hash (UTF8);
+            optional binary file_hash (UTF8);
+            required binary content (UTF8);
+            optional group embedding (LIST) {
+                repeated group list {
+                    required float element;
+                }
+            }
+        }
+    ";
+
+    // Values with definition and repetition levels for one column
+    struct Column<T> {
+        values: Vec<T>,
+        definitions: Vec<i16>,
+        repetitions: Vec<i16>,
+    }
+
+    impl<T> Column<T> {
+        fn new() -> Self {
+            Self { values: Vec::new(), definitions: Vec::new(), repetitions: Vec::new() }
+        }
+
+        fn push_optional(&mut self, value: Option<T>) {
+            self.definitions.push(value.is_some() as i16);
+            self.values.extend(value);
+        }
+
+        // Null lists get level 0, empty lists 1 and each item 2
+        fn push_list(&mut self, items: Option<Vec<T>>) {
+            match items {
+                None => {
+                    self.definitions.push(0);
+                    self.repetitions.push(0);
+                }
+                Some(items) if items.is_empty() => {
+                    self.definitions.push(1);
+                    self.repetitions.push(0);
+                }
+                Some(items) => {
+                    for (index, item) in items.into_iter().enumerate() {
+                        self.definitions.push(2);
+                        self.repetitions.push((index > 0) as i16);
+                        self.values.push(item);
+                    }
+                }
+            }
+        }
+    }
+
+    fn text(value: &str) -> ByteArray {
+        ByteArray::from(value.as_bytes().to_vec())
+    }
+
+    // One row per chunk in a single row group, uncompressed so any reader opens it
+    pub fn write_chunks_parquet<W: Write + Send>(chunks: &[Chunk], manifest: &[ChunkManifestEntry], out: W) -> io::Result<()> {
+        let manifest = manifest_by_id(manifest);
+        let entries: Vec<Option<&&ChunkManifestEntry>> = chunks.iter().map(|chunk| manifest.get(chunk.id.as_str())).collect();
+
+        let required = |value: fn(&Chunk) -> ByteArray| chunks.iter().map(value).collect::<Vec<_>>();
+        let number = |value: fn(&Chunk) -> usize| chunks.iter().map(|chunk| value(chunk) as i64).collect::<Vec<_>>();
+        let optional = |value: &dyn Fn(usize) -> Option<ByteArray>| {
+            let mut column = Column::new();
+            for index in 0..chunks.len() {
+                column.push_optional(value(index));
+            }
+            column
+        };
+        let mut tags = Column::new();
+        let mut embeddings = Column::new();
+        for entry in &entries {
+            tags.push_list(entry.map(|entry| entry.tags.iter().map(|tag| text(tag)).collect()));
+            embeddings.push_list(entry.and_then(|entry| entry.embedding.clone()));
+        }
+
+        let schema = Arc::new(parse_message_type(SCHEMA).map_err(io::Error::other)?);
+        let mut writer = SerializedFileWriter::new(out, schema, Arc::new(WriterProperties::builder().build())).map_err(io::Error::other)?;
+        let mut row_group = writer.next_row_group().map_err(io::Error::other)?;
+        let mut index = 0;
+        while let Some(mut column) = row_group.next_column().map_err(io::Error::other)? {
+            let written = match index {
+                0 => column.typed::<ByteArrayType>().write_batch(&required(|chunk| text(&chunk.id)), None, None),
+                1 => column.typed::<ByteArrayType>().write_batch(&required(|chunk| text(&portable_path(&chunk.path))), None, None),
+                2 => column.typed::<Int64Type>().write_batch(&number(|chunk| chunk.start_line), None, None),
+                3 => column.typed::<Int64Type>().write_batch(&number(|chunk| chunk.end_line), None, None),
+                4 => column.typed::<Int64Type>().write_batch(&number(|chunk| chunk.token_count), None, None),
+                5 | 6 | 8 | 9 => {
+                    let values = optional(&|row| match index {
+                        5 => chunks[row].language.as_deref().map(text),
+                        6 => entries[row].and_then(|entry| entry.symbol.as_deref()).map(text),
+                        8 => entries[row].map(|entry| text(&entry.content_hash)),
+                        _ => entries[row].map(|entry| text(&entry.file_hash)),
+                    });
+                    column.typed::<ByteArrayType>().write_batch(&values.values, Some(&values.definitions), None)
+                }
+                7 => column.typed::<ByteArrayType>().write_batch(&tags.values, Some(&tags.definitions), Some(&tags.repetitions)),
+                10 => column.typed::<ByteArrayType>().write_batch(&required(|chunk| text(&chunk.content)), None, None),
+                _ => column.typed::<FloatType>().write_batch(&embeddings.values, Some(&embeddings.definitions), Some(&embeddings.repetitions)),
+            };
+            written.map_err(io::Error::other)?;
+            column.close().map_err(io::Error::other)?;
+            index += 1;
+        }
+        row_group.close().map_err(io::Error::other)?;
+        writer.close().map_err(io::Error::other)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn sample() -> (Vec<Chunk>, Vec<ChunkManifestEntry>) {
+        let chunk = |id: &str, content: &str| Chunk {
+            id: id.to_string(),
+            path: PathBuf::from("src/lib.rs"),
+            start_line: 1,
+            end_line: 3,
+            token_count: 5,
+            language: Some("rust".to_string()),
+            content: content.to_string(),
+        };
+        let chunks = vec![chunk("src/lib.rs#L1-L3", "fn a() {\n    \"x\"\n}"), chunk("src/lib.rs#L4-L6", "fn b() {}")];
+        let manifest = vec![ChunkManifestEntry {
+            id: "src/lib.rs#L1-L3".to_string(),
+            path: PathBuf::from("src/lib.rs"),
+            start_line: 1,
+            end_line: 3,
+            symbol: Some("a".to_string()),
+            tags: vec!["source".to_string(), "rust".to_string()],
+            content_hash: "c1".to_string(),
+            file_hash: "f1".to_string(),
+            embedding: Some(vec![0.5, 0.25]),
+        }];
+        (chunks, manifest)
+    }
+
+    #[test]
+    fn test_qdrant_points_carry_vectors_and_payload() {
+        let (chunks, manifest) = sample();
+        let lines: Vec<Value> = to_qdrant_points(&chunks, &manifest).lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["id"], json!(chunk_point_id("src/lib.rs#L1-L3")));
+        assert_eq!(lines[0]["vector"], json!([0.5, 0.25]));
+        assert_eq!(lines[0]["payload"]["symbol"], "a");
+        assert_eq!(lines[0]["payload"]["tags"], json!(["source", "rust"]));
+        assert!(lines[1].get("vector").is_none());
+
+        let id = chunk_point_id("src/lib.rs#L1-L3");
+        assert_eq!(id.len(), 36);
+        assert_eq!(&id[14..15], "8");
+        assert!(matches!(&id[19..20], "8" | "9" | "a" | "b"), "{}", id);
+        assert_ne!(id, chunk_point_id("src/lib.rs#L4-L6"));
+    }
+
+    #[test]
+    fn test_pgvector_csv_quotes_values_and_leaves_nulls_empty() {
+        let (chunks, manifest) = sample();
+        let csv = to_pgvector_csv(&chunks, &manifest);
+        let mut lines = csv.split('\n');
+
+        assert_eq!(lines.next().unwrap(), PGVECTOR_COLUMNS.join(","));
+        let first = lines.next().unwrap();
+        assert!(first.starts_with("\"src/lib.rs#L1-L3\",\"src/lib.rs\",\"1\",\"3\",\"5\",\"rust\",\"a\",\"{\"\"source\"\",\"\"rust\"\"}\""), "{}", first);
+        assert!(csv.contains("\"fn a() {\n    \"\"x\"\"\n}\",\"[0.5,0.25]\"\n"), "{}", csv);
+        assert!(csv.contains("\"rust\",,,,,\"fn b() {}\",\n"), "{}", csv);
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_rows_round_trip() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::record::Field;
+
+        let (chunks, manifest) = sample();
+        let file = tempfile::NamedTempFile::new().unwrap();
+        write_chunks_parquet(&chunks, &manifest, file.reopen().unwrap()).unwrap();
+
+        let reader = SerializedFileReader::new(file.reopen().unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+        let rows: Vec<_> = reader.get_row_iter(None).unwrap().map(Result::unwrap).collect();
+        let field = |row: usize, name: &str| rows[row].get_column_iter().find(|(column, _)| *column == name).unwrap().1.clone();
+
+        assert_eq!(field(0, "symbol"), Field::Str("a".to_string()));
+        assert_eq!(field(1, "symbol"), Field::Null);
+        assert_eq!(field(0, "embedding").to_string(), "[0.5, 0.25]");
+        assert_eq!(field(0, "tags").to_string(), "[\"source\", \"rust\"]");
+        assert_eq!(field(1, "embedding"), Field::Null);
+    }
+}
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::chunk_filter::comment_text;
+use crate::layout::language_of;
+use crate::llms_txt::DATA_LANGUAGES;
+use crate::retrieval::split_identifier;
+use crate::{ContentAnalyzer, FileEntry, FileSystem, ScanResult};
+
+// Files larger than this contribute their identifiers only
+const MAX_TEXT_SOURCE: u64 = 1024 * 1024;
+const MIN_TERM_CHARS: usize = 3;
+// English function words and the keywords and types most languages share,
+// which say nothing about the project's domain
+const STOP_WORDS: &[&str] = &[
+    "about", "after", "all", "also", "and", "any", "are", "async", "await", "before", "being", "between", "bool", "both",
+    "but", "can", "cannot", "class", "const", "could", "crate", "def", "default", "does", "each", "else", "enum", "err",
+    "export", "false", "first", "for", "from", "func", "function", "get", "had", "has", "have", "here", "how", "if",
+    "impl", "import", "into", "int", "isn", "its", "just", "let", "like", "may", "more", "most", "must", "mut", "new",
+    "next", "nil", "none", "not", "null", "off", "once", "one", "only", "option", "other", "our", "out", "over", "per",
+    "pub", "result", "return", "same", "see", "self", "set", "should", "some", "static", "str", "string", "struct",
+    "such", "super", "than", "that", "the", "their", "them", "then", "there", "these", "they", "this", "those", "through",
+    "todo", "trait", "true", "two", "type", "under", "use", "used", "uses", "using", "value", "var", "vec", "via", "was",
+    "were", "what", "when", "where", "which", "while", "who", "will", "with", "within", "without", "would", "you", "your",
+];
+
+// A recurring word of the project's identifiers, comments and docs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VocabularyTerm {
+    pub term: String,
+    // Occurrences, and the files they are in
+    pub count: usize,
+    pub files: usize,
+}
+
+// The project's jargon: words of exported and declared names (split at
+// underscores and camelCase humps), comment lines and documentation, found in
+// the most files first, then the most often. Names come from enhanced
+// analysis; comments and docs are read through `fs`.
+pub fn extract_vocabulary(result: &ScanResult, fs: &dyn FileSystem, limit: usize) -> Vec<VocabularyTerm> {
+    let analyzer = ContentAnalyzer::new();
+    let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+    for file in result.files.iter().filter(|file| !file.is_dir && file.media.is_none()) {
+        let mut words = identifier_words(file);
+        let language = language_of(file, &analyzer);
+        let prose = matches!(language.as_str(), "markdown" | "text");
+        if file.size <= MAX_TEXT_SOURCE && (prose || !(DATA_LANGUAGES.contains(&language.as_str()) || language == "unknown")) {
+            if let Ok(content) = fs.read_to_string(&file.path) {
+                words.extend(text_words(&content, prose));
+            }
+        }
+
+        let mut seen = HashSet::new();
+        for word in words {
+            let entry = counts.entry(word.clone()).or_default();
+            entry.0 += 1;
+            if seen.insert(word) {
+                entry.1 += 1;
+            }
+        }
+    }
+
+    let mut terms: Vec<VocabularyTerm> = counts.into_iter()
+        .map(|(term, (count, files))| VocabularyTerm { term, count, files })
+        .collect();
+    terms.sort_by(|a, b| b.files.cmp(&a.files).then_with(|| b.count.cmp(&a.count)).then_with(|| a.term.cmp(&b.term)));
+    terms.truncate(limit);
+    terms
+}
+
+fn identifier_words(file: &FileEntry) -> Vec<String> {
+    let Some(info) = &file.enhanced_info else {
+        return Vec::new();
+    };
+    info.exports.iter()
+        .chain(info.symbols.iter().map(|symbol| &symbol.name))
+        .flat_map(|identifier| words(identifier))
+        .collect()
+}
+
+// All of a document outside code fences, or a source file's comment lines
+fn text_words(content: &str, prose: bool) -> Vec<String> {
+    let mut in_fence = false;
+    content.lines()
+        .filter_map(|line| if prose {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+                return None;
+            }
+            (!in_fence).then_some(line)
+        } else {
+            comment_text(line)
+        })
+        .flat_map(words)
+        .collect()
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .flat_map(split_identifier)
+        .filter(|word| {
+            word.chars().count() >= MIN_TERM_CHARS && word.chars().all(char::is_alphabetic) && !STOP_WORDS.contains(&word.as_str())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryScanner, MemoryFileSystem, ScanOptions};
+
+    fn project() -> (MemoryFileSystem, ScanResult) {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("shop/README.md", "# Shop\nA storefront where a cart becomes an invoice at checkout.\n```rust\nlet ignored_fence = 1;\n```\n");
+        fs.insert("shop/src/cart.rs", "// Adds a line item to the cart\npub fn add_cart_item(cart: &mut Cart) {}\n");
+        fs.insert("shop/src/invoice.rs", "// Turns a cart into an invoice\npub fn invoiceFromCart() {}\n");
+        fs.insert("shop/data.json", "{\"cart\": \"not counted\"}\n");
+        let options = ScanOptions { enhanced_analysis: true, ..Default::default() };
+        let result = DirectoryScanner::new(options).scan_with(&fs, "shop").unwrap();
+        (fs, result)
+    }
+
+    #[test]
+    fn test_ranks_terms_by_spread_then_count() {
+        let (fs, result) = project();
+        let vocabulary = extract_vocabulary(&result, &fs, 3);
+
+        assert_eq!(vocabulary.len(), 3);
+        assert_eq!(vocabulary[0].term, "cart");
+        assert_eq!(vocabulary[0].files, 3);
+        assert_eq!(vocabulary[1].term, "invoice");
+        assert!(vocabulary[0].count > vocabulary[0].files);
+    }
+
+    #[test]
+    fn test_skips_stop_words_fences_and_data_files() {
+        let (fs, result) = project();
+        let terms: Vec<String> = extract_vocabulary(&result, &fs, 100).into_iter().map(|term| term.term).collect();
+
+        assert!(terms.contains(&"storefront".to_string()) && terms.contains(&"checkout".to_string()));
+        assert!(!terms.iter().any(|term| ["the", "pub", "fence", "counted", "an"].contains(&term.as_str())), "{:?}", terms);
+    }
+
+    #[test]
+    fn test_splits_identifiers() {
+        assert_eq!(words("invoiceFromCart add_cart_item HTTPServer v2 x"), ["invoice", "cart", "add", "cart", "item", "httpserver"]);
+        assert_eq!(text_words("// Cart totals\nlet cart = 1;\n", false), ["cart", "totals"]);
+    }
+}
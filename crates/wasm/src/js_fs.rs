@@ -0,0 +1,203 @@
+use js_sys::{Function, Reflect, Uint8Array};
+use serde::Deserialize;
+use serde_wasm_bindgen::from_value;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use thinkeloquent_tools_chunking_directory_mapping_core::{
+    FileSystem, FsEntry, IgnoreStack, ScanError, ScanIssue, ScanOptions, IGNORE_FILE_NAME,
+};
+use wasm_bindgen::{JsCast, JsValue};
+
+// Result of the host's `stat` callback; `null` means the path does not exist
+#[derive(Debug, Deserialize)]
+struct JsStat {
+    is_dir: bool,
+    #[serde(default)]
+    size: f64,
+    #[serde(default)]
+    mtime_ms: Option<f64>,
+    // Device and inode identify a directory reached through symlinks; without
+    // them a link back to an ancestor is followed until max_depth
+    #[serde(default)]
+    dev: Option<f64>,
+    #[serde(default)]
+    ino: Option<f64>,
+    // Permission bits, as fs.Stats.mode, for the permission audit
+    #[serde(default)]
+    mode: Option<f64>,
+}
+
+impl JsStat {
+    fn identity(&self) -> Option<(u64, u64)> {
+        Some((self.dev? as u64, self.ino? as u64))
+    }
+}
+
+// A directory being walked, kept while its descendants are so loops can be spotted
+struct Ancestor {
+    path: PathBuf,
+    identity: (u64, u64),
+}
+
+// Real filesystem access for hosts such as Node, where wasm cannot reach the
+// disk itself: every stat, directory listing and read goes through a callback
+pub struct JsFileSystem {
+    stat: Function,
+    readdir: Function,
+    read_file: Function,
+}
+
+impl JsFileSystem {
+    pub fn from_callbacks(callbacks: &JsValue) -> Result<Self, JsValue> {
+        let callback = |name: &str| -> Result<Function, JsValue> {
+            Reflect::get(callbacks, &JsValue::from_str(name))?
+                .dyn_into::<Function>()
+                .map_err(|_| JsValue::from_str(&format!("File system callbacks must provide a `{}` function", name)))
+        };
+
+        Ok(Self {
+            stat: callback("stat")?,
+            readdir: callback("readdir")?,
+            read_file: callback("read_file")?,
+        })
+    }
+
+    fn call(&self, callback: &Function, path: &Path) -> io::Result<JsValue> {
+        callback.call1(&JsValue::NULL, &JsValue::from_str(&path.to_string_lossy()))
+            .map_err(|e| io_error(path, &e))
+    }
+
+    fn stat_path(&self, path: &Path) -> io::Result<Option<JsStat>> {
+        let value = self.call(&self.stat, path)?;
+        if value.is_null() || value.is_undefined() {
+            return Ok(None);
+        }
+        from_value(value)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid stat for {}: {}", path.display(), e)))
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<String>> {
+        let value = self.call(&self.readdir, path)?;
+        let mut names: Vec<String> = from_value(value)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid listing for {}: {}", path.display(), e)))?;
+        // Hosts list in arbitrary order; sort so scans are reproducible
+        names.sort();
+        Ok(names)
+    }
+
+    fn walk_into(
+        &self,
+        path: &Path,
+        depth: usize,
+        options: &ScanOptions,
+        ignore_files: &mut IgnoreStack,
+        ancestors: &mut Vec<Ancestor>,
+        entries: &mut Vec<Result<FsEntry, ScanIssue>>,
+    ) {
+        let stat = match self.stat_path(path) {
+            Ok(Some(stat)) => stat,
+            Ok(None) => return,
+            Err(e) => {
+                entries.push(Err(ScanIssue::from_scan_error(path, &ScanError::Io(e))));
+                return;
+            }
+        };
+
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("").to_string();
+        if options.is_ignored(path, &name)
+            || (depth > 0 && options.use_ignore_files && ignore_files.is_ignored(path, stat.is_dir))
+        {
+            return;
+        }
+
+        let identity = stat.is_dir.then(|| stat.identity()).flatten();
+        if let Some(ancestor) = identity.and_then(|identity| ancestors.iter().find(|ancestor| ancestor.identity == identity)) {
+            entries.push(Err(ScanIssue::symlink_loop(path, &ancestor.path)));
+            return;
+        }
+
+        entries.push(Ok(FsEntry {
+            path: path.to_path_buf(),
+            name,
+            is_dir: stat.is_dir,
+            size: if stat.is_dir { 0 } else { stat.size.max(0.0) as u64 },
+            modified: stat.mtime_ms
+                .map_or(SystemTime::UNIX_EPOCH, |ms| SystemTime::UNIX_EPOCH + Duration::from_millis(ms.max(0.0) as u64)),
+            symlink: None,
+            mode: stat.mode.map(|mode| mode as u32),
+        }));
+
+        if !stat.is_dir {
+            return;
+        }
+        if depth >= options.walk_depth() {
+            if options.stops_at_depth_guard(depth) && self.read_dir(path).is_ok_and(|names| !names.is_empty()) {
+                entries.push(Err(ScanIssue::depth_limit(path, depth)));
+            }
+            return;
+        }
+        match self.read_dir(path) {
+            Ok(mut names) => {
+                // Only ask the host for the ignore file when the listing has one
+                if options.use_ignore_files && names.iter().any(|name| name == IGNORE_FILE_NAME) {
+                    ignore_files.enter(path, |file| self.read_to_string(file).ok());
+                }
+                // Names the ignore patterns leave out do not count against the cap
+                names.retain(|name| !options.is_ignored(&path.join(name), name));
+                let cap = options.max_entries_per_dir.unwrap_or(usize::MAX);
+                if names.len() > cap {
+                    entries.push(Err(ScanIssue::entry_limit(path, cap, names.len() - cap)));
+                    names.truncate(cap);
+                }
+                if let Some(identity) = identity {
+                    ancestors.push(Ancestor { path: path.to_path_buf(), identity });
+                }
+                for child in names {
+                    self.walk_into(&path.join(child), depth + 1, options, ignore_files, ancestors, entries);
+                }
+                if identity.is_some() {
+                    ancestors.pop();
+                }
+            }
+            Err(e) => entries.push(Err(ScanIssue::from_scan_error(path, &ScanError::Io(e)))),
+        }
+    }
+}
+
+impl FileSystem for JsFileSystem {
+    fn exists(&self, path: &Path) -> bool {
+        matches!(self.stat_path(path), Ok(Some(_)))
+    }
+
+    fn walk<'a>(&'a self, root: &Path, options: &'a ScanOptions) -> Box<dyn Iterator<Item = Result<FsEntry, ScanIssue>> + 'a> {
+        let mut entries = Vec::new();
+        self.walk_into(root, 0, options, &mut IgnoreStack::default(), &mut Vec::new(), &mut entries);
+        Box::new(entries.into_iter())
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let value = self.call(&self.read_file, path)?;
+        if let Some(text) = value.as_string() {
+            return Ok(text.into_bytes());
+        }
+        // Node's Buffer is a Uint8Array subclass
+        value.dyn_into::<Uint8Array>()
+            .map(|bytes| bytes.to_vec())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("read_file for {} must return a string or Uint8Array", path.display())))
+    }
+}
+
+// Maps a thrown JS error to an io::Error, keeping Node's `code` (ENOENT, EACCES)
+// so issues are classified the same way as native scans
+fn io_error(path: &Path, error: &JsValue) -> io::Error {
+    let field = |name: &str| Reflect::get(error, &JsValue::from_str(name)).ok().and_then(|value| value.as_string());
+    let kind = match field("code").as_deref() {
+        Some("ENOENT") => io::ErrorKind::NotFound,
+        Some("EACCES") | Some("EPERM") => io::ErrorKind::PermissionDenied,
+        _ => io::ErrorKind::Other,
+    };
+    let message = field("message").or_else(|| error.as_string()).unwrap_or_else(|| "unknown error".to_string());
+    io::Error::new(kind, format!("{}: {}", path.display(), message))
+}
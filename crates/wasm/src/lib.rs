@@ -1,50 +1,78 @@
 use thinkeloquent_tools_chunking_directory_mapping_core::{
-    ScanOptions, DirectoryScanner, OutputFormat, OutputFormatter,
-    ContentAnalyzer, ScanResult, FileEntry
+    ScanOptions, DirectoryScanner, OutputFormat, OutputFormatter, ComplexityCaps,
+    analyze_content, ContentAnalyzer, ScanResult, SymbolIndex, MapperRegistry, MemoryFileSystem,
+    OsFileSystem, FileSystem, ScanProgress, Chunker, ChunkOptions, FilterExpr, ScanDiff, parse_rfc3339, NestedRepoMode, SecretsMode, Sampling, LanguageMap, ImportanceBoost
 };
 use wasm_bindgen::prelude::*;
-use serde_wasm_bindgen::to_value;
-use serde::{Serialize, Deserialize};
-use std::collections::HashMap;
+use wasm_bindgen_futures::JsFuture;
+use serde_wasm_bindgen::from_value;
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use js_fs::JsFileSystem;
+use types::{
+    BranchingDetailsValue, BuildInfoValue, ChunkArray, ChunkOptionsArg, EnhancedFileInfoValue, FileEntryArray,
+    FsCallbacksArg, ProgressCallbackArg, ScanDiffValue, ScanIssueArray, ScanOptionsArg, ScanResultValue, ScanStatsValue, StringArray,
+    VirtualFilesArg, WasmScanOptionsArg, WasmScanOptionsValue, WasmScanResultValue,
+};
+
+mod js_fs;
+mod types;
 
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = console)]
     fn log(s: &str);
+    
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(callback: &js_sys::Function, delay_ms: i32);
 }
 
+// Entries processed between yields to the JS event loop in async scans
+const ASYNC_SCAN_BATCH: usize = 200;
+
 macro_rules! console_log {
     ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
 }
 
 // Enhanced options wrapper for WASM
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
 pub struct WasmScanOptions {
     pub mapper_profile: Option<String>,
     pub enhanced_analysis: Option<bool>,
-    pub output_format: Option<String>, // "basic", "compact", "detailed", "hierarchical"
+    pub output_format: Option<String>, // "basic", "compact", "detailed", "hierarchical", "repo-map", "tree", "summary"
     pub max_depth: Option<usize>,
     pub include_hidden: Option<bool>,
     pub follow_symlinks: Option<bool>,
     pub ignore_patterns: Option<Vec<String>>,
+    pub include_patterns: Option<Vec<String>>,
+    pub collect_metadata: Option<bool>,
+    pub complexity_caps: Option<ComplexityCaps>,
+    pub normalize_complexity: Option<bool>,
+    pub use_ignore_files: Option<bool>,
+    pub sort_by_path: Option<bool>,
+    pub include_broken_symlinks: Option<bool>,
+    pub sniff_media: Option<bool>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub modified_after: Option<String>, // RFC 3339
+    pub modified_before: Option<String>,
+    pub hash_contents: Option<bool>,
+    pub nested_repos: Option<NestedRepoMode>, // "scan", "summarize" or "skip"
+    pub secrets: Option<SecretsMode>, // "off", "redact" or "strict"
+    pub max_entries_per_dir: Option<usize>, // 0 lifts the cap
+    pub depth_guard: Option<usize>, // 0 lifts the guard
+    pub sampling: Option<Sampling>,
+    pub languages: Option<LanguageMap>,
+    pub importance_boosts: Option<BTreeMap<String, Vec<ImportanceBoost>>>, // Per mapper profile
 }
 
-impl Default for WasmScanOptions {
-    fn default() -> Self {
-        Self {
-            mapper_profile: None,
-            enhanced_analysis: None,
-            output_format: None,
-            max_depth: None,
-            include_hidden: None,
-            follow_symlinks: None,
-            ignore_patterns: None,
-        }
-    }
-}
-
-impl From<WasmScanOptions> for ScanOptions {
-    fn from(wasm_opts: WasmScanOptions) -> Self {
+impl TryFrom<WasmScanOptions> for ScanOptions {
+    type Error = String;
+    
+    fn try_from(wasm_opts: WasmScanOptions) -> Result<Self, Self::Error> {
         let mut opts = ScanOptions::default();
         
         if let Some(profile) = wasm_opts.mapper_profile {
@@ -61,7 +89,12 @@ impl From<WasmScanOptions> for ScanOptions {
                 "compact" => OutputFormat::Compact,
                 "detailed" => OutputFormat::Detailed,
                 "hierarchical" => OutputFormat::Hierarchical,
-                _ => OutputFormat::Basic,
+                "repo-map" => OutputFormat::RepoMap,
+                "tree" => OutputFormat::Tree,
+                "summary" => OutputFormat::Summary,
+                other => return Err(format!(
+                    "unknown output_format '{}', expected one of: basic, compact, detailed, hierarchical, repo-map, tree, summary", other
+                )),
             };
         }
         
@@ -81,7 +114,80 @@ impl From<WasmScanOptions> for ScanOptions {
             opts.ignore_patterns = patterns;
         }
         
-        opts
+        if let Some(patterns) = wasm_opts.include_patterns {
+            opts.include_patterns = patterns;
+        }
+        
+        if let Some(metadata) = wasm_opts.collect_metadata {
+            opts.collect_metadata = metadata;
+        }
+        
+        if let Some(caps) = wasm_opts.complexity_caps {
+            opts.complexity_caps = caps;
+        }
+        
+        if let Some(normalize) = wasm_opts.normalize_complexity {
+            opts.normalize_complexity = normalize;
+        }
+        
+        if let Some(use_ignore_files) = wasm_opts.use_ignore_files {
+            opts.use_ignore_files = use_ignore_files;
+        }
+        
+        if let Some(sort_by_path) = wasm_opts.sort_by_path {
+            opts.sort_by_path = sort_by_path;
+        }
+        
+        if let Some(include_broken_symlinks) = wasm_opts.include_broken_symlinks {
+            opts.include_broken_symlinks = include_broken_symlinks;
+        }
+        
+        if let Some(sniff_media) = wasm_opts.sniff_media {
+            opts.sniff_media = sniff_media;
+        }
+        
+        opts.min_size = wasm_opts.min_size;
+        opts.max_size = wasm_opts.max_size;
+        
+        if let Some(after) = wasm_opts.modified_after {
+            opts.modified_after = Some(parse_rfc3339(&after).ok_or_else(|| format!("invalid modified_after '{}', expected RFC 3339", after))?);
+        }
+        
+        if let Some(before) = wasm_opts.modified_before {
+            opts.modified_before = Some(parse_rfc3339(&before).ok_or_else(|| format!("invalid modified_before '{}', expected RFC 3339", before))?);
+        }
+        
+        if let Some(hash_contents) = wasm_opts.hash_contents {
+            opts.hash_contents = hash_contents;
+        }
+        
+        if let Some(nested_repos) = wasm_opts.nested_repos {
+            opts.nested_repos = nested_repos;
+        }
+        
+        if let Some(secrets) = wasm_opts.secrets {
+            opts.secrets = secrets;
+        }
+        
+        if let Some(cap) = wasm_opts.max_entries_per_dir {
+            opts.max_entries_per_dir = (cap > 0).then_some(cap);
+        }
+        
+        if let Some(guard) = wasm_opts.depth_guard {
+            opts.depth_guard = (guard > 0).then_some(guard);
+        }
+        
+        opts.sampling = wasm_opts.sampling;
+        
+        if let Some(languages) = wasm_opts.languages {
+            opts.languages = languages;
+        }
+        
+        if let Some(importance_boosts) = wasm_opts.importance_boosts {
+            opts.importance_boosts = importance_boosts;
+        }
+        
+        Ok(opts)
     }
 }
 
@@ -94,51 +200,155 @@ pub struct WasmScanResult {
     pub yaml_output: Option<String>,
 }
 
-#[wasm_bindgen]
-pub fn scan_directory(path: &str, options_json: Option<String>) -> Result<JsValue, JsValue> {
-    console_log!("Scanning directory: {}", path);
+#[derive(Serialize, Debug)]
+pub struct WasmBranchingDetails {
+    pub conditional_count: usize,
+    pub loop_count: usize,
+    pub switch_count: usize,
+    pub max_nesting: usize,
+    pub logical_operators: usize,
+    pub cyclomatic_complexity: f64,
+    pub cognitive_complexity: f64,
+    pub hardcoded_dates_count: usize,
+    pub hardcoded_values_count: usize,
+    pub pure_branches: usize,
+    pub non_pure_branches: usize,
+    pub future_logic_count: usize,
+    pub past_logic_count: usize,
+    pub total_branches: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    pub name: &'static str,
+    pub authors: &'static str,
+    pub description: &'static str,
+    pub build_date: String,
+    pub git_sha: String,
+}
+
+// A file handed over from JS, e.g. a dropped folder or the File System Access API
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VirtualFile {
+    pub path: String,
+    pub content: String,
+    pub modified: Option<f64>, // Milliseconds since the Unix epoch, as File.lastModified
+}
+
+// Accepts `[{path, content}]` or a plain `{ [path]: content }` object
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum VirtualFiles {
+    List(Vec<VirtualFile>),
+    Map(HashMap<String, String>),
+}
+
+impl VirtualFiles {
+    fn into_files(self) -> Vec<VirtualFile> {
+        match self {
+            VirtualFiles::List(files) => files,
+            VirtualFiles::Map(map) => map.into_iter()
+                .map(|(path, content)| VirtualFile { path, content, modified: None })
+                .collect(),
+        }
+    }
+}
+
+// Options are plain JS objects; JSON strings are still accepted but deprecated
+fn parse_options<T: DeserializeOwned + Default>(options: Option<JsValue>, kind: &str) -> Result<T, JsValue> {
+    let Some(options) = options.filter(|value| !value.is_undefined() && !value.is_null()) else {
+        return Ok(T::default());
+    };
     
-    let wasm_options = if let Some(opts_str) = options_json {
-        serde_json::from_str::<WasmScanOptions>(&opts_str)
-            .unwrap_or_else(|e| {
-                console_log!("Failed to parse options: {}, using defaults", e);
-                WasmScanOptions::default()
-            })
-    } else {
-        WasmScanOptions::default()
+    if let Some(json) = options.as_string() {
+        console_log!("Passing {} as a JSON string is deprecated, pass an object instead", kind);
+        return parse_json_options(&json, kind).map_err(|e| JsValue::from_str(&e));
+    }
+    
+    from_value(options).map_err(|e| JsValue::from_str(&format!("Invalid {}: {}", kind, e)))
+}
+
+fn parse_json_options<T: DeserializeOwned>(json: &str, kind: &str) -> Result<T, String> {
+    serde_json::from_str(json).map_err(|e| format!("Invalid {}: {}", kind, e))
+}
+
+fn parse_scan_options(options: Option<JsValue>) -> Result<ScanOptions, JsValue> {
+    parse_options::<WasmScanOptions>(options, "scan options")?
+        .try_into()
+        .map_err(|e: String| JsValue::from_str(&format!("Invalid scan options: {}", e)))
+}
+
+// Maps become plain objects and missing values null, matching the JSON outputs
+fn to_js<T: Serialize + ?Sized, R: JsCast>(value: &T) -> Result<R, JsValue> {
+    value.serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map(JsCast::unchecked_into)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+fn to_wasm_result(result: ScanResult, options: &ScanOptions) -> Result<WasmScanResultValue, JsValue> {
+    // Create enhanced result with multiple output formats
+    let mut wasm_result = WasmScanResult {
+        formatted_output: None,
+        json_output: None,
+        yaml_output: None,
+        result,
     };
     
-    let options: ScanOptions = wasm_options.clone().into();
+    // Generate formatted output
+    wasm_result.formatted_output = Some(
+        OutputFormatter::format_result(&wasm_result.result, &options.output_format)
+    );
+    
+    // Generate JSON output
+    wasm_result.json_output = serde_json::to_string_pretty(&wasm_result.result)
+        .map_err(|e| console_log!("JSON serialization error: {}", e))
+        .ok();
+    
+    // Generate YAML output
+    wasm_result.yaml_output = serde_yaml::to_string(&wasm_result.result)
+        .map_err(|e| console_log!("YAML serialization error: {}", e))
+        .ok();
+    
+    to_js(&wasm_result)
+}
+
+// Root of a virtual tree: the shared top-level folder of a dropped directory,
+// otherwise "." with every file placed beneath it
+fn virtual_root(paths: &[PathBuf]) -> Option<PathBuf> {
+    let first = paths.first()?.components().next()?;
+    let shared = paths.iter().all(|path| {
+        path.components().count() > 1 && path.components().next() == Some(first)
+    });
+    shared.then(|| PathBuf::from(first.as_os_str()))
+}
+
+// Without `fs` the scan uses std::fs, which only works on WASI-like hosts;
+// Node passes its own `fs` through callbacks instead
+#[wasm_bindgen]
+pub fn scan_directory(
+    path: &str,
+    options: Option<WasmScanOptionsArg>,
+    fs: Option<FsCallbacksArg>,
+) -> Result<WasmScanResultValue, JsValue> {
+    let options = parse_scan_options(options.map(Into::into))?;
+    let result = run_scan(path, &options, fs)?;
+    to_wasm_result(result, &options)
+}
+
+fn run_scan(path: &str, options: &ScanOptions, fs: Option<FsCallbacksArg>) -> Result<ScanResult, JsValue> {
+    console_log!("Scanning directory: {}", path);
+    
     let scanner = DirectoryScanner::new(options.clone());
+    let scanned = match fs {
+        Some(callbacks) => scanner.scan_with(&JsFileSystem::from_callbacks(&callbacks.into())?, path),
+        None => scanner.scan(path),
+    };
     
-    match scanner.scan(path) {
+    match scanned {
         Ok(result) => {
             console_log!("Scan completed: {} files found", result.stats.total_files);
-            
-            // Create enhanced result with multiple output formats
-            let mut wasm_result = WasmScanResult {
-                result: result.clone(),
-                formatted_output: None,
-                json_output: None,
-                yaml_output: None,
-            };
-            
-            // Generate formatted output
-            wasm_result.formatted_output = Some(
-                OutputFormatter::format_result(&result, &options.output_format)
-            );
-            
-            // Generate JSON output
-            wasm_result.json_output = serde_json::to_string_pretty(&result)
-                .map_err(|e| console_log!("JSON serialization error: {}", e))
-                .ok();
-            
-            // Generate YAML output
-            wasm_result.yaml_output = serde_yaml::to_string(&result)
-                .map_err(|e| console_log!("YAML serialization error: {}", e))
-                .ok();
-            
-            to_value(&wasm_result).map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+            Ok(result)
         },
         Err(e) => {
             console_log!("Scan failed: {}", e);
@@ -148,22 +358,159 @@ pub fn scan_directory(path: &str, options_json: Option<String>) -> Result<JsValu
 }
 
 #[wasm_bindgen]
-pub fn scan_directory_simple(path: &str, options_json: Option<String>) -> Result<JsValue, JsValue> {
-    console_log!("Simple directory scan: {}", path);
+pub fn scan_virtual(files: VirtualFilesArg, options: Option<WasmScanOptionsArg>) -> Result<WasmScanResultValue, JsValue> {
+    let options = parse_scan_options(options.map(Into::into))?;
+    let result = run_virtual_scan(files.into(), &options)?;
+    to_wasm_result(result, &options)
+}
+
+fn run_virtual_scan(files: JsValue, options: &ScanOptions) -> Result<ScanResult, JsValue> {
+    let (fs, root) = virtual_file_system(files)?;
+    console_log!("Scanning {} virtual files", fs.len());
+    
+    let scanner = DirectoryScanner::new(options.clone());
+    
+    match scanner.scan_with(&fs, root) {
+        Ok(result) => {
+            console_log!("Virtual scan completed: {} files found", result.stats.total_files);
+            Ok(result)
+        },
+        Err(e) => {
+            console_log!("Virtual scan failed: {}", e);
+            Err(JsValue::from_str(&format!("Scan error: {}", e)))
+        }
+    }
+}
+
+// Builds the in-memory tree used by `scan_virtual` and returns it with its root
+fn virtual_file_system(files: JsValue) -> Result<(MemoryFileSystem, PathBuf), JsValue> {
+    let files = from_value::<VirtualFiles>(files)
+        .map_err(|e| JsValue::from_str(&format!("Invalid file list, expected [{{path, content}}] or {{path: content}}: {}", e)))?
+        .into_files();
+    virtual_tree(files).map_err(|e| JsValue::from_str(&e))
+}
+
+// Paths may start with `/` but not climb out with `..`, and each names one file
+fn virtual_tree(files: Vec<VirtualFile>) -> Result<(MemoryFileSystem, PathBuf), String> {
+    let mut paths = Vec::with_capacity(files.len());
+    let mut seen = HashSet::with_capacity(files.len());
+    for file in &files {
+        let path = PathBuf::from(file.path.trim_start_matches('/'));
+        if !path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+            return Err(format!("Invalid virtual path: {}", file.path));
+        }
+        if !seen.insert(path.clone()) {
+            return Err(format!("Duplicate virtual path: {}", file.path));
+        }
+        paths.push(path);
+    }
+    
+    let root = virtual_root(&paths);
+    let mut fs = MemoryFileSystem::new();
+    for (path, file) in paths.into_iter().zip(files) {
+        let path = if root.is_some() { path } else { Path::new(".").join(path) };
+        let modified = file.modified
+            .map_or(SystemTime::UNIX_EPOCH, |ms| SystemTime::UNIX_EPOCH + Duration::from_millis(ms.max(0.0) as u64));
+        fs.insert_with_modified(path, file.content, modified);
+    }
     
-    let options = if let Some(opts_str) = options_json {
-        serde_json::from_str::<ScanOptions>(&opts_str)
-            .unwrap_or_else(|_| ScanOptions::default())
-    } else {
-        ScanOptions::default()
+    Ok((fs, root.unwrap_or_else(|| PathBuf::from("."))))
+}
+
+// MessagePack with field names, so optional fields that serde skips still decode.
+// Only the ScanResult is encoded; formatted outputs can be produced on demand.
+fn encode_result(result: &ScanResult) -> Result<Vec<u8>, JsValue> {
+    rmp_serde::to_vec_named(result).map_err(|e| JsValue::from_str(&format!("Binary encoding error: {}", e)))
+}
+
+#[wasm_bindgen]
+pub fn scan_directory_binary(
+    path: &str,
+    options: Option<WasmScanOptionsArg>,
+    fs: Option<FsCallbacksArg>,
+) -> Result<Vec<u8>, JsValue> {
+    let options = parse_scan_options(options.map(Into::into))?;
+    encode_result(&run_scan(path, &options, fs)?)
+}
+
+#[wasm_bindgen]
+pub fn scan_virtual_binary(files: VirtualFilesArg, options: Option<WasmScanOptionsArg>) -> Result<Vec<u8>, JsValue> {
+    let options = parse_scan_options(options.map(Into::into))?;
+    encode_result(&run_virtual_scan(files.into(), &options)?)
+}
+
+#[wasm_bindgen]
+pub fn encode_scan_result(result_json: &str) -> Result<Vec<u8>, JsValue> {
+    let result = ScanResult::from_json(result_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse result: {}", e)))?;
+    encode_result(&result)
+}
+
+#[wasm_bindgen]
+pub fn decode_scan_result(bytes: &[u8]) -> Result<ScanResultValue, JsValue> {
+    let result: ScanResult = rmp_serde::from_slice(bytes)
+        .map_err(|e| JsValue::from_str(&format!("Binary decoding error: {}", e)))?;
+    to_js(&result)
+}
+
+// Resolves on the next macrotask so the browser can render and Node can serve I/O
+async fn yield_to_event_loop() -> Result<(), JsValue> {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| set_timeout(&resolve, 0));
+    JsFuture::from(promise).await.map(|_| ())
+}
+
+fn report_progress(on_progress: &Option<js_sys::Function>, progress: &ScanProgress) -> Result<(), JsValue> {
+    if let Some(callback) = on_progress {
+        let value: JsValue = to_js(progress)?;
+        callback.call1(&JsValue::NULL, &value)?;
+    }
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub async fn scan_directory_async(
+    path: String,
+    options: Option<WasmScanOptionsArg>,
+    on_progress: Option<ProgressCallbackArg>,
+    fs: Option<FsCallbacksArg>,
+) -> Result<WasmScanResultValue, JsValue> {
+    console_log!("Async scanning directory: {}", path);
+    
+    let options = parse_scan_options(options.map(Into::into))?;
+    let on_progress: Option<js_sys::Function> = on_progress.map(JsCast::unchecked_into);
+    let scanner = DirectoryScanner::new(options.clone());
+    let fs: Box<dyn FileSystem> = match fs {
+        Some(callbacks) => Box::new(JsFileSystem::from_callbacks(&callbacks.into())?),
+        None => Box::new(OsFileSystem),
     };
     
+    let mut run = scanner.start(fs.as_ref(), path.as_str())
+        .map_err(|e| JsValue::from_str(&format!("Scan error: {}", e)))?;
+    while run.step() {
+        if run.progress().entries_scanned() % ASYNC_SCAN_BATCH == 0 {
+            report_progress(&on_progress, run.progress())?;
+            yield_to_event_loop().await?;
+        }
+    }
+    report_progress(&on_progress, run.progress())?;
+    
+    let result = run.finish();
+    console_log!("Async scan completed: {} files found", result.stats.total_files);
+    to_wasm_result(result, &options)
+}
+
+#[wasm_bindgen]
+pub fn scan_directory_simple(path: &str, options: Option<ScanOptionsArg>) -> Result<ScanResultValue, JsValue> {
+    console_log!("Simple directory scan: {}", path);
+    
+    let options = parse_options::<ScanOptions>(options.map(Into::into), "scan options")?;
+    
     let scanner = DirectoryScanner::new(options);
     
     match scanner.scan(path) {
         Ok(result) => {
             console_log!("Simple scan completed: {} files found", result.stats.total_files);
-            to_value(&result).map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+            to_js(&result)
         },
         Err(e) => {
             console_log!("Simple scan failed: {}", e);
@@ -174,7 +521,7 @@ pub fn scan_directory_simple(path: &str, options_json: Option<String>) -> Result
 
 #[wasm_bindgen]
 pub fn format_scan_result(result_json: &str, format_type: &str) -> Result<String, JsValue> {
-    let result: ScanResult = serde_json::from_str(result_json)
+    let result = ScanResult::from_json(result_json)
         .map_err(|e| JsValue::from_str(&format!("Failed to parse result: {}", e)))?;
     
     let output_format = match format_type {
@@ -182,7 +529,10 @@ pub fn format_scan_result(result_json: &str, format_type: &str) -> Result<String
         "compact" => OutputFormat::Compact,
         "detailed" => OutputFormat::Detailed,
         "hierarchical" => OutputFormat::Hierarchical,
-        _ => return Err(JsValue::from_str("Invalid format type. Use: basic, compact, detailed, hierarchical")),
+        "repo-map" => OutputFormat::RepoMap,
+        "tree" => OutputFormat::Tree,
+        "summary" => OutputFormat::Summary,
+        _ => return Err(JsValue::from_str("Invalid format type. Use: basic, compact, detailed, hierarchical, repo-map, tree, summary")),
     };
     
     Ok(OutputFormatter::format_result(&result, &output_format))
@@ -190,7 +540,7 @@ pub fn format_scan_result(result_json: &str, format_type: &str) -> Result<String
 
 #[wasm_bindgen]
 pub fn scan_result_to_yaml(result_json: &str) -> Result<String, JsValue> {
-    let result: ScanResult = serde_json::from_str(result_json)
+    let result = ScanResult::from_json(result_json)
         .map_err(|e| JsValue::from_str(&format!("Failed to parse result: {}", e)))?;
     
     serde_yaml::to_string(&result)
@@ -198,82 +548,136 @@ pub fn scan_result_to_yaml(result_json: &str) -> Result<String, JsValue> {
 }
 
 #[wasm_bindgen]
-pub fn analyze_file_content(file_path: &str, content: &str, _language: Option<String>) -> Result<JsValue, JsValue> {
+pub fn analyze_file_content(file_path: &str, content: &str, language: Option<String>) -> Result<EnhancedFileInfoValue, JsValue> {
     console_log!("Analyzing file content: {}", file_path);
     
+    let enhanced_info = analyze_content(file_path, content, language.as_deref());
+    console_log!("File analysis completed for: {}", file_path);
+    to_js(&enhanced_info)
+}
+
+#[wasm_bindgen]
+pub fn analyze_branching_details(content: &str, language: Option<String>) -> Result<BranchingDetailsValue, JsValue> {
+    console_log!("Analyzing branching details for {} chars of content", content.len());
+    
     let analyzer = ContentAnalyzer::new();
+    let details = analyzer.analyze_branching_details(content, &language);
     
-    // Create a basic FileEntry for analysis
-    let file_entry = FileEntry {
-        path: file_path.into(),
-        name: std::path::Path::new(file_path)
-            .file_name()
-            .and_then(|name| name.to_str())
-            .unwrap_or(file_path)
-            .to_string(),
-        size: content.len() as u64,
-        modified: std::time::SystemTime::now(),
-        is_dir: false,
-        tags: vec![],
-        metadata: None,
-        enhanced_info: None,
+    // BranchingDetails itself is not serializable; copy out the public counters
+    let details = WasmBranchingDetails {
+        conditional_count: details.conditional_count,
+        loop_count: details.loop_count,
+        switch_count: details.switch_count,
+        max_nesting: details.max_nesting,
+        logical_operators: details.logical_operators,
+        cyclomatic_complexity: details.cyclomatic_complexity,
+        cognitive_complexity: details.cognitive_complexity,
+        hardcoded_dates_count: details.hardcoded_dates_count,
+        hardcoded_values_count: details.hardcoded_values_count,
+        pure_branches: details.pure_branches,
+        non_pure_branches: details.non_pure_branches,
+        future_logic_count: details.future_logic_count,
+        past_logic_count: details.past_logic_count,
+        total_branches: details.total_branches,
     };
     
-    match analyzer.analyze_file(&file_entry) {
-        Ok(enhanced_info) => {
-            console_log!("File analysis completed for: {}", file_path);
-            to_value(&enhanced_info).map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
-        },
-        Err(e) => {
-            console_log!("File analysis failed: {}", e);
-            Err(JsValue::from_str(&format!("Analysis error: {}", e)))
+    console_log!("Branching analysis completed");
+    to_js(&details)
+}
+
+#[wasm_bindgen]
+pub fn chunk_content(path: &str, content: &str, options: Option<ChunkOptionsArg>) -> Result<ChunkArray, JsValue> {
+    let chunker = Chunker::new(parse_options::<ChunkOptions>(options.map(Into::into), "chunk options")?);
+    let chunks = chunker.chunk_content(Path::new(path), content);
+    
+    to_js(&chunks)
+}
+
+// Content comes from `files` (the same shape `scan_virtual` takes) when given,
+// otherwise from disk
+#[wasm_bindgen]
+pub fn chunk_scan_result(result_json: &str, options: Option<ChunkOptionsArg>, files: Option<VirtualFilesArg>) -> Result<ChunkArray, JsValue> {
+    let result = ScanResult::from_json(result_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse result: {}", e)))?;
+    let chunker = Chunker::new(parse_options::<ChunkOptions>(options.map(Into::into), "chunk options")?);
+    
+    let chunks = match files {
+        Some(files) => {
+            let (fs, _root) = virtual_file_system(files.into())?;
+            chunker.chunk_result(&result, &fs)
         }
-    }
+        None => chunker.chunk_result(&result, &OsFileSystem),
+    };
+    console_log!("Chunked {} files into {} chunks", result.stats.total_files, chunks.len());
+    
+    to_js(&chunks)
 }
 
 #[wasm_bindgen]
-pub fn analyze_branching_details(content: &str, language: Option<String>) -> Result<JsValue, JsValue> {
-    console_log!("Analyzing branching details for {} chars of content", content.len());
+pub fn get_scan_statistics(result_json: &str) -> Result<ScanStatsValue, JsValue> {
+    let result = ScanResult::from_json(result_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse result: {}", e)))?;
     
-    let analyzer = ContentAnalyzer::new();
-    let details = analyzer.analyze_branching_details(content, &language);
+    to_js(&result.stats)
+}
+
+#[wasm_bindgen]
+pub fn get_scan_errors(result_json: &str) -> Result<ScanIssueArray, JsValue> {
+    let result = ScanResult::from_json(result_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse result: {}", e)))?;
     
-    // Convert BranchingDetails to a serializable format
-    let details_map: HashMap<String, serde_json::Value> = [
-        ("conditional_count".to_string(), serde_json::Value::Number(details.conditional_count.into())),
-        ("loop_count".to_string(), serde_json::Value::Number(details.loop_count.into())),
-        ("switch_count".to_string(), serde_json::Value::Number(details.switch_count.into())),
-        ("max_nesting".to_string(), serde_json::Value::Number(details.max_nesting.into())),
-        ("logical_operators".to_string(), serde_json::Value::Number(details.logical_operators.into())),
-        ("cyclomatic_complexity".to_string(), serde_json::json!(details.cyclomatic_complexity)),
-        ("cognitive_complexity".to_string(), serde_json::json!(details.cognitive_complexity)),
-        ("hardcoded_dates_count".to_string(), serde_json::Value::Number(details.hardcoded_dates_count.into())),
-        ("hardcoded_values_count".to_string(), serde_json::Value::Number(details.hardcoded_values_count.into())),
-        ("pure_branches".to_string(), serde_json::Value::Number(details.pure_branches.into())),
-        ("non_pure_branches".to_string(), serde_json::Value::Number(details.non_pure_branches.into())),
-        ("future_logic_count".to_string(), serde_json::Value::Number(details.future_logic_count.into())),
-        ("past_logic_count".to_string(), serde_json::Value::Number(details.past_logic_count.into())),
-        ("total_branches".to_string(), serde_json::Value::Number(details.total_branches.into())),
-    ].into();
+    to_js(&result.errors)
+}
+
+#[wasm_bindgen]
+pub fn query_scan_result(result_json: &str, expression: &str) -> Result<FileEntryArray, JsValue> {
+    let result = ScanResult::from_json(result_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse result: {}", e)))?;
     
-    console_log!("Branching analysis completed");
-    to_value(&details_map).map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+    let matches = result.query(expression)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    
+    to_js(&matches)
 }
 
+// Same result with only the matching files kept; stats still describe the full scan
 #[wasm_bindgen]
-pub fn get_scan_statistics(result_json: &str) -> Result<JsValue, JsValue> {
-    let result: ScanResult = serde_json::from_str(result_json)
+pub fn filter_result(result_json: &str, expression: &str) -> Result<ScanResultValue, JsValue> {
+    let mut result = ScanResult::from_json(result_json)
         .map_err(|e| JsValue::from_str(&format!("Failed to parse result: {}", e)))?;
     
-    to_value(&result.stats).map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+    let expr = FilterExpr::parse(expression)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    result.retain_matching(&expr);
+    
+    to_js(&result)
 }
 
 #[wasm_bindgen]
-pub fn get_scan_errors(result_json: &str) -> Result<JsValue, JsValue> {
-    let result: ScanResult = serde_json::from_str(result_json)
+pub fn get_files_by_tag(result_json: &str, tag: &str) -> Result<FileEntryArray, JsValue> {
+    let result = ScanResult::from_json(result_json)
         .map_err(|e| JsValue::from_str(&format!("Failed to parse result: {}", e)))?;
     
-    to_value(&result.errors).map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+    to_js(&result.files_with_tag(tag))
+}
+
+#[wasm_bindgen]
+pub fn diff_scan_results(old_json: &str, new_json: &str) -> Result<ScanDiffValue, JsValue> {
+    let old = ScanResult::from_json(old_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse old result: {}", e)))?;
+    let new = ScanResult::from_json(new_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse new result: {}", e)))?;
+    
+    to_js(&ScanDiff::between(&old, &new))
+}
+
+#[wasm_bindgen]
+pub fn get_symbol_index(result_json: &str) -> Result<String, JsValue> {
+    let result = ScanResult::from_json(result_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse result: {}", e)))?;
+    
+    SymbolIndex::build(&result.files).to_json()
+        .map_err(|e| JsValue::from_str(&format!("Symbol index serialization error: {}", e)))
 }
 
 #[wasm_bindgen]
@@ -284,7 +688,7 @@ pub fn create_scan_options(
     max_depth: Option<usize>,
     include_hidden: Option<bool>,
     follow_symlinks: Option<bool>,
-) -> Result<String, JsValue> {
+) -> Result<WasmScanOptionsValue, JsValue> {
     let options = WasmScanOptions {
         mapper_profile,
         enhanced_analysis,
@@ -293,30 +697,45 @@ pub fn create_scan_options(
         include_hidden,
         follow_symlinks,
         ignore_patterns: None,
+        include_patterns: None,
+        collect_metadata: None,
+        complexity_caps: None,
+        normalize_complexity: None,
+        use_ignore_files: None,
+        sort_by_path: None,
+        include_broken_symlinks: None,
+        sniff_media: None,
+        min_size: None,
+        max_size: None,
+        modified_after: None,
+        modified_before: None,
+        hash_contents: None,
+        nested_repos: None,
+        secrets: None,
+        max_entries_per_dir: None,
+        depth_guard: None,
+        sampling: None,
+        languages: None,
+        importance_boosts: None,
     };
     
-    serde_json::to_string(&options)
-        .map_err(|e| JsValue::from_str(&format!("Options serialization error: {}", e)))
+    to_js(&options)
 }
 
 #[wasm_bindgen]
-pub fn validate_scan_options(options_json: &str) -> Result<bool, JsValue> {
-    match serde_json::from_str::<WasmScanOptions>(options_json) {
-        Ok(_) => Ok(true),
-        Err(e) => Err(JsValue::from_str(&format!("Invalid options: {}", e))),
-    }
+pub fn validate_scan_options(options: WasmScanOptionsArg) -> Result<bool, JsValue> {
+    parse_scan_options(Some(options.into())).map(|_| true)
 }
 
 #[wasm_bindgen]
-pub fn get_supported_output_formats() -> JsValue {
-    let formats = vec!["basic", "compact", "detailed", "hierarchical"];
-    to_value(&formats).unwrap()
+pub fn get_supported_output_formats() -> StringArray {
+    let formats = ["basic", "compact", "detailed", "hierarchical", "repo-map", "tree", "summary"];
+    to_js(&formats).expect("string arrays always serialize")
 }
 
 #[wasm_bindgen]
-pub fn get_supported_mapper_profiles() -> JsValue {
-    let profiles = vec!["generic", "enhanced"];
-    to_value(&profiles).unwrap()
+pub fn get_supported_mapper_profiles() -> StringArray {
+    to_js(&MapperRegistry::new().profiles()).expect("string arrays always serialize")
 }
 
 #[wasm_bindgen]
@@ -325,21 +744,94 @@ pub fn get_version() -> String {
 }
 
 #[wasm_bindgen]
-pub fn get_build_info() -> JsValue {
-    let info = serde_json::json!({
-        "version": env!("CARGO_PKG_VERSION"),
-        "name": env!("CARGO_PKG_NAME"),
-        "authors": env!("CARGO_PKG_AUTHORS"),
-        "description": env!("CARGO_PKG_DESCRIPTION"),
-        "build_date": std::env::var("VERGEN_BUILD_DATE").unwrap_or_else(|_| "unknown".to_string()),
-        "git_sha": std::env::var("VERGEN_GIT_SHA").unwrap_or_else(|_| "unknown".to_string()),
-    });
+pub fn get_build_info() -> BuildInfoValue {
+    let info = BuildInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        name: env!("CARGO_PKG_NAME"),
+        authors: env!("CARGO_PKG_AUTHORS"),
+        description: env!("CARGO_PKG_DESCRIPTION"),
+        build_date: std::env::var("VERGEN_BUILD_DATE").unwrap_or_else(|_| "unknown".to_string()),
+        git_sha: std::env::var("VERGEN_GIT_SHA").unwrap_or_else(|_| "unknown".to_string()),
+    };
     
-    to_value(&info).unwrap_or_else(|_| JsValue::from_str("Build info unavailable"))
+    to_js(&info).unwrap_or_else(|_| JsValue::from_str("Build info unavailable").unchecked_into())
 }
 
 #[wasm_bindgen(start)]
 pub fn main() {
     console_log!("Thinkeloquent Directory Scanner WASM module loaded v{}", get_version());
     console_log!("Available functions: scan_directory, scan_directory_simple, format_scan_result, analyze_file_content, analyze_branching_details");
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan_options(json: &str) -> Result<ScanOptions, String> {
+        parse_json_options::<WasmScanOptions>(json, "scan options")?.try_into()
+    }
+
+    #[test]
+    fn test_json_string_options_are_still_accepted() {
+        let options = scan_options(r#"{"max_depth": 2, "output_format": "tree", "modified_after": "2024-01-01T00:00:00Z"}"#).unwrap();
+
+        assert_eq!(options.max_depth, Some(2));
+        assert!(matches!(options.output_format, OutputFormat::Tree));
+        assert_eq!(options.modified_after, parse_rfc3339("2024-01-01T00:00:00Z"));
+        assert_eq!(scan_options("{}").unwrap().max_depth, ScanOptions::default().max_depth);
+    }
+
+    #[test]
+    fn test_unknown_option_fields_are_rejected() {
+        let error = scan_options(r#"{"max_dept": 2}"#).unwrap_err();
+
+        assert!(error.starts_with("Invalid scan options: unknown field `max_dept`"), "{}", error);
+    }
+
+    #[test]
+    fn test_invalid_option_values_are_errors() {
+        let error = scan_options(r#"{"output_format": "xml"}"#).unwrap_err();
+        assert!(error.contains("unknown output_format 'xml'"), "{}", error);
+
+        let error = scan_options(r#"{"modified_before": "yesterday"}"#).unwrap_err();
+        assert!(error.contains("invalid modified_before 'yesterday', expected RFC 3339"), "{}", error);
+
+        assert!(scan_options(r#"{"max_depth": "deep"}"#).is_err());
+    }
+
+    fn virtual_files(paths: &[&str]) -> Vec<VirtualFile> {
+        paths.iter()
+            .map(|path| VirtualFile { path: path.to_string(), content: format!("// {}\n", path), modified: None })
+            .collect()
+    }
+
+    fn scanned_paths(fs: &MemoryFileSystem, root: &Path) -> Vec<String> {
+        let result = DirectoryScanner::new(ScanOptions::default()).scan_with(fs, root).unwrap();
+        result.files.iter().filter(|file| !file.is_dir).map(|file| file.path.to_string_lossy().into_owned()).collect()
+    }
+
+    #[test]
+    fn test_virtual_tree_roots_at_a_shared_folder() {
+        let (fs, root) = virtual_tree(virtual_files(&["/app/src/lib.rs", "app/README.md"])).unwrap();
+        assert_eq!(root, Path::new("app"));
+        assert_eq!(scanned_paths(&fs, &root), ["app/README.md", "app/src/lib.rs"]);
+
+        let (fs, root) = virtual_tree(virtual_files(&["/lib.rs", "src/main.rs"])).unwrap();
+        assert_eq!(root, Path::new("."));
+        assert_eq!(scanned_paths(&fs, &root), ["./lib.rs", "./src/main.rs"]);
+    }
+
+    #[test]
+    fn test_virtual_paths_cannot_leave_the_tree() {
+        let error = virtual_tree(virtual_files(&["app/lib.rs", "app/../../etc/passwd"])).unwrap_err();
+        assert_eq!(error, "Invalid virtual path: app/../../etc/passwd");
+        assert!(virtual_tree(virtual_files(&[".."])).is_err());
+        assert!(virtual_tree(virtual_files(&["./app/lib.rs"])).is_ok());
+    }
+
+    #[test]
+    fn test_duplicate_virtual_paths_are_rejected() {
+        let error = virtual_tree(virtual_files(&["app/lib.rs", "/app/lib.rs"])).unwrap_err();
+        assert_eq!(error, "Duplicate virtual path: /app/lib.rs");
+    }
+}
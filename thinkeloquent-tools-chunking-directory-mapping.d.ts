@@ -1,51 +1,186 @@
 /**
  * TypeScript definitions for thinkeloquent-tools-chunking-directory-mapping WASM module
  * Provides 100% feature coverage of the core Rust library
+ *
+ * The interfaces below are also emitted into the wasm-pack generated .d.ts from
+ * crates/wasm/src/types.rs; keep the two in sync.
  */
 
+export interface ComplexityCaps {
+  complexity: number | null; // null disables the cap
+  branching: number | null;
+}
+
 export interface WasmScanOptions {
-  mapper_profile?: 'generic' | 'enhanced';
-  enhanced_analysis?: boolean;
-  output_format?: 'basic' | 'compact' | 'detailed' | 'hierarchical';
-  max_depth?: number;
-  include_hidden?: boolean;
-  follow_symlinks?: boolean;
-  ignore_patterns?: string[];
+  mapper_profile?: string | null;
+  enhanced_analysis?: boolean | null;
+  output_format?: 'basic' | 'compact' | 'detailed' | 'hierarchical' | 'repo-map' | 'tree' | 'summary' | null;
+  max_depth?: number | null;
+  include_hidden?: boolean | null;
+  follow_symlinks?: boolean | null;
+  ignore_patterns?: string[] | null;
+  include_patterns?: string[] | null; // Globs; without '/' they match the file name
+  collect_metadata?: boolean | null;
+  complexity_caps?: ComplexityCaps | null;
+  normalize_complexity?: boolean | null;
+  use_ignore_files?: boolean | null; // Honor .projscanignore files (default true)
+  sort_by_path?: boolean | null; // Sort files and issues by path (default true); false keeps walk order
+  include_broken_symlinks?: boolean | null; // Report links whose target is missing (default false)
+  sniff_media?: boolean | null; // Detect MIME types and media details of non-text files (default true)
+  min_size?: number | null; // Bytes; smaller files are left out
+  max_size?: number | null; // Bytes; larger files are left out
+  modified_after?: string | null; // RFC 3339; files modified earlier are left out
+  modified_before?: string | null; // RFC 3339; files modified later are left out
+  hash_contents?: boolean | null; // Record a SHA-256 of each file in FileEntry.content_hash (default false)
+  nested_repos?: NestedRepoMode | null; // Files of submodules and nested repositories (default 'scan')
+  secrets?: SecretsMode | null; // Detect and redact secrets in file content (default 'off')
+  max_entries_per_dir?: number | null; // Entries scanned per directory, the rest skipped with an 'entry-limit' warning (default 100000; 0 lifts the cap)
+  depth_guard?: number | null; // Nesting not descended past, with a 'depth-limit' warning (default 128; 0 lifts the guard)
+  sampling?: Sampling | null; // Analyze only a sample of files in huge trees, extrapolating totals into ScanStats.sampling
+  languages?: LanguageMap | null; // Extension mappings and per-glob overrides ahead of the built-in language detection
+  importance_boosts?: Record<string, ImportanceBoost[]> | null; // Per mapper profile, replacing its built-in route, store and wiring boosts
 }
 
-export interface FileEntry {
-  path: string;
+// Added to the importance of files matching one of `patterns` (if any) and containing one of `markers` (if any);
+// the largest matching boost applies
+export interface ImportanceBoost {
+  role: string; // e.g. 'route', 'store' or 'wiring'
+  patterns?: string[]; // Globs on the file name, or on the end of its path when they have a '/'
+  markers?: string[];
+  boost: number;
+}
+
+// Overrides are tried first, in order; globs without a '/' match the file name, those with one the end of its path
+export interface LanguageMap {
+  extensions?: Record<string, string>; // e.g. { "mjs": "javascript", "tf": "terraform" }
+  overrides?: { pattern: string; language: string }[];
+}
+
+// Past `above_files` files, each directory and extension group keeps its first `min_per_group` files and a `rate`
+// share of the rest, picked by a hash of the path and `seed`
+export interface Sampling {
+  above_files?: number; // Default 10000
+  rate?: number; // 0 to 1 (default 0.1)
+  min_per_group?: number; // Default 3
+  seed?: number; // Default 0
+}
+
+export type NestedRepoMode = 'scan' | 'summarize' | 'skip';
+
+// 'redact' tags files with secrets as 'contains-secret', reports them as 'hardcoded-secret' findings and
+// replaces the matched text with "[REDACTED]"; 'strict' also leaves out the chunks that contained them
+export type SecretsMode = 'off' | 'redact' | 'strict';
+
+export type OutputFormat = 'Basic' | 'Compact' | 'Detailed' | 'Hierarchical' | 'RepoMap' | 'Tree' | 'Summary';
+
+export interface ScanOptions {
+  max_depth: number | null;
+  ignore_patterns: string[];
+  include_patterns?: string[];
+  follow_symlinks: boolean;
+  include_hidden: boolean;
+  mapper_profile: string;
+  collect_metadata: boolean;
+  enhanced_analysis: boolean;
+  output_format: OutputFormat;
+  complexity_caps?: ComplexityCaps;
+  normalize_complexity?: boolean;
+  use_ignore_files?: boolean;
+  sort_by_path?: boolean;
+  include_broken_symlinks?: boolean;
+  sniff_media?: boolean;
+  min_size?: number | null;
+  max_size?: number | null;
+  modified_after?: string | null;
+  modified_before?: string | null;
+  hash_contents?: boolean;
+  nested_repos?: NestedRepoMode;
+  secrets?: SecretsMode;
+  max_entries_per_dir?: number | null;
+  depth_guard?: number | null;
+  sampling?: Sampling | null;
+  collect_resource_usage?: boolean;
+  languages?: LanguageMap;
+  importance_boosts?: Record<string, ImportanceBoost[]>;
+}
+
+export interface Symbol {
   name: string;
-  size: number;
-  modified: number; // Unix timestamp
-  is_dir: boolean;
-  tags: string[];
-  metadata?: Record<string, any>;
-  enhanced_info?: EnhancedFileInfo;
+  kind: string;
+  line: number;
+  signature: string;
+}
+
+export interface SymbolLocation {
+  path: string;
+  line: number;
+  kind: string;
+  signature: string;
+}
+
+export interface SymbolIndex {
+  symbols: Record<string, SymbolLocation[]>;
 }
 
 export interface EnhancedFileInfo {
-  language?: string;
-  framework?: string;
-  line_count?: number;
-  complexity_score?: number;
-  importance_score?: number;
-  content_summary?: string;
+  language: string | null;
+  framework: string | null;
+  line_count: number | null;
+  complexity_score: number | null;
+  complexity_percentile?: number; // 0-100, set when normalize_complexity is enabled
+  importance_score: number | null;
+  content_summary: string | null;
   exports: string[];
   imports: string[];
   dependencies: string[];
   related_files: string[];
-  purpose?: string;
-  last_author?: string;
-  change_frequency?: string;
+  purpose: string | null;
+  last_author: string | null;
+  change_frequency: string | null; // "stable", "active" or "new", from git history
   api_surface: string[];
+  symbols?: Symbol[];
+  config_keys?: string[]; // Top-level keys of a JSON, YAML or TOML file, sorted
+  config_depth?: number; // Levels of nested mappings and lists in a JSON, YAML or TOML file
+  fan_in?: number; // Files importing this one (afferent coupling)
+  fan_out?: number; // Files this one imports (efferent coupling)
+  stability?: FileStability;
+}
+
+export interface FileStability {
+  class: 'stable' | 'active' | 'new';
+  first_commit: string; // RFC 3339
+  last_commit: string;
+  commits: number;
+  recent_commits: number; // Commits within the last 90 days
 }
 
-export interface DirectoryNode {
+export interface FileEntry {
   path: string;
   name: string;
-  children: DirectoryNode[];
-  files: FileEntry[];
+  size: number;
+  modified: string; // RFC 3339 in UTC, e.g. "2024-05-01T12:30:00.250Z"
+  modified_ms: number; // Milliseconds since the Unix epoch
+  is_dir: boolean;
+  tags: string[];
+  metadata: Record<string, unknown> | null;
+  enhanced_info: EnhancedFileInfo | null;
+  symlink?: SymlinkInfo; // Present for symbolic links
+  media?: MediaInfo; // Present for non-text content
+  content_hash?: string; // Hex SHA-256, when scanned with hash_contents
+}
+
+export interface MediaInfo {
+  mime: string; // e.g. "image/png", "application/octet-stream"
+  kind: 'image' | 'audio' | 'video' | 'font' | 'archive' | 'document' | 'executable' | 'binary';
+  width?: number; // Pixels, for images and video
+  height?: number;
+  duration_ms?: number; // For audio and video whose header records it
+}
+
+export interface SymlinkInfo {
+  target: string; // The link text; relative targets are relative to the link's directory
+  resolves: boolean;
+  outside_root: boolean;
 }
 
 export interface ScanStats {
@@ -54,20 +189,190 @@ export interface ScanStats {
   total_size: number;
   scan_duration_ms: number;
   files_per_second: number;
+  directories?: Record<string, DirStats>;
+  distribution?: Distribution;
+  cache?: CacheStats; // Analysis cache use, for scans that reuse earlier analysis
+  sampling?: SamplingSummary; // For scans with sampling options
+  resources?: ResourceUsage; // For scans that collect resource usage
+}
+
+// Process usage over a scan; figures the platform cannot report are left out
+export interface ResourceUsage {
+  peak_rss_bytes?: number; // High-water mark of the whole process
+  read_bytes?: number; // Read during the scan, cached or not
+  cpu_ms?: number; // User and system time of all threads during the scan
+}
+
+// An extrapolated value with its 95% confidence interval
+export interface Estimate {
+  value: number;
+  low: number;
+  high: number;
+}
+
+export interface SamplingSummary {
+  files: number; // Files that reached content analysis
+  analyzed: number; // Of those, the ones analyzed
+  groups: number; // Directory and extension groups the estimates are made in
+  total_lines: Estimate;
+  total_complexity: Estimate;
+  mean_complexity: Estimate; // Per file, analyzed or not
+}
+
+export interface CacheStats {
+  hits: number;
+  misses: number;
+  bytes_reused: number;
+  bytes_reanalyzed: number;
+  analysis_ms: number;
+  time_saved_ms: number;
+}
+
+export interface HistogramBucket {
+  label: string;
+  count: number;
+}
+
+export interface Distribution {
+  sizes?: HistogramBucket[];
+  extensions?: Record<string, number>;
+  depths?: Record<string, number>;
+  complexity?: HistogramBucket[];
+}
+
+export interface DirStats {
+  files: number;
+  size: number;
+  lines: number;
+  average_complexity?: number;
+  errors: number;
+  fan_in: number; // Files elsewhere importing one of its files
+  fan_out: number; // Files elsewhere its files import
+  partial: boolean; // A directory under it could not be read
+}
+
+export interface TestMapping {
+  test_file: string;
+  source_files: string[];
+}
+
+export interface TestCoverageStructure {
+  test_files: number;
+  source_files: number;
+  covered_source_files: number;
+  mappings: TestMapping[];
+  untested_sources: string[];
+}
+
+export type IssueKind =
+  | 'permission-denied'
+  | 'unreadable'
+  | 'non-utf8'
+  | 'analysis-failed'
+  | 'symlink-loop'
+  | 'entry-limit'
+  | 'depth-limit'
+  | 'other';
+
+export interface ScanIssue {
+  path: string | null;
+  kind: IssueKind;
+  message: string;
+  severity: 'warning' | 'error';
 }
 
 export interface ScanResult {
+  schema_version: number; // Fields are only added within a version
+  root_path: string;
   files: FileEntry[];
-  directories: DirectoryNode[];
   stats: ScanStats;
-  errors: string[];
+  roots?: RootStats[]; // Per-root stats of a multi-root scan
+  errors: ScanIssue[];
+  findings?: SecurityFinding[]; // Unix permission problems; omitted when there are none
+  entry_points?: EntryPoint[]; // Where programs start; omitted when there are none
+  endpoints?: Endpoint[]; // Declared HTTP routes, with enhanced analysis; omitted when there are none
+  schema?: DatabaseSchema; // Tables from SQL files and migrations; omitted when there are neither
+  containers?: ContainerArtifact[]; // Dockerfiles, compose files and dev containers; omitted when there are none
+  nested_repositories?: NestedRepository[]; // Directories with their own .git; omitted when there are none
+  'test-coverage-structure'?: TestCoverageStructure;
+}
+
+export interface RootStats {
+  path: string;
+  stats: ScanStats;
+}
+
+export interface SecurityFinding {
+  path: string;
+  kind: 'world-writable' | 'setuid' | 'setgid' | 'loose-private-key' | 'vulnerable-dependency' | 'hardcoded-secret';
+  severity: 'warning' | 'error';
+  message: string;
+  mode: number; // Permission bits, e.g. 0o4755; 0 for dependency and secret findings
+  advisory?: string; // Advisory ID of a vulnerable dependency, e.g. "RUSTSEC-2021-0003"
+}
+
+export interface EntryPoint {
+  path: string;
+  kind: 'cargo-bin' | 'package-main' | 'package-bin' | 'django-manage' | 'docker' | 'main';
+  name?: string; // Binary or command name, or the Docker command line
+  declared_in?: string; // Manifest or Dockerfile declaring it
+}
+
+export interface Endpoint {
+  method: string; // Uppercase, or 'ANY'
+  route: string; // As declared, e.g. '/users/{id}' or '/users/:id'
+  path: string;
+  line: number; // 1-based
+  framework: 'axum' | 'actix-web' | 'express' | 'flask' | 'fastapi' | 'spring';
+  handler?: string;
+}
+
+export interface DatabaseSchema {
+  tables: SchemaTable[]; // Tables left once every migration has run, by name
+  migrations?: Migration[]; // In the order they apply
+}
+
+export interface SchemaTable {
+  name: string;
+  columns?: string[]; // As created; omitted for tables created through an ORM
+  path: string; // File creating the table
+  line: number;
+}
+
+export interface ContainerArtifact {
+  path: string;
+  kind: 'dockerfile' | 'compose' | 'devcontainer' | 'dockerignore';
+  images?: string[]; // Base and service images, e.g. 'rust:1.79-slim'
+  ports?: string[]; // As declared: '8080', '53/udp' or '80:8080'
+  stages?: string[]; // Named build stages of a multi-stage Dockerfile
+  services?: string[]; // Services of a compose file
+}
+
+export interface NestedRepository {
+  path: string; // Root directory, tagged 'git-submodule' or 'nested-repo'
+  kind: 'submodule' | 'repository';
+  summary?: {
+    // Entries left out with nested_repos 'summarize'
+    files: number;
+    dirs: number;
+    total_size: number;
+    languages?: Record<string, number>;
+  };
+}
+
+export interface Migration {
+  path: string;
+  version?: string; // e.g. '20240101120000', or '1.2' for Flyway's V1_2__
+  name: string;
+  creates?: string[];
+  drops?: string[];
 }
 
 export interface WasmScanResult {
   result: ScanResult;
-  formatted_output?: string;
-  json_output?: string;
-  yaml_output?: string;
+  formatted_output: string | null;
+  json_output: string | null;
+  yaml_output: string | null;
 }
 
 export interface BranchingDetails {
@@ -87,6 +392,95 @@ export interface BranchingDetails {
   total_branches: number;
 }
 
+export interface Chunk {
+  id: string; // "path#Lstart-Lend"
+  path: string;
+  start_line: number; // 1-based, inclusive
+  end_line: number;
+  token_count: number; // Estimated at ~4 characters per token
+  language: string | null;
+  content: string;
+}
+
+export interface ChunkOptions {
+  max_tokens?: number; // Default 512
+  overlap_lines?: number; // Default 2
+  max_file_size?: number; // Bytes, default 1 MiB
+  filter?: ChunkFilter;
+  secrets?: SecretsMode; // Default 'off'
+}
+
+// Chunks left out as embedding noise; nothing is dropped by default
+export interface ChunkFilter {
+  drop_boilerplate?: boolean; // Mostly blank, license-header-only or generated-file chunks
+  min_density?: number; // Distinct words per token, 0-1
+  max_similarity?: number; // Drop chunks more similar than this (0-1) to one already kept
+}
+
+export interface ScanProgress {
+  files_scanned: number;
+  dirs_scanned: number;
+  bytes_scanned: number;
+  issues: number;
+  current_path: string | null;
+}
+
+export interface VirtualFile {
+  path: string;
+  content: string;
+  modified?: number | null; // Milliseconds since the Unix epoch, as File.lastModified
+}
+
+export interface FsStat {
+  is_dir: boolean;
+  size?: number;
+  mtime_ms?: number | null; // Milliseconds since the Unix epoch, as fs.Stats.mtimeMs
+  dev?: number | null; // With ino, lets the walk stop at symlinks back to an ancestor directory
+  ino?: number | null;
+  mode?: number | null; // Permission bits, as fs.Stats.mode, for the permission audit
+}
+
+// Host file system access, e.g. backed by Node's `fs` module. Errors may be
+// thrown; a Node `code` of ENOENT, EACCES or EPERM is kept in the scan issues.
+export interface FsCallbacks {
+  stat(path: string): FsStat | null; // null when the path does not exist
+  readdir(path: string): string[]; // Entry names, not full paths
+  read_file(path: string): Uint8Array | string;
+}
+
+export interface FileChange {
+  path: string; // Relative to the scan root
+  size_before: number;
+  size_after: number;
+  lines_before: number | null;
+  lines_after: number | null;
+  complexity_before: number | null;
+  complexity_after: number | null;
+  tags_added: string[];
+  tags_removed: string[];
+}
+
+export interface ScanDiff {
+  added: string[];
+  removed: string[];
+  moved?: FileMove[];
+  modified: FileChange[];
+  unchanged: number;
+  api_changes?: ApiChange[]; // Public items added, removed or changed; enhanced scans only
+}
+
+export interface FileMove {
+  from: string;
+  to: string;
+}
+
+export interface ApiChange {
+  path: string;
+  added?: string[];
+  removed?: string[];
+  changed?: { name: string; before: string; after: string }[]; // Declarations before and after
+}
+
 export interface BuildInfo {
   version: string;
   name: string;
@@ -96,25 +490,84 @@ export interface BuildInfo {
   git_sha: string;
 }
 
+e BuildInfo {
+  version: string;
+  name: string;
+  authors: string;
+  description: string;
+  build_date: string;
+  git_sha: string;
+}
+
 /**
  * Initialize the WASM module
  */
 export default function init(input?: RequestInfo | URL | Response | BufferSource | WebAssembly.Module): Promise<void>;
 
 /**
- * Enhanced directory scanning with multiple output formats included in the result
+ * Enhanced directory scanning with multiple output formats included in the result.
+ * Pass `fs` callbacks (e.g. from Node's `fs` module) to read the real file system.
+ */
+export function scan_directory(path: string, options?: WasmScanOptions | string, fs?: FsCallbacks): Promise<WasmScanResult>;
+
+/**
+ * Directory scan that yields to the event loop periodically and reports progress
+ */
+export function scan_directory_async(
+  path: string,
+  options?: WasmScanOptions | string,
+  on_progress?: (progress: ScanProgress) => void,
+  fs?: FsCallbacks
+): Promise<WasmScanResult>;
+
+/**
+ * Split file content into line-aligned chunks under a token budget
+ */
+export function chunk_content(path: string, content: string, options?: ChunkOptions | string): Promise<Chunk[]>;
+
+/**
+ * Chunk every text file in a scan result, reading from `files` when given, otherwise from disk
+ */
+export function chunk_scan_result(
+  result_json: string,
+  options?: ChunkOptions | string,
+  files?: VirtualFile[] | Record<string, string>
+): Promise<Chunk[]>;
+
+/**
+ * Scan files provided from JS (dropped folder, File System Access API) entirely in memory
+ */
+export function scan_virtual(files: VirtualFile[] | Record<string, string>, options?: WasmScanOptions | string): Promise<WasmScanResult>;
+
+/**
+ * Directory scan returning the ScanResult as MessagePack bytes (no formatted outputs)
+ */
+export function scan_directory_binary(path: string, options?: WasmScanOptions | string, fs?: FsCallbacks): Uint8Array;
+
+/**
+ * Virtual scan returning the ScanResult as MessagePack bytes
+ */
+export function scan_virtual_binary(files: VirtualFile[] | Record<string, string>, options?: WasmScanOptions | string): Uint8Array;
+
+/**
+ * Encode a JSON scan result as MessagePack bytes
  */
-export function scan_directory(path: string, options?: string): Promise<WasmScanResult>;
+export function encode_scan_result(result_json: string): Uint8Array;
+
+/**
+ * Decode MessagePack bytes produced by the *_binary functions into a ScanResult
+ */
+export function decode_scan_result(bytes: Uint8Array): ScanResult;
 
 /**
  * Basic directory scanning returning just the core scan result
  */
-export function scan_directory_simple(path: string, options?: string): Promise<ScanResult>;
+export function scan_directory_simple(path: string, options?: ScanOptions | string): Promise<ScanResult>;
 
 /**
  * Format scan results using different output formats
  */
-export function format_scan_result(result_json: string, format_type: 'basic' | 'compact' | 'detailed' | 'hierarchical'): Promise<string>;
+export function format_scan_result(result_json: string, format_type: 'basic' | 'compact' | 'detailed' | 'hierarchical' | 'repo-map' | 'tree' | 'summary'): Promise<string>;
 
 /**
  * Convert scan result to YAML format
@@ -139,10 +592,35 @@ export function get_scan_statistics(result_json: string): Promise<ScanStats>;
 /**
  * Extract error information from scan results
  */
-export function get_scan_errors(result_json: string): Promise<string[]>;
+export function get_scan_errors(result_json: string): Promise<ScanIssue[]>;
+
+/**
+ * Return the entries matching a filter expression, e.g. "tag:source AND complexity>5 AND path:src/**"
+ */
+export function query_scan_result(result_json: string, expression: string): Promise<FileEntry[]>;
+
+/**
+ * Scan result keeping only files that match a filter expression (stats describe the full scan)
+ */
+export function filter_result(result_json: string, expression: string): Promise<ScanResult>;
+
+/**
+ * Files carrying the given classification tag
+ */
+export function get_files_by_tag(result_json: string, tag: string): Promise<FileEntry[]>;
+
+/**
+ * Structured differences between two scan results of the same project
+ */
+export function diff_scan_results(old_json: string, new_json: string): Promise<ScanDiff>;
+
+/**
+ * Build the repo-level symbol index (symbol -> file -> line) as a JSON string
+ */
+export function get_symbol_index(result_json: string): Promise<string>;
 
 /**
- * Create properly formatted scan options JSON
+ * Create a scan options object
  */
 export function create_scan_options(
   mapper_profile?: string,
@@ -151,12 +629,12 @@ export function create_scan_options(
   max_depth?: number,
   include_hidden?: boolean,
   follow_symlinks?: boolean
-): Promise<string>;
+): Promise<WasmScanOptions>;
 
 /**
- * Validate scan options format
+ * Validate scan options; rejects with a message naming the invalid field
  */
-export function validate_scan_options(options_json: string): Promise<boolean>;
+export function validate_scan_options(options: WasmScanOptions | string): Promise<boolean>;
 
 /**
  * Get list of supported output formats
@@ -198,7 +676,7 @@ export class ScanOptionsBuilder {
     return this;
   }
 
-  outputFormat(format: 'basic' | 'compact' | 'detailed' | 'hierarchical'): ScanOptionsBuilder {
+  outputFormat(format: 'basic' | 'compact' | 'detailed' | 'hierarchical' | 'repo-map' | 'tree' | 'summary'): ScanOptionsBuilder {
     this.options.output_format = format;
     return this;
   }
@@ -223,8 +701,8 @@ export class ScanOptionsBuilder {
     return this;
   }
 
-  build(): string {
-    return JSON.stringify(this.options);
+  build(): WasmScanOptions {
+    return { ...this.options };
   }
 }
 
@@ -268,7 +746,7 @@ export class ScanResultProcessor {
     );
   }
 
-  async formatAs(format: 'basic' | 'compact' | 'detailed' | 'hierarchical'): Promise<string> {
+  async formatAs(format: 'basic' | 'compact' | 'detailed' | 'hierarchical' | 'repo-map' | 'tree' | 'summary'): Promise<string> {
     return format_scan_result(JSON.stringify(this.result), format);
   }
 